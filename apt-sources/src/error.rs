@@ -19,6 +19,8 @@ pub enum RepositoryError {
     Lossy(deb822_lossless::lossy::Error),
     /// Errors in lossless parser
     Lossless(deb822_lossless::lossless::Error),
+    /// A paragraph could not be converted to a `Repository`
+    Paragraph(deb822_lossless::FromParagraphError),
     /// I/O Error
     Io(std::io::Error)
 }
@@ -29,6 +31,18 @@ impl From<std::io::Error> for RepositoryError {
     }
 }
 
+impl From<deb822_lossless::ParseError> for RepositoryError {
+    fn from(e: deb822_lossless::ParseError) -> Self {
+        Self::Lossless(deb822_lossless::lossless::Error::ParseError(e))
+    }
+}
+
+impl From<deb822_lossless::FromParagraphError> for RepositoryError {
+    fn from(e: deb822_lossless::FromParagraphError) -> Self {
+        Self::Paragraph(e)
+    }
+}
+
 impl std::fmt::Display for RepositoryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -39,6 +53,7 @@ impl std::fmt::Display for RepositoryError {
             Self::InvalidSignature => write!(f, "The field `Signed-By` is incorrect"),
             Self::Lossy(e) => write!(f, "Lossy parser error: {}", e),
             Self::Lossless(e) => write!(f, "Lossless parser error: {}", e),
+            Self::Paragraph(e) => write!(f, "Invalid repository: {}", e),
             Self::Io(e) => write!(f, "IO error: {}", e),
         }
     }
@@ -304,14 +304,12 @@ impl Repositories {
 }
 
 impl std::str::FromStr for Repositories {
-    type Err = String;
+    type Err = RepositoryError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let deb822: deb822_lossless::Deb822 = s
-            .parse()
-            .map_err(|e: deb822_lossless::ParseError| e.to_string())?;
+        let deb822: deb822_lossless::Deb822 = s.parse()?;
 
-        let repos = deb822.paragraphs().map(|p| Repository::from_paragraph(&p)).collect::<Result<Vec<Repository>, Self::Err>>()?;
+        let repos = deb822.paragraphs().map(|p| Repository::from_paragraph(&p)).collect::<Result<Vec<Repository>, _>>()?;
         Ok(Repositories(repos))
     }
 }
@@ -350,7 +348,7 @@ mod tests {
         let ret = s.parse::<Repositories>();
         assert!(ret.is_err());
         //assert_eq!(ret.unwrap_err(), "Not machine readable".to_string());
-        assert_eq!(ret.unwrap_err(), "expected ':', got Some(NEWLINE)\n".to_owned());
+        assert_eq!(ret.unwrap_err().to_string(), "Lossless parser error: expected ':', got Some(NEWLINE)\n");
     }
 
     #[test]
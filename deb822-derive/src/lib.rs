@@ -6,12 +6,30 @@ use syn::{parse_macro_input, DeriveInput};
 use syn::{Type, TypePath};
 
 fn is_option(ty: &syn::Type) -> bool {
+    generic_argument(ty, "Option").is_some()
+}
+
+/// If `ty` is `wrapper<T>` (e.g. `Vec<T>`), return `T`.
+fn generic_argument<'a>(ty: &'a syn::Type, wrapper: &str) -> Option<&'a syn::Type> {
     if let Type::Path(TypePath { path, .. }) = ty {
         if let Some(segment) = path.segments.last() {
-            return segment.ident == "Option";
+            if segment.ident != wrapper {
+                return None;
+            }
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(ty)) = args.args.first() {
+                    return Some(ty);
+                }
+            }
         }
     }
-    false
+    None
+}
+
+/// Return the item type if `ty` is a `Vec<T>` or `Option<Vec<T>>`.
+fn list_item_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let ty = generic_argument(ty, "Option").unwrap_or(ty);
+    generic_argument(ty, "Vec")
 }
 
 // Generate `from_paragraph`, ``to_paragraph`` methods for the annotated struct, i.e.:
@@ -28,18 +46,23 @@ fn is_option(ty: &syn::Type) -> bool {
 // }
 // ```
 //
-// will generate:
+// Fields of type `Vec<T>` hold a list of values, separated by whitespace unless a different
+// separator is set with `#[deb822(separator = ",")]`. Field names default to the name of the
+// struct field, unless a different convention is set with `#[deb822(rename_all = "...")]` on
+// the struct.
+//
+// The above will generate:
 //
 // ```rust
 //
 // impl<P: deb822_lossless::convert::Deb822LikeParagraph> FromDeb822Paragraph<P> for X {
-//     fn from_paragraph(para: &P) -> Result<Self, String> {
+//     fn from_paragraph(para: &P) -> Result<Self, FromParagraphError> {
 //     Ok(Self {
-//         a: para.get("a").ok_or_else(|| "missing field: a")?.parse().map_err(|e| format!("parsing field a: {}", e))?,
-//         b: para.get("b").ok_or_else(|| "missing field: b")?.parse().map_err(|e| format!("parsing field b: {}", e))?,
-//         c: para.get("c").map(|v| v.parse().map_err(|e| format!("parsing field c: {}", e))).transpose()?,
-//         d: para.get("d").ok_or_else(|| "missing field: d")?.split_whitespace().map(|s| s.to_string()).collect(),
-//         e: para.get("E").ok_or_else(|| "missing field: e")?.parse().map_err(|e| format!("parsing field E: {}", e))?,
+//         a: para.get("a").ok_or_else(|| FromParagraphError::MissingField("a".to_string()))?.parse().map_err(|e| FieldError::new("a", &v, e))?,
+//         b: para.get("b").ok_or_else(|| FromParagraphError::MissingField("b".to_string()))?.parse().map_err(|e| FieldError::new("b", &v, e))?,
+//         c: para.get("c").map(|v| v.parse().map_err(|e| FieldError::new("c", &v, e))).transpose()?,
+//         d: deb822_lossless::convert::split_list(&para.get("d").ok_or_else(|| FromParagraphError::MissingField("d".to_string()))?, None).map(|item| item.parse().map_err(|e| FieldError::new("d", item, e))).collect::<Result<Vec<_>, _>>()?,
+//         e: para.get("E").ok_or_else(|| FromParagraphError::MissingField("E".to_string()))?.parse().map_err(|e| FieldError::new("E", &v, e))?,
 //     })
 // }
 //
@@ -68,7 +91,7 @@ fn is_option(ty: &syn::Type) -> bool {
 //         if let Some(v) = &self.c {
 //             fields.set("c", v.to_string());
 //         }
-//         fields.set("d", self.d.join(" "));
+//         fields.set("d", deb822_lossless::convert::join_list(self.d.iter().map(ToString::to_string), None));
 //         fields.set("E", self.e.to_string());
 //         deb822_lossless::Paragraph::from(fields)
 //     }
@@ -81,7 +104,7 @@ fn is_option(ty: &syn::Type) -> bool {
 //         } else {
 //             para.remove("c");
 //         }
-//         para.set("d", &self.d.join(" "));
+//         para.set("d", &deb822_lossless::convert::join_list(self.d.iter().map(ToString::to_string), None));
 //         para.set("E", &self.e.to_string());
 //     }
 // }
@@ -91,173 +114,305 @@ struct FieldAttributes {
     field: Option<String>,
     serialize_with: Option<syn::ExprPath>,
     deserialize_with: Option<syn::ExprPath>,
+    separator: Option<String>,
 }
 
-fn extract_field_attributes(attrs: &[syn::Attribute]) -> Result<FieldAttributes, syn::Error> {
-    let mut field = None;
-    let mut serialize_with = None;
-    let mut deserialize_with = None;
+fn string_value(nv: &syn::MetaNameValue) -> Result<String, syn::Error> {
+    if let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(s),
+        ..
+    }) = &nv.value
+    {
+        Ok(s.value())
+    } else {
+        Err(syn::Error::new(
+            nv.value.span(),
+            "expected string literal in deb822 attribute",
+        ))
+    }
+}
+
+fn path_value(nv: &syn::MetaNameValue) -> Result<syn::ExprPath, syn::Error> {
+    if let syn::Expr::Path(s) = &nv.value {
+        Ok(s.clone())
+    } else {
+        Err(syn::Error::new(
+            nv.value.span(),
+            "expected path in deb822 attribute",
+        ))
+    }
+}
+
+fn deb822_attributes(attrs: &[syn::Attribute]) -> Result<Vec<syn::MetaNameValue>, syn::Error> {
+    let mut ret = vec![];
     for attr in attrs {
         if !attr.path().is_ident("deb822") {
             continue;
         }
         let name_values: syn::punctuated::Punctuated<syn::MetaNameValue, syn::Token![,]> =
             attr.parse_args_with(syn::punctuated::Punctuated::parse_terminated)?;
-        for nv in name_values {
-            if nv.path.is_ident("field") {
-                if let syn::Expr::Lit(syn::ExprLit {
-                    lit: syn::Lit::Str(s),
-                    ..
-                }) = nv.value
-                {
-                    field = Some(s.value());
-                } else {
-                    return Err(syn::Error::new(
-                        nv.value.span(),
-                        "expected string literal in deb822 attribute",
-                    ));
-                }
-            } else if nv.path.is_ident("serialize_with") {
-                if let syn::Expr::Path(s) = nv.value {
-                    serialize_with = Some(s);
-                } else {
-                    return Err(syn::Error::new(
-                        nv.value.span(),
-                        "expected path in deb822 attribute",
-                    ));
-                }
-            } else if nv.path.is_ident("deserialize_with") {
-                if let syn::Expr::Path(s) = nv.value {
-                    deserialize_with = Some(s);
-                } else {
-                    return Err(syn::Error::new(
-                        nv.value.span(),
-                        "expected path in deb822 attribute",
-                    ));
-                }
-            } else {
-                return Err(syn::Error::new(
-                    nv.span(),
-                    format!("unsupported attribute: {}", nv.path.get_ident().unwrap()),
-                ));
-            }
+        ret.extend(name_values);
+    }
+    Ok(ret)
+}
+
+fn unsupported_attribute(nv: &syn::MetaNameValue) -> syn::Error {
+    syn::Error::new(
+        nv.span(),
+        format!(
+            "unsupported attribute: {}",
+            nv.path
+                .get_ident()
+                .map_or_else(String::new, |i| i.to_string())
+        ),
+    )
+}
+
+fn extract_field_attributes(attrs: &[syn::Attribute]) -> Result<FieldAttributes, syn::Error> {
+    let mut field = None;
+    let mut serialize_with = None;
+    let mut deserialize_with = None;
+    let mut separator = None;
+    for nv in deb822_attributes(attrs)? {
+        if nv.path.is_ident("field") {
+            field = Some(string_value(&nv)?);
+        } else if nv.path.is_ident("serialize_with") {
+            serialize_with = Some(path_value(&nv)?);
+        } else if nv.path.is_ident("deserialize_with") {
+            deserialize_with = Some(path_value(&nv)?);
+        } else if nv.path.is_ident("separator") {
+            separator = Some(string_value(&nv)?);
+        } else {
+            return Err(unsupported_attribute(&nv));
         }
     }
     Ok(FieldAttributes {
         field,
         serialize_with,
         deserialize_with,
+        separator,
     })
 }
 
-#[proc_macro_derive(FromDeb822, attributes(deb822))]
-pub fn derive_from_deb822(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let name = &input.ident;
+/// Naming conventions for deriving field names from struct field names.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    /// `build_depends` becomes `Build-Depends`.
+    TrainCase,
+    /// `build_depends` becomes `build-depends`.
+    KebabCase,
+}
+
+impl RenameRule {
+    fn apply(&self, name: &str) -> String {
+        name.split('_')
+            .map(|word| match self {
+                RenameRule::TrainCase => {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                }
+                RenameRule::KebabCase => word.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+}
 
+fn extract_container_attributes(
+    attrs: &[syn::Attribute],
+) -> Result<Option<RenameRule>, syn::Error> {
+    let mut rename_all = None;
+    for nv in deb822_attributes(attrs)? {
+        if nv.path.is_ident("rename_all") {
+            rename_all = Some(match string_value(&nv)?.as_str() {
+                "Train-Case" => RenameRule::TrainCase,
+                "kebab-case" => RenameRule::KebabCase,
+                _ => {
+                    return Err(syn::Error::new(
+                        nv.value.span(),
+                        "expected \"Train-Case\" or \"kebab-case\"",
+                    ))
+                }
+            });
+        } else {
+            return Err(unsupported_attribute(&nv));
+        }
+    }
+    Ok(rename_all)
+}
+
+/// A field of the struct, with the information needed to generate code for it.
+struct Field<'a> {
+    ident: &'a syn::Ident,
+    key: String,
+    attrs: FieldAttributes,
+    ty: &'a syn::Type,
+}
+
+fn struct_fields<'a>(input: &'a DeriveInput, derive: &str) -> Result<Vec<Field<'a>>, syn::Error> {
     let s = if let syn::Data::Struct(s) = &input.data {
         s
     } else {
-        panic!("FromDeb822 can only be derived for structs")
+        return Err(syn::Error::new(
+            input.span(),
+            format!("{} can only be derived for structs", derive),
+        ));
     };
-
-    let from_fields = s.fields.iter().map(|f| {
-        let attrs = extract_field_attributes(&f.attrs).unwrap();
-            let ident = &f.ident;
+    let rename_all = extract_container_attributes(&input.attrs)?;
+    s.fields
+        .iter()
+        .map(|f| {
+            let ident = f.ident.as_ref().ok_or_else(|| {
+                syn::Error::new(f.span(), format!("{} requires named fields", derive))
+            })?;
+            let attrs = extract_field_attributes(&f.attrs)?;
             // Get key either from the #[deb822(field = "foo")] attribute, or derive it from the
             // field name
-            let key = attrs.field.unwrap_or_else(||ident.as_ref().unwrap().to_string());
-            let deserialize_with = if let Some(deserialize_with) = attrs.deserialize_with {
-                quote! { #deserialize_with }
-            } else {
-                quote! { std::str::FromStr::from_str }
+            let key = attrs.field.clone().unwrap_or_else(|| {
+                let name = ident.to_string();
+                rename_all.map_or(name.clone(), |rule| rule.apply(&name))
+            });
+            Ok(Field {
+                ident,
+                key,
+                attrs,
+                ty: &f.ty,
+            })
+        })
+        .collect()
+}
+
+#[proc_macro_derive(FromDeb822, attributes(deb822))]
+pub fn derive_from_deb822(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    impl_from_deb822(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn impl_from_deb822(input: &DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &input.ident;
+
+    let from_fields = struct_fields(input, "FromDeb822")?
+        .into_iter()
+        .map(|f| {
+            let ident = f.ident;
+            let key = &f.key;
+            let separator = match &f.attrs.separator {
+                Some(separator) => quote! { Some(#separator) },
+                None => quote! { None },
+            };
+            // Convert a value `v` to the type of the field
+            let parse = match (&f.attrs.deserialize_with, list_item_type(f.ty)) {
+                (Some(deserialize_with), _) => quote! {
+                    #deserialize_with(&v).map_err(|e| deb822_lossless::convert::FieldError::new(#key, &v, e))
+                },
+                (None, Some(item)) => quote! {
+                    deb822_lossless::convert::split_list(&v, #separator)
+                        .map(|item| {
+                            <#item as core::str::FromStr>::from_str(item)
+                                .map_err(|e| deb822_lossless::convert::FieldError::new(#key, item, e))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+                (None, None) => quote! {
+                    core::str::FromStr::from_str(&v).map_err(|e| deb822_lossless::convert::FieldError::new(#key, &v, e))
+                },
             };
-            // Check if the field is optional or not
-            let ty = &f.ty;
-            let is_option = is_option(ty);
 
-            if is_option {
+            if is_option(f.ty) {
                 // Allow the field to be missing
                 quote! {
-                    #ident: para.get(#key).map(|v| #deserialize_with(&v).map_err(|e| format!("parsing field {}: {}", #key, e))).transpose()?
+                    #ident: para.get(#key).map(|v| #parse).transpose()?
                 }
             } else {
                 // The field is required
                 quote! {
-                    #ident: #deserialize_with(&para.get(#key).ok_or_else(|| format!("missing field: {}", #key))?).map_err(|e| format!("parsing field {}: {}", #key, e))?
+                    #ident: {
+                        let v = para.get(#key).ok_or_else(|| {
+                            deb822_lossless::convert::FromParagraphError::MissingField(#key.to_string())
+                        })?;
+                        #parse?
+                    }
                 }
             }
-        }).collect::<Vec<_>>();
+        })
+        .collect::<Vec<_>>();
 
-    let gen = quote! {
+    Ok(quote! {
         impl<P: deb822_lossless::convert::Deb822LikeParagraph> deb822_lossless::FromDeb822Paragraph<P> for #name {
-            fn from_paragraph(para: &P) -> Result<Self, String> {
+            fn from_paragraph(para: &P) -> Result<Self, deb822_lossless::convert::FromParagraphError> {
                 Ok(Self {
                     #(#from_fields,)*
                 })
             }
         }
-    };
-    gen.into()
+    })
 }
 
 #[proc_macro_derive(ToDeb822, attributes(deb822))]
 pub fn derive_to_deb822(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = &input.ident;
+    impl_to_deb822(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
 
-    let s = if let syn::Data::Struct(s) = &input.data {
-        s
-    } else {
-        panic!("Deb822 can only be derived for structs")
-    };
+fn impl_to_deb822(input: &DeriveInput) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &input.ident;
 
     let mut to_fields = vec![];
     let mut update_fields = vec![];
 
-    for f in s.fields.iter() {
-        let attrs = extract_field_attributes(&f.attrs).unwrap();
-        let ident = &f.ident;
-        let key = attrs
-            .field
-            .unwrap_or_else(|| ident.as_ref().unwrap().to_string());
-        let serialize_with = if let Some(serialize_with) = attrs.serialize_with {
-            quote! { #serialize_with }
-        } else {
-            quote! { ToString::to_string }
+    for f in struct_fields(input, "ToDeb822")? {
+        let ident = f.ident;
+        let key = &f.key;
+        let separator = match &f.attrs.separator {
+            Some(separator) => quote! { Some(#separator) },
+            None => quote! { None },
+        };
+        // Convert a reference `v` to the field type to a string
+        let serialize = match (&f.attrs.serialize_with, list_item_type(f.ty)) {
+            (Some(serialize_with), _) => quote! { #serialize_with(&v) },
+            (None, Some(_)) => quote! {
+                deb822_lossless::convert::join_list(v.iter().map(ToString::to_string), #separator)
+            },
+            (None, None) => quote! { ToString::to_string(&v) },
         };
 
-        let ty = &f.ty;
-        let is_option = is_option(ty);
-
-        to_fields.push(if is_option {
+        to_fields.push(if is_option(f.ty) {
             quote! {
                 if let Some(v) = &self.#ident {
-                    fields.push((#key.to_string(), #serialize_with(&v)));
+                    fields.push((#key.to_string(), #serialize));
                 }
             }
         } else {
             quote! {
-                fields.push((#key.to_string(), #serialize_with(&self.#ident)));
+                let v = &self.#ident;
+                fields.push((#key.to_string(), #serialize));
             }
         });
 
-        update_fields.push(if is_option {
+        update_fields.push(if is_option(f.ty) {
             quote! {
                 if let Some(v) = &self.#ident {
-                    para.set(#key, #serialize_with(&v).as_str());
+                    para.set(#key, #serialize.as_str());
                 } else {
                     para.remove(#key);
                 }
             }
         } else {
             quote! {
-                para.set(#key, #serialize_with(&self.#ident).as_str());
+                let v = &self.#ident;
+                para.set(#key, #serialize.as_str());
             }
         });
     }
 
-    let gen = quote! {
+    Ok(quote! {
         impl<P: deb822_lossless::convert::Deb822LikeParagraph> deb822_lossless::ToDeb822Paragraph<P> for #name {
             fn to_paragraph(&self) -> P {
                 let mut fields = Vec::<(String, String)>::new();
@@ -269,6 +424,5 @@ pub fn derive_to_deb822(input: TokenStream) -> TokenStream {
                 #(#update_fields)*
             }
         }
-    };
-    gen.into()
+    })
 }
@@ -1,17 +1,39 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use deb822_lossless::lossless::Deb822;
 
+mod fixtures;
+
 fn parse_deb822_benchmark(c: &mut Criterion) {
-    let control_data =
-        std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/bench/Sources"))
-            .expect("Could not read control file");
+    let mut group = c.benchmark_group("parse_deb822_lossless");
+    for (name, data) in fixtures::inputs() {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let _deb822: Deb822 = data.parse().unwrap();
+            });
+        });
+    }
+    group.finish();
+}
 
-    c.bench_function("parse_deb822_lossless", |b| {
+/// Parse an input the size of the `Sources` file of unstable's main component, about 47 MB.
+fn parse_archive_sized_benchmark(c: &mut Criterion) {
+    let (_, sources) = fixtures::inputs().swap_remove(0);
+    let data = sources.repeat(16);
+    let mut group = c.benchmark_group("parse_deb822_lossless");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("Sources (archive sized)", |b| {
         b.iter(|| {
-            let _deb822: Deb822 = control_data.parse().unwrap();
+            let _deb822: Deb822 = data.parse().unwrap();
         });
     });
+    group.finish();
 }
 
-criterion_group!(benches, parse_deb822_benchmark);
+criterion_group!(
+    benches,
+    parse_deb822_benchmark,
+    parse_archive_sized_benchmark
+);
 criterion_main!(benches);
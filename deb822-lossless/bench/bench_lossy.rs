@@ -1,16 +1,19 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use deb822_lossless::lossy::Deb822;
 
-fn parse_deb822_benchmark(c: &mut Criterion) {
-    let control_data =
-        std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/bench/Sources"))
-            .expect("Could not read control file");
+mod fixtures;
 
-    c.bench_function("parse_deb822_lossy", |b| {
-        b.iter(|| {
-            let _deb822: Deb822 = control_data.parse().unwrap();
+fn parse_deb822_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_deb822_lossy");
+    for (name, data) in fixtures::inputs() {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let _deb822: Deb822 = data.parse().unwrap();
+            });
         });
-    });
+    }
+    group.finish();
 }
 
 criterion_group!(benches, parse_deb822_benchmark);
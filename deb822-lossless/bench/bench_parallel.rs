@@ -0,0 +1,21 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use deb822_lossless::lossy::Deb822;
+use deb822_lossless::ParseOptions;
+
+mod fixtures;
+
+fn parse_deb822_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_deb822_parallel");
+    for (name, data) in fixtures::inputs() {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let _deb822 = Deb822::from_str_parallel(&data, &ParseOptions::default()).unwrap();
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_deb822_benchmark);
+criterion_main!(benches);
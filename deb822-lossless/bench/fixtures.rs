@@ -0,0 +1,104 @@
+//! Inputs for the parsing benchmarks.
+//!
+//! `bench/Sources` is shipped with the crate. A `Packages` file of similar shape is generated
+//! from it, rather than shipped, unless `DEB822_BENCH_PACKAGES` names a real one, such as a
+//! file from `/var/lib/apt/lists`.
+use deb822_lossless::lossy::Deb822;
+use std::fmt::Write;
+
+/// Return the name and contents of each benchmark input.
+pub fn inputs() -> Vec<(&'static str, String)> {
+    let sources = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/bench/Sources"))
+        .expect("Could not read bench/Sources");
+    let packages = match std::env::var_os("DEB822_BENCH_PACKAGES") {
+        Some(path) => std::fs::read_to_string(path).expect("Could not read Packages file"),
+        None => packages_from_sources(&sources),
+    };
+    vec![("Sources", sources), ("Packages", packages)]
+}
+
+/// A hex digest of `len` characters, derived from `seed`.
+fn digest(seed: &str, len: usize) -> String {
+    let mut state = seed.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    let mut digest = String::with_capacity(len);
+    while digest.len() < len {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        write!(digest, "{:016x}", state).unwrap();
+    }
+    digest.truncate(len);
+    digest
+}
+
+/// Generate a `Packages` file with a paragraph for each binary package in `sources`.
+fn packages_from_sources(sources: &str) -> String {
+    let sources: Deb822 = sources.parse().expect("Could not parse bench/Sources");
+    let mut out = String::new();
+    for source in sources.iter() {
+        let (Some(name), Some(version), Some(package_list)) = (
+            source.get("Package"),
+            source.get("Version"),
+            source.get("Package-List"),
+        ) else {
+            continue;
+        };
+        for line in package_list.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(package), Some(_), Some(section), Some(priority), Some(arch)) = (
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+            ) else {
+                continue;
+            };
+            let arch = if arch == "arch=all" { "all" } else { "amd64" };
+            let seed = format!("{}_{}_{}", package, version, arch);
+            let size = u64::from_str_radix(&digest(&seed, 5), 16).unwrap();
+            writeln!(out, "Package: {}", package).unwrap();
+            if package != name {
+                writeln!(out, "Source: {}", name).unwrap();
+            }
+            writeln!(out, "Version: {}", version).unwrap();
+            writeln!(out, "Installed-Size: {}", size / 256).unwrap();
+            for field in ["Maintainer", "Homepage"] {
+                if let Some(value) = source.get(field) {
+                    writeln!(out, "{}: {}", field, value).unwrap();
+                }
+            }
+            writeln!(out, "Architecture: {}", arch).unwrap();
+            if let Some(depends) = source.get("Build-Depends") {
+                writeln!(out, "Depends: {}", depends).unwrap();
+            }
+            let description = source
+                .get("Description")
+                .map_or_else(|| format!("{} package", package), |d| d.to_string());
+            let mut lines = description.lines();
+            writeln!(out, "Description: {}", lines.next().unwrap_or_default()).unwrap();
+            for line in lines {
+                writeln!(out, " {}", if line.is_empty() { "." } else { line }).unwrap();
+            }
+            writeln!(out, "Description-md5: {}", digest(&description, 32)).unwrap();
+            writeln!(out, "Section: {}", section).unwrap();
+            writeln!(out, "Priority: {}", priority).unwrap();
+            writeln!(
+                out,
+                "Filename: pool/main/{}/{}/{}_{}_{}.deb",
+                &name[..1],
+                name,
+                package,
+                version.rsplit(':').next().unwrap(),
+                arch
+            )
+            .unwrap();
+            writeln!(out, "Size: {}", size).unwrap();
+            writeln!(out, "SHA256: {}", digest(&seed, 64)).unwrap();
+            writeln!(out).unwrap();
+        }
+    }
+    out
+}
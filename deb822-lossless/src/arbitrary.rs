@@ -0,0 +1,187 @@
+//! Implementations of [`arbitrary::Arbitrary`] for fuzzing.
+//!
+//! The generated documents are always well-formed: field names start with a letter and
+//! consist of ASCII letters, digits and hyphens, and values consist of one or more lines of
+//! printable ASCII text without leading or trailing whitespace. Continuation lines may also be
+//! empty or start with `#`. Every paragraph has at least one field, and the names of the fields
+//! in a paragraph are unique. This means that parsing the text of a generated document with
+//! [`ParseOptions::archive`], which reads indented `#` lines as part of the value, returns an
+//! equal document, so fuzz targets can check round-tripping and editing invariants rather than
+//! spending most of their time on syntax errors.
+//!
+//! The lossless types are generated by formatting a lossy document; use the functions in this
+//! module to build documents for other formats on top of deb822.
+//!
+//! # Example
+//!
+//! ```rust
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use deb822_lossless::{lossy::Deb822, ParseOptions};
+//!
+//! let mut u = Unstructured::new(b"some bytes provided by the fuzzer");
+//! let deb822 = Deb822::arbitrary(&mut u).unwrap();
+//! let options = ParseOptions::archive();
+//! assert_eq!(Deb822::from_str_with(&deb822.to_string(), &options).unwrap(), deb822);
+//! ```
+//!
+//! [`ParseOptions::archive`]: crate::ParseOptions::archive
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Generate a field name.
+pub fn field_name(u: &mut Unstructured) -> Result<String> {
+    let mut name = String::from(*u.choose(LETTERS)? as char);
+    for _ in 0..u.int_in_range(0..=15)? {
+        let c = u.int_in_range(0..=LETTERS.len() + 10)?;
+        name.push(match c {
+            c if c < LETTERS.len() => LETTERS[c] as char,
+            c if c < LETTERS.len() + 10 => (b'0' + (c - LETTERS.len()) as u8) as char,
+            _ => '-',
+        });
+    }
+    Ok(name)
+}
+
+/// Generate the first line of a field value.
+///
+/// The line does not start with `#` or `:`, so that it can not be mistaken for a comment or
+/// the separator of a field.
+pub fn field_line(u: &mut Unstructured) -> Result<String> {
+    let mut line = String::from(match u.int_in_range(b'!'..=b'~')? {
+        b'#' | b':' => '.',
+        c => c as char,
+    });
+    for _ in 0..u.int_in_range(0..=30)? {
+        line.push(u.int_in_range(b' '..=b'~')? as char);
+    }
+    line.truncate(line.trim_end().len());
+    Ok(line)
+}
+
+/// Generate a continuation line of a field value.
+///
+/// Unlike the first line, a continuation line may be empty or start with `#`.
+pub fn continuation_line(u: &mut Unstructured) -> Result<String> {
+    Ok(match u.int_in_range(0..=5)? {
+        0 => String::new(),
+        1 => format!("#{}", field_line(u)?),
+        _ => field_line(u)?,
+    })
+}
+
+/// Generate a field value of one or more lines, separated by `\n`.
+///
+/// The last line is never empty, since trailing empty lines are not part of the value.
+pub fn field_value(u: &mut Unstructured) -> Result<String> {
+    let mut lines = vec![field_line(u)?];
+    for _ in 0..u.int_in_range(0..=3)? {
+        lines.push(continuation_line(u)?);
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Generate the fields of a paragraph, with unique names.
+fn fields(u: &mut Unstructured) -> Result<Vec<(String, String)>> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for _ in 0..u.int_in_range(1..=8)? {
+        let name = field_name(u)?;
+        let value = field_value(u)?;
+        if !fields.iter().any(|(n, _)| n.eq_ignore_ascii_case(&name)) {
+            fields.push((name, value));
+        }
+    }
+    Ok(fields)
+}
+
+impl<'a> Arbitrary<'a> for crate::lossy::Paragraph {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(fields(u)?.into_iter().collect())
+    }
+}
+
+impl<'a> Arbitrary<'a> for crate::lossy::Deb822 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        (0..u.int_in_range(0..=4)?)
+            .map(|_| crate::lossy::Paragraph::arbitrary(u))
+            .collect()
+    }
+}
+
+/// Parse generated text, keeping indented `#` lines in the values.
+fn to_lossless(text: &str) -> crate::lossless::Deb822 {
+    crate::lossless::Deb822::from_str_relaxed_with(text, &crate::ParseOptions::archive()).0
+}
+
+impl<'a> Arbitrary<'a> for crate::lossless::Paragraph {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let paragraph = crate::lossy::Paragraph::arbitrary(u)?;
+        Ok(to_lossless(&paragraph.to_string())
+            .paragraphs()
+            .next()
+            .unwrap_or_default())
+    }
+}
+
+impl<'a> Arbitrary<'a> for crate::lossless::Deb822 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(to_lossless(
+            &crate::lossy::Deb822::arbitrary(u)?.to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random input, so that the tests cover more than empty data.
+    fn data(seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..4096)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for seed in 0..50 {
+            let data = data(seed);
+            let mut u = Unstructured::new(&data);
+            let options = crate::ParseOptions::archive();
+            let lossy = crate::lossy::Deb822::arbitrary(&mut u).unwrap();
+            assert_eq!(
+                crate::lossy::Deb822::from_str_with(&lossy.to_string(), &options).unwrap(),
+                lossy
+            );
+
+            let lossless = crate::lossless::Deb822::arbitrary(&mut u).unwrap();
+            let text = lossless.to_string();
+            assert_eq!(
+                crate::lossless::Deb822::from_str_with(&text, &options).unwrap(),
+                lossless
+            );
+            assert_eq!(
+                crate::lossy::Deb822::from(&lossless).to_string(),
+                text,
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let mut u = Unstructured::new(&[]);
+        let paragraph = crate::lossy::Paragraph::arbitrary(&mut u).unwrap();
+        assert_eq!(paragraph.len(), 1);
+    }
+}
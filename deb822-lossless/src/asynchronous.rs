@@ -0,0 +1,245 @@
+//! Asynchronous parsing of deb822 files.
+//!
+//! This module provides a [`Stream`] of paragraphs read from a tokio [`AsyncBufRead`], so that
+//! large files (such as apt `Packages` indexes) can be parsed while they are being
+//! downloaded, without blocking the runtime or reading the whole file into memory.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::asynchronous::AsyncParagraphStream;
+//! use futures_util::StreamExt;
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let input: &[u8] = b"Package: hello\nVersion: 2.10\n\nPackage: world\nVersion: 1.0\n";
+//! let mut stream = AsyncParagraphStream::new(input);
+//! let para = stream.next().await.unwrap().unwrap();
+//! assert_eq!(para.get("Package"), Some("hello"));
+//! let para = stream.next().await.unwrap().unwrap();
+//! assert_eq!(para.get("Package"), Some("world"));
+//! assert!(stream.next().await.is_none());
+//! # });
+//! ```
+//!
+//! To read a complete document, use [`Deb822::from_async_read`].
+use crate::lossy::{Deb822, Error, Paragraph};
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncBufRead, AsyncRead, BufReader};
+
+/// A stream of paragraphs read from an asynchronous reader.
+///
+/// Paragraphs are yielded as soon as the empty line terminating them has been read.
+pub struct AsyncParagraphStream<R> {
+    reader: R,
+    /// The (incomplete) line currently being read.
+    line: Vec<u8>,
+    /// The lines of the paragraph currently being read.
+    paragraph: Vec<u8>,
+    /// Whether the end of the input has been reached.
+    eof: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncParagraphStream<R> {
+    /// Create a new stream of paragraphs from a reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            paragraph: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Parse the buffered paragraph, if it contains any fields.
+    fn take_paragraph(&mut self) -> Option<Result<Paragraph, Error>> {
+        if self.paragraph.is_empty() {
+            return None;
+        }
+        let buf = std::mem::take(&mut self.paragraph);
+        let text = match String::from_utf8(buf) {
+            Ok(text) => text,
+            Err(e) => {
+                return Some(Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e,
+                ))))
+            }
+        };
+        match text.parse::<Deb822>() {
+            // A paragraph that only consists of comments doesn't produce any paragraphs
+            Ok(doc) => doc.into_iter().next().map(Ok),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Process a complete line, returning a paragraph if the line terminated one.
+    fn process_line(&mut self) -> Option<Result<Paragraph, Error>> {
+        let line = std::mem::take(&mut self.line);
+        if line.iter().all(|c| c.is_ascii_whitespace()) {
+            self.take_paragraph()
+        } else {
+            self.paragraph.extend_from_slice(&line);
+            None
+        }
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for AsyncParagraphStream<R> {
+    type Item = Result<Paragraph, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.eof {
+                return Poll::Ready(None);
+            }
+            let buf = match Pin::new(&mut this.reader).poll_fill_buf(cx) {
+                Poll::Ready(Ok(buf)) => buf,
+                Poll::Ready(Err(e)) => {
+                    this.eof = true;
+                    return Poll::Ready(Some(Err(Error::Io(e))));
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            if buf.is_empty() {
+                this.eof = true;
+                if !this.line.is_empty() {
+                    this.line.push(b'\n');
+                    if let Some(ret) = this.process_line() {
+                        return Poll::Ready(Some(ret));
+                    }
+                }
+                return Poll::Ready(this.take_paragraph());
+            }
+            let (consumed, complete) = match buf.iter().position(|c| *c == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (buf.len(), false),
+            };
+            this.line.extend_from_slice(&buf[..consumed]);
+            Pin::new(&mut this.reader).consume(consumed);
+            if complete {
+                if let Some(ret) = this.process_line() {
+                    return Poll::Ready(Some(ret));
+                }
+            }
+        }
+    }
+}
+
+impl Deb822 {
+    /// Read a document from an asynchronous reader.
+    ///
+    /// The input is parsed one paragraph at a time as it is read.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let input: &[u8] = b"Package: hello\n\nPackage: world\n";
+    /// let deb822 = Deb822::from_async_read(input).await.unwrap();
+    /// assert_eq!(deb822.len(), 2);
+    /// # });
+    /// ```
+    pub async fn from_async_read<R: AsyncRead + Unpin>(reader: R) -> Result<Self, Error> {
+        let mut stream = AsyncParagraphStream::new(BufReader::new(reader));
+        let mut paragraphs = Vec::new();
+        while let Some(paragraph) =
+            std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await
+        {
+            paragraphs.push(paragraph?);
+        }
+        Ok(Deb822::from(paragraphs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn collect(input: &[u8]) -> Vec<Result<Paragraph, Error>> {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(AsyncParagraphStream::new(input).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = b"Package: hello
+Version: 2.10
+Description: A program that says hello
+ Some more text
+
+Package: world
+Version: 1.0
+";
+        let paragraphs = collect(input)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].get("Package"), Some("hello"));
+        assert_eq!(
+            paragraphs[0].get("Description"),
+            Some("A program that says hello\nSome more text")
+        );
+        assert_eq!(paragraphs[1].get("Package"), Some("world"));
+        assert_eq!(paragraphs[1].get("Version"), Some("1.0"));
+    }
+
+    #[test]
+    fn test_stream_comments_and_blank_lines() {
+        let input = b"
+
+# A comment
+
+Package: hello
+# Another comment
+Version: 2.10
+
+
+Package: world";
+        let paragraphs = collect(input)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].get("Version"), Some("2.10"));
+        assert_eq!(paragraphs[1].get("Package"), Some("world"));
+    }
+
+    #[test]
+    fn test_from_async_read() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let input = b"Package: hello\n# Comment\nVersion: 2.10\n\n\nPackage: world\n";
+        let deb822 = runtime
+            .block_on(Deb822::from_async_read(&input[..]))
+            .unwrap();
+        assert_eq!(deb822, std::str::from_utf8(input).unwrap().parse().unwrap());
+
+        let input = b"Package: hello\n\n:invalid\n";
+        assert!(runtime
+            .block_on(Deb822::from_async_read(&input[..]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_stream_error() {
+        let input = b"Package: hello\n:invalid\n\nPackage: world\n";
+        let results = collect(input);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_err());
+        assert_eq!(results[1].as_ref().unwrap().get("Package"), Some("world"));
+    }
+}
@@ -0,0 +1,435 @@
+//! A `grep-dctrl` work-alike.
+//!
+//! Searches deb822 files (such as apt `Packages` and `Sources` indexes) for paragraphs matching
+//! a filter, reading compressed input transparently.
+use deb822_lossless::compression::decompress;
+use deb822_lossless::lossy::ParagraphReader;
+use deb822_lossless::query::{Matcher, Query};
+use std::io::{BufRead, BufReader, Write};
+
+const USAGE: &str = "Usage: rdctrl [OPTION...] FILTER [FILE...]
+
+Search deb822 files for paragraphs that match FILTER. With no FILE, or when FILE
+is -, read standard input. Compressed input is decompressed automatically.
+
+A FILTER consists of one or more simple filters, combined with -a/--and, -o/--or
+and -!/--not, and grouped with ( and ). A simple filter is a PATTERN, optionally
+preceded by modifiers:
+
+  -F, --field=FIELD[,FIELD...]  only match PATTERN against the given fields
+  -X, --exact-match             the whole value must match PATTERN
+  -e, --eregex, -r, --regex     PATTERN is a regular expression
+  -i, --ignore-case             ignore case when matching
+
+Output options:
+
+  -s, --show-field=FIELD[,FIELD...]  only show the given fields
+  -n, --no-field-names               don't show field names with -s
+  -c, --count                        only print the number of matching paragraphs
+  -v, --invert-match                 select paragraphs that don't match
+  -q, --quiet, --silent              don't print anything
+  -h, --help                         show this help
+
+The exit status is 0 if any paragraph was selected, 1 if none was, and 2 on error.
+";
+
+#[derive(Debug, Default)]
+struct Options {
+    show_fields: Vec<String>,
+    no_field_names: bool,
+    count: bool,
+    invert: bool,
+    quiet: bool,
+    help: bool,
+}
+
+#[derive(Debug)]
+struct Args {
+    query: Query,
+    options: Options,
+    files: Vec<String>,
+}
+
+/// Split a comma-separated list of field names.
+fn split_fields(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(',')
+        .map(|f| f.trim())
+        .filter(|f| !f.is_empty())
+        .map(|f| f.to_string())
+}
+
+struct ArgParser {
+    args: Vec<String>,
+    pos: usize,
+    options: Options,
+}
+
+impl ArgParser {
+    /// Return the value of an option, if `arg` is one of its spellings.
+    ///
+    /// Supports `-F VALUE`, `-FVALUE`, `--field VALUE` and `--field=VALUE`.
+    fn option_value(
+        &mut self,
+        arg: &str,
+        short: &str,
+        long: &str,
+    ) -> Result<Option<String>, String> {
+        if arg == short || arg == long {
+            self.pos += 1;
+            let value = self
+                .args
+                .get(self.pos)
+                .ok_or_else(|| format!("option {} requires an argument", arg))?
+                .clone();
+            self.pos += 1;
+            Ok(Some(value))
+        } else if let Some(value) = arg
+            .strip_prefix(long)
+            .and_then(|rest| rest.strip_prefix('='))
+            .or_else(|| arg.strip_prefix(short).filter(|rest| !rest.is_empty()))
+        {
+            let value = value.to_string();
+            self.pos += 1;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Return the next argument that isn't an output option, processing any output options
+    /// along the way.
+    fn peek(&mut self) -> Result<Option<String>, String> {
+        while let Some(arg) = self.args.get(self.pos).cloned() {
+            if let Some(value) = self.option_value(&arg, "-s", "--show-field")? {
+                self.options.show_fields.extend(split_fields(&value));
+                continue;
+            }
+            let flag = match arg.as_str() {
+                "-n" | "--no-field-names" => &mut self.options.no_field_names,
+                "-c" | "--count" => &mut self.options.count,
+                "-v" | "--invert-match" => &mut self.options.invert,
+                "-q" | "--quiet" | "--silent" => &mut self.options.quiet,
+                "-h" | "--help" => &mut self.options.help,
+                _ => return Ok(Some(arg)),
+            };
+            *flag = true;
+            self.pos += 1;
+        }
+        Ok(None)
+    }
+
+    fn parse_or(&mut self) -> Result<Query, String> {
+        let mut query = self.parse_and()?;
+        while let Some("-o" | "--or") = self.peek()?.as_deref() {
+            self.pos += 1;
+            query = query.or(self.parse_and()?);
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query, String> {
+        let mut query = self.parse_not()?;
+        while let Some("-a" | "--and") = self.peek()?.as_deref() {
+            self.pos += 1;
+            query = query.and(self.parse_not()?);
+        }
+        Ok(query)
+    }
+
+    fn parse_not(&mut self) -> Result<Query, String> {
+        match self.peek()?.as_deref() {
+            Some("-!" | "--not" | "!") => {
+                self.pos += 1;
+                Ok(!self.parse_not()?)
+            }
+            Some("(") => {
+                self.pos += 1;
+                let query = self.parse_or()?;
+                if self.peek()?.as_deref() != Some(")") {
+                    return Err("missing )".to_string());
+                }
+                self.pos += 1;
+                Ok(query)
+            }
+            _ => self.parse_simple(),
+        }
+    }
+
+    fn parse_simple(&mut self) -> Result<Query, String> {
+        let mut fields = Vec::new();
+        let mut exact = false;
+        let mut regex = false;
+        let mut ignore_case = false;
+        let pattern = loop {
+            let arg = self.peek()?.ok_or("missing pattern")?;
+            if let Some(value) = self.option_value(&arg, "-F", "--field")? {
+                fields.extend(split_fields(&value));
+                continue;
+            }
+            match arg.as_str() {
+                "-X" | "--exact-match" => exact = true,
+                "-e" | "--eregex" | "-r" | "--regex" => regex = true,
+                "-i" | "--ignore-case" => ignore_case = true,
+                "--" => {
+                    self.pos += 1;
+                    break self.args.get(self.pos).ok_or("missing pattern")?.clone();
+                }
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(format!("unknown option {}", arg))
+                }
+                _ => break arg,
+            }
+            self.pos += 1;
+        };
+        self.pos += 1;
+        let matcher = match (regex, exact) {
+            (true, true) => Matcher::regex(&format!("^(?:{})$", pattern)),
+            (true, false) => Matcher::regex(&pattern),
+            (false, true) => Ok(Matcher::exact(&pattern)),
+            (false, false) => Ok(Matcher::substring(&pattern)),
+        }
+        .map_err(|e| e.to_string())?;
+        let matcher = if ignore_case {
+            matcher.ignore_case()
+        } else {
+            matcher
+        };
+        Ok(Query::fields(fields, matcher))
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Args, String> {
+    let mut parser = ArgParser {
+        args,
+        pos: 0,
+        options: Options::default(),
+    };
+    // Process any leading output options, so that "--help" works without a filter
+    parser.peek()?;
+    if parser.options.help {
+        return Ok(Args {
+            query: Query::any_field(Matcher::substring("")),
+            options: parser.options,
+            files: Vec::new(),
+        });
+    }
+    let query = parser.parse_or()?;
+    let mut files = Vec::new();
+    while let Some(arg) = parser.peek()? {
+        if arg.starts_with('-') && arg != "-" {
+            return Err(format!("unexpected option {}", arg));
+        }
+        files.push(arg);
+        parser.pos += 1;
+    }
+    Ok(Args {
+        query,
+        options: parser.options,
+        files,
+    })
+}
+
+/// Write a field, in deb822 format.
+fn write_field(out: &mut dyn Write, name: Option<&str>, value: &str) -> std::io::Result<()> {
+    if let Some(name) = name {
+        write!(out, "{}:", name)?;
+        if !value.starts_with('\n') {
+            out.write_all(b" ")?;
+        }
+    }
+    writeln!(out, "{}", value.replace('\n', "\n "))
+}
+
+/// Search the paragraphs of a file, returning the number of selected paragraphs.
+fn search(args: &Args, input: impl BufRead, out: &mut dyn Write) -> Result<usize, String> {
+    let options = &args.options;
+    let mut count = 0;
+    for paragraph in ParagraphReader::new(input) {
+        let paragraph = paragraph.map_err(|e| e.to_string())?;
+        if args.query.is_match(&paragraph) == options.invert {
+            continue;
+        }
+        count += 1;
+        if options.quiet {
+            break;
+        }
+        if options.count {
+            continue;
+        }
+        let io = |e: std::io::Error| e.to_string();
+        if options.show_fields.is_empty() {
+            write!(out, "{}", paragraph).map_err(io)?;
+        } else {
+            for field in &options.show_fields {
+                if let Some(value) = paragraph.get(field) {
+                    let name = if options.no_field_names {
+                        None
+                    } else {
+                        Some(field.as_str())
+                    };
+                    write_field(out, name, value).map_err(io)?;
+                }
+            }
+        }
+        // Paragraphs are separated by empty lines, unless only a single value is shown
+        if !(options.no_field_names && options.show_fields.len() == 1) {
+            writeln!(out).map_err(io)?;
+        }
+    }
+    Ok(count)
+}
+
+/// Open an input file, or standard input for `-`, decompressing it if necessary.
+fn read_input(path: &str) -> std::io::Result<Box<dyn BufRead>> {
+    let reader = if path == "-" {
+        decompress(BufReader::new(std::io::stdin().lock()))?
+    } else {
+        decompress(BufReader::new(std::fs::File::open(path)?))?
+    };
+    Ok(Box::new(BufReader::new(reader)))
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("rdctrl: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.options.help {
+        print!("{}", USAGE);
+        return;
+    }
+    let files = if args.files.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        args.files.clone()
+    };
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut total = 0;
+    for file in &files {
+        let result = read_input(file)
+            .map_err(|e| e.to_string())
+            .and_then(|input| search(&args, input, &mut out));
+        match result {
+            Ok(count) => total += count,
+            Err(e) => {
+                eprintln!("rdctrl: {}: {}", file, e);
+                std::process::exit(2);
+            }
+        }
+        if args.options.quiet && total > 0 {
+            break;
+        }
+    }
+    if args.options.count && !args.options.quiet {
+        println!("{}", total);
+    }
+    std::process::exit(if total > 0 { 0 } else { 1 });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGES: &str = r#"Package: hello
+Version: 2.10-3
+Section: devel
+Depends: libc6 (>= 2.34)
+Description: example package based on GNU hello
+ The GNU hello program produces a familiar, friendly greeting.
+
+Package: hello-traditional
+Version: 2.10-6
+Section: oldlibs
+Depends: hello
+
+Package: cowsay
+Version: 3.03
+Section: games
+Description: configurable talking cow
+"#;
+
+    fn grep(args: &[&str]) -> (usize, String) {
+        let args = parse_args(args.iter().map(|a| a.to_string()).collect()).unwrap();
+        let mut out = Vec::new();
+        let count = search(&args, PACKAGES.as_bytes(), &mut out).unwrap();
+        (count, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_substring() {
+        let (count, out) = grep(&["-F", "Package", "hello", "Packages"]);
+        assert_eq!(count, 2);
+        assert!(out.starts_with("Package: hello\nVersion: 2.10-3\n"));
+    }
+
+    #[test]
+    fn test_show_fields() {
+        assert_eq!(
+            grep(&["-FPackage", "-X", "hello", "-s", "Package,Version"]).1,
+            "Package: hello\nVersion: 2.10-3\n\n"
+        );
+        assert_eq!(
+            grep(&["-s", "Package", "-n", "hello"]).1,
+            "hello\nhello-traditional\n"
+        );
+        assert_eq!(
+            grep(&["-F", "Package", "-X", "hello", "-s", "Description"]).1,
+            "Description: example package based on GNU hello\n The GNU hello program produces a familiar, friendly greeting.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_boolean() {
+        let (count, out) = grep(&[
+            "-F", "Depends", "hello", "--or", "-F", "Section", "games", "-s", "Package", "-n",
+        ]);
+        assert_eq!(count, 2);
+        assert_eq!(out, "hello-traditional\ncowsay\n");
+
+        let (count, _) = grep(&[
+            "-F", "Package", "hello", "--and", "--not", "-F", "Section", "oldlibs",
+        ]);
+        assert_eq!(count, 1);
+
+        let (count, _) = grep(&[
+            "(", "-F", "Section", "devel", "-o", "-F", "Section", "games", ")", "-a", "-F",
+            "Version", "-X", "3.03",
+        ]);
+        assert_eq!(count, 1);
+
+        let (count, _) = grep(&["-v", "-F", "Package", "hello"]);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_regex_and_case() {
+        assert_eq!(grep(&["-F", "Package", "-e", "^h.*l$"]).0, 1);
+        assert_eq!(grep(&["-F", "Package", "-e", "-X", "hel+o"]).0, 1);
+        assert_eq!(grep(&["-F", "Section", "GAMES"]).0, 0);
+        assert_eq!(grep(&["-F", "Section", "-i", "GAMES"]).0, 1);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse_args(
+            ["-F", "Package", "hello", "-c", "a.gz", "-"]
+                .iter()
+                .map(|a| a.to_string())
+                .collect(),
+        )
+        .unwrap();
+        assert!(args.options.count);
+        assert_eq!(args.files, vec!["a.gz", "-"]);
+
+        assert!(parse_args(vec![]).is_err());
+        assert!(parse_args(vec!["-F".to_string()]).is_err());
+        assert!(parse_args(vec!["--bogus".to_string(), "x".to_string()]).is_err());
+        assert!(parse_args(vec!["(".to_string(), "x".to_string()]).is_err());
+        assert!(parse_args(vec!["--help".to_string()]).unwrap().options.help);
+    }
+}
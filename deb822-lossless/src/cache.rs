@@ -0,0 +1,293 @@
+//! On-disk cache of parsed deb822 files.
+//!
+//! Parsing large apt indexes (hundreds of megabytes of `Packages` files) can take a
+//! significant amount of time. This module allows storing the parsed representation in a
+//! binary cache file, together with a checksum of the file it was parsed from, so that
+//! subsequent runs can skip parsing as long as the source file hasn't changed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::lossy::Deb822;
+//!
+//! let dir = std::env::temp_dir().join(format!("deb822-cache-example-{}", std::process::id()));
+//! std::fs::create_dir_all(&dir).unwrap();
+//! let source = dir.join("Packages");
+//! std::fs::write(&source, "Package: hello\nVersion: 2.10\n").unwrap();
+//!
+//! // The first call parses the file and writes the cache; the second one loads it.
+//! let cache = dir.join("Packages.cache");
+//! let first = Deb822::from_file_cached(&source, &cache).unwrap();
+//! let second = Deb822::from_file_cached(&source, &cache).unwrap();
+//! assert_eq!(first, second);
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use crate::ParseOptions;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::Path;
+
+/// Version of the cache file format.
+///
+/// Bump this whenever the layout of cached types changes, so that old caches are discarded.
+const CACHE_FORMAT_VERSION: u32 = 3;
+
+/// The SHA256 checksum identifying the input a cache was built from.
+pub type Checksum = [u8; 32];
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<T> {
+    version: u32,
+    checksum: Checksum,
+    /// The name of the cached type, so that caches of different types don't get mixed up.
+    kind: String,
+    data: T,
+}
+
+/// Feeds the data passed to a [`Hasher`] into a SHA256 digest.
+struct DigestHasher<'a>(&'a mut Sha256);
+
+impl Hasher for DigestHasher<'_> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        unreachable!("only used to feed the digest")
+    }
+}
+
+/// Compute the checksum of a source file, as it is parsed with `options`.
+///
+/// `source` is the contents of the file as stored on disk, which may be compressed. The
+/// options and the version of the cache format are included, so that a cache is not used
+/// for a file parsed with different options or written by an incompatible version of this
+/// crate.
+pub fn checksum(source: &[u8], options: &ParseOptions) -> Checksum {
+    let mut digest = Sha256::new();
+    digest.update(CACHE_FORMAT_VERSION.to_le_bytes());
+    options.hash(&mut DigestHasher(&mut digest));
+    digest.update(source);
+    digest.finalize().into()
+}
+
+/// Write a cache file for data parsed from a source file with the given checksum.
+///
+/// The cache file is written atomically, so concurrent readers will never see a partially
+/// written cache.
+pub fn save<T: Serialize>(path: &Path, checksum: &Checksum, data: &T) -> std::io::Result<()> {
+    let cache = CacheFile {
+        version: CACHE_FORMAT_VERSION,
+        checksum: *checksum,
+        kind: std::any::type_name::<T>().to_string(),
+        data,
+    };
+    let bytes = bincode::serialize(&cache)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    crate::common::write_atomic(path, &bytes)
+}
+
+/// Load a cache file, if it is valid for a source file with the given checksum.
+///
+/// Returns `None` if the cache file does not exist, was written for a different source file,
+/// for a different type or by an incompatible version of this crate, or can not be decoded.
+pub fn load<T: DeserializeOwned>(path: &Path, checksum: &Checksum) -> std::io::Result<Option<T>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    match bincode::deserialize::<CacheFile<T>>(&bytes) {
+        Ok(cache)
+            if cache.version == CACHE_FORMAT_VERSION
+                && &cache.checksum == checksum
+                && cache.kind == std::any::type_name::<T>() =>
+        {
+            Ok(Some(cache.data))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Load data parsed from a source file from its cache, or parse the source file and write the
+/// cache.
+///
+/// `parse` is only called if `cache_path` does not contain a cache that was written for the
+/// current contents of `path` and the same `options`. It is passed the contents of the file,
+/// decompressed as by [`crate::compression::open`], and `options`. This can be used to cache
+/// any serializable structure that is built from a file, such as an
+/// [`crate::index::Deb822Index`].
+///
+/// # Example
+///
+/// ```rust
+/// use deb822_lossless::index::Deb822Index;
+/// use deb822_lossless::lossy::{Deb822, Error, Paragraph};
+/// use deb822_lossless::ParseOptions;
+///
+/// let dir = std::env::temp_dir()
+///     .join(format!("deb822-cache-index-example-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let source = dir.join("Packages");
+/// std::fs::write(&source, "Package: hello\nVersion: 2.10\n").unwrap();
+///
+/// let index: Deb822Index<Paragraph> = deb822_lossless::cache::load_or_parse(
+///     &source,
+///     &dir.join("Packages.cache"),
+///     &ParseOptions::archive(),
+///     |bytes, options| {
+///         let doc = Deb822::from_bytes_with(bytes, options)?;
+///         Ok::<_, Error>(Deb822Index::new("Package", doc))
+///     },
+/// )
+/// .unwrap();
+/// assert_eq!(index.get("hello").unwrap().get("Version"), Some("2.10"));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn load_or_parse<T, E>(
+    path: &Path,
+    cache_path: &Path,
+    options: &ParseOptions,
+    parse: impl FnOnce(&[u8], &ParseOptions) -> Result<T, E>,
+) -> Result<T, E>
+where
+    T: Serialize + DeserializeOwned,
+    E: From<std::io::Error>,
+{
+    // The checksum is computed over the file as stored, so that a valid cache can be used
+    // without decompressing the file.
+    let source = std::fs::read(path)?;
+    let checksum = checksum(&source, options);
+    if let Some(data) = load(cache_path, &checksum)? {
+        return Ok(data);
+    }
+    let mut bytes = Vec::new();
+    crate::compression::decompress(&source[..])?.read_to_end(&mut bytes)?;
+    let data = parse(&bytes, options)?;
+    save(cache_path, &checksum, &data)?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Deb822Index;
+    use crate::lossy::Deb822;
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("deb822-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache");
+
+        let source = "Package: hello\nDescription: foo\n bar\n\nPackage: world\n";
+        let doc: Deb822 = source.parse().unwrap();
+        let options = ParseOptions::default();
+        let sum = checksum(source.as_bytes(), &options);
+
+        assert_eq!(load::<Deb822>(&path, &sum).unwrap(), None);
+        save(&path, &sum, &doc).unwrap();
+        assert_eq!(load::<Deb822>(&path, &sum).unwrap(), Some(doc));
+
+        // A different source file invalidates the cache
+        assert_eq!(
+            load::<Deb822>(&path, &checksum(b"Package: other\n", &options)).unwrap(),
+            None
+        );
+
+        // So do different parse options
+        let lenient = checksum(source.as_bytes(), &ParseOptions::lenient());
+        assert_ne!(lenient, sum);
+        assert_eq!(load::<Deb822>(&path, &lenient).unwrap(), None);
+
+        // A cache of a different type is not used
+        assert_eq!(
+            load::<Vec<crate::lossy::Paragraph>>(&path, &sum).unwrap(),
+            None
+        );
+
+        // Neither is a corrupted cache
+        std::fs::write(&path, b"garbage").unwrap();
+        assert_eq!(load::<Deb822>(&path, &sum).unwrap(), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_or_parse() {
+        let dir = std::env::temp_dir().join(format!("deb822-cache-index-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("Packages");
+        let cache = dir.join("Packages.cache");
+        std::fs::write(&source, "Package: a\nVersion: 1\n\nPackage: b\n").unwrap();
+
+        let mut calls = 0;
+        let mut load_index = |options: &ParseOptions| {
+            load_or_parse(&source, &cache, options, |bytes, options| {
+                calls += 1;
+                Ok::<_, crate::lossy::Error>(Deb822Index::<crate::lossy::Paragraph>::new(
+                    "Package",
+                    Deb822::from_bytes_with(bytes, options)?,
+                ))
+            })
+        };
+        let default = ParseOptions::default();
+        let first = load_index(&default).unwrap();
+        let second = load_index(&default).unwrap();
+        assert_eq!(second.get("a").unwrap().get("Version"), Some("1"));
+        assert!(second.contains_key("b"));
+        assert_eq!(
+            first.into_inner().into_iter().collect::<Deb822>(),
+            second.into_inner().into_iter().collect::<Deb822>()
+        );
+
+        // Parsing with different options invalidates the cache
+        assert!(load_index(&ParseOptions::strict())
+            .unwrap()
+            .contains_key("a"));
+
+        // So does changing the source file
+        std::fs::write(&source, "Package: c\n").unwrap();
+        assert!(load_index(&default).unwrap().contains_key("c"));
+
+        // Errors from parsing are passed through, and I/O errors are converted
+        std::fs::write(&source, "broken\n").unwrap();
+        assert!(load_index(&default).is_err());
+        assert_eq!(calls, 4);
+        assert!(matches!(
+            load_or_parse(&dir.join("missing"), &cache, &default, |bytes, _| Ok::<
+                _,
+                crate::lossy::Error,
+            >(
+                bytes.to_vec()
+            )),
+            Err(crate::lossy::Error::Io(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_load_or_parse_compressed() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("deb822-cache-gz-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("Packages.gz");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"Package: hello\n").unwrap();
+        std::fs::write(&source, encoder.finish().unwrap()).unwrap();
+
+        let options = ParseOptions::default();
+        let parse = |bytes: &[u8], options: &ParseOptions| Deb822::from_bytes_with(bytes, options);
+        let doc = load_or_parse(&source, &dir.join("cache"), &options, parse).unwrap();
+        assert_eq!(doc.iter().next().unwrap().get("Package"), Some("hello"));
+        let cached = load_or_parse(&source, &dir.join("cache"), &options, parse).unwrap();
+        assert_eq!(cached, doc);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -10,6 +10,18 @@ pub(crate) fn is_newline(c: char) -> bool {
     c == '\n' || c == '\r'
 }
 
+/// Return the length of the line ending at the start of `s`, or 0 if there is none.
+///
+/// A CRLF pair counts as a single line ending.
+#[inline]
+pub(crate) fn newline_len(s: &[u8]) -> usize {
+    match s {
+        [b'\r', b'\n', ..] => 2,
+        [b'\n' | b'\r', ..] => 1,
+        _ => 0,
+    }
+}
+
 #[inline]
 pub(crate) fn is_valid_key_char(c: char) -> bool {
     // deb822(5) says valid field characters are US-ASCII
@@ -23,3 +35,100 @@ pub(crate) fn is_valid_key_char(c: char) -> bool {
 pub(crate) fn is_valid_initial_key_char(c: char) -> bool {
     c != '-' && is_valid_key_char(c)
 }
+
+/// Atomically replace the file at `path` with `contents`.
+///
+/// The contents are written to a new, uniquely named temporary file next to `path`, which is
+/// synced to disk and then renamed over it, so readers never see a partially written file.
+/// The permissions of an existing file are kept.
+///
+/// # Example
+/// ```rust
+/// let path = std::env::temp_dir().join(format!("deb822-write-atomic-{}", std::process::id()));
+/// deb822_lossless::write_atomic(&path, b"Package: foo\n").unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "Package: foo\n");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub fn write_atomic(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let (tmp_path, mut f) = create_temporary(path)?;
+    let result = (|| {
+        f.write_all(contents)?;
+        match std::fs::metadata(path) {
+            Ok(metadata) => f.set_permissions(metadata.permissions())?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        f.sync_all()?;
+        drop(f);
+        std::fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Create a new temporary file in the same directory as `path`.
+///
+/// The name includes the process id and a counter, and the file is created exclusively, so
+/// concurrent writers never share a temporary file.
+#[cfg(feature = "std")]
+fn create_temporary(
+    path: &std::path::Path,
+) -> std::io::Result<(std::path::PathBuf, std::fs::File)> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("not a file path: {}", path.display()),
+        )
+    })?;
+    loop {
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let tmp_path = path.with_file_name(tmp_name);
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(f) => return Ok((tmp_path, f)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir =
+            std::env::temp_dir().join(format!("deb822-write-atomic-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("control");
+        write_atomic(&path, b"Source: foo\n").unwrap();
+        write_atomic(&path, b"Source: bar\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "Source: bar\n");
+        // No temporary files are left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let (tmp1, _) = create_temporary(&path).unwrap();
+        let (tmp2, _) = create_temporary(&path).unwrap();
+        assert_ne!(tmp1, tmp2);
+        assert_eq!(tmp1.parent(), path.parent());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+//! Transparent decompression of compressed deb822 files.
+//!
+//! Apt repositories usually ship their indexes compressed, e.g. as `Packages.gz`,
+//! `Packages.xz` or `Sources.zst`. [`decompress`] detects the compression format from the
+//! magic bytes at the start of the input and wraps the reader in the matching decoder, so that
+//! the result can be handed directly to a parser.
+//!
+//! Support for each compression format is enabled by a feature of the same name: `gzip`, `xz`,
+//! `bzip2` and `zstd`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::compression::decompress;
+//! use deb822_lossless::lossy::Deb822;
+//!
+//! let input: &[u8] = b"Package: hello\nVersion: 2.10\n";
+//! let deb822 = Deb822::from_reader(decompress(input).unwrap()).unwrap();
+//! assert_eq!(deb822.len(), 1);
+//! ```
+use std::io::{BufRead, Read};
+
+/// A compression format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip compression (`.gz`)
+    Gzip,
+
+    /// xz compression (`.xz`)
+    Xz,
+
+    /// bzip2 compression (`.bz2`)
+    Bzip2,
+
+    /// Zstandard compression (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression format from the first bytes of a file.
+    ///
+    /// Returns `None` if the data does not appear to be compressed.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if data.starts_with(&[0xfd, b'7', b'z', b'X', b'Z', 0x00]) {
+            Some(Compression::Xz)
+        } else if data.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Detect the compression format from a file extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "gz" => Some(Compression::Gzip),
+            "xz" => Some(Compression::Xz),
+            "bz2" => Some(Compression::Bzip2),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The file extension usually used for this compression format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Xz => "xz",
+            Compression::Bzip2 => "bz2",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// Whether support for this compression format was enabled at build time.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Compression::Gzip => cfg!(feature = "gzip"),
+            Compression::Xz => cfg!(feature = "xz"),
+            Compression::Bzip2 => cfg!(feature = "bzip2"),
+            Compression::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// Wrap a reader in a decoder for this compression format.
+    pub fn decoder<'a, R: BufRead + 'a>(&self, reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Ok(Box::new(flate2::bufread::MultiGzDecoder::new(reader))),
+            #[cfg(feature = "xz")]
+            Compression::Xz => Ok(Box::new(xz2::bufread::XzDecoder::new_multi_decoder(reader))),
+            #[cfg(feature = "bzip2")]
+            Compression::Bzip2 => Ok(Box::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = reader;
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("support for {} compression is not enabled", self),
+                ))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Compression::Gzip => "gzip",
+            Compression::Xz => "xz",
+            Compression::Bzip2 => "bzip2",
+            Compression::Zstd => "zstd",
+        })
+    }
+}
+
+/// Wrap a reader so that compressed input is transparently decompressed.
+///
+/// The compression format is detected from the magic bytes at the start of the input;
+/// uncompressed input is passed through unchanged. An error of kind
+/// [`std::io::ErrorKind::Unsupported`] is returned if the input is compressed with a format
+/// whose feature is not enabled.
+pub fn decompress<'a, R: BufRead + 'a>(mut reader: R) -> std::io::Result<Box<dyn Read + 'a>> {
+    let compression = Compression::detect(reader.fill_buf()?);
+    match compression {
+        Some(compression) => compression.decoder(reader),
+        None => Ok(Box::new(reader)),
+    }
+}
+
+/// Open a file, transparently decompressing it if it is compressed.
+///
+/// This is a convenience wrapper around [`decompress`].
+pub fn open<'a>(path: impl AsRef<std::path::Path>) -> std::io::Result<Box<dyn Read + 'a>> {
+    decompress(std::io::BufReader::new(std::fs::File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Compression::detect(b"Package: foo\n"), None);
+        assert_eq!(Compression::detect(b""), None);
+        assert_eq!(
+            Compression::detect(&[0x1f, 0x8b, 0x08, 0x00]),
+            Some(Compression::Gzip)
+        );
+        assert_eq!(
+            Compression::detect(b"\xfd7zXZ\x00\x00"),
+            Some(Compression::Xz)
+        );
+        assert_eq!(Compression::detect(b"BZh91AY"), Some(Compression::Bzip2));
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]),
+            Some(Compression::Zstd)
+        );
+    }
+
+    #[test]
+    fn test_extension() {
+        for c in [
+            Compression::Gzip,
+            Compression::Xz,
+            Compression::Bzip2,
+            Compression::Zstd,
+        ] {
+            assert_eq!(Compression::from_extension(c.extension()), Some(c));
+        }
+        assert_eq!(Compression::from_extension("txt"), None);
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let mut buf = String::new();
+        decompress(&b"Package: foo\n"[..])
+            .unwrap()
+            .read_to_string(&mut buf)
+            .unwrap();
+        assert_eq!(buf, "Package: foo\n");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(b"Package: foo\n\nPackage: bar\n")
+            .unwrap();
+        let data = encoder.finish().unwrap();
+        let deb822 = crate::lossy::Deb822::from_reader(decompress(&data[..]).unwrap()).unwrap();
+        assert_eq!(deb822.len(), 2);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_open() {
+        use std::io::Write;
+        let dir = std::env::temp_dir().join(format!("deb822-open-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Packages.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(b"Package: foo\n").unwrap();
+        encoder.finish().unwrap();
+        let deb822 = crate::lossy::Deb822::from_file(&path).unwrap();
+        assert_eq!(deb822.len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn test_xz() {
+        use std::io::Write;
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(b"Package: foo\n").unwrap();
+        let data = encoder.finish().unwrap();
+        let deb822 = crate::lossy::Deb822::from_reader(decompress(&data[..]).unwrap()).unwrap();
+        assert_eq!(deb822.len(), 1);
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn test_bzip2() {
+        use std::io::Write;
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(b"Package: foo\n").unwrap();
+        let data = encoder.finish().unwrap();
+        let deb822 = crate::lossy::Deb822::from_reader(decompress(&data[..]).unwrap()).unwrap();
+        assert_eq!(deb822.len(), 1);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd() {
+        let data = zstd::encode_all(&b"Package: foo\n"[..], 0).unwrap();
+        let deb822 = crate::lossy::Deb822::from_reader(decompress(&data[..]).unwrap()).unwrap();
+        assert_eq!(deb822.len(), 1);
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_unsupported() {
+        let err = decompress(&[0x1f, 0x8b, 0x08, 0x00][..]).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    }
+}
@@ -1,4 +1,6 @@
 //! Conversion between Deb822-like paragraphs and Rust objects.
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 /// Abstract trait for accessing and modifying key-value pairs in a paragraph.
 pub trait Deb822LikeParagraph: FromIterator<(String, String)> {
@@ -26,6 +28,7 @@ impl Deb822LikeParagraph for crate::lossy::Paragraph {
     }
 }
 
+#[cfg(feature = "std")]
 impl Deb822LikeParagraph for crate::lossless::Paragraph {
     fn get(&self, key: &str) -> Option<String> {
         crate::lossless::Paragraph::get(self, key).map(|v| v.to_string())
@@ -43,11 +46,46 @@ impl Deb822LikeParagraph for crate::lossless::Paragraph {
 /// Convert a paragraph to this object.
 pub trait FromDeb822Paragraph<P: Deb822LikeParagraph> {
     /// Convert a paragraph to this object.
-    fn from_paragraph(paragraph: &P) -> Result<Self, String>
+    fn from_paragraph(paragraph: &P) -> Result<Self, FromParagraphError>
     where
         Self: Sized;
 }
 
+/// Error converting a paragraph with [`FromDeb822Paragraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FromParagraphError {
+    /// A required field is missing.
+    MissingField(String),
+
+    /// The value of a field could not be parsed.
+    InvalidField(FieldError),
+}
+
+impl From<FieldError> for FromParagraphError {
+    fn from(e: FieldError) -> Self {
+        Self::InvalidField(e)
+    }
+}
+
+impl core::fmt::Display for FromParagraphError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::MissingField(name) => write!(f, "missing field: {}", name),
+            Self::InvalidField(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromParagraphError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::MissingField(_) => None,
+            Self::InvalidField(e) => Some(e),
+        }
+    }
+}
+
 /// Convert this object to a paragraph.
 pub trait ToDeb822Paragraph<P: Deb822LikeParagraph> {
     /// Convert this object to a paragraph.
@@ -57,6 +95,244 @@ pub trait ToDeb822Paragraph<P: Deb822LikeParagraph> {
     fn update_paragraph(&self, paragraph: &mut P);
 }
 
+/// A field whose value could not be parsed into a typed value.
+///
+/// # Example
+/// ```rust
+/// use deb822_lossless::convert::FieldError;
+/// let err = FieldError::parse::<u32>("Installed-Size", "large").unwrap_err();
+/// assert_eq!(err.field, "Installed-Size");
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid Installed-Size field \"large\": invalid digit found in string"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldError {
+    /// The name of the field.
+    pub field: String,
+
+    /// The value of the field.
+    pub value: String,
+
+    /// Why the value could not be parsed.
+    pub message: String,
+
+    /// The byte range of the field in the input, if known.
+    pub span: Option<core::ops::Range<usize>>,
+}
+
+impl FieldError {
+    /// Create a new error for the given field and value.
+    pub fn new(field: &str, value: &str, message: impl core::fmt::Display) -> Self {
+        Self {
+            field: field.to_string(),
+            value: value.to_string(),
+            message: message.to_string(),
+            span: None,
+        }
+    }
+
+    /// Set the byte range of the field in the input.
+    pub fn with_span(mut self, span: core::ops::Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Parse the value of a field, reporting failures as a [`FieldError`].
+    pub fn parse<T: core::str::FromStr>(field: &str, value: &str) -> Result<T, Self>
+    where
+        T::Err: core::fmt::Display,
+    {
+        value.parse().map_err(|e| Self::new(field, value, e))
+    }
+}
+
+impl core::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid {} field {:?}: {}",
+            self.field, self.value, self.message
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldError {}
+
+/// Split a field value holding a list of items.
+///
+/// Items are separated by whitespace if `separator` is `None`. Surrounding whitespace is
+/// stripped from each item and empty items are skipped.
+///
+/// # Example
+/// ```rust
+/// use deb822_lossless::convert::split_list;
+/// assert_eq!(split_list("a b\n c", None).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+/// assert_eq!(split_list("a, b c,", Some(",")).collect::<Vec<_>>(), vec!["a", "b c"]);
+/// ```
+pub fn split_list<'a>(
+    value: &'a str,
+    separator: Option<&'a str>,
+) -> impl Iterator<Item = &'a str> + 'a {
+    let items: alloc::boxed::Box<dyn Iterator<Item = &'a str>> = match separator {
+        Some(separator) => alloc::boxed::Box::new(value.split(separator)),
+        None => alloc::boxed::Box::new(value.split_whitespace()),
+    };
+    items.map(str::trim).filter(|item| !item.is_empty())
+}
+
+/// Join a list of items into a field value; the inverse of [`split_list`].
+///
+/// Items are separated by a single space if `separator` is `None`. Other separators are
+/// followed by a space, unless they are whitespace themselves.
+///
+/// # Example
+/// ```rust
+/// use deb822_lossless::convert::join_list;
+/// let items = ["a", "b"].iter().map(|s| s.to_string());
+/// assert_eq!(join_list(items, Some(",")), "a, b");
+/// ```
+pub fn join_list(items: impl Iterator<Item = String>, separator: Option<&str>) -> String {
+    let separator = match separator {
+        None => " ".to_string(),
+        Some(separator) if separator.trim().is_empty() => separator.to_string(),
+        Some(separator) => separator.to_string() + " ",
+    };
+    items.collect::<Vec<_>>().join(&separator)
+}
+
+/// The separator between the items of a list field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Separator {
+    /// Items are separated by whitespace, as in `Architecture`.
+    Whitespace,
+
+    /// Items are separated by commas, as in `Binary` or `Uploaders`.
+    Comma,
+}
+
+impl Separator {
+    /// The separator as accepted by [`split_list`] and [`join_list`].
+    pub(crate) fn as_pattern(&self) -> Option<&'static str> {
+        match self {
+            Separator::Whitespace => None,
+            Separator::Comma => Some(","),
+        }
+    }
+}
+
+/// The maximum length of a line before [`fold_list`] folds a list.
+pub const MAX_LINE_LENGTH: usize = 79;
+
+/// Join a list of items into the value of the field `name`, folding it if necessary.
+///
+/// The list is written on the same line as the field name if it fits in
+/// [`MAX_LINE_LENGTH`] columns, and with one item per continuation line otherwise.
+///
+/// # Example
+/// ```rust
+/// use deb822_lossless::convert::{fold_list, Separator};
+/// assert_eq!(fold_list("Binary", ["foo", "libfoo1"], Separator::Comma), "foo, libfoo1");
+/// let long = ["amd64", "arm64", "armel", "armhf", "i386", "mips64el", "ppc64el", "riscv64"]
+///     .map(|arch| format!("{}-linux", arch));
+/// assert_eq!(
+///     fold_list("Architecture", &long, Separator::Whitespace),
+///     format!("\n{}", long.join("\n"))
+/// );
+/// ```
+pub fn fold_list<S: AsRef<str>>(
+    name: &str,
+    items: impl IntoIterator<Item = S>,
+    separator: Separator,
+) -> String {
+    let items = items
+        .into_iter()
+        .map(|item| item.as_ref().trim().to_string())
+        .collect::<Vec<_>>();
+    let value = join_list(items.iter().cloned(), separator.as_pattern());
+    if name.len() + 2 + value.len() <= MAX_LINE_LENGTH {
+        return value;
+    }
+    let separator = match separator {
+        Separator::Whitespace => "\n",
+        Separator::Comma => ",\n",
+    };
+    alloc::format!("\n{}", items.join(separator))
+}
+
+/// Join a comma-separated list into the value of the field `name`, following the layout of
+/// `existing`, the current value of the field.
+///
+/// If the items of `existing` are written one per line, so are the new items; an empty first
+/// line and a trailing comma are kept. Otherwise the list is written as by [`fold_list`].
+/// Commas inside double quotes, as in the names in `Uploaders`, don't separate items.
+///
+/// # Example
+/// ```rust
+/// use deb822_lossless::convert::fold_comma_list_like;
+/// assert_eq!(
+///     fold_comma_list_like("Uploaders", ["a", "b", "c"], Some("\nx,\ny,")),
+///     "\na,\nb,\nc,"
+/// );
+/// assert_eq!(fold_comma_list_like("Uploaders", ["a", "b"], Some("x, y")), "a, b");
+/// assert_eq!(
+///     fold_comma_list_like("Uploaders", ["a", "b"], Some("\"Doe, J\" <j@example.com>,\ny")),
+///     "a,\nb"
+/// );
+/// ```
+pub fn fold_comma_list_like<S: AsRef<str>>(
+    name: &str,
+    items: impl IntoIterator<Item = S>,
+    existing: Option<&str>,
+) -> String {
+    let items = items
+        .into_iter()
+        .map(|item| item.as_ref().trim().to_string())
+        .collect::<Vec<_>>();
+    let Some(existing) = existing else {
+        return fold_list(name, items, Separator::Comma);
+    };
+    let lines = existing.lines().collect::<Vec<_>>();
+    let leading_newline = lines.first().is_some_and(|line| line.trim().is_empty());
+    let trailing_comma = existing.trim_end().ends_with(',');
+    let item_lines = &lines[leading_newline as usize..];
+    let one_per_line = (leading_newline || item_lines.len() > 1)
+        && item_lines
+            .iter()
+            .all(|line| split_list(&strip_quoted(line), Some(",")).count() <= 1);
+    if !one_per_line || items.is_empty() {
+        return fold_list(name, items, Separator::Comma);
+    }
+    let mut value = String::new();
+    if leading_newline {
+        value.push('\n');
+    }
+    value.push_str(&items.join(",\n"));
+    if trailing_comma {
+        value.push(',');
+    }
+    value
+}
+
+/// Remove the parts of `line` that are enclosed in double quotes.
+fn strip_quoted(line: &str) -> String {
+    let mut quoted = false;
+    let mut escaped = false;
+    line.chars()
+        .filter(|c| {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                _ => return !quoted,
+            }
+            false
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "derive")]
@@ -193,5 +469,107 @@ mod tests {
             assert_eq!(para.get("baz"), Some("blah"));
             assert_eq!(para.to_string(), "baz: blah\n");
         }
+
+        #[test]
+        fn test_list() {
+            #[derive(FromDeb822, ToDeb822, Debug, PartialEq)]
+            struct Foo {
+                words: Vec<String>,
+                #[deb822(separator = ",")]
+                numbers: Vec<u32>,
+                #[deb822(separator = "\n")]
+                lines: Option<Vec<String>>,
+            }
+
+            let para: crate::lossy::Paragraph =
+                "words: a b\n c\nnumbers: 1, 2,3\nlines:\n x y\n z\n"
+                    .parse()
+                    .unwrap();
+            let foo = Foo::from_paragraph(&para).unwrap();
+            assert_eq!(
+                foo,
+                Foo {
+                    words: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                    numbers: vec![1, 2, 3],
+                    lines: Some(vec!["x y".to_string(), "z".to_string()]),
+                }
+            );
+
+            let para: crate::lossy::Paragraph = foo.to_paragraph();
+            assert_eq!(
+                para.to_string(),
+                "words: a b c\nnumbers: 1, 2, 3\nlines: x y\n z\n"
+            );
+
+            let para: crate::lossy::Paragraph = "words: a\nnumbers: 1, x\n".parse().unwrap();
+            let err = Foo::from_paragraph(&para).unwrap_err();
+            assert_eq!(
+                err,
+                FromParagraphError::InvalidField(FieldError::new(
+                    "numbers",
+                    "x",
+                    "invalid digit found in string"
+                ))
+            );
+            assert_eq!(
+                err.to_string(),
+                "invalid numbers field \"x\": invalid digit found in string"
+            );
+            let para: crate::lossy::Paragraph = "numbers: 1\n".parse().unwrap();
+            assert_eq!(
+                Foo::from_paragraph(&para).unwrap_err().to_string(),
+                "missing field: words"
+            );
+        }
+
+        #[test]
+        fn test_rename_all() {
+            #[derive(FromDeb822, ToDeb822, Debug, PartialEq)]
+            #[deb822(rename_all = "Train-Case")]
+            struct Foo {
+                #[deb822(field = "Source")]
+                name: String,
+                build_depends: Option<String>,
+                vcs_git: Option<String>,
+            }
+
+            let para: crate::lossy::Paragraph =
+                "Source: foo\nBuild-Depends: bar\n".parse().unwrap();
+            let foo = Foo::from_paragraph(&para).unwrap();
+            assert_eq!(
+                foo,
+                Foo {
+                    name: "foo".to_string(),
+                    build_depends: Some("bar".to_string()),
+                    vcs_git: None,
+                }
+            );
+            let para: crate::lossy::Paragraph = foo.to_paragraph();
+            assert_eq!(para.to_string(), "Source: foo\nBuild-Depends: bar\n");
+
+            #[derive(ToDeb822)]
+            #[deb822(rename_all = "kebab-case")]
+            struct Bar {
+                build_depends: String,
+            }
+            let para: crate::lossy::Paragraph = Bar {
+                build_depends: "foo".to_string(),
+            }
+            .to_paragraph();
+            assert_eq!(para.to_string(), "build-depends: foo\n");
+        }
+    }
+
+    #[test]
+    fn test_split_list() {
+        assert_eq!(
+            super::split_list(" a\n b ", Some("\n")).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(super::split_list("", None).count(), 0);
+        assert_eq!(
+            super::join_list(["a", "b"].iter().map(|s| s.to_string()), Some("\n")),
+            "a\nb"
+        );
     }
 }
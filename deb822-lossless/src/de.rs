@@ -0,0 +1,543 @@
+//! Deserialize Rust data structures from deb822 text using serde.
+//!
+//! A paragraph deserializes as a map from field names to values, so structs can be used to
+//! describe the fields of interest. A document deserializes as a sequence of paragraphs, or
+//! as a single map if it contains exactly one paragraph.
+//!
+//! Field values are interpreted as follows:
+//!
+//! * Booleans are read from `yes`/`no` (as used by e.g. `Essential`) or `true`/`false`.
+//! * Numbers and characters are parsed from their textual representation.
+//! * Sequences are split into lines if the value spans multiple lines, on commas if it
+//!   contains any, and on whitespace otherwise. Each item is interpreted as a value itself,
+//!   so the lines of a `Checksums-Sha256` field can be deserialized as tuples.
+//! * Unit enum variants are matched against the value.
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Package {
+//!     #[serde(rename = "Package")]
+//!     name: String,
+//!     #[serde(rename = "Version")]
+//!     version: String,
+//!     #[serde(rename = "Depends", default)]
+//!     depends: Vec<String>,
+//!     #[serde(rename = "Essential", default)]
+//!     essential: bool,
+//! }
+//!
+//! let text = "Package: hello\nVersion: 2.10\nDepends: libc6, dpkg\n\nPackage: base-files\nVersion: 13\nEssential: yes\n";
+//! let packages: Vec<Package> = deb822_lossless::from_str(text).unwrap();
+//! assert_eq!(packages[0].depends, vec!["libc6", "dpkg"]);
+//! assert!(!packages[0].essential);
+//! assert!(packages[1].essential);
+//!
+//! let hello: Package = deb822_lossless::from_str("Package: hello\nVersion: 2.10\n").unwrap();
+//! assert_eq!(hello.name, "hello");
+//! ```
+use crate::lossy::{Deb822, Paragraph};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Error type for deserialization.
+#[derive(Debug)]
+pub enum Error {
+    /// The text could not be parsed.
+    Parse(crate::lossy::Error),
+
+    /// A single paragraph was expected, but the document contained a different number.
+    ExpectedParagraph(usize),
+
+    /// A custom error reported by the type being deserialized.
+    Message(String),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::ExpectedParagraph(n) => write!(f, "Expected a single paragraph, found {}", n),
+            Self::Message(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: core::fmt::Display>(msg: T) -> Self {
+        Self::Message(msg.to_string())
+    }
+}
+
+impl From<crate::lossy::Error> for Error {
+    fn from(e: crate::lossy::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+/// Deserialize an instance of `T` from deb822 text.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, Error> {
+    let deb822: Deb822 = s.parse()?;
+    from_deb822(&deb822)
+}
+
+/// Deserialize an instance of `T` from a parsed document.
+pub fn from_deb822<'de, T: de::Deserialize<'de>>(deb822: &'de Deb822) -> Result<T, Error> {
+    T::deserialize(Deserializer::from_deb822(deb822))
+}
+
+/// Deserialize an instance of `T` from a single paragraph.
+pub fn from_paragraph<'de, T: de::Deserialize<'de>>(paragraph: &'de Paragraph) -> Result<T, Error> {
+    T::deserialize(Deserializer::from_paragraph(paragraph))
+}
+
+/// A deserializer for deb822 documents.
+#[derive(Debug, Clone, Copy)]
+pub struct Deserializer<'de> {
+    paragraphs: &'de [Paragraph],
+}
+
+impl<'de> Deserializer<'de> {
+    /// Create a deserializer for a parsed document.
+    pub fn from_deb822(deb822: &'de Deb822) -> Self {
+        Self {
+            paragraphs: &deb822.0,
+        }
+    }
+
+    /// Create a deserializer for a single paragraph.
+    pub fn from_paragraph(paragraph: &'de Paragraph) -> Self {
+        Self {
+            paragraphs: core::slice::from_ref(paragraph),
+        }
+    }
+
+    fn paragraph(&self) -> Result<ParagraphDeserializer<'de>, Error> {
+        match self.paragraphs {
+            [paragraph] => Ok(ParagraphDeserializer(paragraph)),
+            paragraphs => Err(Error::ExpectedParagraph(paragraphs.len())),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.paragraphs.iter().map(ParagraphDeserializer),
+        ))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.paragraph()?.deserialize_map(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.paragraph()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.paragraphs.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct enum identifier ignored_any
+    }
+}
+
+/// Deserializes a paragraph as a map from field names to values.
+struct ParagraphDeserializer<'de>(&'de Paragraph);
+
+impl<'de> IntoDeserializer<'de, Error> for ParagraphDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ParagraphDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(FieldsAccess {
+            fields: self.0.fields.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+struct FieldsAccess<'de> {
+    fields: core::slice::Iter<'de, crate::lossy::Field>,
+    value: Option<&'de str>,
+}
+
+impl<'de> de::MapAccess<'de> for FieldsAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.fields.next() {
+            Some(field) => {
+                self.value = Some(&field.value);
+                seed.deserialize(BorrowedStrDeserializer::new(&field.name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| <Error as de::Error>::custom("value requested before key"))?;
+        seed.deserialize(ValueDeserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+/// Split a field value into the items of a list.
+///
+/// Comma-separated lists may be wrapped over several lines; lists without commas
+/// have one item per line, or are separated by whitespace when on a single line.
+fn items(value: &str) -> Vec<&str> {
+    if value.contains(',') {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|i| !i.is_empty())
+            .collect()
+    } else if value.contains('\n') {
+        value
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect()
+    } else {
+        value.split_whitespace().collect()
+    }
+}
+
+/// Deserializes a single field value.
+struct ValueDeserializer<'de>(&'de str);
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl ValueDeserializer<'_> {
+    fn parse<T: core::str::FromStr>(&self, expected: &str) -> Result<T, Error> {
+        self.0.trim().parse().map_err(|_| {
+            <Error as de::Error>::invalid_value(de::Unexpected::Str(self.0), &expected)
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident: $expected:literal,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.parse($expected)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.trim() {
+            "yes" | "true" => visitor.visit_bool(true),
+            "no" | "false" => visitor.visit_bool(false),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Str(self.0),
+                &"yes or no",
+            )),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8: "an integer",
+        deserialize_i16 => visit_i16: "an integer",
+        deserialize_i32 => visit_i32: "an integer",
+        deserialize_i64 => visit_i64: "an integer",
+        deserialize_i128 => visit_i128: "an integer",
+        deserialize_u8 => visit_u8: "an integer",
+        deserialize_u16 => visit_u16: "an integer",
+        deserialize_u32 => visit_u32: "an integer",
+        deserialize_u64 => visit_u64: "an integer",
+        deserialize_u128 => visit_u128: "an integer",
+        deserialize_f32 => visit_f32: "a number",
+        deserialize_f64 => visit_f64: "a number",
+        deserialize_char => visit_char: "a character",
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            items(self.0).into_iter().map(ValueDeserializer),
+        ))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.0.trim()))
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Priority {
+        Required,
+        Optional,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Package {
+        #[serde(rename = "Package")]
+        name: String,
+        #[serde(rename = "Installed-Size")]
+        installed_size: Option<u64>,
+        #[serde(rename = "Priority")]
+        priority: Option<Priority>,
+        #[serde(rename = "Architecture", default)]
+        architectures: Vec<String>,
+        #[serde(rename = "Checksums-Sha256", default)]
+        checksums: Vec<(String, u64, String)>,
+        #[serde(rename = "Description")]
+        description: Option<String>,
+    }
+
+    #[test]
+    fn test_struct() {
+        let text = "Package: hello
+Installed-Size: 280
+Priority: optional
+Architecture: amd64 arm64
+Checksums-Sha256:
+ abcd 12 hello.dsc
+ ef01 34 hello.tar.xz
+Description: A program that says hello
+ Some more text
+X-Unknown: ignored
+";
+        let package: Package = from_str(text).unwrap();
+        assert_eq!(
+            package,
+            Package {
+                name: "hello".to_string(),
+                installed_size: Some(280),
+                priority: Some(Priority::Optional),
+                architectures: vec!["amd64".to_string(), "arm64".to_string()],
+                checksums: vec![
+                    ("abcd".to_string(), 12, "hello.dsc".to_string()),
+                    ("ef01".to_string(), 34, "hello.tar.xz".to_string()),
+                ],
+                description: Some("A program that says hello\nSome more text".to_string()),
+            }
+        );
+
+        let package: Package = from_str("Package: base-files\nPriority: required\n").unwrap();
+        assert_eq!(package.priority, Some(Priority::Required));
+        assert_eq!(package.installed_size, None);
+        assert!(package.architectures.is_empty());
+    }
+
+    #[test]
+    fn test_wrapped_list() {
+        #[derive(Debug, Deserialize)]
+        struct Control {
+            #[serde(rename = "Depends")]
+            depends: Vec<String>,
+        }
+        let control: Control = from_str("Depends: libc6 (>= 2.34),\n libssl3,\n zlib1g\n").unwrap();
+        assert_eq!(
+            control.depends,
+            vec!["libc6 (>= 2.34)", "libssl3", "zlib1g"]
+        );
+
+        let control: Control = from_str("Depends: a,\n b\n").unwrap();
+        assert_eq!(control.depends, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_document() {
+        let text = "Package: a\n\nPackage: b\n";
+        let packages: Vec<std::collections::BTreeMap<String, String>> = from_str(text).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[1]["Package"], "b");
+
+        assert!(matches!(
+            from_str::<Package>(text),
+            Err(Error::ExpectedParagraph(2))
+        ));
+
+        let deb822: Deb822 = text.parse().unwrap();
+        // Values can be borrowed from a parsed document
+        let borrowed: Vec<std::collections::BTreeMap<&str, &str>> = from_deb822(&deb822).unwrap();
+        assert_eq!(borrowed[0]["Package"], "a");
+
+        let paragraph = deb822.iter().next().unwrap();
+        let package: Package = from_paragraph(paragraph).unwrap();
+        assert_eq!(package.name, "a");
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = from_str::<Package>("Package: a\nInstalled-Size: big\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value: string \"big\", expected an integer"
+        );
+        let err = from_str::<Package>("Version: 1\n").unwrap_err();
+        assert_eq!(err.to_string(), "missing field `Package`");
+        assert!(matches!(
+            from_str::<Package>("Package a\n"),
+            Err(Error::Parse(_))
+        ));
+
+        #[derive(Deserialize)]
+        struct Flags {
+            #[serde(rename = "Essential")]
+            essential: bool,
+        }
+        assert!(from_str::<Flags>("Essential: yes\n").unwrap().essential);
+        assert!(!from_str::<Flags>("Essential: no\n").unwrap().essential);
+        assert!(from_str::<Flags>("Essential: maybe\n").is_err());
+    }
+}
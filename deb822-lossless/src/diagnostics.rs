@@ -0,0 +1,181 @@
+//! Rich error reports with [`miette`].
+//!
+//! The errors that refer to a location in the input implement [`miette::Diagnostic`], with a
+//! label pointing at the offending text, so that command-line tools can show where a file is
+//! malformed. Since the errors don't hold on to the input, attach it as the source code of the
+//! report before rendering it.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::lossy::Deb822;
+//! use deb822_lossless::ParseOptions;
+//!
+//! let text = "Package: foo\n# Version: 1.0\n";
+//! let (_, diagnostics) = Deb822::from_str_recovering(text, &ParseOptions::archive());
+//! for diagnostic in diagnostics {
+//!     let report = miette::Report::new(diagnostic).with_source_code(text);
+//!     eprintln!("{:?}", report);
+//! }
+//! ```
+use crate::convert::{FieldError, FromParagraphError};
+use crate::lossless::{RoundtripError, SyntaxError};
+use crate::lossy::{Error, ParseDiagnostic};
+use miette::{Diagnostic, LabeledSpan};
+use std::fmt::Display;
+
+impl Diagnostic for ParseDiagnostic {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(match self.error {
+            Error::UnexpectedToken(..) => "deb822::unexpected_token",
+            Error::UnexpectedEof => "deb822::unexpected_eof",
+            Error::ExpectedEof => "deb822::expected_eof",
+            Error::DuplicateField(_) => "deb822::duplicate_field",
+            Error::InvalidUtf8(_) => "deb822::invalid_utf8",
+            Error::InputTooLarge => "deb822::input_too_large",
+            Error::ParagraphTooLarge => "deb822::paragraph_too_large",
+            Error::FieldTooLarge(_) => "deb822::field_too_large",
+            Error::Io(_) => "deb822::io",
+        }))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            self.span.range(),
+            self.error.to_string(),
+        ))))
+    }
+}
+
+impl Diagnostic for SyntaxError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("deb822::syntax_error"))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            self.span.range(),
+            self.message.clone(),
+        ))))
+    }
+}
+
+impl Diagnostic for RoundtripError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("deb822::roundtrip"))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            self.offset..self.offset + self.expected.len(),
+            format!("the tree has {:?} here", self.found),
+        ))))
+    }
+}
+
+impl Diagnostic for FieldError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("deb822::invalid_field"))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let span = self.span.clone()?;
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            span,
+            self.message.clone(),
+        ))))
+    }
+}
+
+impl Diagnostic for FromParagraphError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            FromParagraphError::MissingField(_) => Some(Box::new("deb822::missing_field")),
+            FromParagraphError::InvalidField(e) => e.code(),
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            FromParagraphError::MissingField(_) => None,
+            FromParagraphError::InvalidField(e) => e.labels(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParseOptions;
+    use miette::{NarratableReportHandler, Report};
+
+    fn render(report: Report) -> String {
+        let mut out = String::new();
+        NarratableReportHandler::new()
+            .render_report(&mut out, report.as_ref())
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_parse_diagnostic() {
+        let text = "Package: foo\nPackage: bar\n";
+        let (_, diagnostics) =
+            crate::lossy::Deb822::from_str_recovering(text, &ParseOptions::strict());
+        let diagnostic = diagnostics.into_iter().next().unwrap();
+        assert_eq!(
+            diagnostic.code().unwrap().to_string(),
+            "deb822::duplicate_field"
+        );
+        let labels = diagnostic.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels[0].label(), Some("Duplicate field: Package"));
+        assert_eq!(
+            &text[labels[0].offset()..][..labels[0].len()],
+            "Package: bar"
+        );
+
+        let output = render(Report::new(diagnostic).with_source_code(text));
+        assert!(output.contains("Duplicate field: Package"), "{}", output);
+        assert!(output.contains("Package: bar"), "{}", output);
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let text = "Package: foo\nbroken value\n";
+        let (_, errors) = crate::lossless::Deb822::from_str_recovering(text);
+        let error = errors.into_iter().next().unwrap();
+        let labels = error.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(&text[labels[0].offset()..][..labels[0].len()], "value");
+        let output = render(Report::new(error).with_source_code(text));
+        assert!(output.contains("broken value"), "{}", output);
+    }
+
+    #[test]
+    fn test_field_error() {
+        let text = "Package: foo\nInstalled-Size: large\n";
+        let error = FieldError::parse::<u64>("Installed-Size", "large")
+            .unwrap_err()
+            .with_span(13..34);
+        assert_eq!(error.code().unwrap().to_string(), "deb822::invalid_field");
+        let labels = error.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            &text[labels[0].offset()..][..labels[0].len()],
+            "Installed-Size: large"
+        );
+        let output = render(Report::new(error).with_source_code(text));
+        assert!(output.contains("invalid digit"), "{}", output);
+
+        let error = FromParagraphError::MissingField("Package".to_string());
+        assert_eq!(error.code().unwrap().to_string(), "deb822::missing_field");
+        assert!(error.labels().is_none());
+    }
+
+    #[test]
+    fn test_roundtrip_error() {
+        let text = "Package: foo\nbroken\n";
+        let deb822: crate::lossless::Deb822 = "Package: foo\n".parse().unwrap();
+        let error = deb822.verify_roundtrip(text).unwrap_err();
+        let labels = error.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(&text[labels[0].offset()..][..labels[0].len()], "broken");
+    }
+}
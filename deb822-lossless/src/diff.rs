@@ -0,0 +1,669 @@
+//! Semantic differences between deb822 documents.
+//!
+//! [`diff`] compares two documents paragraph by paragraph and field by field, and returns a
+//! [`Changeset`] that describes the paragraphs and fields that were added, removed or modified.
+//! Differences in formatting, such as field order, the case of field names and the whitespace
+//! around values, are ignored.
+//!
+//! Paragraphs are matched up by the value of their `Package` field or, failing that, their
+//! `Source` field. Paragraphs that have the same value are matched up in the order in which
+//! they appear, and so are paragraphs that have neither field.
+//!
+//! A changeset can be rendered with its [`Display`](core::fmt::Display) implementation, or
+//! applied to a document with [`Changeset::apply`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::diff::{diff, FieldChange, ParagraphChange, ParagraphKey};
+//! use deb822_lossless::Deb822;
+//!
+//! let old: Deb822 = "Source: foo\n\nPackage: foo\nDepends: bar\n\nPackage: foo-doc\n"
+//!     .parse()
+//!     .unwrap();
+//! let new: Deb822 = "Source: foo\n\nPackage: foo\nDepends: baz\n\nPackage: foo-data\n"
+//!     .parse()
+//!     .unwrap();
+//!
+//! let changes = diff(&old, &new);
+//! assert_eq!(
+//!     changes.paragraphs[0],
+//!     ParagraphChange::Modified {
+//!         key: ParagraphKey::field("Package", "foo"),
+//!         fields: vec![FieldChange::Modified {
+//!             name: "Depends".to_string(),
+//!             old: "bar".to_string(),
+//!             new: "baz".to_string(),
+//!         }],
+//!     }
+//! );
+//! assert_eq!(
+//!     changes.to_string(),
+//!     "Package: foo\n~ Depends: bar -> baz\n- Package: foo-doc\n+ Package: foo-data\n"
+//! );
+//!
+//! let mut doc = old;
+//! changes.apply(&mut doc).unwrap();
+//! assert!(diff(&doc, &new).is_empty());
+//! ```
+use crate::semantic::normalize_value;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Fields that identify a paragraph, in order of preference.
+const KEY_FIELDS: &[&str] = &["Package", "Source"];
+
+/// Identifies a paragraph in a document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ParagraphKey {
+    /// The paragraph with the given value for a key field, such as `Package`.
+    Field {
+        /// Name of the key field.
+        name: String,
+
+        /// Value of the key field.
+        value: String,
+
+        /// Which of the paragraphs with this value it is, counting from zero.
+        occurrence: usize,
+    },
+
+    /// The n-th paragraph (counting from zero) that has none of the key fields.
+    Index(usize),
+}
+
+impl ParagraphKey {
+    /// Create a key for the (first) paragraph with the given value for a key field.
+    pub fn field(name: &str, value: &str) -> Self {
+        Self::Field {
+            name: name.to_string(),
+            value: value.to_string(),
+            occurrence: 0,
+        }
+    }
+}
+
+impl core::fmt::Display for ParagraphKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Field {
+                name,
+                value,
+                occurrence: 0,
+            } => write!(f, "{}: {}", name, value),
+            Self::Field {
+                name,
+                value,
+                occurrence,
+            } => write!(f, "{}: {} (#{})", name, value, occurrence),
+            Self::Index(i) => write!(f, "paragraph #{}", i),
+        }
+    }
+}
+
+/// A change to a single field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldChange {
+    /// The field was added.
+    Added {
+        /// Name of the field.
+        name: String,
+
+        /// Value of the field.
+        value: String,
+    },
+
+    /// The field was removed.
+    Removed {
+        /// Name of the field.
+        name: String,
+
+        /// Value the field had.
+        value: String,
+    },
+
+    /// The value of the field changed.
+    Modified {
+        /// Name of the field.
+        name: String,
+
+        /// The old value.
+        old: String,
+
+        /// The new value.
+        new: String,
+    },
+}
+
+/// A change to a paragraph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParagraphChange {
+    /// The paragraph was added.
+    Added {
+        /// Key of the paragraph.
+        key: ParagraphKey,
+
+        /// Fields of the paragraph.
+        fields: Vec<(String, String)>,
+    },
+
+    /// The paragraph was removed.
+    Removed {
+        /// Key of the paragraph.
+        key: ParagraphKey,
+
+        /// Fields the paragraph had.
+        fields: Vec<(String, String)>,
+    },
+
+    /// Fields of the paragraph were changed.
+    Modified {
+        /// Key of the paragraph.
+        key: ParagraphKey,
+
+        /// The changes to the fields.
+        fields: Vec<FieldChange>,
+    },
+}
+
+impl ParagraphChange {
+    /// The key of the paragraph that changed.
+    pub fn key(&self) -> &ParagraphKey {
+        match self {
+            Self::Added { key, .. } | Self::Removed { key, .. } | Self::Modified { key, .. } => key,
+        }
+    }
+}
+
+/// The differences between two documents.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Changeset {
+    /// The paragraphs that changed.
+    ///
+    /// Removed and modified paragraphs come first, in the order of the old document, followed
+    /// by the added paragraphs in the order of the new document.
+    pub paragraphs: Vec<ParagraphChange>,
+}
+
+/// Error applying a [`Changeset`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyError {
+    /// A paragraph that the changeset removes or modifies does not exist.
+    MissingParagraph(ParagraphKey),
+}
+
+impl core::fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MissingParagraph(key) => write!(f, "missing paragraph: {}", key),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ApplyError {}
+
+/// A document that can be compared and modified by the functions in this module.
+pub trait Diffable {
+    /// The fields of each paragraph, in order.
+    fn paragraph_fields(&self) -> Vec<Vec<(String, String)>>;
+
+    /// Remove the paragraph at `index`.
+    fn remove_paragraph_at(&mut self, index: usize);
+
+    /// Add a paragraph with the given fields to the end of the document.
+    fn push_paragraph(&mut self, fields: &[(String, String)]);
+
+    /// Set the value of a field in the paragraph at `index`.
+    fn set_field(&mut self, index: usize, name: &str, value: &str);
+
+    /// Remove a field from the paragraph at `index`.
+    fn remove_field(&mut self, index: usize, name: &str);
+}
+
+impl Diffable for crate::lossy::Deb822 {
+    fn paragraph_fields(&self) -> Vec<Vec<(String, String)>> {
+        self.iter()
+            .map(|p| {
+                p.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn remove_paragraph_at(&mut self, index: usize) {
+        self.0.remove(index);
+    }
+
+    fn push_paragraph(&mut self, fields: &[(String, String)]) {
+        self.0.push(fields.iter().cloned().collect());
+    }
+
+    fn set_field(&mut self, index: usize, name: &str, value: &str) {
+        self.0[index].set(name, value);
+    }
+
+    fn remove_field(&mut self, index: usize, name: &str) {
+        self.0[index].remove(name);
+    }
+}
+
+#[cfg(feature = "std")]
+impl Diffable for crate::lossless::Deb822 {
+    fn paragraph_fields(&self) -> Vec<Vec<(String, String)>> {
+        self.paragraphs().map(|p| p.items().collect()).collect()
+    }
+
+    fn remove_paragraph_at(&mut self, index: usize) {
+        self.remove_paragraph(index);
+    }
+
+    fn push_paragraph(&mut self, fields: &[(String, String)]) {
+        let mut paragraph = self.add_paragraph();
+        for (name, value) in fields {
+            paragraph.insert(name, value);
+        }
+    }
+
+    fn set_field(&mut self, index: usize, name: &str, value: &str) {
+        if let Some(mut paragraph) = self.paragraphs().nth(index) {
+            paragraph.set(name, value);
+        }
+    }
+
+    fn remove_field(&mut self, index: usize, name: &str) {
+        if let Some(mut paragraph) = self.paragraphs().nth(index) {
+            paragraph.remove(name);
+        }
+    }
+}
+
+/// Look up the value of a field, ignoring the case of its name.
+fn lookup<'a>(fields: &'a [(String, String)], name: &str) -> Option<&'a (String, String)> {
+    fields.iter().find(|(k, _)| k.eq_ignore_ascii_case(name))
+}
+
+/// Compute the keys of the paragraphs in a document.
+///
+/// The keys are unique: paragraphs with the same key field value are told apart by their
+/// occurrence.
+fn paragraph_keys(paragraphs: &[Vec<(String, String)>]) -> Vec<ParagraphKey> {
+    let mut unkeyed = 0;
+    let mut seen = BTreeMap::new();
+    paragraphs
+        .iter()
+        .map(|fields| {
+            KEY_FIELDS
+                .iter()
+                .find_map(|name| {
+                    lookup(fields, name).map(|(_, value)| {
+                        let occurrence = seen.entry((*name, value.trim())).or_insert(0);
+                        *occurrence += 1;
+                        ParagraphKey::Field {
+                            name: name.to_string(),
+                            value: value.trim().to_string(),
+                            occurrence: *occurrence - 1,
+                        }
+                    })
+                })
+                .unwrap_or_else(|| {
+                    unkeyed += 1;
+                    ParagraphKey::Index(unkeyed - 1)
+                })
+        })
+        .collect()
+}
+
+/// Map the keys of the paragraphs in a document to their positions.
+fn key_positions(keys: &[ParagraphKey]) -> BTreeMap<&ParagraphKey, usize> {
+    keys.iter().enumerate().map(|(i, key)| (key, i)).collect()
+}
+
+/// Compare the fields of two versions of a paragraph.
+fn diff_fields(old: &[(String, String)], new: &[(String, String)]) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    for (name, value) in old {
+        match lookup(new, name) {
+            None => changes.push(FieldChange::Removed {
+                name: name.clone(),
+                value: value.clone(),
+            }),
+            Some((new_name, new_value)) if normalize_value(value) != normalize_value(new_value) => {
+                changes.push(FieldChange::Modified {
+                    name: new_name.clone(),
+                    old: value.clone(),
+                    new: new_value.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, value) in new {
+        if lookup(old, name).is_none() {
+            changes.push(FieldChange::Added {
+                name: name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+    changes
+}
+
+/// Compute the differences between two documents.
+///
+/// Applying the result to `old` with [`Changeset::apply`] produces a document that is
+/// semantically equal to `new`, except for the order of the paragraphs.
+pub fn diff<D: Diffable>(old: &D, new: &D) -> Changeset {
+    let old = old.paragraph_fields();
+    let new = new.paragraph_fields();
+    let old_keys = paragraph_keys(&old);
+    let new_keys = paragraph_keys(&new);
+    let old_positions = key_positions(&old_keys);
+    let new_positions = key_positions(&new_keys);
+
+    let mut paragraphs = Vec::new();
+    for (key, fields) in old_keys.iter().zip(old.iter()) {
+        match new_positions.get(key).copied() {
+            None => paragraphs.push(ParagraphChange::Removed {
+                key: key.clone(),
+                fields: fields.clone(),
+            }),
+            Some(i) => {
+                let changes = diff_fields(fields, &new[i]);
+                if !changes.is_empty() {
+                    paragraphs.push(ParagraphChange::Modified {
+                        key: key.clone(),
+                        fields: changes,
+                    });
+                }
+            }
+        }
+    }
+    for (key, fields) in new_keys.iter().zip(new) {
+        if !old_positions.contains_key(key) {
+            paragraphs.push(ParagraphChange::Added {
+                key: key.clone(),
+                fields,
+            });
+        }
+    }
+    Changeset { paragraphs }
+}
+
+impl Changeset {
+    /// Check whether there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.paragraphs.is_empty()
+    }
+
+    /// Apply the changes to a document.
+    ///
+    /// The keys of the changes are looked up in `doc` as it is before any changes are made.
+    /// Added paragraphs are appended to the end of the document. If a paragraph that is
+    /// removed or modified does not exist, an error is returned and the document is left
+    /// unchanged.
+    pub fn apply<D: Diffable>(&self, doc: &mut D) -> Result<(), ApplyError> {
+        let keys = paragraph_keys(&doc.paragraph_fields());
+        let positions = key_positions(&keys);
+        let mut indexes = Vec::with_capacity(self.paragraphs.len());
+        for change in &self.paragraphs {
+            let index = match change {
+                ParagraphChange::Added { .. } => None,
+                ParagraphChange::Removed { key, .. } | ParagraphChange::Modified { key, .. } => {
+                    Some(
+                        *positions
+                            .get(key)
+                            .ok_or_else(|| ApplyError::MissingParagraph(key.clone()))?,
+                    )
+                }
+            };
+            indexes.push(index);
+        }
+
+        let mut removed = Vec::new();
+        for (change, index) in self.paragraphs.iter().zip(indexes) {
+            match (change, index) {
+                (ParagraphChange::Modified { fields, .. }, Some(index)) => {
+                    for field in fields {
+                        match field {
+                            FieldChange::Added { name, value } => doc.set_field(index, name, value),
+                            FieldChange::Modified { name, new, .. } => {
+                                doc.set_field(index, name, new)
+                            }
+                            FieldChange::Removed { name, .. } => doc.remove_field(index, name),
+                        }
+                    }
+                }
+                (ParagraphChange::Removed { .. }, Some(index)) => removed.push(index),
+                _ => {}
+            }
+        }
+        // Remove paragraphs from the end, so that the indexes of the others stay valid.
+        removed.sort_unstable();
+        for index in removed.into_iter().rev() {
+            doc.remove_paragraph_at(index);
+        }
+        for change in &self.paragraphs {
+            if let ParagraphChange::Added { fields, .. } = change {
+                doc.push_paragraph(fields);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Display for Changeset {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for change in &self.paragraphs {
+            match change {
+                ParagraphChange::Added { key, .. } => writeln!(f, "+ {}", key)?,
+                ParagraphChange::Removed { key, .. } => writeln!(f, "- {}", key)?,
+                ParagraphChange::Modified { key, fields } => {
+                    writeln!(f, "{}", key)?;
+                    for field in fields {
+                        match field {
+                            FieldChange::Added { name, value } => {
+                                writeln!(f, "+ {}: {}", name, value)?
+                            }
+                            FieldChange::Removed { name, value } => {
+                                writeln!(f, "- {}: {}", name, value)?
+                            }
+                            FieldChange::Modified { name, old, new } => {
+                                writeln!(f, "~ {}: {} -> {}", name, old, new)?
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lossy::Deb822;
+
+    #[test]
+    fn test_no_changes() {
+        let a: Deb822 = "Package: foo\nDepends: bar,\n baz\n".parse().unwrap();
+        let b: Deb822 = "depends:  bar,\n  baz\nPackage: foo\n".parse().unwrap();
+        assert!(diff(&a, &b).is_empty());
+        assert_eq!(diff(&a, &b).to_string(), "");
+    }
+
+    #[test]
+    fn test_fields() {
+        let a: Deb822 = "Package: foo\nA: 1\nB: 2\n".parse().unwrap();
+        let b: Deb822 = "Package: foo\nB: 3\nC: 4\n".parse().unwrap();
+        let changes = diff(&a, &b);
+        assert_eq!(
+            changes.paragraphs,
+            vec![ParagraphChange::Modified {
+                key: ParagraphKey::field("Package", "foo"),
+                fields: vec![
+                    FieldChange::Removed {
+                        name: "A".to_string(),
+                        value: "1".to_string()
+                    },
+                    FieldChange::Modified {
+                        name: "B".to_string(),
+                        old: "2".to_string(),
+                        new: "3".to_string()
+                    },
+                    FieldChange::Added {
+                        name: "C".to_string(),
+                        value: "4".to_string()
+                    },
+                ],
+            }]
+        );
+        let mut doc = a.clone();
+        changes.apply(&mut doc).unwrap();
+        assert_eq!(doc.to_string(), "Package: foo\nB: 3\nC: 4\n");
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let a: Deb822 = "Source: foo\n\nPackage: foo\n\nA: 1\n\nB: 2\n"
+            .parse()
+            .unwrap();
+        let b: Deb822 = "Source: foo\n\nPackage: bar\n\nA: 1\n".parse().unwrap();
+        let changes = diff(&a, &b);
+        assert_eq!(
+            changes
+                .paragraphs
+                .iter()
+                .map(|c| c.key().to_string())
+                .collect::<Vec<_>>(),
+            vec!["Package: foo", "paragraph #1", "Package: bar"]
+        );
+        assert!(matches!(
+            changes.paragraphs[1],
+            ParagraphChange::Removed { .. }
+        ));
+
+        let mut doc = a.clone();
+        changes.apply(&mut doc).unwrap();
+        assert_eq!(doc.to_string(), "Source: foo\n\nA: 1\n\nPackage: bar\n");
+
+        // The paragraphs that are removed no longer exist.
+        assert_eq!(
+            changes.apply(&mut doc),
+            Err(ApplyError::MissingParagraph(ParagraphKey::field(
+                "Package", "foo"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let a: Deb822 = "Package: foo\nA: 1\n\nPackage: foo\nA: 2\n\nPackage: foo\nA: 3\n"
+            .parse()
+            .unwrap();
+        let b: Deb822 = "Package: foo\nA: 1\n\nPackage: foo\nA: 4\n"
+            .parse()
+            .unwrap();
+        let changes = diff(&a, &b);
+        assert_eq!(
+            changes.to_string(),
+            "Package: foo (#1)\n~ A: 2 -> 4\n- Package: foo (#2)\n"
+        );
+        let mut doc = a.clone();
+        changes.apply(&mut doc).unwrap();
+        assert_eq!(doc, b);
+    }
+
+    #[test]
+    fn test_remove_unkeyed() {
+        let a: Deb822 = "A: 1\n\nB: 2\n\nC: 3\n".parse().unwrap();
+        let b: Deb822 = "C: 3\n".parse().unwrap();
+        let changes = diff(&a, &b);
+        let mut doc = a.clone();
+        changes.apply(&mut doc).unwrap();
+        assert_eq!(doc, b);
+
+        // Nothing is changed if a paragraph is missing.
+        let mut doc: Deb822 = "A: 1\n".parse().unwrap();
+        assert_eq!(
+            changes.apply(&mut doc),
+            Err(ApplyError::MissingParagraph(ParagraphKey::Index(1)))
+        );
+        assert_eq!(doc.to_string(), "A: 1\n");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_lossless() {
+        let old: crate::lossless::Deb822 = "Source: foo\n# Build\nBuild-Depends: bar\n"
+            .parse()
+            .unwrap();
+        let new: crate::lossless::Deb822 = "Source: foo\nBuild-Depends: bar, baz\n\nPackage: foo\n"
+            .parse()
+            .unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes.to_string(),
+            "Source: foo\n~ Build-Depends: bar -> bar, baz\n+ Package: foo\n"
+        );
+        let mut doc = old;
+        changes.apply(&mut doc).unwrap();
+        assert_eq!(
+            doc.to_string(),
+            "Source: foo\n# Build\nBuild-Depends: bar, baz\n\nPackage: foo\n"
+        );
+    }
+
+    #[cfg(feature = "proptest")]
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Documents whose paragraphs often share their `Package` field, or have none.
+        fn document() -> impl Strategy<Value = Deb822> {
+            prop::collection::vec(
+                (
+                    prop::option::of(prop::sample::select(vec!["foo", "bar"])),
+                    prop::collection::vec(
+                        (
+                            prop::sample::select(vec!["A", "B", "C"]),
+                            prop::sample::select(vec!["1", "2"]),
+                        ),
+                        1..=3,
+                    ),
+                ),
+                0..=5,
+            )
+            .prop_map(|paragraphs| {
+                paragraphs
+                    .into_iter()
+                    .map(|(package, fields)| {
+                        let mut paragraph: Vec<(String, String)> = Vec::new();
+                        for (name, value) in
+                            package.map(|p| ("Package", p)).into_iter().chain(fields)
+                        {
+                            if !paragraph.iter().any(|(n, _)| n == name) {
+                                paragraph.push((name.to_string(), value.to_string()));
+                            }
+                        }
+                        paragraph.into_iter().collect::<crate::lossy::Paragraph>()
+                    })
+                    .collect::<Vec<_>>()
+                    .into()
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn test_apply_diff(old in document(), new in document()) {
+                let changes = diff(&old, &new);
+                let mut doc = old.clone();
+                changes.apply(&mut doc).unwrap();
+                prop_assert!(diff(&doc, &new).is_empty(), "{}", diff(&doc, &new));
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Encoding and decoding of field values according to the type of the field.
+//!
+//! deb822(5) distinguishes three types of fields:
+//!
+//! * simple fields, whose value is a single line;
+//! * folded fields, whose value is a single logical line that may be split over several
+//!   physical lines, such as `Uploaders` or `Depends`;
+//! * multiline fields, whose value consists of several lines, such as `Description`. Empty
+//!   lines are written as a single `.`.
+//!
+//! The values returned by the parsers are the raw lines of the field, joined by newlines.
+//! [`FieldKind::decode`] turns them into the value they represent, and [`FieldKind::encode`]
+//! does the reverse.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::folding::FieldKind;
+//!
+//! let paragraph: deb822_lossless::lossy::Paragraph =
+//!     "Uploaders: Jane <jane@example.com>,\n Joe <joe@example.com>\nDescription: foo\n bar\n .\n baz\n"
+//!         .parse()
+//!         .unwrap();
+//! assert_eq!(
+//!     FieldKind::Folded.decode(paragraph.get("Uploaders").unwrap()),
+//!     "Jane <jane@example.com>, Joe <joe@example.com>"
+//! );
+//! assert_eq!(
+//!     FieldKind::Multiline.decode(paragraph.get("Description").unwrap()),
+//!     "foo\nbar\n\nbaz"
+//! );
+//! assert_eq!(FieldKind::Multiline.encode("foo\nbar\n\nbaz"), "foo\nbar\n.\nbaz");
+//! ```
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The type of a field, which determines how its value is spread over lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FieldKind {
+    /// The value is a single line.
+    #[default]
+    Simple,
+
+    /// The value is a single logical line, which may be folded over several lines.
+    Folded,
+
+    /// The value consists of several lines, with empty lines written as `.`.
+    Multiline,
+}
+
+impl FieldKind {
+    /// Decode the raw value of a field, as returned by the parsers.
+    ///
+    /// Simple and folded values are unfolded into a single line, with each line break and the
+    /// surrounding whitespace replaced by a single space. For multiline values, lines consisting
+    /// of a single `.` are replaced by empty lines.
+    pub fn decode(&self, raw: &str) -> String {
+        match self {
+            FieldKind::Simple | FieldKind::Folded => unfold(raw),
+            FieldKind::Multiline => raw
+                .lines()
+                .map(|line| if line.trim() == "." { "" } else { line })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// Encode a value, so that it can be stored in a field of this type.
+    ///
+    /// Simple values are unfolded into a single line. Folded values keep their line breaks,
+    /// but empty lines, which would end the paragraph, are dropped. For multiline values,
+    /// trailing whitespace is removed and empty lines after the first are replaced by `.`.
+    pub fn encode(&self, value: &str) -> String {
+        match self {
+            FieldKind::Simple => unfold(value),
+            FieldKind::Folded => value
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            FieldKind::Multiline => {
+                let mut lines = value.trim_end().lines();
+                let first = lines.next().unwrap_or_default().trim().to_string();
+                lines.fold(first, |mut ret, line| {
+                    let line = line.trim_end();
+                    ret.push('\n');
+                    ret.push_str(if line.is_empty() { "." } else { line });
+                    ret
+                })
+            }
+        }
+    }
+}
+
+/// Join the lines of a value with single spaces.
+fn unfold(value: &str) -> String {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(FieldKind::Simple.decode(" foo "), "foo");
+        assert_eq!(FieldKind::Folded.decode("a,\nb,\n\n c"), "a, b, c");
+        assert_eq!(FieldKind::Folded.decode(""), "");
+        assert_eq!(
+            FieldKind::Multiline.decode("synopsis\nfirst\n.\n  verbatim"),
+            "synopsis\nfirst\n\n  verbatim"
+        );
+        assert_eq!(FieldKind::Multiline.decode("\na\n.\n"), "\na\n");
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(FieldKind::Simple.encode("foo\nbar"), "foo bar");
+        assert_eq!(FieldKind::Folded.encode("a,\n\n  b"), "a,\nb");
+        assert_eq!(
+            FieldKind::Multiline.encode(" synopsis\nfirst\n \n  verbatim\n\n"),
+            "synopsis\nfirst\n.\n  verbatim"
+        );
+        assert_eq!(FieldKind::Multiline.encode(""), "");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for value in ["synopsis", "synopsis\nbody\n\nmore", "\nbody"] {
+            let kind = FieldKind::Multiline;
+            assert_eq!(kind.decode(&kind.encode(value)), value);
+        }
+        let kind = FieldKind::Folded;
+        assert_eq!(kind.decode(&kind.encode("a, b")), "a, b");
+    }
+}
@@ -0,0 +1,210 @@
+//! Undo and redo support for lossless documents.
+//!
+//! [`History`] wraps a [`Deb822`] document and records every edit made through
+//! [`History::edit`] as a pair of snapshots of the syntax tree. Snapshots share structure with
+//! the tree, so recording an edit is cheap even for large files.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::history::History;
+//!
+//! let mut history = History::new("Source: foo\n".parse().unwrap());
+//! history.checkpoint("original");
+//! history.edit(|doc| doc.paragraphs().next().unwrap().set("Section", "net"));
+//! history.edit(|doc| doc.paragraphs().next().unwrap().set("Priority", "optional"));
+//! assert_eq!(history.document().to_string(), "Source: foo\nSection: net\nPriority: optional\n");
+//!
+//! assert!(history.undo());
+//! assert_eq!(history.document().to_string(), "Source: foo\nSection: net\n");
+//! assert!(history.redo());
+//! assert_eq!(history.document().to_string(), "Source: foo\nSection: net\nPriority: optional\n");
+//!
+//! assert!(history.restore("original"));
+//! assert_eq!(history.document().to_string(), "Source: foo\n");
+//! ```
+use crate::lossless::Deb822;
+use rowan::GreenNode;
+use std::collections::HashMap;
+
+/// A single recorded edit.
+struct Step {
+    before: GreenNode,
+    after: GreenNode,
+}
+
+/// A lossless document with undo/redo history.
+pub struct History {
+    document: Deb822,
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    checkpoints: HashMap<String, GreenNode>,
+}
+
+impl History {
+    /// Start recording history for a document.
+    pub fn new(document: Deb822) -> Self {
+        Self {
+            document,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            checkpoints: HashMap::new(),
+        }
+    }
+
+    /// The current state of the document.
+    ///
+    /// Changes made to the document other than through [`History::edit`] are not recorded
+    /// separately; they become part of the next recorded edit.
+    pub fn document(&self) -> &Deb822 {
+        &self.document
+    }
+
+    /// Stop recording history, and return the document.
+    pub fn into_inner(self) -> Deb822 {
+        self.document
+    }
+
+    /// Apply an edit to the document, recording it so that it can be undone.
+    ///
+    /// Edits that do not change the document are not recorded. Recording an edit discards
+    /// anything that could be redone.
+    pub fn edit<R>(&mut self, f: impl FnOnce(&mut Deb822) -> R) -> R {
+        let before = self.document.snapshot();
+        let ret = f(&mut self.document);
+        self.record(before);
+        ret
+    }
+
+    fn record(&mut self, before: GreenNode) {
+        let after = self.document.snapshot();
+        if before != after {
+            self.undo.push(Step { before, after });
+            self.redo.clear();
+        }
+    }
+
+    /// Undo the most recent edit.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(step) => {
+                self.document.restore(&step.before);
+                self.redo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the most recently undone edit.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(step) => {
+                self.document.restore(&step.after);
+                self.undo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether there is an edit that can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is an edit that can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Remember the current state of the document under a name.
+    ///
+    /// An existing checkpoint with the same name is replaced.
+    pub fn checkpoint(&mut self, name: &str) {
+        self.checkpoints
+            .insert(name.to_string(), self.document.snapshot());
+    }
+
+    /// Return the document to the state it was in when a checkpoint was created.
+    ///
+    /// This is recorded as an edit, so it can itself be undone. Returns `false` if there is no
+    /// checkpoint with the given name.
+    pub fn restore(&mut self, name: &str) -> bool {
+        let Some(green) = self.checkpoints.get(name).cloned() else {
+            return false;
+        };
+        let before = self.document.snapshot();
+        self.document.restore(&green);
+        self.record(before);
+        true
+    }
+
+    /// Names of the checkpoints that have been created.
+    pub fn checkpoints(&self) -> impl Iterator<Item = &str> + '_ {
+        self.checkpoints.keys().map(|k| k.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = History::new("A: 1\n\nB: 2\n".parse().unwrap());
+        assert!(!history.can_undo());
+        assert!(!history.undo());
+
+        history.edit(|doc| doc.remove_paragraph(0));
+        history.edit(|doc| doc.add_paragraph().set("C", "3"));
+        assert_eq!(history.document().to_string(), "B: 2\n\nC: 3\n");
+
+        assert!(history.undo());
+        assert!(history.undo());
+        assert!(!history.undo());
+        assert_eq!(history.document().to_string(), "A: 1\n\nB: 2\n");
+        assert!(history.can_redo());
+
+        assert!(history.redo());
+        assert_eq!(history.document().to_string(), "B: 2\n");
+
+        // A new edit discards the redo stack.
+        history.edit(|doc| doc.paragraphs().next().unwrap().set("B", "4"));
+        assert!(!history.can_redo());
+        assert_eq!(history.document().to_string(), "B: 4\n");
+    }
+
+    #[test]
+    fn test_noop_edit() {
+        let mut history = History::new("A: 1\n".parse().unwrap());
+        history.edit(|doc| doc.paragraphs().next().unwrap().get("A"));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let mut history = History::new("A: 1\n".parse().unwrap());
+        history.checkpoint("start");
+        history.edit(|doc| doc.paragraphs().next().unwrap().set("A", "2"));
+        history.checkpoint("two");
+        history.edit(|doc| doc.paragraphs().next().unwrap().set("A", "3"));
+        assert!(!history.restore("missing"));
+
+        assert!(history.restore("start"));
+        assert_eq!(history.document().to_string(), "A: 1\n");
+        assert!(history.restore("two"));
+        assert_eq!(history.document().to_string(), "A: 2\n");
+        assert!(history.undo());
+        assert_eq!(history.document().to_string(), "A: 1\n");
+
+        let mut names = history.checkpoints().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["start", "two"]);
+        assert_eq!(history.into_inner().to_string(), "A: 1\n");
+    }
+}
@@ -0,0 +1,230 @@
+//! Lookup of paragraphs by the value of a key field.
+//!
+//! A [`Deb822Index`] holds the paragraphs of a document together with a hash index on the
+//! value of one field, such as `Package` in a `Packages` file, so that paragraphs can be looked
+//! up in constant time.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::index::Deb822Index;
+//!
+//! let doc: deb822_lossless::lossy::Deb822 = r#"Package: hello
+//! Version: 2.10-2
+//!
+//! Package: hello
+//! Version: 2.10-3
+//!
+//! Package: world
+//! Version: 1.0
+//! "#.parse().unwrap();
+//!
+//! let index = Deb822Index::new("Package", doc);
+//! assert_eq!(index.get("world").unwrap().get("Version"), Some("1.0"));
+//! assert_eq!(index.get_all("hello").count(), 2);
+//! assert!(!index.contains_key("foo"));
+//! ```
+//!
+//! Paragraphs of lossless documents are views into the document, so edits made through the
+//! index show up in the document.
+//!
+//! ```rust
+//! use deb822_lossless::index::Deb822Index;
+//!
+//! let doc: deb822_lossless::Deb822 = "Source: foo\n\nPackage: foo\n\nPackage: foo-doc\n"
+//!     .parse()
+//!     .unwrap();
+//! let mut index = Deb822Index::new("Package", doc.paragraphs());
+//! index.get_mut("foo-doc").unwrap().set("Section", "doc");
+//! assert_eq!(
+//!     doc.to_string(),
+//!     "Source: foo\n\nPackage: foo\n\nPackage: foo-doc\nSection: doc\n"
+//! );
+//! ```
+use crate::convert::Deb822LikeParagraph;
+use std::collections::HashMap;
+
+/// Paragraphs indexed on the value of a key field.
+///
+/// Paragraphs are kept in their original order. Paragraphs that lack the key field are kept,
+/// but can only be reached through [`Deb822Index::iter`].
+///
+/// With the `serde` feature, an index of serializable paragraphs can be serialized, e.g. to
+/// store it in a [`crate::cache`]. Only the key field and the paragraphs are serialized; the
+/// hash index is rebuilt when deserializing.
+#[derive(Debug, Clone)]
+pub struct Deb822Index<P> {
+    key_field: String,
+    paragraphs: Vec<P>,
+    by_key: HashMap<String, Vec<usize>>,
+}
+
+impl<P: Deb822LikeParagraph> Deb822Index<P> {
+    /// Index `paragraphs` on the value of `key_field`.
+    pub fn new(key_field: &str, paragraphs: impl IntoIterator<Item = P>) -> Self {
+        let mut index = Self {
+            key_field: key_field.to_string(),
+            paragraphs: Vec::new(),
+            by_key: HashMap::new(),
+        };
+        index.extend(paragraphs);
+        index
+    }
+
+    /// Add a paragraph to the index.
+    pub fn push(&mut self, paragraph: P) {
+        if let Some(value) = paragraph.get(&self.key_field) {
+            self.by_key
+                .entry(value.trim().to_string())
+                .or_default()
+                .push(self.paragraphs.len());
+        }
+        self.paragraphs.push(paragraph);
+    }
+
+    /// The name of the field the paragraphs are indexed on.
+    pub fn key_field(&self) -> &str {
+        &self.key_field
+    }
+
+    /// Return the first paragraph whose key field has the given value.
+    pub fn get(&self, key: &str) -> Option<&P> {
+        self.get_all(key).next()
+    }
+
+    /// Return the first paragraph whose key field has the given value, mutably.
+    ///
+    /// The index is not updated if the value of the key field is changed.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut P> {
+        let i = *self.by_key.get(key)?.first()?;
+        Some(&mut self.paragraphs[i])
+    }
+
+    /// Return all paragraphs whose key field has the given value, in order.
+    pub fn get_all<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a P> + 'a {
+        self.by_key
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.paragraphs[*i])
+    }
+
+    /// Check whether a paragraph with the given key exists.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.by_key.contains_key(key)
+    }
+
+    /// Iterate over the distinct keys, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.by_key.keys().map(String::as_str)
+    }
+
+    /// Iterate over all paragraphs, in their original order.
+    pub fn iter(&self) -> impl Iterator<Item = &P> {
+        self.paragraphs.iter()
+    }
+
+    /// Number of paragraphs in the index.
+    pub fn len(&self) -> usize {
+        self.paragraphs.len()
+    }
+
+    /// Check whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.paragraphs.is_empty()
+    }
+
+    /// Return the paragraphs, in their original order.
+    pub fn into_inner(self) -> Vec<P> {
+        self.paragraphs
+    }
+}
+
+impl<P: Deb822LikeParagraph> Extend<P> for Deb822Index<P> {
+    fn extend<T: IntoIterator<Item = P>>(&mut self, iter: T) {
+        for paragraph in iter {
+            self.push(paragraph);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<P: serde::Serialize> serde::Serialize for Deb822Index<P> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.key_field, &self.paragraphs).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, P: Deb822LikeParagraph + serde::Deserialize<'de>> serde::Deserialize<'de>
+    for Deb822Index<P>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (key_field, paragraphs): (String, Vec<P>) =
+            serde::Deserialize::deserialize(deserializer)?;
+        Ok(Self::new(&key_field, paragraphs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        let doc: crate::lossy::Deb822 =
+            "Package: a\nV: 1\n\nSource: s\n\nPackage:  b \n\nPackage: a\nV: 2\n"
+                .parse()
+                .unwrap();
+        let mut index = Deb822Index::new("Package", doc);
+        assert_eq!(index.key_field(), "Package");
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.get("a").unwrap().get("V"), Some("1"));
+        assert_eq!(
+            index
+                .get_all("a")
+                .map(|p| p.get("V").unwrap())
+                .collect::<Vec<_>>(),
+            vec!["1", "2"]
+        );
+        assert!(index.contains_key("b"));
+        assert!(index.get("s").is_none());
+        let mut keys = index.keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "b"]);
+
+        index.get_mut("b").unwrap().set("V", "3");
+        assert_eq!(index.get("b").unwrap().get("V"), Some("3"));
+
+        index.push("Package: c\n".parse().unwrap());
+        assert!(index.contains_key("c"));
+        assert_eq!(
+            index
+                .into_inner()
+                .iter()
+                .map(|p| p.len())
+                .collect::<Vec<_>>(),
+            vec![2, 1, 2, 2, 1]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_serde() {
+        let doc: crate::lossy::Deb822 = "Package: a\n\nSource: s\n\nPackage: b\n".parse().unwrap();
+        let index = Deb822Index::new("Package", doc);
+        let json = serde_json::to_string(&index).unwrap();
+        let index: Deb822Index<crate::lossy::Paragraph> = serde_json::from_str(&json).unwrap();
+        assert_eq!(index.key_field(), "Package");
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get("b").unwrap().get("Package"), Some("b"));
+        assert!(!index.contains_key("s"));
+    }
+
+    #[test]
+    fn test_empty() {
+        let index = Deb822Index::<crate::lossy::Paragraph>::new("Package", vec![]);
+        assert!(index.is_empty());
+        assert_eq!(index.get_all("a").count(), 0);
+    }
+}
@@ -0,0 +1,350 @@
+//! Conversion of deb822 documents to and from JSON, preserving the order of fields.
+//!
+//! # Schema
+//!
+//! A paragraph is represented as an array of fields, in the order in which they appear in the
+//! paragraph. Each field is an array of two strings: the name of the field and its value.
+//! Values are represented in the same way as returned by [`crate::Paragraph::get`]: the lines
+//! of multi-line values are separated by `\n`, without their leading indentation. Fields that
+//! occur more than once in a paragraph are all kept.
+//!
+//! A document is represented as an array of paragraphs.
+//!
+//! Unlike JSON objects, whose keys are unordered, this representation can be processed with
+//! tools such as `jq` and read back without reordering the fields. Comments and formatting are
+//! not represented.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::Deb822;
+//!
+//! let deb822: Deb822 = "Source: foo\nMaintainer: Joe <joe@example.com>\n\nPackage: foo\n"
+//!     .parse()
+//!     .unwrap();
+//! let value = deb822.to_json();
+//! assert_eq!(
+//!     value,
+//!     serde_json::json!([
+//!         [["Source", "foo"], ["Maintainer", "Joe <joe@example.com>"]],
+//!         [["Package", "foo"]]
+//!     ])
+//! );
+//! assert_eq!(Deb822::from_json(&value).unwrap().to_string(), deb822.to_string());
+//! ```
+use serde_json::Value;
+
+/// An error converting from JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A JSON value did not have the expected type.
+    UnexpectedType {
+        /// The location of the value, e.g. `[0][1]` for the second field of the first
+        /// paragraph.
+        path: String,
+
+        /// The type that was expected.
+        expected: &'static str,
+    },
+
+    /// The name of a field is not a valid deb822 field name.
+    InvalidFieldName {
+        /// The location of the field.
+        path: String,
+
+        /// The name of the field.
+        name: String,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::UnexpectedType { path, expected } => {
+                write!(f, "expected {} for {}", expected, path)
+            }
+            Error::InvalidFieldName { path, name } => {
+                write!(f, "invalid field name {:?} at {}", name, path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Convert the fields of a paragraph to JSON.
+fn fields_to_json<K: Into<String>, V: Into<String>>(
+    fields: impl IntoIterator<Item = (K, V)>,
+) -> Value {
+    Value::Array(
+        fields
+            .into_iter()
+            .map(|(name, value)| {
+                Value::Array(vec![
+                    Value::String(name.into()),
+                    Value::String(value.into()),
+                ])
+            })
+            .collect(),
+    )
+}
+
+/// Replace the empty lines of a multi-line value by `.`.
+fn encode_empty_lines(value: &str) -> String {
+    value
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            if i > 0 && line.trim().is_empty() {
+                "."
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read the fields of a paragraph from JSON.
+fn fields_from_json<'a>(value: &'a Value, path: &str) -> Result<Vec<(&'a str, String)>, Error> {
+    expect_array(value, path)?
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let path = format!("{}[{}]", path, i);
+            match expect_array(field, &path)?.as_slice() {
+                [name, value] => {
+                    let name = expect_str(name, &format!("{}[0]", path))?;
+                    let value = expect_str(value, &format!("{}[1]", path))?;
+                    if !is_valid_field_name(name) {
+                        return Err(Error::InvalidFieldName {
+                            path,
+                            name: name.to_string(),
+                        });
+                    }
+                    Ok((name, encode_empty_lines(value)))
+                }
+                _ => Err(Error::UnexpectedType {
+                    path,
+                    expected: "an array of a name and a value",
+                }),
+            }
+        })
+        .collect()
+}
+
+fn is_valid_field_name(name: &str) -> bool {
+    name.starts_with(|c| crate::common::is_valid_initial_key_char(c))
+        && name.chars().all(crate::common::is_valid_key_char)
+}
+
+fn expect_array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, Error> {
+    value.as_array().ok_or_else(|| Error::UnexpectedType {
+        path: path.to_string(),
+        expected: "an array",
+    })
+}
+
+fn expect_str<'a>(value: &'a Value, path: &str) -> Result<&'a str, Error> {
+    value.as_str().ok_or_else(|| Error::UnexpectedType {
+        path: path.to_string(),
+        expected: "a string",
+    })
+}
+
+impl crate::lossless::Paragraph {
+    /// Convert the paragraph to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> Value {
+        fields_to_json(self.items())
+    }
+
+    /// Create a paragraph from its JSON representation.
+    pub fn from_json(value: &Value) -> Result<Self, Error> {
+        let mut paragraph = Self::new();
+        for (name, value) in fields_from_json(value, "")? {
+            paragraph.insert(name, &value);
+        }
+        Ok(paragraph)
+    }
+}
+
+impl crate::lossless::Deb822 {
+    /// Convert the document to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.paragraphs().map(|p| p.to_json()).collect())
+    }
+
+    /// Create a document from its JSON representation.
+    pub fn from_json(value: &Value) -> Result<Self, Error> {
+        let mut deb822 = Self::new();
+        for (i, paragraph) in expect_array(value, "")?.iter().enumerate() {
+            let fields = fields_from_json(paragraph, &format!("[{}]", i))?;
+            let mut paragraph = deb822.add_paragraph();
+            for (name, value) in fields {
+                paragraph.insert(name, &value);
+            }
+        }
+        Ok(deb822)
+    }
+}
+
+impl crate::lossy::Paragraph {
+    /// Convert the paragraph to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> Value {
+        fields_to_json(self.iter())
+    }
+
+    /// Create a paragraph from its JSON representation.
+    pub fn from_json(value: &Value) -> Result<Self, Error> {
+        Ok(fields_from_json(value, "")?
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect())
+    }
+}
+
+impl crate::lossy::Deb822 {
+    /// Convert the document to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(|p| p.to_json()).collect())
+    }
+
+    /// Create a document from its JSON representation.
+    pub fn from_json(value: &Value) -> Result<Self, Error> {
+        expect_array(value, "")?
+            .iter()
+            .enumerate()
+            .map(|(i, paragraph)| {
+                fields_from_json(paragraph, &format!("[{}]", i)).map(|fields| {
+                    fields
+                        .into_iter()
+                        .map(|(name, value)| (name.to_string(), value))
+                        .collect()
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TEXT: &str = "Source: foo
+Build-Depends: debhelper-compat (= 13),
+ python3
+Section: net
+
+Package: foo
+Description: synopsis
+ body
+ .
+ more
+Description: again
+";
+
+    #[test]
+    fn test_roundtrip() {
+        let expected = json!([
+            [
+                ["Source", "foo"],
+                ["Build-Depends", "debhelper-compat (= 13),\npython3"],
+                ["Section", "net"]
+            ],
+            [
+                ["Package", "foo"],
+                ["Description", "synopsis\nbody\n.\nmore"],
+                ["Description", "again"]
+            ]
+        ]);
+
+        let lossless: crate::lossless::Deb822 = TEXT.parse().unwrap();
+        assert_eq!(lossless.to_json(), expected);
+        let lossless = crate::lossless::Deb822::from_json(&expected).unwrap();
+        assert_eq!(lossless.to_json(), expected);
+
+        let lossy: crate::lossy::Deb822 = TEXT.parse().unwrap();
+        assert_eq!(lossy.to_json(), expected);
+        let lossy = crate::lossy::Deb822::from_json(&expected).unwrap();
+        assert_eq!(lossy.to_json(), expected);
+
+        let paragraph = json!([["B", "1"], ["A", "2"]]);
+        let lossless = crate::lossless::Paragraph::from_json(&paragraph).unwrap();
+        assert_eq!(lossless.to_string(), "B: 1\nA: 2\n");
+        assert_eq!(lossless.to_json(), paragraph);
+        let lossy = crate::lossy::Paragraph::from_json(&paragraph).unwrap();
+        assert_eq!(lossy.to_string(), "B: 1\nA: 2\n");
+        assert_eq!(lossy.to_json(), paragraph);
+    }
+
+    #[test]
+    fn test_empty_lines() {
+        let value = json!([
+            [
+                ["Package", "foo"],
+                [
+                    "Description",
+                    "synopsis\nFirst paragraph.\n\nSecond paragraph.\n  \nThird paragraph."
+                ]
+            ],
+            [["Package", "bar"]]
+        ]);
+        let expected = json!([
+            [
+                ["Package", "foo"],
+                [
+                    "Description",
+                    "synopsis\nFirst paragraph.\n.\nSecond paragraph.\n.\nThird paragraph."
+                ]
+            ],
+            [["Package", "bar"]]
+        ]);
+        let text = "Package: foo\nDescription: synopsis\n First paragraph.\n .\n Second paragraph.\n .\n Third paragraph.\n\nPackage: bar\n";
+
+        let lossless = crate::lossless::Deb822::from_json(&value).unwrap();
+        assert_eq!(lossless.to_string(), text);
+        let reparsed: crate::lossless::Deb822 = lossless.to_string().parse().unwrap();
+        assert_eq!(reparsed.to_json(), expected);
+        assert_eq!(
+            crate::lossless::Deb822::from_json(&expected)
+                .unwrap()
+                .to_json(),
+            expected
+        );
+
+        let lossy = crate::lossy::Deb822::from_json(&value).unwrap();
+        assert_eq!(lossy.to_string(), text);
+        let reparsed: crate::lossy::Deb822 = lossy.to_string().parse().unwrap();
+        assert_eq!(reparsed.to_json(), expected);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            crate::lossy::Deb822::from_json(&json!({"Package": "foo"})).unwrap_err(),
+            Error::UnexpectedType {
+                path: "".to_string(),
+                expected: "an array"
+            }
+        );
+        assert_eq!(
+            crate::lossless::Deb822::from_json(&json!([[["Package", "foo"]], [["Version"]]]))
+                .unwrap_err()
+                .to_string(),
+            "expected an array of a name and a value for [1][0]"
+        );
+        assert_eq!(
+            crate::lossless::Paragraph::from_json(&json!([["Package", 1]]))
+                .unwrap_err()
+                .to_string(),
+            "expected a string for [0][1]"
+        );
+        assert_eq!(
+            crate::lossy::Paragraph::from_json(&json!([["Package: foo", "bar"]]))
+                .unwrap_err()
+                .to_string(),
+            "invalid field name \"Package: foo\" at [0]"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Lazy parsing of deb822 files.
+//!
+//! When scanning large files where only a few fields of each paragraph are of interest (e.g.
+//! finding all versions of a package in a `Packages` file), fully parsing every field value is
+//! wasted work. The paragraphs returned by [`paragraphs`] only record where each field is
+//! located in the input during the initial scan; the value of a field is assembled from its
+//! lines the first time it is accessed.
+//!
+//! Field values are interpreted the same way as by the [lossy](crate::lossy) parser.
+//!
+//! # Example
+//!
+//! ```rust
+//! let input = r#"Package: hello
+//! Version: 2.10
+//! Description: A program that says hello
+//!  Some more text
+//!
+//! Package: world
+//! Version: 1.0
+//! "#;
+//!
+//! let versions = deb822_lossless::lazy::paragraphs(input)
+//!     .map(|p| p.unwrap())
+//!     .map(|p| (p.get("Package").unwrap().to_string(), p.get("Version").unwrap().to_string()))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(versions, vec![
+//!     ("hello".to_string(), "2.10".to_string()),
+//!     ("world".to_string(), "1.0".to_string()),
+//! ]);
+//! ```
+use crate::common;
+use crate::lex::SyntaxKind;
+use crate::lossy::{Error, Paragraph};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+
+/// A field whose value has not necessarily been parsed yet.
+#[derive(Debug, Clone)]
+struct LazyField<'a> {
+    name: &'a str,
+    /// The raw text of the value, from just after the colon up to (but excluding) the newline
+    /// at the end of the last continuation line.
+    raw: &'a str,
+    value: OnceCell<String>,
+}
+
+impl LazyField<'_> {
+    fn value(&self) -> &str {
+        // Values that fit on a single line can be returned without allocating
+        if !self.raw.contains('\n') {
+            return self.raw.trim_start_matches(common::is_indent);
+        }
+        self.value.get_or_init(|| materialize(self.raw))
+    }
+}
+
+/// Assemble a field value from its raw text.
+fn materialize(raw: &str) -> String {
+    let mut lines = raw.lines();
+    let mut value = lines
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(common::is_indent)
+        .to_string();
+    for line in lines {
+        let line = line.trim_start_matches(common::is_indent);
+        if line.starts_with('#') {
+            continue;
+        }
+        value.push('\n');
+        value.push_str(line);
+    }
+    value
+}
+
+/// A paragraph whose field values are parsed on first access.
+#[derive(Debug, Clone)]
+pub struct LazyParagraph<'a> {
+    fields: Vec<LazyField<'a>>,
+    text: &'a str,
+}
+
+impl<'a> LazyParagraph<'a> {
+    /// Get the value of a field by name.
+    ///
+    /// Returns `None` if the field does not exist.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value())
+    }
+
+    /// Check if the paragraph contains a field.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.fields.iter().any(|field| field.name == name)
+    }
+
+    /// Iterate over the names of the fields in the paragraph.
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.fields.iter().map(|field| field.name)
+    }
+
+    /// Iterate over the fields in the paragraph, parsing all values.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &str)> {
+        self.fields.iter().map(|field| (field.name, field.value()))
+    }
+
+    /// Return the number of fields in the paragraph.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Check if the paragraph is empty.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The text of the paragraph in the input, from the start of the first field to the end of
+    /// the last one.
+    ///
+    /// Comments between fields are included.
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Fully parse the paragraph.
+    pub fn to_paragraph(&self) -> Paragraph {
+        self.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
+impl<'a> From<LazyParagraph<'a>> for Paragraph {
+    fn from(p: LazyParagraph<'a>) -> Self {
+        p.to_paragraph()
+    }
+}
+
+/// Iterator over the paragraphs in a deb822 file.
+///
+/// Created by [`paragraphs`].
+pub struct LazyParagraphs<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> LazyParagraphs<'a> {
+    /// Return the offsets of the next line, excluding its line ending.
+    fn next_line(&mut self) -> Option<(usize, usize)> {
+        if self.pos >= self.text.len() {
+            return None;
+        }
+        let start = self.pos;
+        let (end, next) = match memchr::memchr(b'\n', &self.text.as_bytes()[start..]) {
+            Some(i) => (start + i, start + i + 1),
+            None => (self.text.len(), self.text.len()),
+        };
+        self.pos = next;
+        let line = &self.text[start..end];
+        Some((
+            start,
+            start + line.trim_end_matches(common::is_newline).len(),
+        ))
+    }
+}
+
+impl<'a> Iterator for LazyParagraphs<'a> {
+    type Item = Result<LazyParagraph<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.text;
+        let mut fields: Vec<LazyField<'a>> = Vec::new();
+        // Offsets of the raw value of the last field
+        let mut raw = (0, 0);
+        // Offsets of the text of the paragraph
+        let mut span = (0, 0);
+        while let Some((start, end)) = self.next_line() {
+            let line = &text[start..end];
+            if line.trim_matches(common::is_indent).is_empty() {
+                if fields.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with(common::is_indent) {
+                match fields.last_mut() {
+                    Some(field) => {
+                        // Extend the raw value of the current field to include this line
+                        raw.1 = end;
+                        field.raw = &text[raw.0..raw.1];
+                        span.1 = self.pos;
+                    }
+                    None => {
+                        return Some(Err(Error::UnexpectedToken(
+                            SyntaxKind::INDENT,
+                            line.to_string(),
+                        )));
+                    }
+                }
+                continue;
+            }
+            let colon = match memchr::memchr(b':', line.as_bytes()) {
+                Some(colon) => colon,
+                None => {
+                    return Some(Err(Error::UnexpectedToken(
+                        SyntaxKind::ERROR,
+                        line.to_string(),
+                    )));
+                }
+            };
+            let name = &line[..colon];
+            if !name.starts_with(common::is_valid_initial_key_char)
+                || !name.chars().all(common::is_valid_key_char)
+            {
+                return Some(Err(Error::UnexpectedToken(
+                    SyntaxKind::ERROR,
+                    name.to_string(),
+                )));
+            }
+            raw = (start + colon + 1, end);
+            if fields.is_empty() {
+                span.0 = start;
+            }
+            span.1 = self.pos;
+            fields.push(LazyField {
+                name,
+                raw: &text[raw.0..raw.1],
+                value: OnceCell::new(),
+            });
+        }
+        if fields.is_empty() {
+            None
+        } else {
+            Some(Ok(LazyParagraph {
+                fields,
+                text: &text[span.0..span.1],
+            }))
+        }
+    }
+}
+
+/// Iterate over the paragraphs in a deb822 file, deferring the parsing of field values.
+pub fn paragraphs(text: &str) -> LazyParagraphs<'_> {
+    LazyParagraphs { text, pos: 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs() {
+        let input = r#"# A comment
+Package: hello
+Version: 2.10
+Depends:
+ foo,
+# An indented comment
+ bar
+Description: A program that says hello
+ Some more text
+
+
+Package: world
+Version:  1.0
+"#;
+        let paragraphs = paragraphs(input).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(
+            paragraphs[0].keys().collect::<Vec<_>>(),
+            vec!["Package", "Version", "Depends", "Description"]
+        );
+        assert_eq!(paragraphs[0].get("Version"), Some("2.10"));
+        assert_eq!(paragraphs[0].get("Depends"), Some("\nfoo,\nbar"));
+        assert_eq!(
+            paragraphs[0].get("Description"),
+            Some("A program that says hello\nSome more text")
+        );
+        assert!(paragraphs[0].contains_key("Description"));
+        assert_eq!(paragraphs[0].get("Missing"), None);
+        assert_eq!(paragraphs[1].get("Version"), Some("1.0"));
+        assert_eq!(paragraphs[1].len(), 2);
+        assert_eq!(
+            paragraphs[0].as_str(),
+            r#"Package: hello
+Version: 2.10
+Depends:
+ foo,
+# An indented comment
+ bar
+Description: A program that says hello
+ Some more text
+"#
+        );
+        assert_eq!(paragraphs[1].as_str(), "Package: world\nVersion:  1.0\n");
+    }
+
+    #[test]
+    fn test_matches_lossy() {
+        let input =
+            "Package: hello\nDescription: foo\n bar\n  baz\n\nPackage: world\nVersion: 1.0\n";
+        let lossy: crate::lossy::Deb822 = input.parse().unwrap();
+        let lazy = paragraphs(input)
+            .map(|p| p.unwrap().to_paragraph())
+            .collect::<Vec<_>>();
+        assert_eq!(lazy, Vec::from(lossy));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(paragraphs(" indented\n").next().unwrap().is_err());
+        assert!(paragraphs("no colon\n").next().unwrap().is_err());
+        assert!(paragraphs("-Foo: bar\n").next().unwrap().is_err());
+    }
+}
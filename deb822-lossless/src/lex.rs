@@ -1,107 +1,169 @@
+// Without the lossless parser, only `SyntaxKind` is used (by the lossy parser).
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
 use crate::common;
 
-/// Let's start with defining all kinds of tokens and
-/// composite nodes.
+/// The kinds of tokens and composite nodes in the syntax tree.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(non_camel_case_types)]
 #[repr(u16)]
 pub enum SyntaxKind {
+    /// The name of a field.
     KEY = 0,
+    /// A line of the value of a field.
     VALUE,
+    /// The colon after the name of a field.
     COLON,
+    /// The indentation at the start of a continuation line.
     INDENT,
+    /// A line ending.
     NEWLINE,
-    WHITESPACE, // whitespaces is explicit
-    COMMENT,    // comments
-    ERROR,      // as well as errors
+    /// Whitespace within a line.
+    WHITESPACE,
+    /// A comment, including the leading `#`.
+    COMMENT,
+    /// Text that could not be parsed.
+    ERROR,
 
     // composite nodes
-    ROOT,       // The entire file
-    PARAGRAPH,  // A deb822 paragraph
-    ENTRY,      // A single key-value pair
-    EMPTY_LINE, // An empty line
+    /// The entire file.
+    ROOT,
+    /// A deb822 paragraph.
+    PARAGRAPH,
+    /// A single key-value pair.
+    ENTRY,
+    /// An empty line, or a comment between paragraphs.
+    EMPTY_LINE,
 }
 
 /// Convert our `SyntaxKind` into the rowan `SyntaxKind`.
+#[cfg(feature = "std")]
 impl From<SyntaxKind> for rowan::SyntaxKind {
     fn from(kind: SyntaxKind) -> Self {
         Self(kind as u16)
     }
 }
 
-fn lex_(mut input: &str, mut start_of_line: bool) -> impl Iterator<Item = (SyntaxKind, &str)> {
-    let mut colon_count = if start_of_line { 0 } else { 1 };
-    let mut indent = 0;
+/// A lexer for deb822 files.
+///
+/// The lexer operates on the underlying bytes of the input; all the characters that are
+/// significant to deb822 are ASCII, so runs of other characters can be skipped over using
+/// [`memchr`] rather than decoding them one by one.
+struct Lexer<'a> {
+    input: &'a str,
+    start_of_line: bool,
+    colon_count: usize,
+    indent: usize,
+    /// Whether `#` lines are comments even when indented.
+    indented_comments: bool,
+}
 
-    std::iter::from_fn(move || {
-        if let Some(c) = input.chars().next() {
-            match c {
-                ':' if colon_count == 0 => {
-                    colon_count += 1;
-                    input = &input[1..];
-                    Some((SyntaxKind::COLON, ":"))
-                }
-                _ if common::is_newline(c) => {
-                    let (nl, remaining) = input.split_at(1);
-                    input = remaining;
-                    start_of_line = true;
-                    colon_count = 0;
-                    indent = 0;
-                    Some((SyntaxKind::NEWLINE, nl))
-                }
-                _ if common::is_indent(c) => {
-                    let (whitespace, remaining) = input
-                        .split_at(input.find(|c| !common::is_indent(c)).unwrap_or(input.len()));
-                    input = remaining;
-                    if start_of_line {
-                        indent = whitespace.len();
-                        Some((SyntaxKind::INDENT, whitespace))
-                    } else {
-                        Some((SyntaxKind::WHITESPACE, whitespace))
-                    }
-                }
-                '#' if start_of_line => {
-                    let (comment, remaining) =
-                        input.split_at(input.find(common::is_newline).unwrap_or(input.len()));
-                    input = remaining;
-                    start_of_line = true;
-                    colon_count = 0;
-                    Some((SyntaxKind::COMMENT, comment))
-                }
-                _ if common::is_valid_initial_key_char(c) && start_of_line && indent == 0 => {
-                    let (key, remaining) = input.split_at(
-                        input
-                            .find(|c| !common::is_valid_key_char(c))
-                            .unwrap_or(input.len()),
-                    );
-                    input = remaining;
-                    start_of_line = false;
-                    Some((SyntaxKind::KEY, key))
-                }
-                _ if !start_of_line || indent > 0 => {
-                    let (value, remaining) =
-                        input.split_at(input.find(common::is_newline).unwrap_or(input.len()));
-                    input = remaining;
-                    Some((SyntaxKind::VALUE, value))
-                }
-                _ => {
-                    let (text, remaining) = input.split_at(1);
-                    input = remaining;
-                    Some((SyntaxKind::ERROR, text))
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, start_of_line: bool, indented_comments: bool) -> Self {
+        Self {
+            input,
+            start_of_line,
+            colon_count: if start_of_line { 0 } else { 1 },
+            indent: 0,
+            indented_comments,
+        }
+    }
+
+    /// Split off the first `n` bytes of the remaining input.
+    #[inline]
+    fn split(&mut self, n: usize) -> &'a str {
+        let (head, tail) = self.input.split_at(n);
+        self.input = tail;
+        head
+    }
+
+    /// Find the offset of the end of the current line.
+    #[inline]
+    fn line_end(&self) -> usize {
+        memchr::memchr2(b'\n', b'\r', self.input.as_bytes()).unwrap_or(self.input.len())
+    }
+
+    /// Find the offset of the first byte that does not match `f`.
+    #[inline]
+    fn span_while(&self, f: impl Fn(char) -> bool) -> usize {
+        self.input
+            .bytes()
+            .position(|b| !f(b as char))
+            .unwrap_or(self.input.len())
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = (SyntaxKind, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Bytes are mapped to chars as-is; since all of the predicates used below only
+        // match ASCII characters, this never misclassifies parts of multi-byte characters.
+        let c = *self.input.as_bytes().first()? as char;
+        match c {
+            ':' if self.colon_count == 0 => {
+                self.colon_count += 1;
+                Some((SyntaxKind::COLON, self.split(1)))
+            }
+            _ if common::is_newline(c) => {
+                self.start_of_line = true;
+                self.colon_count = 0;
+                self.indent = 0;
+                let len = common::newline_len(self.input.as_bytes());
+                Some((SyntaxKind::NEWLINE, self.split(len)))
+            }
+            _ if common::is_indent(c) => {
+                let whitespace = self.split(self.span_while(common::is_indent));
+                if self.start_of_line {
+                    self.indent = whitespace.len();
+                    Some((SyntaxKind::INDENT, whitespace))
+                } else {
+                    Some((SyntaxKind::WHITESPACE, whitespace))
                 }
             }
-        } else {
-            None
+            '#' if self.start_of_line && (self.indent == 0 || self.indented_comments) => {
+                self.colon_count = 0;
+                Some((SyntaxKind::COMMENT, self.split(self.line_end())))
+            }
+            _ if common::is_valid_initial_key_char(c) && self.start_of_line && self.indent == 0 => {
+                self.start_of_line = false;
+                Some((
+                    SyntaxKind::KEY,
+                    self.split(self.span_while(common::is_valid_key_char)),
+                ))
+            }
+            _ if !self.start_of_line || self.indent > 0 => {
+                Some((SyntaxKind::VALUE, self.split(self.line_end())))
+            }
+            _ => {
+                let len = self.input.chars().next().map_or(1, char::len_utf8);
+                Some((SyntaxKind::ERROR, self.split(len)))
+            }
         }
-    })
+    }
+}
+
+fn lex_(
+    input: &str,
+    start_of_line: bool,
+    indented_comments: bool,
+) -> impl Iterator<Item = (SyntaxKind, &str)> {
+    Lexer::new(input, start_of_line, indented_comments)
 }
 
 pub(crate) fn lex(input: &str) -> impl Iterator<Item = (SyntaxKind, &str)> {
-    lex_(input, true)
+    lex_(input, true, true)
+}
+
+/// Lex `input`, treating `#` lines as comments only if they start in the first column.
+///
+/// Indented `#` lines are then lexed as values, as dpkg does for files that can not
+/// contain comments.
+pub(crate) fn lex_unindented_comments(input: &str) -> impl Iterator<Item = (SyntaxKind, &str)> {
+    lex_(input, true, false)
 }
 
 pub(crate) fn lex_inline(input: &str) -> impl Iterator<Item = (SyntaxKind, &str)> {
-    lex_(input, false)
+    lex_(input, false, true)
 }
 
 #[cfg(test)]
@@ -265,6 +327,31 @@ Section: vcs
         assert_eq!(tokens.collect::<Vec<_>>(), vec![(VALUE, "syncthing-gtk")]);
     }
 
+    #[test]
+    fn test_lex_non_ascii() {
+        let text = "Maintainer: Jelmer Vernooĳ\näöü: foo\n";
+
+        let tokens = super::lex(text);
+
+        assert_eq!(
+            tokens.collect::<Vec<_>>(),
+            vec![
+                (KEY, "Maintainer"),
+                (COLON, ":"),
+                (WHITESPACE, " "),
+                (VALUE, "Jelmer Vernooĳ"),
+                (NEWLINE, "\n"),
+                (ERROR, "ä"),
+                (ERROR, "ö"),
+                (ERROR, "ü"),
+                (COLON, ":"),
+                (INDENT, " "),
+                (VALUE, "foo"),
+                (NEWLINE, "\n"),
+            ]
+        );
+    }
+
     #[test]
     fn test_lex_odd_key_characters() {
         let text = "foo-bar: baz\n";
@@ -282,4 +369,49 @@ Section: vcs
             ]
         );
     }
+
+    #[test]
+    fn test_lex_crlf() {
+        assert_eq!(
+            super::lex("A: b\r\n c\r\n\r\n# d\r\n").collect::<Vec<_>>(),
+            vec![
+                (KEY, "A"),
+                (COLON, ":"),
+                (WHITESPACE, " "),
+                (VALUE, "b"),
+                (NEWLINE, "\r\n"),
+                (INDENT, " "),
+                (VALUE, "c"),
+                (NEWLINE, "\r\n"),
+                (NEWLINE, "\r\n"),
+                (COMMENT, "# d"),
+                (NEWLINE, "\r\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lex_unindented_comments() {
+        assert_eq!(
+            super::lex_unindented_comments(
+                "# a
+A: b
+ # c
+"
+            )
+            .collect::<Vec<_>>(),
+            vec![
+                (COMMENT, "# a"),
+                (NEWLINE, "\n"),
+                (KEY, "A"),
+                (COLON, ":"),
+                (WHITESPACE, " "),
+                (VALUE, "b"),
+                (NEWLINE, "\n"),
+                (INDENT, " "),
+                (VALUE, "# c"),
+                (NEWLINE, "\n"),
+            ]
+        );
+    }
 }
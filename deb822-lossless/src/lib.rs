@@ -3,16 +3,71 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 // Until we drop support for PyO3 0.22, allow use of deprecated functions.
 #![allow(deprecated)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+#[cfg(feature = "cache")]
+pub mod cache;
 mod common;
+#[cfg(feature = "std")]
+pub mod compression;
 pub mod convert;
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "miette")]
+pub mod diagnostics;
+pub mod diff;
+pub mod folding;
+#[cfg(feature = "std")]
+pub mod history;
+#[cfg(feature = "std")]
+pub mod index;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod lazy;
 mod lex;
+#[cfg(feature = "std")]
 pub mod lossless;
 pub mod lossy;
-pub use convert::{FromDeb822Paragraph, ToDeb822Paragraph};
+pub mod options;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "std")]
+pub mod query;
+pub mod semantic;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod signed;
+#[cfg(feature = "std")]
+pub mod template;
+#[cfg(feature = "std")]
+pub mod visit;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub use common::write_atomic;
+pub use convert::{FieldError, FromDeb822Paragraph, FromParagraphError, ToDeb822Paragraph};
+#[cfg(feature = "serde")]
+pub use de::from_str;
 #[cfg(feature = "derive")]
 pub use deb822_derive::{FromDeb822, ToDeb822};
-pub use lossless::{Deb822, Error, Paragraph, ParseError};
+#[cfg(feature = "std")]
+pub use index::Deb822Index;
+#[cfg(feature = "std")]
+pub use lossless::{Deb822, Error, FileError, Paragraph, ParseError};
+pub use options::ParseOptions;
+#[cfg(feature = "std")]
+pub use rowan;
+pub use semantic::{Semantic, SemanticEq};
+#[cfg(feature = "serde")]
+pub use ser::to_string;
 
 /// The indentation to use when writing a deb822 file.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -33,11 +33,9 @@
 //! assert_eq!(homepage.as_deref(), Some("https://github.com/jelmer/deb822-lossless"));
 //! ```
 
-use crate::{
-    lex::lex,
-    lex::SyntaxKind::{self, *},
-    Indentation,
-};
+use crate::convert::Separator;
+pub use crate::lex::SyntaxKind;
+use crate::{lex::lex, lex::SyntaxKind::*, Indentation};
 use rowan::ast::AstNode;
 use std::path::Path;
 use std::str::FromStr;
@@ -57,6 +55,126 @@ impl std::fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+/// The first difference between the input of the parser and the text of the resulting tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RoundtripError {
+    /// Byte offset of the difference in the input.
+    pub offset: usize,
+
+    /// Line number of the difference, starting at 1.
+    pub line: usize,
+
+    /// Column number of the difference, in characters and starting at 1.
+    pub column: usize,
+
+    /// The remainder of the line in the input, from the difference onwards.
+    pub expected: String,
+
+    /// The remainder of the line in the tree, from the difference onwards.
+    pub found: String,
+}
+
+impl RoundtripError {
+    /// Find the first difference between `expected` and `found`, if any.
+    fn find(expected: &str, found: &str) -> Option<Self> {
+        let offset = expected
+            .char_indices()
+            .zip(found.chars())
+            .find(|((_, a), b)| a != b)
+            .map(|((i, _), _)| i)
+            .unwrap_or_else(|| expected.len().min(found.len()));
+        if offset == expected.len() && offset == found.len() {
+            return None;
+        }
+        let (line, column) = line_column(expected, offset);
+        let rest_of_line = |text: &str| {
+            let rest = &text[offset..];
+            rest[..rest.find('\n').unwrap_or(rest.len())].to_string()
+        };
+        Some(Self {
+            offset,
+            line,
+            column,
+            expected: rest_of_line(expected),
+            found: rest_of_line(found),
+        })
+    }
+}
+
+/// A syntax error in a deb822 file, as returned by [`Deb822::from_str_recovering`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxError {
+    /// A description of the error.
+    pub message: String,
+
+    /// The location of the error.
+    pub span: crate::lossy::Span,
+}
+
+impl SyntaxError {
+    fn new(text: &str, message: &str, range: std::ops::Range<usize>) -> Self {
+        let (line, column) = line_column(text, range.start);
+        Self {
+            message: message.to_string(),
+            span: crate::lossy::Span {
+                start: range.start,
+                end: range.end,
+                line,
+                column,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.span.line, self.span.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Return the line and column, both starting at 1, of the byte offset `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    (
+        text[..offset].matches('\n').count() + 1,
+        text[line_start..offset].chars().count() + 1,
+    )
+}
+
+impl std::fmt::Display for RoundtripError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: round trip differs from input: expected {:?}, found {:?}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for RoundtripError {}
+
+/// Check that parsing `input` into a lossless tree reproduces it byte-for-byte.
+///
+/// Syntax errors are not reported; the tree retains text it can't parse as well. Returns the
+/// first position at which the text of the tree differs from the input.
+///
+/// # Examples
+///
+/// ```
+/// use deb822_lossless::lossless::verify_roundtrip;
+/// assert_eq!(verify_roundtrip("Source: foo\n# comment\n\nPackage: bar\r\n"), Ok(()));
+/// ```
+pub fn verify_roundtrip(input: &str) -> Result<(), RoundtripError> {
+    let (deb822, _) = Deb822::from_str_relaxed(input);
+    deb822.verify_roundtrip(input)
+}
+
 /// Error parsing deb822 control files
 #[derive(Debug)]
 pub enum Error {
@@ -65,6 +183,12 @@ pub enum Error {
 
     /// An I/O error was encountered while reading the file.
     IoError(std::io::Error),
+
+    /// The input was not valid UTF-8.
+    InvalidUtf8(core::str::Utf8Error),
+
+    /// The input exceeded [`crate::ParseOptions::max_input_size`].
+    InputTooLarge,
 }
 
 impl std::fmt::Display for Error {
@@ -72,6 +196,8 @@ impl std::fmt::Display for Error {
         match &self {
             Error::ParseError(err) => write!(f, "{}", err),
             Error::IoError(err) => write!(f, "{}", err),
+            Error::InvalidUtf8(err) => write!(f, "Invalid UTF-8: {}", err),
+            Error::InputTooLarge => f.write_str("Input too large"),
         }
     }
 }
@@ -90,6 +216,46 @@ impl From<std::io::Error> for Error {
 
 impl std::error::Error for Error {}
 
+/// Error reading or writing a file, recording the path of the file
+///
+/// # Examples
+///
+/// ```
+/// use deb822_lossless::{Deb822, FileError};
+/// let err = FileError::new("debian/control", "Source foo\n".parse::<Deb822>().unwrap_err());
+/// assert!(err.to_string().starts_with("debian/control: "));
+/// ```
+#[derive(Debug)]
+pub struct FileError {
+    /// The path of the file.
+    pub path: std::path::PathBuf,
+
+    /// The underlying error.
+    pub error: Error,
+}
+
+impl FileError {
+    /// Create a new error for the file at `path`.
+    pub fn new(path: impl Into<std::path::PathBuf>, error: impl Into<Error>) -> Self {
+        Self {
+            path: path.into(),
+            error: error.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 /// Second, implementing the `Language` trait teaches rowan to convert between
 /// these two SyntaxKind types, allowing for a nicer SyntaxNode API where
 /// "kinds" are values from our `enum SyntaxKind`, instead of plain u16 values.
@@ -120,21 +286,39 @@ struct Parse {
     green_node: GreenNode,
     #[allow(unused)]
     errors: Vec<String>,
+    /// The byte ranges of the input that `errors` refer to.
+    error_ranges: Vec<std::ops::Range<usize>>,
 }
 
 fn parse(text: &str) -> Parse {
-    struct Parser {
+    parse_with(text, true)
+}
+
+/// Parse `text`, treating indented `#` lines as comments only if `indented_comments` is set.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(len = text.len()))
+)]
+fn parse_with(text: &str, indented_comments: bool) -> Parse {
+    struct Parser<'a> {
         /// input tokens, including whitespace,
         /// in *reverse* order.
-        tokens: Vec<(SyntaxKind, String)>,
+        ///
+        /// The token text borrows from the input, so lexing doesn't allocate a string per
+        /// token; the builder interns the text of identical tokens.
+        tokens: Vec<(SyntaxKind, &'a str)>,
         /// the in-progress tree.
         builder: GreenNodeBuilder<'static>,
         /// the list of syntax errors we've accumulated
         /// so far.
         errors: Vec<String>,
+        /// the byte ranges that the errors refer to.
+        error_ranges: Vec<std::ops::Range<usize>>,
+        /// the number of bytes of input consumed so far.
+        offset: usize,
     }
 
-    impl Parser {
+    impl Parser<'_> {
         fn parse_entry(&mut self) {
             while self.current() == Some(COMMENT) {
                 self.bump();
@@ -147,9 +331,10 @@ fn parse(text: &str) -> Parse {
                         return;
                     }
                     Some(g) => {
+                        let start = self.offset;
                         self.builder.start_node(ERROR.into());
                         self.bump();
-                        self.errors.push(format!("expected newline, got {:?}", g));
+                        self.error(format!("expected newline, got {:?}", g), start);
                         self.builder.finish_node();
                     }
                 }
@@ -162,23 +347,24 @@ fn parse(text: &str) -> Parse {
                 self.bump();
                 self.skip_ws();
             } else {
+                let start = self.offset;
                 self.builder.start_node(ERROR.into());
                 if self.current().is_some() {
                     self.bump();
                 }
-                self.errors.push("expected key".to_string());
+                self.error("expected key".to_string(), start);
                 self.builder.finish_node();
             }
             if self.current() == Some(COLON) {
                 self.bump();
                 self.skip_ws();
             } else {
+                let start = self.offset;
                 self.builder.start_node(ERROR.into());
                 if self.current().is_some() {
                     self.bump();
                 }
-                self.errors
-                    .push(format!("expected ':', got {:?}", self.current()));
+                self.error(format!("expected ':', got {:?}", self.current()), start);
                 self.builder.finish_node();
             }
             loop {
@@ -194,9 +380,10 @@ fn parse(text: &str) -> Parse {
                         self.bump();
                     }
                     Some(g) => {
+                        let start = self.offset;
                         self.builder.start_node(ERROR.into());
                         self.bump();
-                        self.errors.push(format!("expected newline, got {:?}", g));
+                        self.error(format!("expected newline, got {:?}", g), start);
                         self.builder.finish_node();
                     }
                 }
@@ -236,12 +423,19 @@ fn parse(text: &str) -> Parse {
             Parse {
                 green_node: self.builder.finish(),
                 errors: self.errors,
+                error_ranges: self.error_ranges,
             }
         }
+        /// Record a syntax error covering the input from `start` up to the current position.
+        fn error(&mut self, message: String, start: usize) {
+            self.errors.push(message);
+            self.error_ranges.push(start..self.offset);
+        }
         /// Advance one token, adding it to the current branch of the tree builder.
         fn bump(&mut self) {
             let (kind, text) = self.tokens.pop().unwrap();
-            self.builder.token(kind.into(), text.as_str());
+            self.offset += text.len();
+            self.builder.token(kind.into(), text);
         }
         /// Peek at the first unprocessed token
         fn current(&self) -> Option<SyntaxKind> {
@@ -269,29 +463,45 @@ fn parse(text: &str) -> Parse {
         }
     }
 
-    let mut tokens = lex(text)
-        .map(|(k, t)| (k, t.to_string()))
-        .collect::<Vec<_>>();
+    let mut tokens = if indented_comments {
+        lex(text).collect::<Vec<_>>()
+    } else {
+        crate::lex::lex_unindented_comments(text).collect::<Vec<_>>()
+    };
     tokens.reverse();
-    Parser {
+    let parse = Parser {
         tokens,
         builder: GreenNodeBuilder::new(),
         errors: Vec::new(),
-    }
-    .parse()
+        error_ranges: Vec::new(),
+        offset: 0,
+    }
+    .parse();
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        paragraphs = parse
+            .green_node
+            .children()
+            .filter(|c| c.kind() == PARAGRAPH.into())
+            .count(),
+        errors = parse.errors.len(),
+        "parsed deb822 text"
+    );
+    parse
 }
 
+/// A node in the syntax tree.
+///
 /// To work with the parse results we need a view into the
 /// green tree - the Syntax tree.
 /// It is also immutable, like a GreenNode,
 /// but it contains parent pointers, offsets, and
 /// has identity semantics.
-
-type SyntaxNode = rowan::SyntaxNode<Lang>;
-#[allow(unused)]
-type SyntaxToken = rowan::SyntaxToken<Lang>;
-#[allow(unused)]
-type SyntaxElement = rowan::NodeOrToken<SyntaxNode, SyntaxToken>;
+pub type SyntaxNode = rowan::SyntaxNode<Lang>;
+/// A token in the syntax tree, such as a [`SyntaxKind::KEY`] or a [`SyntaxKind::COMMENT`].
+pub type SyntaxToken = rowan::SyntaxToken<Lang>;
+/// A node or a token in the syntax tree.
+pub type SyntaxElement = rowan::NodeOrToken<SyntaxNode, SyntaxToken>;
 
 impl Parse {
     #[cfg(test)]
@@ -309,9 +519,29 @@ macro_rules! ast_node {
         #[doc = "An AST node representing a `"]
         #[doc = stringify!($ast)]
         #[doc = "`."]
-        #[derive(Debug, PartialEq, Eq, Hash)]
+        #[derive(Debug)]
         #[repr(transparent)]
         pub struct $ast(SyntaxNode);
+
+        /// Nodes are equal if their text, including comments and formatting, is identical.
+        ///
+        /// See [`crate::SemanticEq`] for a comparison that ignores formatting.
+        impl PartialEq for $ast {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.text() == other.0.text()
+            }
+        }
+
+        impl Eq for $ast {}
+
+        impl std::hash::Hash for $ast {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0
+                    .text()
+                    .for_each_chunk(|chunk| state.write(chunk.as_bytes()));
+                state.write_u8(0xff);
+            }
+        }
         impl $ast {
             #[allow(unused)]
             fn cast(node: SyntaxNode) -> Option<Self> {
@@ -383,6 +613,7 @@ impl Deb822 {
     /// * `sort_entries` - If set, then this function will sort the entries according to the
     ///               given function.
     #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn wrap_and_sort(
         &self,
         sort_paragraphs: Option<&dyn Fn(&Paragraph, &Paragraph) -> std::cmp::Ordering>,
@@ -491,11 +722,7 @@ impl Deb822 {
         let paragraph = Paragraph::new();
         let mut to_insert = vec![];
         if self.0.children().count() > 0 {
-            let mut builder = GreenNodeBuilder::new();
-            builder.start_node(EMPTY_LINE.into());
-            builder.token(NEWLINE.into(), "\n");
-            builder.finish_node();
-            to_insert.push(SyntaxNode::new_root_mut(builder.finish()).into());
+            to_insert.push(empty_line().into());
         }
         to_insert.push(paragraph.0.clone().into());
         let insertion_point = match index {
@@ -531,10 +758,110 @@ impl Deb822 {
     /// another.set("Y", "Z");
     /// assert_eq!(d.to_string(), "Foo: Baz\n\nY: Z\n\nFoo: Bar\nBaz: Qux\n\nA: B\nC: D\n");
     /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
     pub fn insert_paragraph(&mut self, index: usize) -> Paragraph {
         self.insert_empty_paragraph(self.convert_index(index))
     }
 
+    /// Insert a copy of `paragraph` at the given node index, and return the inserted copy.
+    ///
+    /// Empty lines are added where needed to separate the paragraph from its neighbours.
+    fn insert_paragraph_at(&mut self, index: usize, paragraph: Paragraph) -> Paragraph {
+        let paragraph = Paragraph(SyntaxNode::new_root_mut(paragraph.0.green().into_owned()));
+        let children = self.0.children_with_tokens().collect::<Vec<_>>();
+        let mut to_insert: Vec<SyntaxElement> = vec![paragraph.0.clone().into()];
+        if let Some(prev) = index.checked_sub(1).map(|i| &children[i]) {
+            if let Some(node) = prev.as_node() {
+                append_missing_newline(node);
+            }
+            if !is_blank_line(prev) {
+                to_insert.insert(0, empty_line().into());
+            }
+        }
+        if let Some(next) = children.get(index) {
+            append_missing_newline(&paragraph.0);
+            if !is_blank_line(next) {
+                to_insert.push(empty_line().into());
+            }
+        }
+        self.0.splice_children(index..index, to_insert);
+        paragraph
+    }
+
+    /// Insert a copy of `paragraph` directly after `anchor`, and return the inserted copy.
+    ///
+    /// The new paragraph is separated from its neighbours by empty lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `anchor` is not a paragraph in this file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let mut d: Deb822 = "Package: a\n\nPackage: c\n".parse().unwrap();
+    /// let anchor = d.paragraphs().next().unwrap();
+    /// d.insert_paragraph_after(&anchor, vec![("Package", "b")].into());
+    /// assert_eq!(d.to_string(), "Package: a\n\nPackage: b\n\nPackage: c\n");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn insert_paragraph_after(
+        &mut self,
+        anchor: &Paragraph,
+        paragraph: Paragraph,
+    ) -> Paragraph {
+        assert_eq!(
+            anchor.0.parent().as_ref(),
+            Some(&self.0),
+            "anchor is not a paragraph in this file"
+        );
+        self.insert_paragraph_at(anchor.0.index() + 1, paragraph)
+    }
+
+    /// Insert a copy of `paragraph` directly before `anchor`, and return the inserted copy.
+    ///
+    /// Comment lines directly above `anchor` stay attached to it, so the new paragraph is
+    /// inserted above them. The new paragraph is separated from its neighbours by empty lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `anchor` is not a paragraph in this file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let mut d: Deb822 = "Source: foo\n\n# The C library\nPackage: libfoo1\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let anchor = d.paragraphs().nth(1).unwrap();
+    /// d.insert_paragraph_before(&anchor, vec![("Package", "foo")].into());
+    /// assert_eq!(
+    ///     d.to_string(),
+    ///     "Source: foo\n\nPackage: foo\n\n# The C library\nPackage: libfoo1\n"
+    /// );
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn insert_paragraph_before(
+        &mut self,
+        anchor: &Paragraph,
+        paragraph: Paragraph,
+    ) -> Paragraph {
+        assert_eq!(
+            anchor.0.parent().as_ref(),
+            Some(&self.0),
+            "anchor is not a paragraph in this file"
+        );
+        let mut index = anchor.0.index();
+        let mut prev = anchor.0.prev_sibling_or_token();
+        while let Some(comment) = prev.filter(|e| e.kind() == EMPTY_LINE && !is_blank_line(e)) {
+            index = comment.index();
+            prev = comment.prev_sibling_or_token();
+        }
+        self.insert_paragraph_at(index, paragraph)
+    }
+
     /// Remove the paragraph at the specified index from the file.
     ///
     /// # Examples
@@ -552,6 +879,7 @@ impl Deb822 {
     /// d.remove_paragraph(0);
     /// assert_eq!(d.to_string(), "");
     /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
     pub fn remove_paragraph(&mut self, index: usize) {
         if let Some(index) = self.convert_index(index) {
             self.0.splice_children(index..index + 1, []);
@@ -559,18 +887,305 @@ impl Deb822 {
         }
     }
 
+    /// Remove the paragraphs for which `keep` returns false, and return them.
+    ///
+    /// Comment lines directly above a removed paragraph and the empty lines that separate it
+    /// from the next paragraph are removed along with it. Everything else is left untouched.
+    fn remove_paragraphs_where(
+        &mut self,
+        mut keep: impl FnMut(&Paragraph) -> bool,
+    ) -> Vec<Paragraph> {
+        let children = self.0.children_with_tokens().collect::<Vec<_>>();
+        let mut remove = vec![false; children.len()];
+        let mut removed = vec![];
+        for (i, child) in children.iter().enumerate() {
+            let Some(paragraph) = child.as_node().cloned().and_then(Paragraph::cast) else {
+                continue;
+            };
+            if keep(&paragraph) {
+                continue;
+            }
+            remove[i] = true;
+            for j in (0..i).rev() {
+                if children[j].kind() != EMPTY_LINE || is_blank_line(&children[j]) {
+                    break;
+                }
+                remove[j] = true;
+            }
+            for j in i + 1..children.len() {
+                if !is_blank_line(&children[j]) {
+                    break;
+                }
+                remove[j] = true;
+            }
+            removed.push(paragraph);
+        }
+        // Don't leave the separator of the last remaining paragraph dangling at the end of the
+        // file.
+        let last = children
+            .iter()
+            .enumerate()
+            .rposition(|(i, c)| !remove[i] && !is_blank_line(c));
+        let start = last.map_or(0, |i| i + 1);
+        if remove[start..].iter().any(|r| *r) {
+            remove[start..].iter_mut().for_each(|r| *r = true);
+        }
+        // splice_children doesn't cope with deleting more than one child at a time, so detach
+        // them one by one.
+        for (child, remove) in children.into_iter().zip(remove) {
+            if remove {
+                child.detach();
+            }
+        }
+        removed
+    }
+
+    /// Keep only the paragraphs for which `f` returns true.
+    ///
+    /// The separators and comments around the remaining paragraphs are preserved; comment
+    /// lines directly above a removed paragraph are removed along with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let mut d: Deb822 = concat!(
+    ///     "Source: hello\n\n",
+    ///     "Package: hello\n\n",
+    ///     "# Installer variant\n",
+    ///     "Package: hello-udeb\nPackage-Type: udeb\n\n",
+    ///     "Package: hello-doc\n",
+    /// )
+    /// .parse()
+    /// .unwrap();
+    /// d.retain_paragraphs(|p| p.get("Package-Type").as_deref() != Some("udeb"));
+    /// assert_eq!(d.to_string(), "Source: hello\n\nPackage: hello\n\nPackage: hello-doc\n");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn retain_paragraphs(&mut self, mut f: impl FnMut(&Paragraph) -> bool) {
+        self.remove_paragraphs_where(|p| f(p));
+    }
+
+    /// Remove the paragraphs for which `predicate` returns true, and return them as a new file.
+    ///
+    /// The file is modified as by [`Deb822::retain_paragraphs`] with the inverse predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let mut d: Deb822 = "Source: hello\n\nPackage: hello\n\nPackage: hello-doc\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let binaries = d.extract_paragraphs(|p| p.contains_key("Package"));
+    /// assert_eq!(d.to_string(), "Source: hello\n");
+    /// assert_eq!(binaries.to_string(), "Package: hello\n\nPackage: hello-doc\n");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn extract_paragraphs(&mut self, mut predicate: impl FnMut(&Paragraph) -> bool) -> Deb822 {
+        self.remove_paragraphs_where(|p| !predicate(p))
+            .into_iter()
+            .collect()
+    }
+
+    /// Sort the paragraphs in place, using the given comparison function.
+    ///
+    /// The sort is stable. Comment lines directly above a paragraph move along with it, and
+    /// the contents of the paragraphs are left untouched. The empty lines separating the
+    /// paragraphs and any comments not attached to a paragraph stay where they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let mut d: Deb822 = concat!(
+    ///     "Source: hello\n\n",
+    ///     "# The documentation\n",
+    ///     "Package: hello-doc\n\n",
+    ///     "Package: hello\n",
+    ///     "Depends: libc6,\n",
+    ///     "  hello-data\n",
+    /// )
+    /// .parse()
+    /// .unwrap();
+    /// d.sort_paragraphs_by(|a, b| a.get("Package").cmp(&b.get("Package")));
+    /// assert_eq!(
+    ///     d.to_string(),
+    ///     concat!(
+    ///         "Source: hello\n\n",
+    ///         "Package: hello\n",
+    ///         "Depends: libc6,\n",
+    ///         "  hello-data\n\n",
+    ///         "# The documentation\n",
+    ///         "Package: hello-doc\n",
+    ///     )
+    /// );
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn sort_paragraphs_by(
+        &mut self,
+        mut compare: impl FnMut(&Paragraph, &Paragraph) -> std::cmp::Ordering,
+    ) {
+        let children = self.0.children_with_tokens().collect::<Vec<_>>();
+        let is_comment = |c: &SyntaxElement| {
+            c.kind() == EMPTY_LINE
+                && c.as_node()
+                    .is_some_and(|n| n.children_with_tokens().any(|t| t.kind() == COMMENT))
+        };
+        // Split the children into the paragraphs with the comments directly above them, and
+        // everything else.
+        let mut units: Vec<(Paragraph, Vec<SyntaxElement>)> = vec![];
+        let mut layout: Vec<Option<SyntaxElement>> = vec![];
+        let mut pending = vec![];
+        for child in children.iter() {
+            if let Some(paragraph) = child.as_node().cloned().and_then(Paragraph::cast) {
+                pending.push(child.clone());
+                units.push((paragraph, std::mem::take(&mut pending)));
+                layout.push(None);
+            } else if is_comment(child) {
+                pending.push(child.clone());
+            } else {
+                layout.extend(pending.drain(..).map(Some));
+                layout.push(Some(child.clone()));
+            }
+        }
+        layout.extend(pending.into_iter().map(Some));
+
+        units.sort_by(|a, b| compare(&a.0, &b.0));
+
+        // The last paragraph in a file may lack a final newline, which it needs if it is no
+        // longer last.
+        for (paragraph, _) in units.iter().rev().skip(1) {
+            append_missing_newline(&paragraph.0);
+        }
+
+        let mut units = units.into_iter();
+        let ordered = layout
+            .into_iter()
+            .flat_map(|slot| match slot {
+                Some(element) => vec![element],
+                None => units
+                    .next()
+                    .map(|(_, elements)| elements)
+                    .unwrap_or_default(),
+            })
+            .collect::<Vec<_>>();
+        if ordered == children {
+            return;
+        }
+        // splice_children doesn't cope with deleting more than one child at a time, so detach
+        // them one by one.
+        for child in children {
+            child.detach();
+        }
+        self.0.splice_children(0..0, ordered);
+    }
+
     /// Add a new empty paragraph to the end of the file.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
     pub fn add_paragraph(&mut self) -> Paragraph {
         self.insert_empty_paragraph(None)
     }
 
+    /// Append the given elements, copied from another tree, to the end of the file.
+    ///
+    /// Leading empty lines are dropped, and the elements are separated from the existing
+    /// contents of the file by a single empty line.
+    fn append_elements(&mut self, elements: Vec<SyntaxElement>) {
+        let mut elements = elements
+            .into_iter()
+            .skip_while(is_blank_line)
+            .map(|element| match element {
+                rowan::NodeOrToken::Node(node) => {
+                    SyntaxNode::new_root_mut(node.green().into_owned()).into()
+                }
+                rowan::NodeOrToken::Token(token) => token.into(),
+            })
+            .collect::<Vec<SyntaxElement>>();
+        if elements.is_empty() {
+            return;
+        }
+        if let Some(last) = self.0.last_child() {
+            append_missing_newline(&last);
+            if !is_blank_line(&last.clone().into()) {
+                elements.insert(0, empty_line().into());
+            }
+        }
+        let count = self.0.children_with_tokens().count();
+        self.0.splice_children(count..count, elements);
+    }
+
+    /// Append a copy of a paragraph to the end of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::{Deb822, Paragraph};
+    /// let mut d: Deb822 = "Package: foo".parse().unwrap();
+    /// d.push(vec![("Package", "bar")].into());
+    /// assert_eq!(d.to_string(), "Package: foo\n\nPackage: bar\n");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn push(&mut self, paragraph: Paragraph) {
+        self.append_elements(vec![paragraph.0.into()]);
+    }
+
+    /// Append the contents of another file to the end of this one.
+    ///
+    /// Comments in `other` are kept. A single empty line is added between the two files if
+    /// this one doesn't already end with one, and a missing final newline is added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let mut main: Deb822 = "Package: foo\n".parse().unwrap();
+    /// let contrib: Deb822 = "\n# contrib\nPackage: bar\n\nPackage: baz\n".parse().unwrap();
+    /// main.extend(contrib);
+    /// assert_eq!(
+    ///     main.to_string(),
+    ///     "Package: foo\n\n# contrib\nPackage: bar\n\nPackage: baz\n"
+    /// );
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn extend(&mut self, other: Deb822) {
+        self.append_elements(other.0.children_with_tokens().collect());
+    }
+
+    /// Return a snapshot of the current contents of the file.
+    pub(crate) fn snapshot(&self) -> GreenNode {
+        self.0.green().into_owned()
+    }
+
+    /// Replace the contents of the file with a snapshot taken earlier.
+    ///
+    /// The root node is kept, so existing references to the file remain valid.
+    pub(crate) fn restore(&mut self, green: &GreenNode) {
+        // Detach the existing children one by one; splice_children doesn't cope with
+        // deleting more than one child at a time.
+        for child in self.0.children_with_tokens().collect::<Vec<_>>() {
+            child.detach();
+        }
+        let root = SyntaxNode::new_root_mut(green.clone());
+        let children = root.children_with_tokens().collect::<Vec<_>>();
+        self.0.splice_children(0..0, children);
+    }
+
     /// Read a deb822 file from the given path.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+    )]
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
         let text = std::fs::read_to_string(path)?;
         Ok(Self::from_str(&text)?)
     }
 
     /// Read a deb822 file from the given path, ignoring any syntax errors.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+    )]
     pub fn from_file_relaxed(
         path: impl AsRef<Path>,
     ) -> Result<(Self, Vec<String>), std::io::Error> {
@@ -578,17 +1193,201 @@ impl Deb822 {
         Ok(Self::from_str_relaxed(&text))
     }
 
+    /// Parse a deb822 file from a string, allowing syntax errors and reporting their
+    /// locations.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::Deb822;
+    /// let text = "Package: foo\nbroken value\n";
+    /// let (deb822, errors) = Deb822::from_str_recovering(text);
+    /// assert_eq!(deb822.to_string(), text);
+    /// assert_eq!(errors[0].to_string(), "2:8: expected ':', got Some(NEWLINE)");
+    /// assert_eq!(&text[errors[0].span.range()], "value");
+    /// ```
+    pub fn from_str_recovering(s: &str) -> (Self, Vec<SyntaxError>) {
+        let parsed = parse(s);
+        let errors = parsed
+            .errors
+            .iter()
+            .zip(&parsed.error_ranges)
+            .map(|(message, range)| SyntaxError::new(s, message, range.clone()))
+            .collect();
+        (parsed.root_mut(), errors)
+    }
+
     /// Parse a deb822 file from a string, allowing syntax errors.
     pub fn from_str_relaxed(s: &str) -> (Self, Vec<String>) {
-        let parsed = parse(s);
+        Self::from_parse(parse(s))
+    }
+
+    fn from_parse(parsed: Parse) -> (Self, Vec<String>) {
+        #[cfg(feature = "tracing")]
+        if !parsed.errors.is_empty() {
+            tracing::warn!(
+                errors = parsed.errors.len(),
+                first_error = %parsed.errors[0],
+                "recovered from syntax errors"
+            );
+        }
         (parsed.root_mut(), parsed.errors)
     }
 
+    /// Check that the text of the file is identical to `input`, e.g. the text it was parsed
+    /// from before it was edited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use deb822_lossless::Deb822;
+    /// let input = "Source: foo\nSection: net\n";
+    /// let mut d: Deb822 = input.parse().unwrap();
+    /// d.paragraphs().next().unwrap().set("Section", "web");
+    /// let err = d.verify_roundtrip(input).unwrap_err();
+    /// assert_eq!((err.line, err.column), (2, 10));
+    /// assert_eq!(err.expected, "net");
+    /// assert_eq!(err.found, "web");
+    /// ```
+    pub fn verify_roundtrip(&self, input: &str) -> Result<(), RoundtripError> {
+        match RoundtripError::find(input, &self.0.text().to_string()) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse a deb822 file from a string, using the given options.
+    ///
+    /// Note that the normalizations enabled in `options` are applied to the text before it is
+    /// parsed, so they are reflected in the resulting tree.
+    pub fn from_str_with(s: &str, options: &crate::ParseOptions) -> Result<Self, ParseError> {
+        let (deb822, errors) = Self::from_str_relaxed_with(s, options);
+        if errors.is_empty() {
+            Ok(deb822)
+        } else {
+            Err(ParseError(errors))
+        }
+    }
+
+    /// Parse a deb822 file from a string using the given options, allowing syntax errors.
+    pub fn from_str_relaxed_with(s: &str, options: &crate::ParseOptions) -> (Self, Vec<String>) {
+        let text = options.normalize(s);
+        // Without comments, indented `#` lines are part of the field values.
+        let (deb822, mut errors) = Self::from_parse(parse_with(&text, options.allow_comments));
+        if options.verify_roundtrip {
+            if let Err(err) = deb822.verify_roundtrip(s) {
+                errors.push(err.to_string());
+            }
+        }
+        if !options.allow_comments {
+            errors.extend(
+                deb822
+                    .0
+                    .descendants_with_tokens()
+                    .filter_map(|e| e.into_token())
+                    .filter(|t| t.kind() == COMMENT)
+                    .map(|t| {
+                        let (line, column) = line_column(&text, t.text_range().start().into());
+                        format!("{}:{}: unexpected comment: {}", line, column, t.text())
+                    }),
+            );
+        }
+        if crate::ParseOptions::exceeds(options.max_input_size, text.len()) {
+            errors.push("input too large".to_string());
+        }
+        for paragraph in deb822.paragraphs() {
+            let keys = paragraph.keys().collect::<Vec<_>>();
+            if let Some(key) = options.find_duplicate(keys.iter().map(String::as_str)) {
+                errors.push(format!("duplicate field: {}", key));
+            }
+            let size = usize::from(paragraph.0.text_range().len());
+            if crate::ParseOptions::exceeds(options.max_paragraph_size, size) {
+                errors.push("paragraph too large".to_string());
+            }
+            for entry in paragraph.entries() {
+                let size = usize::from(entry.0.text_range().len());
+                if crate::ParseOptions::exceeds(options.max_field_size, size) {
+                    errors.push(format!(
+                        "field too large: {}",
+                        entry.key().unwrap_or_default()
+                    ));
+                }
+            }
+        }
+        (deb822, errors)
+    }
+
+    /// Parse a deb822 file from raw bytes, which have to be valid UTF-8.
+    ///
+    /// Use [`Deb822::from_bytes_with`] to handle other encodings.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        Self::from_bytes_with(bytes, &crate::ParseOptions::default())
+    }
+
+    /// Parse a deb822 file from raw bytes, decoding them as specified by `options`.
+    pub fn from_bytes_with(
+        bytes: &[u8],
+        options: &crate::ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let text = options
+            .decode(bytes)
+            .map_err(|e| ParseError(vec![e.to_string()]))?;
+        Self::from_str_with(&text, options)
+    }
+
     /// Read a deb822 file from a Read object.
-    pub fn read<R: std::io::Read>(mut r: R) -> Result<Self, Error> {
-        let mut buf = String::new();
-        r.read_to_string(&mut buf)?;
-        Ok(Self::from_str(&buf)?)
+    pub fn read<R: std::io::Read>(r: R) -> Result<Self, Error> {
+        Self::from_reader(r)
+    }
+
+    /// Read a deb822 file from a Read object.
+    ///
+    /// Since the syntax tree retains all of the text, the input is read completely before it
+    /// is parsed. Use [`crate::lossy::Deb822::from_reader`] to parse large files
+    /// incrementally.
+    ///
+    /// # Example
+    /// ```rust
+    /// let input: &[u8] = b"Package: hello\n\nPackage: world\n";
+    /// let deb822 = deb822_lossless::Deb822::from_reader(input).unwrap();
+    /// assert_eq!(deb822.paragraphs().count(), 2);
+    /// ```
+    pub fn from_reader<R: std::io::Read>(r: R) -> Result<Self, Error> {
+        Self::from_reader_with(r, &crate::ParseOptions::default())
+    }
+
+    /// Read a deb822 file from a Read object, using the given options.
+    ///
+    /// The input is decoded as specified by `options`; see [`Deb822::from_str_with`] for the
+    /// other options that apply.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::{Deb822, ParseOptions};
+    /// let input: &[u8] = b"Package: hello\r\nMaintainer: J\xf6rg <joerg@example.com>\r\n";
+    /// let options = ParseOptions::lenient();
+    /// let deb822 = Deb822::from_reader_with(input, &options).unwrap();
+    /// let paragraph = deb822.paragraphs().next().unwrap();
+    /// assert_eq!(paragraph.get("Maintainer").as_deref(), Some("J\u{fffd}rg <joerg@example.com>"));
+    /// ```
+    pub fn from_reader_with<R: std::io::Read>(
+        mut r: R,
+        options: &crate::ParseOptions,
+    ) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        match options.max_input_size {
+            // Read one byte more than allowed, to detect overlong input.
+            Some(max) => {
+                std::io::Read::read_to_end(&mut r.take(max as u64 + 1), &mut buf)?;
+                if buf.len() > max {
+                    return Err(Error::InputTooLarge);
+                }
+            }
+            None => {
+                r.read_to_end(&mut buf)?;
+            }
+        }
+        let text = options.decode(&buf).map_err(Error::InvalidUtf8)?;
+        Ok(Self::from_str_with(&text, options)?)
     }
 
     /// Read a deb822 file from a Read object, allowing syntax errors.
@@ -597,6 +1396,73 @@ impl Deb822 {
         r.read_to_string(&mut buf)?;
         Ok(Self::from_str_relaxed(&buf))
     }
+
+    /// Write the deb822 file to a Write object.
+    pub fn write_to<W: std::io::Write>(&self, mut w: W) -> std::io::Result<()> {
+        w.write_all(self.to_string().as_bytes())
+    }
+
+    /// Write the deb822 file to the given path, atomically replacing any existing file.
+    ///
+    /// The file is first written to a new temporary file in the same directory, which is
+    /// synced to disk and then renamed into place. The permissions of an existing file are
+    /// kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// let path = std::env::temp_dir().join(format!("deb822-save-{}", std::process::id()));
+    /// let deb822: deb822_lossless::Deb822 = "Source: foo\n".parse().unwrap();
+    /// let mut source = deb822.paragraphs().next().unwrap();
+    /// source.set("Section", "net");
+    /// deb822.save_atomic(&path).unwrap();
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "Source: foo\nSection: net\n");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_atomic(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        crate::common::write_atomic(path.as_ref(), self.to_string().as_bytes())
+    }
+}
+
+/// Append a newline to the last entry below `node`, if it lacks one.
+///
+/// Only the last line of a file can lack a newline; this is needed when moving it elsewhere.
+fn append_missing_newline(node: &SyntaxNode) {
+    let node = match node.kind() {
+        ENTRY => node.clone(),
+        _ => match node.children().filter(|n| n.kind() == ENTRY).last() {
+            Some(entry) => entry,
+            None => return,
+        },
+    };
+    if node.last_token().is_some_and(|t| t.kind() != NEWLINE) {
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node(ROOT.into());
+        builder.token(NEWLINE.into(), "\n");
+        builder.finish_node();
+        let newline = SyntaxNode::new_root_mut(builder.finish())
+            .first_token()
+            .unwrap();
+        newline.detach();
+        let count = node.children_with_tokens().count();
+        node.splice_children(count..count, vec![newline.into()]);
+    }
+}
+
+/// Create a new node for an empty line.
+fn empty_line() -> SyntaxNode {
+    let mut builder = GreenNodeBuilder::new();
+    builder.start_node(EMPTY_LINE.into());
+    builder.token(NEWLINE.into(), "\n");
+    builder.finish_node();
+    SyntaxNode::new_root_mut(builder.finish())
+}
+
+/// Check whether an element is an empty line, rather than e.g. a comment.
+fn is_blank_line(element: &SyntaxElement) -> bool {
+    element.kind() == EMPTY_LINE
+        && element
+            .as_node()
+            .is_some_and(|n| n.children_with_tokens().all(|t| t.kind() != COMMENT))
 }
 
 fn inject(builder: &mut GreenNodeBuilder, node: SyntaxNode) {
@@ -690,6 +1556,27 @@ impl<'a> FromIterator<(&'a str, &'a str)> for Paragraph {
 }
 
 impl Paragraph {
+    /// Write the paragraph to a Write object.
+    pub fn write_to<W: std::io::Write>(&self, mut w: W) -> std::io::Result<()> {
+        w.write_all(self.to_string().as_bytes())
+    }
+
+    /// Write the paragraph to the given path, atomically replacing any existing file.
+    ///
+    /// See [`Deb822::save_atomic`].
+    ///
+    /// # Example
+    /// ```rust
+    /// let path = std::env::temp_dir().join(format!("deb822-paragraph-save-{}", std::process::id()));
+    /// let paragraph: deb822_lossless::Paragraph = "Description: Fix a bug\n".parse().unwrap();
+    /// paragraph.save_atomic(&path).unwrap();
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "Description: Fix a bug\n");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_atomic(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        crate::common::write_atomic(path.as_ref(), self.to_string().as_bytes())
+    }
+
     /// Create a new empty paragraph.
     pub fn new() -> Paragraph {
         let mut builder = GreenNodeBuilder::new();
@@ -772,20 +1659,173 @@ impl Paragraph {
         Self(SyntaxNode::new_root_mut(builder.finish()))
     }
 
+    /// Sort the fields in place, using the given comparison function.
+    ///
+    /// The sort is stable. Comment lines directly above a field move along with it, and the
+    /// formatting of the fields is left untouched.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::Paragraph;
+    /// let mut paragraph: Paragraph = "Depends: bar\n# The name\nPackage: foo\n".parse().unwrap();
+    /// paragraph.sort_fields_by(|a, b| (a.key().as_deref() != Some("Package")).cmp(&(b.key().as_deref() != Some("Package"))));
+    /// assert_eq!(paragraph.to_string(), "# The name\nPackage: foo\nDepends: bar\n");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn sort_fields_by(
+        &mut self,
+        mut compare: impl FnMut(&Entry, &Entry) -> std::cmp::Ordering,
+    ) {
+        let children = self.0.children_with_tokens().collect::<Vec<_>>();
+        let mut units: Vec<(Entry, Vec<SyntaxElement>)> = vec![];
+        let mut pending = vec![];
+        for child in children.iter() {
+            pending.push(child.clone());
+            if let Some(entry) = child.as_node().cloned().and_then(Entry::cast) {
+                units.push((entry, std::mem::take(&mut pending)));
+            }
+        }
+
+        units.sort_by(|a, b| compare(&a.0, &b.0));
+
+        for (entry, _) in units.iter().rev().skip(1) {
+            append_missing_newline(&entry.0);
+        }
+        let ordered = units
+            .into_iter()
+            .flat_map(|(_, elements)| elements)
+            .chain(pending)
+            .collect::<Vec<_>>();
+        if ordered == children {
+            return;
+        }
+        // splice_children doesn't cope with deleting more than one child at a time, so detach
+        // them one by one.
+        for child in children {
+            child.detach();
+        }
+        self.0.splice_children(0..0, ordered);
+    }
+
+    /// Reformat a single field in place, leaving the rest of the paragraph untouched.
+    ///
+    /// This is useful when fields need different indentation, or when other fields (such as
+    /// `Description`) have significant leading whitespace that reformatting would lose.
+    ///
+    /// Returns whether the field was present.
+    ///
+    /// # Arguments
+    /// * `key` - The name of the field to reformat
+    /// * `indentation` - The indentation to use
+    /// * `immediate_empty_line` - Whether multi-line values should always start with an empty line
+    /// * `max_line_length_one_liner` - If set, then this is the max length of the value if it is
+    ///     crammed into a "one-liner" value
+    /// * `format_value` - If set, then this function will format the value according to the given
+    ///   function
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::{Indentation, Paragraph};
+    /// let mut paragraph: Paragraph = "Package: foo\nDepends: bar,\n baz\n".parse().unwrap();
+    /// paragraph.wrap_and_sort_field("Depends", Indentation::Spaces(9), false, None, None);
+    /// assert_eq!(paragraph.to_string(), "Package: foo\nDepends: bar,\n         baz\n");
+    /// ```
+    pub fn wrap_and_sort_field(
+        &mut self,
+        key: &str,
+        indentation: Indentation,
+        immediate_empty_line: bool,
+        max_line_length_one_liner: Option<usize>,
+        format_value: Option<&dyn Fn(&str, &str) -> String>,
+    ) -> bool {
+        let entries = self
+            .entries()
+            .filter(|e| e.has_key(key))
+            .collect::<Vec<_>>();
+        for entry in entries.iter() {
+            let index = entry.0.index();
+            let new_entry = entry.wrap_and_sort(
+                indentation,
+                immediate_empty_line,
+                max_line_length_one_liner,
+                format_value,
+            );
+            self.0
+                .splice_children(index..index + 1, vec![new_entry.0.into()]);
+        }
+        !entries.is_empty()
+    }
+
     /// Returns the value of the given key in the paragraph.
     pub fn get(&self, key: &str) -> Option<String> {
+        self.get_ref(key).map(FieldValue::into_owned)
+    }
+
+    /// Returns the value of the given key in the paragraph, without copying it if possible.
+    ///
+    /// This is cheaper than [`Paragraph::get`] when the value is only inspected or parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let paragraph: Paragraph = "Package: foo\nDepends: bar,\n baz\n".parse().unwrap();
+    /// assert_eq!(paragraph.get_ref("Package").unwrap(), "foo");
+    /// assert_eq!(paragraph.get_ref("Depends").unwrap(), "bar,\nbaz");
+    /// assert!(paragraph.get_ref("Version").is_none());
+    /// ```
+    pub fn get_ref(&self, key: &str) -> Option<FieldValue> {
+        self.entries()
+            .find(|e| e.has_key(key))
+            .map(|e| e.value_ref())
+    }
+
+    /// Returns the value of the given key in the paragraph, parsed as `T`.
+    ///
+    /// Returns `None` if the field does not exist, and the parse error if its value can not be
+    /// parsed.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let paragraph: Paragraph = "Installed-Size: 42\nPriority: high\n".parse().unwrap();
+    /// assert_eq!(paragraph.get_parsed::<u64>("Installed-Size"), Some(Ok(42)));
+    /// assert!(paragraph.get_parsed::<u64>("Priority").unwrap().is_err());
+    /// assert_eq!(paragraph.get_parsed::<u64>("Size"), None);
+    /// ```
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Option<Result<T, T::Err>> {
+        self.get_ref(key).map(|value| value.parse())
+    }
+
+    /// Returns the value of the given key in the paragraph, ignoring ASCII case.
+    ///
+    /// Field names are case-insensitive in deb822, so this matches what dpkg does when
+    /// looking up a field. If several fields match, the first one is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let paragraph: Paragraph = "package: foo\n".parse().unwrap();
+    /// assert_eq!(paragraph.get("Package"), None);
+    /// assert_eq!(paragraph.get_ci("Package").as_deref(), Some("foo"));
+    /// ```
+    pub fn get_ci(&self, key: &str) -> Option<String> {
         self.entries()
-            .find(|e| e.key().as_deref() == Some(key))
+            .find(|e| e.has_key_ci(key))
             .map(|e| e.value())
     }
 
     /// Returns whether the paragraph contains the given key.
     pub fn contains_key(&self, key: &str) -> bool {
-        self.get(key).is_some()
+        self.entries().any(|e| e.has_key(key))
+    }
+
+    /// Returns whether the paragraph contains the given key, ignoring ASCII case.
+    pub fn contains_key_ci(&self, key: &str) -> bool {
+        self.entries().any(|e| e.has_key_ci(key))
     }
 
     /// Returns an iterator over all entries in the paragraph.
-    fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
+    pub(crate) fn entries(&self) -> impl Iterator<Item = Entry> + '_ {
         self.0.children().filter_map(Entry::cast)
     }
 
@@ -797,8 +1837,9 @@ impl Paragraph {
 
     /// Returns an iterator over all values for the given key in the paragraph.
     pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = String> + 'a {
-        self.items()
-            .filter_map(move |(k, v)| if k.as_str() == key { Some(v) } else { None })
+        self.entries()
+            .filter(move |e| e.has_key(key))
+            .map(|e| e.value())
     }
 
     /// Returns an iterator over all keys in the paragraph.
@@ -807,15 +1848,163 @@ impl Paragraph {
     }
 
     /// Remove the given field from the paragraph.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
     pub fn remove(&mut self, key: &str) {
         for mut entry in self.entries() {
-            if entry.key().as_deref() == Some(key) {
+            if entry.has_key(key) {
                 entry.detach();
             }
         }
     }
 
+    /// Return the items of a list field, such as `Architecture` or `Binary`.
+    ///
+    /// Surrounding whitespace is stripped from each item and empty items are skipped. If the
+    /// field is not present, the iterator is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::convert::Separator;
+    /// use deb822_lossless::Paragraph;
+    /// let paragraph: Paragraph = "Binary: foo,\n libfoo1 ,\n".parse().unwrap();
+    /// assert_eq!(
+    ///     paragraph.get_list("Binary", Separator::Comma).collect::<Vec<_>>(),
+    ///     vec!["foo", "libfoo1"]
+    /// );
+    /// ```
+    pub fn get_list(&self, key: &str, separator: Separator) -> impl Iterator<Item = String> {
+        let value = self.get(key).unwrap_or_default();
+        crate::convert::split_list(&value, separator.as_pattern())
+            .map(|item| item.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Set a list field, folding it over several lines if it is too long.
+    ///
+    /// The field is removed if `items` is empty. See [`crate::convert::fold_list`] for
+    /// details on the folding.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::convert::Separator;
+    /// use deb822_lossless::Paragraph;
+    /// let mut paragraph: Paragraph = "Source: foo\nArchitecture: all\n".parse().unwrap();
+    /// paragraph.set_list("Architecture", ["amd64", "i386"], Separator::Whitespace);
+    /// assert_eq!(paragraph.to_string(), "Source: foo\nArchitecture: amd64 i386\n");
+    /// ```
+    pub fn set_list<S: AsRef<str>>(
+        &mut self,
+        key: &str,
+        items: impl IntoIterator<Item = S>,
+        separator: Separator,
+    ) {
+        let value = crate::convert::fold_list(key, items, separator);
+        if value.is_empty() {
+            self.remove(key);
+        } else {
+            self.set(key, &value);
+        }
+    }
+
+    /// Iterate over the items of a comma-separated list field, such as `Uploaders`.
+    ///
+    /// This is equivalent to [`Paragraph::get_list`] with [`Separator::Comma`].
+    pub fn get_comma_list(&self, key: &str) -> impl Iterator<Item = String> {
+        self.get_list(key, Separator::Comma)
+    }
+
+    /// Set a comma-separated list field, keeping its current layout where possible.
+    ///
+    /// If the items are currently written one per line, the new items are too, so that
+    /// adding or removing an item doesn't reflow the whole field. See
+    /// [`crate::convert::fold_comma_list_like`] for details. The field is removed if `items`
+    /// is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::Paragraph;
+    /// let mut paragraph: Paragraph = concat!(
+    ///     "Source: foo\n",
+    ///     "Uploaders: Jane <jane@example.com>,\n",
+    ///     "   Joe <joe@example.com>\n",
+    /// )
+    /// .parse()
+    /// .unwrap();
+    /// let mut uploaders = paragraph.get_comma_list("Uploaders").collect::<Vec<_>>();
+    /// uploaders.remove(0);
+    /// uploaders.push("John <john@example.com>".to_string());
+    /// paragraph.set_comma_list("Uploaders", uploaders);
+    /// assert_eq!(
+    ///     paragraph.to_string(),
+    ///     "Source: foo\nUploaders: Joe <joe@example.com>,\n   John <john@example.com>\n"
+    /// );
+    /// ```
+    pub fn set_comma_list<S: AsRef<str>>(&mut self, key: &str, items: impl IntoIterator<Item = S>) {
+        // The value returned by `get` doesn't tell whether it starts on the line of the key.
+        let existing = self.entries().find(|e| e.has_key(key)).map(|entry| {
+            if entry.value_starts_on_next_line() {
+                format!("\n{}", entry.value())
+            } else {
+                entry.value()
+            }
+        });
+        let value = crate::convert::fold_comma_list_like(key, items, existing.as_deref());
+        if value.is_empty() {
+            self.remove(key);
+        } else {
+            self.set(key, &value);
+        }
+    }
+
+    /// Keep only the fields for which `f` returns true.
+    ///
+    /// `f` is called with the name and value of each field, in order. Comment lines directly
+    /// above a removed field are removed along with it.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::Paragraph;
+    /// let mut paragraph: Paragraph = "Package: foo\n# Internal\nX-Build-Id: 42\nSection: net\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// paragraph.retain_fields(|name, _| !name.starts_with("X-"));
+    /// assert_eq!(paragraph.to_string(), "Package: foo\nSection: net\n");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub fn retain_fields(&mut self, mut f: impl FnMut(&str, &str) -> bool) {
+        for mut entry in self.entries().collect::<Vec<_>>() {
+            let Some(key) = entry.key() else {
+                continue;
+            };
+            if f(&key, &entry.value_ref()) {
+                continue;
+            }
+            // Comment lines are stored as COMMENT and NEWLINE tokens in front of the entry.
+            let mut comments = vec![];
+            let mut prev = entry.0.prev_sibling_or_token();
+            while let Some(newline) = prev.filter(|e| e.kind() == NEWLINE) {
+                let Some(comment) = newline
+                    .prev_sibling_or_token()
+                    .filter(|e| e.kind() == COMMENT)
+                else {
+                    break;
+                };
+                prev = comment.prev_sibling_or_token();
+                comments.extend([newline, comment]);
+            }
+            for comment in comments {
+                comment.detach();
+            }
+            entry.detach();
+        }
+    }
+
     /// Insert a new field
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, value))
+    )]
     pub fn insert(&mut self, key: &str, value: &str) {
         let entry = Entry::new(key, value);
         let count = self.0.children_with_tokens().count();
@@ -823,31 +2012,88 @@ impl Paragraph {
     }
 
     /// Set a field in the paragraph
+    ///
+    /// If the field already exists, its value is replaced in place: comments above the field,
+    /// the whitespace after the colon and the indentation of continuation lines are kept.
+    /// Otherwise the field is added at the end of the paragraph.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let mut paragraph: Paragraph = "Source: foo\n# Keep sorted\nUploaders: Jane,\n  Joe\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// paragraph.set("Uploaders", "Jane,\nJoe,\nJohn");
+    /// assert_eq!(
+    ///     paragraph.to_string(),
+    ///     "Source: foo\n# Keep sorted\nUploaders: Jane,\n  Joe,\n  John\n"
+    /// );
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self, value))
+    )]
     pub fn set(&mut self, key: &str, value: &str) {
-        let new_entry = Entry::new(key, value);
-
         for entry in self.entries() {
-            if entry.key().as_deref() == Some(key) {
-                self.0.splice_children(
-                    entry.0.index()..entry.0.index() + 1,
-                    vec![new_entry.0.into()],
-                );
+            if entry.has_key(key) {
+                let index = entry.0.index();
+                self.0
+                    .splice_children(index..index + 1, vec![entry.with_value(value).0.into()]);
                 return;
             }
         }
+        let new_entry = Entry::new(key, value);
         let count = self.0.children_with_tokens().count();
         self.0
             .splice_children(count..count, vec![new_entry.0.into()]);
     }
 
+    /// Set a field in the paragraph to the string representation of `value`.
+    ///
+    /// The field is updated in the same way as by [`Paragraph::set`].
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let mut paragraph: Paragraph = "Package: foo\nInstalled-Size: 42\n".parse().unwrap();
+    /// paragraph.set_parsed("Installed-Size", &43);
+    /// assert_eq!(paragraph.to_string(), "Package: foo\nInstalled-Size: 43\n");
+    /// ```
+    pub fn set_parsed<T: std::fmt::Display>(&mut self, key: &str, value: &T) {
+        self.set(key, &value.to_string());
+    }
+
+    /// Replace the value of one entry of the paragraph, keeping its formatting.
+    pub(crate) fn set_entry_value(&mut self, entry: &Entry, value: &str) {
+        let index = entry.0.index();
+        self.0
+            .splice_children(index..index + 1, vec![entry.with_value(value).0.into()]);
+    }
+
     /// Rename the given field in the paragraph.
+    ///
+    /// Only the name of the field changes; its value, position and comments are kept.
+    /// Returns `false` if the field does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let mut paragraph: Paragraph = "Source: foo\n# Old name\nXS-Testsuite:  autopkgtest\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(paragraph.rename("XS-Testsuite", "Testsuite"));
+    /// assert_eq!(
+    ///     paragraph.to_string(),
+    ///     "Source: foo\n# Old name\nTestsuite:  autopkgtest\n"
+    /// );
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
     pub fn rename(&mut self, old_key: &str, new_key: &str) -> bool {
         for entry in self.entries() {
-            if entry.key().as_deref() == Some(old_key) {
-                self.0.splice_children(
-                    entry.0.index()..entry.0.index() + 1,
-                    vec![Entry::new(new_key, entry.value().as_str()).0.into()],
-                );
+            if entry.has_key(old_key) {
+                let index = entry.0.index();
+                self.0
+                    .splice_children(index..index + 1, vec![entry.with_key(new_key).0.into()]);
                 return true;
             }
         }
@@ -907,10 +2153,15 @@ impl Entry {
         builder.start_node(ENTRY.into());
         builder.token(KEY.into(), key);
         builder.token(COLON.into(), ":");
-        builder.token(WHITESPACE.into(), " ");
         for (i, line) in value.split('\n').enumerate() {
             if i > 0 {
                 builder.token(INDENT.into(), " ");
+            } else if line.is_empty() && value.contains('\n') {
+                // The value starts on the next line; avoid trailing whitespace.
+                builder.token(NEWLINE.into(), "\n");
+                continue;
+            } else {
+                builder.token(WHITESPACE.into(), " ");
             }
             builder.token(VALUE.into(), line);
             builder.token(NEWLINE.into(), "\n");
@@ -1022,7 +2273,73 @@ impl Entry {
         );
 
         builder.finish_node();
-        Self(SyntaxNode::new_root_mut(builder.finish()))
+        Self(SyntaxNode::new_root_mut(builder.finish()))
+    }
+
+    /// Return a copy of this entry with a different key, keeping everything else.
+    fn with_key(&self, key: &str) -> Entry {
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node(ENTRY.into());
+        for child in self.0.children_with_tokens() {
+            match child {
+                rowan::NodeOrToken::Node(node) => inject(&mut builder, node),
+                rowan::NodeOrToken::Token(token) if token.kind() == KEY => {
+                    builder.token(KEY.into(), key)
+                }
+                rowan::NodeOrToken::Token(token) => {
+                    builder.token(token.kind().into(), token.text())
+                }
+            }
+        }
+        builder.finish_node();
+        Entry(SyntaxNode::new_root_mut(builder.finish()))
+    }
+
+    /// Return a copy of this entry with a different value.
+    ///
+    /// Everything up to and including the colon is kept, as are the whitespace after the colon
+    /// and the indentation of continuation lines.
+    fn with_value(&self, value: &str) -> Entry {
+        let children = self.0.children_with_tokens().collect::<Vec<_>>();
+        let Some(colon) = children.iter().position(|c| c.kind() == COLON) else {
+            return Entry::new(self.key().as_deref().unwrap_or_default(), value);
+        };
+        let separator = match children.get(colon + 1) {
+            Some(rowan::NodeOrToken::Token(t)) if t.kind() == WHITESPACE => t.text().to_string(),
+            Some(c) if c.kind() == VALUE => String::new(),
+            _ => " ".to_string(),
+        };
+        let indent = children
+            .iter()
+            .filter_map(|c| c.as_token())
+            .find(|t| t.kind() == INDENT)
+            .map_or(" ".to_string(), |t| t.text().to_string());
+
+        let mut builder = GreenNodeBuilder::new();
+        builder.start_node(ENTRY.into());
+        for child in &children[..=colon] {
+            match child {
+                rowan::NodeOrToken::Node(node) => inject(&mut builder, node.clone()),
+                rowan::NodeOrToken::Token(token) => {
+                    builder.token(token.kind().into(), token.text())
+                }
+            }
+        }
+        for (i, line) in value.split('\n').enumerate() {
+            if i > 0 {
+                builder.token(INDENT.into(), &indent);
+            } else if line.is_empty() && value.contains('\n') {
+                // The value starts on the next line; avoid trailing whitespace.
+                builder.token(NEWLINE.into(), "\n");
+                continue;
+            } else if !separator.is_empty() {
+                builder.token(WHITESPACE.into(), &separator);
+            }
+            builder.token(VALUE.into(), line);
+            builder.token(NEWLINE.into(), "\n");
+        }
+        builder.finish_node();
+        Entry(SyntaxNode::new_root_mut(builder.finish()))
     }
 
     /// Returns the key of the entry.
@@ -1034,15 +2351,60 @@ impl Entry {
             .map(|it| it.text().to_string())
     }
 
+    /// Returns whether the entry has the given key, without allocating.
+    fn has_key(&self, key: &str) -> bool {
+        self.0
+            .children_with_tokens()
+            .filter_map(|it| it.into_token())
+            .find(|it| it.kind() == KEY)
+            .is_some_and(|it| it.text() == key)
+    }
+
+    fn has_key_ci(&self, key: &str) -> bool {
+        self.0
+            .children_with_tokens()
+            .filter_map(|it| it.into_token())
+            .find(|it| it.kind() == KEY)
+            .is_some_and(|it| it.text().eq_ignore_ascii_case(key))
+    }
+
     /// Returns the value of the entry.
     pub fn value(&self) -> String {
-        self.0
+        self.value_ref().into_owned()
+    }
+
+    /// Returns the value of the entry, sharing the text with the syntax tree.
+    ///
+    /// Values that span multiple lines are only joined when they are first accessed as a
+    /// whole.
+    fn value_ref(&self) -> FieldValue {
+        let first = self
+            .0
             .children_with_tokens()
             .filter_map(|it| it.into_token())
-            .filter(|it| it.kind() == VALUE)
-            .map(|it| it.text().to_string())
-            .collect::<Vec<_>>()
-            .join("\n")
+            .find(|it| it.kind() == VALUE);
+        let first = match first {
+            Some(first) => first,
+            None => return FieldValue(FieldValueRepr::Owned(String::new())),
+        };
+        match value_lines(&self.0.green()).nth(1) {
+            None => FieldValue(FieldValueRepr::Shared(first.green().to_owned())),
+            Some(_) => FieldValue(FieldValueRepr::Lines(
+                self.0.green().into_owned(),
+                std::sync::OnceLock::new(),
+            )),
+        }
+    }
+
+    /// Returns whether the value starts on the line after the key, rather than directly
+    /// after the colon.
+    fn value_starts_on_next_line(&self) -> bool {
+        self.0
+            .children_with_tokens()
+            .skip_while(|it| it.kind() != COLON)
+            .skip(1)
+            .find(|it| it.kind() != WHITESPACE)
+            .is_some_and(|it| it.kind() == NEWLINE)
     }
 
     /// Detach this entry from the paragraph.
@@ -1051,6 +2413,169 @@ impl Entry {
     }
 }
 
+/// The value of a field, as returned by [`Paragraph::get_ref`].
+///
+/// The value shares its text with the syntax tree, so that no copy has to be made. Values that
+/// span multiple lines are joined into a newly allocated string the first time they are
+/// accessed as a whole; [`FieldValue::lines`] gives access to the individual lines without
+/// joining them.
+#[derive(Clone)]
+pub struct FieldValue(FieldValueRepr);
+
+#[derive(Clone)]
+enum FieldValueRepr {
+    /// A value consisting of a single line.
+    Shared(rowan::GreenToken),
+    /// A value spanning multiple lines of an entry, joined on demand.
+    Lines(GreenNode, std::sync::OnceLock<String>),
+    Owned(String),
+}
+
+impl FieldValue {
+    /// Returns the value as a string slice.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            FieldValueRepr::Shared(token) => token.text(),
+            FieldValueRepr::Lines(entry, joined) => joined.get_or_init(|| join_value_lines(entry)),
+            FieldValueRepr::Owned(value) => value.as_str(),
+        }
+    }
+
+    /// Returns the lines of the value, without joining them.
+    ///
+    /// # Examples
+    /// ```
+    /// use deb822_lossless::lossless::Paragraph;
+    /// let paragraph: Paragraph = "Depends: bar,\n baz\n".parse().unwrap();
+    /// let depends = paragraph.get_ref("Depends").unwrap();
+    /// assert_eq!(depends.lines().collect::<Vec<_>>(), vec!["bar,", "baz"]);
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = &str> + '_ {
+        let (text, entry) = match &self.0 {
+            FieldValueRepr::Lines(entry, _) => (None, Some(value_lines(entry))),
+            _ => (Some(self.as_str().lines()), None),
+        };
+        text.into_iter()
+            .flatten()
+            .chain(entry.into_iter().flatten())
+    }
+
+    /// Converts the value into an owned string.
+    pub fn into_owned(self) -> String {
+        match self.0 {
+            FieldValueRepr::Shared(token) => token.text().to_string(),
+            FieldValueRepr::Lines(entry, joined) => joined
+                .into_inner()
+                .unwrap_or_else(|| join_value_lines(&entry)),
+            FieldValueRepr::Owned(value) => value,
+        }
+    }
+}
+
+/// Returns the lines of the value of an entry.
+///
+/// Continuation lines that only consist of whitespace are returned as empty lines, unless they
+/// trail the value.
+fn value_lines(entry: &rowan::GreenNodeData) -> impl Iterator<Item = &str> {
+    let mut blank = false;
+    let mut pending = 0;
+    entry
+        .children()
+        .filter_map(|it| it.into_token())
+        .flat_map(move |it| {
+            let kind = it.kind();
+            let mut line = None;
+            let mut blanks = 0;
+            if kind == VALUE.into() {
+                blanks = std::mem::take(&mut pending);
+                line = Some(it.text());
+                blank = false;
+            } else if kind == NEWLINE.into() && blank {
+                pending += 1;
+                blank = false;
+            } else {
+                blank = kind == INDENT.into();
+            }
+            std::iter::repeat_n("", blanks).chain(line)
+        })
+}
+
+/// Joins the lines of the value of an entry.
+fn join_value_lines(entry: &rowan::GreenNodeData) -> String {
+    let mut value = String::new();
+    for (i, line) in value_lines(entry).enumerate() {
+        if i > 0 {
+            value.push('\n');
+        }
+        value.push_str(line);
+    }
+    value
+}
+
+impl std::ops::Deref for FieldValue {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for FieldValue {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Debug for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for FieldValue {}
+
+impl std::hash::Hash for FieldValue {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl PartialEq<str> for FieldValue {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for FieldValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for FieldValue {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl From<FieldValue> for String {
+    fn from(value: FieldValue) -> Self {
+        value.into_owned()
+    }
+}
+
 impl FromStr for Deb822 {
     type Err = ParseError;
 
@@ -1421,6 +2946,42 @@ Homepage: https://salsa.debian.org/debian/foo
         assert!(!p.rename("Nonexistent", "Homepage"));
     }
 
+    #[test]
+    fn test_set_preserves_formatting() {
+        let text = r#"Source: foo
+# Build dependencies
+Build-Depends:debhelper-compat (= 13)
+Description: short
+    long line one
+    # not a comment
+"#;
+        let mut p: super::Paragraph = text.parse().unwrap();
+        p.set("Build-Depends", "debhelper-compat (= 13), foo");
+        p.set("Description", "short\nlong line two");
+        assert_eq!(
+            p.to_string(),
+            r#"Source: foo
+# Build dependencies
+Build-Depends:debhelper-compat (= 13), foo
+Description: short
+    long line two
+"#
+        );
+        assert!(p.rename("Build-Depends", "Build-Depends-Indep"));
+        assert_eq!(
+            p.get("Build-Depends-Indep").as_deref(),
+            Some("debhelper-compat (= 13), foo")
+        );
+        assert!(p
+            .to_string()
+            .starts_with("Source: foo\n# Build dependencies\nBuild-Depends-Indep:debhelper"));
+        p.remove("Build-Depends-Indep");
+        assert_eq!(
+            p.to_string(),
+            "Source: foo\n# Build dependencies\nDescription: short\n    long line two\n"
+        );
+    }
+
     #[test]
     fn test_set_field() {
         let d: super::Deb822 = r#"Source: foo
@@ -1530,6 +3091,48 @@ Foo: Bar
         assert_eq!(d.to_string(), "");
     }
 
+    #[test]
+    fn test_retain_paragraphs() {
+        let text = r#"# Leading comment
+
+A: 1
+
+# About B
+B: 2
+
+
+# Free-standing comment
+
+C: 3
+"#;
+        let mut d: super::Deb822 = text.parse().unwrap();
+        d.retain_paragraphs(|_| true);
+        assert_eq!(d.to_string(), text);
+
+        d.retain_paragraphs(|p| !p.contains_key("B"));
+        assert_eq!(
+            d.to_string(),
+            r#"# Leading comment
+
+A: 1
+
+# Free-standing comment
+
+C: 3
+"#
+        );
+
+        let mut d: super::Deb822 = "A: 1\n\nB: 2\n\nC: 3\n".parse().unwrap();
+        let extracted = d.extract_paragraphs(|p| !p.contains_key("A"));
+        assert_eq!(d.to_string(), "A: 1\n");
+        assert_eq!(extracted.to_string(), "B: 2\n\nC: 3\n");
+        assert_eq!(d.paragraphs().count(), 1);
+
+        let extracted = d.extract_paragraphs(|_| true);
+        assert_eq!(d.to_string(), "");
+        assert_eq!(extracted.to_string(), "A: 1\n");
+    }
+
     #[test]
     fn test_multiline_entry() {
         use super::SyntaxKind::*;
@@ -1760,6 +3363,299 @@ C: D
         );
     }
 
+    #[test]
+    fn test_get_blank_continuation_line() {
+        let paragraph: Paragraph = "Description: foo\n \n bar\n".parse().unwrap();
+        assert_eq!(paragraph.get("Description").as_deref(), Some("foo\n\nbar"));
+        let description = paragraph.get_ref("Description").unwrap();
+        assert_eq!(
+            description.lines().collect::<Vec<_>>(),
+            vec!["foo", "", "bar"]
+        );
+    }
+
+    #[test]
+    fn test_from_reader_invalid_utf8() {
+        let input: &[u8] = b"Package: hello\nMaintainer: J\xf6rg\n";
+        assert!(matches!(
+            Deb822::from_reader(input),
+            Err(super::Error::InvalidUtf8(_))
+        ));
+    }
+
+    #[test]
+    fn test_limits() {
+        let text = "Package: a\nDescription: foo\n bar\n\nPackage: b\n";
+        let options = crate::ParseOptions::new().max_field_size(Some(16));
+        assert_eq!(
+            Deb822::from_str_with(text, &options).unwrap_err().0,
+            vec!["field too large: Description"]
+        );
+        let options = crate::ParseOptions::new().max_paragraph_size(Some(20));
+        assert_eq!(
+            Deb822::from_str_with(text, &options).unwrap_err().0,
+            vec!["paragraph too large"]
+        );
+        let options = crate::ParseOptions::new().max_input_size(Some(20));
+        assert!(matches!(
+            Deb822::from_reader_with(text.as_bytes(), &options),
+            Err(super::Error::InputTooLarge)
+        ));
+        let options = crate::ParseOptions::new().max_input_size(Some(text.len()));
+        assert!(Deb822::from_reader_with(text.as_bytes(), &options).is_ok());
+    }
+
+    #[test]
+    fn test_crlf() {
+        let text = "Source: foo\r\nDepends: a,\r\n b\r\n\r\n# binary\r\nPackage: bar\r\n";
+        let deb822: Deb822 = text.parse().unwrap();
+        assert_eq!(deb822.to_string(), text);
+        let paragraphs = deb822.paragraphs().collect::<Vec<_>>();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].get("Depends").as_deref(), Some("a,\nb"));
+        assert_eq!(paragraphs[1].get("Package").as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn test_retain_fields() {
+        let d: Deb822 = concat!(
+            "Source: foo\n",
+            "# Private\n",
+            "X-Origin: bar\n",
+            "Build-Depends: a,\n",
+            " b\n",
+            "\n",
+            "Package: foo\n",
+            "XS-Private: 1\n",
+        )
+        .parse()
+        .unwrap();
+        let mut values = vec![];
+        for mut paragraph in d.paragraphs() {
+            paragraph.retain_fields(|name, value| {
+                values.push(value.to_string());
+                !name.starts_with('X')
+            });
+        }
+        assert_eq!(values, vec!["foo", "bar", "a,\nb", "foo", "1"]);
+        assert_eq!(
+            d.to_string(),
+            "Source: foo\nBuild-Depends: a,\n b\n\nPackage: foo\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_paragraph_after() {
+        let mut d: Deb822 = "Package: a\n\n\nPackage: c".parse().unwrap();
+        let a = d.paragraphs().next().unwrap();
+        let c = d.paragraphs().nth(1).unwrap();
+        let mut b = d.insert_paragraph_after(&a, vec![("Package", "b")].into());
+        b.set("Section", "net");
+        assert_eq!(
+            d.to_string(),
+            "Package: a\n\nPackage: b\nSection: net\n\n\nPackage: c"
+        );
+        d.insert_paragraph_after(&c, vec![("Package", "d")].into());
+        assert_eq!(
+            d.to_string(),
+            "Package: a\n\nPackage: b\nSection: net\n\n\nPackage: c\n\nPackage: d\n"
+        );
+        assert_eq!(d.paragraphs().count(), 4);
+    }
+
+    #[test]
+    fn test_insert_paragraph_before() {
+        let mut d: Deb822 = "# header\nPackage: b\n\n# about c\n# more\nPackage: c\n"
+            .parse()
+            .unwrap();
+        let b = d.paragraphs().next().unwrap();
+        let c = d.paragraphs().nth(1).unwrap();
+        d.insert_paragraph_before(&c, vec![("Package", "bb")].into());
+        assert_eq!(
+            d.to_string(),
+            "# header\nPackage: b\n\nPackage: bb\n\n# about c\n# more\nPackage: c\n"
+        );
+        d.insert_paragraph_before(&b, vec![("Package", "a")].into());
+        assert_eq!(
+            d.to_string(),
+            "Package: a\n\n# header\nPackage: b\n\nPackage: bb\n\n# about c\n# more\nPackage: c\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "anchor is not a paragraph in this file")]
+    fn test_insert_paragraph_foreign_anchor() {
+        let mut d: Deb822 = "Package: a\n".parse().unwrap();
+        let other: Deb822 = "Package: b\n".parse().unwrap();
+        let anchor = other.paragraphs().next().unwrap();
+        d.insert_paragraph_after(&anchor, vec![("Package", "c")].into());
+    }
+
+    #[test]
+    fn test_verify_roundtrip() {
+        for text in [
+            "",
+            "\n\n",
+            "A: 1\r\n B\n\n\n# c\nB:\n",
+            "Å: ö\n# x",
+            "::\n junk",
+        ] {
+            assert_eq!(verify_roundtrip(text), Ok(()));
+        }
+
+        assert_eq!(
+            RoundtripError::find("A: 1\nBé: xyz\n", "A: 1\nBé: x\n"),
+            Some(RoundtripError {
+                offset: 11,
+                line: 2,
+                column: 6,
+                expected: "yz".to_string(),
+                found: String::new(),
+            })
+        );
+        let err = RoundtripError::find("A: 1\n", "A: 1\n\n").unwrap();
+        assert_eq!((err.offset, err.line, err.column), (5, 2, 1));
+        assert_eq!(
+            err.to_string(),
+            "2:1: round trip differs from input: expected \"\", found \"\""
+        );
+    }
+
+    #[test]
+    fn test_from_str_with_verify_roundtrip() {
+        use crate::ParseOptions;
+
+        let options = ParseOptions {
+            verify_roundtrip: true,
+            ..Default::default()
+        };
+        let text = "A: 1\r\n\r\nB: 2\r\n";
+        assert!(Deb822::from_str_with(text, &options).is_ok());
+        let options = ParseOptions {
+            normalize_crlf: true,
+            ..options
+        };
+        assert_eq!(
+            Deb822::from_str_with(text, &options)
+                .unwrap_err()
+                .to_string(),
+            "1:5: round trip differs from input: expected \"\\r\", found \"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_set_comma_list() {
+        let d: Deb822 = concat!(
+            "Source: foo\n",
+            "Uploaders:\n",
+            "\tJane <jane@example.com>,\n",
+            "\tJoe <joe@example.com>,\n",
+            "Binary: foo, libfoo1\n",
+        )
+        .parse()
+        .unwrap();
+        let mut p = d.paragraphs().next().unwrap();
+        let mut uploaders = p.get_comma_list("Uploaders").collect::<Vec<_>>();
+        uploaders.insert(1, "John <john@example.com>".to_string());
+        p.set_comma_list("Uploaders", uploaders);
+        p.set_comma_list("Binary", ["foo", "libfoo2"]);
+        p.set_comma_list("Testsuite", ["autopkgtest"]);
+        assert_eq!(
+            d.to_string(),
+            concat!(
+                "Source: foo\n",
+                "Uploaders:\n",
+                "\tJane <jane@example.com>,\n",
+                "\tJohn <john@example.com>,\n",
+                "\tJoe <joe@example.com>,\n",
+                "Binary: foo, libfoo2\n",
+                "Testsuite: autopkgtest\n",
+            )
+        );
+
+        p.set_comma_list("Uploaders", Vec::<String>::new());
+        assert_eq!(p.get("Uploaders"), None);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut d = Deb822::new();
+        d.push(vec![("Package", "foo")].into());
+        assert_eq!(d.to_string(), "Package: foo\n");
+
+        // Paragraphs that are part of another file are copied.
+        let other: Deb822 = "Package: bar\n\n\nPackage: baz\n".parse().unwrap();
+        d.push(other.paragraphs().nth(1).unwrap());
+        assert_eq!(d.to_string(), "Package: foo\n\nPackage: baz\n");
+        assert_eq!(other.to_string(), "Package: bar\n\n\nPackage: baz\n");
+
+        let mut d: Deb822 = "Package: foo\n\n\n".parse().unwrap();
+        d.push(vec![("Package", "bar")].into());
+        assert_eq!(d.to_string(), "Package: foo\n\n\nPackage: bar\n");
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut d: Deb822 = "# header\nPackage: foo".parse().unwrap();
+        d.extend("\n\n".parse().unwrap());
+        assert_eq!(d.to_string(), "# header\nPackage: foo");
+        d.extend("\nPackage: bar\n\n# trailer\n".parse().unwrap());
+        assert_eq!(
+            d.to_string(),
+            "# header\nPackage: foo\n\nPackage: bar\n\n# trailer\n"
+        );
+        assert_eq!(d.paragraphs().count(), 2);
+
+        let mut d = Deb822::new();
+        d.extend("Package: foo\n".parse().unwrap());
+        assert_eq!(d.to_string(), "Package: foo\n");
+    }
+
+    #[test]
+    fn test_from_str_with() {
+        use crate::ParseOptions;
+
+        let text = "Source: foo\r\n \r\n# comment\r\nPackage: foo\r\nPackage: bar\r\n";
+        assert_eq!(
+            Deb822::from_str_with(
+                "Source: foo\n\n# comment\nPackage: foo\nPackage: bar\n",
+                &ParseOptions::strict()
+            )
+            .unwrap_err()
+            .to_string(),
+            "3:1: unexpected comment: # comment\nduplicate field: Package\n"
+        );
+        let deb822 = Deb822::from_str_with(
+            "Package: foo\nDescription: bar\n # indented\n",
+            &ParseOptions::archive(),
+        )
+        .unwrap();
+        assert_eq!(
+            deb822.paragraphs().next().unwrap().get("Description"),
+            Some("bar\n# indented".to_string())
+        );
+
+        let deb822 = Deb822::from_str_with(text, &ParseOptions::lenient()).unwrap();
+        assert_eq!(deb822.paragraphs().count(), 2);
+        assert_eq!(
+            deb822.to_string(),
+            "Source: foo\n\n# comment\nPackage: foo\nPackage: bar\n"
+        );
+
+        assert!(Deb822::from_bytes_with(b"Package: f\xf6o\n", &ParseOptions::strict()).is_err());
+        let deb822 =
+            Deb822::from_bytes_with(b"Package: f\xf6o\n", &ParseOptions::lenient()).unwrap();
+        assert_eq!(
+            deb822
+                .paragraphs()
+                .next()
+                .unwrap()
+                .get("Package")
+                .as_deref(),
+            Some("f\u{fffd}o")
+        );
+    }
+
     #[test]
     fn test_get_all() {
         let d: super::Deb822 = r#"Source: foo
@@ -1773,4 +3669,100 @@ Maintainer: Bar Foo <bar@example.com>"#
             vec!["Foo Bar <foo@example.com>", "Bar Foo <bar@example.com>"]
         );
     }
+
+    #[test]
+    fn test_sort_paragraphs_by() {
+        let mut d: super::Deb822 = concat!(
+            "# Header\n\n",
+            "Package: c\n\n",
+            "# About b\n# More\n",
+            "Package: b\n",
+            "# Inside b\n",
+            "Depends: c\n\n\n",
+            "Package: a\n\n",
+            "# Trailing\n",
+        )
+        .parse()
+        .unwrap();
+        d.sort_paragraphs_by(|a, b| a.get("Package").cmp(&b.get("Package")));
+        assert_eq!(
+            d.to_string(),
+            concat!(
+                "# Header\n\n",
+                "Package: a\n\n",
+                "# About b\n# More\n",
+                "Package: b\n",
+                "# Inside b\n",
+                "Depends: c\n\n\n",
+                "Package: c\n\n",
+                "# Trailing\n",
+            )
+        );
+
+        // Sorting is stable, so sorting by an equal key changes nothing.
+        let before = d.to_string();
+        d.sort_paragraphs_by(|_, _| std::cmp::Ordering::Equal);
+        assert_eq!(d.to_string(), before);
+
+        let mut d: super::Deb822 = "Package: b\n\nPackage: a".parse().unwrap();
+        d.sort_paragraphs_by(|a, b| a.get("Package").cmp(&b.get("Package")));
+        assert_eq!(d.paragraphs().count(), 2);
+        assert_eq!(d.to_string(), "Package: a\n\nPackage: b\n");
+    }
+
+    #[test]
+    fn test_sort_fields_by() {
+        let mut p: super::Paragraph = concat!(
+            "Depends: b,\n",
+            "  a\n",
+            "# The name\n",
+            "Package: foo\n",
+            "Architecture: all",
+        )
+        .parse()
+        .unwrap();
+        p.sort_fields_by(|a, b| a.key().cmp(&b.key()));
+        assert_eq!(
+            p.to_string(),
+            concat!(
+                "Architecture: all\n",
+                "Depends: b,\n",
+                "  a\n",
+                "# The name\n",
+                "Package: foo\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_get_ci() {
+        let p: super::Paragraph = "package: foo\nMULTI-ARCH: same\nPackage: bar\n"
+            .parse()
+            .unwrap();
+        assert_eq!(p.get_ci("Package").as_deref(), Some("foo"));
+        assert_eq!(p.get_ci("multi-arch").as_deref(), Some("same"));
+        assert_eq!(p.get_ci("Depends"), None);
+        assert!(p.contains_key_ci("Multi-Arch"));
+        assert!(!p.contains_key("Multi-Arch"));
+    }
+
+    #[test]
+    fn test_get_ref() {
+        let p: Paragraph = "Package: foo\nDepends:\n bar,\n baz\nEmpty:\n"
+            .parse()
+            .unwrap();
+        let package = p.get_ref("Package").unwrap();
+        assert_eq!(package.lines().collect::<Vec<_>>(), vec!["foo"]);
+        assert_eq!(package.into_owned(), "foo");
+
+        let depends = p.get_ref("Depends").unwrap();
+        assert_eq!(depends.lines().collect::<Vec<_>>(), vec!["bar,", "baz"]);
+        assert_eq!(depends.clone().into_owned(), "bar,\nbaz");
+        assert_eq!(depends.as_str(), "bar,\nbaz");
+        assert_eq!(depends.into_owned(), "bar,\nbaz");
+
+        let empty = p.get_ref("Empty").unwrap();
+        assert_eq!(empty.lines().count(), 0);
+        assert_eq!(empty, "");
+    }
 }
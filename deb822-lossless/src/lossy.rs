@@ -1,8 +1,15 @@
 //! Lossy parser for deb822 format.
 //!
 //! This parser is lossy in the sense that it will discard whitespace and comments
-//! in the input.
+//! in the input. Comments can optionally be kept by setting
+//! [`ParseOptions::keep_comments`], in which case they are attached to the field or
+//! paragraph that follows them. Comments between paragraphs can be kept as standalone items
+//! of the document by setting [`ParseOptions::standalone_comments`].
+use crate::convert::Separator;
 use crate::lex::SyntaxKind;
+use crate::options::{DuplicateFieldPolicy, ParseOptions, SeenFields, ValueCommentPolicy};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 /// Error type for the parser.
 #[derive(Debug)]
@@ -16,45 +23,247 @@ pub enum Error {
     /// Expected end-of-file.
     ExpectedEof,
 
+    /// A field occurred more than once in a paragraph.
+    DuplicateField(String),
+
+    /// The input was not valid UTF-8.
+    InvalidUtf8(core::str::Utf8Error),
+
+    /// The input exceeded [`ParseOptions::max_input_size`].
+    InputTooLarge,
+
+    /// A paragraph exceeded [`ParseOptions::max_paragraph_size`].
+    ParagraphTooLarge,
+
+    /// A field exceeded [`ParseOptions::max_field_size`].
+    FieldTooLarge(String),
+
     /// IO error.
+    #[cfg(feature = "std")]
     Io(std::io::Error),
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
         Self::Io(e)
     }
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         match self {
             Self::UnexpectedToken(_k, t) => write!(f, "Unexpected token: {}", t),
             Self::UnexpectedEof => f.write_str("Unexpected end-of-file"),
+            #[cfg(feature = "std")]
             Self::Io(e) => write!(f, "IO error: {}", e),
             Self::ExpectedEof => f.write_str("Expected end-of-file"),
+            Self::DuplicateField(name) => write!(f, "Duplicate field: {}", name),
+            Self::InvalidUtf8(e) => write!(f, "Invalid UTF-8: {}", e),
+            Self::InputTooLarge => f.write_str("Input too large"),
+            Self::ParagraphTooLarge => f.write_str("Paragraph too large"),
+            Self::FieldTooLarge(name) => write!(f, "Field too large: {}", name),
         }
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The location of a field or paragraph in the parsed text.
+///
+/// Byte offsets refer to the text after the normalizations requested in [`ParseOptions`];
+/// line and column numbers are not affected by these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    /// Byte offset of the start.
+    pub start: usize,
+
+    /// Byte offset just past the end.
+    pub end: usize,
+
+    /// Line number of the start, starting at 1.
+    pub line: usize,
+
+    /// Column number of the start, in characters and starting at 1.
+    pub column: usize,
+}
+
+impl Span {
+    /// The range of bytes covered.
+    pub fn range(&self) -> core::ops::Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// An error encountered while parsing in recovering mode.
+#[derive(Debug)]
+pub struct ParseDiagnostic {
+    /// The error.
+    pub error: Error,
+
+    /// The location of the error.
+    pub span: Span,
+}
+
+impl core::fmt::Display for ParseDiagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}: {}", self.span.line, self.span.column, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDiagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 /// A field in a deb822 paragraph.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field {
     /// The name of the field.
     pub name: String,
 
     /// The value of the field.
     pub value: String,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    comments: Vec<String>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    value_comments: Vec<(usize, String)>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    span: Option<Span>,
+}
+
+impl Field {
+    /// Create a new field.
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Comment lines directly preceding the field, including the leading `#`.
+    ///
+    /// The parser only fills these in if [`ParseOptions::keep_comments`] is set.
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Mutable access to the comment lines preceding the field.
+    pub fn comments_mut(&mut self) -> &mut Vec<String> {
+        &mut self.comments
+    }
+
+    /// Comment lines within the continuation lines of the field, including the leading `#`,
+    /// together with the index of the line of the value that they precede.
+    ///
+    /// The parser only fills this in if [`ParseOptions::value_comments`] is
+    /// [`crate::options::ValueCommentPolicy::Annotate`].
+    pub fn value_comments(&self) -> &[(usize, String)] {
+        &self.value_comments
+    }
+
+    /// Mutable access to the comment lines within the value of the field.
+    pub fn value_comments_mut(&mut self) -> &mut Vec<(usize, String)> {
+        &mut self.value_comments
+    }
+
+    /// The location of the field in the text it was parsed from, from the start of its name
+    /// to the end of its value.
+    ///
+    /// Returns `None` if the field was not created by the parser.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let paragraph: Paragraph = "Package: foo\nDescription: bar\n baz\n".parse().unwrap();
+    /// let span = paragraph.fields[1].span().unwrap();
+    /// assert_eq!((span.line, span.column), (2, 1));
+    /// assert_eq!(span.range(), 13..34);
+    /// ```
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+// The span is deliberately not taken into account when comparing fields.
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.value == other.value
+            && self.comments == other.comments
+            && self.value_comments == other.value_comments
+    }
+}
+
+impl Eq for Field {}
+
+impl core::hash::Hash for Field {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.value.hash(state);
+        self.comments.hash(state);
+        self.value_comments.hash(state);
+    }
 }
 
 /// A deb822 paragraph.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Paragraph {
     /// Fields in the paragraph.
     pub fields: Vec<Field>,
+
+    #[cfg_attr(feature = "serde", serde(default))]
+    comments: Vec<String>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    span: Option<Span>,
+}
+
+impl PartialEq for Paragraph {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields && self.comments == other.comments
+    }
+}
+
+impl Eq for Paragraph {}
+
+impl core::hash::Hash for Paragraph {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.fields.hash(state);
+        self.comments.hash(state);
+    }
 }
 
 impl Paragraph {
+    /// Comment lines preceding the first field of the paragraph, including the leading `#`.
+    ///
+    /// The parser only fills these in if [`ParseOptions::keep_comments`] is set.
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Mutable access to the comment lines preceding the paragraph.
+    pub fn comments_mut(&mut self) -> &mut Vec<String> {
+        &mut self.comments
+    }
+
+    /// The location of the paragraph in the text it was parsed from, from the start of its
+    /// first field to the end of its last field.
+    ///
+    /// Returns `None` if the paragraph was not created by the parser.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
     /// Get the value of a field by name.
     ///
     /// Returns `None` if the field does not exist.
@@ -67,6 +276,63 @@ impl Paragraph {
         None
     }
 
+    /// Get the value of a field by name, parsed as `T`.
+    ///
+    /// Returns `None` if the field does not exist, and the parse error if its value can not be
+    /// parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let paragraph: Paragraph = "Installed-Size: 42\nPriority: high\n".parse().unwrap();
+    /// assert_eq!(paragraph.get_parsed::<u64>("Installed-Size"), Some(Ok(42)));
+    /// assert!(paragraph.get_parsed::<u64>("Priority").unwrap().is_err());
+    /// assert_eq!(paragraph.get_parsed::<u64>("Size"), None);
+    /// ```
+    pub fn get_parsed<T: core::str::FromStr>(&self, name: &str) -> Option<Result<T, T::Err>> {
+        self.get(name).map(str::parse)
+    }
+
+    /// Get the value of a field by name, ignoring ASCII case.
+    ///
+    /// Field names are case-insensitive in deb822, so this matches what dpkg does when
+    /// looking up a field. If several fields match, the first one is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let paragraph: Paragraph = "package: foo\n".parse().unwrap();
+    /// assert_eq!(paragraph.get("Package"), None);
+    /// assert_eq!(paragraph.get_ci("Package"), Some("foo"));
+    /// ```
+    pub fn get_ci(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+            .map(|field| field.value.as_str())
+    }
+
+    /// Check whether a field with the given name exists, ignoring ASCII case.
+    pub fn contains_key_ci(&self, name: &str) -> bool {
+        self.get_ci(name).is_some()
+    }
+
+    /// Get the values of all fields with the given name, in order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let paragraph: Paragraph = "Bug: 1\nBug-Debian: 2\nBug: 3\n".parse().unwrap();
+    /// assert_eq!(paragraph.get_all("Bug").collect::<Vec<_>>(), vec!["1", "3"]);
+    /// assert_eq!(paragraph.get_all("Bug-Ubuntu").count(), 0);
+    /// ```
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields
+            .iter()
+            .filter(move |field| field.name == name)
+            .map(|field| field.value.as_str())
+    }
+
     /// Check if the paragraph is empty.
     pub fn is_empty(&self) -> bool {
         self.fields.is_empty()
@@ -96,10 +362,7 @@ impl Paragraph {
     /// If a field with the same name already exists, a
     /// new field will be added.
     pub fn insert(&mut self, name: &str, value: &str) {
-        self.fields.push(Field {
-            name: name.to_string(),
-            value: value.to_string(),
-        });
+        self.fields.push(Field::new(name, value));
     }
 
     /// Set the value of a field.
@@ -116,29 +379,229 @@ impl Paragraph {
         self.insert(name, value);
     }
 
+    /// Set the value of a field to the string representation of `value`.
+    ///
+    /// The field is updated in the same way as by [`Paragraph::set`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let mut paragraph: Paragraph = "Package: foo\n".parse().unwrap();
+    /// paragraph.set_parsed("Installed-Size", &42);
+    /// assert_eq!(paragraph.get_parsed::<u64>("Installed-Size"), Some(Ok(42)));
+    /// ```
+    pub fn set_parsed<T: core::fmt::Display>(&mut self, name: &str, value: &T) {
+        self.set(name, &value.to_string());
+    }
+
+    /// Replace the value of an existing field, returning the old value.
+    ///
+    /// If the field occurs more than once, all occurrences are replaced by a single field at
+    /// the position of the first one. If the field does not exist, the paragraph is left
+    /// unchanged and `None` is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let mut paragraph: Paragraph = "Package: foo\nDepends: a\nVersion: 1\nDepends: b\n".parse().unwrap();
+    /// assert_eq!(paragraph.replace("Depends", "c").as_deref(), Some("a"));
+    /// assert_eq!(paragraph.to_string(), "Package: foo\nDepends: c\nVersion: 1\n");
+    /// assert_eq!(paragraph.replace("Section", "misc"), None);
+    /// assert_eq!(paragraph.get("Section"), None);
+    /// ```
+    pub fn replace(&mut self, name: &str, value: &str) -> Option<String> {
+        let index = self.fields.iter().position(|field| field.name == name)?;
+        let old = core::mem::replace(&mut self.fields[index].value, value.to_string());
+        let mut first = true;
+        self.fields.retain(|field| {
+            if field.name != name {
+                return true;
+            }
+            core::mem::take(&mut first)
+        });
+        Some(old)
+    }
+
     /// Remove a field from the paragraph.
+    ///
+    /// All fields with the given name are removed.
     pub fn remove(&mut self, name: &str) {
         self.fields.retain(|field| field.name != name);
     }
+
+    /// Iterate over the items of a list field, such as `Architecture` or `Binary`.
+    ///
+    /// Surrounding whitespace is stripped from each item and empty items are skipped. If the
+    /// field is not present, the iterator is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::convert::Separator;
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let paragraph: Paragraph = "Binary: foo,\n libfoo1 ,\nArchitecture: amd64 i386\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     paragraph.get_list("Binary", Separator::Comma).collect::<Vec<_>>(),
+    ///     vec!["foo", "libfoo1"]
+    /// );
+    /// assert_eq!(paragraph.get_list("Architecture", Separator::Whitespace).count(), 2);
+    /// ```
+    pub fn get_list<'a>(
+        &'a self,
+        name: &str,
+        separator: Separator,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        crate::convert::split_list(self.get(name).unwrap_or_default(), separator.as_pattern())
+    }
+
+    /// Set a list field, folding it over several lines if it is too long.
+    ///
+    /// The field is removed if `items` is empty. See [`crate::convert::fold_list`] for
+    /// details on the folding.
+    pub fn set_list<S: AsRef<str>>(
+        &mut self,
+        name: &str,
+        items: impl IntoIterator<Item = S>,
+        separator: Separator,
+    ) {
+        let value = crate::convert::fold_list(name, items, separator);
+        if value.is_empty() {
+            self.remove(name);
+        } else {
+            self.set(name, &value);
+        }
+    }
+
+    /// Iterate over the items of a comma-separated list field, such as `Uploaders`.
+    ///
+    /// This is equivalent to [`Paragraph::get_list`] with [`Separator::Comma`].
+    pub fn get_comma_list<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.get_list(name, Separator::Comma)
+    }
+
+    /// Set a comma-separated list field, keeping its current layout where possible.
+    ///
+    /// If the items are currently written one per line, the new items are too, so that
+    /// adding or removing an item doesn't reflow the whole field. See
+    /// [`crate::convert::fold_comma_list_like`] for details. The field is removed if `items`
+    /// is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let mut paragraph: Paragraph = "Uploaders:\n Jane <jane@example.com>,\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut uploaders = paragraph
+    ///     .get_comma_list("Uploaders")
+    ///     .map(str::to_string)
+    ///     .collect::<Vec<_>>();
+    /// uploaders.push("Joe <joe@example.com>".to_string());
+    /// paragraph.set_comma_list("Uploaders", uploaders);
+    /// assert_eq!(
+    ///     paragraph.to_string(),
+    ///     "Uploaders:\n Jane <jane@example.com>,\n Joe <joe@example.com>,\n"
+    /// );
+    /// ```
+    pub fn set_comma_list<S: AsRef<str>>(
+        &mut self,
+        name: &str,
+        items: impl IntoIterator<Item = S>,
+    ) {
+        let value = crate::convert::fold_comma_list_like(name, items, self.get(name));
+        if value.is_empty() {
+            self.remove(name);
+        } else {
+            self.set(name, &value);
+        }
+    }
+
+    /// Keep only the fields for which `f` returns true.
+    ///
+    /// `f` is called with the name and value of each field, in order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let mut paragraph: Paragraph = "Package: foo\nX-Build-Id: 42\nSection: net\n".parse().unwrap();
+    /// paragraph.retain_fields(|name, _| !name.starts_with("X-"));
+    /// assert_eq!(paragraph.to_string(), "Package: foo\nSection: net\n");
+    /// ```
+    pub fn retain_fields(&mut self, mut f: impl FnMut(&str, &str) -> bool) {
+        self.fields.retain(|field| f(&field.name, &field.value));
+    }
+
+    /// Convert the paragraph to a lossless paragraph, for editing.
+    ///
+    /// The paragraph is formatted in the same way as by [`ToString::to_string`], including its
+    /// comments.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Paragraph;
+    /// let paragraph: Paragraph = "Package: foo\nDepends: bar,\n  baz\n".parse().unwrap();
+    /// let mut lossless = paragraph.to_lossless();
+    /// lossless.set("Section", "net");
+    /// assert_eq!(
+    ///     lossless.to_string(),
+    ///     "Package: foo\nDepends: bar,\n baz\nSection: net\n"
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_lossless(&self) -> crate::lossless::Paragraph {
+        let (deb822, _) = crate::lossless::Deb822::from_str_relaxed(&self.to_string());
+        deb822.paragraphs().next().unwrap_or_default()
+    }
 }
 
-impl std::fmt::Display for Field {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for Field {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "{}", comment)?;
+        }
         let lines = self.value.lines().collect::<Vec<_>>();
+        // Comments that would precede the first line, or follow the last line, of the value
+        // are written after it.
+        let last = lines.len().max(1);
+        let value_comments_after = |i: usize| {
+            self.value_comments
+                .iter()
+                .filter(move |(at, _)| (*at).clamp(1, last) == i + 1)
+                .map(|(_, comment)| comment)
+        };
         if lines.len() > 1 {
             write!(f, "{}:", self.name)?;
-            for line in lines {
-                writeln!(f, " {}", line)?;
+            for (i, line) in lines.iter().enumerate() {
+                // Avoid trailing whitespace if the value starts on the next line
+                if i == 0 && line.is_empty() {
+                    writeln!(f)?;
+                } else if line.trim().is_empty() {
+                    // A bare indent reads back as an empty line. Escaping it as " ." would
+                    // not round-trip, since parsing keeps the dot in the value.
+                    writeln!(f, " ")?;
+                } else {
+                    writeln!(f, " {}", line)?;
+                }
+                for comment in value_comments_after(i) {
+                    writeln!(f, " {}", comment)?;
+                }
             }
-            Ok(())
         } else {
-            writeln!(f, "{}: {}", self.name, self.value)
+            writeln!(f, "{}: {}", self.name, self.value)?;
+            for comment in value_comments_after(0) {
+                writeln!(f, " {}", comment)?;
+            }
         }
+        Ok(())
     }
 }
 
-impl std::fmt::Display for Paragraph {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for Paragraph {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "{}", comment)?;
+        }
         for field in &self.fields {
             field.fmt(f)?;
         }
@@ -146,19 +609,26 @@ impl std::fmt::Display for Paragraph {
     }
 }
 
-impl std::fmt::Display for Deb822 {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for (i, paragraph) in self.0.iter().enumerate() {
+impl core::fmt::Display for Deb822 {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for (i, item) in self.items().enumerate() {
             if i > 0 {
                 writeln!(f)?;
             }
-            write!(f, "{}", paragraph)?;
+            match item {
+                Item::Paragraph(paragraph) => write!(f, "{}", paragraph)?,
+                Item::Comment(lines) => {
+                    for line in lines {
+                        writeln!(f, "{}", line)?;
+                    }
+                }
+            }
         }
         Ok(())
     }
 }
 
-impl std::str::FromStr for Paragraph {
+impl core::str::FromStr for Paragraph {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -183,15 +653,63 @@ impl FromIterator<(String, String)> for Paragraph {
     fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
         let fields = iter
             .into_iter()
-            .map(|(name, value)| Field { name, value })
+            .map(|(name, value)| Field {
+                name,
+                value,
+                ..Default::default()
+            })
             .collect();
-        Paragraph { fields }
+        Paragraph {
+            fields,
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> FromIterator<(&'a str, &'a str)> for Paragraph {
+    fn from_iter<T: IntoIterator<Item = (&'a str, &'a str)>>(iter: T) -> Self {
+        iter.into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
+impl From<Paragraph> for Vec<(String, String)> {
+    fn from(paragraph: Paragraph) -> Self {
+        paragraph.into_iter().collect()
+    }
+}
+
+/// Convert a lossless paragraph to a lossy one, dropping comments and formatting.
+#[cfg(feature = "std")]
+impl From<crate::lossless::Paragraph> for Paragraph {
+    fn from(paragraph: crate::lossless::Paragraph) -> Self {
+        Self::from(&paragraph)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&crate::lossless::Paragraph> for Paragraph {
+    fn from(paragraph: &crate::lossless::Paragraph) -> Self {
+        paragraph.items().collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a Paragraph {
+    type Item = (&'a str, &'a str);
+    type IntoIter =
+        core::iter::Map<core::slice::Iter<'a, Field>, fn(&'a Field) -> (&'a str, &'a str)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.value.as_str()))
     }
 }
 
 impl IntoIterator for Paragraph {
     type Item = (String, String);
-    type IntoIter = std::iter::Map<std::vec::IntoIter<Field>, fn(Field) -> (String, String)>;
+    type IntoIter = core::iter::Map<alloc::vec::IntoIter<Field>, fn(Field) -> (String, String)>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.fields
@@ -201,8 +719,29 @@ impl IntoIterator for Paragraph {
 }
 
 /// A deb822 document.
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Deb822(Vec<Paragraph>);
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Deb822(
+    pub(crate) Vec<Paragraph>,
+    /// Standalone comment blocks, with the number of paragraphs that precede them.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) Vec<(usize, Vec<String>)>,
+);
+
+/// An item in a deb822 document, as returned by [`Deb822::items`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item<'a> {
+    /// A paragraph.
+    Paragraph(&'a Paragraph),
+
+    /// A block of comment lines that is not attached to a paragraph, including the leading
+    /// `#` of each line.
+    Comment(&'a [String]),
+}
 
 impl From<Deb822> for Vec<Paragraph> {
     fn from(doc: Deb822) -> Self {
@@ -212,13 +751,55 @@ impl From<Deb822> for Vec<Paragraph> {
 
 impl IntoIterator for Deb822 {
     type Item = Paragraph;
-    type IntoIter = std::vec::IntoIter<Paragraph>;
+    type IntoIter = alloc::vec::IntoIter<Paragraph>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.0.into_iter()
     }
 }
 
+impl<'a> IntoIterator for &'a Deb822 {
+    type Item = &'a Paragraph;
+    type IntoIter = core::slice::Iter<'a, Paragraph>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl From<Vec<Paragraph>> for Deb822 {
+    fn from(paragraphs: Vec<Paragraph>) -> Self {
+        Deb822(paragraphs, Vec::new())
+    }
+}
+
+/// Convert a lossless document to a lossy one, dropping comments and formatting.
+#[cfg(feature = "std")]
+impl From<crate::lossless::Deb822> for Deb822 {
+    fn from(deb822: crate::lossless::Deb822) -> Self {
+        Self::from(&deb822)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&crate::lossless::Deb822> for Deb822 {
+    fn from(deb822: &crate::lossless::Deb822) -> Self {
+        deb822.paragraphs().map(|p| Paragraph::from(&p)).collect()
+    }
+}
+
+impl FromIterator<Paragraph> for Deb822 {
+    fn from_iter<T: IntoIterator<Item = Paragraph>>(iter: T) -> Self {
+        Deb822(iter.into_iter().collect(), Vec::new())
+    }
+}
+
+impl Extend<Paragraph> for Deb822 {
+    fn extend<T: IntoIterator<Item = Paragraph>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
 impl Deb822 {
     /// Number of paragraphs in the document.
     pub fn len(&self) -> usize {
@@ -240,140 +821,805 @@ impl Deb822 {
         self.0.iter_mut()
     }
 
+    /// Append a paragraph to the end of the document.
+    ///
+    /// To append all paragraphs of another document, use [`Extend::extend`]:
+    ///
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    /// let mut main: Deb822 = "Package: foo\n".parse().unwrap();
+    /// let contrib: Deb822 = "Package: bar\n\nPackage: baz\n".parse().unwrap();
+    /// main.extend(contrib);
+    /// main.push(vec![("Package".to_string(), "qux".to_string())].into());
+    /// assert_eq!(main.len(), 4);
+    /// ```
+    pub fn push(&mut self, paragraph: Paragraph) {
+        self.0.push(paragraph);
+    }
+
+    /// Keep only the paragraphs for which `f` returns true.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    /// let mut deb822: Deb822 = "Package: hello\n\nPackage: hello-udeb\nPackage-Type: udeb\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// deb822.retain_paragraphs(|p| p.get("Package-Type") != Some("udeb"));
+    /// assert_eq!(deb822.to_string(), "Package: hello\n");
+    /// ```
+    pub fn retain_paragraphs(&mut self, mut f: impl FnMut(&Paragraph) -> bool) {
+        let keep = self.0.iter().map(&mut f).collect::<Vec<_>>();
+        for (at, _) in &mut self.1 {
+            *at = keep[..*at].iter().filter(|k| **k).count();
+        }
+        let mut keep = keep.into_iter();
+        self.0.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// Iterate over the paragraphs and standalone comments in the document, in order.
+    ///
+    /// Standalone comments are only kept by the parser if
+    /// [`ParseOptions::standalone_comments`] is set.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::{Deb822, Item};
+    /// use deb822_lossless::ParseOptions;
+    ///
+    /// let text = "# Generated file, do not edit.\n\nPackage: foo\n\n# Package: bar\n";
+    /// let options = ParseOptions::new().standalone_comments(true);
+    /// let deb822 = Deb822::from_str_with(text, &options).unwrap();
+    /// let items = deb822.items().collect::<Vec<_>>();
+    /// assert_eq!(items.len(), 3);
+    /// assert_eq!(
+    ///     items[0],
+    ///     Item::Comment(&["# Generated file, do not edit.".to_string()])
+    /// );
+    /// assert_eq!(deb822.to_string(), text);
+    /// ```
+    pub fn items(&self) -> impl Iterator<Item = Item<'_>> {
+        let mut comments = self.1.iter().peekable();
+        let mut paragraphs = self.0.iter().enumerate().peekable();
+        core::iter::from_fn(move || {
+            let next_paragraph = paragraphs.peek().map_or(usize::MAX, |(i, _)| *i);
+            if let Some((_, lines)) = comments.next_if(|(at, _)| *at <= next_paragraph) {
+                return Some(Item::Comment(lines));
+            }
+            paragraphs.next().map(|(_, p)| Item::Paragraph(p))
+        })
+    }
+
+    /// Insert a standalone comment before the paragraph at `index`, after any standalone
+    /// comments that are already there.
+    ///
+    /// Each line should start with `#`. If `index` is the number of paragraphs, the comment is
+    /// added at the end of the document.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of paragraphs.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    /// let mut deb822: Deb822 = "Package: foo\n\nPackage: bar\n".parse().unwrap();
+    /// deb822.insert_comment(1, vec!["# Transitional package".to_string()]);
+    /// assert_eq!(
+    ///     deb822.to_string(),
+    ///     "Package: foo\n\n# Transitional package\n\nPackage: bar\n"
+    /// );
+    /// ```
+    pub fn insert_comment(&mut self, index: usize, lines: Vec<String>) {
+        assert!(index <= self.0.len(), "index out of bounds");
+        let position = self.1.partition_point(|(at, _)| *at <= index);
+        self.1.insert(position, (index, lines));
+    }
+
+    /// Convert the document to a lossless document, for editing.
+    ///
+    /// The document is formatted in the same way as by [`ToString::to_string`], including its
+    /// comments, so this is useful to parse a file quickly with the lossy parser and only
+    /// switch to the lossless representation once it turns out that it needs to be modified.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    /// let deb822: Deb822 = "Package: foo\n\nPackage: bar\n".parse().unwrap();
+    /// let lossless = deb822.to_lossless();
+    /// lossless.paragraphs().nth(1).unwrap().set("Package", "baz");
+    /// assert_eq!(lossless.to_string(), "Package: foo\n\nPackage: baz\n");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_lossless(&self) -> crate::lossless::Deb822 {
+        crate::lossless::Deb822::from_str_relaxed(&self.to_string()).0
+    }
+
     /// Read from a reader.
-    pub fn from_reader<R: std::io::Read>(mut r: R) -> Result<Self, Error> {
-        let mut buf = String::new();
-        r.read_to_string(&mut buf)?;
-        buf.parse()
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(r: R) -> Result<Self, Error> {
+        Self::from_reader_with(r, &ParseOptions::default())
+    }
+
+    /// Read from a reader, using the given options.
+    ///
+    /// The input is read and parsed one paragraph at a time, so the whole document never has
+    /// to be held in memory as text.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::{lossy::Deb822, ParseOptions};
+    /// let input: &[u8] = b"Package: hello\nVersion: 2.10\n\nPackage: world\nVersion: 1.0\n";
+    /// let deb822 = Deb822::from_reader_with(input, &ParseOptions::default()).unwrap();
+    /// assert_eq!(deb822.len(), 2);
+    /// let world = deb822.iter().nth(1).unwrap();
+    /// assert_eq!(world.span().unwrap().line, 4);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_reader_with<R: std::io::Read>(r: R, options: &ParseOptions) -> Result<Self, Error> {
+        ParagraphReader::with_options(std::io::BufReader::new(r), options.clone()).collect()
+    }
+
+    /// Read from a file.
+    ///
+    /// Compressed files, such as `Packages.xz` or `Sources.gz`, are decompressed
+    /// transparently; see [`crate::compression`] for the supported formats.
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        Self::from_reader(crate::compression::open(path)?)
+    }
+
+    /// Write the document to a writer.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, mut w: W) -> std::io::Result<()> {
+        w.write_all(self.to_string().as_bytes())
+    }
+
+    /// Write the document to the given path, atomically replacing any existing file.
+    ///
+    /// The file is first written to a new temporary file in the same directory, which is
+    /// synced to disk and then renamed into place. The permissions of an existing file are
+    /// kept.
+    #[cfg(feature = "std")]
+    pub fn save_atomic(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        crate::common::write_atomic(path.as_ref(), self.to_string().as_bytes())
+    }
+
+    /// Read from a file, using an on-disk cache of the parsed file.
+    ///
+    /// If `cache_path` contains a cache that was written for the current contents of `path`,
+    /// the document is loaded from the cache. Otherwise the file is parsed and the cache is
+    /// (re)written. Compressed files are decompressed, as by [`crate::compression::open`].
+    #[cfg(feature = "cache")]
+    pub fn from_file_cached(
+        path: impl AsRef<std::path::Path>,
+        cache_path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, Error> {
+        Self::from_file_cached_with(path, cache_path, &ParseOptions::default())
+    }
+
+    /// Read from a file with the given options, using an on-disk cache of the parsed file.
+    ///
+    /// See [`Deb822::from_file_cached`]. A cache written for the file parsed with different
+    /// options is not used.
+    #[cfg(feature = "cache")]
+    pub fn from_file_cached_with(
+        path: impl AsRef<std::path::Path>,
+        cache_path: impl AsRef<std::path::Path>,
+        options: &ParseOptions,
+    ) -> Result<Self, Error> {
+        crate::cache::load_or_parse(
+            path.as_ref(),
+            cache_path.as_ref(),
+            options,
+            Self::from_bytes_with,
+        )
     }
 }
 
-impl std::str::FromStr for Deb822 {
-    type Err = Error;
+/// A pull-based reader that parses one paragraph at a time.
+///
+/// Only the text of the paragraph currently being parsed is kept in memory, so arbitrarily
+/// large files (such as apt `Packages` indexes) can be processed in constant memory.
+///
+/// After an error the reader skips ahead to the next paragraph.
+///
+/// # Example
+/// ```rust
+/// use deb822_lossless::lossy::ParagraphReader;
+/// let input: &[u8] = b"Package: hello\nVersion: 2.10\n\nPackage: world\nVersion: 1.0\n";
+/// let names = ParagraphReader::new(input)
+///     .map(|p| p.unwrap().get("Package").unwrap().to_string())
+///     .collect::<Vec<_>>();
+/// assert_eq!(names, vec!["hello", "world"]);
+/// ```
+#[cfg(feature = "std")]
+pub struct ParagraphReader<R> {
+    reader: R,
+    options: ParseOptions,
+    /// The text read but not yet parsed.
+    chunk: Vec<u8>,
+    /// The end of the last field in the chunk; trailing comments belong to the next paragraph.
+    field_end: usize,
+    /// The position of the chunk in the (normalized) input, in bytes and lines.
+    offset: usize,
+    line_offset: usize,
+    /// Paragraphs that have been parsed but not yet returned.
+    pending: alloc::vec::IntoIter<Paragraph>,
+    eof: bool,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut tokens = crate::lex::lex(s).peekable();
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> ParagraphReader<R> {
+    /// Create a new reader, using the default parse options.
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, ParseOptions::default())
+    }
 
-        let mut paragraphs = Vec::new();
-        let mut current_paragraph = Vec::new();
-
-        while let Some((k, t)) = tokens.next() {
-            match k {
-                SyntaxKind::EMPTY_LINE
-                | SyntaxKind::PARAGRAPH
-                | SyntaxKind::ROOT
-                | SyntaxKind::ENTRY => unreachable!(),
-                SyntaxKind::INDENT | SyntaxKind::COLON | SyntaxKind::ERROR => {
-                    return Err(Error::UnexpectedToken(k, t.to_string()));
-                }
-                SyntaxKind::WHITESPACE => {
-                    // ignore whitespace
-                }
-                SyntaxKind::KEY => {
-                    current_paragraph.push(Field {
-                        name: t.to_string(),
-                        value: String::new(),
-                    });
-
-                    match tokens.next() {
-                        Some((SyntaxKind::COLON, _)) => {}
-                        Some((k, t)) => {
-                            return Err(Error::UnexpectedToken(k, t.to_string()));
-                        }
-                        None => {
-                            return Err(Error::UnexpectedEof);
-                        }
-                    }
+    /// Create a new reader, using the given parse options.
+    pub fn with_options(reader: R, options: ParseOptions) -> Self {
+        Self {
+            reader,
+            options,
+            chunk: Vec::new(),
+            field_end: 0,
+            offset: 0,
+            line_offset: 0,
+            pending: Vec::new().into_iter(),
+            eof: false,
+        }
+    }
 
-                    while tokens.peek().map(|(k, _)| k) == Some(&SyntaxKind::WHITESPACE) {
-                        tokens.next();
-                    }
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
 
-                    for (k, t) in tokens.by_ref() {
-                        match k {
-                            SyntaxKind::VALUE => {
-                                current_paragraph.last_mut().unwrap().value = t.to_string();
-                            }
-                            SyntaxKind::NEWLINE => {
-                                break;
-                            }
-                            _ => return Err(Error::UnexpectedToken(k, t.to_string())),
-                        }
-                    }
+    /// Parse the first `end` bytes of the chunk.
+    fn parse_chunk(&mut self, end: usize) -> Result<(), Error> {
+        let chunk = self.chunk.drain(..end).collect::<Vec<_>>();
+        let text = self.options.decode(&chunk).map_err(Error::InvalidUtf8)?;
+        let text = self.options.normalize(&text);
+        let (offset, line_offset) = (self.offset, self.line_offset);
+        self.offset += text.len();
+        self.line_offset += count_lines(&text);
+        let mut paragraphs = Deb822::parse(&text, &self.options, None)?.0;
+        for paragraph in &mut paragraphs {
+            paragraph.shift(offset, line_offset);
+        }
+        self.pending = paragraphs.into_iter();
+        Ok(())
+    }
+}
 
-                    current_paragraph.last_mut().unwrap().value.push('\n');
-
-                    // while the next line starts with INDENT, it's a continuation of the value
-                    while tokens.peek().map(|(k, _)| k) == Some(&SyntaxKind::INDENT) {
-                        tokens.next();
-                        loop {
-                            match tokens.peek() {
-                                Some((SyntaxKind::VALUE, t)) => {
-                                    current_paragraph.last_mut().unwrap().value.push_str(t);
-                                    tokens.next();
-                                }
-                                Some((SyntaxKind::COMMENT, _)) => {
-                                    // ignore comments
-                                    tokens.next();
-                                }
-                                Some((SyntaxKind::NEWLINE, n)) => {
-                                    current_paragraph.last_mut().unwrap().value.push_str(n);
-                                    tokens.next();
-                                    break;
-                                }
-                                Some((SyntaxKind::KEY, _)) => {
-                                    break;
-                                }
-                                Some((k, _)) => {
-                                    return Err(Error::UnexpectedToken(*k, t.to_string()));
-                                }
-                                None => {
-                                    break;
-                                }
-                            }
-                        }
-                    }
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> Iterator for ParagraphReader<R> {
+    type Item = Result<Paragraph, Error>;
 
-                    // Trim the trailing newline
-                    assert_eq!(
-                        current_paragraph.last_mut().unwrap().value.pop(),
-                        Some('\n')
-                    );
-                }
-                SyntaxKind::VALUE => {
-                    return Err(Error::UnexpectedToken(k, t.to_string()));
-                }
-                SyntaxKind::COMMENT => {
-                    for (k, _) in tokens.by_ref() {
-                        if k == SyntaxKind::NEWLINE {
-                            break;
-                        }
-                    }
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(paragraph) = self.pending.next() {
+                return Some(Ok(paragraph));
+            }
+            if self.eof {
+                return None;
+            }
+            let start = self.chunk.len();
+            // Read at most one byte more than the limits allow, so that an overlong line
+            // is not read completely.
+            let limit = [
+                self.options
+                    .max_input_size
+                    .map(|max| max.saturating_sub(self.offset)),
+                self.options.max_paragraph_size,
+            ]
+            .into_iter()
+            .flatten()
+            .min()
+            .map_or(u64::MAX, |max| max.saturating_sub(start) as u64 + 1);
+            let mut reader = std::io::Read::take(&mut self.reader, limit);
+            let eof = match std::io::BufRead::read_until(&mut reader, b'\n', &mut self.chunk) {
+                Ok(n) => n == 0,
+                Err(e) => {
+                    self.eof = true;
+                    return Some(Err(e.into()));
                 }
-                SyntaxKind::NEWLINE => {
-                    if !current_paragraph.is_empty() {
-                        paragraphs.push(Paragraph {
-                            fields: current_paragraph,
-                        });
-                        current_paragraph = Vec::new();
-                    }
+            };
+            let error = if ParseOptions::exceeds(
+                self.options.max_input_size,
+                self.offset + self.chunk.len(),
+            ) {
+                Some(Error::InputTooLarge)
+            } else if ParseOptions::exceeds(self.options.max_paragraph_size, self.chunk.len()) {
+                Some(Error::ParagraphTooLarge)
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                self.eof = true;
+                return Some(Err(error));
+            }
+            let line = &self.chunk[start..];
+            if eof || (matches!(line, b"\n" | b"\r\n") && self.field_end > 0) {
+                // The chunk starts with complete paragraphs, which can be parsed by themselves.
+                self.eof = eof;
+                let end = if eof {
+                    self.chunk.len()
+                } else {
+                    self.field_end
+                };
+                self.field_end = 0;
+                if let Err(e) = self.parse_chunk(end) {
+                    return Some(Err(e));
                 }
+            } else if !matches!(line.first(), None | Some(b'#' | b'\n' | b'\r')) {
+                self.field_end = self.chunk.len();
             }
         }
-        if !current_paragraph.is_empty() {
-            paragraphs.push(Paragraph {
-                fields: current_paragraph,
-            });
-        }
-        Ok(Deb822(paragraphs))
     }
 }
 
+impl Paragraph {
+    /// Move the spans of the paragraph and its fields by the given number of bytes and lines.
+    #[cfg(feature = "std")]
+    pub(crate) fn shift(&mut self, offset: usize, lines: usize) {
+        let spans = self
+            .fields
+            .iter_mut()
+            .map(|f| &mut f.span)
+            .chain([&mut self.span]);
+        for span in spans.flatten() {
+            span.start += offset;
+            span.end += offset;
+            span.line += lines;
+        }
+    }
+
+    /// Create a paragraph from parsed fields, spanning from the first to the last field.
+    fn parsed(fields: Vec<Field>, comments: Vec<String>) -> Self {
+        let span = match (
+            fields.first().and_then(|f| f.span),
+            fields.last().and_then(|f| f.span),
+        ) {
+            (Some(first), Some(last)) => Some(Span {
+                end: last.end,
+                ..first
+            }),
+            _ => None,
+        };
+        Paragraph {
+            fields,
+            comments,
+            span,
+        }
+    }
+}
+
+/// Count the line endings in `text`.
+#[cfg(feature = "std")]
+pub(crate) fn count_lines(text: &str) -> usize {
+    // The CR of a CRLF pair does not start a new line.
+    let bytes = text.as_bytes();
+    memchr::memchr2_iter(b'\n', b'\r', bytes)
+        .filter(|i| !bytes[*i..].starts_with(b"\r\n"))
+        .count()
+}
+
+/// Split off the first line of the input.
+///
+/// Returns the contents of the line, its line ending (empty at the end of the input) and the
+/// remaining input.
+#[inline]
+fn split_line(input: &str) -> (&str, &str, &str) {
+    match memchr::memchr2(b'\n', b'\r', input.as_bytes()) {
+        Some(i) => {
+            let end = i + crate::common::newline_len(&input.as_bytes()[i..]);
+            (&input[..i], &input[i..end], &input[end..])
+        }
+        None => (input, "", ""),
+    }
+}
+
+/// Return the length of the leading run of bytes in `s` for which `f` returns true.
+#[inline]
+fn span_while(s: &str, f: impl Fn(char) -> bool) -> usize {
+    s.bytes().position(|b| !f(b as char)).unwrap_or(s.len())
+}
+
+impl core::str::FromStr for Deb822 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str_with(s, &ParseOptions::default())
+    }
+}
+
+impl Deb822 {
+    /// Parse a deb822 document from raw bytes, which have to be valid UTF-8.
+    ///
+    /// Use [`Deb822::from_bytes_with`] to handle other encodings.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with(bytes, &ParseOptions::default())
+    }
+
+    /// Parse a deb822 document from raw bytes, decoding them as specified by `options`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    /// use deb822_lossless::options::{EncodingPolicy, ParseOptions};
+    /// let bytes = b"Package: foo\nMaintainer: J\xf6rg <joerg@example.com>\n";
+    /// assert!(Deb822::from_bytes(bytes).is_err());
+    /// let options = ParseOptions::new().encoding(EncodingPolicy::Latin1Fallback);
+    /// let deb822 = Deb822::from_bytes_with(bytes, &options).unwrap();
+    /// assert_eq!(
+    ///     deb822.iter().next().unwrap().get("Maintainer"),
+    ///     Some("Jörg <joerg@example.com>")
+    /// );
+    /// ```
+    pub fn from_bytes_with(bytes: &[u8], options: &ParseOptions) -> Result<Self, Error> {
+        let text = options.decode(bytes).map_err(Error::InvalidUtf8)?;
+        Self::from_str_with(&text, options)
+    }
+
+    /// Parse a deb822 document, using the given options.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::{lossy::Deb822, ParseOptions};
+    /// let text = "Source: foo\n# Keep this in sync with debian/rules\nSection: net\n";
+    /// let options = ParseOptions::new().keep_comments(true);
+    /// let deb822 = Deb822::from_str_with(text, &options).unwrap();
+    /// let field = &deb822.iter().next().unwrap().fields[1];
+    /// assert_eq!(field.comments(), ["# Keep this in sync with debian/rules"]);
+    /// assert_eq!(deb822.to_string(), text);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(len = s.len()), err(level = "debug"))
+    )]
+    pub fn from_str_with(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        Self::parse(s, options, None)
+    }
+
+    /// Parse a deb822 document, recovering from errors.
+    ///
+    /// Rather than failing at the first error, malformed fields are skipped and the errors are
+    /// returned together with everything that could be parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::{lossy::Deb822, ParseOptions};
+    /// let text = "Package: foo\nBroken line\nVersion: 1.0\n\nPackage: bar\n";
+    /// let (deb822, diagnostics) = Deb822::from_str_recovering(text, &ParseOptions::default());
+    /// assert_eq!(deb822.len(), 2);
+    /// assert_eq!(deb822.iter().next().unwrap().get("Version"), Some("1.0"));
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].span.line, 2);
+    /// ```
+    pub fn from_str_recovering(s: &str, options: &ParseOptions) -> (Self, Vec<ParseDiagnostic>) {
+        let mut diagnostics = Vec::new();
+        let deb822 = Self::parse(s, options, Some(&mut diagnostics))
+            .expect("errors are recorded when recovering");
+        (deb822, diagnostics)
+    }
+
+    pub(crate) fn parse(
+        s: &str,
+        options: &ParseOptions,
+        mut diagnostics: Option<&mut Vec<ParseDiagnostic>>,
+    ) -> Result<Self, Error> {
+        let text = options.normalize(s);
+        let text = text.as_ref();
+        if ParseOptions::exceeds(options.max_input_size, text.len()) {
+            let error = Error::InputTooLarge;
+            return match diagnostics {
+                None => Err(error),
+                Some(diagnostics) => {
+                    let span = Span {
+                        start: 0,
+                        end: text.len(),
+                        line: 1,
+                        column: 1,
+                    };
+                    diagnostics.push(ParseDiagnostic { error, span });
+                    Ok(Deb822(Vec::new(), Vec::new()))
+                }
+            };
+        }
+        let mut paragraphs = Vec::new();
+        let mut current_paragraph: Vec<Field> = Vec::new();
+        let mut seen_fields = SeenFields::default();
+        let check_duplicates = options.duplicate_fields != DuplicateFieldPolicy::Allow;
+        let mut paragraph_comments: Vec<String> = Vec::new();
+        let mut comments: Vec<String> = Vec::new();
+        let mut standalone_comments = Vec::new();
+        let mut lines = Lines {
+            text,
+            rest: text,
+            line_no: 0,
+        };
+
+        while let Some((start, line, newline)) = lines.next_line() {
+            match line.chars().next() {
+                // An empty line ends the current paragraph
+                None => {
+                    if !current_paragraph.is_empty() {
+                        paragraphs.push(Paragraph::parsed(
+                            core::mem::take(&mut current_paragraph),
+                            core::mem::take(&mut paragraph_comments),
+                        ));
+                        seen_fields.clear();
+                    }
+                    if options.standalone_comments && !comments.is_empty() {
+                        standalone_comments
+                            .push((paragraphs.len(), core::mem::take(&mut comments)));
+                    }
+                    continue;
+                }
+                Some('#') if options.allow_comments => {
+                    if options.keep_comments || options.standalone_comments {
+                        comments.push(line.to_string());
+                    }
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            let result = parse_field(&mut lines, start, line, newline, options).and_then(|field| {
+                let duplicate = check_duplicates && seen_fields.contains(&field.name);
+                let span = field.span.unwrap();
+                let start = current_paragraph
+                    .first()
+                    .and_then(|f| f.span)
+                    .map_or(span.start, |s| s.start);
+                if duplicate {
+                    Err((Error::DuplicateField(field.name.clone()), span))
+                } else if ParseOptions::exceeds(options.max_paragraph_size, span.end - start) {
+                    Err((Error::ParagraphTooLarge, span))
+                } else {
+                    Ok(field)
+                }
+            });
+            let mut field = match (result, diagnostics.as_deref_mut()) {
+                (Ok(field), _) => field,
+                (Err((error, _)), None) => return Err(error),
+                (Err((error, span)), Some(diagnostics)) => {
+                    diagnostics.push(ParseDiagnostic { error, span });
+                    // Skip the remainder of the malformed field
+                    lines.skip_continuation();
+                    continue;
+                }
+            };
+
+            if !options.keep_comments {
+                // Only collected in case they turned out to be standalone
+                comments.clear();
+            }
+            // Comments before the first field belong to the paragraph itself
+            if current_paragraph.is_empty() {
+                paragraph_comments = core::mem::take(&mut comments);
+            }
+            field.comments = core::mem::take(&mut comments);
+            if check_duplicates {
+                seen_fields.insert(&field.name);
+            }
+            current_paragraph.push(field);
+        }
+        if !current_paragraph.is_empty() {
+            paragraphs.push(Paragraph::parsed(current_paragraph, paragraph_comments));
+        }
+        if options.standalone_comments && !comments.is_empty() {
+            standalone_comments.push((paragraphs.len(), comments));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(paragraphs = paragraphs.len(), "parsed deb822 text");
+        Ok(Deb822(paragraphs, standalone_comments))
+    }
+}
+
+/// Line-by-line cursor over the input, keeping track of positions.
+struct Lines<'a> {
+    text: &'a str,
+    rest: &'a str,
+    line_no: usize,
+}
+
+impl<'a> Lines<'a> {
+    /// Return the start offset, contents and line ending of the next line.
+    fn next_line(&mut self) -> Option<(usize, &'a str, &'a str)> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let start = self.text.len() - self.rest.len();
+        let (line, newline, remaining) = split_line(self.rest);
+        self.rest = remaining;
+        self.line_no += 1;
+        Some((start, line, newline))
+    }
+
+    /// Return the next line if it is a continuation line.
+    fn next_continuation(&mut self) -> Option<(usize, &'a str, &'a str)> {
+        if self.rest.starts_with(crate::common::is_indent) {
+            self.next_line()
+        } else {
+            None
+        }
+    }
+
+    fn skip_continuation(&mut self) {
+        while self.next_continuation().is_some() {}
+    }
+
+    /// The span of the most recently returned line.
+    fn span(&self, start: usize, line: &str) -> Span {
+        Span {
+            start,
+            end: start + line.len(),
+            line: self.line_no,
+            column: 1,
+        }
+    }
+}
+
+/// Parse a field starting at `line`, including its continuation lines.
+///
+/// The comments preceding the field are left for the caller to fill in.
+fn parse_field<'a>(
+    lines: &mut Lines<'a>,
+    start: usize,
+    line: &'a str,
+    newline: &str,
+    options: &ParseOptions,
+) -> Result<Field, (Error, Span)> {
+    let fail = |error| Err((error, lines.span(start, line)));
+    let c = line.chars().next().unwrap();
+    match c {
+        '#' => {
+            return fail(Error::UnexpectedToken(
+                SyntaxKind::COMMENT,
+                line.to_string(),
+            ));
+        }
+        ':' => {
+            return fail(Error::UnexpectedToken(SyntaxKind::COLON, ":".to_string()));
+        }
+        _ if crate::common::is_indent(c) => {
+            let indent = &line[..span_while(line, crate::common::is_indent)];
+            return fail(Error::UnexpectedToken(
+                SyntaxKind::INDENT,
+                indent.to_string(),
+            ));
+        }
+        _ if !crate::common::is_valid_initial_key_char(c) => {
+            return fail(Error::UnexpectedToken(SyntaxKind::ERROR, c.to_string()));
+        }
+        _ => {}
+    }
+
+    let (name, after_name) = line.split_at(span_while(line, crate::common::is_valid_key_char));
+    let after_colon = match after_name.chars().next() {
+        Some(':') => &after_name[1..],
+        Some(c) if crate::common::is_indent(c) => {
+            let whitespace = &after_name[..span_while(after_name, crate::common::is_indent)];
+            return fail(Error::UnexpectedToken(
+                SyntaxKind::WHITESPACE,
+                whitespace.to_string(),
+            ));
+        }
+        Some(_) => {
+            return fail(Error::UnexpectedToken(
+                SyntaxKind::VALUE,
+                after_name.to_string(),
+            ));
+        }
+        None if newline.is_empty() => {
+            return fail(Error::UnexpectedEof);
+        }
+        None => {
+            return fail(Error::UnexpectedToken(
+                SyntaxKind::NEWLINE,
+                newline.to_string(),
+            ));
+        }
+    };
+
+    let mut span = lines.span(start, line);
+    let first_line = after_colon.trim_start_matches(crate::common::is_indent);
+    let mut value = String::with_capacity(first_line.len() + 1);
+    value.push_str(first_line);
+    value.push('\n');
+    let mut value_lines = 1;
+    let mut value_comments = Vec::new();
+
+    // while the next line starts with an indent, it's a continuation of the value
+    while let Some((line_start, full_line, _)) = lines.next_continuation() {
+        span.end = line_start + full_line.len();
+        let line = &full_line[span_while(full_line, crate::common::is_indent)..];
+        if line.starts_with(':') {
+            return Err((
+                Error::UnexpectedToken(SyntaxKind::COLON, ":".to_string()),
+                lines.span(line_start, full_line),
+            ));
+        }
+        // Without comments, indented `#` lines are part of the value.
+        if line.starts_with('#') && options.allow_comments {
+            match options.value_comments {
+                ValueCommentPolicy::Discard => continue,
+                ValueCommentPolicy::Inline => {}
+                ValueCommentPolicy::Annotate => {
+                    value_comments.push((value_lines, line.to_string()));
+                    continue;
+                }
+            }
+        }
+        value.push_str(line);
+        // Line endings are normalized, so that CRLF input yields the same values.
+        value.push('\n');
+        value_lines += 1;
+    }
+
+    if ParseOptions::exceeds(options.max_field_size, span.end - span.start) {
+        return Err((Error::FieldTooLarge(name.to_string()), span));
+    }
+
+    // Trim the trailing newline
+    if value.ends_with(crate::common::is_newline) {
+        value.pop();
+    }
+    Ok(Field {
+        name: name.to_string(),
+        value,
+        value_comments,
+        span: Some(span),
+        ..Default::default()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::lex::lex;
 
+    #[test]
+    fn test_from_str_with() {
+        let text = "Package: foo\n# comment\nDepends: bar\n  \nPackage: baz\npackage: qux\n";
+        assert_eq!(text.parse::<Deb822>().unwrap().len(), 1);
+
+        let options = ParseOptions {
+            whitespace_only_separators: true,
+            ..Default::default()
+        };
+        assert_eq!(Deb822::from_str_with(text, &options).unwrap().len(), 2);
+
+        let options = ParseOptions::strict();
+        assert!(matches!(
+            Deb822::from_str_with(text, &options),
+            Err(Error::UnexpectedToken(SyntaxKind::COMMENT, _))
+        ));
+
+        let options = ParseOptions {
+            allow_comments: true,
+            whitespace_only_separators: true,
+            ..ParseOptions::strict()
+        };
+        assert!(matches!(
+            Deb822::from_str_with(text, &options),
+            Err(Error::DuplicateField(name)) if name == "package"
+        ));
+
+        assert!(matches!(
+            Deb822::from_bytes_with(b"Package: f\xf6o\n", &ParseOptions::strict()),
+            Err(Error::InvalidUtf8(_))
+        ));
+        let deb822 =
+            Deb822::from_bytes_with(b"Package: f\xf6o\n", &ParseOptions::lenient()).unwrap();
+        assert_eq!(
+            deb822.iter().next().unwrap().get("Package"),
+            Some("f\u{fffd}o")
+        );
+    }
+
     #[test]
     fn test_parse() {
         let input = r#"Package: hello
@@ -394,42 +1640,51 @@ Another-Field: value
         let mut deb822: Deb822 = input.parse().unwrap();
         assert_eq!(
             deb822,
-            Deb822(vec![
+            Deb822::from(vec![
                 Paragraph {
                     fields: vec![
                         Field {
                             name: "Package".to_string(),
                             value: "hello".to_string(),
+                            ..Default::default()
                         },
                         Field {
                             name: "Version".to_string(),
                             value: "2.10".to_string(),
+                            ..Default::default()
                         },
                         Field {
                             name: "Description".to_string(),
                             value: "A program that says hello\nSome more text".to_string(),
+                            ..Default::default()
                         },
                     ],
+                    ..Default::default()
                 },
                 Paragraph {
                     fields: vec![
                         Field {
                             name: "Package".to_string(),
                             value: "world".to_string(),
+                            ..Default::default()
                         },
                         Field {
                             name: "Version".to_string(),
                             value: "1.0".to_string(),
+                            ..Default::default()
                         },
                         Field {
                             name: "Description".to_string(),
                             value: "A program that says world\nAnd some more text".to_string(),
+                            ..Default::default()
                         },
                         Field {
                             name: "Another-Field".to_string(),
                             value: "value".to_string(),
+                            ..Default::default()
                         },
                     ],
+                    ..Default::default()
                 },
             ])
         );
@@ -459,7 +1714,10 @@ Another-Field: value
         para.insert("Another-Field", "value");
         assert_eq!(para.get("Another-Field"), Some("value"));
 
-        let mut newpara = Paragraph { fields: vec![] };
+        let mut newpara = Paragraph {
+            fields: vec![],
+            ..Default::default()
+        };
         newpara.insert("Package", "new");
         assert_eq!(newpara.to_string(), "Package: new\n");
     }
@@ -513,6 +1771,380 @@ Description: A program that says world
         );
     }
 
+    #[test]
+    fn test_get_all_replace_remove() {
+        let mut paragraph: Paragraph = "A: 1\nB: 2\nA: 3\nA: 4\n".parse().unwrap();
+        assert_eq!(
+            paragraph.get_all("A").collect::<Vec<_>>(),
+            vec!["1", "3", "4"]
+        );
+        assert_eq!(paragraph.replace("A", "5").as_deref(), Some("1"));
+        assert_eq!(paragraph.to_string(), "A: 5\nB: 2\n");
+        paragraph.insert("B", "6");
+        paragraph.remove("B");
+        assert_eq!(paragraph.to_string(), "A: 5\n");
+        assert_eq!(paragraph.get_all("B").count(), 0);
+    }
+
+    #[test]
+    fn test_get_ci() {
+        let paragraph: Paragraph = "package: foo\nMULTI-ARCH: same\nPackage: bar\n"
+            .parse()
+            .unwrap();
+        assert_eq!(paragraph.get_ci("Package"), Some("foo"));
+        assert_eq!(paragraph.get_ci("multi-arch"), Some("same"));
+        assert_eq!(paragraph.get_ci("Depends"), None);
+        assert!(paragraph.contains_key_ci("Multi-Arch"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_lossless_conversion() {
+        let text = "# header\nSource: foo\n# comment\nBuild-Depends: bar,\n # baz\n qux\n\nPackage: foo\nDescription: synopsis\n .\n body\n";
+        let options = ParseOptions {
+            keep_comments: true,
+            value_comments: crate::options::ValueCommentPolicy::Annotate,
+            ..Default::default()
+        };
+        let lossy = Deb822::from_str_with(text, &options).unwrap();
+        let lossless = lossy.to_lossless();
+        assert_eq!(lossless.to_string(), text);
+        assert_eq!(Deb822::from(&lossless), text.parse().unwrap());
+        assert_eq!(
+            Paragraph::from(lossless.paragraphs().next().unwrap()),
+            vec![("Source", "foo"), ("Build-Depends", "bar,\nqux")]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            lossy.iter().nth(1).unwrap().to_lossless().to_string(),
+            "Package: foo\nDescription: synopsis\n .\n body\n"
+        );
+        assert_eq!(Paragraph::from(vec![]).to_lossless().to_string(), "");
+    }
+
+    #[test]
+    fn test_standalone_comments() {
+        let text = "# header\n\n# foo\nPackage: foo\n\n# between\n# paragraphs\n\nPackage: bar\n# dangling\n\nPackage: baz\n\n# trailer\n";
+        let options = ParseOptions::new().standalone_comments(true);
+        let mut deb822 = Deb822::from_str_with(text, &options).unwrap();
+        assert_eq!(deb822.len(), 3);
+        assert_eq!(
+            deb822.1,
+            vec![
+                (0, vec!["# header".to_string()]),
+                (1, vec!["# between".to_string(), "# paragraphs".to_string()]),
+                (2, vec!["# dangling".to_string()]),
+                (3, vec!["# trailer".to_string()]),
+            ]
+        );
+        // Comments attached to a paragraph are only kept with keep_comments.
+        assert_eq!(
+            deb822.to_string(),
+            "# header\n\nPackage: foo\n\n# between\n# paragraphs\n\nPackage: bar\n\n# dangling\n\nPackage: baz\n\n# trailer\n"
+        );
+        let with_comments =
+            Deb822::from_str_with(text, &options.clone().keep_comments(true)).unwrap();
+        assert_eq!(with_comments.iter().next().unwrap().comments(), ["# foo"]);
+        assert_eq!(text.parse::<Deb822>().unwrap().1, vec![]);
+
+        deb822.retain_paragraphs(|p| p.get("Package") != Some("bar"));
+        deb822.insert_comment(1, vec!["# inserted".to_string()]);
+        assert_eq!(
+            deb822.to_string(),
+            "# header\n\nPackage: foo\n\n# between\n# paragraphs\n\n# dangling\n\n# inserted\n\nPackage: baz\n\n# trailer\n"
+        );
+    }
+
+    #[test]
+    fn test_keep_comments() {
+        let text = "# Leading\n\n# Source\nSource: foo\n# Binary\nBuild-Depends: bar\n\nPackage: foo\n# Long\nDescription: x\n y\n# Trailing\n";
+        assert_eq!(
+            text.parse::<Deb822>().unwrap().to_string(),
+            "Source: foo\nBuild-Depends: bar\n\nPackage: foo\nDescription: x\n y\n"
+        );
+
+        let options = ParseOptions {
+            keep_comments: true,
+            ..Default::default()
+        };
+        let deb822 = Deb822::from_str_with(text, &options).unwrap();
+        let mut paragraphs = deb822.iter();
+        let source = paragraphs.next().unwrap();
+        assert_eq!(source.comments(), ["# Leading", "# Source"]);
+        assert!(source.fields[0].comments().is_empty());
+        assert_eq!(source.fields[1].comments(), ["# Binary"]);
+        let binary = paragraphs.next().unwrap();
+        assert!(binary.comments().is_empty());
+        assert_eq!(binary.fields[1].comments(), ["# Long"]);
+        assert_eq!(
+            deb822.to_string(),
+            "# Leading\n# Source\nSource: foo\n# Binary\nBuild-Depends: bar\n\nPackage: foo\n# Long\nDescription: x\n y\n"
+        );
+    }
+
+    #[test]
+    fn test_value_comments() {
+        use crate::options::ValueCommentPolicy;
+
+        let text = "Description: foo\n bar\n # TODO: expand\n .\n baz\n #end\nDepends: a\n #b\n";
+        let parse = |value_comments| {
+            let options = ParseOptions {
+                value_comments,
+                ..Default::default()
+            };
+            Deb822::from_str_with(text, &options)
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap()
+        };
+
+        let paragraph = parse(ValueCommentPolicy::Discard);
+        assert_eq!(paragraph.get("Description"), Some("foo\nbar\n.\nbaz"));
+        assert_eq!(paragraph.get("Depends"), Some("a"));
+        assert!(paragraph.fields[0].value_comments().is_empty());
+        assert_eq!(
+            paragraph.to_string(),
+            "Description: foo\n bar\n .\n baz\nDepends: a\n"
+        );
+
+        let paragraph = parse(ValueCommentPolicy::Inline);
+        assert_eq!(
+            paragraph.get("Description"),
+            Some("foo\nbar\n# TODO: expand\n.\nbaz\n#end")
+        );
+        assert_eq!(paragraph.to_string(), text);
+
+        let paragraph = parse(ValueCommentPolicy::Annotate);
+        assert_eq!(paragraph.get("Description"), Some("foo\nbar\n.\nbaz"));
+        assert_eq!(
+            paragraph.fields[0].value_comments(),
+            vec![(2, "# TODO: expand".to_string()), (4, "#end".to_string())]
+        );
+        assert_eq!(
+            paragraph.fields[1].value_comments(),
+            vec![(1, "#b".to_string())]
+        );
+        assert_eq!(paragraph.to_string(), text);
+
+        // Without comments, indented `#` lines are part of the value
+        let options = ParseOptions {
+            allow_comments: false,
+            ..Default::default()
+        };
+        let deb822 = Deb822::from_str_with(text, &options).unwrap();
+        let paragraph = deb822.iter().next().unwrap();
+        assert_eq!(
+            paragraph.get("Description"),
+            Some("foo\nbar\n# TODO: expand\n.\nbaz\n#end")
+        );
+        assert!(Deb822::from_str_with(&format!("# x\n{}", text), &options).is_err());
+    }
+
+    #[test]
+    fn test_from_str_recovering() {
+        let text = "A: 1\n B\n : 2\n C\nD: 3\nE\n\n F\n\nG: 4\nG: 5\n H\nI: 6\nJ";
+        let options = ParseOptions {
+            duplicate_fields: crate::options::DuplicateFieldPolicy::Error,
+            ..Default::default()
+        };
+        let (deb822, diagnostics) = Deb822::from_str_recovering(text, &options);
+        assert_eq!(deb822.to_string(), "D: 3\n\nG: 4\nI: 6\n");
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| (d.span.line, &text[d.span.range()]))
+                .collect::<Vec<_>>(),
+            vec![
+                (3, " : 2"),
+                (6, "E"),
+                (8, " F"),
+                (11, "G: 5\n H"),
+                (14, "J")
+            ]
+        );
+        assert!(matches!(diagnostics[4].error, Error::UnexpectedEof));
+        assert_eq!(diagnostics[0].to_string(), "3:1: Unexpected token: :");
+
+        assert!(Deb822::from_str_with(text, &options).is_err());
+        let (_, diagnostics) = Deb822::from_str_recovering("A: 1\n", &options);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_from_reader() {
+        let text = "# Comment\n\nA: 1\n\n\nB: 2\n 3\n# Note\n\nC: 4\nD";
+        let options = ParseOptions {
+            keep_comments: true,
+            ..Default::default()
+        };
+        let result = Deb822::from_reader_with(text.as_bytes(), &options);
+        assert!(matches!(result, Err(Error::UnexpectedEof)));
+
+        let text = &text[..text.len() - 1];
+        let expected = Deb822::from_str_with(text, &options).unwrap();
+        let deb822 = Deb822::from_reader_with(text.as_bytes(), &options).unwrap();
+        assert_eq!(deb822, expected);
+        assert_eq!(deb822.to_string(), expected.to_string());
+        assert_eq!(deb822.iter().nth(2).unwrap().comments(), ["# Note"]);
+        let spans = deb822
+            .iter()
+            .flat_map(|p| p.fields.iter().map(|f| f.span()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            expected
+                .iter()
+                .flat_map(|p| p.fields.iter().map(|f| f.span()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            deb822.iter().map(|p| p.span()).collect::<Vec<_>>(),
+            expected.iter().map(|p| p.span()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_write() {
+        let deb822: Deb822 = "A: 1\n\nB: 2\n 3\n".parse().unwrap();
+        let mut buf = Vec::new();
+        deb822.write_to(&mut buf).unwrap();
+        assert_eq!(buf, b"A: 1\n\nB: 2\n 3\n");
+
+        let dir = std::env::temp_dir().join(format!("deb822-lossy-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("control");
+        std::fs::write(&path, "old").unwrap();
+        deb822.save_atomic(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), buf);
+        assert_eq!(
+            std::fs::read_dir(&dir).unwrap().count(),
+            1,
+            "temporary file was left behind"
+        );
+        assert!(deb822.save_atomic(dir.join("missing/control")).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_paragraph_reader() {
+        let input = b"Package: a\n\nPackage b\nVersion: 1\n\n# x\nPackage: c\n \nPackage: d";
+        let results = ParagraphReader::new(&input[..]).collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().get("Package"), Some("a"));
+        assert!(matches!(
+            results[1],
+            Err(Error::UnexpectedToken(SyntaxKind::WHITESPACE, _))
+        ));
+        let c = results[2].as_ref().unwrap();
+        assert_eq!(c.get("Package"), Some("c\n"));
+        assert_eq!(c.span().unwrap().line, 7);
+
+        let options = ParseOptions {
+            whitespace_only_separators: true,
+            ..Default::default()
+        };
+        let input = b"Package: c\n \nPackage: d\n";
+        let names = ParagraphReader::with_options(&input[..], options)
+            .filter_map(Result::ok)
+            .map(|p| p.get("Package").unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["c", "d"]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let text = "Package: a\nDescription: foo\n bar\n\nPackage: b\n";
+        let parse = |options: ParseOptions| Deb822::from_str_with(text, &options);
+        assert!(parse(ParseOptions::new().max_field_size(Some(21))).is_ok());
+        assert!(matches!(
+            parse(ParseOptions::new().max_field_size(Some(20))),
+            Err(Error::FieldTooLarge(name)) if name == "Description"
+        ));
+        assert!(matches!(
+            parse(ParseOptions::new().max_paragraph_size(Some(20))),
+            Err(Error::ParagraphTooLarge)
+        ));
+        assert!(matches!(
+            parse(ParseOptions::new().max_input_size(Some(20))),
+            Err(Error::InputTooLarge)
+        ));
+
+        // The reader stops reading once a limit is exceeded
+        let options = ParseOptions::new().max_paragraph_size(Some(16));
+        let input = b"Package: a\n\nPackage: b\nDescription: foo\n bar\n\nPackage: c\n";
+        let results = ParagraphReader::with_options(&input[..], options).collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().get("Package"), Some("a"));
+        assert!(matches!(results[1], Err(Error::ParagraphTooLarge)));
+
+        let options = ParseOptions::new().max_input_size(Some(16));
+        let input = [b'a'; 100];
+        let mut reader = ParagraphReader::with_options(&input[..], options);
+        assert!(matches!(reader.next(), Some(Err(Error::InputTooLarge))));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_spans() {
+        let text = "A: 1\n# comment\nB: 2\n 3\n\n\nC:\n 4\n";
+        let deb822: Deb822 = text.parse().unwrap();
+        let spans = deb822
+            .iter()
+            .flat_map(|p| p.fields.iter())
+            .map(|f| f.span().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans.iter().map(|s| &text[s.range()]).collect::<Vec<_>>(),
+            vec!["A: 1", "B: 2\n 3", "C:\n 4"]
+        );
+        assert_eq!(
+            spans.iter().map(|s| s.line).collect::<Vec<_>>(),
+            vec![1, 3, 7]
+        );
+        let paragraph = deb822.iter().next().unwrap().span().unwrap();
+        assert_eq!(&text[paragraph.range()], "A: 1\n# comment\nB: 2\n 3");
+        assert_eq!((paragraph.line, paragraph.column), (1, 1));
+
+        let mut paragraph: Paragraph = Vec::new().into();
+        paragraph.insert("A", "1");
+        assert_eq!(paragraph.span(), None);
+        assert_eq!(paragraph.fields[0].span(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_crlf() {
+        let text = "A: 1\r\n# comment\r\nB: 2\r\n 3\r\n\r\nC: 4\r\n";
+        let deb822: Deb822 = text.parse().unwrap();
+        assert_eq!(deb822.to_string(), "A: 1\nB: 2\n 3\n\nC: 4\n");
+        let fields = deb822
+            .iter()
+            .flat_map(|p| p.fields.iter())
+            .collect::<Vec<_>>();
+        assert_eq!(fields[1].value, "2\n3");
+        assert_eq!(
+            fields
+                .iter()
+                .map(|f| f.span().unwrap().line)
+                .collect::<Vec<_>>(),
+            vec![1, 3, 6]
+        );
+        assert_eq!(&text[fields[1].span().unwrap().range()], "B: 2\r\n 3");
+
+        let input = b"A: 1\r\n\r\nB: 2\r\n 3\r\n\r\nC: 4\r\n";
+        let paragraphs = ParagraphReader::new(&input[..])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(paragraphs[1].get("B"), Some("2\n3"));
+        assert_eq!(paragraphs[2].span().unwrap().line, 6);
+    }
+
     #[test]
     fn test_paragraph_iter() {
         let input = r#"Package: hello
@@ -531,13 +2163,115 @@ Version: 2.10
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_list() {
+        let mut para: Paragraph = "Source: foo\nUploaders: Jane <jane@example.com>,\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            para.get_list("Uploaders", Separator::Comma)
+                .collect::<Vec<_>>(),
+            vec!["Jane <jane@example.com>"]
+        );
+        assert_eq!(para.get_list("Binary", Separator::Comma).count(), 0);
+
+        let uploaders = [
+            "Jane Doe <jane@example.com>",
+            "Joe Bloggs <joe@example.com>",
+            "John Smith <john@example.com>",
+        ];
+        para.set_list("Uploaders", uploaders, Separator::Comma);
+        assert_eq!(
+            para.to_string(),
+            "Source: foo\nUploaders:\n Jane Doe <jane@example.com>,\n Joe Bloggs <joe@example.com>,\n John Smith <john@example.com>\n"
+        );
+        let reparsed: Paragraph = para.to_string().parse().unwrap();
+        assert_eq!(
+            reparsed
+                .get_list("Uploaders", Separator::Comma)
+                .collect::<Vec<_>>(),
+            uploaders
+        );
+
+        para.set_list("Uploaders", Vec::<String>::new(), Separator::Comma);
+        assert_eq!(para.to_string(), "Source: foo\n");
+    }
+
+    #[test]
+    fn test_conversions() {
+        let deb822: Deb822 = "Package: a\nVersion: 1\n\nPackage: b\n".parse().unwrap();
+        let names = (&deb822)
+            .into_iter()
+            .flat_map(|p| p.get("Package"))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b"]);
+
+        let first = deb822.iter().next().unwrap();
+        assert_eq!(
+            first.into_iter().collect::<Vec<_>>(),
+            vec![("Package", "a"), ("Version", "1")]
+        );
+        let fields: Vec<(String, String)> = first.clone().into();
+        assert_eq!(Paragraph::from(fields), *first);
+
+        let rebuilt: Deb822 = deb822
+            .clone()
+            .into_iter()
+            .filter(|p| p.get("Package") != Some("a"))
+            .collect();
+        assert_eq!(rebuilt.to_string(), "Package: b\n");
+        assert_eq!(
+            Deb822::from(vec![[("Package", "b")].into_iter().collect::<Paragraph>()]),
+            rebuilt
+        );
+    }
+
+    #[test]
+    fn test_parse_continuation_without_trailing_newline() {
+        let deb822: Deb822 = "Description: foo\n bar".parse().unwrap();
+        assert_eq!(
+            deb822.iter().next().unwrap().get("Description"),
+            Some("foo\nbar")
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            " foo\n".parse::<Deb822>(),
+            Err(Error::UnexpectedToken(SyntaxKind::INDENT, _))
+        ));
+        assert!(matches!(
+            "Foo bar: baz\n".parse::<Deb822>(),
+            Err(Error::UnexpectedToken(SyntaxKind::WHITESPACE, _))
+        ));
+        assert!(matches!("Foo".parse::<Deb822>(), Err(Error::UnexpectedEof)));
+        assert!(matches!(
+            "Foo: bar\n :baz\n".parse::<Deb822>(),
+            Err(Error::UnexpectedToken(SyntaxKind::COLON, _))
+        ));
+    }
+
+    #[test]
+    fn test_format_multiline_empty_first_line() {
+        let text = "Checksums-Sha256:\n abc 1 foo.dsc\n def 2 foo.tar.xz\n";
+        let para: Paragraph = text.parse().unwrap();
+        assert_eq!(
+            para.get("Checksums-Sha256"),
+            Some("\nabc 1 foo.dsc\ndef 2 foo.tar.xz")
+        );
+        assert_eq!(para.to_string(), text);
+    }
+
     #[test]
     fn test_format_multiline() {
         let para = Paragraph {
             fields: vec![Field {
                 name: "Description".to_string(),
                 value: "A program that says hello\nSome more text".to_string(),
+                ..Default::default()
             }],
+            ..Default::default()
         };
 
         assert_eq!(
@@ -545,4 +2279,52 @@ Version: 2.10
             "Description: A program that says hello\n Some more text\n"
         );
     }
+
+    #[test]
+    fn test_format_multiline_empty_lines() {
+        let para: Paragraph = vec![(
+            "Description".to_string(),
+            "synopsis\nfirst\n\n  \n.\nsecond\n".to_string(),
+        )]
+        .into();
+        let text = "Description: synopsis\n first\n \n \n .\n second\n";
+        assert_eq!(para.to_string(), text);
+        let reparsed: Paragraph = text.parse().unwrap();
+        assert_eq!(
+            reparsed.get("Description"),
+            Some("synopsis\nfirst\n\n\n.\nsecond")
+        );
+    }
+
+    #[test]
+    fn test_format_empty_line_roundtrip() {
+        let paragraph: Paragraph = "A: x\n \n y\n".parse().unwrap();
+        assert_eq!(paragraph.get("A"), Some("x\n\ny"));
+        let reparsed: Paragraph = paragraph.to_string().parse().unwrap();
+        assert_eq!(reparsed, paragraph);
+        assert_eq!(reparsed.to_string(), "A: x\n \n y\n");
+    }
+
+    #[test]
+    fn test_format_roundtrip() {
+        let text = r#"# Source paragraph
+Source: hello
+Uploaders: Jane <jane@example.com>,
+  Joe <joe@example.com>
+Checksums-Sha256:
+ abc 1 hello.dsc
+ def 2 hello.tar.xz
+
+Package: hello
+Description: example package
+ This package greets the world.
+ .
+   verbatim text
+"#;
+        let deb822: Deb822 = text.parse().unwrap();
+        let formatted = deb822.to_string();
+        let reparsed: Deb822 = formatted.parse().unwrap();
+        assert_eq!(reparsed, deb822);
+        assert_eq!(reparsed.to_string(), formatted);
+    }
 }
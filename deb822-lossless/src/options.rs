@@ -0,0 +1,485 @@
+//! Options controlling how strictly deb822 files are parsed.
+//!
+//! Different consumers have different requirements: dpkg rejects files that apt happily
+//! accepts. [`ParseOptions::strict`] and [`ParseOptions::lenient`] provide presets for both
+//! ends of the spectrum, while [`ParseOptions::default`] matches the behaviour of the plain
+//! `FromStr` implementations. [`ParseOptions::archive`] rejects the comments that are only
+//! allowed in source package files such as `debian/control`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::ParseOptions;
+//!
+//! let text = "Package: foo\r\n \t\r\nPackage: bar\r\n";
+//! let deb822 = deb822_lossless::lossy::Deb822::from_str_with(text, &ParseOptions::lenient())
+//!     .unwrap();
+//! assert_eq!(deb822.len(), 2);
+//!
+//! let text = "Package: foo\nPackage: bar\n";
+//! assert!(deb822_lossless::lossy::Deb822::from_str_with(text, &ParseOptions::strict()).is_err());
+//! ```
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+/// A set of field names; `HashSet` needs `std`.
+#[cfg(feature = "std")]
+type NameSet = std::collections::HashSet<String>;
+#[cfg(not(feature = "std"))]
+type NameSet = alloc::collections::BTreeSet<String>;
+
+/// The names of the fields seen so far in a paragraph, to detect duplicate fields.
+#[derive(Debug, Default)]
+pub(crate) struct SeenFields(NameSet);
+
+impl SeenFields {
+    /// Record `name`, returning whether it was seen before. Field names are case-insensitive.
+    pub(crate) fn insert(&mut self, name: &str) -> bool {
+        !self.0.insert(name.to_ascii_lowercase())
+    }
+
+    /// Whether `name` was seen before.
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.0.contains(&name.to_ascii_lowercase())
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+/// How to handle a field that appears more than once in a paragraph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DuplicateFieldPolicy {
+    /// Keep all occurrences of the field.
+    #[default]
+    Allow,
+
+    /// Treat the repeated field as an error.
+    Error,
+}
+
+/// How to handle input that is not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EncodingPolicy {
+    /// Reject input that is not valid UTF-8.
+    #[default]
+    Strict,
+
+    /// Replace invalid sequences with U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+
+    /// Decode lines that are not valid UTF-8 as ISO-8859-1 (latin-1).
+    ///
+    /// Old archive files and dpkg status files sometimes contain latin-1 text. Since every
+    /// byte sequence is valid latin-1, decoding never fails; lines that are valid UTF-8 are
+    /// left as they are.
+    Latin1Fallback,
+}
+
+/// How the lossy parser handles `#` comment lines within the continuation lines of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ValueCommentPolicy {
+    /// Discard the comments.
+    #[default]
+    Discard,
+
+    /// Keep the comments verbatim, as lines of the value.
+    Inline,
+
+    /// Keep the comments in [`crate::lossy::Field::value_comments()`], so that they are written
+    /// back in the same place but are not part of the value.
+    Annotate,
+}
+
+/// Options controlling how strictly deb822 files are parsed.
+///
+/// Start from [`ParseOptions::new`] or one of the presets, and adjust individual options
+/// with the methods of the same name; since options may be added in future releases, the
+/// struct can not be constructed directly:
+///
+/// ```rust
+/// use deb822_lossless::options::{DuplicateFieldPolicy, ParseOptions};
+///
+/// let options = ParseOptions::new()
+///     .duplicate_fields(DuplicateFieldPolicy::Error)
+///     .normalize_crlf(true)
+///     .keep_comments(true);
+/// let text = "# generated\r\nPackage: foo\r\n";
+/// let deb822 = deb822_lossless::lossy::Deb822::from_str_with(text, &options).unwrap();
+/// assert_eq!(deb822.to_string(), "# generated\nPackage: foo\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// How to handle fields that appear more than once in a paragraph.
+    pub duplicate_fields: DuplicateFieldPolicy,
+
+    /// How to handle input that is not valid UTF-8.
+    pub encoding: EncodingPolicy,
+
+    /// Whether `#` comment lines are accepted.
+    ///
+    /// When comments are not allowed, only `#` lines starting in the first column are
+    /// rejected; indented `#` lines are continuation lines of the field value.
+    pub allow_comments: bool,
+
+    /// Whether to convert CRLF line endings to LF before parsing.
+    pub normalize_crlf: bool,
+
+    /// Whether lines consisting only of spaces and tabs separate paragraphs.
+    ///
+    /// Debian Policy allows parsers to accept these, although control files should use
+    /// empty lines.
+    pub whitespace_only_separators: bool,
+
+    /// Whether the lossy parser keeps `#` comment lines, attached to the field or paragraph
+    /// that follows them.
+    ///
+    /// Comments are discarded by default. Comments that are not followed by any field, such
+    /// as those at the end of the input, are discarded unless
+    /// [`ParseOptions::standalone_comments`] is set.
+    pub keep_comments: bool,
+
+    /// Whether the lossy parser keeps blocks of `#` comment lines that are not attached to a
+    /// paragraph, because they are followed by an empty line or the end of the input.
+    ///
+    /// These are kept as standalone comments of the document, see
+    /// [`crate::lossy::Deb822::items`].
+    pub standalone_comments: bool,
+
+    /// How the lossy parser handles comment lines within the continuation lines of a field,
+    /// such as an indented `# TODO` in a `Description`.
+    pub value_comments: ValueCommentPolicy,
+
+    /// Whether the lossless parser checks that the resulting tree reproduces the input
+    /// byte-for-byte.
+    ///
+    /// Since the normalizations enabled by these options change the text, this fails for
+    /// input they apply to.
+    pub verify_roundtrip: bool,
+
+    /// The maximum size of the input in bytes, if any.
+    ///
+    /// Readers stop reading once the limit is exceeded, so that untrusted input can not
+    /// exhaust memory.
+    pub max_input_size: Option<usize>,
+
+    /// The maximum size of a paragraph in bytes, if any.
+    pub max_paragraph_size: Option<usize>,
+
+    /// The maximum size of a field in bytes, including its name and continuation lines, if
+    /// any.
+    pub max_field_size: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            duplicate_fields: DuplicateFieldPolicy::Allow,
+            encoding: EncodingPolicy::Strict,
+            allow_comments: true,
+            normalize_crlf: false,
+            whitespace_only_separators: false,
+            keep_comments: false,
+            standalone_comments: false,
+            value_comments: ValueCommentPolicy::Discard,
+            verify_roundtrip: false,
+            max_input_size: None,
+            max_paragraph_size: None,
+            max_field_size: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// The default options, matching the behaviour of the plain `FromStr` implementations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how to handle fields that appear more than once in a paragraph.
+    pub fn duplicate_fields(mut self, policy: DuplicateFieldPolicy) -> Self {
+        self.duplicate_fields = policy;
+        self
+    }
+
+    /// Set how to handle input that is not valid UTF-8.
+    pub fn encoding(mut self, policy: EncodingPolicy) -> Self {
+        self.encoding = policy;
+        self
+    }
+
+    /// Set whether `#` comment lines are accepted.
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Set whether to convert CRLF line endings to LF before parsing.
+    pub fn normalize_crlf(mut self, normalize: bool) -> Self {
+        self.normalize_crlf = normalize;
+        self
+    }
+
+    /// Set whether lines consisting only of spaces and tabs separate paragraphs.
+    pub fn whitespace_only_separators(mut self, allow: bool) -> Self {
+        self.whitespace_only_separators = allow;
+        self
+    }
+
+    /// Set whether the lossy parser keeps `#` comment lines.
+    pub fn keep_comments(mut self, keep: bool) -> Self {
+        self.keep_comments = keep;
+        self
+    }
+
+    /// Set whether the lossy parser keeps comment blocks that are not attached to a paragraph.
+    pub fn standalone_comments(mut self, keep: bool) -> Self {
+        self.standalone_comments = keep;
+        self
+    }
+
+    /// Set how the lossy parser handles comment lines within the continuation lines of a
+    /// field.
+    pub fn value_comments(mut self, policy: ValueCommentPolicy) -> Self {
+        self.value_comments = policy;
+        self
+    }
+
+    /// Set whether the lossless parser checks that the resulting tree reproduces the input.
+    pub fn verify_roundtrip(mut self, verify: bool) -> Self {
+        self.verify_roundtrip = verify;
+        self
+    }
+
+    /// Set the maximum size of the input in bytes.
+    pub fn max_input_size(mut self, max: Option<usize>) -> Self {
+        self.max_input_size = max;
+        self
+    }
+
+    /// Set the maximum size of a paragraph in bytes.
+    pub fn max_paragraph_size(mut self, max: Option<usize>) -> Self {
+        self.max_paragraph_size = max;
+        self
+    }
+
+    /// Set the maximum size of a field in bytes.
+    pub fn max_field_size(mut self, max: Option<usize>) -> Self {
+        self.max_field_size = max;
+        self
+    }
+
+    /// Options that reject anything dpkg would reject.
+    pub fn strict() -> Self {
+        Self {
+            duplicate_fields: DuplicateFieldPolicy::Error,
+            encoding: EncodingPolicy::Strict,
+            allow_comments: false,
+            normalize_crlf: false,
+            whitespace_only_separators: false,
+            keep_comments: false,
+            standalone_comments: false,
+            value_comments: ValueCommentPolicy::Discard,
+            verify_roundtrip: false,
+            max_input_size: None,
+            max_paragraph_size: None,
+            max_field_size: None,
+        }
+    }
+
+    /// Options for files written by dpkg and the archive tools, such as `Packages`, `Sources`
+    /// and `.dsc` files.
+    ///
+    /// Unlike `debian/control`, these files can not contain comments, so `#` lines are
+    /// rejected rather than skipped; everything else is accepted as by
+    /// [`ParseOptions::default`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use deb822_lossless::lossy::Deb822;
+    /// use deb822_lossless::ParseOptions;
+    ///
+    /// let text = "Package: foo\n# Version: 1.0\nArchitecture: all\n";
+    /// assert!(Deb822::from_str_with(text, &ParseOptions::default()).is_ok());
+    ///
+    /// let (_, diagnostics) = Deb822::from_str_recovering(text, &ParseOptions::archive());
+    /// assert_eq!(diagnostics[0].to_string(), "2:1: Unexpected token: # Version: 1.0");
+    /// ```
+    pub fn archive() -> Self {
+        Self {
+            allow_comments: false,
+            ..Self::default()
+        }
+    }
+
+    /// Options that accept as much as possible, similar to apt.
+    pub fn lenient() -> Self {
+        Self {
+            duplicate_fields: DuplicateFieldPolicy::Allow,
+            encoding: EncodingPolicy::Replace,
+            allow_comments: true,
+            normalize_crlf: true,
+            whitespace_only_separators: true,
+            keep_comments: false,
+            standalone_comments: false,
+            value_comments: ValueCommentPolicy::Discard,
+            verify_roundtrip: false,
+            max_input_size: None,
+            max_paragraph_size: None,
+            max_field_size: None,
+        }
+    }
+
+    /// Check whether `size` bytes exceed the limit `max`.
+    pub(crate) fn exceeds(max: Option<usize>, size: usize) -> bool {
+        max.is_some_and(|max| size > max)
+    }
+
+    /// Decode raw input according to the encoding policy.
+    pub(crate) fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>, core::str::Utf8Error> {
+        match self.encoding {
+            EncodingPolicy::Strict => core::str::from_utf8(bytes).map(Cow::Borrowed),
+            EncodingPolicy::Replace => Ok(String::from_utf8_lossy(bytes)),
+            EncodingPolicy::Latin1Fallback => Ok(match core::str::from_utf8(bytes) {
+                Ok(text) => Cow::Borrowed(text),
+                Err(_) => Cow::Owned(
+                    bytes
+                        .split_inclusive(|b| *b == b'\n')
+                        .map(|line| match core::str::from_utf8(line) {
+                            Ok(line) => Cow::Borrowed(line),
+                            Err(_) => Cow::Owned(line.iter().map(|b| *b as char).collect()),
+                        })
+                        .collect(),
+                ),
+            }),
+        }
+    }
+
+    /// Apply the line ending and separator normalizations to the input.
+    pub(crate) fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        if self.normalize_crlf && text.contains("\r\n") {
+            text = Cow::Owned(text.replace("\r\n", "\n"));
+        }
+        if self.whitespace_only_separators && has_whitespace_only_line(&text) {
+            let mut ret = String::with_capacity(text.len());
+            for line in text.split_inclusive('\n') {
+                let content = line.trim_end_matches(crate::common::is_newline);
+                if !content.is_empty() && content.chars().all(crate::common::is_indent) {
+                    ret.push_str(&line[content.len()..]);
+                } else {
+                    ret.push_str(line);
+                }
+            }
+            text = Cow::Owned(ret);
+        }
+        text
+    }
+
+    /// Find the first field name that occurs more than once, if duplicates are rejected.
+    #[cfg(feature = "std")]
+    pub(crate) fn find_duplicate<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a str>,
+    ) -> Option<&'a str> {
+        if self.duplicate_fields == DuplicateFieldPolicy::Allow {
+            return None;
+        }
+        let mut seen = SeenFields::default();
+        keys.into_iter().find(|key| seen.insert(key))
+    }
+}
+
+fn has_whitespace_only_line(text: &str) -> bool {
+    text.lines()
+        .any(|line| !line.is_empty() && line.chars().all(crate::common::is_indent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let options = ParseOptions::default();
+        assert_eq!(options.normalize("a\r\n \n"), "a\r\n \n");
+        assert_eq!(options.find_duplicate(["A", "A"]), None);
+        assert!(options.decode(b"\xff").is_err());
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(ParseOptions::new(), ParseOptions::default());
+        assert_eq!(
+            ParseOptions::new()
+                .duplicate_fields(DuplicateFieldPolicy::Error)
+                .allow_comments(false),
+            ParseOptions::archive().duplicate_fields(DuplicateFieldPolicy::Error)
+        );
+        let options = ParseOptions::strict()
+            .encoding(EncodingPolicy::Replace)
+            .normalize_crlf(true)
+            .whitespace_only_separators(true)
+            .allow_comments(true)
+            .duplicate_fields(DuplicateFieldPolicy::Allow);
+        assert_eq!(options, ParseOptions::lenient());
+        let options = ParseOptions::new()
+            .keep_comments(true)
+            .value_comments(ValueCommentPolicy::Annotate)
+            .verify_roundtrip(true);
+        assert!(options.keep_comments && options.verify_roundtrip);
+        assert_eq!(options.value_comments, ValueCommentPolicy::Annotate);
+        let options = ParseOptions::new()
+            .max_input_size(Some(1 << 20))
+            .max_paragraph_size(Some(1 << 16))
+            .max_field_size(Some(1 << 12));
+        assert_eq!(options.max_input_size, Some(1 << 20));
+        assert_eq!(options.max_paragraph_size, Some(1 << 16));
+        assert_eq!(options.max_field_size, Some(1 << 12));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let options = ParseOptions::lenient();
+        assert_eq!(options.normalize("a\r\n \t\r\nb\n"), "a\n\nb\n");
+        assert_eq!(options.normalize("a\n  \n b\n"), "a\n\n b\n");
+        assert!(matches!(options.normalize("a\n b\n"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_find_duplicate() {
+        let options = ParseOptions::strict();
+        assert_eq!(options.find_duplicate(["A", "B", "A"]), Some("A"));
+        assert_eq!(options.find_duplicate(["A", "B", "a"]), Some("a"));
+        assert_eq!(options.find_duplicate(["A", "B"]), None);
+
+        let mut seen = SeenFields::default();
+        assert!(!seen.insert("Package"));
+        assert!(seen.contains("PACKAGE"));
+        assert!(seen.insert("package"));
+        seen.clear();
+        assert!(!seen.contains("Package"));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            ParseOptions::lenient().decode(b"a\xffb").unwrap(),
+            "a\u{fffd}b"
+        );
+        assert_eq!(ParseOptions::strict().decode(b"ab").unwrap(), "ab");
+
+        let latin1 = ParseOptions {
+            encoding: EncodingPolicy::Latin1Fallback,
+            ..Default::default()
+        };
+        assert!(matches!(latin1.decode(b"ab").unwrap(), Cow::Borrowed("ab")));
+        // Only the lines that are not valid UTF-8 are decoded as latin-1.
+        assert_eq!(
+            latin1.decode(b"A: J\xf6rg\nB: J\xc3\xb6rg\n").unwrap(),
+            "A: J\u{f6}rg\nB: J\u{f6}rg\n"
+        );
+    }
+}
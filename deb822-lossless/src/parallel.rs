@@ -0,0 +1,162 @@
+//! Parallel parsing of large deb822 files.
+//!
+//! Paragraphs are separated by empty lines and can be parsed independently of each other, so
+//! large files such as the `Packages` index of a full archive can be split into chunks of
+//! whole paragraphs which are then parsed on the [`rayon`] thread pool.
+//!
+//! The result is the same as that of [`Deb822::from_str_with`], including the spans of the
+//! paragraphs and fields; when the input is malformed, the error for the first malformed
+//! field in the file is returned.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::lossy::Deb822;
+//! use deb822_lossless::ParseOptions;
+//!
+//! let text = "Package: foo\nVersion: 1.0\n\nPackage: bar\nVersion: 2.0\n";
+//! let deb822 = Deb822::from_str_parallel(text, &ParseOptions::default()).unwrap();
+//! assert_eq!(deb822, text.parse().unwrap());
+//! ```
+use crate::lossy::{count_lines, Deb822, Error};
+use crate::options::ParseOptions;
+use rayon::prelude::*;
+
+/// The smallest chunk of input that is worth handing to a separate thread.
+const MIN_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Find the end of the first paragraph that ends at or after `from`.
+///
+/// Returns the offset just past the line ending of the last line of the paragraph, or `None`
+/// if there is no empty line after `from`.
+fn paragraph_end(text: &str, from: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut finder = memchr::memmem::find_iter(&bytes[from..], b"\n\n").map(|i| from + i);
+    finder
+        .find(|&i| {
+            // Comments before an empty line are attached to the paragraph after it, so the
+            // input can only be split after a line that is not a comment.
+            let line_start = memchr::memrchr(b'\n', &bytes[..i]).map_or(0, |j| j + 1);
+            bytes.get(line_start) != Some(&b'#')
+        })
+        .map(|i| i + 1)
+}
+
+/// Split `text` into chunks of whole paragraphs of roughly `chunk_size` bytes.
+fn split_paragraphs(text: &str, chunk_size: usize) -> Vec<(usize, &str)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let end = if start + chunk_size < text.len() {
+            paragraph_end(text, start + chunk_size).unwrap_or(text.len())
+        } else {
+            text.len()
+        };
+        chunks.push((start, &text[start..end]));
+        start = end;
+    }
+    chunks
+}
+
+impl Deb822 {
+    /// Parse a deb822 document, splitting it into chunks that are parsed in parallel.
+    ///
+    /// This is only worthwhile for large inputs; small inputs are parsed on the current
+    /// thread. Paragraphs are only split on line feeds, so files with bare carriage returns
+    /// as line endings are always parsed on a single thread.
+    pub fn from_str_parallel(s: &str, options: &ParseOptions) -> Result<Self, Error> {
+        let text = options.normalize(s);
+        let chunk_size = (text.len() / rayon::current_num_threads()).max(MIN_CHUNK_SIZE);
+        let chunks = split_paragraphs(&text, chunk_size);
+        if chunks.len() <= 1 {
+            return Self::parse(&text, options, None);
+        }
+
+        let parsed = chunks
+            .par_iter()
+            .map(|(offset, chunk)| {
+                let deb822 = Self::parse(chunk, options, None)?;
+                Ok((*offset, count_lines(chunk), deb822))
+            })
+            .collect::<Vec<Result<_, Error>>>();
+
+        let mut paragraphs = Vec::new();
+        let mut comments = Vec::new();
+        let mut line_offset = 0;
+        for result in parsed {
+            let (offset, lines, Deb822(chunk, chunk_comments)) = result?;
+            comments.extend(
+                chunk_comments
+                    .into_iter()
+                    .map(|(at, lines)| (at + paragraphs.len(), lines)),
+            );
+            paragraphs.extend(chunk.into_iter().map(|mut paragraph| {
+                paragraph.shift(offset, line_offset);
+                paragraph
+            }));
+            line_offset += lines;
+        }
+        Ok(Deb822(paragraphs, comments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_paragraphs() {
+        let text = "A: 1\n\n# comment\n\nB: 2\n c\n\n\nC: 3\n";
+        let chunks = split_paragraphs(text, 1);
+        assert_eq!(
+            chunks,
+            vec![
+                (0, "A: 1\n"),
+                (5, "\n# comment\n\nB: 2\n c\n"),
+                (25, "\n\nC: 3\n")
+            ]
+        );
+        assert_eq!(split_paragraphs(text, 100), vec![(0, text)]);
+        assert_eq!(split_paragraphs("", 1), vec![]);
+    }
+
+    #[test]
+    fn test_from_str_parallel() {
+        let options = ParseOptions {
+            keep_comments: true,
+            standalone_comments: true,
+            ..Default::default()
+        };
+        let mut text = String::new();
+        for i in 0..10000 {
+            text.push_str(&format!(
+                "# paragraph {i}\nPackage: package{i}\nDescription: synopsis\n # note\n body\n\n# standalone {i}\n\n"
+            ));
+        }
+        // Make sure the input is split, regardless of the number of CPUs.
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let parallel = pool.install(|| Deb822::from_str_parallel(&text, &options).unwrap());
+        let sequential = Deb822::from_str_with(&text, &options).unwrap();
+        assert_eq!(parallel, sequential);
+        let spans = |deb822: &Deb822| {
+            deb822
+                .iter()
+                .flat_map(|p| p.fields.iter().map(|f| f.span()).chain([p.span()]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&parallel), spans(&sequential));
+
+        text.push_str("Package: broken\nno colon\n");
+        assert_eq!(
+            pool.install(|| Deb822::from_str_parallel(&text, &options))
+                .unwrap_err()
+                .to_string(),
+            Deb822::from_str_with(&text, &options)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! [`proptest`] strategies for deb822 documents.
+//!
+//! The generated documents follow the same rules as those generated in [`crate::arbitrary`]:
+//! they are always well-formed, every paragraph has at least one field and field names are
+//! unique within a paragraph. Continuation lines may be empty or start with `#`, so parsing the
+//! text of a generated document with [`ParseOptions::archive`] returns an equal document.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::{lossy::Deb822, ParseOptions};
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn roundtrip(deb822 in any::<Deb822>()) {
+//!         let options = ParseOptions::archive();
+//!         prop_assert_eq!(Deb822::from_str_with(&deb822.to_string(), &options).unwrap(), deb822);
+//!     }
+//! }
+//! # roundtrip();
+//! ```
+//!
+//! [`ParseOptions::archive`]: crate::ParseOptions::archive
+use ::proptest::prelude::*;
+
+/// A strategy for field names.
+pub fn field_name() -> impl Strategy<Value = String> {
+    "[A-Za-z][A-Za-z0-9-]{0,15}"
+}
+
+/// A strategy for the first line of a field value.
+///
+/// The line does not start with `#` or `:`, so that it can not be mistaken for a comment or
+/// the separator of a field.
+pub fn field_line() -> impl Strategy<Value = String> {
+    "[!-\"$-9;-~]([ -~]{0,30}[!-~])?"
+}
+
+/// A strategy for a continuation line of a field value.
+///
+/// Unlike the first line, a continuation line may be empty or start with `#`.
+pub fn continuation_line() -> impl Strategy<Value = String> {
+    prop_oneof![
+        1 => Just(String::new()),
+        1 => "#([ -~]{0,30}[!-~])?",
+        4 => field_line(),
+    ]
+}
+
+/// A strategy for field values of one or more lines, separated by `\n`.
+///
+/// The last line is never empty, since trailing empty lines are not part of the value.
+pub fn field_value() -> impl Strategy<Value = String> {
+    (
+        field_line(),
+        prop::collection::vec(continuation_line(), 0..=3),
+    )
+        .prop_map(|(first, mut lines)| {
+            while lines.last().is_some_and(|line| line.is_empty()) {
+                lines.pop();
+            }
+            lines.insert(0, first);
+            lines.join("\n")
+        })
+}
+
+/// A strategy for the fields of a paragraph, with unique names.
+fn fields() -> impl Strategy<Value = Vec<(String, String)>> {
+    prop::collection::vec((field_name(), field_value()), 1..=8).prop_map(|fields| {
+        let mut unique: Vec<(String, String)> = Vec::new();
+        for (name, value) in fields {
+            if !unique.iter().any(|(n, _)| n.eq_ignore_ascii_case(&name)) {
+                unique.push((name, value));
+            }
+        }
+        unique
+    })
+}
+
+impl Arbitrary for crate::lossy::Paragraph {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        fields()
+            .prop_map(|fields| fields.into_iter().collect())
+            .boxed()
+    }
+}
+
+impl Arbitrary for crate::lossy::Deb822 {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop::collection::vec(any::<crate::lossy::Paragraph>(), 0..=4)
+            .prop_map(crate::lossy::Deb822::from)
+            .boxed()
+    }
+}
+
+/// Parse generated text, keeping indented `#` lines in the values.
+fn to_lossless(text: &str) -> crate::lossless::Deb822 {
+    crate::lossless::Deb822::from_str_relaxed_with(text, &crate::ParseOptions::archive()).0
+}
+
+impl Arbitrary for crate::lossless::Paragraph {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<crate::lossy::Paragraph>()
+            .prop_map(|paragraph| {
+                to_lossless(&paragraph.to_string())
+                    .paragraphs()
+                    .next()
+                    .unwrap_or_default()
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for crate::lossless::Deb822 {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<crate::lossy::Deb822>()
+            .prop_map(|deb822| to_lossless(&deb822.to_string()))
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_lossy_roundtrip(deb822 in any::<crate::lossy::Deb822>()) {
+            let options = crate::ParseOptions::archive();
+            prop_assert_eq!(
+                crate::lossy::Deb822::from_str_with(&deb822.to_string(), &options).unwrap(),
+                deb822
+            );
+        }
+
+        #[test]
+        fn test_lossless_roundtrip(deb822 in any::<crate::lossless::Deb822>()) {
+            let text = deb822.to_string();
+            let options = crate::ParseOptions::archive();
+            prop_assert_eq!(crate::lossy::Deb822::from(&deb822).to_string(), text.clone());
+            prop_assert_eq!(
+                crate::lossless::Deb822::from_str_with(&text, &options).unwrap(),
+                deb822
+            );
+        }
+
+        #[test]
+        fn test_set_get(
+            mut paragraph in any::<crate::lossless::Paragraph>(),
+            name in field_name(),
+            value in field_value(),
+        ) {
+            paragraph.set(&name, &value);
+            prop_assert_eq!(paragraph.get(&name), Some(value));
+            let options = crate::ParseOptions::archive();
+            let reparsed = crate::lossless::Deb822::from_str_with(&paragraph.to_string(), &options)
+                .unwrap()
+                .paragraphs()
+                .next()
+                .unwrap();
+            prop_assert_eq!(reparsed, paragraph);
+        }
+    }
+}
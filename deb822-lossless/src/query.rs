@@ -0,0 +1,263 @@
+//! Filtering of paragraphs based on the values of their fields.
+//!
+//! This provides the building blocks for `grep-dctrl`-style searches: a [`Matcher`] checks a
+//! single value, and a [`Query`] combines matchers on specific fields with boolean operators.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::query::{Matcher, Query};
+//!
+//! let deb822: deb822_lossless::lossy::Deb822 = r#"Package: hello
+//! Section: devel
+//!
+//! Package: hello-traditional
+//! Section: oldlibs
+//! "#.parse().unwrap();
+//!
+//! let query = Query::field("Package", Matcher::substring("hello"))
+//!     .and(!Query::field("Section", Matcher::exact("oldlibs")));
+//! let names = deb822
+//!     .iter()
+//!     .filter(|p| query.is_match(*p))
+//!     .map(|p| p.get("Package").unwrap())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(names, vec!["hello"]);
+//! ```
+use regex::{Regex, RegexBuilder};
+
+/// A paragraph that can be searched.
+pub trait Searchable {
+    /// Call `f` with the name and value of each field in turn, stopping as soon as it returns
+    /// `true`.
+    ///
+    /// Returns whether `f` returned `true` for any field.
+    fn any_field(&self, f: &mut dyn FnMut(&str, &str) -> bool) -> bool;
+}
+
+impl Searchable for crate::lossy::Paragraph {
+    fn any_field(&self, f: &mut dyn FnMut(&str, &str) -> bool) -> bool {
+        self.iter().any(|(name, value)| f(name, value))
+    }
+}
+
+impl Searchable for crate::lazy::LazyParagraph<'_> {
+    fn any_field(&self, f: &mut dyn FnMut(&str, &str) -> bool) -> bool {
+        self.iter().any(|(name, value)| f(name, value))
+    }
+}
+
+impl Searchable for crate::lossless::Paragraph {
+    fn any_field(&self, f: &mut dyn FnMut(&str, &str) -> bool) -> bool {
+        self.items().any(|(name, value)| f(&name, &value))
+    }
+}
+
+impl<S: Searchable + ?Sized> Searchable for &S {
+    fn any_field(&self, f: &mut dyn FnMut(&str, &str) -> bool) -> bool {
+        (**self).any_field(f)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Substring(String),
+    Exact(String),
+    Regex(Regex),
+}
+
+/// Checks whether a field value matches a pattern.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    pattern: Pattern,
+    ignore_case: bool,
+}
+
+impl Matcher {
+    /// Match values that contain a string.
+    pub fn substring(pattern: &str) -> Self {
+        Self {
+            pattern: Pattern::Substring(pattern.to_string()),
+            ignore_case: false,
+        }
+    }
+
+    /// Match values that are equal to a string.
+    pub fn exact(pattern: &str) -> Self {
+        Self {
+            pattern: Pattern::Exact(pattern.to_string()),
+            ignore_case: false,
+        }
+    }
+
+    /// Match values that contain a match for a regular expression.
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Pattern::Regex(Regex::new(pattern)?),
+            ignore_case: false,
+        })
+    }
+
+    /// Ignore case when matching.
+    pub fn ignore_case(self) -> Self {
+        let pattern = match self.pattern {
+            Pattern::Substring(s) => Pattern::Substring(s.to_lowercase()),
+            Pattern::Exact(s) => Pattern::Exact(s.to_lowercase()),
+            Pattern::Regex(r) => Pattern::Regex(
+                RegexBuilder::new(r.as_str())
+                    .case_insensitive(true)
+                    .build()
+                    .expect("pattern was already compiled successfully"),
+            ),
+        };
+        Self {
+            pattern,
+            ignore_case: true,
+        }
+    }
+
+    /// Check whether a value matches.
+    pub fn is_match(&self, value: &str) -> bool {
+        match &self.pattern {
+            Pattern::Regex(r) => r.is_match(value),
+            Pattern::Substring(s) if self.ignore_case => value.to_lowercase().contains(s.as_str()),
+            Pattern::Substring(s) => value.contains(s.as_str()),
+            Pattern::Exact(s) if self.ignore_case => value.to_lowercase() == *s,
+            Pattern::Exact(s) => value == s,
+        }
+    }
+}
+
+/// A boolean combination of conditions on the fields of a paragraph.
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// Matches if the value of any of the given fields matches. If no fields are given, all
+    /// fields are considered.
+    ///
+    /// Field names are compared case-insensitively.
+    Match(Vec<String>, Matcher),
+
+    /// Matches if the inner query does not match.
+    Not(Box<Query>),
+
+    /// Matches if both queries match.
+    And(Box<Query>, Box<Query>),
+
+    /// Matches if either query matches.
+    Or(Box<Query>, Box<Query>),
+}
+
+impl Query {
+    /// Match the value of a single field.
+    pub fn field(name: &str, matcher: Matcher) -> Self {
+        Query::Match(vec![name.to_string()], matcher)
+    }
+
+    /// Match the values of any of a set of fields.
+    pub fn fields<S: AsRef<str>>(names: impl IntoIterator<Item = S>, matcher: Matcher) -> Self {
+        Query::Match(
+            names.into_iter().map(|n| n.as_ref().to_string()).collect(),
+            matcher,
+        )
+    }
+
+    /// Match the value of any field.
+    pub fn any_field(matcher: Matcher) -> Self {
+        Query::Match(Vec::new(), matcher)
+    }
+
+    /// Combine with another query, requiring both to match.
+    pub fn and(self, other: Query) -> Self {
+        Query::And(Box::new(self), Box::new(other))
+    }
+
+    /// Combine with another query, requiring either to match.
+    pub fn or(self, other: Query) -> Self {
+        Query::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Check whether a paragraph matches the query.
+    pub fn is_match(&self, paragraph: impl Searchable) -> bool {
+        self.is_match_dyn(&paragraph)
+    }
+
+    fn is_match_dyn(&self, paragraph: &dyn Searchable) -> bool {
+        match self {
+            Query::Match(fields, matcher) => paragraph.any_field(&mut |name, value| {
+                (fields.is_empty() || fields.iter().any(|f| f.eq_ignore_ascii_case(name)))
+                    && matcher.is_match(value)
+            }),
+            Query::Not(query) => !query.is_match_dyn(paragraph),
+            Query::And(a, b) => a.is_match_dyn(paragraph) && b.is_match_dyn(paragraph),
+            Query::Or(a, b) => a.is_match_dyn(paragraph) || b.is_match_dyn(paragraph),
+        }
+    }
+}
+
+impl std::ops::Not for Query {
+    type Output = Query;
+
+    fn not(self) -> Self::Output {
+        Query::Not(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lossy::Paragraph;
+
+    fn paragraph() -> Paragraph {
+        "Package: hello\nSection: devel\nDescription: A program\n that says Hello\n"
+            .parse::<crate::lossy::Deb822>()
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matcher() {
+        assert!(Matcher::substring("ell").is_match("hello"));
+        assert!(!Matcher::substring("ELL").is_match("hello"));
+        assert!(Matcher::substring("ELL").ignore_case().is_match("hello"));
+        assert!(Matcher::exact("hello").is_match("hello"));
+        assert!(!Matcher::exact("hell").is_match("hello"));
+        assert!(Matcher::exact("HELLO").ignore_case().is_match("Hello"));
+        assert!(Matcher::regex("^h.*o$").unwrap().is_match("hello"));
+        assert!(Matcher::regex("^H")
+            .unwrap()
+            .ignore_case()
+            .is_match("hello"));
+        assert!(Matcher::regex("(").is_err());
+    }
+
+    #[test]
+    fn test_query() {
+        let p = paragraph();
+        assert!(Query::field("Package", Matcher::exact("hello")).is_match(&p));
+        assert!(Query::field("package", Matcher::exact("hello")).is_match(&p));
+        assert!(!Query::field("Section", Matcher::exact("hello")).is_match(&p));
+        assert!(Query::fields(["Section", "Package"], Matcher::exact("hello")).is_match(&p));
+        assert!(Query::any_field(Matcher::substring("says Hello")).is_match(&p));
+        assert!(Query::field("Package", Matcher::exact("hello"))
+            .and(Query::field("Section", Matcher::exact("devel")))
+            .is_match(&p));
+        assert!(!Query::field("Package", Matcher::exact("hello"))
+            .and(!Query::field("Section", Matcher::exact("devel")))
+            .is_match(&p));
+        assert!(Query::field("Package", Matcher::exact("other"))
+            .or(Query::field("Section", Matcher::exact("devel")))
+            .is_match(&p));
+    }
+
+    #[test]
+    fn test_lazy_and_lossless() {
+        let text = "Package: hello\nSection: devel\n";
+        let query = Query::field("Section", Matcher::exact("devel"));
+        let lazy = crate::lazy::paragraphs(text).next().unwrap().unwrap();
+        assert!(query.is_match(&lazy));
+        let lossless: crate::lossless::Paragraph = text.parse().unwrap();
+        assert!(query.is_match(&lossless));
+    }
+}
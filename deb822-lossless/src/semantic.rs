@@ -0,0 +1,311 @@
+//! Semantic comparison of paragraphs and documents.
+//!
+//! The `PartialEq` and `Hash` implementations of the paragraph types are exact: for the
+//! lossless types, two paragraphs are equal only if their text is identical, including field
+//! order, comments and whitespace; for the lossy types, field order and values have to match.
+//!
+//! [`SemanticEq`] provides a looser notion of equality: two paragraphs are semantically equal
+//! if they contain the same fields with the same values, regardless of the order of the fields,
+//! the case of field names and the whitespace around values. The [`Semantic`] wrapper makes it
+//! possible to use this notion of equality for hash map keys.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::lossy::Paragraph;
+//! use deb822_lossless::{Semantic, SemanticEq};
+//! use std::collections::HashSet;
+//!
+//! let a: Paragraph = "Package: foo\nVersion: 1.0\n".parse().unwrap();
+//! let b: Paragraph = "version:    1.0\nPackage: foo\n".parse().unwrap();
+//! assert_ne!(a, b);
+//! assert!(a.semantic_eq(&b));
+//!
+//! let set: HashSet<_> = [Semantic(&a), Semantic(&b)].into_iter().collect();
+//! assert_eq!(set.len(), 1);
+//! ```
+//!
+//! The `eq_semantic` methods of the paragraph and document types can also take the order of
+//! the fields into account:
+//!
+//! ```rust
+//! use deb822_lossless::lossy::Paragraph;
+//! use deb822_lossless::semantic::FieldOrder;
+//!
+//! let a: Paragraph = "Package: foo\nVersion: 1.0\n".parse().unwrap();
+//! let b: Paragraph = "version:    1.0\nPackage: foo\n".parse().unwrap();
+//! assert!(a.eq_semantic(&b, FieldOrder::Ignore));
+//! assert!(!a.eq_semantic(&b, FieldOrder::Significant));
+//! ```
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+/// Equality that ignores formatting and field order.
+pub trait SemanticEq {
+    /// Check whether two items are semantically equal.
+    fn semantic_eq(&self, other: &Self) -> bool;
+
+    /// Feed the semantically relevant contents of this item into a hasher.
+    ///
+    /// Items that are semantically equal produce the same hash.
+    fn semantic_hash<H: Hasher>(&self, state: &mut H);
+}
+
+/// Wrapper that compares and hashes the wrapped item using [`SemanticEq`].
+#[derive(Debug, Clone, Copy)]
+pub struct Semantic<T>(pub T);
+
+impl<T: SemanticEq> PartialEq for Semantic<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.semantic_eq(&other.0)
+    }
+}
+
+impl<T: SemanticEq> Eq for Semantic<T> {}
+
+impl<T: SemanticEq> Hash for Semantic<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.semantic_hash(state)
+    }
+}
+
+impl<T: SemanticEq + ?Sized> SemanticEq for &T {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        (**self).semantic_eq(*other)
+    }
+
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).semantic_hash(state)
+    }
+}
+
+/// Whether the order of the fields in a paragraph matters when comparing paragraphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldOrder {
+    /// Paragraphs with the same fields in a different order are equal.
+    #[default]
+    Ignore,
+
+    /// The fields have to appear in the same order.
+    Significant,
+}
+
+/// Normalize a field value: surrounding whitespace on each line and trailing empty lines are
+/// not significant.
+pub(crate) fn normalize_value(value: &str) -> String {
+    let mut lines = value.lines().map(str::trim).collect::<Vec<_>>();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Return the fields of a paragraph in a canonical form and order.
+pub(crate) fn normalized_fields<'a>(
+    fields: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<(String, String)> {
+    normalized_fields_with(fields, FieldOrder::Ignore)
+}
+
+/// Return the fields of a paragraph in a canonical form, sorted unless their order matters.
+fn normalized_fields_with<'a>(
+    fields: impl IntoIterator<Item = (&'a str, &'a str)>,
+    order: FieldOrder,
+) -> Vec<(String, String)> {
+    let mut fields = fields
+        .into_iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), normalize_value(value)))
+        .collect::<Vec<_>>();
+    if order == FieldOrder::Ignore {
+        fields.sort();
+    }
+    fields
+}
+
+impl crate::lossy::Paragraph {
+    /// Check whether two paragraphs contain the same fields with the same values.
+    ///
+    /// The case of field names, whitespace around values and comments are ignored, as is the
+    /// order of the fields unless `order` is [`FieldOrder::Significant`].
+    pub fn eq_semantic(&self, other: &Self, order: FieldOrder) -> bool {
+        normalized_fields_with(self.iter(), order) == normalized_fields_with(other.iter(), order)
+    }
+}
+
+impl crate::lossy::Deb822 {
+    /// Check whether two documents contain semantically equal paragraphs, in the same order.
+    ///
+    /// Paragraphs are compared as by [`crate::lossy::Paragraph::eq_semantic`].
+    pub fn eq_semantic(&self, other: &Self, order: FieldOrder) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| a.eq_semantic(b, order))
+    }
+}
+
+impl SemanticEq for crate::lossy::Paragraph {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        normalized_fields(self.iter()) == normalized_fields(other.iter())
+    }
+
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        normalized_fields(self.iter()).hash(state)
+    }
+}
+
+impl SemanticEq for crate::lossy::Deb822 {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        self.eq_semantic(other, FieldOrder::Ignore)
+    }
+
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for paragraph in self.iter() {
+            paragraph.semantic_hash(state);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn lossless_fields(paragraph: &crate::lossless::Paragraph) -> Vec<(String, String)> {
+    lossless_fields_with(paragraph, FieldOrder::Ignore)
+}
+
+#[cfg(feature = "std")]
+fn lossless_fields_with(
+    paragraph: &crate::lossless::Paragraph,
+    order: FieldOrder,
+) -> Vec<(String, String)> {
+    let items = paragraph.items().collect::<Vec<_>>();
+    normalized_fields_with(items.iter().map(|(k, v)| (k.as_str(), v.as_str())), order)
+}
+
+#[cfg(feature = "std")]
+impl crate::lossless::Paragraph {
+    /// Check whether two paragraphs contain the same fields with the same values.
+    ///
+    /// The case of field names, whitespace around values and comments are ignored, as is the
+    /// order of the fields unless `order` is [`FieldOrder::Significant`].
+    pub fn eq_semantic(&self, other: &Self, order: FieldOrder) -> bool {
+        lossless_fields_with(self, order) == lossless_fields_with(other, order)
+    }
+}
+
+#[cfg(feature = "std")]
+impl crate::lossless::Deb822 {
+    /// Check whether two files contain semantically equal paragraphs, in the same order.
+    ///
+    /// Paragraphs are compared as by [`crate::lossless::Paragraph::eq_semantic`]; comments and
+    /// empty lines between paragraphs are ignored.
+    pub fn eq_semantic(&self, other: &Self, order: FieldOrder) -> bool {
+        let mut ours = self.paragraphs();
+        let mut theirs = other.paragraphs();
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return true,
+                (Some(a), Some(b)) if a.eq_semantic(&b, order) => {}
+                _ => return false,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl SemanticEq for crate::lossless::Paragraph {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        lossless_fields(self) == lossless_fields(other)
+    }
+
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        lossless_fields(self).hash(state)
+    }
+}
+
+#[cfg(feature = "std")]
+impl SemanticEq for crate::lossless::Deb822 {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        self.eq_semantic(other, FieldOrder::Ignore)
+    }
+
+    fn semantic_hash<H: Hasher>(&self, state: &mut H) {
+        for paragraph in self.paragraphs() {
+            paragraph.semantic_hash(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: SemanticEq>(t: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        t.semantic_hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_normalize_value() {
+        assert_eq!(normalize_value("  foo \n bar\n\n"), "foo\nbar");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_lossless() {
+        let a: crate::lossless::Deb822 =
+            "Package: foo\nDescription: short\n long\n\nPackage: bar\n"
+                .parse()
+                .unwrap();
+        let b: crate::lossless::Deb822 =
+            "# comment\nDescription: short\n   long\npackage: foo\n\nPackage: bar\n"
+                .parse()
+                .unwrap();
+        let c: crate::lossless::Deb822 =
+            "Package: bar\n\nPackage: foo\nDescription: short\n long\n"
+                .parse()
+                .unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, a.to_string().parse().unwrap());
+        assert!(a.semantic_eq(&b));
+        assert_eq!(hash(&a), hash(&b));
+        assert!(!a.semantic_eq(&c));
+
+        let pa = a.paragraphs().next().unwrap();
+        let pc = c.paragraphs().nth(1).unwrap();
+        assert_eq!(pa, pc);
+        assert!(pa.semantic_eq(&pc));
+        assert!(!pa.semantic_eq(&c.paragraphs().next().unwrap()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_field_order() {
+        let a: crate::lossless::Deb822 = "A: 1\n# comment\nB:  2\n\nC: 3\n".parse().unwrap();
+        let b: crate::lossless::Deb822 = "a: 1\nB: 2\n\n\n# comment\nC: 3".parse().unwrap();
+        let c: crate::lossless::Deb822 = "B: 2\nA: 1\n\nC: 3\n".parse().unwrap();
+        assert!(a.eq_semantic(&b, FieldOrder::Significant));
+        assert!(a.eq_semantic(&c, FieldOrder::Ignore));
+        assert!(!a.eq_semantic(&c, FieldOrder::Significant));
+
+        let a: crate::lossy::Deb822 = a.to_string().parse().unwrap();
+        let c: crate::lossy::Deb822 = c.to_string().parse().unwrap();
+        assert!(a.eq_semantic(&c, FieldOrder::Ignore));
+        assert!(!a.eq_semantic(&c, FieldOrder::Significant));
+        assert!(!a.eq_semantic(&"A: 1\nB: 2\n".parse().unwrap(), FieldOrder::Ignore));
+    }
+
+    #[test]
+    fn test_lossy() {
+        let a: crate::lossy::Deb822 = "A: 1\nB: 2\n\nC: 3\n".parse().unwrap();
+        let b: crate::lossy::Deb822 = "B: 2\nA: 1\n\nC:3\n".parse().unwrap();
+        let c: crate::lossy::Deb822 = "B: 2\nA: 1\n".parse().unwrap();
+        assert_ne!(a, b);
+        assert!(a.semantic_eq(&b));
+        assert_eq!(hash(&a), hash(&b));
+        assert!(!a.semantic_eq(&c));
+    }
+}
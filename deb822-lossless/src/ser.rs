@@ -0,0 +1,830 @@
+//! Serialize Rust data structures as deb822 text using serde.
+//!
+//! This is the counterpart of the [`de`](crate::de) module. Structs and maps serialize as a
+//! paragraph, with fields in the order in which they are declared or inserted. Sequences of
+//! structs or maps serialize as a document with one paragraph per element.
+//!
+//! Field values are written as follows:
+//!
+//! * Booleans are written as `yes` or `no`.
+//! * Fields whose value is `None` or `()` are omitted.
+//! * Strings containing newlines are written as multi-line fields.
+//! * Unit enum variants are written as their (possibly renamed) name.
+//! * Tuples are joined by spaces.
+//! * Sequences are joined by commas, unless their items are tuples or sequences themselves,
+//!   in which case each item is written on a line of its own (as in `Checksums-Sha256`).
+//!
+//! # Example
+//!
+//! ```rust
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Package {
+//!     #[serde(rename = "Package")]
+//!     name: String,
+//!     #[serde(rename = "Depends")]
+//!     depends: Vec<String>,
+//!     #[serde(rename = "Essential")]
+//!     essential: Option<bool>,
+//!     #[serde(rename = "Description")]
+//!     description: String,
+//! }
+//!
+//! let package = Package {
+//!     name: "hello".to_string(),
+//!     depends: vec!["libc6 (>= 2.34)".to_string(), "dpkg".to_string()],
+//!     essential: None,
+//!     description: "A program that says hello\nSome more text".to_string(),
+//! };
+//! assert_eq!(
+//!     deb822_lossless::to_string(&package).unwrap(),
+//!     "Package: hello\nDepends: libc6 (>= 2.34), dpkg\nDescription: A program that says hello\n Some more text\n"
+//! );
+//! ```
+use crate::lossy::{Deb822, Paragraph};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::ser::{self, Impossible, Serialize};
+
+/// Error type for serialization.
+#[derive(Debug)]
+pub enum Error {
+    /// The value can not be represented in deb822.
+    UnsupportedType(&'static str),
+
+    /// A field name is not valid in deb822.
+    InvalidFieldName(String),
+
+    /// A custom error reported by the type being serialized.
+    Message(String),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedType(kind) => write!(f, "Unsupported type: {}", kind),
+            Self::InvalidFieldName(name) => write!(f, "Invalid field name: {:?}", name),
+            Self::Message(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: core::fmt::Display>(msg: T) -> Self {
+        Self::Message(msg.to_string())
+    }
+}
+
+/// Serialize a value as deb822 text.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    Ok(to_deb822(value)?.to_string())
+}
+
+/// Serialize a value as a (lossy) deb822 document.
+pub fn to_deb822<T: Serialize + ?Sized>(value: &T) -> Result<Deb822, Error> {
+    value.serialize(DocumentSerializer)
+}
+
+/// Serialize a value as deb822 text to a writer.
+#[cfg(feature = "std")]
+pub fn to_writer<W: std::io::Write, T: Serialize + ?Sized>(
+    mut writer: W,
+    value: &T,
+) -> Result<(), Error> {
+    writer
+        .write_all(to_string(value)?.as_bytes())
+        .map_err(|e| Error::Message(e.to_string()))
+}
+
+/// Check that `name` can be used as a field name.
+fn check_field_name(name: String) -> Result<String, Error> {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c)
+            if crate::common::is_valid_initial_key_char(c)
+                && chars.all(crate::common::is_valid_key_char) =>
+        {
+            Ok(name)
+        }
+        _ => Err(Error::InvalidFieldName(name)),
+    }
+}
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) => $kind:literal,)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Error> {
+                Err(Error::UnsupportedType($kind))
+            }
+        )*
+    };
+}
+
+/// Serializes a document: either a single paragraph or a sequence of them.
+struct DocumentSerializer;
+
+impl ser::Serializer for DocumentSerializer {
+    type Ok = Deb822;
+    type Error = Error;
+    type SerializeSeq = ParagraphsSerializer;
+    type SerializeTuple = ParagraphsSerializer;
+    type SerializeTupleStruct = ParagraphsSerializer;
+    type SerializeTupleVariant = Impossible<Deb822, Error>;
+    type SerializeMap = SingleParagraphSerializer;
+    type SerializeStruct = SingleParagraphSerializer;
+    type SerializeStructVariant = Impossible<Deb822, Error>;
+
+    unsupported! {
+        serialize_bool(bool) => "bool",
+        serialize_i8(i8) => "integer",
+        serialize_i16(i16) => "integer",
+        serialize_i32(i32) => "integer",
+        serialize_i64(i64) => "integer",
+        serialize_u8(u8) => "integer",
+        serialize_u16(u16) => "integer",
+        serialize_u32(u32) => "integer",
+        serialize_u64(u64) => "integer",
+        serialize_f32(f32) => "float",
+        serialize_f64(f64) => "float",
+        serialize_char(char) => "char",
+        serialize_str(&str) => "string",
+        serialize_bytes(&[u8]) => "bytes",
+        serialize_none() => "none",
+        serialize_unit() => "unit",
+        serialize_unit_struct(&'static str) => "unit struct",
+        serialize_unit_variant(&'static str, u32, &'static str) => "enum",
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Deb822, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Deb822, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Deb822, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ParagraphsSerializer, Error> {
+        Ok(ParagraphsSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ParagraphsSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<ParagraphsSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SingleParagraphSerializer, Error> {
+        ParagraphSerializer
+            .serialize_map(len)
+            .map(SingleParagraphSerializer)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SingleParagraphSerializer, Error> {
+        ParagraphSerializer
+            .serialize_struct(name, len)
+            .map(SingleParagraphSerializer)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+}
+
+/// Collects the paragraphs of a document.
+struct ParagraphsSerializer(Vec<Paragraph>);
+
+impl ser::SerializeSeq for ParagraphsSerializer {
+    type Ok = Deb822;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.0.push(value.serialize(ParagraphSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Deb822, Error> {
+        Ok(Deb822::from(self.0))
+    }
+}
+
+impl ser::SerializeTuple for ParagraphsSerializer {
+    type Ok = Deb822;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Deb822, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for ParagraphsSerializer {
+    type Ok = Deb822;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Deb822, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Serializes a document consisting of a single paragraph.
+struct SingleParagraphSerializer(FieldsSerializer);
+
+impl ser::SerializeMap for SingleParagraphSerializer {
+    type Ok = Deb822;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.0.serialize_key(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.0.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Deb822, Error> {
+        Ok(Deb822::from(alloc::vec![ser::SerializeMap::end(self.0)?]))
+    }
+}
+
+impl ser::SerializeStruct for SingleParagraphSerializer {
+    type Ok = Deb822;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.0, key, value)
+    }
+
+    fn end(self) -> Result<Deb822, Error> {
+        Ok(Deb822::from(alloc::vec![ser::SerializeStruct::end(
+            self.0
+        )?]))
+    }
+}
+
+/// Serializes a struct or map as a paragraph.
+struct ParagraphSerializer;
+
+impl ser::Serializer for ParagraphSerializer {
+    type Ok = Paragraph;
+    type Error = Error;
+    type SerializeSeq = Impossible<Paragraph, Error>;
+    type SerializeTuple = Impossible<Paragraph, Error>;
+    type SerializeTupleStruct = Impossible<Paragraph, Error>;
+    type SerializeTupleVariant = Impossible<Paragraph, Error>;
+    type SerializeMap = FieldsSerializer;
+    type SerializeStruct = FieldsSerializer;
+    type SerializeStructVariant = Impossible<Paragraph, Error>;
+
+    unsupported! {
+        serialize_bool(bool) => "bool",
+        serialize_i8(i8) => "integer",
+        serialize_i16(i16) => "integer",
+        serialize_i32(i32) => "integer",
+        serialize_i64(i64) => "integer",
+        serialize_u8(u8) => "integer",
+        serialize_u16(u16) => "integer",
+        serialize_u32(u32) => "integer",
+        serialize_u64(u64) => "integer",
+        serialize_f32(f32) => "float",
+        serialize_f64(f64) => "float",
+        serialize_char(char) => "char",
+        serialize_str(&str) => "string",
+        serialize_bytes(&[u8]) => "bytes",
+        serialize_none() => "none",
+        serialize_unit() => "unit",
+        serialize_unit_struct(&'static str) => "unit struct",
+        serialize_unit_variant(&'static str, u32, &'static str) => "enum",
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Paragraph, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Paragraph, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Paragraph, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(Error::UnsupportedType("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(Error::UnsupportedType("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(Error::UnsupportedType("tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<FieldsSerializer, Error> {
+        Ok(FieldsSerializer {
+            paragraph: Vec::new().into(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<FieldsSerializer, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+}
+
+/// Collects the fields of a paragraph.
+struct FieldsSerializer {
+    paragraph: Paragraph,
+    key: Option<String>,
+}
+
+impl FieldsSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.paragraph.insert(&key, &value.text);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for FieldsSerializer {
+    type Ok = Paragraph;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = match key.serialize(ValueSerializer)? {
+            Some(Value {
+                text,
+                compound: false,
+            }) => text,
+            _ => return Err(Error::UnsupportedType("non-string key")),
+        };
+        self.key = Some(check_field_name(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <Error as ser::Error>::custom("value serialized before key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Paragraph, Error> {
+        Ok(self.paragraph)
+    }
+}
+
+impl ser::SerializeStruct for FieldsSerializer {
+    type Ok = Paragraph;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.insert(check_field_name(key.to_string())?, value)
+    }
+
+    fn end(self) -> Result<Paragraph, Error> {
+        Ok(self.paragraph)
+    }
+}
+
+/// A serialized field value.
+struct Value {
+    text: String,
+    /// Whether the value consists of several words, and should be put on a line of its own
+    /// when used as an item in a list.
+    compound: bool,
+}
+
+impl Value {
+    fn scalar(text: impl ToString) -> Option<Self> {
+        Some(Self {
+            text: text.to_string(),
+            compound: false,
+        })
+    }
+}
+
+/// Serializes a field value; `None` means the field should be omitted.
+struct ValueSerializer;
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Option<Value>, Error> {
+                Ok(Value::scalar(v))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = Impossible<Option<Value>, Error>;
+    type SerializeMap = Impossible<Option<Value>, Error>;
+    type SerializeStruct = Impossible<Option<Value>, Error>;
+    type SerializeStructVariant = Impossible<Option<Value>, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Option<Value>, Error> {
+        Ok(Value::scalar(if v { "yes" } else { "no" }))
+    }
+
+    serialize_display! {
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Option<Value>, Error> {
+        Err(Error::UnsupportedType("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Option<Value>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<Value>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<Value>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<Value>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Option<Value>, Error> {
+        Ok(Value::scalar(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Option<Value>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Option<Value>, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ListSerializer, Error> {
+        Ok(ListSerializer {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            tuple: false,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer, Error> {
+        Ok(ListSerializer {
+            items: Vec::with_capacity(len),
+            tuple: true,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<ListSerializer, Error> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(Error::UnsupportedType("nested map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(Error::UnsupportedType("nested struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::UnsupportedType("enum"))
+    }
+}
+
+/// Serializes a sequence or tuple into a single field value.
+struct ListSerializer {
+    items: Vec<Value>,
+    tuple: bool,
+}
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.items.push(value);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Value>, Error> {
+        let texts = self.items.iter().map(|i| i.text.as_str());
+        let text = if self.tuple {
+            texts.collect::<Vec<_>>().join(" ")
+        } else if self.items.iter().any(|i| i.compound) {
+            // One item per line, starting on the line after the field name
+            texts.fold(String::new(), |mut acc, item| {
+                acc.push('\n');
+                acc.push_str(item);
+                acc
+            })
+        } else {
+            texts.collect::<Vec<_>>().join(", ")
+        };
+        Ok(Some(Value {
+            text,
+            compound: true,
+        }))
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<Value>, Error> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Priority {
+        Required,
+        Optional,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Package {
+        #[serde(rename = "Package")]
+        name: String,
+        #[serde(rename = "Installed-Size")]
+        installed_size: Option<u64>,
+        #[serde(rename = "Priority")]
+        priority: Option<Priority>,
+        #[serde(rename = "Essential", default)]
+        essential: bool,
+        #[serde(rename = "Depends", default)]
+        depends: Vec<String>,
+        #[serde(rename = "Checksums-Sha256", default)]
+        checksums: Vec<(String, u64, String)>,
+        #[serde(rename = "Description")]
+        description: Option<String>,
+    }
+
+    #[test]
+    fn test_struct() {
+        let package = Package {
+            name: "hello".to_string(),
+            installed_size: Some(280),
+            priority: Some(Priority::Optional),
+            essential: false,
+            depends: vec!["libc6".to_string(), "dpkg (>= 1.20)".to_string()],
+            checksums: vec![
+                ("abcd".to_string(), 12, "hello.dsc".to_string()),
+                ("ef01".to_string(), 34, "hello.tar.xz".to_string()),
+            ],
+            description: Some("A program that says hello\nSome more text".to_string()),
+        };
+        let text = to_string(&package).unwrap();
+        assert_eq!(
+            text,
+            "Package: hello
+Installed-Size: 280
+Priority: optional
+Essential: no
+Depends: libc6, dpkg (>= 1.20)
+Checksums-Sha256:
+ abcd 12 hello.dsc
+ ef01 34 hello.tar.xz
+Description: A program that says hello
+ Some more text
+"
+        );
+        assert_eq!(crate::de::from_str::<Package>(&text).unwrap(), package);
+    }
+
+    #[test]
+    fn test_document() {
+        let packages = vec![
+            std::collections::BTreeMap::from([("Package", "a"), ("Version", "1")]),
+            std::collections::BTreeMap::from([("Package", "b")]),
+        ];
+        assert_eq!(
+            to_string(&packages).unwrap(),
+            "Package: a\nVersion: 1\n\nPackage: b\n"
+        );
+        let deb822 = to_deb822(&packages).unwrap();
+        assert_eq!(deb822.len(), 2);
+
+        let mut buf = Vec::new();
+        to_writer(&mut buf, &packages[1]).unwrap();
+        assert_eq!(buf, b"Package: b\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            to_string("foo"),
+            Err(Error::UnsupportedType("string"))
+        ));
+        assert!(matches!(
+            to_string(&std::collections::BTreeMap::from([("Bad Name", "a")])),
+            Err(Error::InvalidFieldName(name)) if name == "Bad Name"
+        ));
+        assert!(matches!(
+            to_string(&std::collections::BTreeMap::from([("-Foo", "a")])),
+            Err(Error::InvalidFieldName(_))
+        ));
+
+        #[derive(Serialize)]
+        struct Nested {
+            #[serde(rename = "Inner")]
+            inner: std::collections::BTreeMap<String, String>,
+        }
+        assert!(matches!(
+            to_string(&Nested {
+                inner: Default::default()
+            }),
+            Err(Error::UnsupportedType("nested map"))
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+//! OpenPGP clearsigned documents.
+//!
+//! Files such as `InRelease`, signed `.dsc` and signed `.changes` files wrap their deb822
+//! content in an OpenPGP cleartext signature (RFC 4880, section 7). This module splits such
+//! documents into the armor headers, the signed payload and the signature block. The signature
+//! is not verified.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::signed::ClearSigned;
+//!
+//! let text = r#"-----BEGIN PGP SIGNED MESSAGE-----
+//! Hash: SHA256
+//!
+//! Format: 3.0 (quilt)
+//! Source: hello
+//! - Version: 2.10-3
+//! -----BEGIN PGP SIGNATURE-----
+//!
+//! iQIzBAEBCAAdFiEE
+//! =olY7
+//! -----END PGP SIGNATURE-----
+//! "#;
+//!
+//! let signed: ClearSigned = text.parse().unwrap();
+//! assert_eq!(signed.hash(), Some("SHA256"));
+//! assert!(signed.signature.starts_with("-----BEGIN PGP SIGNATURE-----\n"));
+//!
+//! let deb822: deb822_lossless::lossy::Deb822 = signed.parse_payload().unwrap();
+//! let paragraph = deb822.iter().next().unwrap();
+//! assert_eq!(paragraph.get("Version"), Some("2.10-3"));
+//! ```
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+const BEGIN_MESSAGE: &str = "-----BEGIN PGP SIGNED MESSAGE-----";
+const BEGIN_SIGNATURE: &str = "-----BEGIN PGP SIGNATURE-----";
+const END_SIGNATURE: &str = "-----END PGP SIGNATURE-----";
+
+/// Error parsing a clearsigned document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// An armor header line is not of the form `Key: Value`.
+    InvalidHeader(String),
+
+    /// The armor headers are not followed by an empty line and the payload.
+    MissingPayload,
+
+    /// The payload is not followed by a signature block.
+    MissingSignature,
+
+    /// The signature block is not terminated.
+    TruncatedSignature,
+
+    /// There is text after the end of the signature block.
+    TrailingData(String),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHeader(line) => write!(f, "invalid armor header: {}", line),
+            Self::MissingPayload => write!(f, "missing payload"),
+            Self::MissingSignature => write!(f, "missing PGP signature"),
+            Self::TruncatedSignature => write!(f, "truncated PGP signature"),
+            Self::TrailingData(line) => write!(f, "junk after PGP signature: {}", line),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// A clearsigned document, split into its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearSigned {
+    /// The armor headers, such as `Hash: SHA256`, in order.
+    pub headers: Vec<(String, String)>,
+
+    /// The signed text, with dash-escaping removed.
+    pub payload: String,
+
+    /// The ASCII-armored signature, including the `BEGIN` and `END` lines.
+    pub signature: String,
+}
+
+impl ClearSigned {
+    /// Return the value of the `Hash` armor header, if present.
+    pub fn hash(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name == "Hash")
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parse the payload, e.g. into a [`crate::lossy::Deb822`] or a `debian_control::Control`.
+    pub fn parse_payload<T: core::str::FromStr>(&self) -> Result<T, T::Err> {
+        self.payload.parse()
+    }
+}
+
+/// Strip the line ending from a line.
+fn content(line: &str) -> &str {
+    line.trim_end_matches(crate::common::is_newline)
+}
+
+impl core::str::FromStr for ClearSigned {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.split_inclusive('\n');
+        if lines.next().map(content) != Some(BEGIN_MESSAGE) {
+            return Err(Error::MissingPayload);
+        }
+
+        let mut headers = Vec::new();
+        loop {
+            let line = content(lines.next().ok_or(Error::MissingPayload)?);
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| Error::InvalidHeader(line.to_string()))?;
+            headers.push((name.to_string(), value.trim().to_string()));
+        }
+
+        let mut payload = String::new();
+        loop {
+            let line = lines.next().ok_or(Error::MissingSignature)?;
+            if content(line) == BEGIN_SIGNATURE {
+                let mut signature = line.to_string();
+                loop {
+                    let line = lines.next().ok_or(Error::TruncatedSignature)?;
+                    signature.push_str(line);
+                    if content(line) == END_SIGNATURE {
+                        break;
+                    }
+                }
+                if let Some(line) = lines.find(|line| !line.trim().is_empty()) {
+                    return Err(Error::TrailingData(content(line).to_string()));
+                }
+                return Ok(ClearSigned {
+                    headers,
+                    payload,
+                    signature,
+                });
+            }
+            payload.push_str(line.strip_prefix("- ").unwrap_or(line));
+        }
+    }
+}
+
+/// Check whether `text` is a clearsigned document.
+pub fn is_signed(text: &str) -> bool {
+    text.split_inclusive('\n')
+        .next()
+        .is_some_and(|line| content(line) == BEGIN_MESSAGE)
+}
+
+/// Return the payload of `text` if it is clearsigned, or `text` itself otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use deb822_lossless::signed::strip_signature;
+///
+/// assert_eq!(strip_signature("Package: foo\n").unwrap(), "Package: foo\n");
+/// ```
+pub fn strip_signature(text: &str) -> Result<Cow<'_, str>, Error> {
+    if is_signed(text) {
+        Ok(Cow::Owned(text.parse::<ClearSigned>()?.payload))
+    } else {
+        Ok(Cow::Borrowed(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNED: &str = "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA512\n\nOrigin: Debian\n- -Suite: unstable\n\nPackage: foo\n-----BEGIN PGP SIGNATURE-----\n\nabcd\n=efgh\n-----END PGP SIGNATURE-----\n";
+
+    #[test]
+    fn test_parse() {
+        let signed: ClearSigned = SIGNED.parse().unwrap();
+        assert_eq!(
+            signed.headers,
+            vec![("Hash".to_string(), "SHA512".to_string())]
+        );
+        assert_eq!(signed.hash(), Some("SHA512"));
+        assert_eq!(
+            signed.payload,
+            "Origin: Debian\n-Suite: unstable\n\nPackage: foo\n"
+        );
+        assert_eq!(
+            signed.signature,
+            "-----BEGIN PGP SIGNATURE-----\n\nabcd\n=efgh\n-----END PGP SIGNATURE-----\n"
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let signed: ClearSigned = SIGNED.replace('\n', "\r\n").parse().unwrap();
+        assert_eq!(signed.hash(), Some("SHA512"));
+        assert_eq!(
+            signed.payload,
+            "Origin: Debian\r\n-Suite: unstable\r\n\r\nPackage: foo\r\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_signature() {
+        assert!(is_signed(SIGNED));
+        assert!(!is_signed("Package: foo\n"));
+        assert!(matches!(
+            strip_signature(SIGNED).unwrap(),
+            Cow::Owned(payload) if payload.starts_with("Origin: Debian\n")
+        ));
+        assert!(matches!(strip_signature("").unwrap(), Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            "Package: foo\n".parse::<ClearSigned>(),
+            Err(Error::MissingPayload)
+        );
+        assert_eq!(
+            "-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n".parse::<ClearSigned>(),
+            Err(Error::MissingPayload)
+        );
+        assert_eq!(
+            "-----BEGIN PGP SIGNED MESSAGE-----\nHash\n\n".parse::<ClearSigned>(),
+            Err(Error::InvalidHeader("Hash".to_string()))
+        );
+        assert_eq!(
+            "-----BEGIN PGP SIGNED MESSAGE-----\n\nPackage: foo\n".parse::<ClearSigned>(),
+            Err(Error::MissingSignature)
+        );
+        assert_eq!(
+            SIGNED
+                .replace("-----END PGP SIGNATURE-----\n", "")
+                .parse::<ClearSigned>(),
+            Err(Error::TruncatedSignature)
+        );
+        assert_eq!(
+            format!("{}\nfoo\n", SIGNED).parse::<ClearSigned>(),
+            Err(Error::TrailingData("foo".to_string()))
+        );
+    }
+}
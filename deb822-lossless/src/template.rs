@@ -0,0 +1,304 @@
+//! Substitution of `${name}` placeholders in field values.
+//!
+//! Templates such as `debian/control.in` contain placeholders of the form `${name}` in their
+//! field values, which are replaced with values from a map when the final file is generated.
+//! Placeholder names follow the rules for substitution variables in `deb-substvars(5)`: an
+//! alphanumeric character followed by alphanumerics, hyphens and colons. Anything else, such as
+//! `${}` or a `${` without a closing brace, is left as is.
+//!
+//! Substituted values are not searched for further placeholders.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::lossless::Paragraph;
+//! use std::collections::HashMap;
+//!
+//! let mut paragraph: Paragraph = "Package: foo\nDepends: ${shlibs:Depends}, ${misc:Depends}\n"
+//!     .parse()
+//!     .unwrap();
+//! let mut vars = HashMap::new();
+//! vars.insert("shlibs:Depends", "libc6 (>= 2.36)");
+//! assert_eq!(
+//!     paragraph.substitute(&vars).unwrap_err().to_string(),
+//!     "unknown placeholders: ${misc:Depends}"
+//! );
+//!
+//! vars.insert("misc:Depends", "adduser");
+//! paragraph.substitute(&vars).unwrap();
+//! assert_eq!(
+//!     paragraph.to_string(),
+//!     "Package: foo\nDepends: libc6 (>= 2.36), adduser\n"
+//! );
+//! ```
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// An error substituting placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// The names of the placeholders without a value, in the order in which they first appear.
+    pub unknown: Vec<String>,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown placeholders: ")?;
+        for (i, name) in self.unknown.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "${{{}}}", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Check whether `name` is a valid placeholder name.
+///
+/// Names start with an alphanumeric character, followed by alphanumerics, `-` and `:`.
+///
+/// # Example
+///
+/// ```rust
+/// use deb822_lossless::template::is_valid_name;
+/// assert!(is_valid_name("misc:Depends"));
+/// assert!(!is_valid_name("-foo"));
+/// assert!(!is_valid_name("foo bar"));
+/// ```
+pub fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == ':')
+}
+
+/// A part of a field value.
+enum Segment<'a> {
+    /// Text outside placeholders.
+    Text(&'a str),
+
+    /// The name of a placeholder.
+    Placeholder(&'a str),
+}
+
+/// Split a value into literal text and placeholders.
+fn segments(value: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        if is_valid_name(name) {
+            segments.push(Segment::Text(&rest[..start]));
+            segments.push(Segment::Placeholder(name));
+            rest = &rest[start + 3 + len..];
+        } else {
+            segments.push(Segment::Text(&rest[..start + 2]));
+            rest = &rest[start + 2..];
+        }
+    }
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+/// Iterate over the names of the placeholders in a value.
+///
+/// # Example
+///
+/// ```rust
+/// use deb822_lossless::template::placeholders;
+/// assert_eq!(
+///     placeholders("${misc:Depends}, foo (= ${binary:Version}), ${}").collect::<Vec<_>>(),
+///     vec!["misc:Depends", "binary:Version"]
+/// );
+/// ```
+pub fn placeholders(value: &str) -> impl Iterator<Item = &str> {
+    segments(value)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Placeholder(name) => Some(name),
+            Segment::Text(_) => None,
+        })
+}
+
+/// Substitute placeholders in `value`, adding the names of placeholders without a value to
+/// `unknown`.
+fn expand<K, V>(value: &str, vars: &HashMap<K, V>, unknown: &mut Vec<String>) -> String
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+{
+    let mut result = String::with_capacity(value.len());
+    for segment in segments(value) {
+        match segment {
+            Segment::Text(text) => result.push_str(text),
+            Segment::Placeholder(name) => match vars.get(name) {
+                Some(v) => result.push_str(v.as_ref()),
+                None => {
+                    if !unknown.iter().any(|u| u == name) {
+                        unknown.push(name.to_string());
+                    }
+                }
+            },
+        }
+    }
+    result
+}
+
+/// Substitute the placeholders in a value.
+///
+/// Returns an error listing the placeholders that are not in `vars`.
+///
+/// # Example
+///
+/// ```rust
+/// use deb822_lossless::template::substitute;
+/// use std::collections::HashMap;
+///
+/// let vars = HashMap::from([("binary:Version", "1.0-1")]);
+/// assert_eq!(
+///     substitute("foo (= ${binary:Version})", &vars).unwrap(),
+///     "foo (= 1.0-1)"
+/// );
+/// assert_eq!(
+///     substitute("${a} ${b} ${a}", &vars).unwrap_err().unknown,
+///     vec!["a", "b"]
+/// );
+/// ```
+pub fn substitute<K, V>(value: &str, vars: &HashMap<K, V>) -> Result<String, Error>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+{
+    let mut unknown = Vec::new();
+    let result = expand(value, vars, &mut unknown);
+    if unknown.is_empty() {
+        Ok(result)
+    } else {
+        Err(Error { unknown })
+    }
+}
+
+impl crate::lossless::Paragraph {
+    /// Substitute the placeholders in the values of all fields in the paragraph.
+    ///
+    /// See [`crate::template`] for the syntax of placeholders. Only fields that contain
+    /// placeholders are modified; their comments and indentation are kept. If any placeholder
+    /// is not in `vars`, the paragraph is left unchanged and all unknown placeholders are
+    /// returned in the error.
+    pub fn substitute<K, V>(&mut self, vars: &HashMap<K, V>) -> Result<(), Error>
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: AsRef<str>,
+    {
+        let mut unknown = Vec::new();
+        let mut changes = Vec::new();
+        for entry in self.entries() {
+            let value = entry.value();
+            if placeholders(&value).next().is_none() {
+                continue;
+            }
+            let new_value = expand(&value, vars, &mut unknown);
+            changes.push((entry, new_value));
+        }
+        if !unknown.is_empty() {
+            return Err(Error { unknown });
+        }
+        for (entry, value) in changes {
+            self.set_entry_value(&entry, &value);
+        }
+        Ok(())
+    }
+}
+
+impl crate::lossy::Paragraph {
+    /// Substitute the placeholders in the values of all fields in the paragraph.
+    ///
+    /// See [`crate::template`] for the syntax of placeholders. If any placeholder is not in
+    /// `vars`, the paragraph is left unchanged and all unknown placeholders are returned in the
+    /// error.
+    pub fn substitute<K, V>(&mut self, vars: &HashMap<K, V>) -> Result<(), Error>
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: AsRef<str>,
+    {
+        let mut unknown = Vec::new();
+        let values = self
+            .iter()
+            .map(|(_, value)| expand(value, vars, &mut unknown))
+            .collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            return Err(Error { unknown });
+        }
+        for ((_, value), new_value) in self.iter_mut().zip(values) {
+            *value = new_value;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let vars = HashMap::from([("a", "1"), ("b:c", "2")]);
+        assert_eq!(substitute("", &vars).unwrap(), "");
+        assert_eq!(substitute("${a}${b:c}", &vars).unwrap(), "12");
+        assert_eq!(
+            substitute("$a ${ a} ${} ${a", &vars).unwrap(),
+            "$a ${ a} ${} ${a"
+        );
+        assert_eq!(substitute("${${a}}", &vars).unwrap(), "${1}");
+        assert_eq!(
+            substitute("${-a} ${a-b} ${a}", &vars).unwrap_err(),
+            Error {
+                unknown: vec!["a-b".to_string()]
+            }
+        );
+    }
+
+    #[test]
+    fn test_lossless() {
+        let text = "Package: foo\n# dependencies\nDepends: ${misc:Depends},\n    ${shlibs:Depends}\nDescription: ${summary}\nDescription: ${unknown}\n";
+        let mut paragraph: crate::lossless::Paragraph = text.parse().unwrap();
+        let mut vars = HashMap::new();
+        vars.insert("misc:Depends".to_string(), "adduser".to_string());
+        vars.insert("shlibs:Depends".to_string(), "libc6".to_string());
+        vars.insert("summary".to_string(), "Foo".to_string());
+        assert_eq!(
+            paragraph.substitute(&vars).unwrap_err().to_string(),
+            "unknown placeholders: ${unknown}"
+        );
+        assert_eq!(paragraph.to_string(), text);
+
+        vars.insert("unknown".to_string(), "Bar".to_string());
+        paragraph.substitute(&vars).unwrap();
+        assert_eq!(
+            paragraph.to_string(),
+            "Package: foo\n# dependencies\nDepends: adduser,\n    libc6\nDescription: Foo\nDescription: Bar\n"
+        );
+    }
+
+    #[test]
+    fn test_lossy() {
+        let mut paragraph: crate::lossy::Paragraph =
+            "Package: ${name}\nVersion: ${version}\n".parse().unwrap();
+        let vars = HashMap::from([("name", "foo")]);
+        assert_eq!(
+            paragraph.substitute(&vars).unwrap_err().unknown,
+            vec!["version".to_string()]
+        );
+        assert_eq!(paragraph.get("Package"), Some("${name}"));
+        let vars = HashMap::from([("name", "foo"), ("version", "1.0")]);
+        paragraph.substitute(&vars).unwrap();
+        assert_eq!(paragraph.to_string(), "Package: foo\nVersion: 1.0\n");
+    }
+}
@@ -0,0 +1,188 @@
+//! Traversal of the lossless syntax tree.
+//!
+//! Custom formatters and linters often need access to individual tokens, such as comments,
+//! indentation or trailing whitespace, that the high-level [`crate::lossless`] API hides. A
+//! [`Visitor`] is called for every paragraph, entry and token in a document, in order; the
+//! default implementations of its methods descend into the children of each node.
+//!
+//! The underlying [`rowan`] tree is also available directly, through
+//! [`rowan::ast::AstNode::syntax`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use deb822_lossless::lossless::{Entry, SyntaxKind, SyntaxToken};
+//! use deb822_lossless::visit::{walk_entry, Visitor};
+//!
+//! /// Report the fields that have trailing whitespace.
+//! #[derive(Default)]
+//! struct TrailingWhitespace {
+//!     field: Option<String>,
+//!     found: Vec<String>,
+//! }
+//!
+//! impl Visitor for TrailingWhitespace {
+//!     fn visit_entry(&mut self, entry: &Entry) {
+//!         self.field = entry.key();
+//!         walk_entry(self, entry);
+//!     }
+//!
+//!     fn visit_token(&mut self, token: &SyntaxToken) {
+//!         if token.kind() == SyntaxKind::VALUE && token.text().ends_with(' ') {
+//!             self.found.extend(self.field.clone());
+//!         }
+//!     }
+//! }
+//!
+//! let deb822: deb822_lossless::Deb822 = "Source: foo \nSection: net\n".parse().unwrap();
+//! let mut visitor = TrailingWhitespace::default();
+//! deb822.walk(&mut visitor);
+//! assert_eq!(visitor.found, vec!["Source"]);
+//! ```
+use crate::lossless::{Deb822, Entry, Paragraph, SyntaxElement, SyntaxKind, SyntaxToken};
+use rowan::ast::AstNode;
+
+/// A visitor over the syntax tree of a deb822 document.
+///
+/// All methods have default implementations, so implementors only need to override the
+/// methods for the parts of the tree they are interested in. Overriding
+/// [`Visitor::visit_paragraph`] or [`Visitor::visit_entry`] stops the traversal from
+/// descending into the node, unless the override calls [`walk_paragraph`] or [`walk_entry`].
+pub trait Visitor {
+    /// Visit a paragraph.
+    fn visit_paragraph(&mut self, paragraph: &Paragraph) {
+        walk_paragraph(self, paragraph);
+    }
+
+    /// Visit a field.
+    fn visit_entry(&mut self, entry: &Entry) {
+        walk_entry(self, entry);
+    }
+
+    /// Visit a comment, either between paragraphs or within one.
+    fn visit_comment(&mut self, comment: &SyntaxToken) {
+        let _ = comment;
+    }
+
+    /// Visit a token other than a comment.
+    fn visit_token(&mut self, token: &SyntaxToken) {
+        let _ = token;
+    }
+}
+
+/// Visit a node or token, dispatching on its kind.
+fn walk_element<V: Visitor + ?Sized>(visitor: &mut V, element: SyntaxElement) {
+    match element {
+        rowan::NodeOrToken::Node(node) => {
+            if let Some(paragraph) = Paragraph::cast(node.clone()) {
+                visitor.visit_paragraph(&paragraph);
+            } else if let Some(entry) = Entry::cast(node.clone()) {
+                visitor.visit_entry(&entry);
+            } else {
+                for child in node.children_with_tokens() {
+                    walk_element(visitor, child);
+                }
+            }
+        }
+        rowan::NodeOrToken::Token(token) if token.kind() == SyntaxKind::COMMENT => {
+            visitor.visit_comment(&token);
+        }
+        rowan::NodeOrToken::Token(token) => visitor.visit_token(&token),
+    }
+}
+
+/// Visit the paragraphs and the tokens between them, in order.
+pub fn walk_deb822<V: Visitor + ?Sized>(visitor: &mut V, deb822: &Deb822) {
+    for child in deb822.syntax().children_with_tokens() {
+        walk_element(visitor, child);
+    }
+}
+
+/// Visit the fields and comments of a paragraph, in order.
+pub fn walk_paragraph<V: Visitor + ?Sized>(visitor: &mut V, paragraph: &Paragraph) {
+    for child in paragraph.syntax().children_with_tokens() {
+        walk_element(visitor, child);
+    }
+}
+
+/// Visit the tokens of a field, in order.
+pub fn walk_entry<V: Visitor + ?Sized>(visitor: &mut V, entry: &Entry) {
+    for child in entry.syntax().children_with_tokens() {
+        walk_element(visitor, child);
+    }
+}
+
+impl Deb822 {
+    /// Walk the syntax tree of the document with a [`Visitor`].
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        walk_deb822(visitor, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reassemble the text of the document from the visited tokens.
+    #[derive(Default)]
+    struct Collect {
+        text: String,
+        paragraphs: usize,
+        entries: usize,
+        comments: Vec<String>,
+    }
+
+    impl Visitor for Collect {
+        fn visit_paragraph(&mut self, paragraph: &Paragraph) {
+            self.paragraphs += 1;
+            walk_paragraph(self, paragraph);
+        }
+
+        fn visit_entry(&mut self, entry: &Entry) {
+            self.entries += 1;
+            walk_entry(self, entry);
+        }
+
+        fn visit_comment(&mut self, comment: &SyntaxToken) {
+            self.comments.push(comment.text().to_string());
+            self.text.push_str(comment.text());
+        }
+
+        fn visit_token(&mut self, token: &SyntaxToken) {
+            self.text.push_str(token.text());
+        }
+    }
+
+    #[test]
+    fn test_walk() {
+        let text =
+            "# header\n\nSource: foo\n# inner\nBuild-Depends: bar,\n baz\n\n\nPackage: foo\n";
+        let deb822: Deb822 = text.parse().unwrap();
+        let mut visitor = Collect::default();
+        deb822.walk(&mut visitor);
+        assert_eq!(visitor.text, text);
+        assert_eq!(visitor.paragraphs, 2);
+        assert_eq!(visitor.entries, 3);
+        assert_eq!(visitor.comments, vec!["# header", "# inner"]);
+    }
+
+    #[test]
+    fn test_skip_children() {
+        struct Keys(Vec<String>);
+
+        impl Visitor for Keys {
+            fn visit_entry(&mut self, entry: &Entry) {
+                self.0.extend(entry.key());
+            }
+
+            fn visit_token(&mut self, token: &SyntaxToken) {
+                assert_ne!(token.kind(), SyntaxKind::VALUE);
+            }
+        }
+
+        let deb822: Deb822 = "A: 1\nB: 2\n\nC: 3\n".parse().unwrap();
+        let mut visitor = Keys(Vec::new());
+        deb822.walk(&mut visitor);
+        assert_eq!(visitor.0, vec!["A", "B", "C"]);
+    }
+}
@@ -0,0 +1,134 @@
+//! JavaScript bindings, for use with wasm-bindgen.
+//!
+//! The [`Paragraph`](JsParagraph) class wraps a lossless paragraph, so that web-based editors
+//! can modify fields without losing comments or formatting. For read-only use,
+//! [`Paragraph.toObject`](JsParagraph::to_object) converts a paragraph to a plain JavaScript
+//! object mapping field names to values.
+//!
+//! ```js
+//! import { parseDeb822 } from "deb822-lossless";
+//!
+//! const [para] = parseDeb822("Package: hello\nVersion: 2.10\n");
+//! para.set("Version", "2.11");
+//! console.log(para.toObject());  // { Package: "hello", Version: "2.11" }
+//! ```
+use crate::lossless::{Deb822, Paragraph};
+use wasm_bindgen::prelude::*;
+
+/// A paragraph in a deb822 file.
+#[wasm_bindgen(js_name = Paragraph)]
+pub struct JsParagraph(Paragraph);
+
+impl From<Paragraph> for JsParagraph {
+    fn from(paragraph: Paragraph) -> Self {
+        JsParagraph(paragraph)
+    }
+}
+
+impl From<JsParagraph> for Paragraph {
+    fn from(paragraph: JsParagraph) -> Self {
+        paragraph.0
+    }
+}
+
+#[wasm_bindgen(js_class = Paragraph)]
+impl JsParagraph {
+    /// Create a new, empty paragraph.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        JsParagraph(Paragraph::new())
+    }
+
+    /// Get the value of a field, or `undefined` if it is not present.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key)
+    }
+
+    /// Set the value of a field, replacing any existing value.
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.0.set(key, value);
+    }
+
+    /// Remove a field.
+    pub fn remove(&mut self, key: &str) {
+        self.0.remove(key);
+    }
+
+    /// Check whether a field is present.
+    pub fn has(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    /// The names of the fields in the paragraph, in order.
+    pub fn keys(&self) -> Vec<String> {
+        self.0.keys().collect()
+    }
+
+    /// Convert the paragraph to a plain object mapping field names to values.
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> js_sys::Object {
+        paragraph_to_object(self.0.items())
+    }
+
+    /// Serialize the paragraph, including any comments.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string_js(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for JsParagraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Build a plain JavaScript object from a sequence of fields.
+///
+/// Fields are added in order, so iterating over the keys of the object returns them in the
+/// order in which they appeared in the paragraph.
+pub fn paragraph_to_object<K: AsRef<str>, V: AsRef<str>>(
+    fields: impl IntoIterator<Item = (K, V)>,
+) -> js_sys::Object {
+    let obj = js_sys::Object::new();
+    for (key, value) in fields {
+        js_sys::Reflect::set(
+            &obj,
+            &JsValue::from_str(key.as_ref()),
+            &JsValue::from_str(value.as_ref()),
+        )
+        .expect("setting a property on a plain object can not fail");
+    }
+    obj
+}
+
+/// Parse a deb822 file into its paragraphs.
+///
+/// Throws an error if the file can not be parsed.
+#[wasm_bindgen(js_name = parseDeb822)]
+pub fn parse_deb822(text: &str) -> Result<Vec<JsParagraph>, JsError> {
+    let deb822: Deb822 = text
+        .parse()
+        .map_err(|e: crate::ParseError| JsError::new(&e.to_string()))?;
+    Ok(deb822.paragraphs().map(JsParagraph).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraph() {
+        let mut para = parse_deb822("Package: hello\n# A comment\nVersion: 2.10\n")
+            .ok()
+            .unwrap()
+            .remove(0);
+        assert_eq!(para.get("Version"), Some("2.10".to_string()));
+        assert!(para.has("Package"));
+        assert_eq!(para.keys(), vec!["Package", "Version"]);
+        para.set("Version", "2.11");
+        para.remove("Package");
+        assert!(!para.has("Package"));
+        assert_eq!(para.to_string_js(), "# A comment\nVersion: 2.11\n");
+    }
+}
@@ -4,12 +4,12 @@ use debian_control::lossless::Control;
 pub fn main() {
     let mut control = Control::new();
     let mut source = control.add_source("hello");
-    source.set_section(Some("rust"));
+    source.set_section(Some(&"rust".parse().unwrap()));
 
     let mut binary = control.add_binary("hello");
-    binary.set_architecture(Some("amd64"));
+    binary.set_architecture(Some(&"amd64".parse().unwrap()));
     binary.set_priority(Some(Priority::Optional));
-    binary.set_description(Some("Hello, world!"));
+    binary.set_description("Hello, world!", None).unwrap();
 
     println!("{}", control);
 }
@@ -0,0 +1,159 @@
+//! Implementations of [`arbitrary::Arbitrary`] for fuzzing.
+//!
+//! Generated relations are always valid: they consist of package names with an optional
+//! architecture qualifier, version constraint, architecture restriction list and build
+//! profiles, so formatting and parsing them again returns equal relations.
+//!
+//! # Example
+//!
+//! ```rust
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use debian_control::lossy::Relations;
+//!
+//! let mut u = Unstructured::new(b"some bytes provided by the fuzzer");
+//! let relations = Relations::arbitrary(&mut u).unwrap();
+//! assert_eq!(relations.to_string().parse::<Relations>().unwrap(), relations);
+//! ```
+use crate::relations::{BuildProfile, VersionConstraint};
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+const ARCHITECTURES: &[&str] = &["amd64", "arm64", "armhf", "i386", "riscv64", "s390x"];
+
+const ARCHQUALS: &[&str] = &["any", "native", "amd64", "i386"];
+
+const PROFILES: &[&str] = &[
+    "nocheck",
+    "nodoc",
+    "cross",
+    "stage1",
+    "stage2",
+    "pkg.foo.bar",
+];
+
+const CONSTRAINTS: &[VersionConstraint] = &[
+    VersionConstraint::LessThan,
+    VersionConstraint::LessThanEqual,
+    VersionConstraint::Equal,
+    VersionConstraint::GreaterThan,
+    VersionConstraint::GreaterThanEqual,
+];
+
+/// Generate a package name.
+///
+/// Package names consist of at least two lower case letters, digits, `+`, `-` and `.`, and
+/// start with a letter or digit.
+pub fn package_name(u: &mut Unstructured) -> Result<String> {
+    const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789+-.";
+    let mut name = String::from(*u.choose(FIRST)? as char);
+    for _ in 0..u.int_in_range(1..=15)? {
+        name.push(*u.choose(REST)? as char);
+    }
+    Ok(name)
+}
+
+/// Generate a Debian version, with an optional epoch and revision.
+pub fn version(u: &mut Unstructured) -> Result<debversion::Version> {
+    let mut version = String::new();
+    if u.arbitrary()? {
+        version.push_str(&format!("{}:", u.int_in_range(1..=9)?));
+    }
+    version.push_str(&u.int_in_range(0u32..=999)?.to_string());
+    for _ in 0..u.int_in_range(0..=2)? {
+        version.push_str(&format!(".{}", u.int_in_range(0u32..=99)?));
+    }
+    if u.arbitrary()? {
+        version.push_str(&format!("~rc{}", u.int_in_range(1..=9)?));
+    }
+    if u.arbitrary()? {
+        version.push_str(&format!("-{}", u.int_in_range(1..=20)?));
+    }
+    Ok(version.parse().unwrap())
+}
+
+impl<'a> Arbitrary<'a> for crate::lossy::Relation {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut relation = Self::new();
+        relation.name = package_name(u)?;
+        if u.ratio(1, 4)? {
+            relation.archqual = Some(u.choose(ARCHQUALS)?.to_string());
+        }
+        if u.arbitrary()? {
+            relation.version = Some((u.choose(CONSTRAINTS)?.clone(), version(u)?));
+        }
+        if u.ratio(1, 4)? {
+            let mut architectures = vec![u.choose(ARCHITECTURES)?.to_string()];
+            for _ in 0..u.int_in_range(0..=2)? {
+                architectures.push(u.choose(ARCHITECTURES)?.to_string());
+            }
+            relation.architectures = Some(architectures);
+        }
+        for _ in 0..u.int_in_range(0..=2)? {
+            let name = u.choose(PROFILES)?.to_string();
+            relation.profiles.push(vec![if u.arbitrary()? {
+                BuildProfile::Disabled(name)
+            } else {
+                BuildProfile::Enabled(name)
+            }]);
+        }
+        Ok(relation)
+    }
+}
+
+impl<'a> Arbitrary<'a> for crate::lossy::Relations {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        (0..u.int_in_range(0..=5)?)
+            .map(|_| {
+                (0..u.int_in_range(1..=3)?)
+                    .map(|_| crate::lossy::Relation::arbitrary(u))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl<'a> Arbitrary<'a> for crate::lossless::relations::Relations {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(crate::lossy::Relations::arbitrary(u)?
+            .to_string()
+            .parse()
+            .unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random input, so that the tests cover more than empty data.
+    fn data(seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..1024)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for seed in 0..50 {
+            let data = data(seed);
+            let mut u = Unstructured::new(&data);
+            let relations = crate::lossy::Relations::arbitrary(&mut u).unwrap();
+            let text = relations.to_string();
+            assert_eq!(text.parse::<crate::lossy::Relations>().unwrap(), relations);
+            #[cfg(feature = "lossless")]
+            assert_eq!(
+                text.parse::<crate::lossless::relations::Relations>()
+                    .unwrap()
+                    .to_string(),
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,718 @@
+//! Basic structural checks for Debian source packages.
+//!
+//! Checks `debian/control`, `debian/tests/control` and the DEP-3 headers of the patches listed
+//! in `debian/patches/series` for common errors, and reports them annotated with file names and
+//! line numbers. This is not a replacement for lintian, but it is fast and has no dependencies
+//! on the rest of the Debian toolchain, so it is suitable as a pre-upload or CI check.
+use clap::Parser;
+use deb822_lossless::lazy::{paragraphs, LazyParagraph};
+use debian_control::fields::{MultiArch, Priority, RELATION_FIELDS};
+use debian_control::format::{FormatOptions, FormattingIssue};
+use debian_control::lossless::relations::Relations;
+use debian_control::vcs::Vcs;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Check debian/control, debian/tests/control and DEP-3 patch headers for errors"
+)]
+struct Args {
+    /// Path to the unpacked source tree
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Exit with a non-zero status if there are warnings, not just errors
+    #[arg(short = 'W', long)]
+    fail_on_warnings: bool,
+
+    /// Warn about deviations from a formatting style, given as a preset such as
+    /// "wrap-and-sort -ast" or a configuration file
+    #[arg(long)]
+    style: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A problem found in a file.
+#[derive(Debug, PartialEq, Eq)]
+struct Finding {
+    line: Option<usize>,
+    /// The byte range in the file that the finding applies to, if known.
+    span: Option<std::ops::Range<usize>>,
+    severity: Severity,
+    message: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Finding {}
+
+impl miette::Diagnostic for Finding {
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.severity {
+            Severity::Warning => miette::Severity::Warning,
+            Severity::Error => miette::Severity::Error,
+        })
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span.clone()?;
+        Some(Box::new(std::iter::once(miette::LabeledSpan::at(
+            span,
+            self.message.clone(),
+        ))))
+    }
+}
+
+/// Collects the findings for a single file.
+struct Checker<'a> {
+    text: &'a str,
+    findings: Vec<Finding>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            findings: Vec::new(),
+        }
+    }
+
+    /// Determine the byte range of a slice of the text being checked.
+    fn span_of(&self, s: &str) -> std::ops::Range<usize> {
+        let offset = s.as_ptr() as usize - self.text.as_ptr() as usize;
+        offset..offset + s.len()
+    }
+
+    /// Report a problem with a slice of the text, or with the file as a whole.
+    fn report(&mut self, at: Option<&str>, severity: Severity, message: String) {
+        let span = at.map(|s| self.span_of(s));
+        self.findings.push(Finding {
+            line: span
+                .as_ref()
+                .map(|span| self.text[..span.start].matches('\n').count() + 1),
+            span,
+            severity,
+            message,
+        });
+    }
+
+    /// Report a problem with a field.
+    fn field(
+        &mut self,
+        paragraph: &LazyParagraph<'a>,
+        name: &str,
+        severity: Severity,
+        message: String,
+    ) {
+        let key = paragraph.keys().find(|k| *k == name);
+        self.report(key, severity, message);
+    }
+
+    /// Report a problem with a paragraph as a whole.
+    fn paragraph(&mut self, paragraph: &LazyParagraph<'a>, severity: Severity, message: String) {
+        self.report(Some(paragraph.as_str()), severity, message);
+    }
+
+    /// Parse the paragraphs of the text, reporting syntax errors.
+    fn parse(&mut self) -> Vec<LazyParagraph<'a>> {
+        let mut result = Vec::new();
+        for paragraph in paragraphs(self.text) {
+            match paragraph {
+                Ok(paragraph) => result.push(paragraph),
+                Err(e) => {
+                    self.report(None, Severity::Error, e.to_string());
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    fn check_duplicate_fields(&mut self, paragraph: &LazyParagraph<'a>) {
+        let mut seen = std::collections::HashSet::new();
+        for key in paragraph.keys() {
+            if !seen.insert(key.to_ascii_lowercase()) {
+                self.report(
+                    Some(key),
+                    Severity::Error,
+                    format!("duplicate field {}", key),
+                );
+            }
+        }
+    }
+
+    fn check_required(
+        &mut self,
+        paragraph: &LazyParagraph<'a>,
+        fields: &[&str],
+        severity: Severity,
+    ) {
+        for field in fields {
+            if !paragraph.contains_key(field) {
+                self.paragraph(paragraph, severity, format!("missing {} field", field));
+            }
+        }
+    }
+
+    /// Check that a relations field can be parsed.
+    ///
+    /// Substitution variables are allowed, as are any of the given special values.
+    fn check_relations(&mut self, paragraph: &LazyParagraph<'a>, name: &str, special: &[&str]) {
+        let Some(value) = paragraph.get(name) else {
+            return;
+        };
+        let value = value
+            .split(',')
+            .filter(|item| !special.contains(&item.trim()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let (_, errors) = Relations::parse_relaxed(&value, true);
+        for error in errors {
+            self.field(
+                paragraph,
+                name,
+                Severity::Error,
+                format!("invalid {} field: {}", name, error),
+            );
+        }
+    }
+
+    fn check_url(&mut self, paragraph: &LazyParagraph<'a>, name: &str, severity: Severity) {
+        if let Some(value) = paragraph.get(name) {
+            if let Err(e) = url::Url::parse(value) {
+                self.field(
+                    paragraph,
+                    name,
+                    severity,
+                    format!("invalid URL in {} field: {}", name, e),
+                );
+            }
+        }
+    }
+}
+
+fn is_valid_package_name(name: &str) -> bool {
+    name.len() >= 2
+        && name.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "+-.".contains(c))
+}
+
+fn check_source<'a>(checker: &mut Checker<'a>, source: &LazyParagraph<'a>) {
+    checker.check_required(source, &["Maintainer"], Severity::Error);
+    checker.check_required(source, &["Standards-Version"], Severity::Warning);
+    if let Some(name) = source.get("Source") {
+        if !is_valid_package_name(name) {
+            checker.field(
+                source,
+                "Source",
+                Severity::Error,
+                format!("invalid source package name {:?}", name),
+            );
+        }
+    }
+    if let Some(version) = source.get("Standards-Version") {
+        let valid = (3..=4).contains(&version.split('.').count())
+            && version
+                .split('.')
+                .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+        if !valid {
+            checker.field(
+                source,
+                "Standards-Version",
+                Severity::Error,
+                format!("invalid Standards-Version {:?}", version),
+            );
+        }
+    }
+    for key in source.keys().collect::<Vec<_>>() {
+        let Some(vcs) = key.strip_prefix("Vcs-") else {
+            continue;
+        };
+        if vcs == "Browser" {
+            checker.check_url(source, key, Severity::Error);
+        } else if let Err(e) = Vcs::from_field(vcs, source.get(key).unwrap()) {
+            checker.field(
+                source,
+                key,
+                Severity::Error,
+                format!("invalid {} field: {}", key, e),
+            );
+        }
+    }
+    checker.check_url(source, "Homepage", Severity::Error);
+}
+
+fn check_binary<'a>(checker: &mut Checker<'a>, binary: &LazyParagraph<'a>) {
+    checker.check_required(binary, &["Architecture", "Description"], Severity::Error);
+    if let Some(name) = binary.get("Package") {
+        if !is_valid_package_name(name) {
+            checker.field(
+                binary,
+                "Package",
+                Severity::Error,
+                format!("invalid package name {:?}", name),
+            );
+        }
+    }
+    if let Some(description) = binary.get("Description") {
+        if description.lines().next().unwrap_or("").trim().is_empty() {
+            checker.field(
+                binary,
+                "Description",
+                Severity::Error,
+                "empty synopsis in Description field".to_string(),
+            );
+        }
+    }
+    if let Some(Err(e)) = binary.get("Multi-Arch").map(|v| v.parse::<MultiArch>()) {
+        checker.field(binary, "Multi-Arch", Severity::Error, e.to_string());
+    }
+}
+
+fn check_control(text: &str) -> Vec<Finding> {
+    let mut checker = Checker::new(text);
+    let paragraphs = checker.parse();
+    let mut names = std::collections::HashSet::new();
+    for (i, paragraph) in paragraphs.iter().enumerate() {
+        checker.check_duplicate_fields(paragraph);
+        if i == 0 {
+            if paragraph.contains_key("Source") {
+                check_source(&mut checker, paragraph);
+            } else {
+                checker.paragraph(
+                    paragraph,
+                    Severity::Error,
+                    "first paragraph is not a source package paragraph".to_string(),
+                );
+            }
+        } else if let Some(name) = paragraph.get("Package") {
+            if !names.insert(name) {
+                checker.field(
+                    paragraph,
+                    "Package",
+                    Severity::Error,
+                    format!("duplicate binary package {}", name),
+                );
+            }
+            check_binary(&mut checker, paragraph);
+        } else {
+            checker.paragraph(
+                paragraph,
+                Severity::Error,
+                "missing Package field".to_string(),
+            );
+        }
+        if let Some(Err(e)) = paragraph.get("Priority").map(|v| v.parse::<Priority>()) {
+            checker.field(paragraph, "Priority", Severity::Error, e.to_string());
+        }
+        for field in RELATION_FIELDS {
+            checker.check_relations(paragraph, field, &[]);
+        }
+    }
+    if paragraphs.len() == 1 {
+        checker.report(None, Severity::Error, "no binary packages".to_string());
+    }
+    checker.findings
+}
+
+fn check_tests_control(text: &str) -> Vec<Finding> {
+    let mut checker = Checker::new(text);
+    for paragraph in checker.parse() {
+        checker.check_duplicate_fields(&paragraph);
+        if !paragraph.contains_key("Tests") && !paragraph.contains_key("Test-Command") {
+            checker.paragraph(
+                &paragraph,
+                Severity::Error,
+                "missing Tests or Test-Command field".to_string(),
+            );
+        }
+        checker.check_relations(&paragraph, "Depends", &["@", "@builddeps@", "@recommends@"]);
+    }
+    checker.findings
+}
+
+/// Find the DEP-3 header at the start of a patch.
+///
+/// The header is the first paragraph, after an optional mbox `From` line as written by
+/// `git format-patch`. It ends at the first empty line or at the start of the diff.
+fn patch_header(text: &str) -> &str {
+    let mut start = 0;
+    if text.starts_with("From ") {
+        start = text.find('\n').map_or(text.len(), |i| i + 1);
+    }
+    let mut end = start;
+    for line in text[start..].split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed.is_empty()
+            || trimmed.starts_with("---")
+            || trimmed.starts_with("diff ")
+            || trimmed.starts_with("Index: ")
+            || trimmed.starts_with("===")
+        {
+            break;
+        }
+        end += line.len();
+    }
+    &text[start..end]
+}
+
+fn check_patch(text: &str) -> Vec<Finding> {
+    let mut checker = Checker::new(text);
+    let header = patch_header(text);
+    let paragraph = match paragraphs(header).next() {
+        Some(Ok(paragraph)) => paragraph,
+        Some(Err(e)) => {
+            checker.report(
+                Some(header),
+                Severity::Error,
+                format!("invalid DEP-3 header: {}", e),
+            );
+            return checker.findings;
+        }
+        None => {
+            checker.report(
+                Some(&text[..0]),
+                Severity::Warning,
+                "missing DEP-3 header".to_string(),
+            );
+            return checker.findings;
+        }
+    };
+    checker.check_duplicate_fields(&paragraph);
+    if !paragraph.contains_key("Description") && !paragraph.contains_key("Subject") {
+        checker.paragraph(
+            &paragraph,
+            Severity::Error,
+            "missing Description field".to_string(),
+        );
+    }
+    if !paragraph.contains_key("Origin")
+        && !paragraph.contains_key("Author")
+        && !paragraph.contains_key("From")
+    {
+        checker.paragraph(
+            &paragraph,
+            Severity::Error,
+            "missing Origin or Author field".to_string(),
+        );
+    }
+    if let Some(origin) = paragraph.get("Origin") {
+        if let Some((category, _)) = origin.split_once(", ") {
+            if let Err(e) = category.parse::<dep3::OriginCategory>() {
+                checker.field(&paragraph, "Origin", Severity::Warning, e.to_string());
+            }
+        }
+    }
+    if let Some(date) = paragraph.get("Last-Update") {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            checker.field(
+                &paragraph,
+                "Last-Update",
+                Severity::Error,
+                format!("invalid date {:?}, expected YYYY-MM-DD", date),
+            );
+        }
+    }
+    for key in paragraph.keys().collect::<Vec<_>>() {
+        if key == "Bug" || key.starts_with("Bug-") {
+            checker.check_url(&paragraph, key, Severity::Warning);
+        }
+    }
+    checker.findings
+}
+
+/// Report where a file deviates from a formatting style.
+fn check_style(
+    text: &str,
+    check: fn(&deb822_lossless::Deb822, &FormatOptions) -> Vec<FormattingIssue>,
+    options: &FormatOptions,
+) -> Vec<Finding> {
+    // Syntax errors are reported by the other checks.
+    let (doc, _) = deb822_lossless::Deb822::from_str_relaxed(text);
+    check(&doc, options)
+        .into_iter()
+        .map(|issue| Finding {
+            line: Some(text[..issue.span.start].matches('\n').count() + 1),
+            span: Some(issue.span),
+            severity: Severity::Warning,
+            message: issue.message,
+        })
+        .collect()
+}
+
+/// Return the patches listed in a quilt series file, with their line numbers.
+fn series_patches(text: &str) -> Vec<(usize, &str)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line = line.split('#').next().unwrap_or("");
+            line.split_whitespace().next().map(|name| (i + 1, name))
+        })
+        .collect()
+}
+
+/// Run a check on a file, adding the findings to `results`.
+fn check_file(path: &Path, check: fn(&str) -> Vec<Finding>, results: &mut Vec<(PathBuf, Finding)>) {
+    match std::fs::read_to_string(path) {
+        Ok(text) => results.extend(check(&text).into_iter().map(|f| (path.to_path_buf(), f))),
+        Err(e) => {
+            let finding = Finding {
+                line: None,
+                span: None,
+                severity: Severity::Error,
+                message: e.to_string(),
+            };
+            results.push((path.to_path_buf(), finding));
+        }
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let debian = args.path.join("debian");
+    let style = args
+        .style
+        .as_ref()
+        .map(|style| match FormatOptions::load(style) {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("{}: {}", style, e);
+                std::process::exit(1);
+            }
+        });
+
+    let mut results: Vec<(PathBuf, Finding)> = Vec::new();
+
+    let control = debian.join("control");
+    check_file(&control, check_control, &mut results);
+    let tests_control = debian.join("tests/control");
+    if tests_control.exists() {
+        check_file(&tests_control, check_tests_control, &mut results);
+    }
+    if let Some(options) = &style {
+        for (path, check) in [
+            (&control, debian_control::format::check as fn(&_, &_) -> _),
+            (&tests_control, debian_control::format::check_tests_control),
+        ] {
+            if let Ok(text) = std::fs::read_to_string(path) {
+                let findings = check_style(&text, check, options);
+                results.extend(findings.into_iter().map(|f| (path.clone(), f)));
+            }
+        }
+    }
+    let series_path = debian.join("patches/series");
+    if let Ok(series) = std::fs::read_to_string(&series_path) {
+        for (line, name) in series_patches(&series) {
+            let patch = debian.join("patches").join(name);
+            if !patch.exists() {
+                let finding = Finding {
+                    line: Some(line),
+                    span: None,
+                    severity: Severity::Error,
+                    message: format!("patch {} does not exist", name),
+                };
+                results.push((series_path.clone(), finding));
+                continue;
+            }
+            check_file(&patch, check_patch, &mut results);
+        }
+    }
+
+    let mut failed = false;
+    for (path, finding) in &results {
+        match finding.line {
+            Some(line) => print!("{}:{}: ", path.display(), line),
+            None => print!("{}: ", path.display()),
+        }
+        println!("{}: {}", finding.severity, finding.message);
+        failed |= finding.severity == Severity::Error || args.fail_on_warnings;
+    }
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(findings: Vec<Finding>) -> Vec<(Option<usize>, Severity, String)> {
+        findings
+            .into_iter()
+            .map(|f| (f.line, f.severity, f.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_control_clean() {
+        let text = r#"Source: hello
+Maintainer: Jane Doe <jane@example.com>
+Standards-Version: 4.6.2
+Build-Depends: debhelper-compat (= 13)
+Vcs-Git: https://salsa.debian.org/debian/hello.git -b debian/main
+Vcs-Browser: https://salsa.debian.org/debian/hello
+
+Package: hello
+Architecture: any
+Depends: ${shlibs:Depends}, ${misc:Depends}
+Multi-Arch: foreign
+Description: example package
+ Long description.
+"#;
+        assert_eq!(summary(check_control(text)), vec![]);
+    }
+
+    #[test]
+    fn test_control_errors() {
+        let text = r#"Source: hello
+Standards-Version: 4.6
+Build-Depends: debhelper-compat (= 13
+Vcs-Browser: not a url
+
+Package: hello
+Architecture: any
+Multi-Arch: sideways
+
+Package: hello
+Architecture: all
+Architecture: any
+Description: example
+"#;
+        let findings = summary(check_control(text));
+        assert_eq!(
+            findings.iter().map(|f| f.0).collect::<Vec<_>>(),
+            vec![
+                Some(1),
+                Some(2),
+                Some(4),
+                Some(3),
+                Some(6),
+                Some(8),
+                Some(12),
+                Some(10)
+            ]
+        );
+        assert_eq!(findings[0].2, "missing Maintainer field");
+        assert_eq!(findings[1].2, "invalid Standards-Version \"4.6\"");
+        assert_eq!(findings[4].2, "missing Description field");
+        assert_eq!(findings[6].2, "duplicate field Architecture");
+        assert_eq!(findings[7].2, "duplicate binary package hello");
+    }
+
+    #[test]
+    fn test_tests_control() {
+        let text = "Tests: smoke\nDepends: @, python3\n\nRestrictions: needs-root\n";
+        assert_eq!(
+            summary(check_tests_control(text)),
+            vec![(
+                Some(4),
+                Severity::Error,
+                "missing Tests or Test-Command field".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_style() {
+        let text = "Source: hello\n\nPackage: hello\nDepends: libc6, ${misc:Depends}\n";
+        let options = FormatOptions::default();
+        assert_eq!(
+            summary(check_style(text, debian_control::format::check, &options)),
+            vec![]
+        );
+        let options = FormatOptions::preset("wrap-and-sort -a").unwrap();
+        assert_eq!(
+            summary(check_style(text, debian_control::format::check, &options)),
+            vec![(
+                Some(4),
+                Severity::Warning,
+                "Depends field is not wrapped as expected".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_patch() {
+        let text = r#"From 0123456789abcdef Mon Sep 17 00:00:00 2001
+From: Jane Doe <jane@example.com>
+Subject: Fix a bug
+Last-Update: 2024-13-01
+
+Some more text.
+---
+ src/main.c | 1 +
+"#;
+        assert_eq!(
+            summary(check_patch(text)),
+            vec![(
+                Some(4),
+                Severity::Error,
+                "invalid date \"2024-13-01\", expected YYYY-MM-DD".to_string()
+            )]
+        );
+
+        let text = "Forwarded: no\n--- a/foo\n+++ b/foo\n";
+        assert_eq!(
+            summary(check_patch(text))
+                .into_iter()
+                .map(|f| f.2)
+                .collect::<Vec<_>>(),
+            vec![
+                "missing Description field",
+                "missing Origin or Author field"
+            ]
+        );
+
+        assert_eq!(
+            summary(check_patch("--- a/foo\n+++ b/foo\n")),
+            vec![(
+                Some(1),
+                Severity::Warning,
+                "missing DEP-3 header".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        use miette::Diagnostic;
+        let text = "Source: hello\nMaintainer: Jane Doe <jane@example.com>\nVcs-Browser: bogus\n";
+        let finding = check_control(text)
+            .into_iter()
+            .find(|f| f.message.contains("Vcs-Browser"))
+            .unwrap();
+        assert_eq!(finding.severity(), Some(miette::Severity::Error));
+        let labels = finding.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            &text[labels[0].offset()..][..labels[0].len()],
+            "Vcs-Browser"
+        );
+    }
+
+    #[test]
+    fn test_series() {
+        assert_eq!(
+            series_patches("# comment\nfoo.patch\n\nbar.patch -p1 # fix\n"),
+            vec![(2, "foo.patch"), (4, "bar.patch")]
+        );
+    }
+}
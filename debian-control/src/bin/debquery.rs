@@ -0,0 +1,400 @@
+//! Query APT `Packages` files and the dpkg status file, in the style of `apt-cache`.
+//!
+//! Supports `show`, `depends`, `rdepends` and `policy`. Virtual packages are resolved through
+//! the `Provides` fields of the packages in the given files.
+use clap::{Parser, Subcommand};
+use deb822_lossless::FromDeb822Paragraph;
+use debian_control::lossy::apt::Package;
+use debian_control::lossy::index::{IndexEntry, PackagesIndex};
+use debian_control::lossy::Relation;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Dependency fields, in the order `apt-cache` shows them.
+const DEPENDENCY_FIELDS: &[&str] = &[
+    "PreDepends",
+    "Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Replaces",
+];
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Query APT Packages files and the dpkg status file")]
+struct Args {
+    /// Packages or status file to load; can be specified multiple times
+    #[arg(short = 'f', long = "file", default_value = "/var/lib/dpkg/status")]
+    files: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the records of packages
+    Show { packages: Vec<String> },
+
+    /// Show the dependencies of packages
+    Depends { packages: Vec<String> },
+
+    /// Show the packages that depend on packages
+    Rdepends { packages: Vec<String> },
+
+    /// Show the installed and available versions of packages
+    Policy { packages: Vec<String> },
+}
+
+/// A package record, along with the file it was read from.
+struct Record {
+    package: Package,
+    paragraph: deb822_lossless::lossy::Paragraph,
+    file: usize,
+}
+
+impl Record {
+    fn is_installed(&self) -> bool {
+        self.paragraph
+            .get("Status")
+            .is_some_and(|status| status.ends_with(" installed"))
+    }
+
+    fn relations(&self, field: &str) -> Option<&debian_control::lossy::Relations> {
+        let package = &self.package;
+        match field {
+            "PreDepends" => package.pre_depends.as_ref(),
+            "Depends" => package.depends.as_ref(),
+            "Recommends" => package.recommends.as_ref(),
+            "Suggests" => package.suggests.as_ref(),
+            "Enhances" => package.enhances.as_ref(),
+            "Breaks" => package.breaks.as_ref(),
+            "Conflicts" => package.conflicts.as_ref(),
+            "Replaces" => package.replaces.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl IndexEntry for Record {
+    fn name(&self) -> &str {
+        self.package.name()
+    }
+
+    fn version(&self) -> &debversion::Version {
+        self.package.version()
+    }
+
+    fn architecture(&self) -> Option<&str> {
+        self.package.architecture()
+    }
+}
+
+/// All packages from the loaded files.
+struct Universe {
+    files: Vec<String>,
+    index: PackagesIndex<Record>,
+    /// Packages that provide a name, with the version they provide it at.
+    providers: HashMap<String, Vec<(String, Option<debversion::Version>)>>,
+}
+
+impl Universe {
+    fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            index: PackagesIndex::new(),
+            providers: HashMap::new(),
+        }
+    }
+
+    /// Load the packages from the contents of a file.
+    ///
+    /// Returns the errors for paragraphs that could not be loaded.
+    fn load(&mut self, name: &str, text: &str) -> Result<Vec<String>, String> {
+        let doc: deb822_lossless::lossy::Deb822 = text.parse().map_err(|e| format!("{}", e))?;
+        let file = self.files.len();
+        self.files.push(name.to_string());
+        let mut errors = vec![];
+        for paragraph in doc {
+            let package = match Package::from_paragraph(&paragraph) {
+                Ok(package) => package,
+                Err(e) => {
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+            for provided in package.provides.iter().flat_map(|p| p.0.iter().flatten()) {
+                self.providers
+                    .entry(provided.name.clone())
+                    .or_default()
+                    .push((
+                        package.name.clone(),
+                        provided.version.as_ref().map(|(_, v)| v.clone()),
+                    ));
+            }
+            self.index.push(Record {
+                package,
+                paragraph,
+                file,
+            });
+        }
+        Ok(errors)
+    }
+
+    /// Find the packages that provide a relation, if it is not satisfied by a real package.
+    fn providers(&self, relation: &Relation) -> Vec<&str> {
+        let mut ret = self
+            .providers
+            .get(&relation.name)
+            .into_iter()
+            .flatten()
+            .filter(|(_, version)| match (&relation.version, version) {
+                (None, _) => true,
+                (Some(_), None) => false,
+                (Some(_), Some(version)) => {
+                    relation.satisfied_by((relation.name.clone(), version.clone()))
+                }
+            })
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        ret.sort();
+        ret.dedup();
+        ret
+    }
+
+    fn show(&self, name: &str) -> Option<String> {
+        let mut out = String::new();
+        for record in self.index.get_versions(name) {
+            writeln!(out, "{}", record.paragraph).unwrap();
+        }
+        (!out.is_empty()).then_some(out)
+    }
+
+    fn depends(&self, name: &str) -> Option<String> {
+        let record = self.index.get(name)?;
+        let mut out = format!("{}\n", name);
+        for field in DEPENDENCY_FIELDS {
+            for entry in record.relations(field).into_iter().flat_map(|r| r.iter()) {
+                for (i, relation) in entry.iter().enumerate() {
+                    let marker = if i + 1 < entry.len() { '|' } else { ' ' };
+                    if self.index.contains(&relation.name) {
+                        writeln!(out, " {}{}: {}", marker, field, relation).unwrap();
+                    } else {
+                        writeln!(out, " {}{}: <{}>", marker, field, relation).unwrap();
+                        for provider in self.providers(relation) {
+                            writeln!(out, "    {}", provider).unwrap();
+                        }
+                    }
+                }
+            }
+        }
+        Some(out)
+    }
+
+    fn rdepends(&self, name: &str) -> Option<String> {
+        let record = self.index.get(name)?;
+        // Dependencies on virtual packages provided by this package count as well.
+        let mut names = vec![name.to_string()];
+        if let Some(provides) = &record.package.provides {
+            names.extend(provides.0.iter().flatten().map(|r| r.name.clone()));
+        }
+        let mut rdepends = BTreeSet::new();
+        for other in self.index.iter() {
+            for field in DEPENDENCY_FIELDS {
+                for entry in other.relations(field).into_iter().flat_map(|r| r.iter()) {
+                    if entry.iter().any(|r| names.contains(&r.name)) {
+                        let marker = if entry.len() > 1 { '|' } else { ' ' };
+                        rdepends.insert((marker, other.package.name.clone()));
+                    }
+                }
+            }
+        }
+        let mut out = format!("{}\nReverse Depends:\n", name);
+        for (marker, name) in rdepends {
+            writeln!(out, " {}{}", marker, name).unwrap();
+        }
+        Some(out)
+    }
+
+    fn policy(&self, name: &str) -> Option<String> {
+        let candidate = self.index.get(name)?;
+        let installed = self
+            .index
+            .get_versions(name)
+            .find(|record| record.is_installed());
+        let mut out = format!("{}:\n", name);
+        writeln!(
+            out,
+            "  Installed: {}",
+            installed.map_or("(none)".to_string(), |r| r.package.version.to_string())
+        )
+        .unwrap();
+        writeln!(out, "  Candidate: {}", candidate.package.version).unwrap();
+        writeln!(out, "  Version table:").unwrap();
+        let mut versions = self.index.get_versions(name).collect::<Vec<_>>();
+        versions.sort_by(|a, b| b.package.version.cmp(&a.package.version));
+        versions.dedup_by(|a, b| a.package.version == b.package.version);
+        for record in versions {
+            let version = &record.package.version;
+            let marker = if installed.is_some_and(|i| &i.package.version == version) {
+                "***"
+            } else {
+                "   "
+            };
+            writeln!(out, " {} {}", marker, version).unwrap();
+            let mut files = self
+                .index
+                .get_versions(name)
+                .filter(|r| &r.package.version == version)
+                .map(|r| r.file)
+                .collect::<Vec<_>>();
+            files.dedup();
+            for file in files {
+                writeln!(out, "        {}", self.files[file]).unwrap();
+            }
+        }
+        Some(out)
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut universe = Universe::new();
+    for path in &args.files {
+        let name = path.display().to_string();
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| universe.load(&name, &text));
+        match result {
+            Ok(errors) => {
+                for e in errors {
+                    eprintln!("W: {}: {}", name, e);
+                }
+            }
+            Err(e) => {
+                eprintln!("E: {}: {}", name, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let (packages, query): (_, fn(&Universe, &str) -> Option<String>) = match &args.command {
+        Command::Show { packages } => (packages, Universe::show),
+        Command::Depends { packages } => (packages, Universe::depends),
+        Command::Rdepends { packages } => (packages, Universe::rdepends),
+        Command::Policy { packages } => (packages, Universe::policy),
+    };
+
+    let mut failed = false;
+    for name in packages {
+        match query(&universe, name) {
+            Some(out) => print!("{}", out),
+            None => {
+                eprintln!("E: Unable to locate package {}", name);
+                failed = true;
+            }
+        }
+    }
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGES: &str = r#"Package: hello
+Version: 2.10-3
+Architecture: amd64
+Depends: libc6 (>= 2.34), mail-transport-agent | foo
+
+Package: libc6
+Version: 2.36-9
+Architecture: amd64
+
+Package: postfix
+Version: 3.7.6-0
+Architecture: amd64
+Provides: mail-transport-agent
+Recommends: hello
+"#;
+
+    const STATUS: &str = r#"Package: hello
+Status: install ok installed
+Version: 2.10-2
+Architecture: amd64
+
+Package: broken
+Status: install ok installed
+"#;
+
+    fn universe() -> Universe {
+        let mut universe = Universe::new();
+        assert!(universe.load("Packages", PACKAGES).unwrap().is_empty());
+        assert_eq!(universe.load("status", STATUS).unwrap().len(), 1);
+        universe
+    }
+
+    #[test]
+    fn test_show() {
+        let universe = universe();
+        let out = universe.show("libc6").unwrap();
+        assert_eq!(
+            out,
+            "Package: libc6\nVersion: 2.36-9\nArchitecture: amd64\n\n"
+        );
+        assert_eq!(
+            universe.show("hello").unwrap().matches("Package:").count(),
+            2
+        );
+        assert!(universe.show("missing").is_none());
+    }
+
+    #[test]
+    fn test_depends() {
+        let universe = universe();
+        assert_eq!(
+            universe.depends("hello").unwrap(),
+            r#"hello
+  Depends: libc6 (>= 2.34)
+ |Depends: <mail-transport-agent>
+    postfix
+  Depends: <foo>
+"#
+        );
+    }
+
+    #[test]
+    fn test_rdepends() {
+        let universe = universe();
+        assert_eq!(
+            universe.rdepends("postfix").unwrap(),
+            "postfix\nReverse Depends:\n |hello\n"
+        );
+        assert_eq!(
+            universe.rdepends("hello").unwrap(),
+            "hello\nReverse Depends:\n  postfix\n"
+        );
+    }
+
+    #[test]
+    fn test_policy() {
+        let universe = universe();
+        assert_eq!(
+            universe.policy("hello").unwrap(),
+            r#"hello:
+  Installed: 2.10-2
+  Candidate: 2.10-3
+  Version table:
+     2.10-3
+        Packages
+ *** 2.10-2
+        status
+"#
+        );
+        assert!(universe.policy("missing").is_none());
+    }
+}
@@ -0,0 +1,290 @@
+//! A reimplementation of `wrap-and-sort` from devscripts.
+//!
+//! Wraps and sorts the dependency fields in `debian/control` and `debian/tests/control`, the
+//! `Files` fields in machine-readable `debian/copyright` files and the lines in debhelper list
+//! files such as `debian/*.install`. Only the fields that are reformatted change; comments and
+//! the formatting of other fields are preserved.
+use clap::Parser;
+use deb822_lossless::Deb822;
+use debian_control::format::FormatOptions;
+use std::path::{Path, PathBuf};
+
+/// Extensions of debhelper files that contain one item per line, in no particular order.
+const LIST_FILE_EXTENSIONS: &[&str] = &[
+    "clean", "dirs", "docs", "examples", "info", "install", "links", "manpages",
+];
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Wrap long lines and sort items in Debian packaging files"
+)]
+struct Args {
+    /// Preset or configuration file to start from, e.g. "wrap-and-sort -ast" or "black-style";
+    /// the other options are applied on top of it
+    #[arg(long)]
+    style: Option<String>,
+
+    /// Wrap lists even if they fit on a single line
+    #[arg(short = 'a', long)]
+    wrap_always: bool,
+
+    /// Indent wrapped lines by a single space, rather than aligning them with the field value
+    #[arg(short = 's', long)]
+    short_indent: bool,
+
+    /// Sort binary package paragraphs by package name
+    #[arg(short = 'b', long)]
+    sort_binary_packages: bool,
+
+    /// When sorting binary packages, keep the first one in place
+    #[arg(short = 'k', long)]
+    keep_first: bool,
+
+    /// Add a trailing comma to wrapped lists
+    #[arg(short = 't', long)]
+    trailing_comma: bool,
+
+    /// Put the fields of debian/control in their conventional order
+    #[arg(long)]
+    sort_fields: bool,
+
+    /// Location of the debian directory
+    #[arg(short = 'd', long, default_value = "debian")]
+    debian_directory: PathBuf,
+
+    /// Process only the given file; can be specified multiple times
+    #[arg(short = 'f', long = "file")]
+    files: Vec<PathBuf>,
+
+    /// Maximum length of a line before a list is wrapped [default: 79]
+    #[arg(short = 'l', long)]
+    max_line_length: Option<usize>,
+
+    /// Show a diff of the changes, rather than writing them
+    #[arg(short = 'N', long)]
+    dry_run: bool,
+
+    /// Print the names of the files that are processed
+    #[arg(short = 'v', long)]
+    verbose: bool,
+}
+
+fn parse(text: &str) -> Result<Deb822, String> {
+    text.parse()
+        .map_err(|e: deb822_lossless::ParseError| e.to_string())
+}
+
+/// Sort the lines of a debhelper list file.
+///
+/// Files with comments are left alone, since it's not clear which lines the comments belong to.
+fn format_list_file(text: &str) -> Option<String> {
+    if text.lines().any(|line| line.trim_start().starts_with('#')) {
+        return None;
+    }
+    let mut lines = text
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.dedup();
+    Some(lines.into_iter().map(|line| line + "\n").collect())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+fn is_list_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| LIST_FILE_EXTENSIONS.contains(&e))
+}
+
+/// Find the files to process in a debian directory.
+fn find_files(debian: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = ["control", "control.in", "copyright", "tests/control"]
+        .iter()
+        .map(|name| debian.join(name))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    let mut list_files = std::fs::read_dir(debian)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    list_files.retain(|path| path.is_file() && is_list_file(path));
+    list_files.sort();
+    files.extend(list_files);
+    Ok(files)
+}
+
+/// Reformat a file, returning the new contents or `None` if the file is not handled.
+fn format_file(path: &Path, text: &str, options: &FormatOptions) -> Result<Option<String>, String> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let parent = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str());
+    match name {
+        "control" if parent == Some("tests") => {
+            let mut doc = parse(text)?;
+            debian_control::format::format_tests_control(&mut doc, options);
+            Ok(Some(doc.to_string()))
+        }
+        "control" | "control.in" => {
+            let mut doc = parse(text)?;
+            debian_control::format::format(&mut doc, options);
+            Ok(Some(doc.to_string()))
+        }
+        "copyright" => {
+            let mut doc = parse(text)?;
+            Ok(
+                debian_control::format::format_copyright(&mut doc, options)
+                    .then(|| doc.to_string()),
+            )
+        }
+        // Executable files are typically dh-exec scripts
+        _ if is_list_file(path) && !is_executable(path) => Ok(format_list_file(text)),
+        _ => Ok(None),
+    }
+}
+
+impl Args {
+    fn options(&self) -> Result<FormatOptions, debian_control::format::ConfigError> {
+        let mut options = match &self.style {
+            Some(style) => FormatOptions::load(style)?,
+            None => FormatOptions::default(),
+        };
+        options.wrap_always |= self.wrap_always;
+        options.short_indent |= self.short_indent;
+        options.trailing_comma |= self.trailing_comma;
+        options.sort_binary_packages |= self.sort_binary_packages;
+        options.keep_first |= self.keep_first;
+        options.sort_fields |= self.sort_fields;
+        if let Some(max_line_length) = self.max_line_length {
+            options.max_line_length = max_line_length;
+        }
+        Ok(options)
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let options = match args.options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}: {}", args.style.as_deref().unwrap_or_default(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let files = if args.files.is_empty() {
+        match find_files(&args.debian_directory) {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("{}: {}", args.debian_directory.display(), e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        args.files.clone()
+    };
+
+    let mut failed = false;
+    for path in files {
+        if args.verbose {
+            eprintln!("{}", path.display());
+        }
+        let result = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Ok((format_file(&path, &text, &options)?, text)));
+        let (new_text, old_text) = match result {
+            Ok((Some(new_text), old_text)) => (new_text, old_text),
+            Ok((None, _)) => continue,
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                failed = true;
+                continue;
+            }
+        };
+        if new_text == old_text {
+            continue;
+        }
+        if args.dry_run {
+            let name = path.display().to_string();
+            print!(
+                "{}",
+                similar::TextDiff::from_lines(&old_text, &new_text)
+                    .unified_diff()
+                    .header(&name, &name)
+            );
+        } else if let Err(e) = std::fs::write(&path, new_text) {
+            eprintln!("{}: {}", path.display(), e);
+            failed = true;
+        }
+    }
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_file() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            format_file(Path::new("debian/tests/control"), "Tests: b a\n", &options).unwrap(),
+            Some("Tests: a, b\n".to_string())
+        );
+        assert_eq!(
+            format_file(Path::new("debian/control"), "Depends: b, a\n", &options).unwrap(),
+            Some("Depends: a, b\n".to_string())
+        );
+        assert_eq!(
+            format_file(Path::new("debian/copyright"), "Files: b a\n", &options).unwrap(),
+            None
+        );
+        assert!(format_file(Path::new("debian/control"), "Foo\n", &options).is_err());
+        assert_eq!(
+            format_file(Path::new("debian/rules"), "all:\n", &options).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let args = Args::parse_from(["wrap-and-sort-rs", "--style", "wrap-and-sort -as", "-t"]);
+        assert_eq!(
+            args.options().unwrap(),
+            FormatOptions {
+                wrap_always: true,
+                short_indent: true,
+                trailing_comma: true,
+                ..Default::default()
+            }
+        );
+        let args = Args::parse_from(["wrap-and-sort-rs", "--style", "dpkg-default", "-l", "60"]);
+        let options = args.options().unwrap();
+        assert!(!options.sort_lists);
+        assert_eq!(options.max_line_length, 60);
+        let args = Args::parse_from(["wrap-and-sort-rs", "--style", "no-such-style"]);
+        assert!(args.options().is_err());
+    }
+
+    #[test]
+    fn test_list_file() {
+        assert_eq!(
+            format_list_file("usr/bin/foo\n\nusr/bin/bar   \nusr/bin/foo\n"),
+            Some("usr/bin/bar\nusr/bin/foo\n".to_string())
+        );
+        assert_eq!(format_list_file("# keep\nb\na\n"), None);
+    }
+}
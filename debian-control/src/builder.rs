@@ -0,0 +1,635 @@
+//! Programmatic construction of paragraphs.
+//!
+//! [`ParagraphBuilder`] takes care of the details of the deb822 format that are easy to get
+//! wrong when concatenating strings: continuation lines are indented, empty lines in
+//! descriptions are written as `.`, and long relationship fields are wrapped.
+//! [`SourceBuilder`] and [`BinaryBuilder`] do the same for the paragraphs of a
+//! `debian/control` file, with typed methods for the common fields, and [`ControlBuilder`]
+//! combines them into a complete control file.
+//!
+//! # Example
+//! ```rust
+//! use debian_control::builder::ParagraphBuilder;
+//! use debian_control::lossy::Relations;
+//!
+//! let depends: Relations = "libc6 (>= 2.34), libgtk-3-0".parse().unwrap();
+//! let paragraph = ParagraphBuilder::new()
+//!     .field("Package", "hello")
+//!     .field("Architecture", "any")
+//!     .relations("Depends", &depends)
+//!     .multiline(
+//!         "Description",
+//!         "example package",
+//!         "This package greets the world.\n\nIt is not very useful.",
+//!     )
+//!     .build();
+//! assert_eq!(
+//!     paragraph.to_string(),
+//!     r#"Package: hello
+//! Architecture: any
+//! Depends: libc6 (>= 2.34), libgtk-3-0
+//! Description: example package
+//!  This package greets the world.
+//!  .
+//!  It is not very useful.
+//! "#
+//! );
+//! ```
+use crate::fields::{MultiArch, PackageType, Priority};
+use crate::lossy::Relations;
+use deb822_lossless::convert::{fold_list, Separator};
+use deb822_lossless::folding::FieldKind;
+use deb822_lossless::lossy::Paragraph;
+
+/// A builder for a paragraph.
+///
+/// Fields are added in the order the methods are called. Adding a field that is already
+/// present replaces its value, keeping its position.
+#[derive(Debug, Clone, Default)]
+pub struct ParagraphBuilder {
+    fields: Vec<(String, String)>,
+}
+
+impl ParagraphBuilder {
+    /// Create a new, empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a field.
+    ///
+    /// If the value spans multiple lines, the lines after the first are written as
+    /// continuation lines.
+    pub fn field(self, name: &str, value: &str) -> Self {
+        self.push(name, FieldKind::Multiline.encode(value))
+    }
+
+    /// Add a field, if `value` is `Some`.
+    pub fn optional_field(self, name: &str, value: Option<&str>) -> Self {
+        match value {
+            Some(value) => self.field(name, value),
+            None => self,
+        }
+    }
+
+    /// Add a multi-line field, such as `Description`, with a synopsis and an extended body.
+    ///
+    /// Empty lines in `body` are written as `.`.
+    pub fn multiline(self, name: &str, synopsis: &str, body: &str) -> Self {
+        let value = format!("{}\n{}", synopsis.trim(), body.trim_matches('\n'));
+        self.push(name, FieldKind::Multiline.encode(&value))
+    }
+
+    /// Add a relationship field, such as `Depends`.
+    ///
+    /// The field is written on a single line if it fits in 79 columns, and with one entry per
+    /// line otherwise. Nothing is added if `relations` is empty.
+    pub fn relations(self, name: &str, relations: &Relations) -> Self {
+        if relations.is_empty() {
+            return self;
+        }
+        let entries = relations.iter().map(|entry| {
+            entry
+                .iter()
+                .map(|relation| relation.to_string())
+                .collect::<Vec<_>>()
+                .join(" | ")
+        });
+        let value = fold_list(name, entries, Separator::Comma);
+        self.push(name, value)
+    }
+
+    /// Set the value of a field, replacing the field if it is already present.
+    fn push(mut self, name: &str, value: String) -> Self {
+        match self
+            .fields
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            Some(field) => field.1 = value,
+            None => self.fields.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    /// Build the paragraph.
+    pub fn build(self) -> Paragraph {
+        self.fields.into()
+    }
+}
+
+impl From<ParagraphBuilder> for Paragraph {
+    fn from(builder: ParagraphBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A builder for the source package paragraph in a `debian/control` file.
+///
+/// The `Source` field comes first; the other fields are added in the order the methods are
+/// called.
+///
+/// # Example
+/// ```rust
+/// use debian_control::builder::SourceBuilder;
+///
+/// let paragraph = SourceBuilder::new("hello")
+///     .maintainer("Jane Doe <jane@example.com>")
+///     .build_depends(&"debhelper-compat (= 13)".parse().unwrap())
+///     .standards_version("4.7.0")
+///     .rules_requires_root(false)
+///     .build();
+/// assert_eq!(
+///     paragraph.to_string(),
+///     r#"Source: hello
+/// Maintainer: Jane Doe <jane@example.com>
+/// Build-Depends: debhelper-compat (= 13)
+/// Standards-Version: 4.7.0
+/// Rules-Requires-Root: no
+/// "#
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceBuilder(ParagraphBuilder);
+
+impl SourceBuilder {
+    /// Create a new builder for the source package with the given name.
+    pub fn new(name: &str) -> Self {
+        Self(ParagraphBuilder::new().field("Source", name))
+    }
+
+    /// Set the section of the source package.
+    pub fn section(self, section: &str) -> Self {
+        self.field("Section", section)
+    }
+
+    /// Set the priority of the source package.
+    pub fn priority(self, priority: Priority) -> Self {
+        self.field("Priority", &priority.to_string())
+    }
+
+    /// Set the maintainer, e.g. `Jane Doe <jane@example.com>`.
+    pub fn maintainer(self, maintainer: &str) -> Self {
+        self.field("Maintainer", maintainer)
+    }
+
+    /// Set the uploaders.
+    ///
+    /// The list is written on a single line if it fits in 79 columns, and with one uploader
+    /// per line otherwise. Nothing is added if `uploaders` is empty.
+    pub fn uploaders(mut self, uploaders: &[&str]) -> Self {
+        if !uploaders.is_empty() {
+            let value = fold_list("Uploaders", uploaders, Separator::Comma);
+            self.0 = self.0.push("Uploaders", value);
+        }
+        self
+    }
+
+    /// Set the `Build-Depends` field.
+    pub fn build_depends(self, relations: &Relations) -> Self {
+        self.relations("Build-Depends", relations)
+    }
+
+    /// Set the `Build-Depends-Indep` field.
+    pub fn build_depends_indep(self, relations: &Relations) -> Self {
+        self.relations("Build-Depends-Indep", relations)
+    }
+
+    /// Set the `Build-Depends-Arch` field.
+    pub fn build_depends_arch(self, relations: &Relations) -> Self {
+        self.relations("Build-Depends-Arch", relations)
+    }
+
+    /// Set the `Build-Conflicts` field.
+    pub fn build_conflicts(self, relations: &Relations) -> Self {
+        self.relations("Build-Conflicts", relations)
+    }
+
+    /// Set the `Build-Conflicts-Indep` field.
+    pub fn build_conflicts_indep(self, relations: &Relations) -> Self {
+        self.relations("Build-Conflicts-Indep", relations)
+    }
+
+    /// Set the `Build-Conflicts-Arch` field.
+    pub fn build_conflicts_arch(self, relations: &Relations) -> Self {
+        self.relations("Build-Conflicts-Arch", relations)
+    }
+
+    /// Set the version of the Debian policy the package complies with.
+    pub fn standards_version(self, version: &str) -> Self {
+        self.field("Standards-Version", version)
+    }
+
+    /// Set the upstream homepage.
+    pub fn homepage(self, homepage: &str) -> Self {
+        self.field("Homepage", homepage)
+    }
+
+    /// Set the URL of the git repository with the packaging.
+    pub fn vcs_git(self, url: &str) -> Self {
+        self.field("Vcs-Git", url)
+    }
+
+    /// Set the URL of the web interface of the repository with the packaging.
+    pub fn vcs_browser(self, url: &str) -> Self {
+        self.field("Vcs-Browser", url)
+    }
+
+    /// Set whether building the package requires root.
+    pub fn rules_requires_root(self, requires_root: bool) -> Self {
+        self.field(
+            "Rules-Requires-Root",
+            if requires_root {
+                "binary-targets"
+            } else {
+                "no"
+            },
+        )
+    }
+
+    /// Add a relationship field that has no dedicated method.
+    ///
+    /// See [`ParagraphBuilder::relations`].
+    pub fn relations(self, name: &str, relations: &Relations) -> Self {
+        Self(self.0.relations(name, relations))
+    }
+
+    /// Add a field that has no dedicated method.
+    ///
+    /// See [`ParagraphBuilder::field`].
+    pub fn field(self, name: &str, value: &str) -> Self {
+        Self(self.0.field(name, value))
+    }
+
+    /// Build the paragraph.
+    pub fn build(self) -> Paragraph {
+        self.0.build()
+    }
+}
+
+impl From<SourceBuilder> for Paragraph {
+    fn from(builder: SourceBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A builder for a binary package paragraph in a `debian/control` file.
+///
+/// The `Package` field comes first; the other fields are added in the order the methods are
+/// called.
+///
+/// # Example
+/// ```rust
+/// use debian_control::builder::BinaryBuilder;
+/// use debian_control::fields::MultiArch;
+///
+/// let paragraph = BinaryBuilder::new("libhello1")
+///     .architecture("any")
+///     .multi_arch(MultiArch::Same)
+///     .depends(&"libc6 (>= 2.34)".parse().unwrap())
+///     .description("hello library", "This package contains the shared library.")
+///     .build();
+/// assert_eq!(
+///     paragraph.to_string(),
+///     r#"Package: libhello1
+/// Architecture: any
+/// Multi-Arch: same
+/// Depends: libc6 (>= 2.34)
+/// Description: hello library
+///  This package contains the shared library.
+/// "#
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinaryBuilder(ParagraphBuilder);
+
+impl BinaryBuilder {
+    /// Create a new builder for the binary package with the given name.
+    pub fn new(name: &str) -> Self {
+        Self(ParagraphBuilder::new().field("Package", name))
+    }
+
+    /// Set the architectures the package is built for, e.g. `any` or `all`.
+    pub fn architecture(self, architecture: &str) -> Self {
+        self.field("Architecture", architecture)
+    }
+
+    /// Set the section of the package.
+    pub fn section(self, section: &str) -> Self {
+        self.field("Section", section)
+    }
+
+    /// Set the priority of the package.
+    pub fn priority(self, priority: Priority) -> Self {
+        self.field("Priority", &priority.to_string())
+    }
+
+    /// Set the multi-arch behaviour of the package.
+    pub fn multi_arch(self, multi_arch: MultiArch) -> Self {
+        self.field("Multi-Arch", &multi_arch.to_string())
+    }
+
+    /// Set the `Package-Type` field.
+    pub fn package_type(self, package_type: PackageType) -> Self {
+        self.field("Package-Type", &package_type.to_string())
+    }
+
+    /// Mark the package as essential.
+    ///
+    /// Nothing is added if `essential` is false.
+    pub fn essential(self, essential: bool) -> Self {
+        if essential {
+            self.field("Essential", "yes")
+        } else {
+            self
+        }
+    }
+
+    /// Mark the package as protected.
+    ///
+    /// Nothing is added if `protected` is false.
+    pub fn protected(self, protected: bool) -> Self {
+        if protected {
+            self.field("Protected", "yes")
+        } else {
+            self
+        }
+    }
+
+    /// Set the `Depends` field.
+    pub fn depends(self, relations: &Relations) -> Self {
+        self.relations("Depends", relations)
+    }
+
+    /// Set the `Pre-Depends` field.
+    pub fn pre_depends(self, relations: &Relations) -> Self {
+        self.relations("Pre-Depends", relations)
+    }
+
+    /// Set the `Recommends` field.
+    pub fn recommends(self, relations: &Relations) -> Self {
+        self.relations("Recommends", relations)
+    }
+
+    /// Set the `Suggests` field.
+    pub fn suggests(self, relations: &Relations) -> Self {
+        self.relations("Suggests", relations)
+    }
+
+    /// Set the `Enhances` field.
+    pub fn enhances(self, relations: &Relations) -> Self {
+        self.relations("Enhances", relations)
+    }
+
+    /// Set the `Breaks` field.
+    pub fn breaks(self, relations: &Relations) -> Self {
+        self.relations("Breaks", relations)
+    }
+
+    /// Set the `Conflicts` field.
+    pub fn conflicts(self, relations: &Relations) -> Self {
+        self.relations("Conflicts", relations)
+    }
+
+    /// Set the `Replaces` field.
+    pub fn replaces(self, relations: &Relations) -> Self {
+        self.relations("Replaces", relations)
+    }
+
+    /// Set the `Provides` field.
+    pub fn provides(self, relations: &Relations) -> Self {
+        self.relations("Provides", relations)
+    }
+
+    /// Set the description, from a synopsis and an extended description.
+    ///
+    /// Empty lines in `body` are written as `.`.
+    pub fn description(self, synopsis: &str, body: &str) -> Self {
+        Self(self.0.multiline("Description", synopsis, body))
+    }
+
+    /// Add a relationship field that has no dedicated method.
+    ///
+    /// See [`ParagraphBuilder::relations`].
+    pub fn relations(self, name: &str, relations: &Relations) -> Self {
+        Self(self.0.relations(name, relations))
+    }
+
+    /// Add a field that has no dedicated method.
+    ///
+    /// See [`ParagraphBuilder::field`].
+    pub fn field(self, name: &str, value: &str) -> Self {
+        Self(self.0.field(name, value))
+    }
+
+    /// Build the paragraph.
+    pub fn build(self) -> Paragraph {
+        self.0.build()
+    }
+}
+
+impl From<BinaryBuilder> for Paragraph {
+    fn from(builder: BinaryBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A builder for a complete `debian/control` file.
+///
+/// Create one with [`Control::builder`](crate::lossless::Control::builder).
+///
+/// # Example
+/// ```rust
+/// use debian_control::builder::{BinaryBuilder, SourceBuilder};
+/// use debian_control::lossless::Control;
+///
+/// let control = Control::builder()
+///     .source(SourceBuilder::new("hello").section("devel"))
+///     .binary(BinaryBuilder::new("hello").architecture("any"))
+///     .binary(BinaryBuilder::new("hello-doc").architecture("all"))
+///     .build();
+/// assert_eq!(
+///     control.to_string(),
+///     r#"Source: hello
+/// Section: devel
+///
+/// Package: hello
+/// Architecture: any
+///
+/// Package: hello-doc
+/// Architecture: all
+/// "#
+/// );
+/// ```
+#[cfg(feature = "lossless")]
+#[derive(Debug, Clone, Default)]
+pub struct ControlBuilder {
+    source: Option<Paragraph>,
+    binaries: Vec<Paragraph>,
+}
+
+#[cfg(feature = "lossless")]
+impl ControlBuilder {
+    /// Create a new, empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the source package paragraph.
+    ///
+    /// The source package paragraph always comes first, regardless of the order the methods
+    /// are called in.
+    pub fn source(mut self, source: impl Into<Paragraph>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Add a binary package paragraph.
+    ///
+    /// Binary packages are written in the order they are added.
+    pub fn binary(mut self, binary: impl Into<Paragraph>) -> Self {
+        self.binaries.push(binary.into());
+        self
+    }
+
+    /// Build the control file.
+    pub fn build(self) -> crate::lossless::Control {
+        let paragraphs = self.source.into_iter().chain(self.binaries);
+        deb822_lossless::lossy::Deb822::from_iter(paragraphs)
+            .to_lossless()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field() {
+        let paragraph = ParagraphBuilder::new()
+            .field("Package", "foo ")
+            .field("Comment", "first\nsecond\n\nthird")
+            .optional_field("Section", None)
+            .optional_field("Priority", Some("optional"))
+            .field("package", "bar")
+            .build();
+        assert_eq!(
+            paragraph.to_string(),
+            "Package: bar\nComment: first\n second\n .\n third\nPriority: optional\n"
+        );
+    }
+
+    #[test]
+    fn test_multiline() {
+        let paragraph = ParagraphBuilder::new()
+            .multiline("Description", "synopsis", "")
+            .build();
+        assert_eq!(paragraph.to_string(), "Description: synopsis\n");
+        let paragraph = ParagraphBuilder::new()
+            .multiline("Description", "synopsis", "\n  indented\n\nbody\n")
+            .build();
+        assert_eq!(
+            paragraph.to_string(),
+            "Description: synopsis\n   indented\n .\n body\n"
+        );
+    }
+
+    #[test]
+    fn test_relations() {
+        let short: Relations = "foo | bar, baz".parse().unwrap();
+        let long: Relations =
+            "debhelper-compat (= 13), dh-python, python3-all, python3-setuptools, python3-pytest"
+                .parse()
+                .unwrap();
+        let paragraph = ParagraphBuilder::new()
+            .relations("Depends", &short)
+            .relations("Build-Depends", &long)
+            .relations("Recommends", &Relations::new())
+            .build();
+        assert_eq!(
+            paragraph.to_string(),
+            r#"Depends: foo | bar, baz
+Build-Depends:
+ debhelper-compat (= 13),
+ dh-python,
+ python3-all,
+ python3-setuptools,
+ python3-pytest
+"#
+        );
+        let parsed: Relations = paragraph.get("Build-Depends").unwrap().parse().unwrap();
+        assert_eq!(parsed, long);
+    }
+
+    #[test]
+    fn test_binary() {
+        let paragraph = BinaryBuilder::new("hello")
+            .section("utils")
+            .priority(Priority::Optional)
+            .architecture("any")
+            .essential(false)
+            .pre_depends(&Relations::new())
+            .depends(&"libc6".parse().unwrap())
+            .field("X-Custom", "value")
+            .description("greet the world", "")
+            .build();
+        assert_eq!(
+            paragraph.to_string(),
+            r#"Package: hello
+Section: utils
+Priority: optional
+Architecture: any
+Depends: libc6
+X-Custom: value
+Description: greet the world
+"#
+        );
+        assert_eq!(
+            Paragraph::from(BinaryBuilder::new("hello").essential(true)).to_string(),
+            "Package: hello\nEssential: yes\n"
+        );
+    }
+
+    #[test]
+    fn test_source() {
+        let paragraph = SourceBuilder::new("hello")
+            .priority(Priority::Optional)
+            .maintainer("Jane Doe <jane@example.com>")
+            .uploaders(&[])
+            .uploaders(&[
+                "John Doe <john.doe@example.com>",
+                "Jelmer Vernooĳ <jelmer@debian.org>",
+                "Some One Else <someone.else@example.org>",
+            ])
+            .build_depends_indep(&"python3-sphinx".parse().unwrap())
+            .rules_requires_root(true)
+            .build();
+        assert_eq!(
+            paragraph.to_string(),
+            r#"Source: hello
+Priority: optional
+Maintainer: Jane Doe <jane@example.com>
+Uploaders:
+ John Doe <john.doe@example.com>,
+ Jelmer Vernooĳ <jelmer@debian.org>,
+ Some One Else <someone.else@example.org>
+Build-Depends-Indep: python3-sphinx
+Rules-Requires-Root: binary-targets
+"#
+        );
+    }
+
+    #[cfg(feature = "lossless")]
+    #[test]
+    fn test_control() {
+        let control = ControlBuilder::new()
+            .binary(BinaryBuilder::new("hello"))
+            .source(SourceBuilder::new("hello"))
+            .build();
+        assert_eq!(control.to_string(), "Source: hello\n\nPackage: hello\n");
+        assert_eq!(control.source().unwrap().name(), Some("hello".to_owned()));
+        assert_eq!(control.binaries().count(), 1);
+        assert_eq!(ControlBuilder::new().build().to_string(), "");
+    }
+}
@@ -0,0 +1,299 @@
+//! Canonical capitalization of field names.
+//!
+//! Field names in deb822 files are case-insensitive, but tools and humans expect them to be
+//! spelled the way policy spells them. The functions in this module look up the canonical
+//! spelling of known fields and rewrite sloppy field names, preserving values and comments.
+//!
+//! # Example
+//! ```rust
+//! use debian_control::casing::{normalize_field_names, FileType};
+//!
+//! let mut doc: deb822_lossless::Deb822 =
+//!     "source: foo\nvcs-git: https://example.com/foo.git\n\nPackage: foo\nmulti-arch: same\n"
+//!         .parse()
+//!         .unwrap();
+//! assert_eq!(normalize_field_names(&mut doc, FileType::Control), 3);
+//! assert_eq!(
+//!     doc.to_string(),
+//!     "Source: foo\nVcs-Git: https://example.com/foo.git\n\nPackage: foo\nMulti-Arch: same\n"
+//! );
+//! ```
+use deb822_lossless::Deb822;
+
+/// The kind of file, which determines the set of known fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileType {
+    /// A `debian/control` file.
+    Control,
+
+    /// A `debian/tests/control` file.
+    TestsControl,
+
+    /// A machine-readable `debian/copyright` file.
+    Copyright,
+
+    /// A source package description (`.dsc`) file.
+    Dsc,
+
+    /// A `.changes` file.
+    Changes,
+}
+
+/// Fields in the source package paragraph of `debian/control`.
+const CONTROL_SOURCE_FIELDS: &[&str] = &[
+    "Source",
+    "Section",
+    "Priority",
+    "Maintainer",
+    "Uploaders",
+    "Standards-Version",
+    "Build-Depends",
+    "Build-Depends-Arch",
+    "Build-Depends-Indep",
+    "Build-Conflicts",
+    "Build-Conflicts-Arch",
+    "Build-Conflicts-Indep",
+    "Build-Driver",
+    "Homepage",
+    "Rules-Requires-Root",
+    "Testsuite",
+    "Testsuite-Triggers",
+    "Vcs-Arch",
+    "Vcs-Browser",
+    "Vcs-Bzr",
+    "Vcs-Cvs",
+    "Vcs-Darcs",
+    "Vcs-Git",
+    "Vcs-Hg",
+    "Vcs-Mtn",
+    "Vcs-Svn",
+];
+
+/// Fields in the binary package paragraphs of `debian/control`.
+const CONTROL_BINARY_FIELDS: &[&str] = &[
+    "Package",
+    "Architecture",
+    "Multi-Arch",
+    "Essential",
+    "Protected",
+    "Package-Type",
+    "Build-Profiles",
+    "Pre-Depends",
+    "Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Provides",
+    "Replaces",
+    "Built-Using",
+    "Static-Built-Using",
+    "Description",
+];
+
+/// Fields in `debian/tests/control`.
+const TESTS_CONTROL_FIELDS: &[&str] = &[
+    "Tests",
+    "Test-Command",
+    "Restrictions",
+    "Features",
+    "Depends",
+    "Tests-Directory",
+    "Classes",
+    "Architecture",
+];
+
+/// Fields in machine-readable `debian/copyright` files.
+const COPYRIGHT_FIELDS: &[&str] = &[
+    "Format",
+    "Upstream-Name",
+    "Upstream-Contact",
+    "Source",
+    "Disclaimer",
+    "Comment",
+    "License",
+    "Copyright",
+    "Files",
+    "Files-Excluded",
+];
+
+/// Fields in `.dsc` files, in addition to those of the source package paragraph.
+const DSC_FIELDS: &[&str] = &[
+    "Format",
+    "Binary",
+    "Architecture",
+    "Version",
+    "Dgit",
+    "Package-List",
+    "Checksums-Sha1",
+    "Checksums-Sha256",
+    "Files",
+];
+
+/// Fields in `.changes` files.
+const CHANGES_FIELDS: &[&str] = &[
+    "Format",
+    "Date",
+    "Source",
+    "Binary",
+    "Architecture",
+    "Version",
+    "Distribution",
+    "Urgency",
+    "Maintainer",
+    "Changed-By",
+    "Description",
+    "Closes",
+    "Changes",
+    "Built-For-Profiles",
+    "Checksums-Sha1",
+    "Checksums-Sha256",
+    "Files",
+];
+
+impl FileType {
+    /// The canonical names of the fields known for this type of file.
+    pub fn known_fields(&self) -> impl Iterator<Item = &'static str> {
+        let lists: &[&[&'static str]] = match self {
+            FileType::Control => &[CONTROL_SOURCE_FIELDS, CONTROL_BINARY_FIELDS],
+            FileType::TestsControl => &[TESTS_CONTROL_FIELDS],
+            FileType::Copyright => &[COPYRIGHT_FIELDS],
+            FileType::Dsc => &[CONTROL_SOURCE_FIELDS, DSC_FIELDS],
+            FileType::Changes => &[CHANGES_FIELDS],
+        };
+        lists.iter().flat_map(|list| list.iter().copied())
+    }
+}
+
+/// Return the canonical spelling of a field name, if the field is known.
+///
+/// User-defined fields in `debian/control` keep their `X[SBC]*-` prefix, which is written in
+/// upper case, followed by the canonical spelling of the remainder.
+///
+/// # Example
+/// ```rust
+/// use debian_control::casing::{canonical_field_name, FileType};
+///
+/// assert_eq!(canonical_field_name(FileType::Control, "multi-arch").as_deref(), Some("Multi-Arch"));
+/// assert_eq!(canonical_field_name(FileType::Control, "xs-testsuite").as_deref(), Some("XS-Testsuite"));
+/// assert_eq!(canonical_field_name(FileType::Control, "X-Unknown"), None);
+/// ```
+pub fn canonical_field_name(file_type: FileType, name: &str) -> Option<String> {
+    let lookup = |name: &str| {
+        file_type
+            .known_fields()
+            .find(|known| known.eq_ignore_ascii_case(name))
+    };
+    if let Some(known) = lookup(name) {
+        return Some(known.to_string());
+    }
+    if file_type != FileType::Control {
+        return None;
+    }
+    let (prefix, rest) = name.split_once('-')?;
+    let mut chars = prefix.chars();
+    if !chars.next()?.eq_ignore_ascii_case(&'x')
+        || !chars.all(|c| matches!(c.to_ascii_uppercase(), 'S' | 'B' | 'C'))
+    {
+        return None;
+    }
+    Some(format!("{}-{}", prefix.to_ascii_uppercase(), lookup(rest)?))
+}
+
+/// Rewrite the names of known fields to their canonical spelling.
+///
+/// Values, comments and the names of unknown fields are left untouched. Returns the number of
+/// fields that were renamed.
+pub fn normalize_field_names(doc: &mut Deb822, file_type: FileType) -> usize {
+    let mut renamed = 0;
+    for mut paragraph in doc.paragraphs() {
+        for key in paragraph.keys().collect::<Vec<_>>() {
+            match canonical_field_name(file_type, &key) {
+                Some(canonical) if canonical != key => {
+                    paragraph.rename(&key, &canonical);
+                    renamed += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_field_name() {
+        assert_eq!(
+            canonical_field_name(FileType::Control, "BUILD-DEPENDS").as_deref(),
+            Some("Build-Depends")
+        );
+        assert_eq!(
+            canonical_field_name(FileType::Control, "Vcs-Git").as_deref(),
+            Some("Vcs-Git")
+        );
+        assert_eq!(
+            canonical_field_name(FileType::Control, "xsbc-original-maintainer"),
+            None
+        );
+        assert_eq!(
+            canonical_field_name(FileType::Control, "xb-multi-arch").as_deref(),
+            Some("XB-Multi-Arch")
+        );
+        assert_eq!(canonical_field_name(FileType::Control, "x-"), None);
+        assert_eq!(
+            canonical_field_name(FileType::TestsControl, "xs-tests"),
+            None
+        );
+        assert_eq!(
+            canonical_field_name(FileType::TestsControl, "test-command").as_deref(),
+            Some("Test-Command")
+        );
+        assert_eq!(
+            canonical_field_name(FileType::Copyright, "files-excluded").as_deref(),
+            Some("Files-Excluded")
+        );
+        assert_eq!(
+            canonical_field_name(FileType::Dsc, "checksums-sha256").as_deref(),
+            Some("Checksums-Sha256")
+        );
+        assert_eq!(
+            canonical_field_name(FileType::Changes, "changed-by").as_deref(),
+            Some("Changed-By")
+        );
+    }
+
+    #[test]
+    fn test_normalize_field_names() {
+        let mut doc: Deb822 = r#"source: foo
+# Build dependencies
+build-depends: bar,
+ baz
+X-Custom: blah
+
+package: foo
+depends: bar
+depends: baz
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(normalize_field_names(&mut doc, FileType::Control), 5);
+        assert_eq!(
+            doc.to_string(),
+            r#"Source: foo
+# Build dependencies
+Build-Depends: bar,
+ baz
+X-Custom: blah
+
+Package: foo
+Depends: bar
+Depends: baz
+"#
+        );
+        assert_eq!(normalize_field_names(&mut doc, FileType::Control), 0);
+    }
+}
@@ -0,0 +1,119 @@
+//! Rich error reports with [`miette`].
+//!
+//! [`ControlError`] implements [`miette::Diagnostic`]. Errors about the value of a field are
+//! labelled with the location of the field, so attach the text of the file as the source code
+//! of the report before rendering it. The spans of a [`RelationsParseError`] refer to the value
+//! of the relations field that was parsed, rather than to the file it came from.
+//!
+//! # Example
+//!
+//! ```rust
+//! use debian_control::lossless::Control;
+//!
+//! let text = "Source: hello\n\nPackage: hello\nMulti-Arch: sideways\n";
+//! let control: Control = text.parse().unwrap();
+//! let binary = control.binaries().next().unwrap();
+//! let error = binary.multi_arch().unwrap().unwrap_err();
+//! let report = miette::Report::new(error).with_source_code(text);
+//! eprintln!("{:?}", report);
+//! ```
+use crate::error::{ControlError, RelationsParseError, SyntaxError};
+use miette::{Diagnostic, LabeledSpan};
+use std::fmt::Display;
+
+impl Diagnostic for SyntaxError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("debian_control::relations::syntax_error"))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::at(
+            self.span.clone(),
+            self.message.clone(),
+        ))))
+    }
+}
+
+impl Diagnostic for RelationsParseError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(match self {
+            RelationsParseError::Syntax(_) => "debian_control::relations::syntax",
+            RelationsParseError::NoEntry => "debian_control::relations::no_entry",
+            RelationsParseError::MultipleEntries => "debian_control::relations::multiple_entries",
+            RelationsParseError::NoRelation => "debian_control::relations::no_relation",
+            RelationsParseError::MultipleRelations => {
+                "debian_control::relations::multiple_relations"
+            }
+        }))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            RelationsParseError::Syntax(errors) => Some(Box::new(
+                errors
+                    .iter()
+                    .map(|e| LabeledSpan::at(e.span.clone(), e.message.clone())),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for ControlError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self {
+            ControlError::Io(_) => Some(Box::new("debian_control::io")),
+            ControlError::Parse(_) | ControlError::LossyParse(_) => {
+                Some(Box::new("debian_control::parse"))
+            }
+            ControlError::Paragraph(e) => e.code(),
+            ControlError::Document(_) => Some(Box::new("debian_control::document")),
+            ControlError::Relations { .. } => Some(Box::new("debian_control::relations")),
+            ControlError::Field(e) => e.code(),
+            ControlError::InvalidValue { .. } => Some(Box::new("debian_control::invalid_value")),
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        match self {
+            ControlError::Paragraph(e) => e.labels(),
+            ControlError::Field(e) => e.labels(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lossless::Control;
+
+    #[test]
+    fn test_field_error() {
+        let text = "Source: hello\n\nPackage: hello\nMulti-Arch: sideways\n";
+        let control: Control = text.parse().unwrap();
+        let binary = control.binaries().next().unwrap();
+        let error = ControlError::from(binary.multi_arch().unwrap().unwrap_err());
+        assert_eq!(error.code().unwrap().to_string(), "deb822::invalid_field");
+        let labels = error.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            &text[labels[0].offset()..][..labels[0].len()],
+            "Multi-Arch: sideways\n"
+        );
+    }
+
+    #[test]
+    fn test_relations_error() {
+        let text = "foo, $bar, baz";
+        let error = text.parse::<crate::lossless::Relations>().unwrap_err();
+        assert_eq!(
+            error.code().unwrap().to_string(),
+            "debian_control::relations::syntax"
+        );
+        let labels = error.labels().unwrap().collect::<Vec<_>>();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0].label(), Some("Substvars are not allowed"));
+        assert_eq!(&text[labels[0].offset()..][..labels[0].len()], "$");
+        assert!(RelationsParseError::NoEntry.labels().is_none());
+    }
+}
@@ -0,0 +1,221 @@
+//! Error types used throughout this crate.
+
+pub use deb822_lossless::FieldError;
+
+/// A single syntax error found while parsing a relations field.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+#[error("{message}")]
+pub struct SyntaxError {
+    /// Description of the problem.
+    pub message: String,
+
+    /// Byte range in the input that the error applies to.
+    ///
+    /// This is empty if the error was found at the end of the input.
+    pub span: std::ops::Range<usize>,
+}
+
+/// Error parsing a relations field, such as `Depends` or `Build-Depends`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum RelationsParseError {
+    /// The field contained one or more syntax errors.
+    #[error("{}", join_errors(.0))]
+    Syntax(Vec<SyntaxError>),
+
+    /// A single entry was expected, but none was found.
+    #[error("No entry found")]
+    NoEntry,
+
+    /// A single entry was expected, but multiple were found.
+    #[error("Multiple entries found")]
+    MultipleEntries,
+
+    /// A single relation was expected, but none was found.
+    #[error("No relation found")]
+    NoRelation,
+
+    /// A single relation was expected, but multiple alternatives were found.
+    #[error("Multiple relations found")]
+    MultipleRelations,
+}
+
+fn join_errors(errors: &[SyntaxError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl RelationsParseError {
+    /// The syntax errors that were encountered, if any.
+    pub fn syntax_errors(&self) -> &[SyntaxError] {
+        match self {
+            RelationsParseError::Syntax(errors) => errors,
+            _ => &[],
+        }
+    }
+}
+
+/// Error type for operations on control files and their fields.
+#[derive(Debug, thiserror::Error)]
+pub enum ControlError {
+    /// An I/O error occurred while reading or writing a file.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The file could not be parsed as deb822.
+    #[error(transparent)]
+    Parse(#[from] deb822_lossless::ParseError),
+
+    /// The file could not be parsed by the lossy deb822 parser.
+    #[error(transparent)]
+    LossyParse(#[from] deb822_lossless::lossy::Error),
+
+    /// A paragraph could not be converted into the requested type, for example because a
+    /// required field is missing.
+    #[error(transparent)]
+    Paragraph(#[from] deb822_lossless::FromParagraphError),
+
+    /// The document does not consist of the expected paragraphs, for example because a
+    /// control file has no source paragraph.
+    #[error("{0}")]
+    Document(&'static str),
+
+    /// A relations field could not be parsed.
+    #[error("invalid {field} field: {source}")]
+    Relations {
+        /// Name of the field.
+        field: String,
+
+        /// The underlying parse error.
+        #[source]
+        source: RelationsParseError,
+    },
+
+    /// The value of a field could not be parsed.
+    #[error(transparent)]
+    Field(#[from] FieldError),
+
+    /// A value could not be parsed.
+    #[error("invalid {kind}: {value:?}")]
+    InvalidValue {
+        /// What kind of value was expected.
+        kind: &'static str,
+
+        /// The value that could not be parsed.
+        value: String,
+    },
+}
+
+impl ControlError {
+    pub(crate) fn invalid_value(kind: &'static str, value: &str) -> Self {
+        ControlError::InvalidValue {
+            kind,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Attach the location of a field in its document to an error about its value.
+#[cfg(feature = "lossless")]
+fn locate(error: FieldError, paragraph: &deb822_lossless::Paragraph) -> FieldError {
+    use rowan::ast::AstNode;
+    let entry = paragraph
+        .syntax()
+        .children()
+        .filter_map(deb822_lossless::lossless::Entry::cast)
+        .find(|entry| entry.key().as_deref() == Some(error.field.as_str()));
+    match entry {
+        Some(entry) => {
+            let range = entry.syntax().text_range();
+            error.with_span(range.start().into()..range.end().into())
+        }
+        None => error,
+    }
+}
+
+/// Look up a field in a paragraph and parse its value.
+#[cfg(feature = "lossless")]
+pub(crate) fn parse_field<T: std::str::FromStr>(
+    paragraph: &deb822_lossless::Paragraph,
+    field: &str,
+) -> Option<Result<T, FieldError>>
+where
+    T::Err: std::fmt::Display,
+{
+    paragraph
+        .get_ref(field)
+        .map(|value| FieldError::parse(field, &value).map_err(|e| locate(e, paragraph)))
+}
+
+/// Look up a field in a paragraph and parse each line of its value.
+///
+/// Returns an empty list if the field is not present.
+#[cfg(feature = "lossless")]
+pub(crate) fn parse_lines<T: std::str::FromStr>(
+    paragraph: &deb822_lossless::Paragraph,
+    field: &str,
+) -> Result<Vec<T>, FieldError>
+where
+    T::Err: std::fmt::Display,
+{
+    paragraph.get_ref(field).map_or_else(
+        || Ok(Vec::new()),
+        |value| {
+            value
+                .lines()
+                .map(|line| FieldError::parse(field, line))
+                .collect::<Result<_, _>>()
+                .map_err(|e| locate(e, paragraph))
+        },
+    )
+}
+
+impl From<deb822_lossless::Error> for ControlError {
+    fn from(e: deb822_lossless::Error) -> Self {
+        match e {
+            deb822_lossless::Error::ParseError(e) => ControlError::Parse(e),
+            deb822_lossless::Error::IoError(e) => ControlError::Io(e),
+            deb822_lossless::Error::InvalidUtf8(e) => {
+                ControlError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            }
+            e @ deb822_lossless::Error::InputTooLarge => {
+                ControlError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_relations_display() {
+        let e = RelationsParseError::Syntax(vec![
+            SyntaxError {
+                message: "first".to_string(),
+                span: 0..1,
+            },
+            SyntaxError {
+                message: "second".to_string(),
+                span: 3..3,
+            },
+        ]);
+        assert_eq!(e.to_string(), "first\nsecond");
+        assert_eq!(e.syntax_errors().len(), 2);
+        assert!(RelationsParseError::NoEntry.syntax_errors().is_empty());
+    }
+
+    #[test]
+    fn test_source_chaining() {
+        let e = ControlError::Relations {
+            field: "Depends".to_string(),
+            source: RelationsParseError::NoEntry,
+        };
+        assert_eq!(e.to_string(), "invalid Depends field: No entry found");
+        assert_eq!(e.source().unwrap().to_string(), "No entry found");
+    }
+}
@@ -2,6 +2,7 @@
 use std::str::FromStr;
 
 /// Priority of a package
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum Priority {
     /// Required
@@ -33,7 +34,7 @@ impl std::fmt::Display for Priority {
 }
 
 impl std::str::FromStr for Priority {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -42,7 +43,7 @@ impl std::str::FromStr for Priority {
             "standard" => Ok(Priority::Standard),
             "optional" => Ok(Priority::Optional),
             "extra" => Ok(Priority::Extra),
-            _ => Err(format!("Invalid priority: {}", s)),
+            _ => Err(crate::ControlError::invalid_value("priority", s)),
         }
     }
 }
@@ -58,6 +59,7 @@ pub trait Checksum {
 
 /// SHA1 checksum
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sha1Checksum {
     /// SHA1 checksum
     pub sha1: String,
@@ -86,20 +88,18 @@ impl std::fmt::Display for Sha1Checksum {
 }
 
 impl std::str::FromStr for Sha1Checksum {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("SHA-1 checksum", s);
         let mut parts = s.split_whitespace();
-        let sha1 = parts.next().ok_or_else(|| "Missing sha1".to_string())?;
+        let sha1 = parts.next().ok_or_else(invalid)?;
         let size = parts
             .next()
-            .ok_or_else(|| "Missing size".to_string())?
+            .ok_or_else(invalid)?
             .parse()
-            .map_err(|e: std::num::ParseIntError| e.to_string())?;
-        let filename = parts
-            .next()
-            .ok_or_else(|| "Missing filename".to_string())?
-            .to_string();
+            .map_err(|_| invalid())?;
+        let filename = parts.next().ok_or_else(invalid)?.to_string();
         Ok(Self {
             sha1: sha1.to_string(),
             size,
@@ -110,6 +110,7 @@ impl std::str::FromStr for Sha1Checksum {
 
 /// SHA-256 checksum
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sha256Checksum {
     /// SHA-256 checksum
     pub sha256: String,
@@ -138,20 +139,18 @@ impl std::fmt::Display for Sha256Checksum {
 }
 
 impl std::str::FromStr for Sha256Checksum {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("SHA-256 checksum", s);
         let mut parts = s.split_whitespace();
-        let sha256 = parts.next().ok_or_else(|| "Missing sha256".to_string())?;
+        let sha256 = parts.next().ok_or_else(invalid)?;
         let size = parts
             .next()
-            .ok_or_else(|| "Missing size".to_string())?
+            .ok_or_else(invalid)?
             .parse()
-            .map_err(|e: std::num::ParseIntError| e.to_string())?;
-        let filename = parts
-            .next()
-            .ok_or_else(|| "Missing filename".to_string())?
-            .to_string();
+            .map_err(|_| invalid())?;
+        let filename = parts.next().ok_or_else(invalid)?.to_string();
         Ok(Self {
             sha256: sha256.to_string(),
             size,
@@ -162,6 +161,7 @@ impl std::str::FromStr for Sha256Checksum {
 
 /// SHA-512 checksum
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sha512Checksum {
     /// SHA-512 checksum
     pub sha512: String,
@@ -190,20 +190,18 @@ impl std::fmt::Display for Sha512Checksum {
 }
 
 impl std::str::FromStr for Sha512Checksum {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("SHA-512 checksum", s);
         let mut parts = s.split_whitespace();
-        let sha512 = parts.next().ok_or_else(|| "Missing sha512".to_string())?;
+        let sha512 = parts.next().ok_or_else(invalid)?;
         let size = parts
             .next()
-            .ok_or_else(|| "Missing size".to_string())?
+            .ok_or_else(invalid)?
             .parse()
-            .map_err(|e: std::num::ParseIntError| e.to_string())?;
-        let filename = parts
-            .next()
-            .ok_or_else(|| "Missing filename".to_string())?
-            .to_string();
+            .map_err(|_| invalid())?;
+        let filename = parts.next().ok_or_else(invalid)?.to_string();
         Ok(Self {
             sha512: sha512.to_string(),
             size,
@@ -214,6 +212,7 @@ impl std::str::FromStr for Sha512Checksum {
 
 /// An MD5 checksum of a file
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Md5Checksum {
     /// The MD5 checksum
     pub md5sum: String,
@@ -230,13 +229,18 @@ impl std::fmt::Display for Md5Checksum {
 }
 
 impl std::str::FromStr for Md5Checksum {
-    type Err = ();
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("MD5 checksum", s);
         let mut parts = s.split_whitespace();
-        let md5sum = parts.next().ok_or(())?;
-        let size = parts.next().ok_or(())?.parse().map_err(|_| ())?;
-        let filename = parts.next().ok_or(())?.to_string();
+        let md5sum = parts.next().ok_or_else(invalid)?;
+        let size = parts
+            .next()
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+        let filename = parts.next().ok_or_else(invalid)?.to_string();
         Ok(Self {
             md5sum: md5sum.to_string(),
             size,
@@ -255,6 +259,232 @@ impl Checksum for Md5Checksum {
     }
 }
 
+/// A checksum algorithm used in lists of files, such as `Files` or `Checksums-Sha256`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ChecksumAlgorithm {
+    /// MD5
+    Md5,
+
+    /// SHA-1
+    Sha1,
+
+    /// SHA-256
+    Sha256,
+
+    /// SHA-512
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// All algorithms, from weakest to strongest.
+    pub const ALL: [ChecksumAlgorithm; 4] = [
+        ChecksumAlgorithm::Md5,
+        ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256,
+        ChecksumAlgorithm::Sha512,
+    ];
+
+    /// The name of the field holding checksums of this type in source packages and
+    /// `.changes` files, e.g. `Checksums-Sha256`.
+    pub fn source_field(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "Files",
+            ChecksumAlgorithm::Sha1 => "Checksums-Sha1",
+            ChecksumAlgorithm::Sha256 => "Checksums-Sha256",
+            ChecksumAlgorithm::Sha512 => "Checksums-Sha512",
+        }
+    }
+
+    /// The name of the field holding checksums of this type in `Release` files, e.g. `SHA256`.
+    pub fn release_field(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "MD5Sum",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+            ChecksumAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    /// The length of a digest, in hexadecimal digits.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            ChecksumAlgorithm::Md5 => 32,
+            ChecksumAlgorithm::Sha1 => 40,
+            ChecksumAlgorithm::Sha256 => 64,
+            ChecksumAlgorithm::Sha512 => 128,
+        }
+    }
+}
+
+impl std::fmt::Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            ChecksumAlgorithm::Md5 => "MD5",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+            ChecksumAlgorithm::Sha512 => "SHA512",
+        })
+    }
+}
+
+/// An entry in a list of checksums: the digest, size and name of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct ChecksumEntry {
+    /// The digest, in hexadecimal
+    pub digest: String,
+
+    /// Size of the file, in bytes
+    pub size: usize,
+
+    /// Filename
+    pub filename: String,
+}
+
+impl Checksum for ChecksumEntry {
+    fn filename(&self) -> String {
+        self.filename.clone()
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl std::fmt::Display for ChecksumEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.digest, self.size, self.filename)
+    }
+}
+
+impl std::str::FromStr for ChecksumEntry {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("checksum", s);
+        let mut parts = s.split_whitespace();
+        let digest = parts.next().ok_or_else(invalid)?;
+        let size = parts
+            .next()
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+        let filename = parts.next().ok_or_else(invalid)?.to_string();
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            digest: digest.to_string(),
+            size,
+            filename,
+        })
+    }
+}
+
+/// A list of checksums of files, as found in the `Files` and `Checksums-*` fields of source
+/// packages and `.changes` files, and the `MD5Sum` and `SHA*` fields of `Release` files.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::{ChecksumAlgorithm, ChecksumList};
+///
+/// let value = "\n e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 1234 hello_2.10-3.dsc";
+/// let list = ChecksumList::parse(ChecksumAlgorithm::Sha256, value).unwrap();
+/// assert_eq!(list.get("hello_2.10-3.dsc").unwrap().size, 1234);
+/// assert_eq!(list.to_string(), value.replace("\n ", "\n"));
+/// assert!(ChecksumList::parse(ChecksumAlgorithm::Sha1, value).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChecksumList {
+    /// The algorithm used to compute the digests.
+    pub algorithm: ChecksumAlgorithm,
+
+    /// The entries, in order.
+    pub entries: Vec<ChecksumEntry>,
+}
+
+impl ChecksumList {
+    /// Create an empty list.
+    pub fn new(algorithm: ChecksumAlgorithm) -> Self {
+        Self {
+            algorithm,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Parse the value of a field, with one entry per line.
+    ///
+    /// Digests that don't have the length expected for `algorithm`, or that contain
+    /// characters other than hexadecimal digits, are rejected.
+    pub fn parse(algorithm: ChecksumAlgorithm, value: &str) -> Result<Self, crate::ControlError> {
+        let entries = value
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let entry: ChecksumEntry = line.parse()?;
+                if entry.digest.len() != algorithm.digest_len()
+                    || !entry.digest.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    return Err(crate::ControlError::invalid_value("checksum", line.trim()));
+                }
+                Ok(entry)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { algorithm, entries })
+    }
+
+    /// Return the entry for the given file, if any.
+    pub fn get(&self, filename: &str) -> Option<&ChecksumEntry> {
+        self.entries.iter().find(|entry| entry.filename == filename)
+    }
+
+    /// Iterate over the entries.
+    pub fn iter(&self) -> impl Iterator<Item = &ChecksumEntry> {
+        self.entries.iter()
+    }
+
+    /// Add an entry, replacing any existing entry for the same file.
+    pub fn insert(&mut self, entry: ChecksumEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.filename == entry.filename)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Formats the list as the value of a field: each entry on a line of its own, starting on
+/// the line after the field name.
+impl std::fmt::Display for ChecksumList {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for entry in &self.entries {
+            write!(f, "\n{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a ChecksumList {
+    type Item = &'a ChecksumEntry;
+    type IntoIter = std::slice::Iter<'a, ChecksumEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
 /// A package list entry
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PackageListEntry {
@@ -302,37 +532,20 @@ impl std::fmt::Display for PackageListEntry {
 }
 
 impl std::str::FromStr for PackageListEntry {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("package list entry", s);
         let mut parts = s.split_whitespace();
-        let package = parts
-            .next()
-            .ok_or_else(|| "Missing package".to_string())?
-            .to_string();
-        let package_type = parts
-            .next()
-            .ok_or_else(|| "Missing package type".to_string())?
-            .to_string();
-        let section = parts
-            .next()
-            .ok_or_else(|| "Missing section".to_string())?
-            .to_string();
-        let priority = parts
-            .next()
-            .ok_or_else(|| "Missing priority".to_string())?
-            .parse()?;
+        let package = parts.next().ok_or_else(invalid)?.to_string();
+        let package_type = parts.next().ok_or_else(invalid)?.to_string();
+        let section = parts.next().ok_or_else(invalid)?.to_string();
+        let priority = parts.next().ok_or_else(invalid)?.parse()?;
         let mut extra = std::collections::HashMap::new();
         for part in parts {
             let mut kv = part.split('=');
-            let k = kv
-                .next()
-                .ok_or_else(|| "Missing key".to_string())?
-                .to_string();
-            let v = kv
-                .next()
-                .ok_or_else(|| "Missing value".to_string())?
-                .to_string();
+            let k = kv.next().ok_or_else(invalid)?.to_string();
+            let v = kv.next().ok_or_else(invalid)?.to_string();
             extra.insert(k, v);
         }
         Ok(Self {
@@ -374,7 +587,7 @@ impl std::fmt::Display for Urgency {
 }
 
 impl FromStr for Urgency {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
@@ -383,13 +596,13 @@ impl FromStr for Urgency {
             "high" => Ok(Urgency::High),
             "emergency" => Ok(Urgency::Emergency),
             "critical" => Ok(Urgency::Critical),
-            _ => Err(format!("invalid urgency: {}", s)),
+            _ => Err(crate::ControlError::invalid_value("urgency", s)),
         }
     }
 }
 
 /// Multi-arch policy
-#[derive(PartialEq, Eq, Debug, Default)]
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, Hash)]
 pub enum MultiArch {
     /// Indicates that the package is identical across all architectures. The package can satisfy dependencies for other architectures.
     Same,
@@ -403,7 +616,7 @@ pub enum MultiArch {
 }
 
 impl std::str::FromStr for MultiArch {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -411,7 +624,7 @@ impl std::str::FromStr for MultiArch {
             "foreign" => Ok(MultiArch::Foreign),
             "no" => Ok(MultiArch::No),
             "allowed" => Ok(MultiArch::Allowed),
-            _ => Err(format!("Invalid multiarch: {}", s)),
+            _ => Err(crate::ControlError::invalid_value("multiarch", s)),
         }
     }
 }
@@ -426,3 +639,557 @@ impl std::fmt::Display for MultiArch {
         })
     }
 }
+
+/// The archive components a package can be in.
+pub const COMPONENTS: &[&str] = &["main", "contrib", "non-free", "non-free-firmware"];
+
+/// The sections of the Debian archive, as listed in the Debian Policy.
+pub const SECTIONS: &[&str] = &[
+    "admin",
+    "cli-mono",
+    "comm",
+    "database",
+    "debian-installer",
+    "debug",
+    "devel",
+    "doc",
+    "editors",
+    "education",
+    "electronics",
+    "embedded",
+    "fonts",
+    "games",
+    "gnome",
+    "gnu-r",
+    "gnustep",
+    "golang",
+    "graphics",
+    "hamradio",
+    "haskell",
+    "httpd",
+    "interpreters",
+    "introspection",
+    "java",
+    "javascript",
+    "kde",
+    "kernel",
+    "libdevel",
+    "libs",
+    "lisp",
+    "localization",
+    "mail",
+    "math",
+    "metapackages",
+    "misc",
+    "net",
+    "news",
+    "ocaml",
+    "oldlibs",
+    "otherosfs",
+    "perl",
+    "php",
+    "python",
+    "ruby",
+    "rust",
+    "science",
+    "shells",
+    "sound",
+    "tasks",
+    "tex",
+    "text",
+    "utils",
+    "vcs",
+    "video",
+    "web",
+    "x11",
+    "xfce",
+    "zope",
+];
+
+/// Section of a package, optionally prefixed by the archive component.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::{Section, SectionWarning};
+/// let section: Section = "non-free/libs".parse().unwrap();
+/// assert_eq!(section.component.as_deref(), Some("non-free"));
+/// assert_eq!(section.name, "libs");
+/// assert!(section.validate().is_empty());
+/// assert_eq!(section.to_string(), "non-free/libs");
+///
+/// let section: Section = "libz".parse().unwrap();
+/// assert_eq!(
+///     section.validate(),
+///     vec![SectionWarning::UnknownSection("libz".to_string())]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Section {
+    /// The archive component, such as `contrib`. `None` for packages in `main`.
+    pub component: Option<String>,
+
+    /// The name of the section.
+    pub name: String,
+}
+
+impl Section {
+    /// Create a new section.
+    pub fn new(component: Option<&str>, name: &str) -> Self {
+        Self {
+            component: component.map(|c| c.to_string()),
+            name: name.to_string(),
+        }
+    }
+
+    /// Whether the name of the section is one of [`SECTIONS`].
+    pub fn is_known(&self) -> bool {
+        SECTIONS.contains(&self.name.as_str())
+    }
+
+    /// Check the section against the known components and sections.
+    ///
+    /// # Returns
+    /// A list of problems; empty if the section is valid.
+    pub fn validate(&self) -> Vec<SectionWarning> {
+        let mut problems = Vec::new();
+        if let Some(component) = &self.component {
+            if !COMPONENTS.contains(&component.as_str()) {
+                problems.push(SectionWarning::UnknownComponent(component.clone()));
+            }
+        }
+        if !self.is_known() {
+            problems.push(SectionWarning::UnknownSection(self.name.clone()));
+        }
+        problems
+    }
+}
+
+/// A problem with a [`Section`], as found by [`Section::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionWarning {
+    /// The component is not one of [`COMPONENTS`].
+    UnknownComponent(String),
+
+    /// The name of the section is not one of [`SECTIONS`].
+    UnknownSection(String),
+}
+
+impl std::fmt::Display for SectionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SectionWarning::UnknownComponent(component) => {
+                write!(f, "unknown component: {}", component)
+            }
+            SectionWarning::UnknownSection(name) => write!(f, "unknown section: {}", name),
+        }
+    }
+}
+
+impl FromStr for Section {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (component, name) = match s.split_once('/') {
+            Some((component, name)) => (Some(component), name),
+            None => (None, s),
+        };
+        if component.is_some_and(str::is_empty)
+            || name.is_empty()
+            || s.contains(|c: char| c.is_whitespace())
+            || name.contains('/')
+        {
+            return Err(crate::ControlError::invalid_value("section", s));
+        }
+        Ok(Self::new(component, name))
+    }
+}
+
+impl std::fmt::Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(component) = &self.component {
+            write!(f, "{}/", component)?;
+        }
+        f.write_str(&self.name)
+    }
+}
+
+/// Type of a binary package, as found in the `Package-Type` field.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::PackageType;
+/// assert_eq!("udeb".parse::<PackageType>().unwrap(), PackageType::Udeb);
+/// assert_eq!(
+///     "ddeb".parse::<PackageType>().unwrap(),
+///     PackageType::Other("ddeb".to_string())
+/// );
+/// assert_eq!(PackageType::default().to_string(), "deb");
+/// ```
+#[derive(PartialEq, Eq, Debug, Default, Clone, Hash)]
+pub enum PackageType {
+    /// A regular binary package. This is the type of packages without a `Package-Type` field.
+    #[default]
+    Deb,
+    /// A micro binary package, used by the Debian installer.
+    Udeb,
+    /// Any other package type.
+    Other(String),
+}
+
+impl std::str::FromStr for PackageType {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deb" => Ok(PackageType::Deb),
+            "udeb" => Ok(PackageType::Udeb),
+            _ if s.is_empty() || s.contains(char::is_whitespace) => {
+                Err(crate::ControlError::invalid_value("package type", s))
+            }
+            _ => Ok(PackageType::Other(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for PackageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            PackageType::Deb => "deb",
+            PackageType::Udeb => "udeb",
+            PackageType::Other(s) => s,
+        })
+    }
+}
+
+/// A list of architectures, as found in the `Architecture` field of a package.
+///
+/// Besides concrete architectures such as `amd64`, the list may contain `all` for
+/// architecture-independent packages, `any` for all architectures, and wildcards such as
+/// `linux-any` or `any-arm`.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::Architectures;
+/// let archs: Architectures = "linux-any kfreebsd-amd64".parse().unwrap();
+/// assert!(archs.matches("amd64"));
+/// assert!(archs.matches("musl-linux-arm64"));
+/// assert!(archs.matches("kfreebsd-amd64"));
+/// assert!(!archs.matches("hurd-i386"));
+/// assert!(!archs.matches("all"));
+/// assert_eq!(archs.to_string(), "linux-any kfreebsd-amd64");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Architectures(Vec<String>);
+
+impl Architectures {
+    /// Iterate over the architectures and wildcards in the list.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Check whether a package with this list is built on the given architecture.
+    ///
+    /// `arch` is a concrete architecture, such as `amd64` or `hurd-i386`. Wildcards match
+    /// architectures by their ABI, operating system and CPU, so `any-arm` matches both
+    /// `armel` and `armhf`. `all` only matches `all`, and is not matched by `any`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::Architectures;
+    /// let archs: Architectures = "any-arm any-i386 all".parse().unwrap();
+    /// assert!(archs.matches("armhf"));
+    /// assert!(archs.matches("hurd-i386"));
+    /// assert!(archs.matches("all"));
+    /// assert!(!archs.matches("arm64"));
+    /// ```
+    pub fn matches(&self, arch: &str) -> bool {
+        self.iter().any(|pattern| arch_matches(pattern, arch))
+    }
+}
+
+/// Split a concrete architecture into its ABI, operating system and CPU.
+fn arch_tuple(arch: &str) -> (&str, &str, &str) {
+    match arch.split('-').collect::<Vec<_>>().as_slice() {
+        [abi, os, cpu] => (abi, os, cpu),
+        [os, cpu] => ("gnu", os, cpu),
+        ["armel" | "armhf"] => ("gnu", "linux", "arm"),
+        ["x32"] => ("gnu", "linux", "amd64"),
+        ["arm64ilp32"] => ("gnu", "linux", "arm64"),
+        _ => ("gnu", "linux", arch),
+    }
+}
+
+/// Check whether `pattern`, an architecture or wildcard, matches the concrete `arch`.
+fn arch_matches(pattern: &str, arch: &str) -> bool {
+    if pattern == arch {
+        return true;
+    }
+    if pattern == "all" || arch == "all" || !pattern.split('-').any(|p| p == "any") {
+        return false;
+    }
+    let (abi, os, cpu) = match pattern.split('-').collect::<Vec<_>>().as_slice() {
+        [abi, os, cpu] => (*abi, *os, *cpu),
+        [os, cpu] => ("any", *os, *cpu),
+        _ => ("any", "any", "any"),
+    };
+    let actual = arch_tuple(arch);
+    [(abi, actual.0), (os, actual.1), (cpu, actual.2)]
+        .iter()
+        .all(|(wanted, actual)| *wanted == "any" || wanted == actual)
+}
+
+impl FromStr for Architectures {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let archs = s
+            .split_whitespace()
+            .map(|arch| {
+                if arch
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                {
+                    Ok(arch.to_string())
+                } else {
+                    Err(crate::ControlError::invalid_value("architecture", arch))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if archs.is_empty() {
+            return Err(crate::ControlError::invalid_value("architecture list", s));
+        }
+        Ok(Architectures(archs))
+    }
+}
+
+impl std::fmt::Display for Architectures {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0.join(" "))
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Architectures {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Architectures(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// The files generated from `debian/control` that a user-defined field is copied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FieldTargets {
+    /// The source package control file (`.dsc`), selected by `S`.
+    pub source: bool,
+
+    /// The control files of the binary packages, selected by `B`.
+    pub binary: bool,
+
+    /// The upload control file (`.changes`), selected by `C`.
+    pub changes: bool,
+}
+
+/// A user-defined field in `debian/control`, such as `XS-Go-Import-Path`.
+///
+/// The letters between the `X` and the hyphen select the files that `dpkg` copies the field
+/// to, with the prefix removed. Fields with a bare `X-` prefix are not copied anywhere.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::{FieldTargets, UserField};
+/// let field: UserField = "XBS-Go-Import-Path".parse().unwrap();
+/// assert_eq!(field.name, "Go-Import-Path");
+/// assert_eq!(
+///     field.targets,
+///     FieldTargets { source: true, binary: true, changes: false }
+/// );
+/// assert_eq!(field.to_string(), "XSB-Go-Import-Path");
+/// assert!("Go-Import-Path".parse::<UserField>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UserField {
+    /// The name of the field, without the prefix.
+    pub name: String,
+
+    /// The files the field is copied to.
+    pub targets: FieldTargets,
+}
+
+impl UserField {
+    /// Create a new user-defined field.
+    pub fn new(name: &str, targets: FieldTargets) -> Self {
+        Self {
+            name: name.to_string(),
+            targets,
+        }
+    }
+}
+
+impl FromStr for UserField {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("user-defined field", s);
+        let (prefix, name) = s.split_once('-').ok_or_else(invalid)?;
+        let letters = prefix
+            .strip_prefix(['X', 'x'])
+            .filter(|_| !name.is_empty())
+            .ok_or_else(invalid)?;
+        let mut targets = FieldTargets::default();
+        for c in letters.chars() {
+            match c.to_ascii_uppercase() {
+                'S' => targets.source = true,
+                'B' => targets.binary = true,
+                'C' => targets.changes = true,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(Self::new(name, targets))
+    }
+}
+
+impl std::fmt::Display for UserField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("X")?;
+        for (enabled, letter) in [
+            (self.targets.source, "S"),
+            (self.targets.binary, "B"),
+            (self.targets.changes, "C"),
+        ] {
+            if enabled {
+                f.write_str(letter)?;
+            }
+        }
+        write!(f, "-{}", self.name)
+    }
+}
+
+/// A test suite declared in the `Testsuite` field of a source package.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::Testsuite;
+/// let testsuite: Testsuite = "autopkgtest-pkg-go".parse().unwrap();
+/// assert_eq!(testsuite, Testsuite::AutopkgtestPkg("go".to_string()));
+/// assert!(!testsuite.needs_tests_control());
+/// assert!(Testsuite::Autopkgtest.needs_tests_control());
+/// assert_eq!(testsuite.to_string(), "autopkgtest-pkg-go");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Testsuite {
+    /// Tests declared in `debian/tests/control`, run by autopkgtest.
+    Autopkgtest,
+
+    /// Tests generated by autodep8 for the given kind of package, such as `go` or `python`.
+    AutopkgtestPkg(String),
+
+    /// Any other test suite.
+    Other(String),
+}
+
+impl Testsuite {
+    /// Whether the test suite is declared in `debian/tests/control`.
+    ///
+    /// Test suites generated by autodep8 do not need that file.
+    pub fn needs_tests_control(&self) -> bool {
+        matches!(self, Testsuite::Autopkgtest)
+    }
+}
+
+impl FromStr for Testsuite {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.contains(|c: char| c.is_whitespace() || c == ',') {
+            return Err(crate::ControlError::invalid_value("testsuite", s));
+        }
+        Ok(match s.strip_prefix("autopkgtest-pkg-") {
+            Some(kind) if !kind.is_empty() => Testsuite::AutopkgtestPkg(kind.to_string()),
+            _ if s == "autopkgtest" => Testsuite::Autopkgtest,
+            _ => Testsuite::Other(s.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for Testsuite {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Testsuite::Autopkgtest => f.write_str("autopkgtest"),
+            Testsuite::AutopkgtestPkg(kind) => write!(f, "autopkgtest-pkg-{}", kind),
+            Testsuite::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Fields that contain comma-separated package relations.
+pub const RELATION_FIELDS: &[&str] = &[
+    "Pre-Depends",
+    "Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Provides",
+    "Replaces",
+    "Build-Depends",
+    "Build-Depends-Arch",
+    "Build-Depends-Indep",
+    "Build-Conflicts",
+    "Build-Conflicts-Arch",
+    "Build-Conflicts-Indep",
+    "Built-Using",
+    "Static-Built-Using",
+];
+
+/// Fields whose value consists of several lines.
+const MULTILINE_FIELDS: &[&str] = &[
+    "Description",
+    "Changes",
+    "Files",
+    "Checksums-Md5",
+    "Checksums-Sha1",
+    "Checksums-Sha256",
+    "Checksums-Sha512",
+    "Package-List",
+    "Conffiles",
+    "Copyright",
+    "License",
+    "Comment",
+    "Disclaimer",
+];
+
+/// Fields other than [`RELATION_FIELDS`] whose value is a single logical line that may be
+/// folded over several lines.
+const FOLDED_FIELDS: &[&str] = &[
+    "Uploaders",
+    "Binary",
+    "Testsuite-Triggers",
+    "Tests",
+    "Restrictions",
+    "Features",
+    "Files-Excluded",
+];
+
+/// Return the type of a field, which determines how its value is folded.
+///
+/// Field names are compared case-insensitively; unknown fields are simple fields.
+///
+/// # Example
+/// ```rust
+/// use debian_control::fields::field_kind;
+/// use deb822_lossless::folding::FieldKind;
+///
+/// assert_eq!(field_kind("Description"), FieldKind::Multiline);
+/// assert_eq!(field_kind("uploaders"), FieldKind::Folded);
+/// assert_eq!(field_kind("Version"), FieldKind::Simple);
+/// ```
+pub fn field_kind(name: &str) -> deb822_lossless::folding::FieldKind {
+    use deb822_lossless::folding::FieldKind;
+    let matches = |fields: &[&str]| fields.iter().any(|f| f.eq_ignore_ascii_case(name));
+    if matches(MULTILINE_FIELDS) {
+        FieldKind::Multiline
+    } else if matches(RELATION_FIELDS) || matches(FOLDED_FIELDS) {
+        FieldKind::Folded
+    } else {
+        FieldKind::Simple
+    }
+}
@@ -0,0 +1,950 @@
+//! Formatting of Debian packaging files in the style of `wrap-and-sort`.
+//!
+//! The functions in this module sort and wrap the fields that contain lists, such as the
+//! relation fields in `debian/control`. Only those fields are reformatted; comments and the
+//! formatting of other fields are preserved. Optionally, binary package paragraphs and the
+//! fields in `debian/control` can be put in their conventional order as well.
+//!
+//! The `check_*` functions report where a file deviates from the style, without modifying it.
+//!
+//! # Example
+//! ```rust
+//! use debian_control::format::{check, format, FormatOptions, IssueKind};
+//!
+//! let mut doc: deb822_lossless::Deb822 =
+//!     "Source: foo\nBuild-Depends: libfoo-dev, debhelper-compat (= 13)\n".parse().unwrap();
+//! let options = FormatOptions::default();
+//!
+//! let issues = check(&doc, &options);
+//! assert_eq!(issues.len(), 1);
+//! assert_eq!(issues[0].kind, IssueKind::Unsorted);
+//! assert_eq!(issues[0].field.as_deref(), Some("Build-Depends"));
+//! assert_eq!(issues[0].span, 12..63);
+//!
+//! format(&mut doc, &options);
+//! assert_eq!(doc.to_string(), "Source: foo\nBuild-Depends: debhelper-compat (= 13), libfoo-dev\n");
+//! assert!(check(&doc, &options).is_empty());
+//! ```
+use crate::fields::RELATION_FIELDS;
+use crate::lossless::relations::Entry;
+use crate::ordering::{Profile, Reorder};
+use deb822_lossless::{Deb822, Indentation, Paragraph};
+use rowan::ast::AstNode;
+
+/// Fields in `debian/tests/control` that contain comma or whitespace separated lists.
+const TESTS_LIST_FIELDS: &[&str] = &["Restrictions", "Features", "Tests"];
+
+/// Options controlling how files are formatted.
+///
+/// The defaults match those of `wrap-and-sort` without any arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Wrap lists even if they fit on a single line.
+    pub wrap_always: bool,
+
+    /// Indent wrapped lines by a single space, rather than aligning them with the field value.
+    pub short_indent: bool,
+
+    /// Add a trailing comma to wrapped lists.
+    pub trailing_comma: bool,
+
+    /// Maximum length of a line before a list is wrapped.
+    pub max_line_length: usize,
+
+    /// Sort the items of lists, removing duplicates.
+    pub sort_lists: bool,
+
+    /// Sort binary package paragraphs by package name.
+    pub sort_binary_packages: bool,
+
+    /// When sorting binary packages, keep the first one in place.
+    pub keep_first: bool,
+
+    /// Put the fields of `debian/control` in their conventional order.
+    pub sort_fields: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            wrap_always: false,
+            short_indent: false,
+            trailing_comma: false,
+            max_line_length: 79,
+            sort_lists: true,
+            sort_binary_packages: false,
+            keep_first: false,
+            sort_fields: false,
+        }
+    }
+}
+
+/// Names of the built-in presets accepted by [`FormatOptions::preset`].
+///
+/// `wrap-and-sort` can be followed by any of its short options, e.g. `wrap-and-sort -ast`.
+pub const PRESETS: &[&str] = &["wrap-and-sort", "black-style", "dpkg-default"];
+
+/// Error loading formatting options.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The configuration file is not a single deb822 paragraph.
+    #[error("invalid configuration: {0}")]
+    Syntax(String),
+
+    /// The preset is not known.
+    #[error("unknown preset: {0}")]
+    UnknownPreset(String),
+
+    /// The configuration contains an unknown field.
+    #[error("unknown field: {0}")]
+    UnknownField(String),
+
+    /// A field has an invalid value.
+    #[error("invalid value for {field}: {value}")]
+    InvalidValue {
+        /// Name of the field.
+        field: String,
+
+        /// The invalid value.
+        value: String,
+    },
+}
+
+fn parse_bool(field: &str, value: &str) -> Result<bool, ConfigError> {
+    match value {
+        "yes" | "true" => Ok(true),
+        "no" | "false" => Ok(false),
+        _ => Err(ConfigError::InvalidValue {
+            field: field.to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+impl FormatOptions {
+    /// Look up a built-in preset by name.
+    ///
+    /// The available presets are:
+    ///  * `wrap-and-sort`, optionally followed by short options as accepted by `wrap-and-sort`
+    ///    (`-a`, `-s`, `-t`, `-b`, `-k`), e.g. `wrap-and-sort -ast`
+    ///  * `black-style`: one item per line, with a trailing comma
+    ///  * `dpkg-default`: lists on a single line, in their original order, as written by `dpkg`
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::format::FormatOptions;
+    ///
+    /// let options = FormatOptions::preset("wrap-and-sort -ast").unwrap();
+    /// assert!(options.wrap_always && options.short_indent && options.trailing_comma);
+    /// assert!(FormatOptions::preset("wrap-and-sort -x").is_none());
+    /// ```
+    pub fn preset(name: &str) -> Option<Self> {
+        let mut words = name.split_whitespace();
+        match words.next()? {
+            "wrap-and-sort" => {
+                let mut options = Self::default();
+                for arg in words {
+                    let flags = arg.strip_prefix('-').filter(|f| !f.is_empty())?;
+                    for flag in flags.chars() {
+                        match flag {
+                            'a' => options.wrap_always = true,
+                            's' => options.short_indent = true,
+                            't' => options.trailing_comma = true,
+                            'b' => options.sort_binary_packages = true,
+                            'k' => options.keep_first = true,
+                            _ => return None,
+                        }
+                    }
+                }
+                Some(options)
+            }
+            "black-style" if words.next().is_none() => Some(Self {
+                wrap_always: true,
+                short_indent: true,
+                trailing_comma: true,
+                sort_binary_packages: true,
+                keep_first: true,
+                ..Default::default()
+            }),
+            "dpkg-default" if words.next().is_none() => Some(Self {
+                max_line_length: usize::MAX,
+                sort_lists: false,
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// Parse formatting options from a configuration file.
+    ///
+    /// The configuration is a single deb822 paragraph. The `Profile` field selects a preset to
+    /// start from (see [`FormatOptions::preset`]); the other fields override individual options:
+    /// `Wrap-Always`, `Short-Indent`, `Trailing-Comma`, `Sort-Lists`, `Sort-Binary-Packages` and
+    /// `Keep-First` and `Sort-Fields` take `yes` or `no`, and `Max-Line-Length` takes a number.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::format::FormatOptions;
+    ///
+    /// let options = FormatOptions::from_config("Profile: wrap-and-sort -ast\nMax-Line-Length: 100\n").unwrap();
+    /// assert!(options.wrap_always);
+    /// assert_eq!(options.max_line_length, 100);
+    /// ```
+    pub fn from_config(text: &str) -> Result<Self, ConfigError> {
+        let paragraph: deb822_lossless::lossy::Paragraph = text
+            .parse()
+            .map_err(|e: deb822_lossless::lossy::Error| ConfigError::Syntax(e.to_string()))?;
+        let mut options = match paragraph.get("Profile") {
+            Some(profile) => Self::preset(profile)
+                .ok_or_else(|| ConfigError::UnknownPreset(profile.to_string()))?,
+            None => Self::default(),
+        };
+        for (field, value) in paragraph.iter() {
+            match field {
+                "Profile" => {}
+                "Wrap-Always" => options.wrap_always = parse_bool(field, value)?,
+                "Short-Indent" => options.short_indent = parse_bool(field, value)?,
+                "Trailing-Comma" => options.trailing_comma = parse_bool(field, value)?,
+                "Sort-Lists" => options.sort_lists = parse_bool(field, value)?,
+                "Sort-Binary-Packages" => options.sort_binary_packages = parse_bool(field, value)?,
+                "Keep-First" => options.keep_first = parse_bool(field, value)?,
+                "Sort-Fields" => options.sort_fields = parse_bool(field, value)?,
+                "Max-Line-Length" => {
+                    options.max_line_length =
+                        value.parse().map_err(|_| ConfigError::InvalidValue {
+                            field: field.to_string(),
+                            value: value.to_string(),
+                        })?
+                }
+                _ => return Err(ConfigError::UnknownField(field.to_string())),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Read formatting options from a configuration file.
+    ///
+    /// See [`FormatOptions::from_config`] for the format of the file.
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, ConfigError> {
+        Self::from_config(&std::fs::read_to_string(path)?)
+    }
+
+    /// Load formatting options from a preset name or, failing that, a configuration file.
+    ///
+    /// This is meant for command-line options that accept either.
+    pub fn load(spec: &str) -> Result<Self, ConfigError> {
+        match Self::preset(spec) {
+            Some(options) => Ok(options),
+            None if std::path::Path::new(spec).exists() => Self::from_file(spec),
+            None => Err(ConfigError::UnknownPreset(spec.to_string())),
+        }
+    }
+}
+
+impl FormatOptions {
+    fn indentation(&self, key: &str) -> Indentation {
+        if self.short_indent {
+            Indentation::Spaces(1)
+        } else {
+            Indentation::Spaces(key.len() as u32 + 2)
+        }
+    }
+
+    /// Join the items of a list, wrapping it to one item per line if necessary.
+    fn wrap(&self, key: &str, items: &[String], separator: &str) -> String {
+        let one_line = items.join(separator);
+        if !self.wrap_always && key.len() + 2 + one_line.len() <= self.max_line_length {
+            return one_line;
+        }
+        let separator = separator.trim_end();
+        let mut wrapped = items.join(&format!("{}\n", separator));
+        if self.trailing_comma && separator == "," {
+            wrapped.push(',');
+        }
+        wrapped
+    }
+}
+
+/// The kind of deviation from the formatting style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    /// Items in a list contain irregular whitespace.
+    Whitespace,
+
+    /// Items in a list are not sorted, or contain duplicates.
+    Unsorted,
+
+    /// A list is not wrapped or indented as expected.
+    Wrapping,
+
+    /// Paragraphs are not in the expected order.
+    ParagraphOrder,
+
+    /// Fields in a paragraph are not in the expected order.
+    FieldOrder,
+}
+
+/// A place where a file deviates from the formatting style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattingIssue {
+    /// What kind of deviation this is.
+    pub kind: IssueKind,
+
+    /// Name of the field the issue applies to, if it applies to a single field.
+    pub field: Option<String>,
+
+    /// Byte range in the document that the issue applies to.
+    pub span: std::ops::Range<usize>,
+
+    /// Description of the issue.
+    pub message: String,
+}
+
+impl std::fmt::Display for FormattingIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// How the items of a list field are split, normalized and sorted.
+struct ListStyle {
+    split: fn(&str) -> Vec<String>,
+    normalize: fn(&str) -> String,
+    sort: fn(&mut Vec<String>),
+    separator: &'static str,
+}
+
+impl ListStyle {
+    fn items(&self, options: &FormatOptions, value: &str) -> Vec<String> {
+        let mut items = (self.split)(value)
+            .iter()
+            .map(|item| (self.normalize)(item))
+            .collect::<Vec<_>>();
+        if options.sort_lists {
+            (self.sort)(&mut items);
+        }
+        items
+    }
+
+    fn format(&self, options: &FormatOptions, key: &str, value: &str) -> String {
+        options.wrap(key, &self.items(options, value), self.separator)
+    }
+}
+
+const RELATIONS: ListStyle = ListStyle {
+    split: split_commas,
+    normalize: normalize_relation,
+    sort: sort_list,
+    separator: ", ",
+};
+
+const COMMA_LIST: ListStyle = ListStyle {
+    split: split_commas_and_whitespace,
+    normalize: str::to_string,
+    sort: sort_list,
+    separator: ", ",
+};
+
+const GLOBS: ListStyle = ListStyle {
+    split: split_whitespace,
+    normalize: str::to_string,
+    sort: sort_globs,
+    separator: " ",
+};
+
+/// Sort a list, removing duplicates.
+///
+/// Like in `wrap-and-sort`, items that don't start with a lowercase letter or digit (such as
+/// substitution variables) are sorted after all others.
+fn sort_list(items: &mut Vec<String>) {
+    items.sort_by_key(|item| {
+        let special = !item.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit());
+        (special, item.clone())
+    });
+    items.dedup();
+}
+
+/// Sort the globs in a `Files` field of a copyright file, removing duplicates.
+///
+/// The catch-all `*` pattern is kept first.
+fn sort_globs(items: &mut Vec<String>) {
+    items.sort_by_key(|item| (item != "*", item.clone()));
+    items.dedup();
+}
+
+/// Split a list on commas, dropping empty items.
+fn split_commas(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn split_commas_and_whitespace(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+fn split_whitespace(value: &str) -> Vec<String> {
+    value
+        .split_whitespace()
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Normalize the whitespace in an entry of a relations field.
+///
+/// Entries that can not be parsed, such as substitution variables, only have their whitespace
+/// collapsed.
+fn normalize_relation(item: &str) -> String {
+    let item = item.split_whitespace().collect::<Vec<_>>().join(" ");
+    match item.parse::<Entry>() {
+        Ok(entry) => entry
+            .relations()
+            .map(|r| r.wrap_and_sort().to_string())
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Err(_) => item,
+    }
+}
+
+/// Format the value of a relations field, such as `Depends`, according to `options`.
+///
+/// `key` is the name of the field; it determines the indentation and where lines are wrapped.
+///
+/// # Example
+/// ```rust
+/// use debian_control::format::{format_relations, FormatOptions};
+///
+/// let options = FormatOptions::preset("wrap-and-sort -ast").unwrap();
+/// assert_eq!(
+///     format_relations("Depends", "libc6, foo (>=1.0)", &options),
+///     "foo (>= 1.0),\nlibc6,"
+/// );
+/// ```
+pub fn format_relations(key: &str, value: &str, options: &FormatOptions) -> String {
+    RELATIONS.format(options, key, value)
+}
+
+/// The fields reformatted in each kind of file, and how.
+type Rules = &'static [(&'static [&'static str], &'static ListStyle)];
+
+const CONTROL_RULES: Rules = &[(RELATION_FIELDS, &RELATIONS)];
+
+const TESTS_CONTROL_RULES: Rules = &[(&["Depends"], &RELATIONS), (TESTS_LIST_FIELDS, &COMMA_LIST)];
+
+const COPYRIGHT_RULES: Rules = &[(&["Files"], &GLOBS)];
+
+fn format_fields(doc: &Deb822, rules: Rules, options: &FormatOptions) {
+    for mut paragraph in doc.paragraphs() {
+        for (keys, style) in rules {
+            for key in keys.iter() {
+                let format_value = |key: &str, value: &str| style.format(options, key, value);
+                paragraph.wrap_and_sort_field(
+                    key,
+                    options.indentation(key),
+                    false,
+                    None,
+                    Some(&format_value),
+                );
+            }
+        }
+    }
+}
+
+fn check_fields(doc: &Deb822, rules: Rules, options: &FormatOptions) -> Vec<FormattingIssue> {
+    let mut issues = vec![];
+    for paragraph in doc.paragraphs() {
+        for entry in paragraph
+            .syntax()
+            .children()
+            .filter_map(deb822_lossless::lossless::Entry::cast)
+        {
+            let Some(key) = entry.key() else {
+                continue;
+            };
+            let Some((_, style)) = rules.iter().find(|(keys, _)| keys.contains(&key.as_str()))
+            else {
+                continue;
+            };
+            let format_value = |key: &str, value: &str| style.format(options, key, value);
+            let formatted =
+                entry.wrap_and_sort(options.indentation(&key), false, None, Some(&format_value));
+            if formatted.to_string() == entry.to_string() {
+                continue;
+            }
+            let value = entry.value();
+            let items = (style.split)(&value);
+            let normalized = items
+                .iter()
+                .map(|item| (style.normalize)(item))
+                .collect::<Vec<_>>();
+            let mut sorted = normalized.clone();
+            if options.sort_lists {
+                (style.sort)(&mut sorted);
+            }
+            let (kind, message) = if items != normalized {
+                (IssueKind::Whitespace, "contains irregular whitespace")
+            } else if normalized != sorted {
+                (IssueKind::Unsorted, "is not sorted")
+            } else {
+                (IssueKind::Wrapping, "is not wrapped as expected")
+            };
+            let range = entry.syntax().text_range();
+            issues.push(FormattingIssue {
+                kind,
+                message: format!("{} field {}", key, message),
+                field: Some(key),
+                span: range.start().into()..range.end().into(),
+            });
+        }
+    }
+    issues
+}
+
+/// The key by which binary package paragraphs are sorted; the source paragraph, and optionally
+/// the first binary package, are kept at the start.
+fn binary_sort_key(
+    doc: &Deb822,
+    options: &FormatOptions,
+) -> impl Fn(&Paragraph) -> (bool, Option<String>) {
+    let first = doc
+        .paragraphs()
+        .find(|p| p.contains_key("Package"))
+        .and_then(|p| p.get("Package"));
+    let keep_first = options.keep_first;
+    move |p: &Paragraph| {
+        let name = p.get("Package");
+        let pinned = p.contains_key("Source") || (keep_first && name == first);
+        (!pinned, name)
+    }
+}
+
+/// The conventional field order for a paragraph of `debian/control`.
+fn field_order(paragraph: &Paragraph) -> Profile {
+    if paragraph.contains_key("Source") {
+        Profile::ControlSource
+    } else {
+        Profile::ControlBinary
+    }
+}
+
+/// Format a `debian/control` file.
+pub fn format(doc: &mut Deb822, options: &FormatOptions) {
+    if options.sort_binary_packages {
+        let sort_key = binary_sort_key(doc, options);
+        doc.sort_paragraphs_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    }
+    if options.sort_fields {
+        for mut paragraph in doc.paragraphs() {
+            paragraph.reorder(field_order(&paragraph));
+        }
+    }
+    format_fields(doc, CONTROL_RULES, options);
+}
+
+/// Report where a `debian/control` file deviates from the formatting style.
+///
+/// The document is left unchanged; formatting it with [`format`] fixes all reported issues.
+pub fn check(doc: &Deb822, options: &FormatOptions) -> Vec<FormattingIssue> {
+    let mut issues = vec![];
+    if options.sort_binary_packages {
+        let sort_key = binary_sort_key(doc, options);
+        let paragraphs = doc.paragraphs().collect::<Vec<_>>();
+        if let Some(p) = paragraphs
+            .windows(2)
+            .find(|w| sort_key(&w[0]) > sort_key(&w[1]))
+            .map(|w| &w[1])
+        {
+            let range = p.syntax().text_range();
+            issues.push(FormattingIssue {
+                kind: IssueKind::ParagraphOrder,
+                field: None,
+                span: range.start().into()..range.end().into(),
+                message: match p.get("Package") {
+                    Some(name) => format!("binary package {} is not sorted", name),
+                    None => "paragraph is not sorted".to_string(),
+                },
+            });
+        }
+    }
+    if options.sort_fields {
+        for paragraph in doc.paragraphs() {
+            let order = field_order(&paragraph);
+            let entries = paragraph
+                .syntax()
+                .children()
+                .filter_map(deb822_lossless::lossless::Entry::cast)
+                .collect::<Vec<_>>();
+            if let Some(entry) = entries
+                .windows(2)
+                .find(|w| {
+                    let position = |e: &deb822_lossless::lossless::Entry| {
+                        e.key().map_or(usize::MAX, |key| order.position(&key))
+                    };
+                    position(&w[0]) > position(&w[1])
+                })
+                .map(|w| &w[1])
+            {
+                let key = entry.key();
+                let range = entry.syntax().text_range();
+                issues.push(FormattingIssue {
+                    kind: IssueKind::FieldOrder,
+                    message: format!(
+                        "{} field is not in the expected order",
+                        key.as_deref().unwrap_or("")
+                    ),
+                    field: key,
+                    span: range.start().into()..range.end().into(),
+                });
+            }
+        }
+    }
+    issues.extend(check_fields(doc, CONTROL_RULES, options));
+    issues
+}
+
+/// Format a `debian/tests/control` file.
+pub fn format_tests_control(doc: &mut Deb822, options: &FormatOptions) {
+    format_fields(doc, TESTS_CONTROL_RULES, options);
+}
+
+/// Report where a `debian/tests/control` file deviates from the formatting style.
+pub fn check_tests_control(doc: &Deb822, options: &FormatOptions) -> Vec<FormattingIssue> {
+    check_fields(doc, TESTS_CONTROL_RULES, options)
+}
+
+/// Only machine-readable copyright files have a structure that can be sorted.
+fn is_machine_readable(doc: &Deb822) -> bool {
+    doc.paragraphs()
+        .next()
+        .is_some_and(|p| p.contains_key("Format"))
+}
+
+/// Format a `debian/copyright` file.
+///
+/// Returns `false`, without changing anything, if the file is not machine-readable.
+pub fn format_copyright(doc: &mut Deb822, options: &FormatOptions) -> bool {
+    if !is_machine_readable(doc) {
+        return false;
+    }
+    format_fields(doc, COPYRIGHT_RULES, options);
+    true
+}
+
+/// Report where a `debian/copyright` file deviates from the formatting style.
+///
+/// Copyright files that are not machine-readable are not checked.
+pub fn check_copyright(doc: &Deb822, options: &FormatOptions) -> Vec<FormattingIssue> {
+    if !is_machine_readable(doc) {
+        return vec![];
+    }
+    check_fields(doc, COPYRIGHT_RULES, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatted(text: &str, options: &FormatOptions) -> String {
+        let mut doc: Deb822 = text.parse().unwrap();
+        format(&mut doc, options);
+        doc.to_string()
+    }
+
+    #[test]
+    fn test_control() {
+        let text = r#"Source: foo
+# Build dependencies
+Build-Depends: libfoo-dev, debhelper-compat (=   13),
+  bar
+Description: not
+  a real  field
+
+Package: foo-utils
+Depends: ${misc:Depends}, libc6, foo (>=1.0) | bar, libc6
+Description: Utilities
+ Some text
+   with verbatim lines
+
+Package: foo
+Architecture: any
+"#;
+        assert_eq!(
+            formatted(text, &FormatOptions::default()),
+            r#"Source: foo
+# Build dependencies
+Build-Depends: bar, debhelper-compat (= 13), libfoo-dev
+Description: not
+  a real  field
+
+Package: foo-utils
+Depends: foo (>= 1.0) | bar, libc6, ${misc:Depends}
+Description: Utilities
+ Some text
+   with verbatim lines
+
+Package: foo
+Architecture: any
+"#
+        );
+        let options = FormatOptions {
+            sort_binary_packages: true,
+            ..Default::default()
+        };
+        let sorted = formatted(text, &options);
+        assert!(sorted.contains("Package: foo\nArchitecture: any\n\nPackage: foo-utils\n"));
+        let options = FormatOptions {
+            sort_binary_packages: true,
+            keep_first: true,
+            ..Default::default()
+        };
+        let kept = formatted(text, &options);
+        assert!(kept.ends_with("Package: foo\nArchitecture: any\n"));
+    }
+
+    #[test]
+    fn test_sort_binary_packages_preserves_layout() {
+        let text = "Source: foo\n\n\n# The utilities\nPackage: foo-utils\n\nPackage: foo\n";
+        let options = FormatOptions {
+            sort_binary_packages: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            formatted(text, &options),
+            "Source: foo\n\n\nPackage: foo\n\n# The utilities\nPackage: foo-utils\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_fields() {
+        let text = r#"Build-Depends: debhelper-compat (= 13)
+Maintainer: Jane <jane@example.com>
+X-Custom: yes
+Source: foo
+# Upstream
+Homepage: https://example.com/
+
+Description: Foo
+ Long description
+Depends: libc6
+XB-Custom: yes
+Package: foo
+"#;
+        let options = FormatOptions {
+            sort_fields: true,
+            ..Default::default()
+        };
+        let doc: Deb822 = text.parse().unwrap();
+        let issues = check(&doc, &options);
+        assert_eq!(
+            issues
+                .iter()
+                .map(|i| (i.kind, i.field.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                (IssueKind::FieldOrder, Some("Maintainer")),
+                (IssueKind::FieldOrder, Some("Depends")),
+            ]
+        );
+        assert_eq!(
+            formatted(text, &options),
+            r#"Source: foo
+Maintainer: Jane <jane@example.com>
+Build-Depends: debhelper-compat (= 13)
+# Upstream
+Homepage: https://example.com/
+X-Custom: yes
+
+Package: foo
+Depends: libc6
+XB-Custom: yes
+Description: Foo
+ Long description
+"#
+        );
+        assert_eq!(
+            FormatOptions::from_config("Sort-Fields: yes\n").unwrap(),
+            options
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let text = "Package: foo\nDepends: aaa, bbb\n";
+        let options = FormatOptions {
+            wrap_always: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            formatted(text, &options),
+            "Package: foo\nDepends: aaa,\n         bbb\n"
+        );
+        let options = FormatOptions {
+            wrap_always: true,
+            short_indent: true,
+            trailing_comma: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            formatted(text, &options),
+            "Package: foo\nDepends: aaa,\n bbb,\n"
+        );
+        let options = FormatOptions {
+            max_line_length: 15,
+            ..Default::default()
+        };
+        assert_eq!(
+            formatted(text, &options),
+            "Package: foo\nDepends: aaa,\n         bbb\n"
+        );
+    }
+
+    #[test]
+    fn test_presets() {
+        for name in PRESETS {
+            assert!(FormatOptions::preset(name).is_some(), "{}", name);
+        }
+        assert_eq!(
+            FormatOptions::preset("wrap-and-sort").unwrap(),
+            FormatOptions::default()
+        );
+        assert!(FormatOptions::preset("black-style -a").is_none());
+
+        let text = "Package: foo\nDepends: bbb,\n aaa\n";
+        let dpkg = FormatOptions::preset("dpkg-default").unwrap();
+        assert_eq!(formatted(text, &dpkg), "Package: foo\nDepends: bbb, aaa\n");
+        let black = FormatOptions::preset("black-style").unwrap();
+        assert_eq!(
+            formatted(text, &black),
+            "Package: foo\nDepends: aaa,\n bbb,\n"
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        assert_eq!(
+            FormatOptions::from_config("Sort-Lists: no\n").unwrap(),
+            FormatOptions {
+                sort_lists: false,
+                ..Default::default()
+            }
+        );
+        assert!(matches!(
+            FormatOptions::from_config("Profile: unknown\n"),
+            Err(ConfigError::UnknownPreset(_))
+        ));
+        assert!(matches!(
+            FormatOptions::from_config("Wrap-Always: maybe\n"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            FormatOptions::from_config("Colour: blue\n"),
+            Err(ConfigError::UnknownField(_))
+        ));
+        assert!(matches!(
+            FormatOptions::from_config("A: b\n\nC: d\n"),
+            Err(ConfigError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn test_tests_control() {
+        let mut doc: Deb822 =
+            "Tests: b a\nDepends: @, python3\nRestrictions: needs-root, allow-stderr\n"
+                .parse()
+                .unwrap();
+        format_tests_control(&mut doc, &FormatOptions::default());
+        assert_eq!(
+            doc.to_string(),
+            "Tests: a, b\nDepends: python3, @\nRestrictions: allow-stderr, needs-root\n"
+        );
+    }
+
+    #[test]
+    fn test_copyright() {
+        let mut doc: Deb822 = "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n\nFiles: src/* *\nLicense: GPL-2+\n".parse().unwrap();
+        assert_eq!(
+            check_copyright(&doc, &FormatOptions::default())[0].kind,
+            IssueKind::Unsorted
+        );
+        assert!(format_copyright(&mut doc, &FormatOptions::default()));
+        assert_eq!(
+            doc.to_string(),
+            "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n\nFiles: * src/*\nLicense: GPL-2+\n"
+        );
+
+        let mut doc: Deb822 = "Files: b a\n".parse().unwrap();
+        assert!(check_copyright(&doc, &FormatOptions::default()).is_empty());
+        assert!(!format_copyright(&mut doc, &FormatOptions::default()));
+        assert_eq!(doc.to_string(), "Files: b a\n");
+    }
+
+    #[test]
+    fn test_check() {
+        let text = r#"Source: foo
+Build-Depends: debhelper-compat (=   13)
+
+Package: foo-utils
+Depends: libc6,
+ python3
+Recommends: b, a
+
+Package: bar
+Depends: libc6
+"#;
+        let doc: Deb822 = text.parse().unwrap();
+        let issues = check(&doc, &FormatOptions::default());
+        assert_eq!(
+            issues
+                .iter()
+                .map(|i| (i.kind, i.field.as_deref(), &text[i.span.clone()]))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    IssueKind::Whitespace,
+                    Some("Build-Depends"),
+                    "Build-Depends: debhelper-compat (=   13)\n"
+                ),
+                (
+                    IssueKind::Wrapping,
+                    Some("Depends"),
+                    "Depends: libc6,\n python3\n"
+                ),
+                (
+                    IssueKind::Unsorted,
+                    Some("Recommends"),
+                    "Recommends: b, a\n"
+                ),
+            ]
+        );
+        assert_eq!(issues[2].to_string(), "Recommends field is not sorted");
+        // Checking does not modify the document.
+        assert_eq!(doc.to_string(), text);
+
+        let options = FormatOptions {
+            sort_binary_packages: true,
+            ..Default::default()
+        };
+        let issues = check(&doc, &options);
+        assert_eq!(issues.len(), 4);
+        assert_eq!(issues[0].kind, IssueKind::ParagraphOrder);
+        assert_eq!(
+            &text[issues[0].span.clone()],
+            "Package: bar\nDepends: libc6\n"
+        );
+        assert_eq!(issues[0].message, "binary package bar is not sorted");
+
+        let mut doc = doc;
+        format(&mut doc, &options);
+        assert!(check(&doc, &options).is_empty());
+    }
+}
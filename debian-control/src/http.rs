@@ -0,0 +1,421 @@
+//! Fetching of apt indexes over HTTP.
+//!
+//! [`AptClient`] implements the pipeline that apt uses to retrieve archive metadata: it
+//! downloads the `InRelease` file of a suite, optionally verifies its signature, looks up the
+//! `Packages` and `Sources` files in it and downloads the best available compressed variant,
+//! using by-hash URLs if the archive supports them. Every downloaded file is checked against the
+//! size and SHA-256 checksum listed in `InRelease`.
+//!
+//! # Example
+//! ```rust,no_run
+//! use debian_control::http::AptClient;
+//!
+//! let client = AptClient::new("https://deb.debian.org/debian", "sid")
+//!     .unwrap()
+//!     .with_keyring("/usr/share/keyrings/debian-archive-keyring.gpg");
+//! let release = client.release().unwrap();
+//! let packages = client.packages(&release, "main", "amd64").unwrap();
+//! println!("{}", packages.get("hello").unwrap().version);
+//! ```
+use crate::fields::Sha256Checksum;
+use crate::lossless::apt::Release;
+use crate::lossy::apt::{Package, Source};
+use crate::lossy::index::{IndexEntry, PackagesIndex};
+use deb822_lossless::compression::Compression;
+use deb822_lossless::FromDeb822Paragraph;
+use sha2::Digest;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Error fetching an index.
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    /// The repository URL is not valid.
+    #[error("invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
+    /// The HTTP request failed.
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// The signature of the `InRelease` file could not be verified.
+    #[error("unable to verify signature: {0}")]
+    Signature(String),
+
+    /// The `InRelease` file is malformed.
+    #[error("invalid InRelease file: {0}")]
+    Pgp(#[from] deb822_lossless::signed::Error),
+
+    /// A field in the `InRelease` file has an invalid value.
+    #[error("invalid InRelease file: {0}")]
+    Field(#[from] crate::FieldError),
+
+    /// The index is not listed in the `InRelease` file.
+    #[error("{0} not listed in InRelease")]
+    MissingIndex(String),
+
+    /// The downloaded file does not match the size or checksum listed in `InRelease`.
+    #[error("{path}: expected {expected}, found {actual}")]
+    Mismatch {
+        /// Path of the file, relative to the suite.
+        path: String,
+
+        /// Value listed in `InRelease`.
+        expected: String,
+
+        /// Value of the downloaded file.
+        actual: String,
+    },
+
+    /// The file could not be decompressed.
+    #[error("unable to decompress {path}")]
+    Decompress {
+        /// Path of the file, relative to the suite.
+        path: String,
+
+        /// The underlying I/O error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The file could not be parsed.
+    #[error("unable to parse {path}: {source}")]
+    Parse {
+        /// Path of the file, relative to the suite.
+        path: String,
+
+        /// The underlying parse error.
+        #[source]
+        source: Box<crate::ControlError>,
+    },
+}
+
+/// Compression formats, in order of preference.
+const COMPRESSIONS: &[&str] = &[".xz", ".gz", ""];
+
+fn decompress(path: &str, data: Vec<u8>) -> Result<Vec<u8>, FetchError> {
+    let compression = match path
+        .rsplit_once('.')
+        .and_then(|(_, ext)| Compression::from_extension(ext))
+    {
+        Some(compression) => compression,
+        None => return Ok(data),
+    };
+    let mut ret = vec![];
+    compression
+        .decoder(&data[..])
+        .and_then(|mut decoder| decoder.read_to_end(&mut ret))
+        .map_err(|source| FetchError::Decompress {
+            path: path.to_string(),
+            source,
+        })?;
+    Ok(ret)
+}
+
+/// Client for retrieving the indexes of a suite in an apt repository.
+pub struct AptClient {
+    client: reqwest::blocking::Client,
+    dists: url::Url,
+    keyring: Option<PathBuf>,
+}
+
+impl AptClient {
+    /// Create a client for a suite in the repository at `url`.
+    ///
+    /// The URL is the one used in `sources.list`, e.g. `https://deb.debian.org/debian`.
+    pub fn new(url: &str, suite: &str) -> Result<Self, FetchError> {
+        let mut base = url::Url::parse(url)?;
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        Ok(Self {
+            client: reqwest::blocking::Client::new(),
+            dists: base.join(&format!("dists/{}/", suite))?,
+            keyring: None,
+        })
+    }
+
+    /// Verify the signature of the `InRelease` file against the keys in a keyring.
+    ///
+    /// Verification is done with `gpgv`, which needs to be installed. Without a keyring, the
+    /// signature is stripped but not checked.
+    pub fn with_keyring(mut self, keyring: impl Into<PathBuf>) -> Self {
+        self.keyring = Some(keyring.into());
+        self
+    }
+
+    fn get(&self, path: &str) -> Result<Vec<u8>, FetchError> {
+        let url = self.dists.join(path)?;
+        let response = self.client.get(url).send()?.error_for_status()?;
+        Ok(response.bytes()?.to_vec())
+    }
+
+    fn verify_signature(&self, text: &str) -> Result<String, FetchError> {
+        let Some(keyring) = self.keyring.as_ref() else {
+            return Ok(deb822_lossless::signed::strip_signature(text)?.into_owned());
+        };
+        let mut child = std::process::Command::new("gpgv")
+            .arg("--keyring")
+            .arg(keyring)
+            .args(["--output", "-", "-"])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| FetchError::Signature(format!("unable to run gpgv: {}", e)))?;
+        let mut stdin = child.stdin.take().unwrap();
+        let input = text.as_bytes().to_vec();
+        let writer = std::thread::spawn(move || std::io::Write::write_all(&mut stdin, &input));
+        let output = child
+            .wait_with_output()
+            .map_err(|e| FetchError::Signature(e.to_string()))?;
+        // gpgv may exit before reading all of its input, so errors writing are not fatal.
+        let _ = writer.join();
+        if !output.status.success() {
+            return Err(FetchError::Signature(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        String::from_utf8(output.stdout).map_err(|e| FetchError::Signature(e.to_string()))
+    }
+
+    /// Download and verify the `InRelease` file of the suite.
+    pub fn release(&self) -> Result<Release, FetchError> {
+        let data = self.get("InRelease")?;
+        let text = String::from_utf8_lossy(&data);
+        let payload = self.verify_signature(&text)?;
+        payload
+            .parse()
+            .map_err(|e: deb822_lossless::ParseError| FetchError::Parse {
+                path: "InRelease".to_string(),
+                source: Box::new(e.into()),
+            })
+    }
+
+    /// Download an index listed in `release`, and return its decompressed contents.
+    ///
+    /// `path` is relative to the suite and excludes any compression suffix, e.g.
+    /// `main/binary-amd64/Packages`. The best compression format that is listed in the release is
+    /// used.
+    pub fn fetch(&self, release: &Release, path: &str) -> Result<Vec<u8>, FetchError> {
+        let checksums = release.checksums_sha256()?;
+        let checksum = COMPRESSIONS
+            .iter()
+            .find_map(|ext| {
+                let name = format!("{}{}", path, ext);
+                checksums.iter().find(|c| c.filename == name)
+            })
+            .ok_or_else(|| FetchError::MissingIndex(path.to_string()))?;
+        let data = if release.acquire_by_hash() {
+            let dir = checksum
+                .filename
+                .rsplit_once('/')
+                .map_or(String::new(), |(dir, _)| format!("{}/", dir));
+            self.get(&format!("{}by-hash/SHA256/{}", dir, checksum.sha256))?
+        } else {
+            self.get(&checksum.filename)?
+        };
+        check(checksum, &data)?;
+        decompress(&checksum.filename, data)
+    }
+
+    fn fetch_index<T>(&self, release: &Release, path: &str) -> Result<PackagesIndex<T>, FetchError>
+    where
+        T: IndexEntry + FromDeb822Paragraph<deb822_lossless::lossy::Paragraph>,
+    {
+        let data = self.fetch(release, path)?;
+        String::from_utf8_lossy(&data)
+            .parse()
+            .map_err(|source| FetchError::Parse {
+                path: path.to_string(),
+                source: Box::new(source),
+            })
+    }
+
+    /// Download the `Packages` index for a component and architecture.
+    pub fn packages(
+        &self,
+        release: &Release,
+        component: &str,
+        architecture: &str,
+    ) -> Result<PackagesIndex<Package>, FetchError> {
+        self.fetch_index(
+            release,
+            &format!("{}/binary-{}/Packages", component, architecture),
+        )
+    }
+
+    /// Download the `Sources` index for a component.
+    pub fn sources(
+        &self,
+        release: &Release,
+        component: &str,
+    ) -> Result<PackagesIndex<Source>, FetchError> {
+        self.fetch_index(release, &format!("{}/source/Sources", component))
+    }
+}
+
+fn check(checksum: &Sha256Checksum, data: &[u8]) -> Result<(), FetchError> {
+    if data.len() != checksum.size {
+        return Err(FetchError::Mismatch {
+            path: checksum.filename.clone(),
+            expected: format!("size {}", checksum.size),
+            actual: format!("size {}", data.len()),
+        });
+    }
+    let actual = format!("{:x}", sha2::Sha256::digest(data));
+    if !actual.eq_ignore_ascii_case(&checksum.sha256) {
+        return Err(FetchError::Mismatch {
+            path: checksum.filename.clone(),
+            expected: format!("SHA256 {}", checksum.sha256),
+            actual: format!("SHA256 {}", actual),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+
+    const PACKAGES: &str = "Package: hello\nVersion: 2.10-3\nArchitecture: amd64\n";
+
+    const SOURCES: &str =
+        "Package: hello\nVersion: 2.10-3\nDirectory: pool/main/h/hello\nPackage-List: hello deb devel optional\n";
+
+    /// Serve files over HTTP on a local port, returning the base URL.
+    fn serve(files: HashMap<String, Vec<u8>>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                reader.read_line(&mut request).unwrap();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                }
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                match files.get(path) {
+                    Some(body) => {
+                        write!(
+                            stream,
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            body.len()
+                        )
+                        .unwrap();
+                        stream.write_all(body).unwrap();
+                    }
+                    None => {
+                        write!(
+                            stream,
+                            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        });
+        format!("http://{}/debian", addr)
+    }
+
+    fn checksum(name: &str, data: &[u8]) -> String {
+        format!(
+            " {:x} {} {}\n",
+            sha2::Sha256::digest(data),
+            data.len(),
+            name
+        )
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_fetch() {
+        let packages_gz = gzip(PACKAGES.as_bytes());
+        let in_release = format!(
+            "Suite: sid\nCodename: sid\nSHA256:\n{}{}{}",
+            checksum("main/binary-amd64/Packages", PACKAGES.as_bytes()),
+            checksum("main/binary-amd64/Packages.gz", &packages_gz),
+            checksum("main/source/Sources", SOURCES.as_bytes()),
+        );
+        let files = HashMap::from([
+            ("/debian/dists/sid/InRelease".to_string(), in_release.into()),
+            (
+                "/debian/dists/sid/main/binary-amd64/Packages.gz".to_string(),
+                packages_gz,
+            ),
+            (
+                "/debian/dists/sid/main/source/Sources".to_string(),
+                SOURCES.into(),
+            ),
+        ]);
+        let client = AptClient::new(&serve(files), "sid").unwrap();
+        let release = client.release().unwrap();
+        assert_eq!(release.suite().as_deref(), Some("sid"));
+
+        let packages = client.packages(&release, "main", "amd64").unwrap();
+        assert_eq!(
+            packages.get("hello").unwrap().version,
+            "2.10-3".parse().unwrap()
+        );
+        let sources = client.sources(&release, "main").unwrap();
+        assert_eq!(sources.len(), 1);
+
+        assert!(matches!(
+            client.packages(&release, "contrib", "amd64"),
+            Err(FetchError::MissingIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_fetch_by_hash() {
+        let digest = format!("{:x}", sha2::Sha256::digest(PACKAGES.as_bytes()));
+        let in_release = format!(
+            "Suite: sid\nAcquire-By-Hash: yes\nSHA256:\n{}",
+            checksum("main/binary-amd64/Packages", PACKAGES.as_bytes()),
+        );
+        let files = HashMap::from([
+            ("/debian/dists/sid/InRelease".to_string(), in_release.into()),
+            (
+                format!(
+                    "/debian/dists/sid/main/binary-amd64/by-hash/SHA256/{}",
+                    digest
+                ),
+                PACKAGES.into(),
+            ),
+        ]);
+        let client = AptClient::new(&serve(files), "sid").unwrap();
+        let release = client.release().unwrap();
+        assert_eq!(client.packages(&release, "main", "amd64").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_fetch_mismatch() {
+        let in_release = format!(
+            "Suite: sid\nSHA256:\n{}",
+            checksum("main/binary-amd64/Packages", PACKAGES.as_bytes()),
+        );
+        let files = HashMap::from([
+            ("/debian/dists/sid/InRelease".to_string(), in_release.into()),
+            (
+                "/debian/dists/sid/main/binary-amd64/Packages".to_string(),
+                PACKAGES.replace("2.10-3", "2.10-4").into(),
+            ),
+        ]);
+        let client = AptClient::new(&serve(files), "sid").unwrap();
+        let release = client.release().unwrap();
+        let e = client.packages(&release, "main", "amd64").unwrap_err();
+        assert!(matches!(e, FetchError::Mismatch { .. }), "{}", e);
+    }
+}
@@ -0,0 +1,435 @@
+//! Conversion of control data to and from JSON.
+//!
+//! # Schema
+//!
+//! A paragraph is represented as a JSON object that maps field names to values. Field names are
+//! kept exactly as they appear in the file. Values are strings, with the lines of multi-line
+//! values separated by `\n` and without their leading indentation - the same representation as
+//! returned by [`deb822_lossless::Paragraph::get`].
+//!
+//! When [`Options::expand_relations`] is set, the values of relation fields (see
+//! [`RELATION_FIELDS`]) are instead represented as an array of entries, where each entry is an
+//! array of alternatives and each alternative is an object with the following keys:
+//!
+//! * `name`: the name of the package (always present)
+//! * `archqual`: the architecture qualifier, e.g. `any` for `python3:any`
+//! * `version`: an object with `operator` (one of `<<`, `<=`, `=`, `>=` or `>>`) and `version`
+//! * `architectures`: an array of architecture restrictions, e.g. `["amd64", "!i386"]`
+//! * `profiles`: an array of build profile restriction formulas, each an array of profiles,
+//!   e.g. `[["!nocheck"]]`
+//!
+//! Keys that don't apply to a relation are omitted. Relation fields that can not be parsed or
+//! that contain substitution variables (such as `${misc:Depends}`) are always kept as strings.
+//!
+//! A control file is represented as an object with a `source` key containing the source
+//! paragraph (or `null`), and a `binaries` key containing an array of binary paragraphs.
+//!
+//! With the `yaml` feature, the same structure can be exported as YAML using the `to_yaml`
+//! methods.
+//!
+//! # Example
+//!
+//! ```rust
+//! use debian_control::lossless::apt::Package;
+//! use debian_control::json::Options;
+//!
+//! let package: Package = "Package: hello\nDepends: libc6 (>= 2.34)\n".parse().unwrap();
+//! assert_eq!(
+//!     package.to_json(),
+//!     serde_json::json!({"Package": "hello", "Depends": "libc6 (>= 2.34)"})
+//! );
+//! assert_eq!(
+//!     package.to_json_with(&Options { expand_relations: true }),
+//!     serde_json::json!({
+//!         "Package": "hello",
+//!         "Depends": [[{"name": "libc6", "version": {"operator": ">=", "version": "2.34"}}]]
+//!     })
+//! );
+//! ```
+use crate::lossless::relations::{Relation, Relations};
+use deb822_lossless::Paragraph;
+use serde_json::{Map, Value};
+
+pub use crate::fields::RELATION_FIELDS;
+
+/// Options for converting to JSON.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Represent relation fields as structured data rather than as strings.
+    pub expand_relations: bool,
+}
+
+/// An error converting from JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A JSON value did not have the expected type.
+    UnexpectedType {
+        /// The location of the value, e.g. the name of the field.
+        path: String,
+
+        /// The type that was expected.
+        expected: &'static str,
+    },
+
+    /// The value of a relations field is invalid.
+    InvalidRelations {
+        /// The name of the field.
+        field: String,
+
+        /// The error message from the relations parser.
+        error: String,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::UnexpectedType { path, expected } => {
+                write!(f, "expected {} for {}", expected, path)
+            }
+            Error::InvalidRelations { field, error } => {
+                write!(f, "invalid relations in {}: {}", field, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Render a JSON value as YAML.
+#[cfg(feature = "yaml")]
+pub(crate) fn to_yaml(value: &Value) -> String {
+    serde_yaml::to_string(value).expect("JSON values can always be represented as YAML")
+}
+
+fn relation_to_json(relation: &Relation) -> Value {
+    let mut obj = Map::new();
+    obj.insert("name".to_string(), Value::String(relation.name()));
+    if let Some(archqual) = relation.archqual() {
+        obj.insert("archqual".to_string(), Value::String(archqual));
+    }
+    if let Some((constraint, version)) = relation.version() {
+        obj.insert(
+            "version".to_string(),
+            serde_json::json!({
+                "operator": constraint.to_string(),
+                "version": version.to_string(),
+            }),
+        );
+    }
+    if let Some(architectures) = relation.architectures() {
+        obj.insert(
+            "architectures".to_string(),
+            architectures.map(Value::String).collect(),
+        );
+    }
+    let profiles = relation
+        .profiles()
+        .map(|profile| {
+            profile
+                .iter()
+                .map(|p| Value::String(p.to_string()))
+                .collect()
+        })
+        .collect::<Vec<Value>>();
+    if !profiles.is_empty() {
+        obj.insert("profiles".to_string(), Value::Array(profiles));
+    }
+    Value::Object(obj)
+}
+
+/// Expand the value of a relations field, if it can be represented as structured data.
+fn relations_to_json(value: &str) -> Option<Value> {
+    let (relations, errors) = Relations::parse_relaxed(value, true);
+    if !errors.is_empty() || relations.substvars().next().is_some() {
+        return None;
+    }
+    Some(Value::Array(
+        relations
+            .entries()
+            .map(|entry| Value::Array(entry.relations().map(|r| relation_to_json(&r)).collect()))
+            .collect(),
+    ))
+}
+
+fn expect_str<'a>(value: &'a Value, path: &str) -> Result<&'a str, Error> {
+    value.as_str().ok_or_else(|| Error::UnexpectedType {
+        path: path.to_string(),
+        expected: "a string",
+    })
+}
+
+pub(crate) fn expect_array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, Error> {
+    value.as_array().ok_or_else(|| Error::UnexpectedType {
+        path: path.to_string(),
+        expected: "an array",
+    })
+}
+
+pub(crate) fn expect_object<'a>(
+    value: &'a Value,
+    path: &str,
+) -> Result<&'a Map<String, Value>, Error> {
+    value.as_object().ok_or_else(|| Error::UnexpectedType {
+        path: path.to_string(),
+        expected: "an object",
+    })
+}
+
+/// Format a relation from its structured representation.
+fn relation_from_json(value: &Value, path: &str) -> Result<String, Error> {
+    let obj = expect_object(value, path)?;
+    let name = obj.get("name").unwrap_or(&Value::Null);
+    let mut ret = expect_str(name, &format!("{}.name", path))?.to_string();
+    if let Some(archqual) = obj.get("archqual") {
+        ret.push(':');
+        ret.push_str(expect_str(archqual, &format!("{}.archqual", path))?);
+    }
+    if let Some(version) = obj.get("version") {
+        let path = format!("{}.version", path);
+        let version = expect_object(version, &path)?;
+        let operator = version.get("operator").unwrap_or(&Value::Null);
+        let operator = expect_str(operator, &format!("{}.operator", path))?;
+        let version = version.get("version").unwrap_or(&Value::Null);
+        let version = expect_str(version, &format!("{}.version", path))?;
+        ret.push_str(&format!(" ({} {})", operator, version));
+    }
+    if let Some(architectures) = obj.get("architectures") {
+        let path = format!("{}.architectures", path);
+        let architectures = expect_array(architectures, &path)?
+            .iter()
+            .map(|a| expect_str(a, &path))
+            .collect::<Result<Vec<_>, _>>()?;
+        ret.push_str(&format!(" [{}]", architectures.join(" ")));
+    }
+    if let Some(profiles) = obj.get("profiles") {
+        let path = format!("{}.profiles", path);
+        for profile in expect_array(profiles, &path)? {
+            let profile = expect_array(profile, &path)?
+                .iter()
+                .map(|p| expect_str(p, &path))
+                .collect::<Result<Vec<_>, _>>()?;
+            ret.push_str(&format!(" <{}>", profile.join(" ")));
+        }
+    }
+    Ok(ret)
+}
+
+/// Format the value of a relations field from its structured representation.
+fn relations_from_json(entries: &[Value], field: &str) -> Result<String, Error> {
+    let mut ret = Vec::new();
+    for entry in entries {
+        let alternatives = expect_array(entry, field)?
+            .iter()
+            .map(|relation| relation_from_json(relation, field))
+            .collect::<Result<Vec<_>, _>>()?;
+        ret.push(alternatives.join(" | "));
+    }
+    let ret = ret.join(", ");
+    // Make sure that the result can be parsed again
+    let (_, errors) = Relations::parse_relaxed(&ret, true);
+    if !errors.is_empty() {
+        return Err(Error::InvalidRelations {
+            field: field.to_string(),
+            error: errors.join("\n"),
+        });
+    }
+    Ok(ret)
+}
+
+/// Convert a paragraph to a JSON object.
+pub(crate) fn paragraph_to_json(paragraph: &Paragraph, options: &Options) -> Value {
+    let mut obj = Map::new();
+    for (key, value) in paragraph.items() {
+        let value = if options.expand_relations && RELATION_FIELDS.contains(&key.as_str()) {
+            relations_to_json(&value).unwrap_or(Value::String(value))
+        } else {
+            Value::String(value)
+        };
+        obj.insert(key, value);
+    }
+    Value::Object(obj)
+}
+
+/// Set the fields in a paragraph from a JSON object.
+pub(crate) fn paragraph_from_json(
+    paragraph: &mut Paragraph,
+    value: &Value,
+    path: &str,
+) -> Result<(), Error> {
+    for (key, value) in expect_object(value, path)? {
+        let value = match value {
+            Value::String(s) => s.clone(),
+            Value::Array(entries) if RELATION_FIELDS.contains(&key.as_str()) => {
+                relations_from_json(entries, key)?
+            }
+            _ => {
+                return Err(Error::UnexpectedType {
+                    path: key.clone(),
+                    expected: "a string",
+                })
+            }
+        };
+        paragraph.set(key, &value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn expanded() -> Options {
+        Options {
+            expand_relations: true,
+        }
+    }
+
+    #[test]
+    fn test_relations_roundtrip() {
+        let para: Paragraph = r#"Package: foo
+Depends: bar:any (>= 1.0) [amd64 !i386] <!nocheck> <cross>, baz | qux
+Description: A package
+ With a long description
+"#
+        .parse()
+        .unwrap();
+        let value = paragraph_to_json(&para, &expanded());
+        assert_eq!(
+            value,
+            json!({
+                "Package": "foo",
+                "Depends": [
+                    [{
+                        "name": "bar",
+                        "archqual": "any",
+                        "version": {"operator": ">=", "version": "1.0"},
+                        "architectures": ["amd64", "!i386"],
+                        "profiles": [["!nocheck"], ["cross"]],
+                    }],
+                    [{"name": "baz"}, {"name": "qux"}],
+                ],
+                "Description": "A package\nWith a long description",
+            })
+        );
+
+        let mut new = Paragraph::new();
+        paragraph_from_json(&mut new, &value, "paragraph").unwrap();
+        assert_eq!(
+            new.get("Depends").as_deref(),
+            Some("bar:any (>= 1.0) [amd64 !i386] <!nocheck> <cross>, baz | qux")
+        );
+        assert_eq!(
+            new.get("Description").as_deref(),
+            Some("A package\nWith a long description")
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml() {
+        let control: crate::lossless::Control = r#"Source: foo
+
+Package: foo
+Depends: bar (>= 1.0)
+Description: A package
+ With a long description
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            control.to_yaml(),
+            r#"binaries:
+- Depends: bar (>= 1.0)
+  Description: |-
+    A package
+    With a long description
+  Package: foo
+source:
+  Source: foo
+"#
+        );
+        let package: crate::lossless::apt::Package =
+            "Package: foo\nDepends: bar (>= 1.0)\n".parse().unwrap();
+        assert_eq!(
+            package.to_yaml_with(&Options {
+                expand_relations: true
+            }),
+            r#"Depends:
+- - name: bar
+    version:
+      operator: '>='
+      version: '1.0'
+Package: foo
+"#
+        );
+    }
+
+    #[test]
+    fn test_control() {
+        let control: crate::lossless::Control = r#"Source: foo
+Build-Depends: debhelper-compat (= 13)
+
+Package: foo
+Architecture: any
+Depends: ${misc:Depends}
+"#
+        .parse()
+        .unwrap();
+        let value = control.to_json();
+        assert_eq!(
+            value,
+            json!({
+                "source": {"Source": "foo", "Build-Depends": "debhelper-compat (= 13)"},
+                "binaries": [
+                    {"Package": "foo", "Architecture": "any", "Depends": "${misc:Depends}"},
+                ],
+            })
+        );
+        let control = crate::lossless::Control::from_json(&value).unwrap();
+        assert_eq!(control.source().unwrap().name(), Some("foo".to_string()));
+        assert_eq!(control.binaries().count(), 1);
+
+        let control = crate::lossless::Control::from_json(&json!({"binaries": []})).unwrap();
+        assert!(control.source().is_none());
+    }
+
+    #[test]
+    fn test_substvars_kept_as_string() {
+        let para: Paragraph = "Depends: ${misc:Depends}, foo\n".parse().unwrap();
+        assert_eq!(
+            paragraph_to_json(&para, &expanded()),
+            json!({"Depends": "${misc:Depends}, foo"})
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut para = Paragraph::new();
+        assert_eq!(
+            paragraph_from_json(&mut para, &json!([]), "paragraph"),
+            Err(Error::UnexpectedType {
+                path: "paragraph".to_string(),
+                expected: "an object"
+            })
+        );
+        assert_eq!(
+            paragraph_from_json(&mut para, &json!({"Package": 1}), "paragraph"),
+            Err(Error::UnexpectedType {
+                path: "Package".to_string(),
+                expected: "a string"
+            })
+        );
+        assert_eq!(
+            paragraph_from_json(&mut para, &json!({"Depends": [[{"version": {}}]]}), "p")
+                .unwrap_err()
+                .to_string(),
+            "expected a string for Depends.name"
+        );
+        assert!(matches!(
+            paragraph_from_json(&mut para, &json!({"Depends": [[{"name": "foo ("}]]}), "p"),
+            Err(Error::InvalidRelations { .. })
+        ));
+    }
+}
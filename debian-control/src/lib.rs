@@ -38,10 +38,26 @@
 pub mod lossy;
 #[cfg(feature = "lossless")]
 pub use lossless::control::{Binary, Control, Source};
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod builder;
+#[cfg(feature = "lossless")]
+pub mod casing;
+#[cfg(feature = "miette")]
+pub mod diagnostics;
+pub mod error;
+pub use error::{ControlError, FieldError, RelationsParseError};
 pub mod fields;
 pub use fields::*;
 #[cfg(feature = "lossless")]
+pub mod format;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "lossless")]
 pub mod lossless;
+pub mod ordering;
 #[cfg(feature = "lossless")]
 pub use lossless::apt;
 #[cfg(feature = "lossless")]
@@ -50,8 +66,17 @@ pub use lossless::changes;
 pub use lossless::control;
 #[cfg(feature = "lossless")]
 pub mod pgp;
+#[cfg(feature = "verify")]
+pub mod pool;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod relations;
+pub mod substvars;
 pub mod vcs;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use std::borrow::Cow;
 
@@ -60,12 +85,16 @@ use std::borrow::Cow;
 pub enum ParseIdentityError {
     /// No email address found.
     NoEmail,
+
+    /// A quoted name is missing its closing quote.
+    UnterminatedQuote,
 }
 
 impl std::fmt::Display for ParseIdentityError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             ParseIdentityError::NoEmail => write!(f, "No email found"),
+            ParseIdentityError::UnterminatedQuote => write!(f, "Unterminated quote in name"),
         }
     }
 }
@@ -103,6 +132,128 @@ pub fn parse_identity(s: &str) -> Result<(&str, &str), ParseIdentityError> {
     }
 }
 
+/// The name and email address of a person, as found in the `Maintainer` field.
+///
+/// Names are written as an RFC 5322 quoted string if they contain characters that would
+/// otherwise be misread, such as the comma in `"Doe, John" <john@example.com>`.
+///
+/// # Example
+/// ```
+/// use debian_control::Identity;
+/// let identity: Identity = "\"Doe, John\" <john@example.com>".parse().unwrap();
+/// assert_eq!(identity.name, "Doe, John");
+/// assert_eq!(identity.email, "john@example.com");
+/// assert_eq!(identity.to_string(), "\"Doe, John\" <john@example.com>");
+/// assert_eq!(
+///     Identity::new("Jane Doe", "jane@example.com").to_string(),
+///     "Jane Doe <jane@example.com>"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Identity {
+    /// The name of the person, without quotes. This may be empty.
+    pub name: String,
+
+    /// The email address of the person.
+    pub email: String,
+}
+
+impl Identity {
+    /// Create a new identity.
+    pub fn new(name: &str, email: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for Identity {
+    type Err = ParseIdentityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(quoted) = s.strip_prefix('"') else {
+            let (name, email) = parse_identity(s)?;
+            return Ok(Self::new(name, email));
+        };
+        let mut name = String::new();
+        let mut chars = quoted.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((_, '\\')) => name.extend(chars.next().map(|(_, c)| c)),
+                Some((i, '"')) => break i + 1,
+                Some((_, c)) => name.push(c),
+                None => return Err(ParseIdentityError::UnterminatedQuote),
+            }
+        };
+        let (_, email) = parse_identity(&quoted[end..])?;
+        Ok(Self {
+            name,
+            email: email.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.name.is_empty() {
+            return f.write_str(&self.email);
+        }
+        if self.name.contains([',', '"', '<', '>', '\\']) {
+            f.write_str("\"")?;
+            for c in self.name.chars() {
+                if c == '"' || c == '\\' {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{}", c)?;
+            }
+            f.write_str("\"")?;
+        } else {
+            f.write_str(&self.name)?;
+        }
+        write!(f, " <{}>", self.email)
+    }
+}
+
+/// Split a comma-separated list of identities, as found in the `Uploaders` field.
+///
+/// Identities are only separated by commas that follow the closing `>` of an email
+/// address (or a bare address), so commas in names, quoted or not, as in
+/// `Doe, John <john@example.com>`, are kept. Surrounding whitespace is stripped and
+/// empty items are skipped.
+pub(crate) fn split_identities(s: &str) -> impl Iterator<Item = &str> {
+    let mut items = vec![];
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut closed = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                let item = s[start..i].trim();
+                let bare_address = !item.contains('<') && item.contains('@');
+                if closed || bare_address || item.is_empty() {
+                    items.push(&s[start..i]);
+                    start = i + 1;
+                }
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            closed = c == '>' && !quoted;
+        }
+    }
+    items.push(&s[start..]);
+    items
+        .into_iter()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
 /// A trait for looking up versions of packages.
 pub trait VersionLookup {
     /// Look up the version of a package.
@@ -153,4 +304,59 @@ mod tests {
         );
         assert_eq!(parse_identity("somebody"), Err(ParseIdentityError::NoEmail));
     }
+
+    #[test]
+    fn test_split_identities() {
+        assert_eq!(
+            split_identities(
+                "\"Doe, John\" <john@example.com>,\n Jane <jane@example.com>, ,\"a\\\",b\" <a@b>"
+            )
+            .collect::<Vec<_>>(),
+            vec![
+                "\"Doe, John\" <john@example.com>",
+                "Jane <jane@example.com>",
+                "\"a\\\",b\" <a@b>"
+            ]
+        );
+        assert_eq!(split_identities("").count(), 0);
+        assert_eq!(
+            split_identities(
+                "Doe, John <j@d.o>, Jane Roe <jane@example.com>, joe@example.com, X <x@y>"
+            )
+            .collect::<Vec<_>>(),
+            vec![
+                "Doe, John <j@d.o>",
+                "Jane Roe <jane@example.com>",
+                "joe@example.com",
+                "X <x@y>"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identity() {
+        let identity: Identity = "Joe Example <joe@example.com>".parse().unwrap();
+        assert_eq!(identity, Identity::new("Joe Example", "joe@example.com"));
+        assert_eq!(identity.to_string(), "Joe Example <joe@example.com>");
+
+        let identity: Identity = "joe@example.com".parse().unwrap();
+        assert_eq!(identity, Identity::new("", "joe@example.com"));
+        assert_eq!(identity.to_string(), "joe@example.com");
+
+        let identity = Identity::new("Joe \"The <Example>\" Doe", "joe@example.com");
+        assert_eq!(
+            identity.to_string(),
+            r#""Joe \"The <Example>\" Doe" <joe@example.com>"#
+        );
+        assert_eq!(identity.to_string().parse::<Identity>(), Ok(identity));
+
+        assert_eq!(
+            "\"Doe, Joe <joe@example.com>".parse::<Identity>(),
+            Err(ParseIdentityError::UnterminatedQuote)
+        );
+        assert_eq!(
+            "\"Doe, Joe\"".parse::<Identity>(),
+            Err(ParseIdentityError::NoEmail)
+        );
+    }
 }
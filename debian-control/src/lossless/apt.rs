@@ -1,10 +1,13 @@
 //! APT package manager files
+use crate::error::{parse_field, parse_lines, FieldError};
 use crate::fields::{
-    Md5Checksum, MultiArch, Priority, Sha1Checksum, Sha256Checksum, Sha512Checksum,
+    ChecksumAlgorithm, ChecksumList, Md5Checksum, MultiArch, Priority, Sha1Checksum,
+    Sha256Checksum, Sha512Checksum,
 };
 use crate::lossless::relations::Relations;
 
 /// A source package in the APT package manager.
+#[derive(PartialEq, Eq, Hash)]
 pub struct Source(deb822_lossless::Paragraph);
 
 #[cfg(feature = "python-debian")]
@@ -28,6 +31,39 @@ impl pyo3::FromPyObject<'_> for Source {
     }
 }
 
+#[cfg(feature = "json")]
+impl Source {
+    /// Convert the source package to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> serde_json::Value {
+        self.to_json_with(&crate::json::Options::default())
+    }
+
+    /// Convert the source package to JSON, with the given options.
+    pub fn to_json_with(&self, options: &crate::json::Options) -> serde_json::Value {
+        crate::json::paragraph_to_json(&self.0, options)
+    }
+
+    /// Create a source package from its JSON representation.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, crate::json::Error> {
+        let mut paragraph = deb822_lossless::Paragraph::new();
+        crate::json::paragraph_from_json(&mut paragraph, value, "source")?;
+        Ok(Self(paragraph))
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Source {
+    /// Convert the source package to YAML, using the same structure as [`Source::to_json`].
+    pub fn to_yaml(&self) -> String {
+        self.to_yaml_with(&crate::json::Options::default())
+    }
+
+    /// Convert the source package to YAML, with the given options.
+    pub fn to_yaml_with(&self, options: &crate::json::Options) -> String {
+        crate::json::to_yaml(&self.to_json_with(options))
+    }
+}
+
 impl From<deb822_lossless::Paragraph> for Source {
     fn from(paragraph: deb822_lossless::Paragraph) -> Self {
         Self(paragraph)
@@ -48,7 +84,7 @@ impl Source {
 
     /// Get the source name
     pub fn package(&self) -> Option<String> {
-        self.0.get("Package").map(|s| s.to_string())
+        self.0.get("Package")
     }
 
     /// Set the package name
@@ -57,8 +93,8 @@ impl Source {
     }
 
     /// Get the version of the package
-    pub fn version(&self) -> Option<debversion::Version> {
-        self.0.get("Version").map(|s| s.parse().unwrap())
+    pub fn version(&self) -> Option<Result<debversion::Version, FieldError>> {
+        parse_field(&self.0, "Version")
     }
 
     /// Set the version of the package
@@ -68,7 +104,7 @@ impl Source {
 
     /// Get the maintainer of the package
     pub fn maintainer(&self) -> Option<String> {
-        self.0.get("Maintainer").map(|s| s.to_string())
+        self.0.get("Maintainer")
     }
 
     /// Set the maintainer of the package
@@ -79,8 +115,8 @@ impl Source {
     /// Get the uploaders of the package
     pub fn uploaders(&self) -> Option<Vec<String>> {
         self.0.get("Uploaders").map(|s| {
-            s.split(',')
-                .map(|s| s.trim().to_string())
+            crate::split_identities(&s)
+                .map(str::to_string)
                 .collect::<Vec<String>>()
         })
     }
@@ -92,7 +128,7 @@ impl Source {
 
     /// Get the standards version of the package
     pub fn standards_version(&self) -> Option<String> {
-        self.0.get("Standards-Version").map(|s| s.to_string())
+        self.0.get("Standards-Version")
     }
 
     /// Set the standards version of the package
@@ -102,7 +138,7 @@ impl Source {
 
     /// Get the source format of the package
     pub fn format(&self) -> Option<String> {
-        self.0.get("Format").map(|s| s.to_string())
+        self.0.get("Format")
     }
 
     /// Set the format of the package
@@ -112,7 +148,7 @@ impl Source {
 
     /// Get the Vcs-Browser field
     pub fn vcs_browser(&self) -> Option<String> {
-        self.0.get("Vcs-Browser").map(|s| s.to_string())
+        self.0.get("Vcs-Browser")
     }
 
     /// Set the Vcs-Browser field
@@ -122,7 +158,7 @@ impl Source {
 
     /// Get the Vcs-Git field
     pub fn vcs_git(&self) -> Option<String> {
-        self.0.get("Vcs-Git").map(|s| s.to_string())
+        self.0.get("Vcs-Git")
     }
 
     /// Set the Vcs-Git field
@@ -132,7 +168,7 @@ impl Source {
 
     /// Get the Vcs-Svn field
     pub fn vcs_svn(&self) -> Option<String> {
-        self.0.get("Vcs-Svn").map(|s| s.to_string())
+        self.0.get("Vcs-Svn")
     }
 
     /// Set the Vcs-Svn field
@@ -142,7 +178,7 @@ impl Source {
 
     /// Get the Vcs-Hg field
     pub fn vcs_hg(&self) -> Option<String> {
-        self.0.get("Vcs-Hg").map(|s| s.to_string())
+        self.0.get("Vcs-Hg")
     }
 
     /// Set the Vcs-Hg field
@@ -152,7 +188,7 @@ impl Source {
 
     /// Get the Vcs-Bzr field
     pub fn vcs_bzr(&self) -> Option<String> {
-        self.0.get("Vcs-Bzr").map(|s| s.to_string())
+        self.0.get("Vcs-Bzr")
     }
 
     /// Set the Vcs-Bzr field
@@ -162,7 +198,7 @@ impl Source {
 
     /// Get the Vcs-Arch field
     pub fn vcs_arch(&self) -> Option<String> {
-        self.0.get("Vcs-Arch").map(|s| s.to_string())
+        self.0.get("Vcs-Arch")
     }
 
     /// Set the Vcs-Arch field
@@ -172,7 +208,7 @@ impl Source {
 
     /// Get the Vcs-Svk field
     pub fn vcs_svk(&self) -> Option<String> {
-        self.0.get("Vcs-Svk").map(|s| s.to_string())
+        self.0.get("Vcs-Svk")
     }
 
     /// Set the Svk VCS
@@ -182,7 +218,7 @@ impl Source {
 
     /// Get the Darcs VCS
     pub fn vcs_darcs(&self) -> Option<String> {
-        self.0.get("Vcs-Darcs").map(|s| s.to_string())
+        self.0.get("Vcs-Darcs")
     }
 
     /// Set the Darcs VCS
@@ -192,7 +228,7 @@ impl Source {
 
     /// Get the Mtn VCS
     pub fn vcs_mtn(&self) -> Option<String> {
-        self.0.get("Vcs-Mtn").map(|s| s.to_string())
+        self.0.get("Vcs-Mtn")
     }
 
     /// Set the Mtn VCS
@@ -202,7 +238,7 @@ impl Source {
 
     /// Get the Cvs VCS
     pub fn vcs_cvs(&self) -> Option<String> {
-        self.0.get("Vcs-Cvs").map(|s| s.to_string())
+        self.0.get("Vcs-Cvs")
     }
 
     /// Set the Cvs VCS
@@ -211,8 +247,8 @@ impl Source {
     }
 
     /// Get the build depends
-    pub fn build_depends(&self) -> Option<Relations> {
-        self.0.get("Build-Depends").map(|s| s.parse().unwrap())
+    pub fn build_depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Build-Depends")
     }
 
     /// Set the build depends
@@ -221,10 +257,8 @@ impl Source {
     }
 
     /// Get the arch-independent build depends
-    pub fn build_depends_indep(&self) -> Option<Relations> {
-        self.0
-            .get("Build-Depends-Indep")
-            .map(|s| s.parse().unwrap())
+    pub fn build_depends_indep(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Build-Depends-Indep")
     }
 
     /// Set the arch-independent build depends
@@ -233,8 +267,8 @@ impl Source {
     }
 
     /// Get the arch-dependent build depends
-    pub fn build_depends_arch(&self) -> Option<Relations> {
-        self.0.get("Build-Depends-Arch").map(|s| s.parse().unwrap())
+    pub fn build_depends_arch(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Build-Depends-Arch")
     }
 
     /// Set the arch-dependent build depends
@@ -243,8 +277,8 @@ impl Source {
     }
 
     /// Get the build conflicts
-    pub fn build_conflicts(&self) -> Option<Relations> {
-        self.0.get("Build-Conflicts").map(|s| s.parse().unwrap())
+    pub fn build_conflicts(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Build-Conflicts")
     }
 
     /// Set the build conflicts
@@ -253,10 +287,8 @@ impl Source {
     }
 
     /// Get the build conflicts indep
-    pub fn build_conflicts_indep(&self) -> Option<Relations> {
-        self.0
-            .get("Build-Conflicts-Indep")
-            .map(|s| s.parse().unwrap())
+    pub fn build_conflicts_indep(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Build-Conflicts-Indep")
     }
 
     /// Set the build conflicts indep
@@ -265,10 +297,8 @@ impl Source {
     }
 
     /// Get the build conflicts arch
-    pub fn build_conflicts_arch(&self) -> Option<Relations> {
-        self.0
-            .get("Build-Conflicts-Arch")
-            .map(|s| s.parse().unwrap())
+    pub fn build_conflicts_arch(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Build-Conflicts-Arch")
     }
 
     /// Set the build conflicts arch
@@ -277,8 +307,8 @@ impl Source {
     }
 
     /// Get the binary relations
-    pub fn binary(&self) -> Option<Relations> {
-        self.0.get("Binary").map(|s| s.parse().unwrap())
+    pub fn binary(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Binary")
     }
 
     /// Set the binary relations
@@ -288,7 +318,7 @@ impl Source {
 
     /// Get the homepage of the package.
     pub fn homepage(&self) -> Option<String> {
-        self.0.get("Homepage").map(|s| s.to_string())
+        self.0.get("Homepage")
     }
 
     /// Set the homepage of the package.
@@ -298,7 +328,7 @@ impl Source {
 
     /// Get the section of the package.
     pub fn section(&self) -> Option<String> {
-        self.0.get("Section").map(|s| s.to_string())
+        self.0.get("Section")
     }
 
     /// Set the section of the package.
@@ -308,7 +338,7 @@ impl Source {
 
     /// Get the priority of the package.
     pub fn priority(&self) -> Option<Priority> {
-        self.0.get("Priority").and_then(|v| v.parse().ok())
+        self.0.get_ref("Priority").and_then(|v| v.parse().ok())
     }
 
     /// Set the priority of the package.
@@ -328,7 +358,7 @@ impl Source {
 
     /// Get the directory
     pub fn directory(&self) -> Option<String> {
-        self.0.get("Directory").map(|s| s.to_string())
+        self.0.get("Directory")
     }
 
     /// Set the directory
@@ -338,7 +368,7 @@ impl Source {
 
     /// Get the test suite
     pub fn testsuite(&self) -> Option<String> {
-        self.0.get("Testsuite").map(|s| s.to_string())
+        self.0.get("Testsuite")
     }
 
     /// Set the testsuite
@@ -347,15 +377,8 @@ impl Source {
     }
 
     /// Get the files
-    pub fn files(&self) -> Vec<Md5Checksum> {
-        self.0
-            .get("Files")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Md5Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn files(&self) -> Result<Vec<Md5Checksum>, FieldError> {
+        parse_lines(&self.0, "Files")
     }
 
     /// Set the files
@@ -371,15 +394,8 @@ impl Source {
     }
 
     /// Get the SHA1 checksums
-    pub fn checksums_sha1(&self) -> Vec<Sha1Checksum> {
-        self.0
-            .get("Checksums-Sha1")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha1Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha1(&self) -> Result<Vec<Sha1Checksum>, FieldError> {
+        parse_lines(&self.0, "Checksums-Sha1")
     }
 
     /// Set the SHA1 checksums
@@ -395,15 +411,8 @@ impl Source {
     }
 
     /// Get the SHA256 checksums
-    pub fn checksums_sha256(&self) -> Vec<Sha256Checksum> {
-        self.0
-            .get("Checksums-Sha256")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha256Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha256(&self) -> Result<Vec<Sha256Checksum>, FieldError> {
+        parse_lines(&self.0, "Checksums-Sha256")
     }
 
     /// Set the SHA256 checksums
@@ -419,15 +428,8 @@ impl Source {
     }
 
     /// Get the SHA512 checksums
-    pub fn checksums_sha512(&self) -> Vec<Sha512Checksum> {
-        self.0
-            .get("Checksums-Sha512")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha512Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha512(&self) -> Result<Vec<Sha512Checksum>, FieldError> {
+        parse_lines(&self.0, "Checksums-Sha512")
     }
 
     /// Set the SHA512 checksums
@@ -441,6 +443,31 @@ impl Source {
                 .join("\n"),
         );
     }
+
+    /// Get the checksums of the files computed with the given algorithm.
+    ///
+    /// Returns an empty list if the field is not present.
+    pub fn checksums(
+        &self,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<ChecksumList, crate::ControlError> {
+        match self.0.get(algorithm.source_field()) {
+            Some(value) => ChecksumList::parse(algorithm, &value),
+            None => Ok(ChecksumList::new(algorithm)),
+        }
+    }
+
+    /// Set the checksums of the files, in the field for the algorithm of the list.
+    ///
+    /// The field is removed if the list is empty.
+    pub fn set_checksums(&mut self, checksums: &ChecksumList) {
+        let field = checksums.algorithm.source_field();
+        if checksums.is_empty() {
+            self.0.remove(field);
+        } else {
+            self.0.set(field, &checksums.to_string());
+        }
+    }
 }
 
 impl std::str::FromStr for Source {
@@ -452,6 +479,7 @@ impl std::str::FromStr for Source {
 }
 
 /// A package in the APT package manager.
+#[derive(PartialEq, Eq, Hash)]
 pub struct Package(deb822_lossless::Paragraph);
 
 #[cfg(feature = "python-debian")]
@@ -475,6 +503,39 @@ impl pyo3::FromPyObject<'_> for Package {
     }
 }
 
+#[cfg(feature = "json")]
+impl Package {
+    /// Convert the package to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> serde_json::Value {
+        self.to_json_with(&crate::json::Options::default())
+    }
+
+    /// Convert the package to JSON, with the given options.
+    pub fn to_json_with(&self, options: &crate::json::Options) -> serde_json::Value {
+        crate::json::paragraph_to_json(&self.0, options)
+    }
+
+    /// Create a package from its JSON representation.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, crate::json::Error> {
+        let mut paragraph = deb822_lossless::Paragraph::new();
+        crate::json::paragraph_from_json(&mut paragraph, value, "package")?;
+        Ok(Self(paragraph))
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Package {
+    /// Convert the package to YAML, using the same structure as [`Package::to_json`].
+    pub fn to_yaml(&self) -> String {
+        self.to_yaml_with(&crate::json::Options::default())
+    }
+
+    /// Convert the package to YAML, with the given options.
+    pub fn to_yaml_with(&self, options: &crate::json::Options) -> String {
+        crate::json::to_yaml(&self.to_json_with(options))
+    }
+}
+
 impl Package {
     /// Create a new package.
     pub fn new(paragraph: deb822_lossless::Paragraph) -> Self {
@@ -483,7 +544,7 @@ impl Package {
 
     /// Get the name of the package.
     pub fn name(&self) -> Option<String> {
-        self.0.get("Package").map(|s| s.to_string())
+        self.0.get("Package")
     }
 
     /// Set the name of the package.
@@ -492,8 +553,8 @@ impl Package {
     }
 
     /// Get the version of the package.
-    pub fn version(&self) -> Option<debversion::Version> {
-        self.0.get("Version").map(|s| s.parse().unwrap())
+    pub fn version(&self) -> Option<Result<debversion::Version, FieldError>> {
+        parse_field(&self.0, "Version")
     }
 
     /// Set the version of the package.
@@ -502,8 +563,8 @@ impl Package {
     }
 
     /// Get the installed size of the package in bytes.
-    pub fn installed_size(&self) -> Option<usize> {
-        self.0.get("Installed-Size").map(|s| s.parse().unwrap())
+    pub fn installed_size(&self) -> Option<Result<usize, FieldError>> {
+        parse_field(&self.0, "Installed-Size")
     }
 
     /// Set the installed size of the package in bytes.
@@ -513,7 +574,7 @@ impl Package {
 
     /// Get the maintainer of the package.
     pub fn maintainer(&self) -> Option<String> {
-        self.0.get("Maintainer").map(|s| s.to_string())
+        self.0.get("Maintainer")
     }
 
     /// Set the maintainer of the package.
@@ -523,7 +584,7 @@ impl Package {
 
     /// Get the architecture of the package.
     pub fn architecture(&self) -> Option<String> {
-        self.0.get("Architecture").map(|s| s.to_string())
+        self.0.get("Architecture")
     }
 
     /// Set the architecture of the package.
@@ -532,8 +593,8 @@ impl Package {
     }
 
     /// Get the packages that this package depends on.
-    pub fn depends(&self) -> Option<Relations> {
-        self.0.get("Depends").map(|s| s.parse().unwrap())
+    pub fn depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Depends")
     }
 
     /// Set the packages that this package depends on.
@@ -542,8 +603,8 @@ impl Package {
     }
 
     /// Get the packages that this package suggests.
-    pub fn recommends(&self) -> Option<Relations> {
-        self.0.get("Recommends").map(|s| s.parse().unwrap())
+    pub fn recommends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Recommends")
     }
 
     /// Set the packages that this package recommends.
@@ -552,8 +613,8 @@ impl Package {
     }
 
     /// Get the packages that this package suggests.
-    pub fn suggests(&self) -> Option<Relations> {
-        self.0.get("Suggests").map(|s| s.parse().unwrap())
+    pub fn suggests(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Suggests")
     }
 
     /// Set the packages that this package suggests.
@@ -562,8 +623,8 @@ impl Package {
     }
 
     /// Get the packages that this package enhances.
-    pub fn enhances(&self) -> Option<Relations> {
-        self.0.get("Enhances").map(|s| s.parse().unwrap())
+    pub fn enhances(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Enhances")
     }
 
     /// Set the packages that this package enhances.
@@ -572,8 +633,8 @@ impl Package {
     }
 
     /// Get the relations that this package pre-depends on.
-    pub fn pre_depends(&self) -> Option<Relations> {
-        self.0.get("Pre-Depends").map(|s| s.parse().unwrap())
+    pub fn pre_depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Pre-Depends")
     }
 
     /// Set the relations that this package pre-depends on.
@@ -582,8 +643,8 @@ impl Package {
     }
 
     /// Get the relations that this package breaks.
-    pub fn breaks(&self) -> Option<Relations> {
-        self.0.get("Breaks").map(|s| s.parse().unwrap())
+    pub fn breaks(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Breaks")
     }
 
     /// Set the relations that this package breaks.
@@ -592,8 +653,8 @@ impl Package {
     }
 
     /// Get the relations that this package conflicts with.
-    pub fn conflicts(&self) -> Option<Relations> {
-        self.0.get("Conflicts").map(|s| s.parse().unwrap())
+    pub fn conflicts(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Conflicts")
     }
 
     /// Set the relations that this package conflicts with.
@@ -602,8 +663,8 @@ impl Package {
     }
 
     /// Get the relations that this package replaces.
-    pub fn replaces(&self) -> Option<Relations> {
-        self.0.get("Replaces").map(|s| s.parse().unwrap())
+    pub fn replaces(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Replaces")
     }
 
     /// Set the relations that this package replaces.
@@ -612,8 +673,8 @@ impl Package {
     }
 
     /// Get the relations that this package provides.
-    pub fn provides(&self) -> Option<Relations> {
-        self.0.get("Provides").map(|s| s.parse().unwrap())
+    pub fn provides(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Provides")
     }
 
     /// Set the relations that the package provides.
@@ -623,7 +684,7 @@ impl Package {
 
     /// Get the section of the package.
     pub fn section(&self) -> Option<String> {
-        self.0.get("Section").map(|s| s.to_string())
+        self.0.get("Section")
     }
 
     /// Set the section of the package.
@@ -633,7 +694,7 @@ impl Package {
 
     /// Get the priority of the package.
     pub fn priority(&self) -> Option<Priority> {
-        self.0.get("Priority").and_then(|v| v.parse().ok())
+        self.0.get_ref("Priority").and_then(|v| v.parse().ok())
     }
 
     /// Set the priority of the package.
@@ -643,7 +704,7 @@ impl Package {
 
     /// Get the description of the package.
     pub fn description(&self) -> Option<String> {
-        self.0.get("Description").map(|s| s.to_string())
+        self.0.get("Description")
     }
 
     /// Set the description of the package.
@@ -652,8 +713,8 @@ impl Package {
     }
 
     /// Get the upstream homepage of the package.
-    pub fn homepage(&self) -> Option<url::Url> {
-        self.0.get("Homepage").map(|s| s.parse().unwrap())
+    pub fn homepage(&self) -> Option<Result<url::Url, FieldError>> {
+        parse_field(&self.0, "Homepage")
     }
 
     /// Set the upstream homepage of the package.
@@ -663,7 +724,7 @@ impl Package {
 
     /// Get the source of the package.
     pub fn source(&self) -> Option<String> {
-        self.0.get("Source").map(|s| s.to_string())
+        self.0.get("Source")
     }
 
     /// Set the source of the package.
@@ -673,7 +734,7 @@ impl Package {
 
     /// Get the MD5 checksum of the description.
     pub fn description_md5(&self) -> Option<String> {
-        self.0.get("Description-md5").map(|s| s.to_string())
+        self.0.get("Description-md5")
     }
 
     /// Set the MD5 checksum of the description.
@@ -681,6 +742,20 @@ impl Package {
         self.0.set("Description-md5", md5);
     }
 
+    /// Get the description of the package in a particular language.
+    ///
+    /// The translation is looked up in `translations` using the `Description-md5` field. If
+    /// there is no translation for the given language, the English description is returned.
+    pub fn description_localized(
+        &self,
+        lang: &str,
+        translations: &crate::lossy::apt::TranslationFile,
+    ) -> Option<String> {
+        self.description_md5()
+            .and_then(|md5| translations.description(&md5, lang).map(|d| d.to_string()))
+            .or_else(|| self.description())
+    }
+
     /// Get the tags of the package.
     pub fn tags(&self, tag: &str) -> Option<Vec<String>> {
         self.0
@@ -695,7 +770,7 @@ impl Package {
 
     /// Get the filename of the package.
     pub fn filename(&self) -> Option<String> {
-        self.0.get("Filename").map(|s| s.to_string())
+        self.0.get("Filename")
     }
 
     /// Set the filename of the package.
@@ -704,8 +779,8 @@ impl Package {
     }
 
     /// Get the size of the package.
-    pub fn size(&self) -> Option<usize> {
-        self.0.get("Size").map(|s| s.parse().unwrap())
+    pub fn size(&self) -> Option<Result<usize, FieldError>> {
+        parse_field(&self.0, "Size")
     }
 
     /// Set the size of the package.
@@ -715,7 +790,7 @@ impl Package {
 
     /// Get the MD5 checksum.
     pub fn md5sum(&self) -> Option<String> {
-        self.0.get("MD5sum").map(|s| s.to_string())
+        self.0.get("MD5sum")
     }
 
     /// Set the MD5 checksum.
@@ -725,7 +800,7 @@ impl Package {
 
     /// Get the SHA256 checksum.
     pub fn sha256(&self) -> Option<String> {
-        self.0.get("SHA256").map(|s| s.to_string())
+        self.0.get("SHA256")
     }
 
     /// Set the SHA256 checksum.
@@ -734,8 +809,8 @@ impl Package {
     }
 
     /// Get the multi-arch field.
-    pub fn multi_arch(&self) -> Option<MultiArch> {
-        self.0.get("Multi-Arch").map(|s| s.parse().unwrap())
+    pub fn multi_arch(&self) -> Option<Result<MultiArch, FieldError>> {
+        parse_field(&self.0, "Multi-Arch")
     }
 
     /// Set the multi-arch field.
@@ -753,6 +828,7 @@ impl std::str::FromStr for Package {
 }
 
 /// A release in the APT package manager.
+#[derive(PartialEq, Eq, Hash)]
 pub struct Release(deb822_lossless::Paragraph);
 
 #[cfg(feature = "python-debian")]
@@ -784,7 +860,7 @@ impl Release {
 
     /// Get the origin of the release
     pub fn origin(&self) -> Option<String> {
-        self.0.get("Origin").map(|s| s.to_string())
+        self.0.get("Origin")
     }
 
     /// Set the origin of the release
@@ -794,7 +870,7 @@ impl Release {
 
     /// Get the label of the release
     pub fn label(&self) -> Option<String> {
-        self.0.get("Label").map(|s| s.to_string())
+        self.0.get("Label")
     }
 
     /// Set the label of the release
@@ -804,7 +880,7 @@ impl Release {
 
     /// Get the suite of the release
     pub fn suite(&self) -> Option<String> {
-        self.0.get("Suite").map(|s| s.to_string())
+        self.0.get("Suite")
     }
 
     /// Set the suite of the release
@@ -814,7 +890,7 @@ impl Release {
 
     /// Get the codename of the release
     pub fn codename(&self) -> Option<String> {
-        self.0.get("Codename").map(|s| s.to_string())
+        self.0.get("Codename")
     }
 
     /// Set the codename of the release
@@ -838,11 +914,10 @@ impl Release {
 
     #[cfg(feature = "chrono")]
     /// Get the date of the release
-    pub fn date(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
-        self.0
-            .get("Date")
-            .as_ref()
-            .map(|s| chrono::DateTime::parse_from_rfc2822(s).unwrap())
+    pub fn date(&self) -> Option<Result<chrono::DateTime<chrono::FixedOffset>, FieldError>> {
+        self.0.get_ref("Date").map(|s| {
+            chrono::DateTime::parse_from_rfc2822(&s).map_err(|e| FieldError::new("Date", &s, e))
+        })
     }
 
     #[cfg(feature = "chrono")]
@@ -853,11 +928,11 @@ impl Release {
 
     #[cfg(feature = "chrono")]
     /// Get the date until the release is valid
-    pub fn valid_until(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
-        self.0
-            .get("Valid-Until")
-            .as_ref()
-            .map(|s| chrono::DateTime::parse_from_rfc2822(s).unwrap())
+    pub fn valid_until(&self) -> Option<Result<chrono::DateTime<chrono::FixedOffset>, FieldError>> {
+        self.0.get_ref("Valid-Until").map(|s| {
+            chrono::DateTime::parse_from_rfc2822(&s)
+                .map_err(|e| FieldError::new("Valid-Until", &s, e))
+        })
     }
 
     #[cfg(feature = "chrono")]
@@ -932,7 +1007,7 @@ impl Release {
 
     /// Get the description
     pub fn description(&self) -> Option<String> {
-        self.0.get("Description").map(|s| s.to_string())
+        self.0.get("Description")
     }
 
     /// Set the description
@@ -941,15 +1016,8 @@ impl Release {
     }
 
     /// Get the MD5 checksums
-    pub fn checksums_md5(&self) -> Vec<Md5Checksum> {
-        self.0
-            .get("MD5Sum")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Md5Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_md5(&self) -> Result<Vec<Md5Checksum>, FieldError> {
+        parse_lines(&self.0, "MD5Sum")
     }
 
     /// Set the MD5 checksums
@@ -965,15 +1033,8 @@ impl Release {
     }
 
     /// Get the SHA1 checksums
-    pub fn checksums_sha1(&self) -> Vec<Sha1Checksum> {
-        self.0
-            .get("SHA1")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha1Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha1(&self) -> Result<Vec<Sha1Checksum>, FieldError> {
+        parse_lines(&self.0, "SHA1")
     }
 
     /// Set the SHA1 checksums
@@ -989,15 +1050,8 @@ impl Release {
     }
 
     /// Get the SHA256 checksums
-    pub fn checksums_sha256(&self) -> Vec<Sha256Checksum> {
-        self.0
-            .get("SHA256")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha256Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha256(&self) -> Result<Vec<Sha256Checksum>, FieldError> {
+        parse_lines(&self.0, "SHA256")
     }
 
     /// Set the SHA256 checksums
@@ -1013,15 +1067,8 @@ impl Release {
     }
 
     /// Get the SHA512 checksums
-    pub fn checksums_sha512(&self) -> Vec<Sha512Checksum> {
-        self.0
-            .get("SHA512")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha512Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha512(&self) -> Result<Vec<Sha512Checksum>, FieldError> {
+        parse_lines(&self.0, "SHA512")
     }
 
     /// Set the SHA512 checksums
@@ -1035,6 +1082,31 @@ impl Release {
                 .join("\n"),
         );
     }
+
+    /// Get the checksums of the files computed with the given algorithm.
+    ///
+    /// Returns an empty list if the field is not present.
+    pub fn checksums(
+        &self,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<ChecksumList, crate::ControlError> {
+        match self.0.get(algorithm.release_field()) {
+            Some(value) => ChecksumList::parse(algorithm, &value),
+            None => Ok(ChecksumList::new(algorithm)),
+        }
+    }
+
+    /// Set the checksums of the files, in the field for the algorithm of the list.
+    ///
+    /// The field is removed if the list is empty.
+    pub fn set_checksums(&mut self, checksums: &ChecksumList) {
+        let field = checksums.algorithm.release_field();
+        if checksums.is_empty() {
+            self.0.remove(field);
+        } else {
+            self.0.set(field, &checksums.to_string());
+        }
+    }
 }
 
 impl std::str::FromStr for Release {
@@ -1045,6 +1117,8 @@ impl std::str::FromStr for Release {
     }
 }
 
+impl_semantic_eq!(Source, Package, Release);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1109,6 +1183,47 @@ mod tests {
         assert_eq!(f.filename, "filename");
     }
 
+    #[test]
+    fn test_checksum_list() {
+        let mut p: super::Source = r#"Package: foo
+Files:
+ d41d8cd98f00b204e9800998ecf8427e 0 foo_1.0.dsc
+ 0cc175b9c0f1b6a831c399e269772661 1 foo_1.0.tar.gz
+Checksums-Sha1:
+ da39a3ee5e6b4b0d3255bfef95601890afd80709 0 foo_1.0.dsc
+"#
+        .parse()
+        .unwrap();
+        let files = p.checksums(ChecksumAlgorithm::Md5).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files.get("foo_1.0.tar.gz").unwrap().size, 1);
+        assert_eq!(p.checksums(ChecksumAlgorithm::Sha1).unwrap().len(), 1);
+        assert!(p.checksums(ChecksumAlgorithm::Sha256).unwrap().is_empty());
+
+        let mut sha256 = ChecksumList::new(ChecksumAlgorithm::Sha256);
+        sha256.insert(crate::fields::ChecksumEntry {
+            digest: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+            size: 0,
+            filename: "foo_1.0.dsc".to_string(),
+        });
+        p.set_checksums(&sha256);
+        p.set_checksums(&ChecksumList::new(ChecksumAlgorithm::Sha1));
+        assert_eq!(
+            p.0.to_string(),
+            r#"Package: foo
+Files:
+ d41d8cd98f00b204e9800998ecf8427e 0 foo_1.0.dsc
+ 0cc175b9c0f1b6a831c399e269772661 1 foo_1.0.tar.gz
+Checksums-Sha256:
+ e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 0 foo_1.0.dsc
+"#
+        );
+        assert_eq!(p.checksums(ChecksumAlgorithm::Sha256).unwrap(), sha256);
+
+        p.0.set("Files", "\nd41d8cd98f00b204e9800998ecf8427e foo_1.0.dsc");
+        assert!(p.checksums(ChecksumAlgorithm::Md5).is_err());
+    }
+
     #[test]
     fn test_source() {
         let s = r#"Package: foo
@@ -1138,7 +1253,7 @@ Checksums-Sha1:
 "#;
         let p: super::Source = s.parse().unwrap();
         assert_eq!(p.package(), Some("foo".to_string()));
-        assert_eq!(p.version(), Some("1.0".parse().unwrap()));
+        assert_eq!(p.version(), Some(Ok("1.0".parse().unwrap())));
         assert_eq!(
             p.maintainer(),
             Some("John Doe <john@example.com>".to_string())
@@ -1152,30 +1267,36 @@ Checksums-Sha1:
         assert_eq!(p.vcs_browser(), Some("https://example.com/foo".to_string()));
         assert_eq!(p.vcs_git(), Some("https://example.com/foo.git".to_string()));
         assert_eq!(
-            p.build_depends_indep().map(|x| x.to_string()),
+            p.build_depends_indep().map(|x| x.unwrap().to_string()),
             Some("python".parse().unwrap())
         );
-        assert_eq!(p.build_depends(), Some("debhelper (>= 9)".parse().unwrap()));
-        assert_eq!(p.build_depends_arch(), Some("gcc".parse().unwrap()));
-        assert_eq!(p.build_conflicts(), Some("bar".parse().unwrap()));
-        assert_eq!(p.build_conflicts_indep(), Some("python".parse().unwrap()));
-        assert_eq!(p.build_conflicts_arch(), Some("gcc".parse().unwrap()));
-        assert_eq!(p.binary(), Some("foo, bar".parse().unwrap()));
+        assert_eq!(
+            p.build_depends(),
+            Some(Ok("debhelper (>= 9)".parse().unwrap()))
+        );
+        assert_eq!(p.build_depends_arch(), Some(Ok("gcc".parse().unwrap())));
+        assert_eq!(p.build_conflicts(), Some(Ok("bar".parse().unwrap())));
+        assert_eq!(
+            p.build_conflicts_indep(),
+            Some(Ok("python".parse().unwrap()))
+        );
+        assert_eq!(p.build_conflicts_arch(), Some(Ok("gcc".parse().unwrap())));
+        assert_eq!(p.binary(), Some(Ok("foo, bar".parse().unwrap())));
         assert_eq!(p.homepage(), Some("https://example.com/foo".to_string()));
         assert_eq!(p.section(), Some("devel".to_string()));
         assert_eq!(p.priority(), Some(super::Priority::Optional));
         assert_eq!(p.architecture(), Some("any".to_string()));
         assert_eq!(p.directory(), Some("pool/main/f/foo".to_string()));
-        assert_eq!(p.files().len(), 1);
+        assert_eq!(p.files().unwrap().len(), 1);
         assert_eq!(
-            p.files()[0].md5sum,
+            p.files().unwrap()[0].md5sum,
             "25dcf3b4b6b3b3b3b3b3b3b3b3b3b3b3".to_string()
         );
-        assert_eq!(p.files()[0].size, 1234);
-        assert_eq!(p.files()[0].filename, "foo_1.0.tar.gz".to_string());
-        assert_eq!(p.checksums_sha1().len(), 1);
+        assert_eq!(p.files().unwrap()[0].size, 1234);
+        assert_eq!(p.files().unwrap()[0].filename, "foo_1.0.tar.gz".to_string());
+        assert_eq!(p.checksums_sha1().unwrap().len(), 1);
         assert_eq!(
-            p.checksums_sha1()[0].sha1,
+            p.checksums_sha1().unwrap()[0].sha1,
             "b72b5fae3b3b3b3b3b3b3b3b3b3b3b3".to_string()
         );
     }
@@ -1211,28 +1332,28 @@ Multi-Arch: same
 "#;
         let p: super::Package = s.parse().unwrap();
         assert_eq!(p.name(), Some("foo".to_string()));
-        assert_eq!(p.version(), Some("1.0".parse().unwrap()));
+        assert_eq!(p.version(), Some(Ok("1.0".parse().unwrap())));
         assert_eq!(p.source(), Some("bar".to_string()));
         assert_eq!(
             p.maintainer(),
             Some("John Doe <john@example.com>".to_string())
         );
         assert_eq!(p.architecture(), Some("any".to_string()));
-        assert_eq!(p.depends(), Some("bar".parse().unwrap()));
-        assert_eq!(p.recommends(), Some("baz".parse().unwrap()));
-        assert_eq!(p.suggests(), Some("qux".parse().unwrap()));
-        assert_eq!(p.enhances(), Some("quux".parse().unwrap()));
-        assert_eq!(p.pre_depends(), Some("quuz".parse().unwrap()));
-        assert_eq!(p.breaks(), Some("corge".parse().unwrap()));
-        assert_eq!(p.conflicts(), Some("grault".parse().unwrap()));
-        assert_eq!(p.replaces(), Some("garply".parse().unwrap()));
-        assert_eq!(p.provides(), Some("waldo".parse().unwrap()));
+        assert_eq!(p.depends(), Some(Ok("bar".parse().unwrap())));
+        assert_eq!(p.recommends(), Some(Ok("baz".parse().unwrap())));
+        assert_eq!(p.suggests(), Some(Ok("qux".parse().unwrap())));
+        assert_eq!(p.enhances(), Some(Ok("quux".parse().unwrap())));
+        assert_eq!(p.pre_depends(), Some(Ok("quuz".parse().unwrap())));
+        assert_eq!(p.breaks(), Some(Ok("corge".parse().unwrap())));
+        assert_eq!(p.conflicts(), Some(Ok("grault".parse().unwrap())));
+        assert_eq!(p.replaces(), Some(Ok("garply".parse().unwrap())));
+        assert_eq!(p.provides(), Some(Ok("waldo".parse().unwrap())));
         assert_eq!(p.section(), Some("devel".to_string()));
         assert_eq!(p.priority(), Some(super::Priority::Optional));
         assert_eq!(p.description(), Some("Foo is a bar".to_string()));
         assert_eq!(
             p.homepage(),
-            Some(url::Url::parse("https://example.com/foo").unwrap())
+            Some(Ok(url::Url::parse("https://example.com/foo").unwrap()))
         );
         assert_eq!(p.description_md5(), Some("1234".to_string()));
         assert_eq!(
@@ -1243,11 +1364,30 @@ Multi-Arch: same
             p.filename(),
             Some("pool/main/f/foo/foo_1.0.deb".to_string())
         );
-        assert_eq!(p.size(), Some(1234));
-        assert_eq!(p.installed_size(), Some(1234));
+        assert_eq!(p.size(), Some(Ok(1234)));
+        assert_eq!(p.installed_size(), Some(Ok(1234)));
         assert_eq!(p.md5sum(), Some("1234".to_string()));
         assert_eq!(p.sha256(), Some("1234".to_string()));
-        assert_eq!(p.multi_arch(), Some(MultiArch::Same));
+        assert_eq!(p.multi_arch(), Some(Ok(MultiArch::Same)));
+    }
+
+    #[test]
+    fn test_description_localized() {
+        let p: Package = "Package: test\nDescription: A test package\nDescription-md5: 1234\n"
+            .parse()
+            .unwrap();
+        let translations: crate::lossy::apt::TranslationFile =
+            "Package: test\nDescription-md5: 1234\nDescription-de: Ein Testpaket\n"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            p.description_localized("de", &translations),
+            Some("Ein Testpaket".to_string())
+        );
+        assert_eq!(
+            p.description_localized("fr", &translations),
+            Some("A test package".to_string())
+        );
     }
 
     #[test]
@@ -1283,6 +1423,6 @@ Multi-Arch: same
             release.description(),
             Some("Debian x.y Testing distribution - Not Released".to_string())
         );
-        assert_eq!(318, release.checksums_md5().len());
+        assert_eq!(318, release.checksums_md5().unwrap().len());
     }
 }
@@ -4,10 +4,12 @@
 //! information about the build environment of a package. See https://wiki.debian.org/Buildinfo for
 //! more information.
 
+use crate::error::{parse_field, parse_lines, FieldError};
 use crate::fields::{Md5Checksum, Sha1Checksum, Sha256Checksum};
 use crate::lossless::relations::Relations;
 
 /// A buildinfo file
+#[derive(PartialEq, Eq, Hash)]
 pub struct Buildinfo(deb822_lossless::Paragraph);
 
 impl From<deb822_lossless::Paragraph> for Buildinfo {
@@ -32,7 +34,7 @@ impl Buildinfo {
 
     /// Get the source name
     pub fn source(&self) -> Option<String> {
-        self.0.get("Source").map(|s| s.to_string())
+        self.0.get("Source")
     }
 
     /// Set the package name
@@ -55,8 +57,8 @@ impl Buildinfo {
     }
 
     /// Get the version of the package
-    pub fn version(&self) -> Option<debversion::Version> {
-        self.0.get("Version").map(|s| s.parse().unwrap())
+    pub fn version(&self) -> Option<Result<debversion::Version, FieldError>> {
+        parse_field(&self.0, "Version")
     }
 
     /// Set the version of the package
@@ -66,7 +68,7 @@ impl Buildinfo {
 
     /// Get the build architecture
     pub fn build_architecture(&self) -> Option<String> {
-        self.0.get("Build-Architecture").map(|s| s.to_string())
+        self.0.get("Build-Architecture")
     }
 
     /// Set the build architecture
@@ -76,7 +78,7 @@ impl Buildinfo {
 
     /// Get the architecture
     pub fn architecture(&self) -> Option<String> {
-        self.0.get("Architecture").map(|s| s.to_string())
+        self.0.get("Architecture")
     }
 
     /// Set the architecture
@@ -85,15 +87,8 @@ impl Buildinfo {
     }
 
     /// Get Sha256 checksums
-    pub fn checksums_sha256(&self) -> Vec<Sha256Checksum> {
-        self.0
-            .get("Checksums-Sha256")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha256Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha256(&self) -> Result<Vec<Sha256Checksum>, FieldError> {
+        parse_lines(&self.0, "Checksums-Sha256")
     }
 
     /// Set Sha256 checksums
@@ -109,15 +104,8 @@ impl Buildinfo {
     }
 
     /// Get SHA1 checksums
-    pub fn checksums_sha1(&self) -> Vec<Sha1Checksum> {
-        self.0
-            .get("Checksums-Sha1")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Sha1Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_sha1(&self) -> Result<Vec<Sha1Checksum>, FieldError> {
+        parse_lines(&self.0, "Checksums-Sha1")
     }
 
     /// Set SHA1 checksums
@@ -133,15 +121,8 @@ impl Buildinfo {
     }
 
     /// Get MD5 checksums
-    pub fn checksums_md5(&self) -> Vec<Md5Checksum> {
-        self.0
-            .get("Checksums-Md5")
-            .map(|s| {
-                s.lines()
-                    .map(|line| line.parse().unwrap())
-                    .collect::<Vec<Md5Checksum>>()
-            })
-            .unwrap_or_default()
+    pub fn checksums_md5(&self) -> Result<Vec<Md5Checksum>, FieldError> {
+        parse_lines(&self.0, "Checksums-Md5")
     }
 
     /// Set MD5 checksums
@@ -158,7 +139,7 @@ impl Buildinfo {
 
     /// Get the build origin
     pub fn build_origin(&self) -> Option<String> {
-        self.0.get("Build-Origin").map(|s| s.to_string())
+        self.0.get("Build-Origin")
     }
 
     /// Set the build origin
@@ -168,7 +149,7 @@ impl Buildinfo {
 
     /// Date on which the package was built
     pub fn build_date(&self) -> Option<String> {
-        self.0.get("Build-Date").map(|s| s.to_string())
+        self.0.get("Build-Date")
     }
 
     /// Set the build date
@@ -190,7 +171,7 @@ impl Buildinfo {
 
     /// Get the source format of the package
     pub fn format(&self) -> Option<String> {
-        self.0.get("Format").map(|s| s.to_string())
+        self.0.get("Format")
     }
 
     /// Set the format of the package
@@ -200,7 +181,7 @@ impl Buildinfo {
 
     /// Get the build path
     pub fn build_path(&self) -> Option<String> {
-        self.0.get("Build-Path").map(|s| s.to_string())
+        self.0.get("Build-Path")
     }
 
     /// Set the build path
@@ -209,12 +190,16 @@ impl Buildinfo {
     }
 
     /// Get the build environment
-    pub fn environment(&self) -> Option<std::collections::HashMap<String, String>> {
+    pub fn environment(
+        &self,
+    ) -> Option<Result<std::collections::HashMap<String, String>, FieldError>> {
         self.0.get("Environment").map(|s| {
             s.lines()
                 .map(|line| {
-                    let (key, value) = line.split_once('=').unwrap();
-                    (key.to_string(), value.to_string())
+                    let (key, value) = line.split_once('=').ok_or_else(|| {
+                        FieldError::new("Environment", line, "expected a KEY=VALUE pair")
+                    })?;
+                    Ok((key.to_string(), value.to_string()))
                 })
                 .collect()
         })
@@ -230,10 +215,8 @@ impl Buildinfo {
     }
 
     /// Get the list of installed build depends
-    pub fn installed_build_depends(&self) -> Option<Relations> {
-        self.0
-            .get("Installed-Build-Depends")
-            .map(|s| s.parse().unwrap())
+    pub fn installed_build_depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_field(&self.0, "Installed-Build-Depends")
     }
 
     /// Set the list of installed build depends
@@ -249,6 +232,8 @@ impl std::str::FromStr for Buildinfo {
         Ok(Self(s.parse()?))
     }
 }
+impl_semantic_eq!(Buildinfo);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,8 @@
 //! Changes files
+use crate::error::{parse_field, FieldError};
 
 /// Changes file
+#[derive(PartialEq, Eq, Hash)]
 pub struct Changes(deb822_lossless::Paragraph);
 
 /// Errors that can occur when parsing a Changes file.
@@ -60,15 +62,24 @@ impl std::fmt::Display for File {
 }
 
 impl std::str::FromStr for File {
-    type Err = ();
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::ControlError::invalid_value("file entry", s);
         let mut parts = s.split_whitespace();
-        let md5sum = parts.next().ok_or(())?;
-        let size = parts.next().ok_or(())?.parse().map_err(|_| ())?;
-        let section = parts.next().ok_or(())?.to_string();
-        let priority = parts.next().ok_or(())?.parse().map_err(|_| ())?;
-        let filename = parts.next().ok_or(())?.to_string();
+        let md5sum = parts.next().ok_or_else(invalid)?;
+        let size = parts
+            .next()
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+        let section = parts.next().ok_or_else(invalid)?.to_string();
+        let priority = parts
+            .next()
+            .ok_or_else(invalid)?
+            .parse()
+            .map_err(|_| invalid())?;
+        let filename = parts.next().ok_or_else(invalid)?.to_string();
         Ok(Self {
             md5sum: md5sum.to_string(),
             size,
@@ -82,7 +93,7 @@ impl std::str::FromStr for File {
 impl Changes {
     /// Returns the format of the Changes file.
     pub fn format(&self) -> Option<String> {
-        self.0.get("Format").map(|s| s.to_string())
+        self.0.get("Format")
     }
 
     /// Set the format of the Changes file.
@@ -92,7 +103,7 @@ impl Changes {
 
     /// Returns the name of the source package.
     pub fn source(&self) -> Option<String> {
-        self.0.get("Source").map(|s| s.to_string())
+        self.0.get("Source")
     }
 
     /// Returns the list of binary packages generated by the source package.
@@ -110,61 +121,69 @@ impl Changes {
     }
 
     /// Returns the version of the source package.
-    pub fn version(&self) -> Option<debversion::Version> {
-        self.0.get("Version").map(|s| s.parse().unwrap())
+    pub fn version(&self) -> Option<Result<debversion::Version, FieldError>> {
+        parse_field(&self.0, "Version")
     }
 
     /// Returns the distribution the source package is intended for.
     pub fn distribution(&self) -> Option<String> {
-        self.0.get("Distribution").map(|s| s.to_string())
+        self.0.get("Distribution")
     }
 
     /// Returns the urgency of the source package.
-    pub fn urgency(&self) -> Option<crate::fields::Urgency> {
-        self.0.get("Urgency").map(|s| s.parse().unwrap())
+    pub fn urgency(&self) -> Option<Result<crate::fields::Urgency, FieldError>> {
+        parse_field(&self.0, "Urgency")
     }
 
     /// Returns the name and email address of the person who maintains the package.
     pub fn maintainer(&self) -> Option<String> {
-        self.0.get("Maintainer").map(|s| s.to_string())
+        self.0.get("Maintainer")
     }
 
     /// Returns the name and email address of the person who uploaded the package.
     pub fn changed_by(&self) -> Option<String> {
-        self.0.get("Changed-By").map(|s| s.to_string())
+        self.0.get("Changed-By")
     }
 
     /// Returns the description of the source package.
     pub fn description(&self) -> Option<String> {
-        self.0.get("Description").map(|s| s.to_string())
+        self.0.get("Description")
     }
 
     /// Returns the SHA-1 checksums of the files in the source package.
-    pub fn checksums_sha1(&self) -> Option<Vec<crate::fields::Sha1Checksum>> {
-        self.0
-            .get("Checksums-Sha1")
-            .map(|s| s.lines().map(|line| line.parse().unwrap()).collect())
+    pub fn checksums_sha1(&self) -> Option<Result<Vec<crate::fields::Sha1Checksum>, FieldError>> {
+        self.0.get_ref("Checksums-Sha1").map(|s| {
+            s.lines()
+                .map(|line| FieldError::parse("Checksums-Sha1", line))
+                .collect()
+        })
     }
 
     /// Returns the SHA-256 checksums of the files in the source package.
-    pub fn checksums_sha256(&self) -> Option<Vec<crate::fields::Sha256Checksum>> {
-        self.0
-            .get("Checksums-Sha256")
-            .map(|s| s.lines().map(|line| line.parse().unwrap()).collect())
+    pub fn checksums_sha256(
+        &self,
+    ) -> Option<Result<Vec<crate::fields::Sha256Checksum>, FieldError>> {
+        self.0.get_ref("Checksums-Sha256").map(|s| {
+            s.lines()
+                .map(|line| FieldError::parse("Checksums-Sha256", line))
+                .collect()
+        })
     }
 
     /// Returns the list of files in the source package.
-    pub fn files(&self) -> Option<Vec<File>> {
-        self.0
-            .get("Files")
-            .map(|s| s.lines().map(|line| line.parse().unwrap()).collect())
+    pub fn files(&self) -> Option<Result<Vec<File>, FieldError>> {
+        self.0.get_ref("Files").map(|s| {
+            s.lines()
+                .map(|line| FieldError::parse("Files", line))
+                .collect()
+        })
     }
 
     /// Returns the path to the pool directory for the source package.
     pub fn get_pool_path(&self) -> Option<String> {
-        let files = self.files()?;
+        let files = self.files()?.ok()?;
 
-        let section = &files.first().unwrap().section;
+        let section = &files.first()?.section;
 
         let section = if let Some((section, _subsection)) = section.split_once('/') {
             section
@@ -272,6 +291,28 @@ impl pyo3::FromPyObject<'_> for Changes {
     }
 }
 
+#[cfg(feature = "json")]
+impl Changes {
+    /// Convert the changes file to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> serde_json::Value {
+        self.to_json_with(&crate::json::Options::default())
+    }
+
+    /// Convert the changes file to JSON, with the given options.
+    pub fn to_json_with(&self, options: &crate::json::Options) -> serde_json::Value {
+        crate::json::paragraph_to_json(&self.0, options)
+    }
+
+    /// Create a changes file from its JSON representation.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, crate::json::Error> {
+        let mut paragraph = deb822_lossless::Paragraph::new();
+        crate::json::paragraph_from_json(&mut paragraph, value, "changes")?;
+        Ok(Self(paragraph))
+    }
+}
+
+impl_semantic_eq!(Changes);
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -317,9 +358,9 @@ Files:
             Some(vec!["python3-buildlog-consultant".to_string()])
         );
         assert_eq!(changes.architecture(), Some(vec!["all".to_string()]));
-        assert_eq!(changes.version(), Some("0.0.34-1".parse().unwrap()));
+        assert_eq!(changes.version(), Some(Ok("0.0.34-1".parse().unwrap())));
         assert_eq!(changes.distribution(), Some("unstable".to_string()));
-        assert_eq!(changes.urgency(), Some(crate::fields::Urgency::Medium));
+        assert_eq!(changes.urgency(), Some(Ok(crate::fields::Urgency::Medium)));
         assert_eq!(
             changes.maintainer(),
             Some("Jelmer Vernooĳ <jelmer@debian.org>".to_string())
@@ -334,28 +375,28 @@ Files:
         );
         assert_eq!(
             changes.checksums_sha1(),
-            Some(vec![
+            Some(Ok(vec![
                 "f1657e628254428ad74542e82c253a181894e8d0 17153 buildlog-consultant_0.0.34-1_amd64.buildinfo".parse().unwrap(),
                 "b44493c05d014bcd59180942d0125b20ddf45d03 2550812 python3-buildlog-consultant_0.0.34-1_all.deb".parse().unwrap()
-            ])
+            ]))
         );
         assert_eq!(
             changes.checksums_sha256(),
-            Some(vec![
+            Some(Ok(vec![
                 "342a5782bf6a4f282d9002f726d2cac9c689c7e0fa7f61a1b0ecbf4da7916bdb 17153 buildlog-consultant_0.0.34-1_amd64.buildinfo"
                     .parse()
                     .unwrap(),
                 "7f7e5df81ee23fbbe89015edb37e04f4bb40672fa6e9b1afd4fd698e57db78fd 2550812 python3-buildlog-consultant_0.0.34-1_all.deb"
                     .parse()
                     .unwrap()
-            ])
+            ]))
         );
         assert_eq!(
             changes.files(),
-            Some(vec![
+            Some(Ok(vec![
                 "aa83112b0f8774a573bcf0b7b5cc12cc 17153 python optional buildlog-consultant_0.0.34-1_amd64.buildinfo".parse().unwrap(),
                 "a55858b90fe0ca728c89c1a1132b45c5 2550812 python optional python3-buildlog-consultant_0.0.34-1_all.deb".parse().unwrap()
-            ])
+            ]))
         );
 
         assert_eq!(
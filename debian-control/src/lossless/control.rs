@@ -18,7 +18,7 @@
 //!
 //! // Bump debhelper-compat
 //! let source = control.source().unwrap();
-//! let bd = source.build_depends().unwrap();
+//! let bd = source.build_depends().unwrap().unwrap();
 //!
 //! // Get entry with index 1 in Build-Depends, then set the version
 //! let entry = bd.get_entry(1).unwrap();
@@ -32,16 +32,22 @@
 //! Build-Depends: python3, debhelper-compat (= 12)
 //! "###);
 //! ```
-use crate::fields::{MultiArch, Priority};
+use crate::error::{parse_field, ControlError, FieldError};
+use crate::fields::{
+    Architectures, MultiArch, PackageType, Priority, Section, Testsuite, UserField,
+};
 use crate::lossless::relations::Relations;
+use crate::relations::RestrictionFormula;
+use crate::substvars::Substvars;
+use crate::{split_identities, Identity};
+use deb822_lossless::folding::FieldKind;
+use deb822_lossless::template::{self, placeholders};
+use std::cell::RefCell;
+use std::rc::Rc;
 
 fn format_field(name: &str, value: &str) -> String {
     match name {
-        "Uploaders" => value
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect::<Vec<_>>()
-            .join(",\n"),
+        "Uploaders" => split_identities(value).collect::<Vec<_>>().join(",\n"),
         "Build-Depends"
         | "Build-Depends-Indep"
         | "Build-Depends-Arch"
@@ -53,22 +59,290 @@ fn format_field(name: &str, value: &str) -> String {
         | "Suggests"
         | "Enhances"
         | "Pre-Depends"
-        | "Breaks" => {
-            let relations: Relations = value.parse().unwrap();
-            let relations = relations.wrap_and_sort();
-            relations.to_string()
-        }
+        | "Breaks" => match value.parse::<Relations>() {
+            Ok(relations) => relations.wrap_and_sort().to_string(),
+            Err(_) => value.to_string(),
+        },
         _ => value.to_string(),
     }
 }
 
+/// Look up a relations field in a paragraph and parse its value.
+///
+/// Unlike [`parse_field`], substvars such as `${misc:Depends}` are accepted.
+fn parse_relations(
+    paragraph: &deb822_lossless::Paragraph,
+    field: &str,
+) -> Option<Result<Relations, FieldError>> {
+    paragraph.get_ref(field).map(|value| {
+        Relations::parse_with_substvars(&value).map_err(|e| FieldError::new(field, &value, e))
+    })
+}
+
+/// The maximum length of the synopsis of a package, as checked by lintian.
+const MAX_SYNOPSIS_LENGTH: usize = 80;
+
+/// A part of a package description that does not follow Debian policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptionWarning {
+    /// The synopsis is longer than 80 characters.
+    SynopsisTooLong {
+        /// The length of the synopsis, in characters.
+        length: usize,
+    },
+}
+
+impl std::fmt::Display for DescriptionWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DescriptionWarning::SynopsisTooLong { length } => write!(
+                f,
+                "synopsis is {} characters long, more than {}",
+                length, MAX_SYNOPSIS_LENGTH
+            ),
+        }
+    }
+}
+
+/// Look up a field whose value is `yes` or `no` in a paragraph and parse its value.
+fn parse_yesno(
+    paragraph: &deb822_lossless::Paragraph,
+    field: &str,
+) -> Option<Result<bool, FieldError>> {
+    paragraph.get(field).map(|value| {
+        crate::lossy::deserialize_yesno(&value)
+            .map_err(|_| FieldError::new(field, &value, "expected \"yes\" or \"no\""))
+    })
+}
+
+/// Iterate over the user-defined fields in a paragraph, with their values.
+fn user_fields(
+    paragraph: &deb822_lossless::Paragraph,
+) -> impl Iterator<Item = (UserField, String)> + '_ {
+    paragraph
+        .items()
+        .filter_map(|(key, value)| Some((key.parse().ok()?, value)))
+}
+
+/// Find the key of the user-defined field with the given name, regardless of its prefix.
+fn find_user_field(paragraph: &deb822_lossless::Paragraph, name: &str) -> Option<String> {
+    paragraph.keys().find(|key| {
+        key.parse::<UserField>()
+            .is_ok_and(|field| field.name.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Return the user-defined field with the given name, without prefix, and its value.
+fn user_field(paragraph: &deb822_lossless::Paragraph, name: &str) -> Option<(UserField, String)> {
+    let key = find_user_field(paragraph, name)?;
+    Some((key.parse().ok()?, paragraph.get(&key)?))
+}
+
+/// Set a user-defined field, replacing a field with the same name but a different prefix, and
+/// notify `listeners` of the fields that changed.
+fn set_user_field(
+    paragraph: &mut deb822_lossless::Paragraph,
+    listeners: &Listeners,
+    field: &UserField,
+    value: &str,
+) {
+    let key = field.to_string();
+    if let Some(existing) = find_user_field(paragraph, &field.name) {
+        if existing != key {
+            let old_value = paragraph.get(&existing);
+            paragraph.rename(&existing, &key);
+            listeners.notify(paragraph, &existing, old_value);
+        }
+    }
+    let old_value = paragraph.get(&key);
+    paragraph.set(&key, value);
+    listeners.notify(paragraph, &key, old_value);
+}
+
+/// Remove the user-defined field with the given name, without prefix, and notify `listeners`.
+fn remove_user_field(
+    paragraph: &mut deb822_lossless::Paragraph,
+    listeners: &Listeners,
+    name: &str,
+) -> bool {
+    let Some(key) = find_user_field(paragraph, name) else {
+        return false;
+    };
+    let old_value = paragraph.get(&key);
+    paragraph.remove(&key);
+    listeners.notify(paragraph, &key, old_value);
+    true
+}
+
+/// Substitute the substitution variables in all fields of a paragraph, notifying `listeners`
+/// of the fields that changed.
+///
+/// Relations that are empty after substitution are dropped, and relation fields that end up
+/// empty are removed.
+fn substitute_paragraph(
+    paragraph: &mut deb822_lossless::Paragraph,
+    listeners: &Listeners,
+    substvars: &Substvars,
+) -> Result<(), template::Error> {
+    let old_values = paragraph
+        .items()
+        .filter(|(_, value)| placeholders(value).next().is_some())
+        .collect::<Vec<_>>();
+    paragraph.substitute(&substvars.vars())?;
+    for (field, _) in &old_values {
+        if !crate::fields::RELATION_FIELDS
+            .iter()
+            .any(|f| f.eq_ignore_ascii_case(field))
+        {
+            continue;
+        }
+        let value = paragraph.get(field).unwrap_or_default();
+        if value.split(',').any(|item| item.trim().is_empty()) {
+            let items = paragraph.get_comma_list(field).collect::<Vec<_>>();
+            paragraph.set_comma_list(field, items);
+        }
+    }
+    for (field, old_value) in old_values {
+        listeners.notify(paragraph, &field, Some(old_value));
+    }
+    Ok(())
+}
+
+/// A change made to a field through one of the typed setters.
+#[derive(Debug)]
+pub struct FieldChange<'a> {
+    /// The paragraph that was changed, with the change applied.
+    pub paragraph: &'a deb822_lossless::Paragraph,
+
+    /// Name of the field that was changed.
+    pub field: &'a str,
+
+    /// Value of the field before the change, or `None` if it was not set.
+    pub old_value: Option<String>,
+
+    /// Value of the field after the change, or `None` if it was removed.
+    pub new_value: Option<String>,
+}
+
+type Callback = Box<dyn Fn(&FieldChange)>;
+
+/// Callbacks registered with [`Control::on_change`].
+///
+/// These are shared between a control file and the paragraphs obtained from it. They are not
+/// part of the value of a control file, so they are ignored when comparing or hashing.
+#[derive(Clone, Default)]
+struct Listeners(Rc<RefCell<Vec<Callback>>>);
+
+impl Listeners {
+    fn notify(
+        &self,
+        paragraph: &deb822_lossless::Paragraph,
+        field: &str,
+        old_value: Option<String>,
+    ) {
+        let new_value = paragraph.get(field);
+        if old_value == new_value {
+            return;
+        }
+        let change = FieldChange {
+            paragraph,
+            field,
+            old_value,
+            new_value,
+        };
+        for callback in self.0.borrow().iter() {
+            callback(&change);
+        }
+    }
+}
+
+/// The typed setters shared by [`Source`] and [`Binary`], which notify the listeners of the
+/// control file of every change.
+trait FieldSetters {
+    /// The underlying paragraph and the listeners to notify.
+    fn parts_mut(&mut self) -> (&mut deb822_lossless::Paragraph, &Listeners);
+
+    fn set_field(&mut self, name: &str, value: &str) {
+        let (paragraph, listeners) = self.parts_mut();
+        let old_value = paragraph.get(name);
+        paragraph.set(name, value);
+        listeners.notify(paragraph, name, old_value);
+    }
+
+    fn remove_field(&mut self, name: &str) {
+        let (paragraph, listeners) = self.parts_mut();
+        let old_value = paragraph.get(name);
+        paragraph.remove(name);
+        listeners.notify(paragraph, name, old_value);
+    }
+
+    /// Set a field to `value`, or remove it if `value` is `None`.
+    fn set_optional_field<T: std::fmt::Display>(&mut self, name: &str, value: Option<T>) {
+        match value {
+            Some(value) => self.set_field(name, &value.to_string()),
+            None => self.remove_field(name),
+        }
+    }
+}
+
+impl PartialEq for Listeners {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Listeners {}
+
+impl std::hash::Hash for Listeners {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
 /// A Debian control file
-pub struct Control(deb822_lossless::Deb822);
+#[derive(PartialEq, Eq, Hash)]
+pub struct Control(deb822_lossless::Deb822, Listeners);
 
 impl Control {
     /// Create a new control file
     pub fn new() -> Self {
-        Control(deb822_lossless::Deb822::new())
+        Control(deb822_lossless::Deb822::new(), Listeners::default())
+    }
+
+    /// Create a builder for a new control file
+    ///
+    /// See [`ControlBuilder`](crate::builder::ControlBuilder).
+    pub fn builder() -> crate::builder::ControlBuilder {
+        crate::builder::ControlBuilder::new()
+    }
+
+    /// Register a callback that is called whenever a field is changed through one of the typed
+    /// setters on [`Source`] or [`Binary`].
+    ///
+    /// The callback applies to paragraphs obtained from this control file, including ones
+    /// obtained before the callback was registered. Changes that leave the value of a field
+    /// unchanged, and changes made directly to the underlying deb822 objects, are not reported.
+    ///
+    /// Callbacks must not register further callbacks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::Control;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let mut control: Control = "Source: foo\nSection: net\n".parse().unwrap();
+    /// let changes = Rc::new(RefCell::new(Vec::new()));
+    /// let log = changes.clone();
+    /// control.on_change(move |change| {
+    ///     log.borrow_mut().push((change.field.to_string(), change.old_value.clone(), change.new_value.clone()));
+    /// });
+    /// control.source().unwrap().set_section(Some(&"libs".parse().unwrap()));
+    /// assert_eq!(
+    ///     changes.borrow().as_slice(),
+    ///     &[("Section".to_string(), Some("net".to_string()), Some("libs".to_string()))]
+    /// );
+    /// ```
+    pub fn on_change(&mut self, callback: impl Fn(&FieldChange) + 'static) {
+        self.1 .0.borrow_mut().push(Box::new(callback));
     }
 
     /// Return the underlying deb822 object, mutable
@@ -85,16 +359,33 @@ impl Control {
     pub fn source(&self) -> Option<Source> {
         self.0
             .paragraphs()
-            .find(|p| p.get("Source").is_some())
-            .map(Source)
+            .find(|p| p.contains_key("Source"))
+            .map(|p| Source(p, self.1.clone()))
     }
 
     /// Iterate over all binary packages
     pub fn binaries(&self) -> impl Iterator<Item = Binary> {
+        let listeners = self.1.clone();
         self.0
             .paragraphs()
-            .filter(|p| p.get("Package").is_some())
-            .map(Binary)
+            .filter(|p| p.contains_key("Package"))
+            .map(move |p| Binary(p, listeners.clone()))
+    }
+
+    /// Find the binary package with the given name
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo\n\nPackage: foo-doc\nSection: doc\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(control.find_binary("foo-doc").unwrap().raw_section(), Some("doc".to_owned()));
+    /// assert!(control.find_binary("bar").is_none());
+    /// ```
+    pub fn find_binary(&self, name: &str) -> Option<Binary> {
+        self.binaries()
+            .find(|b| b.name().is_some_and(|n| n.trim() == name))
     }
 
     /// Add a new source package
@@ -136,12 +427,160 @@ impl Control {
     pub fn add_binary(&mut self, name: &str) -> Binary {
         let mut p = self.0.add_paragraph();
         p.set("Package", name);
-        Binary(p)
+        Binary(p, self.1.clone())
+    }
+
+    /// Append a binary package paragraph, for example one built with a
+    /// [`BinaryBuilder`](crate::builder::BinaryBuilder) or
+    /// [`ParagraphBuilder`](crate::builder::ParagraphBuilder).
+    ///
+    /// The paragraph is separated from the previous one by an empty line.
+    ///
+    /// # Returns
+    /// The newly added binary package
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::builder::BinaryBuilder;
+    /// use debian_control::lossless::control::Control;
+    /// let mut control: Control = "Source: foo\n\nPackage: foo\nArchitecture: any\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let binary = control.push_binary(BinaryBuilder::new("foo-doc").architecture("all"));
+    /// assert_eq!(binary.name(), Some("foo-doc".to_owned()));
+    /// assert_eq!(
+    ///     control.to_string(),
+    ///     "Source: foo\n\nPackage: foo\nArchitecture: any\n\nPackage: foo-doc\nArchitecture: all\n"
+    /// );
+    /// ```
+    pub fn push_binary(
+        &mut self,
+        paragraph: impl Into<deb822_lossless::lossy::Paragraph>,
+    ) -> Binary {
+        self.0.push(paragraph.into().to_lossless());
+        Binary(self.0.paragraphs().last().unwrap(), self.1.clone())
+    }
+
+    /// Remove the binary package with the given name
+    ///
+    /// Comment lines directly above the paragraph and the empty lines separating it from the
+    /// next paragraph are removed along with it.
+    ///
+    /// # Returns
+    /// Whether a binary package was removed
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let mut control: Control = "Source: foo\n\nPackage: foo\n\n# Documentation\nPackage: foo-doc\n\nPackage: foo-dev\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(control.remove_binary("foo-doc"));
+    /// assert!(!control.remove_binary("bar"));
+    /// assert_eq!(control.to_string(), "Source: foo\n\nPackage: foo\n\nPackage: foo-dev\n");
+    /// ```
+    pub fn remove_binary(&mut self, name: &str) -> bool {
+        let removed = self
+            .0
+            .extract_paragraphs(|p| p.get("Package").is_some_and(|n| n.trim() == name));
+        removed.paragraphs().next().is_some()
+    }
+
+    /// Substitute the substitution variables in the fields of all paragraphs
+    ///
+    /// If any variable is not set, the control file is left unchanged and all unresolved
+    /// variables are returned in the error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// use debian_control::substvars::Substvars;
+    /// let mut control: Control = "Source: foo\n\nPackage: foo\nDepends: ${shlibs:Depends}, ${misc:Depends}\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut substvars: Substvars = "shlibs:Depends=libc6\n".parse().unwrap();
+    /// assert_eq!(
+    ///     control.substitute(&substvars).unwrap_err().unknown,
+    ///     vec!["misc:Depends"]
+    /// );
+    /// substvars.set("misc:Depends", "adduser");
+    /// control.substitute(&substvars).unwrap();
+    /// assert_eq!(
+    ///     control.to_string(),
+    ///     "Source: foo\n\nPackage: foo\nDepends: libc6, adduser\n"
+    /// );
+    /// ```
+    pub fn substitute(&mut self, substvars: &Substvars) -> Result<(), template::Error> {
+        let mut unknown: Vec<String> = Vec::new();
+        for paragraph in self.0.paragraphs() {
+            for (_, value) in paragraph.items() {
+                for name in substvars.unresolved(&value) {
+                    if !unknown.iter().any(|u| u == name) {
+                        unknown.push(name.to_string());
+                    }
+                }
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(template::Error { unknown });
+        }
+        for mut paragraph in self.0.paragraphs() {
+            substitute_paragraph(&mut paragraph, &self.1, substvars)?;
+        }
+        Ok(())
     }
 
     /// Read a control file from a file
-    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, deb822_lossless::Error> {
-        Ok(Control(deb822_lossless::Deb822::from_file(path)?))
+    ///
+    /// The error records the path of the file.
+    pub fn from_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, deb822_lossless::FileError> {
+        let path = path.as_ref();
+        deb822_lossless::Deb822::from_file(path)
+            .map(Self::from)
+            .map_err(|e| deb822_lossless::FileError::new(path, e))
+    }
+
+    /// Write the control file to the given path, atomically replacing any existing file.
+    ///
+    /// Unlike [`Control::save`], the error does not record the path of the file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let path = std::env::temp_dir().join(format!("debian-control-save-atomic-{}", std::process::id()));
+    /// let control: Control = "Source: foo\n".parse().unwrap();
+    /// control.save_atomic(&path).unwrap();
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "Source: foo\n");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_atomic<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        self.0.save_atomic(path)
+    }
+
+    /// Write the control file to a file
+    ///
+    /// The file is replaced atomically, so readers never see a partially written file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let path = std::env::temp_dir().join(format!("debian-control-save-{}", std::process::id()));
+    /// let control: Control = "Source: foo\n".parse().unwrap();
+    /// control.source().unwrap().set_section(Some(&"net".parse().unwrap()));
+    /// control.save(&path).unwrap();
+    /// let control = Control::from_file(&path).unwrap();
+    /// assert_eq!(control.source().unwrap().raw_section(), Some("net".to_owned()));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), deb822_lossless::FileError> {
+        let path = path.as_ref();
+        self.save_atomic(path)
+            .map_err(|e| deb822_lossless::FileError::new(path, e))
     }
 
     /// Read a control file from a file, allowing syntax errors
@@ -149,12 +588,12 @@ impl Control {
         path: P,
     ) -> Result<(Self, Vec<String>), std::io::Error> {
         let (control, errors) = deb822_lossless::Deb822::from_file_relaxed(path)?;
-        Ok((Control(control), errors))
+        Ok((control.into(), errors))
     }
 
     /// Read a control file from a reader
     pub fn read<R: std::io::Read>(mut r: R) -> Result<Self, deb822_lossless::Error> {
-        Ok(Control(deb822_lossless::Deb822::read(&mut r)?))
+        Ok(deb822_lossless::Deb822::read(&mut r)?.into())
     }
 
     /// Read a control file from a reader, allowing syntax errors
@@ -162,7 +601,7 @@ impl Control {
         mut r: R,
     ) -> Result<(Self, Vec<String>), deb822_lossless::Error> {
         let (control, errors) = deb822_lossless::Deb822::read_relaxed(&mut r)?;
-        Ok((Self(control), errors))
+        Ok((control.into(), errors))
     }
 
     /// Wrap and sort the control file
@@ -181,8 +620,8 @@ impl Control {
                                b: &deb822_lossless::Paragraph|
          -> std::cmp::Ordering {
             // Sort Source before Package
-            let a_is_source = a.get("Source").is_some();
-            let b_is_source = b.get("Source").is_some();
+            let a_is_source = a.contains_key("Source");
+            let b_is_source = b.contains_key("Source");
 
             if a_is_source && !b_is_source {
                 return std::cmp::Ordering::Less;
@@ -221,7 +660,7 @@ impl From<Control> for deb822_lossless::Deb822 {
 
 impl From<deb822_lossless::Deb822> for Control {
     fn from(d: deb822_lossless::Deb822) -> Self {
-        Control(d)
+        Control(d, Listeners::default())
     }
 }
 
@@ -235,12 +674,13 @@ impl std::str::FromStr for Control {
     type Err = deb822_lossless::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Control(s.parse()?))
+        Ok(Control(s.parse()?, Listeners::default()))
     }
 }
 
 /// A source package paragraph
-pub struct Source(deb822_lossless::Paragraph);
+#[derive(PartialEq, Eq, Hash)]
+pub struct Source(deb822_lossless::Paragraph, Listeners);
 
 impl From<Source> for deb822_lossless::Paragraph {
     fn from(s: Source) -> Self {
@@ -250,7 +690,13 @@ impl From<Source> for deb822_lossless::Paragraph {
 
 impl From<deb822_lossless::Paragraph> for Source {
     fn from(p: deb822_lossless::Paragraph) -> Self {
-        Source(p)
+        Source(p, Listeners::default())
+    }
+}
+
+impl FieldSetters for Source {
+    fn parts_mut(&mut self) -> (&mut deb822_lossless::Paragraph, &Listeners) {
+        (&mut self.0, &self.1)
     }
 }
 
@@ -294,86 +740,133 @@ impl Source {
 
     /// Set the name of the source package.
     pub fn set_name(&mut self, name: &str) {
-        self.0.set("Source", name);
+        self.set_field("Source", name);
     }
 
     /// The default section of the packages built from this source package.
-    pub fn section(&self) -> Option<String> {
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::Section;
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\nSection: contrib/net\n".parse().unwrap();
+    /// assert_eq!(
+    ///     control.source().unwrap().section(),
+    ///     Some(Ok(Section::new(Some("contrib"), "net")))
+    /// );
+    /// ```
+    pub fn section(&self) -> Option<Result<Section, FieldError>> {
+        parse_field(&self.0, "Section")
+    }
+
+    /// The default section of the packages built from this source package, as written in the
+    /// control file
+    pub fn raw_section(&self) -> Option<String> {
         self.0.get("Section")
     }
 
     /// Set the section of the source package
-    pub fn set_section(&mut self, section: Option<&str>) {
-        if let Some(section) = section {
-            self.0.set("Section", section);
-        } else {
-            self.0.remove("Section");
-        }
+    pub fn set_section(&mut self, section: Option<&Section>) {
+        self.set_optional_field("Section", section);
     }
 
     /// The default priority of the packages built from this source package.
     pub fn priority(&self) -> Option<Priority> {
-        self.0.get("Priority").and_then(|v| v.parse().ok())
+        self.0.get_ref("Priority").and_then(|v| v.parse().ok())
     }
 
     /// Set the priority of the source package
     pub fn set_priority(&mut self, priority: Option<Priority>) {
-        if let Some(priority) = priority {
-            self.0.set("Priority", priority.to_string().as_str());
-        } else {
-            self.0.remove("Priority");
-        }
+        self.set_optional_field("Priority", priority);
     }
 
     /// The maintainer of the package.
-    pub fn maintainer(&self) -> Option<String> {
+    pub fn maintainer(&self) -> Option<Result<Identity, FieldError>> {
+        parse_field(&self.0, "Maintainer")
+    }
+
+    /// The maintainer of the package, as written in the control file
+    pub fn raw_maintainer(&self) -> Option<String> {
         self.0.get("Maintainer")
     }
 
     /// Set the maintainer of the package
-    pub fn set_maintainer(&mut self, maintainer: &str) {
-        self.0.set("Maintainer", maintainer);
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::Identity;
+    /// use debian_control::lossless::control::Control;
+    /// let mut control = Control::new();
+    /// let mut source = control.add_source("foo");
+    /// source.set_maintainer(&Identity::new("Doe, Jane", "jane@example.com"));
+    /// assert_eq!(
+    ///     source.raw_maintainer(),
+    ///     Some("\"Doe, Jane\" <jane@example.com>".to_owned())
+    /// );
+    /// assert_eq!(source.maintainer().unwrap().unwrap().name, "Doe, Jane");
+    /// ```
+    pub fn set_maintainer(&mut self, maintainer: &Identity) {
+        self.set_field("Maintainer", &maintainer.to_string());
     }
 
     /// The build dependencies of the package.
-    pub fn build_depends(&self) -> Option<Relations> {
-        self.0.get("Build-Depends").map(|s| s.parse().unwrap())
+    pub fn build_depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Build-Depends")
     }
 
     /// Set the Build-Depends field
     pub fn set_build_depends(&mut self, relations: &Relations) {
-        self.0.set("Build-Depends", relations.to_string().as_str());
+        self.set_field("Build-Depends", relations.to_string().as_str());
     }
 
     /// Return the Build-Depends-Indep field
-    pub fn build_depends_indep(&self) -> Option<Relations> {
-        self.0
-            .get("Build-Depends-Indep")
-            .map(|s| s.parse().unwrap())
+    pub fn build_depends_indep(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Build-Depends-Indep")
+    }
+
+    /// Set the Build-Depends-Indep field
+    pub fn set_build_depends_indep(&mut self, relations: Option<&Relations>) {
+        self.set_optional_field("Build-Depends-Indep", relations);
     }
 
     /// Return the Build-Depends-Arch field
-    pub fn build_depends_arch(&self) -> Option<Relations> {
-        self.0.get("Build-Depends-Arch").map(|s| s.parse().unwrap())
+    pub fn build_depends_arch(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Build-Depends-Arch")
+    }
+
+    /// Set the Build-Depends-Arch field
+    pub fn set_build_depends_arch(&mut self, relations: Option<&Relations>) {
+        self.set_optional_field("Build-Depends-Arch", relations);
     }
 
     /// The build conflicts of the package.
-    pub fn build_conflicts(&self) -> Option<Relations> {
-        self.0.get("Build-Conflicts").map(|s| s.parse().unwrap())
+    pub fn build_conflicts(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Build-Conflicts")
+    }
+
+    /// Set the Build-Conflicts field
+    pub fn set_build_conflicts(&mut self, relations: Option<&Relations>) {
+        self.set_optional_field("Build-Conflicts", relations);
     }
 
     /// Return the Build-Conflicts-Indep field
-    pub fn build_conflicts_indep(&self) -> Option<Relations> {
-        self.0
-            .get("Build-Conflicts-Indep")
-            .map(|s| s.parse().unwrap())
+    pub fn build_conflicts_indep(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Build-Conflicts-Indep")
+    }
+
+    /// Set the Build-Conflicts-Indep field
+    pub fn set_build_conflicts_indep(&mut self, relations: Option<&Relations>) {
+        self.set_optional_field("Build-Conflicts-Indep", relations);
     }
 
     /// Return the Build-Conflicts-Arch field
-    pub fn build_conflicts_arch(&self) -> Option<Relations> {
-        self.0
-            .get("Build-Conflicts-Arch")
-            .map(|s| s.parse().unwrap())
+    pub fn build_conflicts_arch(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Build-Conflicts-Arch")
+    }
+
+    /// Set the Build-Conflicts-Arch field
+    pub fn set_build_conflicts_arch(&mut self, relations: Option<&Relations>) {
+        self.set_optional_field("Build-Conflicts-Arch", relations);
     }
 
     /// Return the standards version
@@ -383,17 +876,17 @@ impl Source {
 
     /// Set the Standards-Version field
     pub fn set_standards_version(&mut self, version: &str) {
-        self.0.set("Standards-Version", version);
+        self.set_field("Standards-Version", version);
     }
 
     /// Return the upstrea mHomepage
     pub fn homepage(&self) -> Option<url::Url> {
-        self.0.get("Homepage").and_then(|s| s.parse().ok())
+        self.0.get_ref("Homepage").and_then(|s| s.parse().ok())
     }
 
     /// Set the Homepage field
     pub fn set_homepage(&mut self, homepage: &url::Url) {
-        self.0.set("Homepage", homepage.to_string().as_str());
+        self.set_field("Homepage", homepage.to_string().as_str());
     }
 
     /// Return the Vcs-Git field
@@ -403,87 +896,87 @@ impl Source {
 
     /// Set the Vcs-Git field
     pub fn set_vcs_git(&mut self, url: &str) {
-        self.0.set("Vcs-Git", url);
+        self.set_field("Vcs-Git", url);
     }
 
     /// Return the Vcs-Browser field
     pub fn vcs_svn(&self) -> Option<String> {
-        self.0.get("Vcs-Svn").map(|s| s.to_string())
+        self.0.get("Vcs-Svn")
     }
 
     /// Set the Vcs-Svn field
     pub fn set_vcs_svn(&mut self, url: &str) {
-        self.0.set("Vcs-Svn", url);
+        self.set_field("Vcs-Svn", url);
     }
 
     /// Return the Vcs-Bzr field
     pub fn vcs_bzr(&self) -> Option<String> {
-        self.0.get("Vcs-Bzr").map(|s| s.to_string())
+        self.0.get("Vcs-Bzr")
     }
 
     /// Set the Vcs-Bzr field
     pub fn set_vcs_bzr(&mut self, url: &str) {
-        self.0.set("Vcs-Bzr", url);
+        self.set_field("Vcs-Bzr", url);
     }
 
     /// Return the Vcs-Arch field
     pub fn vcs_arch(&self) -> Option<String> {
-        self.0.get("Vcs-Arch").map(|s| s.to_string())
+        self.0.get("Vcs-Arch")
     }
 
     /// Set the Vcs-Arch field
     pub fn set_vcs_arch(&mut self, url: &str) {
-        self.0.set("Vcs-Arch", url);
+        self.set_field("Vcs-Arch", url);
     }
 
     /// Return the Vcs-Svk field
     pub fn vcs_svk(&self) -> Option<String> {
-        self.0.get("Vcs-Svk").map(|s| s.to_string())
+        self.0.get("Vcs-Svk")
     }
 
     /// Set the Vcs-Svk field
     pub fn set_vcs_svk(&mut self, url: &str) {
-        self.0.set("Vcs-Svk", url);
+        self.set_field("Vcs-Svk", url);
     }
 
     /// Return the Vcs-Darcs field
     pub fn vcs_darcs(&self) -> Option<String> {
-        self.0.get("Vcs-Darcs").map(|s| s.to_string())
+        self.0.get("Vcs-Darcs")
     }
 
     /// Set the Vcs-Darcs field
     pub fn set_vcs_darcs(&mut self, url: &str) {
-        self.0.set("Vcs-Darcs", url);
+        self.set_field("Vcs-Darcs", url);
     }
 
     /// Return the Vcs-Mtn field
     pub fn vcs_mtn(&self) -> Option<String> {
-        self.0.get("Vcs-Mtn").map(|s| s.to_string())
+        self.0.get("Vcs-Mtn")
     }
 
     /// Set the Vcs-Mtn field
     pub fn set_vcs_mtn(&mut self, url: &str) {
-        self.0.set("Vcs-Mtn", url);
+        self.set_field("Vcs-Mtn", url);
     }
 
     /// Return the Vcs-Cvs field
     pub fn vcs_cvs(&self) -> Option<String> {
-        self.0.get("Vcs-Cvs").map(|s| s.to_string())
+        self.0.get("Vcs-Cvs")
     }
 
     /// Set the Vcs-Cvs field
     pub fn set_vcs_cvs(&mut self, url: &str) {
-        self.0.set("Vcs-Cvs", url);
+        self.set_field("Vcs-Cvs", url);
     }
 
     /// Return the Vcs-Hg field
     pub fn vcs_hg(&self) -> Option<String> {
-        self.0.get("Vcs-Hg").map(|s| s.to_string())
+        self.0.get("Vcs-Hg")
     }
 
     /// Set the Vcs-Hg field
     pub fn set_vcs_hg(&mut self, url: &str) {
-        self.0.set("Vcs-Hg", url);
+        self.set_field("Vcs-Hg", url);
     }
 
     /// Return the Vcs-Browser field
@@ -503,74 +996,244 @@ impl Source {
 
     /// Set the Vcs-Browser field
     pub fn set_vcs_browser(&mut self, url: Option<&str>) {
-        if let Some(url) = url {
-            self.0.set("Vcs-Browser", url);
-        } else {
-            self.0.remove("Vcs-Browser");
-        }
+        self.set_optional_field("Vcs-Browser", url);
     }
 
     /// Return the Uploaders field
-    pub fn uploaders(&self) -> Option<Vec<String>> {
+    ///
+    /// Commas inside quoted names, as in `"Doe, John" <john@example.com>`, don't separate
+    /// uploaders.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::Identity;
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\nUploaders: \"Doe, John\" <john@example.com>,\n Jane <jane@example.com>\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     control.source().unwrap().uploaders(),
+    ///     Some(Ok(vec![
+    ///         Identity::new("Doe, John", "john@example.com"),
+    ///         Identity::new("Jane", "jane@example.com"),
+    ///     ]))
+    /// );
+    /// ```
+    pub fn uploaders(&self) -> Option<Result<Vec<Identity>, FieldError>> {
+        self.0.get("Uploaders").map(|value| {
+            split_identities(&value)
+                .map(|item| {
+                    item.parse()
+                        .map_err(|e| FieldError::new("Uploaders", &value, e))
+                })
+                .collect()
+        })
+    }
+
+    /// Set the uploaders field
+    ///
+    /// If the uploaders are currently written one per line, the new ones are too. The field
+    /// is removed if `uploaders` is empty.
+    pub fn set_uploaders(&mut self, uploaders: &[Identity]) {
+        self.set_uploaders_list(uploaders.iter().map(|u| u.to_string()).collect());
+    }
+
+    /// Add an uploader
+    ///
+    /// The other uploaders and the layout of the field are left untouched.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::Identity;
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\nUploaders:\n Jane <jane@example.com>,\n Joe <joe@example.com>\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut source = control.source().unwrap();
+    /// source.add_uploader(&Identity::new("Doe, John", "john@example.com"));
+    /// assert!(source.remove_uploader_by_email("joe@example.com"));
+    /// assert_eq!(
+    ///     control.to_string(),
+    ///     "Source: foo\nUploaders:\n Jane <jane@example.com>,\n \"Doe, John\" <john@example.com>\n"
+    /// );
+    /// ```
+    pub fn add_uploader(&mut self, uploader: &Identity) {
+        let mut uploaders = self.raw_uploaders();
+        uploaders.push(uploader.to_string());
+        self.set_uploaders_list(uploaders);
+    }
+
+    /// Remove the uploaders with the given email address
+    ///
+    /// Email addresses are compared case-insensitively. The field is removed if no uploaders
+    /// are left.
+    ///
+    /// # Returns
+    /// Whether an uploader was removed
+    pub fn remove_uploader_by_email(&mut self, email: &str) -> bool {
+        let uploaders = self.raw_uploaders();
+        let count = uploaders.len();
+        let remaining = uploaders
+            .into_iter()
+            .filter(|item| {
+                !item
+                    .parse::<Identity>()
+                    .is_ok_and(|u| u.email.eq_ignore_ascii_case(email))
+            })
+            .collect::<Vec<_>>();
+        if remaining.len() == count {
+            return false;
+        }
+        self.set_uploaders_list(remaining);
+        true
+    }
+
+    /// The items of the Uploaders field, as written in the control file.
+    fn raw_uploaders(&self) -> Vec<String> {
         self.0
             .get("Uploaders")
-            .map(|s| s.split(',').map(|s| s.trim().to_owned()).collect())
+            .map(|value| split_identities(&value).map(str::to_string).collect())
+            .unwrap_or_default()
     }
 
-    /// Set the uploaders field
-    pub fn set_uploaders(&mut self, uploaders: &[&str]) {
-        self.0.set(
-            "Uploaders",
-            uploaders
-                .iter()
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>()
-                .join(", ")
-                .as_str(),
-        );
+    fn set_uploaders_list(&mut self, uploaders: Vec<String>) {
+        let old_value = self.0.get("Uploaders");
+        self.0.set_comma_list("Uploaders", uploaders);
+        self.1.notify(&self.0, "Uploaders", old_value);
+    }
+
+    /// Substitute the substitution variables in the fields of the package
+    ///
+    /// If any variable is not set, the paragraph is left unchanged and all unresolved
+    /// variables are returned in the error.
+    pub fn substitute(&mut self, substvars: &Substvars) -> Result<(), template::Error> {
+        substitute_paragraph(&mut self.0, &self.1, substvars)
+    }
+
+    /// Iterate over the user-defined fields, such as `XS-Go-Import-Path`, with their values
+    pub fn user_fields(&self) -> impl Iterator<Item = (UserField, String)> + '_ {
+        user_fields(&self.0)
+    }
+
+    /// Return the user-defined field with the given name, without prefix, and its value
+    ///
+    /// The name is compared case-insensitively, regardless of the prefix of the field.
+    pub fn user_field(&self, name: &str) -> Option<(UserField, String)> {
+        user_field(&self.0, name)
+    }
+
+    /// Set a user-defined field
+    ///
+    /// A field with the same name but a different prefix is replaced.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::{FieldTargets, UserField};
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\nX-Go-Import-Path: example.com/foo\n".parse().unwrap();
+    /// let mut source = control.source().unwrap();
+    /// let targets = FieldTargets { source: true, ..Default::default() };
+    /// source.set_user_field(&UserField::new("Go-Import-Path", targets), "example.com/bar");
+    /// assert_eq!(control.to_string(), "Source: foo\nXS-Go-Import-Path: example.com/bar\n");
+    /// let (field, value) = source.user_field("go-import-path").unwrap();
+    /// assert!(field.targets.source);
+    /// assert_eq!(value, "example.com/bar");
+    /// ```
+    pub fn set_user_field(&mut self, field: &UserField, value: &str) {
+        set_user_field(&mut self.0, &self.1, field, value)
+    }
+
+    /// Remove the user-defined field with the given name, without prefix
+    ///
+    /// # Returns
+    /// Whether a field was removed
+    pub fn remove_user_field(&mut self, name: &str) -> bool {
+        remove_user_field(&mut self.0, &self.1, name)
     }
 
     /// Return the architecture field
-    pub fn architecture(&self) -> Option<String> {
-        self.0.get("Architecture")
+    pub fn architecture(&self) -> Option<Result<Architectures, FieldError>> {
+        parse_field(&self.0, "Architecture")
     }
 
     /// Set the architecture field
-    pub fn set_architecture(&mut self, arch: Option<&str>) {
-        if let Some(arch) = arch {
-            self.0.set("Architecture", arch);
-        } else {
-            self.0.remove("Architecture");
-        }
+    pub fn set_architecture(&mut self, arch: Option<&Architectures>) {
+        self.set_optional_field("Architecture", arch);
     }
 
     /// Return the Rules-Requires-Root field
-    pub fn rules_requires_root(&self) -> Option<bool> {
+    pub fn rules_requires_root(&self) -> Option<Result<bool, FieldError>> {
         self.0
             .get("Rules-Requires-Root")
             .map(|s| match s.to_lowercase().as_str() {
-                "yes" => true,
-                "no" => false,
-                _ => panic!("invalid Rules-Requires-Root value"),
+                "yes" => Ok(true),
+                "no" => Ok(false),
+                _ => Err(FieldError::new(
+                    "Rules-Requires-Root",
+                    &s,
+                    "expected \"yes\" or \"no\"",
+                )),
             })
     }
 
     /// Set the Rules-Requires-Root field
     pub fn set_rules_requires_root(&mut self, requires_root: bool) {
-        self.0.set(
+        self.set_field(
             "Rules-Requires-Root",
             if requires_root { "yes" } else { "no" },
         );
     }
 
-    /// Return the Testsuite field
-    pub fn testsuite(&self) -> Option<String> {
-        self.0.get("Testsuite")
+    /// Return the test suites from the Testsuite field
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::Testsuite;
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\nTestsuite: autopkgtest, autopkgtest-pkg-python\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     control.source().unwrap().testsuite(),
+    ///     Some(Ok(vec![
+    ///         Testsuite::Autopkgtest,
+    ///         Testsuite::AutopkgtestPkg("python".to_string()),
+    ///     ]))
+    /// );
+    /// ```
+    pub fn testsuite(&self) -> Option<Result<Vec<Testsuite>, FieldError>> {
+        self.0.get("Testsuite").map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| {
+                    item.parse()
+                        .map_err(|e| FieldError::new("Testsuite", &value, e))
+                })
+                .collect()
+        })
     }
 
     /// Set the Testsuite field
-    pub fn set_testsuite(&mut self, testsuite: &str) {
-        self.0.set("Testsuite", testsuite);
+    ///
+    /// The field is removed if `testsuites` is empty.
+    pub fn set_testsuite(&mut self, testsuites: &[Testsuite]) {
+        let old_value = self.0.get("Testsuite");
+        self.0
+            .set_comma_list("Testsuite", testsuites.iter().map(|t| t.to_string()));
+        self.1.notify(&self.0, "Testsuite", old_value);
+    }
+
+    /// Whether the package is expected to ship `debian/tests/control`
+    ///
+    /// This is the case if the Testsuite field lists `autopkgtest`. Note that `dpkg-source`
+    /// adds `autopkgtest` to the field of the `.dsc` if the file exists, so the field in
+    /// `debian/control` may omit it. Unparseable entries are ignored.
+    pub fn expects_tests_control(&self) -> bool {
+        self.testsuite()
+            .and_then(Result::ok)
+            .is_some_and(|testsuites| testsuites.iter().any(Testsuite::needs_tests_control))
     }
 }
 
@@ -585,7 +1248,58 @@ impl pyo3::ToPyObject for Source {
 impl pyo3::FromPyObject<'_> for Source {
     fn extract_bound(ob: &pyo3::Bound<pyo3::PyAny>) -> pyo3::PyResult<Self> {
         use pyo3::prelude::*;
-        Ok(Source(ob.extract()?))
+        Ok(Source(ob.extract()?, Listeners::default()))
+    }
+}
+
+#[cfg(feature = "json")]
+impl Control {
+    /// Convert the control file to JSON, using the schema described in [`crate::json`].
+    pub fn to_json(&self) -> serde_json::Value {
+        self.to_json_with(&crate::json::Options::default())
+    }
+
+    /// Convert the control file to JSON, with the given options.
+    pub fn to_json_with(&self, options: &crate::json::Options) -> serde_json::Value {
+        serde_json::json!({
+            "source": self
+                .source()
+                .map(|source| crate::json::paragraph_to_json(&source.0, options)),
+            "binaries": self
+                .binaries()
+                .map(|binary| crate::json::paragraph_to_json(&binary.0, options))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Create a control file from its JSON representation.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, crate::json::Error> {
+        let obj = crate::json::expect_object(value, "control")?;
+        let mut control = Control::new();
+        if let Some(source) = obj.get("source").filter(|source| !source.is_null()) {
+            let mut paragraph = control.0.add_paragraph();
+            crate::json::paragraph_from_json(&mut paragraph, source, "source")?;
+        }
+        if let Some(binaries) = obj.get("binaries") {
+            for binary in crate::json::expect_array(binaries, "binaries")? {
+                let mut paragraph = control.0.add_paragraph();
+                crate::json::paragraph_from_json(&mut paragraph, binary, "binary")?;
+            }
+        }
+        Ok(control)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl Control {
+    /// Convert the control file to YAML, using the same structure as [`Control::to_json`].
+    pub fn to_yaml(&self) -> String {
+        self.to_yaml_with(&crate::json::Options::default())
+    }
+
+    /// Convert the control file to YAML, with the given options.
+    pub fn to_yaml_with(&self, options: &crate::json::Options) -> String {
+        crate::json::to_yaml(&self.to_json_with(options))
     }
 }
 
@@ -596,7 +1310,8 @@ impl std::fmt::Display for Control {
 }
 
 /// A binary package paragraph
-pub struct Binary(deb822_lossless::Paragraph);
+#[derive(PartialEq, Eq, Hash)]
+pub struct Binary(deb822_lossless::Paragraph, Listeners);
 
 impl From<Binary> for deb822_lossless::Paragraph {
     fn from(b: Binary) -> Self {
@@ -606,7 +1321,7 @@ impl From<Binary> for deb822_lossless::Paragraph {
 
 impl From<deb822_lossless::Paragraph> for Binary {
     fn from(p: deb822_lossless::Paragraph) -> Self {
-        Binary(p)
+        Binary(p, Listeners::default())
     }
 }
 
@@ -621,7 +1336,13 @@ impl pyo3::ToPyObject for Binary {
 impl pyo3::FromPyObject<'_> for Binary {
     fn extract_bound(ob: &pyo3::Bound<pyo3::PyAny>) -> pyo3::PyResult<Self> {
         use pyo3::prelude::*;
-        Ok(Binary(ob.extract()?))
+        Ok(Binary(ob.extract()?, Listeners::default()))
+    }
+}
+
+impl FieldSetters for Binary {
+    fn parts_mut(&mut self) -> (&mut deb822_lossless::Paragraph, &Listeners) {
+        (&mut self.0, &self.1)
     }
 }
 
@@ -634,7 +1355,7 @@ impl Default for Binary {
 impl Binary {
     /// Create a new binary package control file
     pub fn new() -> Self {
-        Binary(deb822_lossless::Paragraph::new())
+        Binary(deb822_lossless::Paragraph::new(), Listeners::default())
     }
 
     /// Return the underlying deb822 paragraph, mutable
@@ -670,217 +1391,341 @@ impl Binary {
 
     /// Set the name of the package
     pub fn set_name(&mut self, name: &str) {
-        self.0.set("Package", name);
+        self.set_field("Package", name);
     }
 
     /// The section of the package.
-    pub fn section(&self) -> Option<String> {
+    pub fn section(&self) -> Option<Result<Section, FieldError>> {
+        parse_field(&self.0, "Section")
+    }
+
+    /// The section of the package, as written in the control file
+    pub fn raw_section(&self) -> Option<String> {
         self.0.get("Section")
     }
 
     /// Set the section
-    pub fn set_section(&mut self, section: Option<&str>) {
-        if let Some(section) = section {
-            self.0.set("Section", section);
-        } else {
-            self.0.remove("Section");
-        }
+    pub fn set_section(&mut self, section: Option<&Section>) {
+        self.set_optional_field("Section", section);
     }
 
     /// The priority of the package.
     pub fn priority(&self) -> Option<Priority> {
-        self.0.get("Priority").and_then(|v| v.parse().ok())
+        self.0.get_ref("Priority").and_then(|v| v.parse().ok())
     }
 
     /// Set the priority of the package
     pub fn set_priority(&mut self, priority: Option<Priority>) {
-        if let Some(priority) = priority {
-            self.0.set("Priority", priority.to_string().as_str());
-        } else {
-            self.0.remove("Priority");
-        }
+        self.set_optional_field("Priority", priority);
     }
 
     /// The architecture of the package.
-    pub fn architecture(&self) -> Option<String> {
-        self.0.get("Architecture")
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo\nArchitecture: linux-any\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let binary = control.binaries().next().unwrap();
+    /// let archs = binary.architecture().unwrap().unwrap();
+    /// assert!(archs.matches("amd64"));
+    /// assert!(!archs.matches("hurd-amd64"));
+    /// ```
+    pub fn architecture(&self) -> Option<Result<Architectures, FieldError>> {
+        parse_field(&self.0, "Architecture")
     }
 
     /// Set the architecture of the package
-    pub fn set_architecture(&mut self, arch: Option<&str>) {
-        if let Some(arch) = arch {
-            self.0.set("Architecture", arch);
-        } else {
-            self.0.remove("Architecture");
-        }
+    pub fn set_architecture(&mut self, arch: Option<&Architectures>) {
+        self.set_optional_field("Architecture", arch);
     }
 
     /// The dependencies of the package.
-    pub fn depends(&self) -> Option<Relations> {
-        self.0.get("Depends").map(|s| s.parse().unwrap())
+    pub fn depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Depends")
     }
 
     /// Set the Depends field
     pub fn set_depends(&mut self, depends: Option<&Relations>) {
-        if let Some(depends) = depends {
-            self.0.set("Depends", depends.to_string().as_str());
-        } else {
-            self.0.remove("Depends");
-        }
+        self.set_optional_field("Depends", depends);
     }
 
     /// The package that this package recommends
-    pub fn recommends(&self) -> Option<Relations> {
-        self.0.get("Recommends").map(|s| s.parse().unwrap())
+    pub fn recommends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Recommends")
     }
 
     /// Set the Recommends field
     pub fn set_recommends(&mut self, recommends: Option<&Relations>) {
-        if let Some(recommends) = recommends {
-            self.0.set("Recommends", recommends.to_string().as_str());
-        } else {
-            self.0.remove("Recommends");
-        }
+        self.set_optional_field("Recommends", recommends);
     }
 
     /// Packages that this package suggests
-    pub fn suggests(&self) -> Option<Relations> {
-        self.0.get("Suggests").map(|s| s.parse().unwrap())
+    pub fn suggests(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Suggests")
     }
 
     /// Set the Suggests field
     pub fn set_suggests(&mut self, suggests: Option<&Relations>) {
-        if let Some(suggests) = suggests {
-            self.0.set("Suggests", suggests.to_string().as_str());
-        } else {
-            self.0.remove("Suggests");
-        }
+        self.set_optional_field("Suggests", suggests);
     }
 
     /// The package that this package enhances
-    pub fn enhances(&self) -> Option<Relations> {
-        self.0.get("Enhances").map(|s| s.parse().unwrap())
+    pub fn enhances(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Enhances")
     }
 
     /// Set the Enhances field
     pub fn set_enhances(&mut self, enhances: Option<&Relations>) {
-        if let Some(enhances) = enhances {
-            self.0.set("Enhances", enhances.to_string().as_str());
-        } else {
-            self.0.remove("Enhances");
-        }
+        self.set_optional_field("Enhances", enhances);
     }
 
     /// The package that this package pre-depends on
-    pub fn pre_depends(&self) -> Option<Relations> {
-        self.0.get("Pre-Depends").map(|s| s.parse().unwrap())
+    pub fn pre_depends(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Pre-Depends")
     }
 
     /// Set the Pre-Depends field
     pub fn set_pre_depends(&mut self, pre_depends: Option<&Relations>) {
-        if let Some(pre_depends) = pre_depends {
-            self.0.set("Pre-Depends", pre_depends.to_string().as_str());
-        } else {
-            self.0.remove("Pre-Depends");
-        }
+        self.set_optional_field("Pre-Depends", pre_depends);
     }
 
     /// The package that this package breaks
-    pub fn breaks(&self) -> Option<Relations> {
-        self.0.get("Breaks").map(|s| s.parse().unwrap())
+    pub fn breaks(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Breaks")
     }
 
     /// Set the Breaks field
     pub fn set_breaks(&mut self, breaks: Option<&Relations>) {
-        if let Some(breaks) = breaks {
-            self.0.set("Breaks", breaks.to_string().as_str());
-        } else {
-            self.0.remove("Breaks");
-        }
+        self.set_optional_field("Breaks", breaks);
     }
 
     /// The package that this package conflicts with
-    pub fn conflicts(&self) -> Option<Relations> {
-        self.0.get("Conflicts").map(|s| s.parse().unwrap())
+    pub fn conflicts(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Conflicts")
     }
 
     /// Set the Conflicts field
     pub fn set_conflicts(&mut self, conflicts: Option<&Relations>) {
-        if let Some(conflicts) = conflicts {
-            self.0.set("Conflicts", conflicts.to_string().as_str());
-        } else {
-            self.0.remove("Conflicts");
-        }
+        self.set_optional_field("Conflicts", conflicts);
     }
 
     /// The package that this package replaces
-    pub fn replaces(&self) -> Option<Relations> {
-        self.0.get("Replaces").map(|s| s.parse().unwrap())
+    pub fn replaces(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Replaces")
     }
 
     /// Set the Replaces field
     pub fn set_replaces(&mut self, replaces: Option<&Relations>) {
-        if let Some(replaces) = replaces {
-            self.0.set("Replaces", replaces.to_string().as_str());
-        } else {
-            self.0.remove("Replaces");
-        }
+        self.set_optional_field("Replaces", replaces);
     }
 
     /// Return the Provides field
-    pub fn provides(&self) -> Option<Relations> {
-        self.0.get("Provides").map(|s| s.parse().unwrap())
+    pub fn provides(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Provides")
     }
 
     /// Set the Provides field
     pub fn set_provides(&mut self, provides: Option<&Relations>) {
-        if let Some(provides) = provides {
-            self.0.set("Provides", provides.to_string().as_str());
-        } else {
-            self.0.remove("Provides");
-        }
+        self.set_optional_field("Provides", provides);
     }
 
     /// Return the Built-Using field
-    pub fn built_using(&self) -> Option<Relations> {
-        self.0.get("Built-Using").map(|s| s.parse().unwrap())
+    pub fn built_using(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Built-Using")
     }
 
     /// Set the Built-Using field
     pub fn set_built_using(&mut self, built_using: Option<&Relations>) {
-        if let Some(built_using) = built_using {
-            self.0.set("Built-Using", built_using.to_string().as_str());
-        } else {
-            self.0.remove("Built-Using");
-        }
+        self.set_optional_field("Built-Using", built_using);
+    }
+
+    /// Return the Static-Built-Using field
+    pub fn static_built_using(&self) -> Option<Result<Relations, FieldError>> {
+        parse_relations(&self.0, "Static-Built-Using")
+    }
+
+    /// Set the Static-Built-Using field
+    pub fn set_static_built_using(&mut self, static_built_using: Option<&Relations>) {
+        self.set_optional_field("Static-Built-Using", static_built_using);
     }
 
     /// The Multi-Arch field
-    pub fn multi_arch(&self) -> Option<MultiArch> {
-        self.0.get("Multi-Arch").map(|s| s.parse().unwrap())
+    ///
+    /// Unknown values are reported as an error that includes the value, so that they can be
+    /// shown to the user or preserved.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::MultiArch;
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo\nMulti-Arch: same\n\nPackage: bar\nMulti-Arch: sometimes\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut binaries = control.binaries();
+    /// assert_eq!(binaries.next().unwrap().multi_arch(), Some(Ok(MultiArch::Same)));
+    /// let err = binaries.next().unwrap().multi_arch().unwrap().unwrap_err();
+    /// assert_eq!(err.value, "sometimes");
+    /// ```
+    pub fn multi_arch(&self) -> Option<Result<MultiArch, FieldError>> {
+        parse_field(&self.0, "Multi-Arch")
     }
 
     /// Set the Multi-Arch field
     pub fn set_multi_arch(&mut self, multi_arch: Option<MultiArch>) {
-        if let Some(multi_arch) = multi_arch {
-            self.0.set("Multi-Arch", multi_arch.to_string().as_str());
-        } else {
-            self.0.remove("Multi-Arch");
-        }
+        self.set_optional_field("Multi-Arch", multi_arch);
+    }
+
+    /// The type of the package, from the Package-Type field
+    ///
+    /// Packages without a Package-Type field are regular binary packages, of type
+    /// [`PackageType::Deb`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::PackageType;
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo-udeb\nPackage-Type: udeb\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let binary = control.binaries().next().unwrap();
+    /// assert_eq!(binary.package_type(), Some(Ok(PackageType::Udeb)));
+    /// ```
+    pub fn package_type(&self) -> Option<Result<PackageType, FieldError>> {
+        parse_field(&self.0, "Package-Type")
+    }
+
+    /// Set the Package-Type field
+    pub fn set_package_type(&mut self, package_type: Option<&PackageType>) {
+        self.set_optional_field("Package-Type", package_type);
+    }
+
+    /// The build profiles the package is built for, from the Build-Profiles field
+    pub fn build_profiles(&self) -> Option<Result<RestrictionFormula, FieldError>> {
+        parse_field(&self.0, "Build-Profiles")
+    }
+
+    /// Set the Build-Profiles field
+    ///
+    /// The field is removed if `formula` is `None` or empty.
+    pub fn set_build_profiles(&mut self, formula: Option<&RestrictionFormula>) {
+        self.set_optional_field("Build-Profiles", formula.filter(|f| !f.is_empty()));
+    }
+
+    /// Whether the package is built when building with the given profiles
+    ///
+    /// A package without a Build-Profiles field is always built.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo-doc\nBuild-Profiles: <!nodoc>\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let binary = control.binaries().next().unwrap();
+    /// assert_eq!(binary.is_built_with(&[]), Ok(true));
+    /// assert_eq!(binary.is_built_with(&["nodoc", "nocheck"]), Ok(false));
+    /// ```
+    pub fn is_built_with(&self, profiles: &[&str]) -> Result<bool, FieldError> {
+        Ok(self
+            .build_profiles()
+            .transpose()?
+            .is_none_or(|formula| formula.is_satisfied_by(profiles)))
     }
 
     /// Whether the package is essential
-    pub fn essential(&self) -> bool {
-        self.0.get("Essential").map(|s| s == "yes").unwrap_or(false)
+    ///
+    /// A package without an `Essential` field is not essential.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo\nEssential: yes\n\nPackage: bar\nEssential: true\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut binaries = control.binaries();
+    /// assert_eq!(binaries.next().unwrap().essential(), Some(Ok(true)));
+    /// let err = binaries.next().unwrap().essential().unwrap().unwrap_err();
+    /// assert_eq!(err.value, "true");
+    /// ```
+    pub fn essential(&self) -> Option<Result<bool, FieldError>> {
+        parse_yesno(&self.0, "Essential")
     }
 
     /// Set whether the package is essential
+    ///
+    /// The field is removed if `essential` is false.
     pub fn set_essential(&mut self, essential: bool) {
-        if essential {
-            self.0.set("Essential", "yes");
-        } else {
-            self.0.remove("Essential");
-        }
+        self.set_optional_field("Essential", essential.then_some("yes"));
+    }
+
+    /// Whether the package is protected
+    ///
+    /// Like essential packages, protected packages can't be removed without a warning, but
+    /// they are not required to be installed. A package without a `Protected` field is not
+    /// protected.
+    pub fn protected(&self) -> Option<Result<bool, FieldError>> {
+        parse_yesno(&self.0, "Protected")
+    }
+
+    /// Set whether the package is protected
+    ///
+    /// The field is removed if `protected` is false.
+    pub fn set_protected(&mut self, protected: bool) {
+        self.set_optional_field("Protected", protected.then_some("yes"));
+    }
+
+    /// Substitute the substitution variables in the fields of the package
+    ///
+    /// If any variable is not set, the paragraph is left unchanged and all unresolved
+    /// variables are returned in the error.
+    pub fn substitute(&mut self, substvars: &Substvars) -> Result<(), template::Error> {
+        substitute_paragraph(&mut self.0, &self.1, substvars)
+    }
+
+    /// Iterate over the user-defined fields, such as `XS-Go-Import-Path`, with their values
+    pub fn user_fields(&self) -> impl Iterator<Item = (UserField, String)> + '_ {
+        user_fields(&self.0)
+    }
+
+    /// Return the user-defined field with the given name, without prefix, and its value
+    ///
+    /// The name is compared case-insensitively, regardless of the prefix of the field.
+    pub fn user_field(&self, name: &str) -> Option<(UserField, String)> {
+        user_field(&self.0, name)
+    }
+
+    /// Set a user-defined field
+    ///
+    /// A field with the same name but a different prefix is replaced.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::fields::{FieldTargets, UserField};
+    /// use debian_control::lossless::control::Control;
+    /// let control: Control = "Source: foo\n\nPackage: foo\nXC-Built-For: amd64\n"
+    ///     .parse()
+    ///     .unwrap();
+    /// let mut binary = control.binaries().next().unwrap();
+    /// let targets = FieldTargets { binary: true, ..Default::default() };
+    /// binary.set_user_field(&UserField::new("Go-Import-Path", targets), "example.com/foo");
+    /// assert!(binary.remove_user_field("built-for"));
+    /// assert_eq!(
+    ///     control.to_string(),
+    ///     "Source: foo\n\nPackage: foo\nXB-Go-Import-Path: example.com/foo\n"
+    /// );
+    /// ```
+    pub fn set_user_field(&mut self, field: &UserField, value: &str) {
+        set_user_field(&mut self.0, &self.1, field, value)
+    }
+
+    /// Remove the user-defined field with the given name, without prefix
+    ///
+    /// # Returns
+    /// Whether a field was removed
+    pub fn remove_user_field(&mut self, name: &str) -> bool {
+        remove_user_field(&mut self.0, &self.1, name)
     }
 
     /// Binary package description
@@ -888,29 +1733,107 @@ impl Binary {
         self.0.get("Description")
     }
 
-    /// Set the binary package description
-    pub fn set_description(&mut self, description: Option<&str>) {
-        if let Some(description) = description {
-            self.0.set("Description", description);
-        } else {
-            self.0.remove("Description");
+    /// The synopsis of the package: the first line of its description
+    pub fn synopsis(&self) -> Option<String> {
+        self.0
+            .get_ref("Description")
+            .map(|d| d.lines().next().unwrap_or_default().trim().to_string())
+    }
+
+    /// The extended description of the package: the lines of its description after the
+    /// synopsis
+    ///
+    /// Lines consisting of a single `.` are returned as empty lines. Returns `None` if the
+    /// description consists of just the synopsis.
+    pub fn extended_description(&self) -> Option<String> {
+        let description = self.0.get_ref("Description")?;
+        let extended = description.lines().skip(1).collect::<Vec<_>>();
+        if extended.is_empty() {
+            return None;
+        }
+        Some(FieldKind::Multiline.decode(&extended.join("\n")))
+    }
+
+    /// Set the binary package description, from a synopsis and an extended description
+    ///
+    /// Empty lines in `extended` are written as `.`.
+    ///
+    /// # Returns
+    /// Warnings about parts of the description that do not follow Debian policy, such as a
+    /// synopsis of more than 80 characters. The description is set regardless.
+    ///
+    /// # Errors
+    /// If the synopsis is empty or spans multiple lines; the description is left unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::control::Control;
+    /// let mut control = Control::new();
+    /// let mut binary = control.add_binary("foo");
+    /// let warnings = binary
+    ///     .set_description(
+    ///         "tool that does foo",
+    ///         Some("Foo does things.\n\nIt also does other things."),
+    ///     )
+    ///     .unwrap();
+    /// assert!(warnings.is_empty());
+    /// assert_eq!(
+    ///     control.to_string(),
+    ///     "Package: foo\nDescription: tool that does foo\n Foo does things.\n .\n It also does other things.\n"
+    /// );
+    /// assert_eq!(binary.synopsis(), Some("tool that does foo".to_owned()));
+    /// assert_eq!(
+    ///     binary.extended_description(),
+    ///     Some("Foo does things.\n\nIt also does other things.".to_owned())
+    /// );
+    /// ```
+    pub fn set_description(
+        &mut self,
+        synopsis: &str,
+        extended: Option<&str>,
+    ) -> Result<Vec<DescriptionWarning>, ControlError> {
+        let trimmed = synopsis.trim();
+        if trimmed.is_empty() || trimmed.contains('\n') {
+            return Err(ControlError::invalid_value("synopsis", synopsis));
         }
+        let synopsis = trimmed;
+        let mut warnings = vec![];
+        let length = synopsis.chars().count();
+        if length > MAX_SYNOPSIS_LENGTH {
+            warnings.push(DescriptionWarning::SynopsisTooLong { length });
+        }
+        let value = match extended.map(|e| e.trim_matches('\n')) {
+            Some(extended) if !extended.trim().is_empty() => {
+                format!("{}\n{}", synopsis, extended)
+            }
+            _ => synopsis.to_string(),
+        };
+        self.set_field("Description", &FieldKind::Multiline.encode(&value));
+        Ok(warnings)
+    }
+
+    /// Remove the binary package description
+    pub fn remove_description(&mut self) {
+        self.remove_field("Description");
     }
 
     /// Return the upstream homepage
     pub fn homepage(&self) -> Option<url::Url> {
-        self.0.get("Homepage").and_then(|s| s.parse().ok())
+        self.0.get_ref("Homepage").and_then(|s| s.parse().ok())
     }
 
     /// Set the upstream homepage
     pub fn set_homepage(&mut self, url: &url::Url) {
-        self.0.set("Homepage", url.as_str());
+        self.set_field("Homepage", url.as_str());
     }
 }
 
+impl_semantic_eq!(Control, Source, Binary);
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fields::SectionWarning;
     use crate::relations::VersionConstraint;
     #[test]
     fn test_parse() {
@@ -926,13 +1849,13 @@ Homepage: https://example.com
         let source = control.source().unwrap();
 
         assert_eq!(source.name(), Some("foo".to_owned()));
-        assert_eq!(source.section(), Some("libs".to_owned()));
+        assert_eq!(source.raw_section(), Some("libs".to_owned()));
         assert_eq!(source.priority(), Some(super::Priority::Optional));
         assert_eq!(
             source.homepage(),
             Some("https://example.com".parse().unwrap())
         );
-        let bd = source.build_depends().unwrap();
+        let bd = source.build_depends().unwrap().unwrap();
         let entries = bd.entries().collect::<Vec<_>>();
         assert_eq!(entries.len(), 2);
         let rel = entries[0].relations().collect::<Vec<_>>().pop().unwrap();
@@ -955,6 +1878,30 @@ Homepage: https://example.com
         );
     }
 
+    #[test]
+    fn test_semantic_eq() {
+        use deb822_lossless::SemanticEq;
+
+        let a: Control = "Source: foo\nSection: net\n\nPackage: foo\nArchitecture: all\n"
+            .parse()
+            .unwrap();
+        let b: Control =
+            "Section: net\nSource: foo\n\n# binary\nPackage: foo\nArchitecture:  all\n"
+                .parse()
+                .unwrap();
+        assert!(a != b);
+        assert!(a == a.to_string().parse().unwrap());
+        assert!(a.semantic_eq(&b));
+        assert!(a.source().unwrap().semantic_eq(&b.source().unwrap()));
+        assert!(!a.source().unwrap().semantic_eq(
+            &"Source: bar\n"
+                .parse::<Control>()
+                .unwrap()
+                .source()
+                .unwrap()
+        ));
+    }
+
     #[test]
     fn test_description() {
         let control: Control = r#"Source: foo
@@ -975,6 +1922,50 @@ Description: this is the short description
                     .to_owned()
             )
         );
+        assert_eq!(
+            binary.synopsis(),
+            Some("this is the short description".to_owned())
+        );
+        assert_eq!(
+            binary.extended_description(),
+            Some("And the longer one\n\nis on the next lines".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_set_description() {
+        let control: Control = "Source: foo\n\nPackage: foo\nDescription: old\nSection: net\n"
+            .parse()
+            .unwrap();
+        let mut binary = control.binaries().next().unwrap();
+        assert_eq!(binary.extended_description(), None);
+        let long = "x".repeat(81);
+        let warnings = binary.set_description(&long, Some("\n\n")).unwrap();
+        assert_eq!(
+            warnings,
+            vec![DescriptionWarning::SynopsisTooLong { length: 81 }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "synopsis is 81 characters long, more than 80"
+        );
+        assert_eq!(binary.extended_description(), None);
+        assert!(binary
+            .set_description("short", Some("First paragraph.\n\n  verbatim\n"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\n\nPackage: foo\nDescription: short\n First paragraph.\n .\n   verbatim\nSection: net\n"
+        );
+        for invalid in [" ", "two\nlines"] {
+            let err = binary.set_description(invalid, None).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid synopsis: {:?}", invalid));
+        }
+        assert_eq!(binary.synopsis(), Some("short".to_owned()));
+        binary.remove_description();
+        assert_eq!(binary.description(), None);
+        assert_eq!(binary.synopsis(), None);
     }
 
     #[test]
@@ -1000,6 +1991,470 @@ Description: this is the short description
         binary.set_depends(Some(&relations));
     }
 
+    #[test]
+    fn test_source_setters() {
+        let control: Control = r#"Source: foo
+# Keep in sync with upstream
+Build-Depends: debhelper-compat (= 13),
+               python3
+Build-Depends-Indep: python3-sphinx
+Standards-Version: 4.5.0
+
+Package: foo
+"#
+        .parse()
+        .unwrap();
+        let mut source = control.source().unwrap();
+        let mut build_depends = source.build_depends().unwrap().unwrap();
+        build_depends.push("python3-setuptools".parse().unwrap());
+        source.set_build_depends(&build_depends);
+        source.set_build_depends_indep(None);
+        source.set_build_conflicts(Some(&"python3-foo".parse().unwrap()));
+        source.set_standards_version("4.7.0");
+        source.set_section(Some(&"python".parse().unwrap()));
+        assert_eq!(
+            control.to_string(),
+            r#"Source: foo
+# Keep in sync with upstream
+Build-Depends: debhelper-compat (= 13),
+               python3, python3-setuptools
+Standards-Version: 4.7.0
+Build-Conflicts: python3-foo
+Section: python
+
+Package: foo
+"#
+        );
+    }
+
+    #[test]
+    fn test_binary_setters() {
+        let control: Control = r#"Source: foo
+
+Package: foo
+Architecture: any
+# Needed for the plugin loader
+Depends: libc6,
+         libbar1
+Description: foo
+ Foo does things.
+"#
+        .parse()
+        .unwrap();
+        let mut binary = control.binaries().next().unwrap();
+        binary.set_architecture(Some(&"linux-any".parse().unwrap()));
+        let mut depends = binary.depends().unwrap().unwrap();
+        depends.remove_entry(1);
+        binary.set_depends(Some(&depends));
+        binary.set_section(Some(&"libs".parse().unwrap()));
+        binary.set_multi_arch(Some(MultiArch::Same));
+        binary.set_static_built_using(Some(&"rustc (= 1.80.0)".parse().unwrap()));
+        assert_eq!(
+            binary.static_built_using(),
+            Some(Ok("rustc (= 1.80.0)".parse().unwrap()))
+        );
+        assert_eq!(
+            control.to_string(),
+            r#"Source: foo
+
+Package: foo
+Architecture: linux-any
+# Needed for the plugin loader
+Depends: libc6
+Description: foo
+ Foo does things.
+Section: libs
+Multi-Arch: same
+Static-Built-Using: rustc (= 1.80.0)
+"#
+        );
+    }
+
+    #[test]
+    fn test_relations_substvars() {
+        let control: Control = r#"Source: foo
+Build-Depends: debhelper-compat (= 13), ${python3:Depends}
+
+Package: foo
+Depends: ${shlibs:Depends}, ${misc:Depends}, libc6
+Recommends: foo (>= 1.0
+"#
+        .parse()
+        .unwrap();
+        let source = control.source().unwrap();
+        let build_depends = source.build_depends().unwrap().unwrap();
+        assert_eq!(
+            build_depends.substvars().collect::<Vec<_>>(),
+            vec!["${python3:Depends}"]
+        );
+        let binary = control.binaries().next().unwrap();
+        let depends = binary.depends().unwrap().unwrap();
+        assert_eq!(
+            depends.substvars().collect::<Vec<_>>(),
+            vec!["${shlibs:Depends}", "${misc:Depends}"]
+        );
+        assert_eq!(depends.entries().count(), 1);
+        let err = binary.recommends().unwrap().unwrap_err();
+        assert_eq!(err.field, "Recommends");
+        assert_eq!(err.value, "foo (>= 1.0");
+        assert!(binary.suggests().is_none());
+    }
+
+    #[test]
+    fn test_from_file_error() {
+        let dir = std::env::temp_dir().join(format!(
+            "debian-control-from-file-error-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing");
+        let Err(err) = Control::from_file(&missing) else {
+            panic!("expected an error");
+        };
+        assert_eq!(err.path, missing);
+        assert!(matches!(err.error, deb822_lossless::Error::IoError(_)));
+
+        let invalid = dir.join("control");
+        std::fs::write(&invalid, "Source foo\n").unwrap();
+        let Err(err) = Control::from_file(&invalid) else {
+            panic!("expected an error");
+        };
+        assert_eq!(err.path, invalid);
+        assert!(matches!(err.error, deb822_lossless::Error::ParseError(_)));
+        assert!(err
+            .to_string()
+            .starts_with(&format!("{}: ", invalid.display())));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut control: Control = "Source: foo\nBuild-Depends: debhelper-compat (= 13)\n\nPackage: foo\n# Generated\nDepends: ${shlibs:Depends},\n ${misc:Depends}\n\nPackage: foo-dev\nDepends: foo (= ${binary:Version}), ${unknown}\n"
+            .parse()
+            .unwrap();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let log = changes.clone();
+        control.on_change(move |change| log.borrow_mut().push(change.field.to_string()));
+
+        let mut substvars = Substvars::new();
+        substvars.set("shlibs:Depends", "libc6 (>= 2.36)");
+        substvars.set("misc:Depends", "adduser");
+        substvars.set_version(&"1.0-1".parse().unwrap());
+        let text = control.to_string();
+        assert_eq!(
+            control.substitute(&substvars).unwrap_err().unknown,
+            vec!["unknown"]
+        );
+        assert_eq!(control.to_string(), text);
+
+        let mut binary = control.binaries().next().unwrap();
+        binary.substitute(&substvars).unwrap();
+        assert_eq!(changes.borrow().as_slice(), &["Depends".to_string()]);
+
+        substvars.set("unknown", "bar");
+        control.substitute(&substvars).unwrap();
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\nBuild-Depends: debhelper-compat (= 13)\n\nPackage: foo\n# Generated\nDepends: libc6 (>= 2.36),\n adduser\n\nPackage: foo-dev\nDepends: foo (= 1.0-1), bar\n"
+        );
+        assert_eq!(changes.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_substitute_empty_relations() {
+        let mut control: Control = "Source: foo
+
+Package: foo
+Depends: ${misc:Depends}, libc6
+Recommends: ${misc:Recommends}
+Description: ${misc:Depends}, bar
+"
+        .parse()
+        .unwrap();
+        let mut substvars = Substvars::new();
+        substvars.set("misc:Depends", "");
+        substvars.set("misc:Recommends", "");
+        control.substitute(&substvars).unwrap();
+        assert_eq!(
+            control.to_string(),
+            "Source: foo
+
+Package: foo
+Depends: libc6
+Description: , bar
+"
+        );
+    }
+
+    #[test]
+    fn test_section() {
+        let control: Control =
+            "Source: foo\nSection: libs\n\nPackage: foo\nSection: private/libz\n"
+                .parse()
+                .unwrap();
+        let mut binary = control.binaries().next().unwrap();
+        let section = binary.section().unwrap().unwrap();
+        assert_eq!(section, Section::new(Some("private"), "libz"));
+        assert!(!section.is_known());
+        assert_eq!(
+            section.validate(),
+            vec![
+                SectionWarning::UnknownComponent("private".to_string()),
+                SectionWarning::UnknownSection("libz".to_string())
+            ]
+        );
+        assert_eq!(section.validate()[1].to_string(), "unknown section: libz");
+        binary.set_section(Some(&Section::new(Some("contrib"), "libs")));
+        assert_eq!(binary.raw_section(), Some("contrib/libs".to_string()));
+        assert!(binary.section().unwrap().unwrap().validate().is_empty());
+        for invalid in ["/libs", "contrib/", "a/b/c", "lib s"] {
+            binary.as_mut_deb822().set("Section", invalid);
+            assert!(binary.section().unwrap().is_err(), "{}", invalid);
+        }
+        assert_eq!(
+            control.source().unwrap().section(),
+            Some(Ok(Section::new(None, "libs")))
+        );
+    }
+
+    #[test]
+    fn test_package_type() {
+        let control: Control = "Source: foo\n\nPackage: foo\n".parse().unwrap();
+        let mut binary = control.binaries().next().unwrap();
+        assert_eq!(binary.package_type(), None);
+        binary.set_package_type(Some(&PackageType::Udeb));
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\n\nPackage: foo\nPackage-Type: udeb\n"
+        );
+        binary.set_package_type(None);
+        assert_eq!(control.to_string(), "Source: foo\n\nPackage: foo\n");
+        binary.as_mut_deb822().set("Package-Type", "u deb");
+        assert!(binary.package_type().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_build_profiles() {
+        use crate::relations::BuildProfile;
+        let control: Control =
+            "Source: foo\n\nPackage: foo-tests\nBuild-Profiles: <!nocheck !nodoc>\n <pkg.foo.tests>\n"
+                .parse()
+                .unwrap();
+        let mut binary = control.binaries().next().unwrap();
+        let formula = binary.build_profiles().unwrap().unwrap();
+        assert_eq!(
+            formula.iter().collect::<Vec<_>>(),
+            vec![
+                &[
+                    BuildProfile::Disabled("nocheck".to_string()),
+                    BuildProfile::Disabled("nodoc".to_string())
+                ][..],
+                &[BuildProfile::Enabled("pkg.foo.tests".to_string())][..],
+            ]
+        );
+        assert_eq!(binary.is_built_with(&["nodoc"]), Ok(false));
+        assert_eq!(binary.is_built_with(&["nodoc", "pkg.foo.tests"]), Ok(true));
+
+        binary.set_build_profiles(Some(
+            &vec![vec![BuildProfile::Enabled("cross".to_string())]].into(),
+        ));
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\n\nPackage: foo-tests\nBuild-Profiles: <cross>\n"
+        );
+        binary.set_build_profiles(Some(&RestrictionFormula::default()));
+        assert_eq!(binary.build_profiles(), None);
+        assert_eq!(binary.is_built_with(&["cross"]), Ok(true));
+
+        for invalid in ["<>", "<!>", "nocheck", "<nocheck", "<nocheck> ,"] {
+            binary.as_mut_deb822().set("Build-Profiles", invalid);
+            let err = binary.is_built_with(&[]).unwrap_err();
+            assert_eq!(err.field, "Build-Profiles");
+        }
+    }
+
+    #[test]
+    fn test_essential_protected() {
+        let control: Control =
+            "Source: foo\n\nPackage: foo\nProtected: no\n\nPackage: bar\nProtected: Yes\n"
+                .parse()
+                .unwrap();
+        let mut binaries = control.binaries();
+        let mut foo = binaries.next().unwrap();
+        assert_eq!(foo.essential(), None);
+        assert_eq!(foo.protected(), Some(Ok(false)));
+        foo.set_protected(true);
+        foo.set_essential(true);
+        assert_eq!(foo.protected(), Some(Ok(true)));
+        assert_eq!(foo.essential(), Some(Ok(true)));
+        assert_eq!(
+            binaries.next().unwrap().protected(),
+            Some(Err(FieldError::new(
+                "Protected",
+                "Yes",
+                "expected \"yes\" or \"no\""
+            )))
+        );
+        foo.set_essential(false);
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\n\nPackage: foo\nProtected: yes\n\nPackage: bar\nProtected: Yes\n"
+        );
+    }
+
+    #[test]
+    fn test_uploaders() {
+        let control: Control =
+            "Source: foo\nUploaders: \"Doe, John\" <john@example.com>, Jane <JANE@example.com>\n"
+                .parse()
+                .unwrap();
+        let mut source = control.source().unwrap();
+        assert!(source.remove_uploader_by_email("jane@example.com"));
+        assert!(!source.remove_uploader_by_email("jane@example.com"));
+        assert_eq!(
+            source.uploaders(),
+            Some(Ok(vec![Identity::new("Doe, John", "john@example.com")]))
+        );
+        source.set_uploaders(&[
+            Identity::new("Joe", "joe@example.com"),
+            Identity::new("", "anonymous@example.com"),
+        ]);
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\nUploaders: Joe <joe@example.com>, anonymous@example.com\n"
+        );
+        source.set_uploaders(&[]);
+        assert_eq!(source.uploaders(), None);
+        source.add_uploader(&Identity::new("Doe, John", "john@example.com"));
+        source
+            .as_mut_deb822()
+            .set("Uploaders", "\"Doe, John\" <john@example.com>, somebody");
+        let err = source.uploaders().unwrap().unwrap_err();
+        assert_eq!(err.field, "Uploaders");
+        assert_eq!(err.value, "\"Doe, John\" <john@example.com>, somebody");
+    }
+
+    #[test]
+    fn test_uploaders_unquoted_comma() {
+        let control: Control =
+            "Source: foo\nUploaders: Doe, John <j@d.o>, Jane Roe <jane@example.com>\n"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            control.source().unwrap().uploaders(),
+            Some(Ok(vec![
+                Identity::new("Doe, John", "j@d.o"),
+                Identity::new("Jane Roe", "jane@example.com"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_testsuite() {
+        let control: Control = "Source: foo\n".parse().unwrap();
+        let mut source = control.source().unwrap();
+        assert_eq!(source.testsuite(), None);
+        assert!(!source.expects_tests_control());
+        source.set_testsuite(&[Testsuite::AutopkgtestPkg("go".to_string())]);
+        assert!(!source.expects_tests_control());
+        source.set_testsuite(&[
+            Testsuite::Autopkgtest,
+            Testsuite::AutopkgtestPkg("go".to_string()),
+        ]);
+        assert!(source.expects_tests_control());
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\nTestsuite: autopkgtest, autopkgtest-pkg-go\n"
+        );
+        source.set_testsuite(&[]);
+        assert_eq!(control.to_string(), "Source: foo\n");
+        source
+            .as_mut_deb822()
+            .set("Testsuite", "autopkgtest, not valid");
+        let err = source.testsuite().unwrap().unwrap_err();
+        assert_eq!(err.field, "Testsuite");
+        assert!(!source.expects_tests_control());
+    }
+
+    #[test]
+    fn test_user_fields() {
+        use crate::fields::FieldTargets;
+        let control: Control = "Source: foo\nXSC-Origin-Url: https://example.com/\nX-Local: yes\nXS-: bogus\n\nPackage: foo\nXB-Built-Using-Go: 1.21\n"
+            .parse()
+            .unwrap();
+        let mut source = control.source().unwrap();
+        let fields: Vec<_> = source.user_fields().collect();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    UserField::new(
+                        "Origin-Url",
+                        FieldTargets {
+                            source: true,
+                            changes: true,
+                            ..Default::default()
+                        }
+                    ),
+                    "https://example.com/".to_string()
+                ),
+                (
+                    UserField::new("Local", FieldTargets::default()),
+                    "yes".to_string()
+                ),
+            ]
+        );
+        source.set_user_field(
+            &UserField::new(
+                "local",
+                FieldTargets {
+                    binary: true,
+                    ..Default::default()
+                },
+            ),
+            "no",
+        );
+        assert!(source.remove_user_field("ORIGIN-URL"));
+        assert!(!source.remove_user_field("Origin-Url"));
+        assert_eq!(source.user_field("missing"), None);
+
+        let mut binary = control.binaries().next().unwrap();
+        let (field, value) = binary.user_field("built-using-go").unwrap();
+        assert!(field.targets.binary && !field.targets.source);
+        assert_eq!(value, "1.21");
+        assert!(binary.remove_user_field("Built-Using-Go"));
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\nXB-local: no\nXS-: bogus\n\nPackage: foo\n"
+        );
+    }
+
+    #[test]
+    fn test_push_remove_binary() {
+        let mut control: Control = "Source: foo\n\nPackage: foo\n\n\nPackage: foo-doc"
+            .parse()
+            .unwrap();
+        let binary = control.push_binary(
+            crate::builder::ParagraphBuilder::new()
+                .field("Package", "foo-dev")
+                .field("Architecture", "any"),
+        );
+        assert_eq!(binary.architecture(), Some(Ok("any".parse().unwrap())));
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\n\nPackage: foo\n\n\nPackage: foo-doc\n\nPackage: foo-dev\nArchitecture: any\n"
+        );
+        assert!(control.remove_binary("foo-dev"));
+        assert_eq!(
+            control.to_string(),
+            "Source: foo\n\nPackage: foo\n\n\nPackage: foo-doc\n"
+        );
+        assert!(control.remove_binary("foo"));
+        assert!(control.remove_binary("foo-doc"));
+        assert_eq!(control.to_string(), "Source: foo\n");
+        assert_eq!(control.binaries().count(), 0);
+    }
+
     #[test]
     fn test_wrap_and_sort() {
         let mut control: Control = r#"Package: blah
@@ -1042,4 +2497,105 @@ Depends: bar (<= 1.0.0), foo
         .to_owned();
         assert_eq!(control.to_string(), expected);
     }
+
+    #[test]
+    fn test_on_change() {
+        let mut control: Control = "Source: foo\n\nPackage: foo\nSection: net\n"
+            .parse()
+            .unwrap();
+        let mut source = control.source().unwrap();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let log = changes.clone();
+        control.on_change(move |change| {
+            log.borrow_mut().push((
+                change
+                    .paragraph
+                    .get("Package")
+                    .or(change.paragraph.get("Source")),
+                change.field.to_string(),
+                change.old_value.clone(),
+                change.new_value.clone(),
+            ));
+        });
+
+        source.set_maintainer(&Identity::new("Joe", "joe@example.com"));
+        let mut binary = control.binaries().next().unwrap();
+        binary.set_section(None);
+        // Unchanged values are not reported.
+        binary.set_section(None);
+        binary.set_name("foo");
+        control.add_binary("bar").set_essential(true);
+        // Neither are changes made outside the typed setters.
+        source
+            .as_mut_deb822()
+            .set("Homepage", "https://example.com/");
+
+        assert_eq!(
+            changes.borrow().as_slice(),
+            &[
+                (
+                    Some("foo".to_string()),
+                    "Maintainer".to_string(),
+                    None,
+                    Some("Joe <joe@example.com>".to_string())
+                ),
+                (
+                    Some("foo".to_string()),
+                    "Section".to_string(),
+                    Some("net".to_string()),
+                    None
+                ),
+                (
+                    Some("bar".to_string()),
+                    "Essential".to_string(),
+                    None,
+                    Some("yes".to_string())
+                ),
+            ]
+        );
+
+        // Paragraphs that are not part of a control file have no listeners.
+        let mut binary = Binary::new();
+        binary.set_name("baz");
+        assert_eq!(changes.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_fields() {
+        let control: Control = r#"Source: foo
+Rules-Requires-Root: maybe
+Build-Depends: bar (>= 1.0
+
+Package: foo
+Multi-Arch: sometimes
+Depends: baz
+"#
+        .parse()
+        .unwrap();
+        let source = control.source().unwrap();
+        assert_eq!(
+            source
+                .rules_requires_root()
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+            "invalid Rules-Requires-Root field \"maybe\": expected \"yes\" or \"no\""
+        );
+        let err = source.build_depends().unwrap().unwrap_err();
+        assert_eq!(err.field, "Build-Depends");
+        assert_eq!(err.value, "bar (>= 1.0");
+        assert_eq!(source.build_conflicts(), None);
+
+        let binary = control.binaries().next().unwrap();
+        assert_eq!(
+            binary.multi_arch(),
+            Some(Err(FieldError::new(
+                "Multi-Arch",
+                "sometimes",
+                "invalid multiarch: \"sometimes\""
+            )
+            .with_span(80..102)))
+        );
+        assert_eq!(binary.depends().unwrap().unwrap().to_string(), "baz");
+    }
 }
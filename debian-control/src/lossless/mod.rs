@@ -4,6 +4,23 @@
 //! and apt `Release`, `Packages`, and `Sources` files. The parser is lossless, meaning that it
 //! preserves all formatting as well as any possible errors in the files.
 
+/// Implement `SemanticEq` for wrappers around deb822 paragraphs and documents.
+macro_rules! impl_semantic_eq {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl deb822_lossless::SemanticEq for $ty {
+                fn semantic_eq(&self, other: &Self) -> bool {
+                    self.0.semantic_eq(&other.0)
+                }
+
+                fn semantic_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.semantic_hash(state)
+                }
+            }
+        )*
+    };
+}
+
 pub mod apt;
 pub mod buildinfo;
 pub mod changes;
@@ -24,20 +24,11 @@ use debversion::Version;
 use rowan::{Direction, NodeOrToken};
 use std::collections::HashSet;
 
-/// Error type for parsing relations fields
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ParseError(Vec<String>);
-
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for err in &self.0 {
-            writeln!(f, "{}", err)?;
-        }
-        Ok(())
-    }
-}
+pub use crate::error::{RelationsParseError, SyntaxError};
 
-impl std::error::Error for ParseError {}
+/// Error type for parsing relations fields
+#[deprecated(note = "use RelationsParseError instead")]
+pub type ParseError = RelationsParseError;
 
 /// Second, implementing the `Language` trait teaches rowan to convert between
 /// these two SyntaxKind types, allowing for a nicer SyntaxNode API where
@@ -67,8 +58,7 @@ use rowan::GreenNodeBuilder;
 /// We'll discuss working with the results later
 struct Parse {
     green_node: GreenNode,
-    #[allow(unused)]
-    errors: Vec<String>,
+    errors: Vec<SyntaxError>,
 }
 
 fn parse(text: &str, allow_substvar: bool) -> Parse {
@@ -80,7 +70,9 @@ fn parse(text: &str, allow_substvar: bool) -> Parse {
         builder: GreenNodeBuilder<'static>,
         /// the list of syntax errors we've accumulated
         /// so far.
-        errors: Vec<String>,
+        errors: Vec<SyntaxError>,
+        /// length of the input text, used to calculate error spans
+        text_len: usize,
         /// whether to allow substvars
         allow_substvar: bool,
     }
@@ -136,15 +128,20 @@ fn parse(text: &str, allow_substvar: bool) -> Parse {
                     _ => {
                         self.skip_ws();
                         self.builder.start_node(SyntaxKind::ERROR.into());
+                        let span = self.span();
                         match self.tokens.pop() {
                             Some((k, t)) => {
                                 self.builder.token(k.into(), t.as_str());
-                                self.errors
-                                    .push(format!("Expected comma or pipe, not {:?}", (k, t)));
+                                self.errors.push(SyntaxError {
+                                    message: format!("Expected comma or pipe, not {:?}", (k, t)),
+                                    span,
+                                });
                             }
                             None => {
-                                self.errors
-                                    .push("Expected comma or pipe, got end of file".to_string());
+                                self.errors.push(SyntaxError {
+                                    message: "Expected comma or pipe, got end of file".to_string(),
+                                    span,
+                                });
                             }
                         }
                         self.builder.finish_node();
@@ -154,8 +151,18 @@ fn parse(text: &str, allow_substvar: bool) -> Parse {
             self.builder.finish_node();
         }
 
+        /// Byte range of the current token in the input.
+        fn span(&self) -> std::ops::Range<usize> {
+            let remaining: usize = self.tokens.iter().map(|(_, t)| t.len()).sum();
+            let start = self.text_len - remaining;
+            start..start + self.tokens.last().map_or(0, |(_, t)| t.len())
+        }
+
         fn error(&mut self, error: String) {
-            self.errors.push(error);
+            self.errors.push(SyntaxError {
+                message: error,
+                span: self.span(),
+            });
             self.builder.start_node(SyntaxKind::ERROR.into());
             if self.current().is_some() {
                 self.bump();
@@ -205,19 +212,40 @@ fn parse(text: &str, allow_substvar: bool) -> Parse {
 
                 self.builder.start_node(CONSTRAINT.into());
 
+                let start = self.span().start;
+                let mut constraint = String::new();
                 while self.current() == Some(L_ANGLE)
                     || self.current() == Some(R_ANGLE)
                     || self.current() == Some(EQUAL)
                 {
+                    constraint.push_str(&self.tokens.last().unwrap().1);
                     self.bump();
                 }
+                if constraint.parse::<VersionConstraint>().is_err() {
+                    self.errors.push(SyntaxError {
+                        message: format!("Invalid version constraint: {:?}", constraint),
+                        span: start..self.span().start,
+                    });
+                }
 
                 self.builder.finish_node();
 
                 self.skip_ws();
 
                 if self.current() == Some(IDENT) {
-                    self.bump();
+                    let start = self.span().start;
+                    let mut version = String::new();
+                    // The epoch is separated from the rest of the version by a colon
+                    while self.current() == Some(IDENT) || self.current() == Some(COLON) {
+                        version.push_str(&self.tokens.last().unwrap().1);
+                        self.bump();
+                    }
+                    if let Err(e) = version.parse::<Version>() {
+                        self.errors.push(SyntaxError {
+                            message: e.to_string(),
+                            span: start..self.span().start,
+                        });
+                    }
                 } else {
                     self.error("Expected version".to_string());
                 }
@@ -378,6 +406,7 @@ fn parse(text: &str, allow_substvar: bool) -> Parse {
         tokens,
         builder: GreenNodeBuilder::new(),
         errors: Vec::new(),
+        text_len: text.len(),
         allow_substvar,
     }
     .parse()
@@ -555,6 +584,24 @@ impl Relations {
         Self::from(vec![])
     }
 
+    /// Format this relations field according to `options`, for use as the value of `key`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::format::FormatOptions;
+    /// use debian_control::lossless::relations::Relations;
+    ///
+    /// let relations: Relations = "libc6, foo (>=1.0) | bar".parse().unwrap();
+    /// let options = FormatOptions::preset("dpkg-default").unwrap();
+    /// assert_eq!(
+    ///     relations.to_formatted_string("Depends", &options),
+    ///     "libc6, foo (>= 1.0) | bar"
+    /// );
+    /// ```
+    pub fn to_formatted_string(&self, key: &str, options: &crate::format::FormatOptions) -> String {
+        crate::format::format_relations(key, &self.to_string(), options)
+    }
+
     /// Wrap and sort this relations field
     #[must_use]
     pub fn wrap_and_sort(self) -> Self {
@@ -652,10 +699,36 @@ impl Relations {
             .map(|s| s.to_string())
     }
 
+    /// Parse a relations field from a string, allowing substvars
+    ///
+    /// Substvars such as `${misc:Depends}` are used in `debian/control` files and are replaced
+    /// when the binary packages are built. They are kept as-is, and can be listed with
+    /// [`Relations::substvars`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossless::relations::Relations;
+    /// let relations = Relations::parse_with_substvars("${misc:Depends}, libc6").unwrap();
+    /// assert_eq!(relations.substvars().collect::<Vec<_>>(), vec!["${misc:Depends}"]);
+    /// assert_eq!(relations.entries().count(), 1);
+    /// assert!("${misc:Depends}, libc6".parse::<Relations>().is_err());
+    /// ```
+    pub fn parse_with_substvars(s: &str) -> Result<Relations, RelationsParseError> {
+        let parse = parse(s, true);
+        if parse.errors.is_empty() {
+            Ok(parse.root_mut())
+        } else {
+            Err(RelationsParseError::Syntax(parse.errors))
+        }
+    }
+
     /// Parse a relations field from a string, allowing syntax errors
     pub fn parse_relaxed(s: &str, allow_substvar: bool) -> (Relations, Vec<String>) {
         let parse = parse(s, allow_substvar);
-        (parse.root_mut(), parse.errors)
+        (
+            parse.root_mut(),
+            parse.errors.into_iter().map(|e| e.message).collect(),
+        )
     }
 
     /// Check if this relations field is satisfied by the given package versions.
@@ -1300,17 +1373,21 @@ impl Relation {
         let vc = vc.as_ref()?;
         let constraint = vc.children().find(|n| n.kind() == CONSTRAINT);
 
-        let version = vc.children_with_tokens().find_map(|it| match it {
-            SyntaxElement::Token(token) if token.kind() == IDENT => Some(token),
-            _ => None,
-        });
+        let version = vc
+            .children_with_tokens()
+            .filter_map(|it| match it {
+                SyntaxElement::Token(token) if token.kind() == IDENT || token.kind() == COLON => {
+                    Some(token.text().to_string())
+                }
+                _ => None,
+            })
+            .collect::<String>();
+        let version = Some(version).filter(|v| !v.is_empty());
 
-        if let (Some(constraint), Some(version)) = (constraint, version) {
-            let vc: VersionConstraint = constraint.to_string().parse().unwrap();
-            return Some((vc, (version.text().to_string()).parse().unwrap()));
-        } else {
-            None
-        }
+        // Both are checked by the parser, so they only fail to parse in documents that were
+        // parsed leniently.
+        let vc: VersionConstraint = constraint?.to_string().parse().ok()?;
+        Some((vc, version?.parse().ok()?))
     }
 
     /// Set the version constraint for this relation
@@ -1403,6 +1480,8 @@ impl Relation {
 
     /// Return an iterator over the architectures for this relation
     ///
+    /// Negated architectures are prefixed with `!`.
+    ///
     /// # Example
     /// ```
     /// use debian_control::lossless::relations::Relation;
@@ -1412,14 +1491,23 @@ impl Relation {
     pub fn architectures(&self) -> Option<impl Iterator<Item = String> + '_> {
         let architectures = self.0.children().find(|n| n.kind() == ARCHITECTURES)?;
 
-        Some(architectures.children_with_tokens().filter_map(|node| {
-            let token = node.as_token()?;
-            if token.kind() == IDENT {
-                Some(token.text().to_string())
-            } else {
-                None
-            }
-        }))
+        let mut negated = false;
+        Some(
+            architectures
+                .children_with_tokens()
+                .filter_map(move |node| {
+                    let token = node.as_token()?;
+                    match token.kind() {
+                        NOT => {
+                            negated = true;
+                            None
+                        }
+                        IDENT if std::mem::take(&mut negated) => Some(format!("!{}", token.text())),
+                        IDENT => Some(token.text().to_string()),
+                        _ => None,
+                    }
+                }),
+        )
     }
 
     /// Returns an iterator over the build profiles for this relation
@@ -1431,6 +1519,16 @@ impl Relation {
     /// let relation: Relation = "samba <!nocheck>".parse().unwrap();
     /// assert_eq!(relation.profiles().collect::<Vec<_>>(), vec![vec![BuildProfile::Disabled("nocheck".to_string())]]);
     /// ```
+    ///
+    /// The profiles can be collected into a [`RestrictionFormula`](crate::relations::RestrictionFormula)
+    /// to check whether the relation applies to a build:
+    /// ```
+    /// use debian_control::lossless::relations::Relation;
+    /// use debian_control::relations::RestrictionFormula;
+    /// let relation: Relation = "python3-pytest <!nocheck>".parse().unwrap();
+    /// let formula: RestrictionFormula = relation.profiles().collect();
+    /// assert!(!formula.is_satisfied_by(&["nocheck"]));
+    /// ```
     pub fn profiles(&self) -> impl Iterator<Item = Vec<BuildProfile>> + '_ {
         let profiles = self.0.children().filter(|n| n.kind() == PROFILES);
 
@@ -1442,7 +1540,7 @@ impl Relation {
                 match token.kind() {
                     WHITESPACE | NEWLINE => {
                         if !current.is_empty() {
-                            ret.push(current.join("").parse::<BuildProfile>().unwrap());
+                            ret.push(BuildProfile::new(&current.join("")));
                             current = vec![];
                         }
                     }
@@ -1453,7 +1551,7 @@ impl Relation {
                 }
             }
             if !current.is_empty() {
-                ret.push(current.concat().parse().unwrap());
+                ret.push(BuildProfile::new(&current.join("")));
             }
             ret
         })
@@ -1768,20 +1866,20 @@ impl Ord for Relation {
 }
 
 impl std::str::FromStr for Relations {
-    type Err = String;
+    type Err = RelationsParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parse = parse(s, false);
         if parse.errors.is_empty() {
             Ok(parse.root_mut())
         } else {
-            Err(parse.errors.join("\n"))
+            Err(RelationsParseError::Syntax(parse.errors))
         }
     }
 }
 
 impl std::str::FromStr for Entry {
-    type Err = String;
+    type Err = RelationsParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let root: Relations = s.parse()?;
@@ -1790,11 +1888,11 @@ impl std::str::FromStr for Entry {
         let entry = if let Some(entry) = entries.next() {
             entry
         } else {
-            return Err("No entry found".to_string());
+            return Err(RelationsParseError::NoEntry);
         };
 
         if entries.next().is_some() {
-            return Err("Multiple entries found".to_string());
+            return Err(RelationsParseError::MultipleEntries);
         }
 
         Ok(entry)
@@ -1802,7 +1900,7 @@ impl std::str::FromStr for Entry {
 }
 
 impl std::str::FromStr for Relation {
-    type Err = String;
+    type Err = RelationsParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let entry: Entry = s.parse()?;
@@ -1811,11 +1909,11 @@ impl std::str::FromStr for Relation {
         let relation = if let Some(relation) = relations.next() {
             relation
         } else {
-            return Err("No relation found".to_string());
+            return Err(RelationsParseError::NoRelation);
         };
 
         if relations.next().is_some() {
-            return Err("Multiple relations found".to_string());
+            return Err(RelationsParseError::MultipleRelations);
         }
 
         Ok(relation)
@@ -2204,9 +2302,12 @@ mod tests {
 
         assert_eq!(
             "foo, bar".parse::<Entry>().unwrap_err(),
-            "Multiple entries found"
+            RelationsParseError::MultipleEntries
+        );
+        assert_eq!(
+            "".parse::<Entry>().unwrap_err(),
+            RelationsParseError::NoEntry
         );
-        assert_eq!("".parse::<Entry>().unwrap_err(), "No entry found");
     }
 
     #[test]
@@ -2220,11 +2321,71 @@ mod tests {
                 "0.20.21".parse().unwrap()
             ))
         );
+        let parsed: Relation = "dpkg (>= 1:1.21.1)".parse().unwrap();
+        assert_eq!(parsed.to_string(), "dpkg (>= 1:1.21.1)");
+        assert_eq!(
+            parsed.version(),
+            Some((
+                VersionConstraint::GreaterThanEqual,
+                "1:1.21.1".parse().unwrap()
+            ))
+        );
         assert_eq!(
             "foo | bar".parse::<Relation>().unwrap_err(),
-            "Multiple relations found"
+            RelationsParseError::MultipleRelations
+        );
+        assert_eq!(
+            "".parse::<Relation>().unwrap_err(),
+            RelationsParseError::NoEntry
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_version() {
+        let err = "foo (>= a:1)".parse::<Relations>().unwrap_err();
+        assert_eq!(err.syntax_errors().len(), 1);
+        assert_eq!(err.syntax_errors()[0].span, 8..11);
+        let err = "foo (=> 1.0), bar".parse::<Relations>().unwrap_err();
+        assert_eq!(
+            err.syntax_errors(),
+            &[SyntaxError {
+                message: "Invalid version constraint: \"=>\"".to_string(),
+                span: 5..7,
+            }]
+        );
+
+        let (relations, errors) = Relations::parse_relaxed("foo (>= a:1)", false);
+        assert_eq!(errors.len(), 1);
+        let relation = relations
+            .entries()
+            .next()
+            .unwrap()
+            .relations()
+            .next()
+            .unwrap();
+        assert_eq!(relation.version(), None);
+    }
+
+    #[test]
+    fn test_parse_error_span() {
+        let err = "foo, $bar, baz".parse::<Relations>().unwrap_err();
+        assert_eq!(
+            err.syntax_errors(),
+            &[
+                SyntaxError {
+                    message: "Substvars are not allowed".to_string(),
+                    span: 5..6,
+                },
+                SyntaxError {
+                    message: "expected comma or end of file but got Some(IDENT)".to_string(),
+                    span: 6..9,
+                }
+            ]
+        );
+        assert_eq!(
+            err.to_string(),
+            "Substvars are not allowed\nexpected comma or end of file but got Some(IDENT)"
         );
-        assert_eq!("".parse::<Relation>().unwrap_err(), "No entry found");
     }
 
     #[test]
@@ -2308,6 +2469,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_negated_architectures() {
+        let relation: Relation = "foo [amd64  !i386]".parse().unwrap();
+        assert_eq!(
+            relation.architectures().unwrap().collect::<Vec<_>>(),
+            vec!["amd64".to_string(), "!i386".to_string()]
+        );
+        assert_eq!(relation.wrap_and_sort().to_string(), "foo [amd64 !i386]");
+    }
+
     #[test]
     fn test_wrap_and_sort_relations() {
         let entry: Relations =
@@ -2,18 +2,6 @@
 use crate::lossy::Relations;
 use deb822_lossless::{FromDeb822, FromDeb822Paragraph, ToDeb822, ToDeb822Paragraph};
 
-fn deserialize_components(value: &str) -> Result<Vec<String>, String> {
-    Ok(value.split_whitespace().map(|s| s.to_string()).collect())
-}
-
-fn join_whitespace(components: &[String]) -> String {
-    components.join(" ")
-}
-
-fn deserialize_architectures(value: &str) -> Result<Vec<String>, String> {
-    Ok(value.split_whitespace().map(|s| s.to_string()).collect())
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, ToDeb822, FromDeb822)]
 /// A Release file
 pub struct Release {
@@ -21,19 +9,11 @@ pub struct Release {
     /// The codename of the release
     pub codename: String,
 
-    #[deb822(
-        field = "Components",
-        deserialize_with = deserialize_components,
-        serialize_with = join_whitespace
-    )]
+    #[deb822(field = "Components")]
     /// Components supported by the release
     pub components: Vec<String>,
 
-    #[deb822(
-        field = "Architectures",
-        deserialize_with = deserialize_architectures,
-        serialize_with = join_whitespace
-    )]
+    #[deb822(field = "Architectures")]
     /// Architectures supported by the release
     pub architectures: Vec<String>,
 
@@ -74,10 +54,6 @@ pub struct Release {
     pub acquire_by_hash: bool,
 }
 
-fn deserialize_binaries(value: &str) -> Result<Vec<String>, String> {
-    Ok(value.split_whitespace().map(|s| s.to_string()).collect())
-}
-
 fn join_lines(components: &[String]) -> String {
     components.join("\n")
 }
@@ -86,7 +62,29 @@ fn deserialize_package_list(value: &str) -> Result<Vec<String>, String> {
     Ok(value.split('\n').map(|s| s.to_string()).collect())
 }
 
+/// Serialize versions as strings.
+#[cfg(feature = "serde")]
+mod version_serde {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        version: &debversion::Version,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(version)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<debversion::Version, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, ToDeb822, FromDeb822)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A source
 pub struct Source {
     #[deb822(field = "Directory")]
@@ -98,6 +96,7 @@ pub struct Source {
     pub description: Option<String>,
 
     #[deb822(field = "Version")]
+    #[cfg_attr(feature = "serde", serde(with = "version_serde"))]
     /// Version of the source
     pub version: debversion::Version,
 
@@ -105,7 +104,7 @@ pub struct Source {
     /// Package of the source
     pub package: String,
 
-    #[deb822(field = "Binary", deserialize_with = deserialize_binaries, serialize_with = join_whitespace)]
+    #[deb822(field = "Binary")]
     /// Binaries of the source
     pub binaries: Option<Vec<String>>,
 
@@ -196,17 +195,19 @@ pub struct Source {
     #[deb822(field = "Package-List", deserialize_with = deserialize_package_list, serialize_with = join_lines)]
     /// Package list of the source
     pub package_list: Vec<String>,
+
+    #[deb822(field = "Checksums-Sha256", separator = "\n")]
+    /// SHA-256 checksums of the files of the source
+    pub checksums_sha256: Option<Vec<crate::fields::Sha256Checksum>>,
 }
 
 impl std::str::FromStr for Source {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let para = s
-            .parse::<deb822_lossless::lossy::Paragraph>()
-            .map_err(|e| e.to_string())?;
+        let para = s.parse::<deb822_lossless::lossy::Paragraph>()?;
 
-        FromDeb822Paragraph::from_paragraph(&para)
+        FromDeb822Paragraph::from_paragraph(&para).map_err(crate::ControlError::Paragraph)
     }
 }
 
@@ -219,6 +220,7 @@ impl std::fmt::Display for Source {
 
 /// A package
 #[derive(Debug, Clone, PartialEq, Eq, ToDeb822, FromDeb822)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Package {
     /// The name of the package
     #[deb822(field = "Package")]
@@ -226,6 +228,7 @@ pub struct Package {
 
     /// The version of the package
     #[deb822(field = "Version")]
+    #[cfg_attr(feature = "serde", serde(with = "version_serde"))]
     pub version: debversion::Version,
 
     /// The architecture of the package
@@ -304,6 +307,10 @@ pub struct Package {
     #[deb822(field = "Tag")]
     pub tag: Option<String>,
 
+    /// Filename, relative to the root of the archive
+    #[deb822(field = "Filename")]
+    pub filename: Option<String>,
+
     /// Size
     #[deb822(field = "Size")]
     pub size: Option<usize>,
@@ -317,19 +324,17 @@ pub struct Package {
     pub sha256: Option<String>,
 
     /// Description (MD5)
-    #[deb822(field = "Description-MD5")]
+    #[deb822(field = "Description-md5")]
     pub description_md5: Option<String>,
 }
 
 impl std::str::FromStr for Package {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let para = s
-            .parse::<deb822_lossless::lossy::Paragraph>()
-            .map_err(|e| e.to_string())?;
+        let para = s.parse::<deb822_lossless::lossy::Paragraph>()?;
 
-        FromDeb822Paragraph::from_paragraph(&para)
+        FromDeb822Paragraph::from_paragraph(&para).map_err(crate::ControlError::Paragraph)
     }
 }
 
@@ -340,6 +345,165 @@ impl std::fmt::Display for Package {
     }
 }
 
+impl Package {
+    /// Get the description of the package in a particular language.
+    ///
+    /// The translation is looked up in `translations` using the `Description-md5` field. If
+    /// there is no translation for the given language, the English description is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::lossy::apt::{Package, TranslationFile};
+    /// let package: Package = r#"Package: hello
+    /// Version: 2.10-3
+    /// Architecture: amd64
+    /// Description: example package based on GNU hello
+    /// Description-md5: 6e3ab4b6a1ac8bc6a0ba3a3a38ac4d1b
+    /// "#.parse().unwrap();
+    /// let translations: TranslationFile = r#"Package: hello
+    /// Description-md5: 6e3ab4b6a1ac8bc6a0ba3a3a38ac4d1b
+    /// Description-de: Beispielpaket, das auf GNU hello basiert
+    /// "#.parse().unwrap();
+    /// assert_eq!(
+    ///     package.description_localized("de", &translations),
+    ///     Some("Beispielpaket, das auf GNU hello basiert")
+    /// );
+    /// assert_eq!(
+    ///     package.description_localized("fr", &translations),
+    ///     Some("example package based on GNU hello")
+    /// );
+    /// ```
+    pub fn description_localized<'a>(
+        &'a self,
+        lang: &str,
+        translations: &'a TranslationFile,
+    ) -> Option<&'a str> {
+        self.description_md5
+            .as_deref()
+            .and_then(|md5| translations.description(md5, lang))
+            .or(self.description.as_deref())
+    }
+}
+
+/// The translated descriptions of a package, from a `Translation-<lang>` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    /// The name of the package
+    pub package: String,
+
+    /// MD5 checksum of the English description that was translated
+    pub description_md5: String,
+
+    /// The translated descriptions, as pairs of language code and description
+    pub descriptions: Vec<(String, String)>,
+}
+
+impl Translation {
+    /// Get the translated description for a language.
+    pub fn description(&self, lang: &str) -> Option<&str> {
+        self.descriptions
+            .iter()
+            .find(|(l, _)| l == lang)
+            .map(|(_, description)| description.as_str())
+    }
+
+    fn from_paragraph(
+        para: &deb822_lossless::lossy::Paragraph,
+    ) -> Result<Self, deb822_lossless::FromParagraphError> {
+        use deb822_lossless::FromParagraphError::MissingField;
+
+        let mut package = None;
+        let mut description_md5 = None;
+        let mut descriptions = Vec::new();
+        for (name, value) in para.iter() {
+            if name.eq_ignore_ascii_case("Package") {
+                package = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("Description-md5") {
+                description_md5 = Some(value.to_string());
+            } else if let Some(lang) = name.strip_prefix("Description-") {
+                descriptions.push((lang.to_string(), value.to_string()));
+            }
+        }
+        Ok(Self {
+            package: package.ok_or_else(|| MissingField("Package".to_string()))?,
+            description_md5: description_md5
+                .ok_or_else(|| MissingField("Description-md5".to_string()))?,
+            descriptions,
+        })
+    }
+}
+
+/// A `Translation-<lang>` file, containing translated package descriptions.
+///
+/// Translations are looked up by the MD5 checksum of the English description, as found in the
+/// `Description-md5` field of `Packages` files.
+#[derive(Debug, Clone, Default)]
+pub struct TranslationFile {
+    entries: Vec<Translation>,
+    by_md5: std::collections::HashMap<String, usize>,
+}
+
+impl TranslationFile {
+    /// Create a new, empty translation file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a translation.
+    ///
+    /// If there already is a translation for the same description, it is replaced.
+    pub fn push(&mut self, translation: Translation) {
+        if let Some(idx) = self.by_md5.get(&translation.description_md5) {
+            self.entries[*idx] = translation;
+        } else {
+            self.by_md5
+                .insert(translation.description_md5.clone(), self.entries.len());
+            self.entries.push(translation);
+        }
+    }
+
+    /// Number of translations in the file.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the file contains no translations.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all translations, in their original order.
+    pub fn iter(&self) -> impl Iterator<Item = &Translation> {
+        self.entries.iter()
+    }
+
+    /// Get the translations of the description with the given MD5 checksum.
+    pub fn get(&self, description_md5: &str) -> Option<&Translation> {
+        self.by_md5
+            .get(description_md5)
+            .map(|idx| &self.entries[*idx])
+    }
+
+    /// Get the translation of the description with the given MD5 checksum for a language.
+    pub fn description(&self, description_md5: &str, lang: &str) -> Option<&str> {
+        self.get(description_md5)
+            .and_then(|translation| translation.description(lang))
+    }
+}
+
+impl std::str::FromStr for TranslationFile {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let doc: deb822_lossless::lossy::Deb822 = s.parse()?;
+        let mut ret = Self::new();
+        for para in doc.iter() {
+            ret.push(Translation::from_paragraph(para).map_err(crate::ControlError::Paragraph)?);
+        }
+        Ok(ret)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,4 +567,67 @@ Suggests: apt-doc, aptitude | synaptic | wajig
         assert_eq!(package.version, "2.1.10".parse().unwrap());
         assert_eq!(package.architecture, "amd64");
     }
+    #[test]
+    fn test_translation_file() {
+        let translations: TranslationFile = r#"Package: hello
+Description-md5: 6e3ab4b6a1ac8bc6a0ba3a3a38ac4d1b
+Description-de: Beispielpaket
+ Dies ist ein Beispiel.
+
+Package: world
+Description-md5: 0a1b2c
+Description-de: Welt
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(translations.len(), 2);
+        let hello = translations
+            .get("6e3ab4b6a1ac8bc6a0ba3a3a38ac4d1b")
+            .unwrap();
+        assert_eq!(hello.package, "hello");
+        assert_eq!(
+            hello.description("de"),
+            Some("Beispielpaket\nDies ist ein Beispiel.")
+        );
+        assert_eq!(translations.description("0a1b2c", "de"), Some("Welt"));
+        assert_eq!(translations.description("0a1b2c", "fr"), None);
+        assert!(translations.get("ffff").is_none());
+
+        assert!("Package: hello\nDescription-de: Hallo\n"
+            .parse::<TranslationFile>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_description_localized() {
+        let translations: TranslationFile = "Package: apt\nDescription-md5: 9fb97a88cb7383934ef963352b53b4a7\nDescription-de: Paketverwaltung\n"
+            .parse()
+            .unwrap();
+        let mut package: Package = r#"Package: apt
+Version: 2.1.10
+Architecture: amd64
+Description: commandline package manager
+Description-md5: 9fb97a88cb7383934ef963352b53b4a7
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            package.description_md5.as_deref(),
+            Some("9fb97a88cb7383934ef963352b53b4a7")
+        );
+        assert_eq!(
+            package.description_localized("de", &translations),
+            Some("Paketverwaltung")
+        );
+        assert_eq!(
+            package.description_localized("fr", &translations),
+            Some("commandline package manager")
+        );
+
+        package.description_md5 = None;
+        assert_eq!(
+            package.description_localized("de", &translations),
+            Some("commandline package manager")
+        );
+    }
 }
@@ -127,13 +127,11 @@ pub struct Buildinfo {
 }
 
 impl std::str::FromStr for Buildinfo {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let para: deb822_lossless::lossless::Paragraph = s
-            .parse()
-            .map_err(|e: deb822_lossless::lossless::ParseError| e.to_string())?;
-        Self::from_paragraph(&para)
+        let para: deb822_lossless::lossless::Paragraph = s.parse()?;
+        Self::from_paragraph(&para).map_err(crate::ControlError::Paragraph)
     }
 }
 
@@ -3,7 +3,7 @@ use crate::lossy::relations::Relations;
 use deb822_lossless::{FromDeb822, ToDeb822};
 use deb822_lossless::{FromDeb822Paragraph, ToDeb822Paragraph};
 
-fn deserialize_yesno(s: &str) -> Result<bool, String> {
+pub(crate) fn deserialize_yesno(s: &str) -> Result<bool, String> {
     match s {
         "yes" => Ok(true),
         "no" => Ok(false),
@@ -21,59 +21,44 @@ fn serialize_yesno(b: &bool) -> String {
 
 /// The source package.
 #[derive(FromDeb822, ToDeb822, Default)]
+#[deb822(rename_all = "Train-Case")]
 pub struct Source {
     #[deb822(field = "Source")]
     /// The name of the source package.
     pub name: String,
-    #[deb822(field = "Build-Depends")]
     /// The packages that this package depends on during build.
     pub build_depends: Option<Relations>,
-    #[deb822(field = "Build-Depends-Indep")]
     /// The packages that this package depends on during build.
     pub build_depends_indep: Option<Relations>,
-    #[deb822(field = "Build-Depends-Arch")]
     /// The packages that this package depends on during build.
     pub build_depends_arch: Option<Relations>,
-    #[deb822(field = "Build-Conflicts")]
     /// The packages that this package conflicts with during build.
     pub build_conflicts: Option<Relations>,
-    #[deb822(field = "Build-Conflicts-Indep")]
     /// The packages that this package conflicts with during build.
     pub build_conflicts_indep: Option<Relations>,
-    #[deb822(field = "Build-Conflicts-Arch")]
     /// The packages that this package conflicts with during build.
     pub build_conflicts_arch: Option<Relations>,
-    #[deb822(field = "Standards-Version")]
     /// The version of the Debian Policy Manual that the package complies with.
     pub standards_version: Option<String>,
-    #[deb822(field = "Homepage")]
     /// The homepage of the package.
     pub homepage: Option<url::Url>,
-    #[deb822(field = "Section")]
     /// The section of the package.
     pub section: Option<String>,
-    #[deb822(field = "Priority")]
     /// The priority of the package.
     pub priority: Option<Priority>,
-    #[deb822(field = "Maintainer")]
     /// The maintainer of the package.
     pub maintainer: Option<String>,
-    #[deb822(field = "Uploaders")]
     /// The uploaders of the package.
     pub uploaders: Option<String>,
-    #[deb822(field = "Architecture")]
     /// The architecture the package is built for.
     pub architecture: Option<String>,
-    #[deb822(field = "Rules-Requires-Root", deserialize_with = deserialize_yesno, serialize_with = serialize_yesno)]
+    #[deb822(deserialize_with = deserialize_yesno, serialize_with = serialize_yesno)]
     /// Whether the package's build rules require root.
     pub rules_requires_root: Option<bool>,
-    #[deb822(field = "Testsuite")]
     /// The name of the test suite.
     pub testsuite: Option<String>,
-    #[deb822(field = "Vcs-Git")]
     /// The URL of the Git repository.
     pub vcs_git: Option<crate::vcs::ParsedVcs>,
-    #[deb822(field = "Vcs-Browser")]
     /// The URL to the web interface of the VCS.
     pub vcs_browser: Option<url::Url>,
 }
@@ -88,56 +73,44 @@ impl std::fmt::Display for Source {
 
 /// A binary package.
 #[derive(FromDeb822, ToDeb822, Default)]
+#[deb822(rename_all = "Train-Case")]
 pub struct Binary {
     #[deb822(field = "Package")]
     /// The name of the package.
     pub name: String,
-    #[deb822(field = "Depends")]
     /// The packages that this package depends on.
     pub depends: Option<Relations>,
-    #[deb822(field = "Recommends")]
     /// The packages that this package recommends.
     pub recommends: Option<Relations>,
-    #[deb822(field = "Suggests")]
     /// The packages that this package suggests.
     pub suggests: Option<Relations>,
-    #[deb822(field = "Enhances")]
     /// The packages that this package enhances.
     pub enhances: Option<Relations>,
-    #[deb822(field = "Pre-Depends")]
     /// The packages that this package depends on before it is installed.
     pub pre_depends: Option<Relations>,
-    #[deb822(field = "Breaks")]
     /// The packages that this package breaks.
     pub breaks: Option<Relations>,
-    #[deb822(field = "Conflicts")]
     /// The packages that this package conflicts with.
     pub conflicts: Option<Relations>,
-    #[deb822(field = "Replaces")]
     /// The packages that this package replaces.
     pub replaces: Option<Relations>,
-    #[deb822(field = "Provides")]
     /// The packages that this package provides.
     pub provides: Option<Relations>,
-    #[deb822(field = "Built-Using")]
     /// The packages that this package is built using.
     pub built_using: Option<Relations>,
-    #[deb822(field = "Architecture")]
     /// The architecture the package is built for.
     pub architecture: Option<String>,
-    #[deb822(field = "Section")]
     /// The section of the package.
     pub section: Option<String>,
-    #[deb822(field = "Priority")]
     /// The priority of the package.
     pub priority: Option<Priority>,
-    #[deb822(field = "Multi-Arch")]
     /// The multi-arch field.
     pub multi_arch: Option<crate::fields::MultiArch>,
-    #[deb822(field = "Essential", deserialize_with = deserialize_yesno, serialize_with = serialize_yesno)]
+    /// The type of the package.
+    pub package_type: Option<crate::fields::PackageType>,
+    #[deb822(deserialize_with = deserialize_yesno, serialize_with = serialize_yesno)]
     /// Whether the package is essential.
     pub essential: Option<bool>,
-    #[deb822(field = "Description")]
     /// The description of the package. The first line is the short description, and the rest is the long description.
     pub description: Option<String>,
 }
@@ -170,31 +143,36 @@ impl std::fmt::Display for Control {
 }
 
 impl std::str::FromStr for Control {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let deb822: deb822_lossless::Deb822 =
-            s.parse().map_err(|e| format!("parse error: {}", e))?;
+        let deb822: deb822_lossless::Deb822 = s.parse()?;
 
         let mut source: Option<Source> = None;
         let mut binaries: Vec<Binary> = Vec::new();
 
         for para in deb822.paragraphs() {
             if para.get("Package").is_some() {
-                let binary: Binary = Binary::from_paragraph(&para)?;
+                let binary =
+                    Binary::from_paragraph(&para).map_err(crate::ControlError::Paragraph)?;
                 binaries.push(binary);
             } else if para.get("Source").is_some() {
                 if source.is_some() {
-                    return Err("more than one source paragraph".to_string());
+                    return Err(crate::ControlError::Document(
+                        "more than one source paragraph",
+                    ));
                 }
-                source = Some(Source::from_paragraph(&para)?);
+                source =
+                    Some(Source::from_paragraph(&para).map_err(crate::ControlError::Paragraph)?);
             } else {
-                return Err("paragraph without Source or Package field".to_string());
+                return Err(crate::ControlError::Document(
+                    "paragraph without Source or Package field",
+                ));
             }
         }
 
         Ok(Control {
-            source: source.ok_or_else(|| "no source paragraph".to_string())?,
+            source: source.ok_or(crate::ControlError::Document("no source paragraph"))?,
             binaries,
         })
     }
@@ -44,11 +44,10 @@ pub struct Removal {
 }
 
 impl std::str::FromStr for Removal {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let paragraph =
-            deb822_lossless::lossless::Paragraph::from_str(s).map_err(|e| e.to_string())?;
-        Self::from_paragraph(&paragraph).map_err(|e| e.to_string())
+        let paragraph = deb822_lossless::lossless::Paragraph::from_str(s)?;
+        Self::from_paragraph(&paragraph).map_err(crate::ControlError::Paragraph)
     }
 }
@@ -0,0 +1,445 @@
+//! Indexes over apt `Packages` and `Sources` files.
+//!
+//! # Example
+//!
+//! ```rust
+//! use debian_control::lossy::apt::Package;
+//! use debian_control::lossy::index::PackagesIndex;
+//!
+//! let index: PackagesIndex<Package> = r#"Package: hello
+//! Version: 2.10-2
+//! Architecture: amd64
+//!
+//! Package: hello
+//! Version: 2.10-3
+//! Architecture: amd64
+//!
+//! Package: hello
+//! Version: 2.10-3
+//! Architecture: arm64
+//! "#.parse().unwrap();
+//!
+//! assert_eq!(index.get("hello").unwrap().version, "2.10-3".parse().unwrap());
+//! assert_eq!(index.get_versions("hello").count(), 3);
+//! assert_eq!(index.get_arch("hello", "arm64").unwrap().architecture, "arm64");
+//! assert!(index.get("world").is_none());
+//! ```
+use crate::lossy::apt::{Package, Source};
+use deb822_lossless::convert::Deb822LikeParagraph;
+use deb822_lossless::FromDeb822Paragraph;
+use std::collections::HashMap;
+
+/// An entry that can be stored in a [`PackagesIndex`].
+pub trait IndexEntry {
+    /// The name of the package.
+    fn name(&self) -> &str;
+
+    /// The version of the package.
+    fn version(&self) -> &debversion::Version;
+
+    /// The architecture of the package, if the entry is architecture-specific.
+    fn architecture(&self) -> Option<&str>;
+}
+
+impl IndexEntry for Package {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &debversion::Version {
+        &self.version
+    }
+
+    fn architecture(&self) -> Option<&str> {
+        Some(&self.architecture)
+    }
+}
+
+impl IndexEntry for Source {
+    fn name(&self) -> &str {
+        &self.package
+    }
+
+    fn version(&self) -> &debversion::Version {
+        &self.version
+    }
+
+    fn architecture(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// An index over the entries of a `Packages` or `Sources` file.
+///
+/// Entries are kept in their original order, with hash indexes on the package name and on the
+/// combination of package name and architecture.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackagesIndex<T> {
+    entries: Vec<T>,
+    by_name: HashMap<String, Vec<usize>>,
+    by_name_arch: HashMap<String, HashMap<String, Vec<usize>>>,
+}
+
+impl<T> Default for PackagesIndex<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            by_name: HashMap::new(),
+            by_name_arch: HashMap::new(),
+        }
+    }
+}
+
+impl<T: IndexEntry> PackagesIndex<T> {
+    /// Create a new, empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry to the index.
+    pub fn push(&mut self, entry: T) {
+        let idx = self.entries.len();
+        self.by_name
+            .entry(entry.name().to_string())
+            .or_default()
+            .push(idx);
+        if let Some(arch) = entry.architecture() {
+            self.by_name_arch
+                .entry(entry.name().to_string())
+                .or_default()
+                .entry(arch.to_string())
+                .or_default()
+                .push(idx);
+        }
+        self.entries.push(entry);
+    }
+
+    /// Number of entries in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all entries, in their original order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter()
+    }
+
+    /// Iterate over the names of all packages in the index, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.by_name.keys().map(|name| name.as_str())
+    }
+
+    /// Check if the index contains a package with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    /// Get the entry with the highest version for a package name.
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.get_versions(name)
+            .max_by(|a, b| a.version().cmp(b.version()))
+    }
+
+    /// Get all entries for a package name, in their original order.
+    pub fn get_versions(&self, name: &str) -> impl Iterator<Item = &T> {
+        self.by_name
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.entries[*idx])
+    }
+
+    /// Get the entry with the highest version for a package name and architecture.
+    pub fn get_arch(&self, name: &str, arch: &str) -> Option<&T> {
+        self.by_name_arch
+            .get(name)
+            .and_then(|archs| archs.get(arch))
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.entries[*idx])
+            .max_by(|a, b| a.version().cmp(b.version()))
+    }
+
+    /// Build an index from a sequence of paragraphs.
+    pub fn from_paragraphs<P: Deb822LikeParagraph>(
+        paragraphs: impl IntoIterator<Item = P>,
+    ) -> Result<Self, crate::ControlError>
+    where
+        T: FromDeb822Paragraph<P>,
+    {
+        let mut index = Self::new();
+        for para in paragraphs {
+            index.push(T::from_paragraph(&para).map_err(crate::ControlError::Paragraph)?);
+        }
+        Ok(index)
+    }
+}
+
+#[cfg(feature = "verify")]
+impl<T: crate::pool::Verify + Sync> PackagesIndex<T> {
+    /// Verify the files referenced by all entries, returning the entries that failed.
+    ///
+    /// Entries are verified in parallel, using one thread per available CPU.
+    pub fn verify(&self, pool_root: &std::path::Path) -> Vec<(&T, crate::pool::VerifyError)> {
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = self.entries.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            let handles = self
+                .entries
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .filter_map(|entry| entry.verify(pool_root).err().map(|e| (entry, e)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+impl<T: IndexEntry> FromIterator<T> for PackagesIndex<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut index = Self::new();
+        index.extend(iter);
+        index
+    }
+}
+
+impl<T: IndexEntry> Extend<T> for PackagesIndex<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for entry in iter {
+            self.push(entry);
+        }
+    }
+}
+
+impl<T> IntoIterator for PackagesIndex<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(feature = "cache")]
+impl<T: IndexEntry + serde::Serialize + serde::de::DeserializeOwned> PackagesIndex<T> {
+    /// Write the index to a cache file.
+    ///
+    /// `checksum` identifies the input the index was built from, see
+    /// [`deb822_lossless::cache::checksum`].
+    pub fn save_cache(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        checksum: &deb822_lossless::cache::Checksum,
+    ) -> std::io::Result<()> {
+        deb822_lossless::cache::save(path.as_ref(), checksum, self)
+    }
+
+    /// Read an index from a cache file.
+    ///
+    /// Returns `None` if the cache file does not exist, or was written for a different input,
+    /// a different kind of entry or by an incompatible version of this crate.
+    pub fn load_cache(
+        path: impl AsRef<std::path::Path>,
+        checksum: &deb822_lossless::cache::Checksum,
+    ) -> std::io::Result<Option<Self>> {
+        deb822_lossless::cache::load(path.as_ref(), checksum)
+    }
+}
+
+#[cfg(feature = "cache")]
+impl<T> PackagesIndex<T>
+where
+    T: IndexEntry
+        + FromDeb822Paragraph<deb822_lossless::lossy::Paragraph>
+        + serde::Serialize
+        + serde::de::DeserializeOwned,
+{
+    /// Read an index from a file, using an on-disk cache of the index.
+    ///
+    /// The file may be compressed, e.g. `Packages.xz`. The index is only built again if the
+    /// contents of the file change.
+    pub fn from_file_cached(
+        path: impl AsRef<std::path::Path>,
+        cache_path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, crate::ControlError> {
+        Self::from_file_cached_with(path, cache_path, &deb822_lossless::ParseOptions::default())
+    }
+
+    /// Like [`PackagesIndex::from_file_cached`], but parse the file with the given options.
+    ///
+    /// The options are part of the cache key, so a cache written with different options is
+    /// not used.
+    pub fn from_file_cached_with(
+        path: impl AsRef<std::path::Path>,
+        cache_path: impl AsRef<std::path::Path>,
+        options: &deb822_lossless::ParseOptions,
+    ) -> Result<Self, crate::ControlError> {
+        deb822_lossless::cache::load_or_parse(
+            path.as_ref(),
+            cache_path.as_ref(),
+            options,
+            |bytes, options| {
+                Self::from_paragraphs(deb822_lossless::lossy::Deb822::from_bytes_with(
+                    bytes, options,
+                )?)
+            },
+        )
+    }
+}
+
+impl<T: IndexEntry + FromDeb822Paragraph<deb822_lossless::lossy::Paragraph>> std::str::FromStr
+    for PackagesIndex<T>
+{
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let doc: deb822_lossless::lossy::Deb822 = s.parse()?;
+        Self::from_paragraphs(doc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGES: &str = r#"Package: hello
+Version: 2.10-2
+Architecture: amd64
+
+Package: world
+Version: 1.0
+Architecture: all
+
+Package: hello
+Version: 2.10-3
+Architecture: arm64
+
+Package: hello
+Version: 2.9-1
+Architecture: arm64
+"#;
+
+    #[test]
+    fn test_packages() {
+        let index: PackagesIndex<Package> = PACKAGES.parse().unwrap();
+        assert_eq!(index.len(), 4);
+        assert!(!index.is_empty());
+        assert!(index.contains("world"));
+        assert!(!index.contains("missing"));
+
+        assert_eq!(
+            index.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["hello", "world", "hello", "hello"]
+        );
+        assert_eq!(
+            index
+                .get_versions("hello")
+                .map(|p| p.version.to_string())
+                .collect::<Vec<_>>(),
+            vec!["2.10-2", "2.10-3", "2.9-1"]
+        );
+        assert_eq!(index.get("hello").unwrap().version.to_string(), "2.10-3");
+        assert_eq!(
+            index
+                .get_arch("hello", "amd64")
+                .unwrap()
+                .version
+                .to_string(),
+            "2.10-2"
+        );
+        assert!(index.get_arch("hello", "i386").is_none());
+        assert!(index.get("missing").is_none());
+        assert_eq!(index.get_versions("missing").count(), 0);
+
+        let mut names = index.names().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["hello", "world"]);
+    }
+
+    #[test]
+    fn test_sources() {
+        let index: PackagesIndex<Source> = r#"Package: hello
+Version: 2.10-3
+Directory: pool/main/h/hello
+Package-List:
+ hello deb devel optional arch=any
+
+Package: hello
+Version: 2.10-2
+Directory: pool/main/h/hello
+Package-List:
+ hello deb devel optional arch=any
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(index.get("hello").unwrap().version.to_string(), "2.10-3");
+        assert!(index.get_arch("hello", "amd64").is_none());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_from_file_cached() {
+        let dir = std::env::temp_dir().join(format!("packages-index-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Packages");
+        let cache_path = dir.join("Packages.cache");
+        std::fs::write(&path, PACKAGES).unwrap();
+
+        let index = PackagesIndex::<Package>::from_file_cached(&path, &cache_path).unwrap();
+        let checksum = deb822_lossless::cache::checksum(PACKAGES.as_bytes(), &Default::default());
+        let cached = PackagesIndex::<Package>::load_cache(&cache_path, &checksum)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            index.clone().into_iter().collect::<Vec<_>>(),
+            cached.into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            PackagesIndex::<Package>::from_file_cached(&path, &cache_path)
+                .unwrap()
+                .get("hello")
+                .unwrap()
+                .version
+                .to_string(),
+            "2.10-3"
+        );
+        // A cache for a different kind of entry, or different options, is not used.
+        assert!(PackagesIndex::<Source>::load_cache(&cache_path, &checksum)
+            .unwrap()
+            .is_none());
+        let strict = deb822_lossless::ParseOptions::strict();
+        assert!(PackagesIndex::<Package>::load_cache(
+            &cache_path,
+            &deb822_lossless::cache::checksum(PACKAGES.as_bytes(), &strict)
+        )
+        .unwrap()
+        .is_none());
+
+        std::fs::write(&path, "Package: hello\n").unwrap();
+        assert!(PackagesIndex::<Package>::from_file_cached(&path, &cache_path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("Package: hello\n"
+            .parse::<PackagesIndex<Package>>()
+            .is_err());
+    }
+}
@@ -5,5 +5,6 @@ pub mod buildinfo;
 mod control;
 pub use control::*;
 pub mod ftpmaster;
+pub mod index;
 mod relations;
 pub use relations::*;
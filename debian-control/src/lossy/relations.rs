@@ -21,6 +21,7 @@
 
 use std::iter::Peekable;
 
+use crate::error::{RelationsParseError, SyntaxError};
 use crate::relations::SyntaxKind::*;
 use crate::relations::{lex, BuildProfile, SyntaxKind, VersionConstraint};
 
@@ -132,8 +133,8 @@ impl RelationBuilder {
     }
 
     /// Set the version constraint and version.
-    pub fn version(mut self, constraint: VersionConstraint, version: &str) -> Self {
-        self.version = Some((constraint, version.parse().unwrap()));
+    pub fn version(mut self, constraint: VersionConstraint, version: debversion::Version) -> Self {
+        self.version = Some((constraint, version));
         self
     }
 
@@ -289,10 +290,18 @@ impl std::fmt::Display for Relations {
     }
 }
 
+fn syntax_error(message: &str, span: std::ops::Range<usize>) -> RelationsParseError {
+    RelationsParseError::Syntax(vec![SyntaxError {
+        message: message.to_string(),
+        span,
+    }])
+}
+
 impl std::str::FromStr for Relation {
-    type Err = String;
+    type Err = RelationsParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |message: &str| syntax_error(message, 0..s.len());
         let tokens = lex(s);
         let mut tokens = tokens.into_iter().peekable();
 
@@ -304,7 +313,7 @@ impl std::str::FromStr for Relation {
 
         let name = match tokens.next() {
             Some((IDENT, name)) => name,
-            _ => return Err("Expected package name".to_string()),
+            _ => return Err(error("Expected package name")),
         };
 
         eat_whitespace(&mut tokens);
@@ -313,7 +322,7 @@ impl std::str::FromStr for Relation {
             tokens.next();
             match tokens.next() {
                 Some((IDENT, s)) => Some(s),
-                _ => return Err("Expected architecture qualifier".to_string()),
+                _ => return Err(error("Expected architecture qualifier")),
             }
         } else {
             None
@@ -333,7 +342,9 @@ impl std::str::FromStr for Relation {
                     _ => break,
                 }
             }
-            let constraint = constraint.parse()?;
+            let constraint = constraint
+                .parse()
+                .map_err(|e: crate::ControlError| error(&e.to_string()))?;
             eat_whitespace(&mut tokens);
             // Read IDENT and COLON tokens until we see R_PARENS
             let mut version_string = String::new();
@@ -341,17 +352,17 @@ impl std::str::FromStr for Relation {
                 match kind {
                     R_PARENS => break,
                     IDENT | COLON => version_string.push_str(s),
-                    n => return Err(format!("Unexpected token: {:?}", n)),
+                    n => return Err(error(&format!("Unexpected token: {:?}", n))),
                 }
                 tokens.next();
             }
             let version = version_string
                 .parse()
-                .map_err(|e: debversion::ParseError| e.to_string())?;
+                .map_err(|e: debversion::ParseError| error(&e.to_string()))?;
             eat_whitespace(&mut tokens);
             if let Some((R_PARENS, _)) = tokens.next() {
             } else {
-                return Err(format!("Expected ')', found {:?}", tokens.next()));
+                return Err(error(&format!("Expected ')', found {:?}", tokens.next())));
             }
             Some((constraint, version))
         } else {
@@ -368,7 +379,7 @@ impl std::str::FromStr for Relation {
                     Some((IDENT, s)) => archs.push(s),
                     Some((WHITESPACE, _)) => {}
                     Some((R_BRACKET, _)) => break,
-                    _ => return Err("Expected architecture name".to_string()),
+                    _ => return Err(error("Expected architecture name")),
                 }
             }
             Some(archs)
@@ -388,13 +399,13 @@ impl std::str::FromStr for Relation {
                         Some((NOT, _)) => {
                             let profile_name = match tokens.next() {
                                 Some((IDENT, s)) => s,
-                                _ => return Err("Expected profile name".to_string()),
+                                _ => return Err(error("Expected profile name")),
                             };
                             profile.push(BuildProfile::Disabled(profile_name));
                         }
                         Some((IDENT, s)) => profile.push(BuildProfile::Enabled(s)),
                         Some((WHITESPACE, _)) => {}
-                        _ => return Err("Expected profile name".to_string()),
+                        _ => return Err(error("Expected profile name")),
                     }
                     if let Some((COMMA, _)) = tokens.peek() {
                         tokens.next();
@@ -413,7 +424,7 @@ impl std::str::FromStr for Relation {
         eat_whitespace(&mut tokens);
 
         if let Some((kind, _)) = tokens.next() {
-            return Err(format!("Unexpected token: {:?}", kind));
+            return Err(error(&format!("Unexpected token: {:?}", kind)));
         }
 
         Ok(Relation {
@@ -427,7 +438,7 @@ impl std::str::FromStr for Relation {
 }
 
 impl std::str::FromStr for Relations {
-    type Err = String;
+    type Err = RelationsParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut relations = Vec::new();
@@ -442,10 +453,15 @@ impl std::str::FromStr for Relations {
             }
             let entry_relations = entry.split('|').map(|relation| {
                 let relation = relation.trim();
+                // Report errors relative to the whole field rather than to the relation.
+                let start = relation.as_ptr() as usize - s.as_ptr() as usize;
+                let span = start..start + relation.len();
                 if relation.is_empty() {
-                    return Err("Empty relation".to_string());
+                    return Err(syntax_error("Empty relation", span));
                 }
-                relation.parse()
+                relation
+                    .parse()
+                    .map_err(|e: RelationsParseError| syntax_error(&e.to_string(), span))
             });
             relations.push(entry_relations.collect::<Result<Vec<_>, _>>()?);
         }
@@ -698,7 +714,10 @@ mod tests {
     #[test]
     fn test_relations_from_iter() {
         let relation1 = Relation::build("python3-dulwich")
-            .version(VersionConstraint::GreaterThanEqual, "0.19.0")
+            .version(
+                VersionConstraint::GreaterThanEqual,
+                "0.19.0".parse().unwrap(),
+            )
             .build();
         let relation2 = Relation::build("python3-requests").build();
 
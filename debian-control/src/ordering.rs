@@ -0,0 +1,315 @@
+//! Conventional order of the fields in the paragraphs of Debian files.
+//!
+//! A [`Profile`] lists the fields of one kind of paragraph in the order that tools such as
+//! `dpkg-source` write them. [`Reorder::reorder`] puts the fields of a paragraph in that order;
+//! fields that are not part of the profile are kept together, in their original order.
+//!
+//! # Example
+//! ```rust
+//! use debian_control::ordering::{Profile, Reorder};
+//!
+//! let mut paragraph: deb822_lossless::lossy::Paragraph =
+//!     "Maintainer: Jane <jane@example.com>\nSource: foo\n".parse().unwrap();
+//! paragraph.reorder(Profile::ControlSource);
+//! assert_eq!(paragraph.to_string(), "Source: foo\nMaintainer: Jane <jane@example.com>\n");
+//! ```
+
+/// The kind of paragraph, which determines the conventional order of its fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// The source package paragraph of `debian/control`.
+    ControlSource,
+
+    /// A binary package paragraph of `debian/control`.
+    ControlBinary,
+
+    /// A source package description (`.dsc`) file.
+    Dsc,
+
+    /// A `.changes` file.
+    Changes,
+
+    /// The header paragraph of a machine-readable (DEP-5) `debian/copyright` file.
+    CopyrightHeader,
+
+    /// A `Files` paragraph of a machine-readable `debian/copyright` file.
+    CopyrightFiles,
+
+    /// A stand-alone `License` paragraph of a machine-readable `debian/copyright` file.
+    CopyrightLicense,
+}
+
+/// Placeholder for the fields that are not part of a profile.
+const OTHER: &str = "*";
+
+const CONTROL_SOURCE: &[&str] = &[
+    "Source",
+    "Section",
+    "Priority",
+    "Maintainer",
+    "Uploaders",
+    "Build-Depends",
+    "Build-Depends-Arch",
+    "Build-Depends-Indep",
+    "Build-Conflicts",
+    "Build-Conflicts-Arch",
+    "Build-Conflicts-Indep",
+    "Standards-Version",
+    "Homepage",
+    "Vcs-Browser",
+    "Vcs-Arch",
+    "Vcs-Bzr",
+    "Vcs-Cvs",
+    "Vcs-Darcs",
+    "Vcs-Git",
+    "Vcs-Hg",
+    "Vcs-Mtn",
+    "Vcs-Svn",
+    "Testsuite",
+    "Testsuite-Triggers",
+    "Rules-Requires-Root",
+    OTHER,
+];
+
+const CONTROL_BINARY: &[&str] = &[
+    "Package",
+    "Architecture",
+    "Multi-Arch",
+    "Section",
+    "Priority",
+    "Essential",
+    "Protected",
+    "Package-Type",
+    "Build-Profiles",
+    "Pre-Depends",
+    "Depends",
+    "Recommends",
+    "Suggests",
+    "Enhances",
+    "Breaks",
+    "Conflicts",
+    "Provides",
+    "Replaces",
+    "Built-Using",
+    "Static-Built-Using",
+    OTHER,
+    "Description",
+];
+
+const DSC: &[&str] = &[
+    "Format",
+    "Source",
+    "Binary",
+    "Architecture",
+    "Version",
+    "Maintainer",
+    "Uploaders",
+    "Homepage",
+    "Standards-Version",
+    "Vcs-Browser",
+    "Vcs-Arch",
+    "Vcs-Bzr",
+    "Vcs-Cvs",
+    "Vcs-Darcs",
+    "Vcs-Git",
+    "Vcs-Hg",
+    "Vcs-Mtn",
+    "Vcs-Svn",
+    "Testsuite",
+    "Testsuite-Triggers",
+    "Build-Depends",
+    "Build-Depends-Arch",
+    "Build-Depends-Indep",
+    "Build-Conflicts",
+    "Build-Conflicts-Arch",
+    "Build-Conflicts-Indep",
+    OTHER,
+    "Package-List",
+    "Checksums-Sha1",
+    "Checksums-Sha256",
+    "Files",
+];
+
+const CHANGES: &[&str] = &[
+    "Format",
+    "Date",
+    "Source",
+    "Binary",
+    "Built-For-Profiles",
+    "Architecture",
+    "Version",
+    "Distribution",
+    "Urgency",
+    "Maintainer",
+    "Changed-By",
+    "Description",
+    "Closes",
+    "Changes",
+    OTHER,
+    "Checksums-Sha1",
+    "Checksums-Sha256",
+    "Files",
+];
+
+const COPYRIGHT_HEADER: &[&str] = &[
+    "Format",
+    "Upstream-Name",
+    "Upstream-Contact",
+    "Source",
+    "Files-Excluded",
+    "Disclaimer",
+    "Comment",
+    "License",
+    "Copyright",
+    OTHER,
+];
+
+const COPYRIGHT_FILES: &[&str] = &["Files", "Copyright", "License", "Comment", OTHER];
+
+const COPYRIGHT_LICENSE: &[&str] = &["License", "Comment", OTHER];
+
+impl Profile {
+    /// All profiles.
+    pub const ALL: &'static [Profile] = &[
+        Profile::ControlSource,
+        Profile::ControlBinary,
+        Profile::Dsc,
+        Profile::Changes,
+        Profile::CopyrightHeader,
+        Profile::CopyrightFiles,
+        Profile::CopyrightLicense,
+    ];
+
+    /// The fields of this profile, in order.
+    ///
+    /// Fields that are not part of the profile go in the place of `*`.
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Profile::ControlSource => CONTROL_SOURCE,
+            Profile::ControlBinary => CONTROL_BINARY,
+            Profile::Dsc => DSC,
+            Profile::Changes => CHANGES,
+            Profile::CopyrightHeader => COPYRIGHT_HEADER,
+            Profile::CopyrightFiles => COPYRIGHT_FILES,
+            Profile::CopyrightLicense => COPYRIGHT_LICENSE,
+        }
+    }
+
+    /// The position of a field in this profile, for use as a sort key.
+    ///
+    /// Field names are compared case-insensitively.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_control::ordering::Profile;
+    ///
+    /// let profile = Profile::ControlBinary;
+    /// assert!(profile.position("Package") < profile.position("depends"));
+    /// assert!(profile.position("X-Custom") < profile.position("Description"));
+    /// ```
+    pub fn position(&self, field: &str) -> usize {
+        let fields = self.fields();
+        fields
+            .iter()
+            .position(|known| known.eq_ignore_ascii_case(field))
+            .or_else(|| fields.iter().position(|known| *known == OTHER))
+            .unwrap_or(fields.len())
+    }
+}
+
+/// A paragraph whose fields can be put in the order of a [`Profile`].
+pub trait Reorder {
+    /// Put the fields in the order of `profile`.
+    ///
+    /// The sort is stable, so fields that are not part of the profile and repeated fields keep
+    /// their relative order. Comments move along with the field they precede.
+    fn reorder(&mut self, profile: Profile);
+
+    /// Check whether the fields are in the order of `profile`.
+    fn is_ordered(&self, profile: Profile) -> bool;
+}
+
+impl Reorder for deb822_lossless::lossy::Paragraph {
+    fn reorder(&mut self, profile: Profile) {
+        self.fields
+            .sort_by_key(|field| profile.position(&field.name));
+    }
+
+    fn is_ordered(&self, profile: Profile) -> bool {
+        self.fields
+            .windows(2)
+            .all(|w| profile.position(&w[0].name) <= profile.position(&w[1].name))
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl Reorder for deb822_lossless::lossless::Paragraph {
+    fn reorder(&mut self, profile: Profile) {
+        let position = |entry: &deb822_lossless::lossless::Entry| {
+            entry.key().map_or(usize::MAX, |key| profile.position(&key))
+        };
+        self.sort_fields_by(|a, b| position(a).cmp(&position(b)));
+    }
+
+    fn is_ordered(&self, profile: Profile) -> bool {
+        let positions = self
+            .keys()
+            .map(|key| profile.position(&key))
+            .collect::<Vec<_>>();
+        positions.windows(2).all(|w| w[0] <= w[1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        for profile in Profile::ALL {
+            let fields = profile.fields();
+            assert_eq!(
+                fields.iter().filter(|f| **f == OTHER).count(),
+                1,
+                "{:?}",
+                profile
+            );
+            for (i, field) in fields.iter().enumerate() {
+                if *field != OTHER {
+                    assert_eq!(profile.position(field), i, "{:?} {}", profile, field);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reorder_lossy() {
+        let mut paragraph: deb822_lossless::lossy::Paragraph =
+            "Files:\n abc 1 foo.dsc\nFormat: 1.8\nX-Extra: 1\nSource: foo\nX-Other: 2\n"
+                .parse()
+                .unwrap();
+        assert!(!paragraph.is_ordered(Profile::Changes));
+        paragraph.reorder(Profile::Changes);
+        assert!(paragraph.is_ordered(Profile::Changes));
+        assert_eq!(
+            paragraph.to_string(),
+            "Format: 1.8\nSource: foo\nX-Extra: 1\nX-Other: 2\nFiles:\n abc 1 foo.dsc\n"
+        );
+    }
+
+    #[cfg(feature = "lossless")]
+    #[test]
+    fn test_reorder_lossless() {
+        let mut paragraph: deb822_lossless::lossless::Paragraph =
+            "Comment: Upstream tarball\nLicense: GPL-2+\n# Authors\nCopyright:  2024 Jane\nFiles: *\n"
+                .parse()
+                .unwrap();
+        assert!(!paragraph.is_ordered(Profile::CopyrightFiles));
+        paragraph.reorder(Profile::CopyrightFiles);
+        assert!(paragraph.is_ordered(Profile::CopyrightFiles));
+        assert_eq!(
+            paragraph.to_string(),
+            "Files: *\n# Authors\nCopyright:  2024 Jane\nLicense: GPL-2+\nComment: Upstream tarball\n"
+        );
+    }
+}
@@ -29,6 +29,18 @@ impl std::fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+impl From<deb822_lossless::signed::Error> for Error {
+    fn from(e: deb822_lossless::signed::Error) -> Self {
+        use deb822_lossless::signed::Error as SignedError;
+        match e {
+            SignedError::InvalidHeader(_) | SignedError::MissingPayload => Error::MissingPayload,
+            SignedError::MissingSignature => Error::MissingPgpSignature,
+            SignedError::TruncatedSignature => Error::TruncatedPgpSignature,
+            SignedError::TrailingData(_) => Error::JunkAfterPgpSignature,
+        }
+    }
+}
+
 /// Strip a PGP signature from a signed message.
 ///
 /// This function takes a signed message and returns the payload and the PGP signature.
@@ -44,10 +56,12 @@ impl std::error::Error for Error {}
 /// The error indicates the reason for the failure.
 ///
 /// # Returns
-/// A tuple containing the payload and the PGP signature, if present.
+/// A tuple containing the payload and the body of the PGP signature, without the armor lines,
+/// if present.
 ///
 /// # Examples
 /// ```
+/// # #![allow(deprecated)]
 /// let input = "-----BEGIN PGP SIGNED MESSAGE-----
 /// Hash: SHA256
 ///
@@ -63,67 +77,22 @@ impl std::error::Error for Error {}
 /// assert_eq!(output, "Hello, world!\n");
 /// assert_eq!(signature.unwrap().len(), 136);
 /// ```
+#[deprecated(note = "use deb822_lossless::signed::ClearSigned instead")]
 pub fn strip_pgp_signature(input: &str) -> Result<(String, Option<String>), Error> {
-    let mut lines = input.lines();
-    let first_line = if let Some(line) = lines.next() {
-        line
-    } else {
-        return Ok((input.to_string(), None));
-    };
-    if first_line != "-----BEGIN PGP SIGNED MESSAGE-----" {
+    if !deb822_lossless::signed::is_signed(input) {
         return Ok((input.to_string(), None));
     }
-
-    // Read the metadata
-    let mut metadata = String::new();
-    loop {
-        let line = if let Some(line) = lines.next() {
-            line
-        } else {
-            return Err(Error::MissingPayload);
-        };
-        if line.is_empty() {
-            break;
-        }
-        metadata.push_str(line);
-        metadata.push('\n');
-    }
-
-    let mut payload = String::new();
-    loop {
-        let line = if let Some(line) = lines.next() {
-            line
-        } else {
-            return Err(Error::MissingPgpSignature);
-        };
-        if line == "-----BEGIN PGP SIGNATURE-----" {
-            break;
-        }
-        payload.push_str(line);
-        payload.push('\n');
-    }
-
-    let mut signature = String::new();
-    loop {
-        let line = if let Some(line) = lines.next() {
-            line
-        } else {
-            return Err(Error::TruncatedPgpSignature);
-        };
-        if line == "-----END PGP SIGNATURE-----" {
-            break;
-        }
-        signature.push_str(line);
-    }
-
-    if let Some(_line) = lines.next() {
-        return Err(Error::JunkAfterPgpSignature);
-    }
-
-    Ok((payload, Some(signature)))
+    let signed: deb822_lossless::signed::ClearSigned = input.parse()?;
+    let signature = signed
+        .signature
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    Ok((signed.payload, Some(signature)))
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     #[test]
     fn test_strip_pgp_wrapper() {
@@ -0,0 +1,342 @@
+//! Verification of the files referenced by `Packages` and `Sources` entries.
+//!
+//! The `Filename` field of a `Packages` entry and the `Directory` field of a `Sources` entry are
+//! relative to the root of the archive, which contains the `pool` directory. [`Verify`] looks up
+//! the referenced files under that root and checks their size and SHA-256 checksum.
+//!
+//! [`crate::lossy::index::PackagesIndex::verify`] verifies all entries of an index in parallel.
+//!
+//! # Example
+//! ```rust,no_run
+//! use debian_control::lossy::apt::Package;
+//! use debian_control::lossy::index::PackagesIndex;
+//! use debian_control::pool::Verify;
+//! use std::path::Path;
+//!
+//! let text = std::fs::read_to_string("/srv/mirror/dists/sid/main/binary-amd64/Packages").unwrap();
+//! let index: PackagesIndex<Package> = text.parse().unwrap();
+//! for (package, error) in index.verify(Path::new("/srv/mirror")) {
+//!     eprintln!("{} {}: {}", package.name, package.version, error);
+//! }
+//! ```
+use sha2::Digest;
+use std::path::{Component, Path, PathBuf};
+
+/// Error verifying the files referenced by an index entry.
+#[derive(Debug, thiserror::Error)]
+pub enum VerifyError {
+    /// The entry lacks a field that is needed to verify it.
+    #[error("missing field: {0}")]
+    MissingField(&'static str),
+
+    /// A field needed to verify the entry has an invalid value.
+    #[error(transparent)]
+    Field(#[from] crate::FieldError),
+
+    /// A referenced file is not inside the archive, because its path is absolute or contains
+    /// `..`.
+    #[error("path outside the archive: {}", .0.display())]
+    UnsafePath(PathBuf),
+
+    /// The file could not be read.
+    #[error("unable to read {}", .path.display())]
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+
+        /// The underlying I/O error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The file does not have the expected size.
+    #[error("{}: expected size {expected}, found {actual}", .path.display())]
+    SizeMismatch {
+        /// Path of the file.
+        path: PathBuf,
+
+        /// Size listed in the index.
+        expected: u64,
+
+        /// Actual size of the file.
+        actual: u64,
+    },
+
+    /// The file does not have the expected checksum.
+    #[error("{}: expected SHA256 {expected}, found {actual}", .path.display())]
+    ChecksumMismatch {
+        /// Path of the file.
+        path: PathBuf,
+
+        /// Checksum listed in the index.
+        expected: String,
+
+        /// Actual checksum of the file.
+        actual: String,
+    },
+}
+
+/// An index entry that references files in the archive.
+pub trait Verify {
+    /// Check that the files referenced by this entry exist under `pool_root`, with the
+    /// expected size and SHA-256 checksum.
+    fn verify(&self, pool_root: &Path) -> Result<(), VerifyError>;
+}
+
+/// Check the size and SHA-256 checksum of a file.
+///
+/// The size is compared first, so that truncated files are detected without reading them.
+pub fn verify_file(path: &Path, size: Option<u64>, sha256: &str) -> Result<(), VerifyError> {
+    let io_error = |source| VerifyError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(expected) = size {
+        let actual = std::fs::metadata(path).map_err(io_error)?.len();
+        if actual != expected {
+            return Err(VerifyError::SizeMismatch {
+                path: path.to_path_buf(),
+                expected,
+                actual,
+            });
+        }
+    }
+    let mut file = std::fs::File::open(path).map_err(io_error)?;
+    let mut hasher = sha2::Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(io_error)?;
+    let actual = format!("{:x}", hasher.finalize());
+    if !actual.eq_ignore_ascii_case(sha256) {
+        return Err(VerifyError::ChecksumMismatch {
+            path: path.to_path_buf(),
+            expected: sha256.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Resolve a path from an index entry against the root of the archive.
+///
+/// Only relative paths that stay below the root are accepted.
+fn archive_path(pool_root: &Path, path: &Path) -> Result<PathBuf, VerifyError> {
+    if !path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(VerifyError::UnsafePath(path.to_path_buf()));
+    }
+    Ok(pool_root.join(path))
+}
+
+fn verify_package(
+    pool_root: &Path,
+    filename: Option<String>,
+    size: Option<usize>,
+    sha256: Option<String>,
+) -> Result<(), VerifyError> {
+    let filename = filename.ok_or(VerifyError::MissingField("Filename"))?;
+    let sha256 = sha256.ok_or(VerifyError::MissingField("SHA256"))?;
+    verify_file(
+        &archive_path(pool_root, Path::new(&filename))?,
+        size.map(|s| s as u64),
+        &sha256,
+    )
+}
+
+fn verify_source(
+    pool_root: &Path,
+    directory: &str,
+    checksums: &[crate::fields::Sha256Checksum],
+) -> Result<(), VerifyError> {
+    if checksums.is_empty() {
+        return Err(VerifyError::MissingField("Checksums-Sha256"));
+    }
+    let directory = Path::new(directory);
+    for checksum in checksums {
+        verify_file(
+            &archive_path(pool_root, &directory.join(&checksum.filename))?,
+            Some(checksum.size as u64),
+            &checksum.sha256,
+        )?;
+    }
+    Ok(())
+}
+
+impl Verify for crate::lossy::apt::Package {
+    fn verify(&self, pool_root: &Path) -> Result<(), VerifyError> {
+        verify_package(
+            pool_root,
+            self.filename.clone(),
+            self.size,
+            self.sha256.clone(),
+        )
+    }
+}
+
+impl Verify for crate::lossy::apt::Source {
+    fn verify(&self, pool_root: &Path) -> Result<(), VerifyError> {
+        verify_source(
+            pool_root,
+            &self.directory,
+            self.checksums_sha256.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl Verify for crate::lossless::apt::Package {
+    fn verify(&self, pool_root: &Path) -> Result<(), VerifyError> {
+        verify_package(
+            pool_root,
+            self.filename(),
+            self.size().transpose()?,
+            self.sha256(),
+        )
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl Verify for crate::lossless::apt::Source {
+    fn verify(&self, pool_root: &Path) -> Result<(), VerifyError> {
+        let directory = self
+            .directory()
+            .ok_or(VerifyError::MissingField("Directory"))?;
+        verify_source(pool_root, &directory, &self.checksums_sha256()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lossy::apt::{Package, Source};
+
+    // SHA-256 of "hello\n"
+    const HELLO_SHA256: &str = "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+
+    fn pool(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "debian-control-pool-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let dir = root.join("pool/main/h/hello");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("hello_1.0_amd64.deb"), "hello\n").unwrap();
+        std::fs::write(dir.join("hello_1.0.dsc"), "hello\n").unwrap();
+        root
+    }
+
+    fn package(size: usize, sha256: &str) -> Package {
+        format!(
+            "Package: hello\nVersion: 1.0\nArchitecture: amd64\nFilename: pool/main/h/hello/hello_1.0_amd64.deb\nSize: {}\nSHA256: {}\n",
+            size, sha256
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_verify_package() {
+        let root = pool("package");
+        assert!(package(6, HELLO_SHA256).verify(&root).is_ok());
+        assert!(matches!(
+            package(7, HELLO_SHA256).verify(&root),
+            Err(VerifyError::SizeMismatch {
+                expected: 7,
+                actual: 6,
+                ..
+            })
+        ));
+        let e = package(6, "00").verify(&root).unwrap_err();
+        assert!(matches!(e, VerifyError::ChecksumMismatch { .. }));
+        assert!(e.to_string().ends_with("expected SHA256 00, found 5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03"));
+
+        let mut missing = package(6, HELLO_SHA256);
+        missing.filename = Some("pool/main/h/hello/missing.deb".to_string());
+        assert!(matches!(missing.verify(&root), Err(VerifyError::Io { .. })));
+        missing.filename = None;
+        assert!(matches!(
+            missing.verify(&root),
+            Err(VerifyError::MissingField("Filename"))
+        ));
+
+        #[cfg(feature = "lossless")]
+        {
+            let lossless: crate::lossless::apt::Package =
+                package(6, HELLO_SHA256).to_string().parse().unwrap();
+            assert!(lossless.verify(&root).is_ok());
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_verify_unsafe_path() {
+        let root = pool("unsafe");
+        let mut package = package(6, HELLO_SHA256);
+        for filename in ["../etc/passwd", "/etc/passwd", "pool/../../etc/passwd"] {
+            package.filename = Some(filename.to_string());
+            assert!(
+                matches!(package.verify(&root), Err(VerifyError::UnsafePath(ref path)) if path == Path::new(filename)),
+                "{}",
+                filename
+            );
+        }
+
+        let text = format!(
+            "Package: hello\nVersion: 1.0\nDirectory: pool/main/h/hello\nPackage-List: hello deb misc optional\nChecksums-Sha256:\n {} 6 hello_1.0.dsc\n",
+            HELLO_SHA256
+        );
+        for (from, to) in [
+            ("pool/main/h/hello", "../etc"),
+            ("pool/main/h/hello", "/etc"),
+            ("hello_1.0.dsc", "../../../../../etc/passwd"),
+            ("hello_1.0.dsc", "/etc/passwd"),
+        ] {
+            let source: Source = text.replace(from, to).parse().unwrap();
+            assert!(matches!(
+                source.verify(&root),
+                Err(VerifyError::UnsafePath(_))
+            ));
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_verify_source() {
+        let root = pool("source");
+        let text = format!(
+            "Package: hello\nVersion: 1.0\nDirectory: pool/main/h/hello\nPackage-List: hello deb misc optional\nChecksums-Sha256:\n {} 6 hello_1.0.dsc\n",
+            HELLO_SHA256
+        );
+        let source: Source = text.parse().unwrap();
+        assert!(source.verify(&root).is_ok());
+        #[cfg(feature = "lossless")]
+        {
+            let lossless: crate::lossless::apt::Source = text.parse().unwrap();
+            assert!(lossless.verify(&root).is_ok());
+        }
+
+        let source: Source = text.replace(" 6 ", " 5 ").parse().unwrap();
+        assert!(matches!(
+            source.verify(&root),
+            Err(VerifyError::SizeMismatch { .. })
+        ));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_verify_index() {
+        let root = pool("index");
+        let index: crate::lossy::index::PackagesIndex<Package> = vec![
+            package(6, HELLO_SHA256),
+            package(6, "00"),
+            package(6, HELLO_SHA256),
+        ]
+        .into_iter()
+        .collect();
+        let errors = index.verify(&root);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0.sha256.as_deref(), Some("00"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! [`proptest`] strategies for relations.
+//!
+//! The generated relations follow the same rules as those generated in [`crate::arbitrary`],
+//! so formatting and parsing them again returns equal relations.
+//!
+//! # Example
+//!
+//! ```rust
+//! use debian_control::lossy::Relations;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn roundtrip(relations in any::<Relations>()) {
+//!         prop_assert_eq!(relations.to_string().parse::<Relations>().unwrap(), relations);
+//!     }
+//! }
+//! # roundtrip();
+//! ```
+use crate::relations::{BuildProfile, VersionConstraint};
+use ::proptest::prelude::*;
+
+/// A strategy for package names.
+pub fn package_name() -> impl Strategy<Value = String> {
+    "[a-z0-9][a-z0-9+.-]{1,15}"
+}
+
+/// A strategy for Debian versions, with an optional epoch and revision.
+pub fn version() -> impl Strategy<Value = debversion::Version> {
+    "([1-9]:)?[0-9]{1,3}(\\.[0-9]{1,2}){0,2}(~rc[1-9])?(-[1-9][0-9]?)?"
+        .prop_map(|version| version.parse().unwrap())
+}
+
+fn version_constraint() -> impl Strategy<Value = VersionConstraint> {
+    prop_oneof![
+        Just(VersionConstraint::LessThan),
+        Just(VersionConstraint::LessThanEqual),
+        Just(VersionConstraint::Equal),
+        Just(VersionConstraint::GreaterThan),
+        Just(VersionConstraint::GreaterThanEqual),
+    ]
+}
+
+fn architecture() -> impl Strategy<Value = String> {
+    prop::sample::select(&["amd64", "arm64", "armhf", "i386", "riscv64", "s390x"][..])
+        .prop_map(String::from)
+}
+
+fn build_profile() -> impl Strategy<Value = BuildProfile> {
+    (
+        any::<bool>(),
+        prop::sample::select(
+            &[
+                "nocheck",
+                "nodoc",
+                "cross",
+                "stage1",
+                "stage2",
+                "pkg.foo.bar",
+            ][..],
+        ),
+    )
+        .prop_map(|(disabled, name)| {
+            if disabled {
+                BuildProfile::Disabled(name.to_string())
+            } else {
+                BuildProfile::Enabled(name.to_string())
+            }
+        })
+}
+
+impl Arbitrary for crate::lossy::Relation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            package_name(),
+            prop::option::weighted(
+                0.25,
+                prop::sample::select(&["any", "native", "amd64", "i386"][..]),
+            ),
+            prop::option::of((version_constraint(), version())),
+            prop::option::weighted(0.25, prop::collection::vec(architecture(), 1..=3)),
+            prop::collection::vec(build_profile(), 0..=2),
+        )
+            .prop_map(
+                |(name, archqual, version, architectures, profiles)| crate::lossy::Relation {
+                    name,
+                    archqual: archqual.map(String::from),
+                    architectures,
+                    version,
+                    profiles: profiles.into_iter().map(|profile| vec![profile]).collect(),
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for crate::lossy::Relations {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop::collection::vec(
+            prop::collection::vec(any::<crate::lossy::Relation>(), 1..=3),
+            0..=5,
+        )
+        .prop_map(crate::lossy::Relations)
+        .boxed()
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl Arbitrary for crate::lossless::relations::Relations {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<crate::lossy::Relations>()
+            .prop_map(|relations| relations.to_string().parse().unwrap())
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_lossy_roundtrip(relations in any::<crate::lossy::Relations>()) {
+            prop_assert_eq!(
+                relations.to_string().parse::<crate::lossy::Relations>().unwrap(),
+                relations
+            );
+        }
+
+        #[cfg(feature = "lossless")]
+        #[test]
+        fn test_lossless_roundtrip(relations in any::<crate::lossless::relations::Relations>()) {
+            let text = relations.to_string();
+            prop_assert_eq!(
+                text.parse::<crate::lossless::relations::Relations>().unwrap(),
+                relations
+            );
+        }
+    }
+}
@@ -0,0 +1,446 @@
+//! Python bindings for the lossless control file parser.
+//!
+//! The classes in this module wrap the types from [`crate::lossless`], so editing a control file
+//! from Python preserves comments, field order and formatting just like it does from Rust. The
+//! paragraphs returned by [`Control.source`](PyControl::source) and
+//! [`Control.binaries`](PyControl::binaries) share their syntax tree with the control file, so
+//! changes made through them are visible when the control file is serialized again.
+//!
+//! Use [`debian_control`] to register the classes in a Python extension module.
+use crate::fields::{Architectures, Section};
+use crate::lossless::control::{Binary, Control, Source};
+use crate::lossless::relations::Relations;
+use crate::Identity;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+
+/// A Debian package control file.
+#[pyclass(name = "Control", unsendable)]
+pub struct PyControl(Control);
+
+#[pymethods]
+impl PyControl {
+    #[new]
+    #[pyo3(signature = (text=None))]
+    fn new(text: Option<&str>) -> PyResult<Self> {
+        match text {
+            Some(text) => text
+                .parse()
+                .map(PyControl)
+                .map_err(|e: deb822_lossless::ParseError| PyValueError::new_err(e.to_string())),
+            None => Ok(PyControl(Control::new())),
+        }
+    }
+
+    /// The source package paragraph, if any.
+    #[getter]
+    fn source(&self) -> Option<PySource> {
+        self.0.source().map(PySource)
+    }
+
+    /// The binary package paragraphs.
+    #[getter]
+    fn binaries(&self) -> Vec<PyBinary> {
+        self.0.binaries().map(PyBinary).collect()
+    }
+
+    /// Add a new source package paragraph.
+    fn add_source(&mut self, name: &str) -> PySource {
+        PySource(self.0.add_source(name))
+    }
+
+    /// Add a new binary package paragraph.
+    fn add_binary(&mut self, name: &str) -> PyBinary {
+        PyBinary(self.0.add_binary(name))
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Parse a relations field value, raising `ValueError` if it is invalid.
+///
+/// Unlike the `FromStr` implementation, this allows substvars, since those are common in
+/// control files.
+fn parse_relations(text: &str) -> PyResult<Relations> {
+    let (relations, errors) = Relations::parse_relaxed(text, true);
+    if errors.is_empty() {
+        Ok(relations)
+    } else {
+        Err(PyValueError::new_err(errors.join("\n")))
+    }
+}
+
+/// Get the value of a relations field from a paragraph.
+fn get_relations(
+    paragraph: &deb822_lossless::Paragraph,
+    key: &str,
+) -> PyResult<Option<PyRelations>> {
+    paragraph
+        .get(key)
+        .map(|value| parse_relations(&value).map(PyRelations))
+        .transpose()
+}
+
+/// Define the methods of a paragraph wrapper, including the mapping protocol.
+macro_rules! paragraph_methods {
+    ($ty:ident { $($item:item)* }) => {
+        #[pymethods]
+        impl $ty {
+            $($item)*
+
+            fn __getitem__(&self, key: &str) -> PyResult<String> {
+                self.0
+                    .as_deb822()
+                    .get(key)
+                    .ok_or_else(|| PyKeyError::new_err(key.to_string()))
+            }
+
+            fn __setitem__(&mut self, key: &str, value: &str) {
+                self.0.as_mut_deb822().set(key, value);
+            }
+
+            fn __delitem__(&mut self, key: &str) -> PyResult<()> {
+                if !self.0.as_deb822().contains_key(key) {
+                    return Err(PyKeyError::new_err(key.to_string()));
+                }
+                self.0.as_mut_deb822().remove(key);
+                Ok(())
+            }
+
+            fn __contains__(&self, key: &str) -> bool {
+                self.0.as_deb822().contains_key(key)
+            }
+
+            fn __len__(&self) -> usize {
+                self.0.as_deb822().keys().count()
+            }
+
+            /// The names of the fields in the paragraph, in order.
+            fn keys(&self) -> Vec<String> {
+                self.0.as_deb822().keys().collect()
+            }
+
+            /// Get the value of a field, or a default if it is not present.
+            #[pyo3(signature = (key, default=None))]
+            fn get(&self, key: &str, default: Option<String>) -> Option<String> {
+                self.0.as_deb822().get(key).or(default)
+            }
+
+            fn __str__(&self) -> String {
+                self.0.as_deb822().to_string()
+            }
+        }
+    };
+}
+
+/// The source package paragraph of a control file.
+#[pyclass(name = "Source", unsendable)]
+pub struct PySource(Source);
+
+paragraph_methods!(PySource {
+    /// The name of the source package.
+    #[getter]
+    fn get_name(&self) -> Option<String> {
+        self.0.name()
+    }
+
+    #[setter]
+    fn set_name(&mut self, name: &str) {
+        self.0.set_name(name);
+    }
+
+    /// The section of the source package.
+    #[getter]
+    fn get_section(&self) -> Option<String> {
+        self.0.raw_section()
+    }
+
+    #[setter]
+    fn set_section(&mut self, section: Option<&str>) -> PyResult<()> {
+        let section = section
+            .map(|section| section.parse::<Section>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_section(section.as_ref());
+        Ok(())
+    }
+
+    /// The maintainer of the source package.
+    #[getter]
+    fn get_maintainer(&self) -> Option<String> {
+        self.0.raw_maintainer()
+    }
+
+    #[setter]
+    fn set_maintainer(&mut self, maintainer: &str) -> PyResult<()> {
+        let maintainer = maintainer
+            .parse::<Identity>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_maintainer(&maintainer);
+        Ok(())
+    }
+
+    /// The version of Debian policy the package complies with.
+    #[getter]
+    fn get_standards_version(&self) -> Option<String> {
+        self.0.standards_version()
+    }
+
+    #[setter]
+    fn set_standards_version(&mut self, version: &str) {
+        self.0.set_standards_version(version);
+    }
+
+    /// The build dependencies of the source package.
+    #[getter]
+    fn get_build_depends(&self) -> PyResult<Option<PyRelations>> {
+        get_relations(self.0.as_deb822(), "Build-Depends")
+    }
+
+    #[setter]
+    fn set_build_depends(&mut self, relations: Option<PyRef<PyRelations>>) {
+        match relations {
+            Some(relations) => self.0.set_build_depends(&relations.0),
+            None => self.0.as_mut_deb822().remove("Build-Depends"),
+        }
+    }
+});
+
+/// A binary package paragraph of a control file.
+#[pyclass(name = "Binary", unsendable)]
+pub struct PyBinary(Binary);
+
+paragraph_methods!(PyBinary {
+    /// The name of the binary package.
+    #[getter]
+    fn get_name(&self) -> Option<String> {
+        self.0.name()
+    }
+
+    #[setter]
+    fn set_name(&mut self, name: &str) {
+        self.0.set_name(name);
+    }
+
+    /// The section of the binary package.
+    #[getter]
+    fn get_section(&self) -> Option<String> {
+        self.0.raw_section()
+    }
+
+    #[setter]
+    fn set_section(&mut self, section: Option<&str>) -> PyResult<()> {
+        let section = section
+            .map(|section| section.parse::<Section>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_section(section.as_ref());
+        Ok(())
+    }
+
+    /// The architecture of the binary package.
+    #[getter]
+    fn get_architecture(&self) -> Option<String> {
+        self.0.as_deb822().get("Architecture")
+    }
+
+    #[setter]
+    fn set_architecture(&mut self, arch: Option<&str>) -> PyResult<()> {
+        let arch = arch
+            .map(|arch| arch.parse::<Architectures>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_architecture(arch.as_ref());
+        Ok(())
+    }
+
+    /// The description of the binary package.
+    #[getter]
+    fn get_description(&self) -> Option<String> {
+        self.0.description()
+    }
+
+    #[setter]
+    fn set_description(&mut self, description: Option<&str>) -> PyResult<()> {
+        match description.map(|d| d.split_once('\n').unwrap_or((d, ""))) {
+            Some((synopsis, extended)) => {
+                self.0
+                    .set_description(synopsis, Some(extended))
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            }
+            None => self.0.remove_description(),
+        }
+        Ok(())
+    }
+
+    /// The dependencies of the binary package.
+    #[getter]
+    fn get_depends(&self) -> PyResult<Option<PyRelations>> {
+        get_relations(self.0.as_deb822(), "Depends")
+    }
+
+    #[setter]
+    fn set_depends(&mut self, relations: Option<PyRef<PyRelations>>) {
+        self.0.set_depends(relations.as_ref().map(|r| &r.0));
+    }
+
+    /// The recommended packages of the binary package.
+    #[getter]
+    fn get_recommends(&self) -> PyResult<Option<PyRelations>> {
+        get_relations(self.0.as_deb822(), "Recommends")
+    }
+
+    #[setter]
+    fn set_recommends(&mut self, relations: Option<PyRef<PyRelations>>) {
+        self.0.set_recommends(relations.as_ref().map(|r| &r.0));
+    }
+});
+
+/// A list of package relations, as found in e.g. `Depends` fields.
+#[pyclass(name = "Relations", unsendable)]
+pub struct PyRelations(Relations);
+
+#[pymethods]
+impl PyRelations {
+    #[new]
+    #[pyo3(signature = (text=""))]
+    fn new(text: &str) -> PyResult<Self> {
+        parse_relations(text).map(PyRelations)
+    }
+
+    /// The substitution variables (e.g. `${misc:Depends}`) used in the relations.
+    fn substvars(&self) -> Vec<String> {
+        self.0.substvars().collect()
+    }
+
+    /// Check whether the relations are satisfied by a set of installed package versions.
+    fn satisfied_by(&self, versions: std::collections::HashMap<String, String>) -> PyResult<bool> {
+        let versions = versions
+            .into_iter()
+            .map(|(name, version)| {
+                version
+                    .parse::<debversion::Version>()
+                    .map(|v| (name, v))
+                    .map_err(|e| PyValueError::new_err(e.to_string()))
+            })
+            .collect::<PyResult<std::collections::HashMap<_, _>>>()?;
+        Ok(self
+            .0
+            .satisfied_by(|name: &str| versions.get(name).cloned()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Relations({:?})", self.0.to_string())
+    }
+}
+
+/// Add the classes from this module to a Python module.
+#[pymodule]
+pub fn debian_control(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyControl>()?;
+    m.add_class::<PySource>()?;
+    m.add_class::<PyBinary>()?;
+    m.add_class::<PyRelations>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    fn run(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let m = PyModule::new_bound(py, "debian_control").unwrap();
+            debian_control(&m).unwrap();
+            let locals = PyDict::new_bound(py);
+            locals.set_item("debian_control", m).unwrap();
+            if let Err(e) = py.run_bound(code, None, Some(&locals)) {
+                e.display(py);
+                panic!("Python code failed");
+            }
+        });
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        run(r#"
+text = """Source: foo
+# A comment
+Maintainer: Joe <joe@example.com>
+Build-Depends: debhelper-compat (= 13)
+
+Package: foo
+Architecture: any
+Depends: ${misc:Depends}, libc6
+Description: A package
+ With a long description
+"""
+control = debian_control.Control(text)
+assert str(control) == text
+source = control.source
+assert source.name == "foo"
+assert source["Maintainer"] == "Joe <joe@example.com>"
+assert "Vcs-Git" not in source
+source["Vcs-Git"] = "https://example.com/foo.git"
+source.build_depends = debian_control.Relations("debhelper-compat (= 13), python3")
+[binary] = control.binaries
+assert binary.architecture == "any"
+assert binary.depends.substvars() == ["${misc:Depends}"]
+assert str(binary.depends) == "${misc:Depends}, libc6"
+binary.architecture = "all"
+assert str(control) == """Source: foo
+# A comment
+Maintainer: Joe <joe@example.com>
+Build-Depends: debhelper-compat (= 13), python3
+Vcs-Git: https://example.com/foo.git
+
+Package: foo
+Architecture: all
+Depends: ${misc:Depends}, libc6
+Description: A package
+ With a long description
+"""
+"#);
+    }
+
+    #[test]
+    fn test_errors() {
+        run(r#"
+try:
+    debian_control.Control("Source: foo\n\n:invalid\n")
+except ValueError:
+    pass
+else:
+    raise AssertionError("expected ValueError")
+
+try:
+    debian_control.Relations("foo (")
+except ValueError:
+    pass
+else:
+    raise AssertionError("expected ValueError")
+
+control = debian_control.Control()
+binary = control.add_binary("foo")
+try:
+    binary["Depends"]
+except KeyError:
+    pass
+else:
+    raise AssertionError("expected KeyError")
+assert binary.get("Depends") is None
+"#);
+    }
+}
@@ -22,14 +22,130 @@ impl std::fmt::Display for BuildProfile {
 }
 
 impl std::str::FromStr for BuildProfile {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(s) = s.strip_prefix('!') {
-            Ok(BuildProfile::Disabled(s.to_string()))
+        Ok(Self::new(s))
+    }
+}
+
+impl BuildProfile {
+    /// Create a term from its textual form, such as `nocheck` or `!nocheck`.
+    pub(crate) fn new(term: &str) -> Self {
+        if let Some(name) = term.strip_prefix('!') {
+            BuildProfile::Disabled(name.to_string())
         } else {
-            Ok(BuildProfile::Enabled(s.to_string()))
+            BuildProfile::Enabled(term.to_string())
+        }
+    }
+
+    /// Whether this term is satisfied when building with the given profiles.
+    pub fn is_satisfied_by(&self, profiles: &[&str]) -> bool {
+        match self {
+            BuildProfile::Enabled(name) => profiles.contains(&name.as_str()),
+            BuildProfile::Disabled(name) => !profiles.contains(&name.as_str()),
+        }
+    }
+}
+
+/// A restriction formula, such as `<!nocheck> <pkg.foo.bar>`.
+///
+/// Restriction formulas are used in the `Build-Profiles` field of binary packages, and
+/// restrict relations to particular build profiles. A formula consists of restriction lists,
+/// of which at least one must be satisfied; a list is satisfied if all of its terms are.
+/// An empty formula is always satisfied.
+///
+/// # Example
+/// ```rust
+/// use debian_control::relations::RestrictionFormula;
+/// let formula: RestrictionFormula = "<!nocheck> <pkg.foo.bar cross>".parse().unwrap();
+/// assert!(formula.is_satisfied_by(&[]));
+/// assert!(!formula.is_satisfied_by(&["nocheck"]));
+/// assert!(formula.is_satisfied_by(&["nocheck", "cross", "pkg.foo.bar"]));
+/// assert_eq!(formula.to_string(), "<!nocheck> <pkg.foo.bar cross>");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RestrictionFormula(Vec<Vec<BuildProfile>>);
+
+impl RestrictionFormula {
+    /// Iterate over the restriction lists of the formula.
+    pub fn iter(&self) -> impl Iterator<Item = &[BuildProfile]> {
+        self.0.iter().map(|list| list.as_slice())
+    }
+
+    /// Whether the formula has no restriction lists.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether the formula is satisfied when building with the given profiles.
+    pub fn is_satisfied_by(&self, profiles: &[&str]) -> bool {
+        self.0.is_empty()
+            || self
+                .0
+                .iter()
+                .any(|list| list.iter().all(|term| term.is_satisfied_by(profiles)))
+    }
+}
+
+impl From<Vec<Vec<BuildProfile>>> for RestrictionFormula {
+    fn from(lists: Vec<Vec<BuildProfile>>) -> Self {
+        Self(lists)
+    }
+}
+
+impl FromIterator<Vec<BuildProfile>> for RestrictionFormula {
+    fn from_iter<I: IntoIterator<Item = Vec<BuildProfile>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl std::str::FromStr for RestrictionFormula {
+    type Err = crate::ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use SyntaxKind::*;
+        let invalid = || crate::ControlError::invalid_value("restriction formula", s);
+        let mut tokens = Lexer::new(s).filter(|(kind, _)| !matches!(kind, WHITESPACE | NEWLINE));
+        let mut lists = Vec::new();
+        while let Some((kind, _)) = tokens.next() {
+            if kind != L_ANGLE {
+                return Err(invalid());
+            }
+            let mut list = Vec::new();
+            loop {
+                match tokens.next() {
+                    Some((R_ANGLE, _)) if !list.is_empty() => break,
+                    Some((NOT, _)) => match tokens.next() {
+                        Some((IDENT, name)) => list.push(BuildProfile::Disabled(name)),
+                        _ => return Err(invalid()),
+                    },
+                    Some((IDENT, name)) => list.push(BuildProfile::Enabled(name)),
+                    _ => return Err(invalid()),
+                }
+            }
+            lists.push(list);
+        }
+        Ok(Self(lists))
+    }
+}
+
+impl std::fmt::Display for RestrictionFormula {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, list) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            f.write_str("<")?;
+            for (j, term) in list.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{}", term)?;
+            }
+            f.write_str(">")?;
         }
+        Ok(())
     }
 }
 
@@ -49,7 +165,7 @@ pub enum VersionConstraint {
 }
 
 impl std::str::FromStr for VersionConstraint {
-    type Err = String;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -58,7 +174,7 @@ impl std::str::FromStr for VersionConstraint {
             "=" => Ok(VersionConstraint::Equal),
             ">>" => Ok(VersionConstraint::GreaterThan),
             "<<" => Ok(VersionConstraint::LessThan),
-            _ => Err(format!("Invalid version constraint: {}", s)),
+            _ => Err(crate::ControlError::invalid_value("version constraint", s)),
         }
     }
 }
@@ -0,0 +1,240 @@
+//! Substitution variables, as used by `dpkg-gencontrol`.
+//!
+//! Fields in `debian/control` can refer to substitution variables such as
+//! `${shlibs:Depends}`, `${misc:Depends}` or `${binary:Version}`. Their values are collected by
+//! the package helpers in `debian/substvars` files, which contain one `name=value` assignment
+//! per line; see `deb-substvars(5)`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use debian_control::substvars::Substvars;
+//!
+//! let mut substvars: Substvars = "# generated\nshlibs:Depends=libc6 (>= 2.36)\nmisc:Depends=\n"
+//!     .parse()
+//!     .unwrap();
+//! substvars.set_version(&"1.0-2".parse().unwrap());
+//! assert_eq!(
+//!     substvars.expand("${shlibs:Depends}, foo-data (= ${binary:Version})${misc:Depends}").unwrap(),
+//!     "libc6 (>= 2.36), foo-data (= 1.0-2)"
+//! );
+//! assert_eq!(
+//!     substvars.expand("${python3:Depends}").unwrap_err().unknown,
+//!     vec!["python3:Depends"]
+//! );
+//! ```
+use crate::error::ControlError;
+use deb822_lossless::template::{self, is_valid_name, placeholders};
+use std::collections::{HashMap, HashSet};
+
+/// Variables that are always defined.
+const BUILTIN: &[(&str, &str)] = &[("Newline", "\n"), ("Space", " "), ("Tab", "\t")];
+
+/// A set of substitution variables.
+///
+/// Besides the variables that are set explicitly, `${Newline}`, `${Space}` and `${Tab}` are
+/// always defined.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Substvars {
+    vars: HashMap<String, String>,
+    optional: HashSet<String>,
+}
+
+impl Substvars {
+    /// Create an empty set of substitution variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read substitution variables from a file, such as `debian/foo.substvars`.
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ControlError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Return the value of a variable.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(|value| value.as_str()).or_else(|| {
+            BUILTIN
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, value)| *value)
+        })
+    }
+
+    /// Set the value of a variable.
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.optional.remove(name);
+        self.vars.insert(name.to_string(), value.to_string());
+    }
+
+    /// Set the value of a variable that is not expected to be used.
+    ///
+    /// Such variables are written as `name?=value`.
+    pub fn set_optional(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_string(), value.to_string());
+        self.optional.insert(name.to_string());
+    }
+
+    /// Whether the variable was set with [`Substvars::set_optional`].
+    pub fn is_optional(&self, name: &str) -> bool {
+        self.optional.contains(name)
+    }
+
+    /// Remove a variable.
+    ///
+    /// # Returns
+    /// The previous value of the variable, if any.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.optional.remove(name);
+        self.vars.remove(name)
+    }
+
+    /// Iterate over the variables that are set, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        let mut vars = self
+            .vars
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect::<Vec<_>>();
+        vars.sort();
+        vars.into_iter()
+    }
+
+    /// Set `${binary:Version}`, `${source:Version}` and `${source:Upstream-Version}` from the
+    /// version of the package.
+    pub fn set_version(&mut self, version: &debversion::Version) {
+        let upstream = match version.epoch {
+            Some(epoch) => format!("{}:{}", epoch, version.upstream_version),
+            None => version.upstream_version.clone(),
+        };
+        self.set("binary:Version", &version.to_string());
+        self.set("source:Version", &version.to_string());
+        self.set("source:Upstream-Version", &upstream);
+    }
+
+    /// Iterate over the placeholders in `value` that refer to variables that are not set.
+    pub fn unresolved<'a>(&'a self, value: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        placeholders(value).filter(|name| self.get(name).is_none())
+    }
+
+    /// All variables, including the builtin ones, for use with [`deb822_lossless::template`].
+    pub(crate) fn vars(&self) -> HashMap<&str, &str> {
+        BUILTIN
+            .iter()
+            .copied()
+            .chain(
+                self.vars
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str())),
+            )
+            .collect()
+    }
+
+    /// Substitute the variables in a value.
+    ///
+    /// Returns an error listing the variables that are not set.
+    pub fn expand(&self, value: &str) -> Result<String, template::Error> {
+        template::substitute(value, &self.vars())
+    }
+}
+
+impl std::str::FromStr for Substvars {
+    type Err = ControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut substvars = Self::new();
+        for line in s.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| ControlError::invalid_value("substvars line", line))?;
+            let (name, optional) = match name.strip_suffix('?') {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            if !is_valid_name(name) {
+                return Err(ControlError::invalid_value("substvars line", line));
+            }
+            if optional {
+                substvars.set_optional(name, value);
+            } else {
+                substvars.set(name, value);
+            }
+        }
+        Ok(substvars)
+    }
+}
+
+impl std::fmt::Display for Substvars {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (name, value) in self.iter() {
+            let assign = if self.is_optional(name) { "?=" } else { "=" };
+            writeln!(f, "{}{}{}", name, assign, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let substvars: Substvars =
+            "misc:Depends=\n\n# comment\nshlibs:Depends=libc6 (>= 2.36), libfoo1\nmisc:Pre-Depends?=dpkg (>= 1.15)\n"
+                .parse()
+                .unwrap();
+        assert_eq!(substvars.get("misc:Depends"), Some(""));
+        assert_eq!(
+            substvars.get("shlibs:Depends"),
+            Some("libc6 (>= 2.36), libfoo1")
+        );
+        assert!(substvars.is_optional("misc:Pre-Depends"));
+        assert!(!substvars.is_optional("misc:Depends"));
+        assert_eq!(
+            substvars.to_string(),
+            "misc:Depends=\nmisc:Pre-Depends?=dpkg (>= 1.15)\nshlibs:Depends=libc6 (>= 2.36), libfoo1\n"
+        );
+        assert_eq!(
+            substvars.to_string().parse::<Substvars>().unwrap(),
+            substvars
+        );
+
+        for invalid in ["misc:Depends", "=foo", "-foo=bar", "foo bar=baz"] {
+            let err = invalid.parse::<Substvars>().unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("invalid substvars line: {:?}", invalid)
+            );
+        }
+    }
+
+    #[test]
+    fn test_builtin() {
+        let mut substvars = Substvars::new();
+        assert_eq!(substvars.expand("a${Space}b${Tab}c").unwrap(), "a b\tc");
+        assert_eq!(substvars.to_string(), "");
+        substvars.set("Space", "_");
+        assert_eq!(substvars.expand("a${Space}b").unwrap(), "a_b");
+        assert_eq!(substvars.remove("Space"), Some("_".to_string()));
+        assert_eq!(substvars.get("Space"), Some(" "));
+    }
+
+    #[test]
+    fn test_set_version() {
+        let mut substvars = Substvars::new();
+        substvars.set_version(&"2:1.0+dfsg-3".parse().unwrap());
+        assert_eq!(substvars.get("binary:Version"), Some("2:1.0+dfsg-3"));
+        assert_eq!(substvars.get("source:Version"), Some("2:1.0+dfsg-3"));
+        assert_eq!(substvars.get("source:Upstream-Version"), Some("2:1.0+dfsg"));
+        assert_eq!(
+            substvars
+                .unresolved("${binary:Version} ${foo} ${Newline}")
+                .collect::<Vec<_>>(),
+            vec!["foo"]
+        );
+    }
+}
@@ -17,7 +17,7 @@ pub struct ParsedVcs {
 }
 
 impl FromStr for ParsedVcs {
-    type Err = &'static str;
+    type Err = crate::ControlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut s: Cow<str> = s.trim().into();
@@ -113,11 +113,10 @@ impl Vcs {
     /// # Arguments
     /// * `name` - Name of the VCS
     /// * `value` - Value of the VCS field
-    pub fn from_field(name: &str, value: &str) -> Result<Vcs, String> {
+    pub fn from_field(name: &str, value: &str) -> Result<Vcs, crate::ControlError> {
         match name {
             "Git" => {
-                let parsed_vcs: ParsedVcs =
-                    value.parse::<ParsedVcs>().map_err(|e| e.to_string())?;
+                let parsed_vcs: ParsedVcs = value.parse()?;
                 Ok(Vcs::Git {
                     repo_url: parsed_vcs.repo_url,
                     branch: parsed_vcs.branch,
@@ -125,10 +124,9 @@ impl Vcs {
                 })
             }
             "Bzr" => {
-                let parsed_vcs: ParsedVcs =
-                    value.parse::<ParsedVcs>().map_err(|e| e.to_string())?;
+                let parsed_vcs: ParsedVcs = value.parse()?;
                 if parsed_vcs.branch.is_some() {
-                    return Err("Invalid branch value for Vcs-Bzr".to_string());
+                    return Err(crate::ControlError::invalid_value("Vcs-Bzr value", value));
                 }
                 Ok(Vcs::Bzr {
                     repo_url: parsed_vcs.repo_url,
@@ -154,7 +152,7 @@ impl Vcs {
                     })
                 }
             }
-            n => Err(format!("Unknown VCS: {}", n)),
+            n => Err(crate::ControlError::invalid_value("VCS type", n)),
         }
     }
 
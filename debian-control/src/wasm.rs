@@ -0,0 +1,146 @@
+//! JavaScript bindings, for use with wasm-bindgen.
+//!
+//! Control files are parsed losslessly, so a web-based editor can modify them and write them
+//! back out with comments and formatting intact. `Packages` files are typically large and only
+//! read, so [`parsePackages`](parse_packages) returns plain objects instead.
+//!
+//! ```js
+//! import { parseControl } from "debian-control";
+//!
+//! const control = parseControl("Source: hello\n\nPackage: hello\nArchitecture: any\n");
+//! console.log(control.source.get("Source"));  // "hello"
+//! console.log(control.binaries.map((b) => b.get("Package")));  // ["hello"]
+//! ```
+use crate::lossless::control::Control;
+use deb822_lossless::wasm::{paragraph_to_object, JsParagraph};
+use wasm_bindgen::prelude::*;
+
+/// A Debian source package control file.
+#[wasm_bindgen(js_name = Control)]
+pub struct JsControl(Control);
+
+#[wasm_bindgen(js_class = Control)]
+impl JsControl {
+    /// Create a new, empty control file.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        JsControl(Control::new())
+    }
+
+    /// The source package paragraph, if any.
+    ///
+    /// Changes to the returned paragraph are reflected in the control file.
+    #[wasm_bindgen(getter)]
+    pub fn source(&self) -> Option<JsParagraph> {
+        self.0
+            .source()
+            .map(|source| deb822_lossless::Paragraph::from(source).into())
+    }
+
+    /// The binary package paragraphs.
+    ///
+    /// Changes to the returned paragraphs are reflected in the control file.
+    #[wasm_bindgen(getter)]
+    pub fn binaries(&self) -> Vec<JsParagraph> {
+        self.0
+            .binaries()
+            .map(|binary| deb822_lossless::Paragraph::from(binary).into())
+            .collect()
+    }
+
+    /// Add a new source package paragraph.
+    #[wasm_bindgen(js_name = addSource)]
+    pub fn add_source(&mut self, name: &str) -> JsParagraph {
+        deb822_lossless::Paragraph::from(self.0.add_source(name)).into()
+    }
+
+    /// Add a new binary package paragraph.
+    #[wasm_bindgen(js_name = addBinary)]
+    pub fn add_binary(&mut self, name: &str) -> JsParagraph {
+        deb822_lossless::Paragraph::from(self.0.add_binary(name)).into()
+    }
+
+    /// Serialize the control file, including any comments.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string_js(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for JsControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a `debian/control` file.
+///
+/// Throws an error if the file can not be parsed.
+#[wasm_bindgen(js_name = parseControl)]
+pub fn parse_control(text: &str) -> Result<JsControl, JsError> {
+    text.parse()
+        .map(JsControl)
+        .map_err(|e: deb822_lossless::ParseError| JsError::new(&e.to_string()))
+}
+
+/// Parse an apt `Packages` file into an array of plain objects, one per package.
+///
+/// Throws an error if the file can not be parsed.
+#[wasm_bindgen(js_name = parsePackages)]
+pub fn parse_packages(text: &str) -> Result<js_sys::Array, JsError> {
+    let deb822: deb822_lossless::lossy::Deb822 = text
+        .parse()
+        .map_err(|e: deb822_lossless::lossy::Error| JsError::new(&e.to_string()))?;
+    Ok(deb822
+        .iter()
+        .map(|paragraph| JsValue::from(paragraph_to_object(paragraph.iter())))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control() {
+        let control = parse_control(
+            r#"Source: hello
+# A comment
+Maintainer: Joe <joe@example.com>
+
+Package: hello
+Architecture: any
+"#,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(
+            control.source().unwrap().get("Source"),
+            Some("hello".to_string())
+        );
+        let mut binaries = control.binaries();
+        assert_eq!(binaries.len(), 1);
+        binaries[0].set("Architecture", "all");
+        assert_eq!(
+            control.to_string_js(),
+            r#"Source: hello
+# A comment
+Maintainer: Joe <joe@example.com>
+
+Package: hello
+Architecture: all
+"#
+        );
+    }
+
+    #[test]
+    fn test_new() {
+        let mut control = JsControl::new();
+        control.add_source("hello");
+        control.add_binary("hello").set("Architecture", "any");
+        assert_eq!(
+            control.to_string_js(),
+            "Source: hello\n\nPackage: hello\nArchitecture: any\n"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! License expressions, as used in the `License` field.
+//!
+//! The syntax is described in the [DEP-5 specification]: license names are combined with `or`
+//! and `and`, where `and` takes precedence over `or`; a comma followed by `and` or `or` has
+//! lower precedence than both. Parentheses can be used for grouping, as in
+//! `(MIT or GPL-2+) and BSD-3-clause`. A license can carry an exception, as in
+//! `GPL-2+ with OpenSSL exception`.
+//!
+//! [DEP-5 specification]: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/#license-syntax
+//!
+//! # Example
+//! ```rust
+//! use debian_copyright::expression::LicenseExpr;
+//!
+//! let expr: LicenseExpr = "GPL-2+ or Artistic-2.0, and BSD-3-clause".parse().unwrap();
+//! assert_eq!(
+//!     expr,
+//!     LicenseExpr::And(vec![
+//!         LicenseExpr::Or(vec![
+//!             LicenseExpr::License("GPL-2+".to_string()),
+//!             LicenseExpr::License("Artistic-2.0".to_string()),
+//!         ]),
+//!         LicenseExpr::License("BSD-3-clause".to_string()),
+//!     ])
+//! );
+//! assert_eq!(expr.to_string(), "GPL-2+ or Artistic-2.0, and BSD-3-clause");
+//! assert_eq!(expr.licenses().collect::<Vec<_>>(), vec!["GPL-2+", "Artistic-2.0", "BSD-3-clause"]);
+//! ```
+
+/// Error parsing a license expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The expression is empty.
+    Empty,
+
+    /// A license name is missing, e.g. after an operator.
+    MissingLicense,
+
+    /// The license is followed by `with`, but not by the name of an exception.
+    MissingException(String),
+
+    /// A comma is not followed by `and` or `or`.
+    MissingOperator,
+
+    /// A parenthesis is not matched.
+    UnbalancedParenthesis,
+
+    /// The expression contains something that is not expected at that position.
+    Unexpected(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty license expression"),
+            ParseError::MissingLicense => write!(f, "missing license name"),
+            ParseError::MissingException(name) => write!(f, "missing exception name for {}", name),
+            ParseError::MissingOperator => write!(f, "expected 'and' or 'or' after comma"),
+            ParseError::UnbalancedParenthesis => write!(f, "unbalanced parenthesis"),
+            ParseError::Unexpected(text) => write!(f, "unexpected {:?}", text),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A license expression.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum LicenseExpr {
+    /// A single license.
+    License(String),
+
+    /// A license with an exception, such as `GPL-2+ with OpenSSL exception`.
+    ///
+    /// The exception is stored without the trailing `exception`.
+    WithException(String, String),
+
+    /// All of the licenses apply.
+    And(Vec<LicenseExpr>),
+
+    /// Any of the licenses can be chosen.
+    Or(Vec<LicenseExpr>),
+}
+
+impl LicenseExpr {
+    /// Iterate over the names of all licenses in the expression, in order.
+    pub fn licenses(&self) -> impl Iterator<Item = &str> + '_ {
+        let mut ret = vec![];
+        self.collect_licenses(&mut ret);
+        ret.into_iter()
+    }
+
+    fn collect_licenses<'a>(&'a self, ret: &mut Vec<&'a str>) {
+        match self {
+            LicenseExpr::License(name) | LicenseExpr::WithException(name, _) => ret.push(name),
+            LicenseExpr::And(exprs) | LicenseExpr::Or(exprs) => {
+                for expr in exprs {
+                    expr.collect_licenses(ret);
+                }
+            }
+        }
+    }
+
+    /// Replace every license name in the expression.
+    ///
+    /// Exceptions are left unchanged.
+    pub fn map_licenses(&self, f: &mut impl FnMut(&str) -> String) -> LicenseExpr {
+        match self {
+            LicenseExpr::License(name) => LicenseExpr::License(f(name)),
+            LicenseExpr::WithException(name, exception) => {
+                LicenseExpr::WithException(f(name), exception.clone())
+            }
+            LicenseExpr::And(exprs) => {
+                LicenseExpr::And(exprs.iter().map(|e| e.map_licenses(f)).collect())
+            }
+            LicenseExpr::Or(exprs) => {
+                LicenseExpr::Or(exprs.iter().map(|e| e.map_licenses(f)).collect())
+            }
+        }
+    }
+}
+
+/// How two expressions are combined.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    And,
+    Or,
+}
+
+/// Combine two expressions, flattening nested expressions of the same kind.
+pub(crate) fn combine(lhs: LicenseExpr, rhs: LicenseExpr, op: Op) -> LicenseExpr {
+    let mut exprs = vec![];
+    for expr in [lhs, rhs] {
+        match (expr, op) {
+            (LicenseExpr::And(inner), Op::And) | (LicenseExpr::Or(inner), Op::Or) => {
+                exprs.extend(inner)
+            }
+            (expr, _) => exprs.push(expr),
+        }
+    }
+    match op {
+        Op::And => LicenseExpr::And(exprs),
+        Op::Or => LicenseExpr::Or(exprs),
+    }
+}
+
+/// Tokens of a license expression.
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    Comma,
+    Open,
+    Close,
+}
+
+impl Token<'_> {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+/// The error for a token that is not expected at its position.
+fn unexpected(token: &Token) -> ParseError {
+    match token {
+        Token::Word(word) => ParseError::Unexpected(word.to_string()),
+        Token::Comma => ParseError::Unexpected(",".to_string()),
+        Token::Open | Token::Close => ParseError::UnbalancedParenthesis,
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        let token = match c {
+            ',' => Some(Token::Comma),
+            '(' => Some(Token::Open),
+            ')' => Some(Token::Close),
+            c if c.is_whitespace() => None,
+            _ => {
+                start.get_or_insert(i);
+                continue;
+            }
+        };
+        if let Some(start) = start.take() {
+            tokens.push(Token::Word(&s[start..i]));
+        }
+        tokens.extend(token);
+    }
+    if let Some(start) = start {
+        tokens.push(Token::Word(&s[start..]));
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Parse operands separated by a comma and `and` or `or`, which have the lowest precedence.
+    fn parse_comma(&mut self) -> Result<LicenseExpr, ParseError> {
+        let mut ret = self.parse_or()?;
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            let op = match self.next() {
+                Some(token) if token.is_keyword("and") => Op::And,
+                Some(token) if token.is_keyword("or") => Op::Or,
+                _ => return Err(ParseError::MissingOperator),
+            };
+            ret = combine(ret, self.parse_or()?, op);
+        }
+        Ok(ret)
+    }
+
+    fn parse_or(&mut self) -> Result<LicenseExpr, ParseError> {
+        let mut ret = self.parse_and()?;
+        while self.peek().is_some_and(|t| t.is_keyword("or")) {
+            self.pos += 1;
+            ret = combine(ret, self.parse_and()?, Op::Or);
+        }
+        Ok(ret)
+    }
+
+    fn parse_and(&mut self) -> Result<LicenseExpr, ParseError> {
+        let mut ret = self.parse_with()?;
+        while self.peek().is_some_and(|t| t.is_keyword("and")) {
+            self.pos += 1;
+            ret = combine(ret, self.parse_with()?, Op::And);
+        }
+        Ok(ret)
+    }
+
+    fn parse_with(&mut self) -> Result<LicenseExpr, ParseError> {
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let expr = self.parse_comma()?;
+            if self.next() != Some(&Token::Close) {
+                return Err(ParseError::UnbalancedParenthesis);
+            }
+            return Ok(expr);
+        }
+        let mut words = vec![];
+        while let Some(Token::Word(word)) = self.peek() {
+            if word.eq_ignore_ascii_case("and") || word.eq_ignore_ascii_case("or") {
+                break;
+            }
+            words.push(*word);
+            self.pos += 1;
+        }
+        match words[..] {
+            [] => Err(ParseError::MissingLicense),
+            [name] => Ok(LicenseExpr::License(name.to_string())),
+            [name, with, ref exception @ .., last]
+                if with.eq_ignore_ascii_case("with") && last.eq_ignore_ascii_case("exception") =>
+            {
+                if exception.is_empty() {
+                    return Err(ParseError::MissingException(name.to_string()));
+                }
+                Ok(LicenseExpr::WithException(
+                    name.to_string(),
+                    exception.join(" "),
+                ))
+            }
+            _ => Err(ParseError::Unexpected(words.join(" "))),
+        }
+    }
+}
+
+impl std::str::FromStr for LicenseExpr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            pos: 0,
+        };
+        let expr = parser.parse_comma()?;
+        if let Some(token) = parser.peek() {
+            return Err(unexpected(token));
+        }
+        Ok(expr)
+    }
+}
+
+impl LicenseExpr {
+    /// Write the expression; `nested` is set for operands of another expression.
+    fn write(&self, f: &mut std::fmt::Formatter<'_>, nested: bool) -> std::fmt::Result {
+        match self {
+            LicenseExpr::License(name) => f.write_str(name),
+            LicenseExpr::WithException(name, exception) => {
+                write!(f, "{} with {} exception", name, exception)
+            }
+            LicenseExpr::Or(exprs) => {
+                for (i, expr) in exprs.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" or ")?;
+                    }
+                    expr.write(f, true)?;
+                }
+                Ok(())
+            }
+            LicenseExpr::And(exprs) => {
+                let has_or = exprs.iter().any(|e| matches!(e, LicenseExpr::Or(_)));
+                for (i, expr) in exprs.iter().enumerate() {
+                    if i > 0 {
+                        // `and` binds more tightly than `or`, so use the comma form if any of
+                        // the operands is an `or` expression. That only works at the top level;
+                        // elsewhere, the `or` expressions are put in parentheses.
+                        f.write_str(if has_or && !nested { ", and " } else { " and " })?;
+                    }
+                    if nested && matches!(expr, LicenseExpr::Or(_)) {
+                        f.write_str("(")?;
+                        expr.write(f, true)?;
+                        f.write_str(")")?;
+                    } else {
+                        expr.write(f, true)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for LicenseExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write(f, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(name: &str) -> LicenseExpr {
+        LicenseExpr::License(name.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("GPL-2+".parse::<LicenseExpr>().unwrap(), license("GPL-2+"));
+        assert_eq!(
+            "GPL-2+ or Artistic-1.0 or BSD-3-clause"
+                .parse::<LicenseExpr>()
+                .unwrap(),
+            LicenseExpr::Or(vec![
+                license("GPL-2+"),
+                license("Artistic-1.0"),
+                license("BSD-3-clause")
+            ])
+        );
+        assert_eq!(
+            "Apache-2.0 or MIT and Zlib".parse::<LicenseExpr>().unwrap(),
+            LicenseExpr::Or(vec![
+                license("Apache-2.0"),
+                LicenseExpr::And(vec![license("MIT"), license("Zlib")])
+            ])
+        );
+        assert_eq!(
+            "GPL-2+ with OpenSSL exception"
+                .parse::<LicenseExpr>()
+                .unwrap(),
+            LicenseExpr::WithException("GPL-2+".to_string(), "OpenSSL".to_string())
+        );
+        assert_eq!(
+            "GPL-2+ with Font embedding exception and MIT"
+                .parse::<LicenseExpr>()
+                .unwrap(),
+            LicenseExpr::And(vec![
+                LicenseExpr::WithException("GPL-2+".to_string(), "Font embedding".to_string()),
+                license("MIT")
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s: &str| s.parse::<LicenseExpr>().unwrap_err();
+        assert_eq!(err(""), ParseError::Empty);
+        assert_eq!(err("GPL-2+ or"), ParseError::MissingLicense);
+        assert_eq!(
+            err("GPL-2+ MIT"),
+            ParseError::Unexpected("GPL-2+ MIT".to_string())
+        );
+        assert_eq!(err("GPL-2+, MIT"), ParseError::MissingOperator);
+        assert_eq!(
+            err("GPL-2+ with exception"),
+            ParseError::MissingException("GPL-2+".to_string())
+        );
+        assert_eq!(err("GPL-2+, and").to_string(), "missing license name");
+        assert_eq!(err("(MIT or GPL-2+"), ParseError::UnbalancedParenthesis);
+        assert_eq!(err("MIT or GPL-2+)"), ParseError::UnbalancedParenthesis);
+        assert_eq!(err("MIT (GPL-2+)"), ParseError::UnbalancedParenthesis);
+        assert_eq!(err("() and MIT"), ParseError::MissingLicense);
+    }
+
+    #[test]
+    fn test_parse_parentheses() {
+        assert_eq!(
+            "(MIT or GPL-2+) and BSD-3-clause"
+                .parse::<LicenseExpr>()
+                .unwrap(),
+            LicenseExpr::And(vec![
+                LicenseExpr::Or(vec![license("MIT"), license("GPL-2+")]),
+                license("BSD-3-clause")
+            ])
+        );
+        assert_eq!(
+            "Zlib or (MIT or GPL-2+, and BSD-3-clause)"
+                .parse::<LicenseExpr>()
+                .unwrap(),
+            LicenseExpr::Or(vec![
+                license("Zlib"),
+                LicenseExpr::And(vec![
+                    LicenseExpr::Or(vec![license("MIT"), license("GPL-2+")]),
+                    license("BSD-3-clause")
+                ])
+            ])
+        );
+        assert_eq!(
+            "((GPL-2+ with OpenSSL exception))"
+                .parse::<LicenseExpr>()
+                .unwrap(),
+            LicenseExpr::WithException("GPL-2+".to_string(), "OpenSSL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for text in [
+            "GPL-2+",
+            "GPL-2+ or Artistic-1.0",
+            "Apache-2.0 or MIT and Zlib",
+            "GPL-2+ or Artistic-2.0, and BSD-3-clause",
+            "GPL-3+ with OpenSSL exception or MIT",
+            "Zlib or (MIT or GPL-2+) and BSD-3-clause",
+        ] {
+            assert_eq!(text.parse::<LicenseExpr>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_map_licenses() {
+        let expr: LicenseExpr = "GPL-2+ with OpenSSL exception or MIT".parse().unwrap();
+        assert_eq!(
+            expr.map_licenses(&mut |name| name.to_lowercase())
+                .to_string(),
+            "gpl-2+ with OpenSSL exception or mit"
+        );
+    }
+}
@@ -1,4 +1,25 @@
-pub fn glob_to_regex(glob: &str) -> regex::Regex {
+/// Error translating a file glob to a regular expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobError {
+    /// The glob contains an escape sequence other than `\*`, `\?` or `\\`.
+    InvalidEscape(String),
+
+    /// The translated regular expression could not be compiled.
+    InvalidPattern(String),
+}
+
+impl std::fmt::Display for GlobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GlobError::InvalidEscape(s) => write!(f, "invalid escape sequence: {}", s),
+            GlobError::InvalidPattern(e) => write!(f, "invalid pattern: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GlobError {}
+
+pub fn glob_to_regex(glob: &str) -> Result<regex::Regex, GlobError> {
     let mut it = glob.chars();
     let mut r = "^".to_string();
 
@@ -14,10 +35,10 @@ pub fn glob_to_regex(glob: &str) -> regex::Regex {
                             regex::escape(c.unwrap().to_string().as_str())
                         }
                         Some(x) => {
-                            panic!("invalid escape sequence: \\{}", x);
+                            return Err(GlobError::InvalidEscape(format!("\\{}", x)));
                         }
                         None => {
-                            panic!("invalid escape sequence: \\");
+                            return Err(GlobError::InvalidEscape("\\".to_string()));
                         }
                     }
                 }
@@ -29,14 +50,14 @@ pub fn glob_to_regex(glob: &str) -> regex::Regex {
 
     r.push_str("$");
 
-    regex::Regex::new(r.as_str()).unwrap()
+    regex::Regex::new(r.as_str()).map_err(|e| GlobError::InvalidPattern(e.to_string()))
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
     fn test_simple() {
-        let r = super::glob_to_regex("*.rs");
+        let r = super::glob_to_regex("*.rs").unwrap();
         assert!(r.is_match("foo.rs"));
         assert!(r.is_match("bar.rs"));
         assert!(!r.is_match("foo.rs.bak"));
@@ -45,7 +66,7 @@ mod tests {
 
     #[test]
     fn test_single_char() {
-        let r = super::glob_to_regex("?.rs");
+        let r = super::glob_to_regex("?.rs").unwrap();
         assert!(r.is_match("a.rs"));
         assert!(r.is_match("b.rs"));
         assert!(!r.is_match("foo.rs"));
@@ -54,31 +75,35 @@ mod tests {
 
     #[test]
     fn test_escape() {
-        let r = super::glob_to_regex(r"\?.rs");
+        let r = super::glob_to_regex(r"\?.rs").unwrap();
         assert!(r.is_match("?.rs"));
         assert!(!r.is_match("a.rs"));
         assert!(!r.is_match("b.rs"));
 
-        let r = super::glob_to_regex(r"\*.rs");
+        let r = super::glob_to_regex(r"\*.rs").unwrap();
         assert!(r.is_match("*.rs"));
         assert!(!r.is_match("a.rs"));
         assert!(!r.is_match("b.rs"));
 
-        let r = super::glob_to_regex(r"\\?.rs");
+        let r = super::glob_to_regex(r"\\?.rs").unwrap();
         assert!(r.is_match("\\a.rs"));
         assert!(r.is_match("\\b.rs"));
         assert!(!r.is_match("a.rs"));
     }
 
-    #[should_panic]
     #[test]
     fn test_invalid_escape() {
-        super::glob_to_regex(r"\x.rs");
+        assert_eq!(
+            super::glob_to_regex(r"\x.rs").unwrap_err(),
+            super::GlobError::InvalidEscape(r"\x".to_string())
+        );
     }
 
-    #[should_panic]
     #[test]
     fn test_invalid_escape2() {
-        super::glob_to_regex(r"\");
+        assert_eq!(
+            super::glob_to_regex(r"\").unwrap_err(),
+            super::GlobError::InvalidEscape(r"\".to_string())
+        );
     }
 }
@@ -38,9 +38,12 @@
 //! allows partial parsing, parsing files with errors and unknown fields and editing while
 //! preserving formatting.
 
+pub mod expression;
 #[cfg(feature = "lossless")]
 pub mod lossless;
 pub mod lossy;
+#[cfg(feature = "spdx")]
+pub mod spdx;
 pub use lossy::Copyright;
 
 /// The current version of the DEP-5 format.
@@ -51,6 +54,77 @@ pub const CURRENT_FORMAT: &str =
 pub const KNOWN_FORMATS: &[&str] = &[CURRENT_FORMAT];
 
 mod glob;
+pub use glob::GlobError;
+
+/// Error parsing copyright files
+#[derive(Debug)]
+pub enum Error {
+    /// Parse error
+    ParseError(deb822_lossless::ParseError),
+
+    /// IO error
+    IoError(std::io::Error),
+
+    /// The file is not machine readable
+    NotMachineReadable,
+
+    /// The file does not contain any paragraphs
+    NoParagraphs,
+
+    /// A paragraph is neither a Files nor a License paragraph
+    UnknownParagraph,
+
+    /// A paragraph could not be converted
+    InvalidParagraph(deb822_lossless::FromParagraphError),
+}
+
+impl From<deb822_lossless::Error> for Error {
+    fn from(e: deb822_lossless::Error) -> Self {
+        match e {
+            deb822_lossless::Error::ParseError(e) => Error::ParseError(e),
+            deb822_lossless::Error::IoError(e) => Error::IoError(e),
+            deb822_lossless::Error::InvalidUtf8(e) => {
+                Error::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            }
+            e @ deb822_lossless::Error::InputTooLarge => {
+                Error::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl From<deb822_lossless::ParseError> for Error {
+    fn from(e: deb822_lossless::ParseError) -> Self {
+        Error::ParseError(e)
+    }
+}
+
+impl From<deb822_lossless::FromParagraphError> for Error {
+    fn from(e: deb822_lossless::FromParagraphError) -> Self {
+        Error::InvalidParagraph(e)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self {
+            Error::ParseError(e) => write!(f, "parse error: {}", e),
+            Error::NotMachineReadable => write!(f, "not machine readable"),
+            Error::IoError(e) => write!(f, "io error: {}", e),
+            Error::NoParagraphs => write!(f, "no paragraphs"),
+            Error::UnknownParagraph => write!(f, "paragraph is neither License nor Files"),
+            Error::InvalidParagraph(e) => write!(f, "invalid paragraph: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
 
 /// A license, which can be just a name, a text or a named license.
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -86,7 +160,7 @@ impl License {
 }
 
 impl std::str::FromStr for License {
-    type Err = String;
+    type Err = std::convert::Infallible;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
         if let Some((name, rest)) = text.split_once('\n') {
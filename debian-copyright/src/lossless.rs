@@ -39,8 +39,10 @@ use crate::{License, CURRENT_FORMAT, KNOWN_FORMATS};
 use deb822_lossless::{Deb822, Paragraph};
 use std::path::Path;
 
+pub use crate::Error;
+
 /// A copyright file
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Copyright(Deb822);
 
 impl Copyright {
@@ -131,52 +133,6 @@ impl Copyright {
     }
 }
 
-/// Error parsing copyright files
-#[derive(Debug)]
-pub enum Error {
-    /// Parse error
-    ParseError(deb822_lossless::ParseError),
-
-    /// IO error
-    IoError(std::io::Error),
-
-    /// The file is not machine readable
-    NotMachineReadable,
-}
-
-impl From<deb822_lossless::Error> for Error {
-    fn from(e: deb822_lossless::Error) -> Self {
-        match e {
-            deb822_lossless::Error::ParseError(e) => Error::ParseError(e),
-            deb822_lossless::Error::IoError(e) => Error::IoError(e),
-        }
-    }
-}
-
-impl From<std::io::Error> for Error {
-    fn from(e: std::io::Error) -> Self {
-        Error::IoError(e)
-    }
-}
-
-impl From<deb822_lossless::ParseError> for Error {
-    fn from(e: deb822_lossless::ParseError) -> Self {
-        Error::ParseError(e)
-    }
-}
-
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match &self {
-            Error::ParseError(e) => write!(f, "parse error: {}", e),
-            Error::NotMachineReadable => write!(f, "not machine readable"),
-            Error::IoError(e) => write!(f, "io error: {}", e),
-        }
-    }
-}
-
-impl std::error::Error for Error {}
-
 impl Default for Copyright {
     fn default() -> Self {
         Copyright(Deb822::new())
@@ -194,6 +150,62 @@ impl std::str::FromStr for Copyright {
     }
 }
 
+#[cfg(feature = "yaml")]
+fn paragraph_to_yaml(paragraph: &Paragraph) -> serde_yaml::Value {
+    serde_yaml::Value::Mapping(
+        paragraph
+            .items()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect(),
+    )
+}
+
+#[cfg(feature = "yaml")]
+impl Copyright {
+    /// Convert the copyright file to YAML.
+    ///
+    /// The result is a mapping with a `header` key containing the header paragraph (or `null`),
+    /// a `files` key with a list of the files paragraphs and a `licenses` key with a list of the
+    /// standalone license paragraphs. Each paragraph maps field names to values, with the lines
+    /// of multi-line values separated by newlines, as in the JSON export of `debian-control`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use debian_copyright::lossless::Copyright;
+    /// let copyright: Copyright = r#"Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+    ///
+    /// Files: *
+    /// License: MIT
+    /// "#.parse().unwrap();
+    /// assert_eq!(copyright.to_yaml(), r#"header:
+    ///   Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+    /// files:
+    /// - Files: '*'
+    ///   License: MIT
+    /// licenses: []
+    /// "#);
+    /// ```
+    pub fn to_yaml(&self) -> String {
+        let mut mapping = serde_yaml::Mapping::new();
+        mapping.insert(
+            "header".into(),
+            self.header()
+                .map_or(serde_yaml::Value::Null, |h| paragraph_to_yaml(&h.0)),
+        );
+        mapping.insert(
+            "files".into(),
+            self.iter_files().map(|f| paragraph_to_yaml(&f.0)).collect(),
+        );
+        mapping.insert(
+            "licenses".into(),
+            self.iter_licenses()
+                .map(|l| paragraph_to_yaml(&l.0))
+                .collect(),
+        );
+        serde_yaml::to_string(&mapping).expect("strings can always be represented as YAML")
+    }
+}
+
 impl std::fmt::Display for Copyright {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.write_str(&self.0.to_string())
@@ -201,6 +213,7 @@ impl std::fmt::Display for Copyright {
 }
 
 /// A header paragraph
+#[derive(PartialEq, Eq, Hash)]
 pub struct Header(Paragraph);
 
 impl Header {
@@ -291,6 +304,7 @@ impl Header {
 }
 
 /// A files paragraph
+#[derive(PartialEq, Eq, Hash)]
 pub struct FilesParagraph(Paragraph);
 
 impl FilesParagraph {
@@ -306,9 +320,9 @@ impl FilesParagraph {
 
     /// Check whether the paragraph matches the given filename
     pub fn matches(&self, filename: &std::path::Path) -> bool {
-        self.files()
-            .iter()
-            .any(|f| crate::glob::glob_to_regex(f).is_match(filename.to_str().unwrap()))
+        self.files().iter().any(|f| {
+            crate::glob::glob_to_regex(f).is_ok_and(|r| r.is_match(filename.to_str().unwrap()))
+        })
     }
 
     /// Copyright holders in the paragraph
@@ -364,6 +378,7 @@ impl FilesParagraph {
 }
 
 /// A paragraph that contains a license
+#[derive(PartialEq, Eq, Hash)]
 pub struct LicenseParagraph(Paragraph);
 
 impl From<LicenseParagraph> for License {
@@ -403,6 +418,24 @@ impl LicenseParagraph {
     }
 }
 
+macro_rules! impl_semantic_eq {
+    ($($ty:ty),*) => {
+        $(
+            impl deb822_lossless::SemanticEq for $ty {
+                fn semantic_eq(&self, other: &Self) -> bool {
+                    self.0.semantic_eq(&other.0)
+                }
+
+                fn semantic_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                    self.0.semantic_hash(state)
+                }
+            }
+        )*
+    };
+}
+
+impl_semantic_eq!(Copyright, Header, FilesParagraph, LicenseParagraph);
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -38,7 +38,10 @@ use crate::CURRENT_FORMAT;
 use deb822_lossless::{FromDeb822, FromDeb822Paragraph, ToDeb822, ToDeb822Paragraph};
 use std::path::Path;
 
-fn deserialize_file_list(text: &str) -> Result<Vec<String>, String> {
+fn deserialize_file_list(text: &str) -> Result<Vec<String>, crate::GlobError> {
+    for pattern in text.split_whitespace() {
+        crate::glob::glob_to_regex(pattern)?;
+    }
     Ok(text.split('\n').map(|x| x.to_string()).collect())
 }
 
@@ -99,23 +102,21 @@ pub struct Copyright {
 }
 
 impl std::str::FromStr for Copyright {
-    type Err = String;
+    type Err = crate::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if !s.starts_with("Format:") {
-            return Err("Not machine readable".to_string());
+            return Err(crate::Error::NotMachineReadable);
         }
 
-        let deb822: deb822_lossless::Deb822 = s
-            .parse()
-            .map_err(|e: deb822_lossless::ParseError| e.to_string())?;
+        let deb822: deb822_lossless::Deb822 = s.parse()?;
 
         let mut paragraphs = deb822.paragraphs();
 
         let first_para = if let Some(para) = paragraphs.next() {
             para
         } else {
-            return Err("No paragraphs".to_string());
+            return Err(crate::Error::NoParagraphs);
         };
 
         let header: Header = Header::from_paragraph(&first_para)?;
@@ -129,7 +130,7 @@ impl std::str::FromStr for Copyright {
             } else if para.get("License").is_some() {
                 license_paras.push(LicenseParagraph::from_paragraph(&para)?);
             } else {
-                return Err("Paragraph is neither License nor Files".to_string());
+                return Err(crate::Error::UnknownParagraph);
             }
         }
 
@@ -184,9 +185,9 @@ pub struct FilesParagraph {
 impl FilesParagraph {
     /// Check if the given filename matches one of the file patterns in this paragraph.
     pub fn matches(&self, filename: &std::path::Path) -> bool {
-        self.files
-            .iter()
-            .any(|f| crate::glob::glob_to_regex(f).is_match(filename.to_str().unwrap()))
+        self.files.iter().any(|f| {
+            crate::glob::glob_to_regex(f).is_ok_and(|r| r.is_match(filename.to_str().unwrap()))
+        })
     }
 }
 
@@ -271,7 +272,22 @@ This copyright file is not machine readable.
 "#;
         let ret = s.parse::<super::Copyright>();
         assert!(ret.is_err());
-        assert_eq!(ret.unwrap_err(), "Not machine readable".to_string());
+        assert!(matches!(ret.unwrap_err(), crate::Error::NotMachineReadable));
+    }
+
+    #[test]
+    fn test_invalid_glob() {
+        let s = r#"Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/
+
+Files: src/\x.c
+License: MIT
+Copyright: 2019 John Doe
+"#;
+        let err = s.parse::<super::Copyright>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid paragraph: invalid Files field \"src/\\\\x.c\": invalid escape sequence: \\x"
+        );
     }
 
     #[test]
@@ -0,0 +1,544 @@
+//! Mapping between Debian license short names and SPDX license identifiers.
+//!
+//! DEP-5 uses its own set of short names for licenses, which mostly but not entirely agree
+//! with the [SPDX license list]: `Expat` is called `MIT` by SPDX, and the GNU licenses use
+//! `GPL-2+` rather than `GPL-2.0-or-later`. The functions in this module translate between the
+//! two, for single names and for whole license expressions.
+//!
+//! Names that have no SPDX equivalent are mapped to a `LicenseRef-` identifier, which SPDX
+//! reserves for licenses that are not on its list.
+//!
+//! [SPDX license list]: https://spdx.org/licenses/
+//!
+//! # Example
+//! ```rust
+//! use debian_copyright::expression::LicenseExpr;
+//!
+//! let expr: LicenseExpr = "GPL-2+ or Expat, and BSD-3-clause".parse().unwrap();
+//! assert_eq!(expr.to_spdx(), "(GPL-2.0-or-later OR MIT) AND BSD-3-Clause");
+//!
+//! let expr = LicenseExpr::from_spdx("LGPL-2.1-only WITH Classpath-exception-2.0 OR MIT").unwrap();
+//! assert_eq!(expr.to_string(), "LGPL-2.1 with Classpath exception or Expat");
+//! ```
+use crate::expression::{combine, LicenseExpr, Op, ParseError};
+
+/// Debian short names and the SPDX identifiers they correspond to.
+///
+/// The GNU licenses are handled separately, since their SPDX identifiers encode whether later
+/// versions are allowed.
+const LICENSES: &[(&str, &str)] = &[
+    ("Apache-1.0", "Apache-1.0"),
+    ("Apache-1.1", "Apache-1.1"),
+    ("Apache-2.0", "Apache-2.0"),
+    ("Artistic-1.0", "Artistic-1.0"),
+    ("Artistic-2.0", "Artistic-2.0"),
+    ("BSD-2-clause", "BSD-2-Clause"),
+    ("BSD-3-clause", "BSD-3-Clause"),
+    ("BSD-4-clause", "BSD-4-Clause"),
+    ("BSL-1.0", "BSL-1.0"),
+    ("CC-BY-1.0", "CC-BY-1.0"),
+    ("CC-BY-2.0", "CC-BY-2.0"),
+    ("CC-BY-2.5", "CC-BY-2.5"),
+    ("CC-BY-3.0", "CC-BY-3.0"),
+    ("CC-BY-4.0", "CC-BY-4.0"),
+    ("CC-BY-SA-1.0", "CC-BY-SA-1.0"),
+    ("CC-BY-SA-2.0", "CC-BY-SA-2.0"),
+    ("CC-BY-SA-2.5", "CC-BY-SA-2.5"),
+    ("CC-BY-SA-3.0", "CC-BY-SA-3.0"),
+    ("CC-BY-SA-4.0", "CC-BY-SA-4.0"),
+    ("CC0-1.0", "CC0-1.0"),
+    ("CDDL-1.0", "CDDL-1.0"),
+    ("CDDL-1.1", "CDDL-1.1"),
+    ("CPL-1.0", "CPL-1.0"),
+    ("EFL-1", "EFL-1.0"),
+    ("EFL-2", "EFL-2.0"),
+    ("Expat", "MIT"),
+    ("ISC", "ISC"),
+    ("LPPL-1.0", "LPPL-1.0"),
+    ("LPPL-1.1", "LPPL-1.1"),
+    ("LPPL-1.2", "LPPL-1.2"),
+    ("LPPL-1.3", "LPPL-1.3a"),
+    ("LPPL-1.3c", "LPPL-1.3c"),
+    ("MPL-1.0", "MPL-1.0"),
+    ("MPL-1.1", "MPL-1.1"),
+    ("MPL-2.0", "MPL-2.0"),
+    ("OFL-1.0", "OFL-1.0"),
+    ("OFL-1.1", "OFL-1.1"),
+    ("Python-2.0", "Python-2.0"),
+    ("QPL-1.0", "QPL-1.0"),
+    ("Unlicense", "Unlicense"),
+    ("W3C", "W3C"),
+    ("WTFPL", "WTFPL"),
+    ("Zlib", "Zlib"),
+    ("Zope-1.1", "ZPL-1.1"),
+    ("Zope-2.0", "ZPL-2.0"),
+    ("Zope-2.1", "ZPL-2.1"),
+];
+
+/// Debian names for license exceptions, and the SPDX identifiers they correspond to.
+const EXCEPTIONS: &[(&str, &str)] = &[
+    ("Autoconf", "Autoconf-exception-3.0"),
+    ("Bison", "Bison-exception-2.2"),
+    ("Classpath", "Classpath-exception-2.0"),
+    ("Font", "Font-exception-2.0"),
+    ("GCC", "GCC-exception-3.1"),
+    ("Libtool", "Libtool-exception"),
+];
+
+/// Families of GNU licenses, whose SPDX identifiers end in `-only` or `-or-later`.
+const GNU_LICENSES: &[&str] = &["GPL", "LGPL", "AGPL", "GFDL"];
+
+/// SPDX expression for the `Perl` license, which is a choice between two licenses.
+const PERL: &str = "Artistic-1.0-Perl OR GPL-1.0-or-later";
+
+/// Look up the SPDX identifier for a Debian license short name.
+///
+/// Returns `None` if the license has no SPDX equivalent.
+///
+/// # Example
+/// ```rust
+/// use debian_copyright::spdx::debian_to_spdx;
+/// assert_eq!(debian_to_spdx("GPL-2+").as_deref(), Some("GPL-2.0-or-later"));
+/// assert_eq!(debian_to_spdx("LGPL-2.1").as_deref(), Some("LGPL-2.1-only"));
+/// assert_eq!(debian_to_spdx("Expat").as_deref(), Some("MIT"));
+/// assert_eq!(debian_to_spdx("MPL-1.1+").as_deref(), Some("MPL-1.1+"));
+/// assert_eq!(debian_to_spdx("public-domain"), None);
+/// ```
+pub fn debian_to_spdx(name: &str) -> Option<String> {
+    let (base, or_later) = match name.strip_suffix('+') {
+        Some(base) => (base, true),
+        None => (name, false),
+    };
+    if let Some((family, version)) = base.split_once('-') {
+        if let Some(family) = GNU_LICENSES.iter().find(|f| f.eq_ignore_ascii_case(family)) {
+            if !version.chars().all(|c| c.is_ascii_digit() || c == '.') || version.is_empty() {
+                return None;
+            }
+            let version = if version.contains('.') {
+                version.to_string()
+            } else {
+                format!("{}.0", version)
+            };
+            let suffix = if or_later { "or-later" } else { "only" };
+            return Some(format!("{}-{}-{}", family, version, suffix));
+        }
+    }
+    if base.eq_ignore_ascii_case("Perl") && !or_later {
+        return Some(PERL.to_string());
+    }
+    LICENSES
+        .iter()
+        .find(|(debian, _)| debian.eq_ignore_ascii_case(base))
+        .map(|(_, spdx)| {
+            if or_later {
+                format!("{}+", spdx)
+            } else {
+                spdx.to_string()
+            }
+        })
+}
+
+/// Look up the Debian short name for an SPDX license identifier.
+///
+/// Returns `None` if the identifier is not known.
+///
+/// # Example
+/// ```rust
+/// use debian_copyright::spdx::spdx_to_debian;
+/// assert_eq!(spdx_to_debian("GPL-3.0-or-later").as_deref(), Some("GPL-3+"));
+/// assert_eq!(spdx_to_debian("GPL-2.0-only").as_deref(), Some("GPL-2"));
+/// assert_eq!(spdx_to_debian("MIT").as_deref(), Some("Expat"));
+/// assert_eq!(spdx_to_debian("Apache-2.0+").as_deref(), Some("Apache-2.0+"));
+/// ```
+pub fn spdx_to_debian(id: &str) -> Option<String> {
+    for family in GNU_LICENSES {
+        let Some(rest) = id
+            .strip_prefix(family)
+            .and_then(|rest| rest.strip_prefix('-'))
+        else {
+            continue;
+        };
+        let (version, or_later) = if let Some(version) = rest.strip_suffix("-or-later") {
+            (version, true)
+        } else if let Some(version) = rest.strip_suffix("-only") {
+            (version, false)
+        } else if let Some(version) = rest.strip_suffix('+') {
+            // Deprecated form
+            (version, true)
+        } else {
+            (rest, false)
+        };
+        let version = version.strip_suffix(".0").unwrap_or(version);
+        return Some(format!(
+            "{}-{}{}",
+            family,
+            version,
+            if or_later { "+" } else { "" }
+        ));
+    }
+    let (base, or_later) = match id.strip_suffix('+') {
+        Some(base) => (base, true),
+        None => (id, false),
+    };
+    LICENSES
+        .iter()
+        .find(|(_, spdx)| spdx.eq_ignore_ascii_case(base))
+        .map(|(debian, _)| {
+            if or_later {
+                format!("{}+", debian)
+            } else {
+                debian.to_string()
+            }
+        })
+}
+
+/// Turn a name into a valid SPDX `LicenseRef-` identifier.
+fn license_ref(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("LicenseRef-{}", name)
+}
+
+fn exception_to_spdx(name: &str) -> String {
+    EXCEPTIONS
+        .iter()
+        .find(|(debian, _)| debian.eq_ignore_ascii_case(name))
+        .map_or_else(
+            || license_ref(&format!("{}-exception", name)),
+            |(_, spdx)| spdx.to_string(),
+        )
+}
+
+fn exception_from_spdx(id: &str) -> String {
+    EXCEPTIONS
+        .iter()
+        .find(|(_, spdx)| spdx.eq_ignore_ascii_case(id))
+        .map_or_else(
+            || {
+                let id = id.strip_prefix("LicenseRef-").unwrap_or(id);
+                id.strip_suffix("-exception").unwrap_or(id).to_string()
+            },
+            |(debian, _)| debian.to_string(),
+        )
+}
+
+/// Precedence of SPDX operators, from loosest to tightest.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Or,
+    And,
+    With,
+}
+
+fn to_spdx(expr: &LicenseExpr, context: Precedence) -> String {
+    let (text, precedence) = match expr {
+        LicenseExpr::License(name) => match debian_to_spdx(name) {
+            Some(id) if id.contains(" OR ") => (id, Precedence::Or),
+            Some(id) => (id, Precedence::With),
+            // Names that are already SPDX identifiers are kept as they are.
+            None if spdx_to_debian(name).is_some() => (name.clone(), Precedence::With),
+            None => (license_ref(name), Precedence::With),
+        },
+        LicenseExpr::WithException(name, exception) => (
+            format!(
+                "{} WITH {}",
+                to_spdx(&LicenseExpr::License(name.clone()), Precedence::With),
+                exception_to_spdx(exception)
+            ),
+            Precedence::With,
+        ),
+        LicenseExpr::And(exprs) => (
+            exprs
+                .iter()
+                .map(|e| to_spdx(e, Precedence::And))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+            Precedence::And,
+        ),
+        LicenseExpr::Or(exprs) => (
+            exprs
+                .iter()
+                .map(|e| to_spdx(e, Precedence::Or))
+                .collect::<Vec<_>>()
+                .join(" OR "),
+            Precedence::Or,
+        ),
+    };
+    if precedence < context {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
+
+/// Tokens of an SPDX license expression.
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Id(&'a str),
+    And,
+    Or,
+    With,
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Token::Id(id) => id,
+            Token::And => "AND",
+            Token::Or => "OR",
+            Token::With => "WITH",
+            Token::Open => "(",
+            Token::Close => ")",
+        })
+    }
+}
+
+/// The error for a token that is not expected at its position.
+fn unexpected(token: &Token) -> ParseError {
+    match token {
+        Token::Open | Token::Close => ParseError::UnbalancedParenthesis,
+        token => ParseError::Unexpected(token.to_string()),
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    for word in s.split_whitespace() {
+        let mut word = word;
+        while let Some(rest) = word.strip_prefix('(') {
+            tokens.push(Token::Open);
+            word = rest;
+        }
+        let mut closes = 0;
+        while let Some(rest) = word.strip_suffix(')') {
+            closes += 1;
+            word = rest;
+        }
+        match word {
+            "" => {}
+            "AND" | "and" => tokens.push(Token::And),
+            "OR" | "or" => tokens.push(Token::Or),
+            "WITH" | "with" => tokens.push(Token::With),
+            id => tokens.push(Token::Id(id)),
+        }
+        tokens.extend(std::iter::repeat_with(|| Token::Close).take(closes));
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn parse_or(&mut self) -> Result<LicenseExpr, ParseError> {
+        let mut ret = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            ret = combine(ret, self.parse_and()?, Op::Or);
+        }
+        Ok(ret)
+    }
+
+    fn parse_and(&mut self) -> Result<LicenseExpr, ParseError> {
+        let mut ret = self.parse_with()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            ret = combine(ret, self.parse_with()?, Op::And);
+        }
+        Ok(ret)
+    }
+
+    fn parse_with(&mut self) -> Result<LicenseExpr, ParseError> {
+        let license = match self.next() {
+            Some(Token::Open) => {
+                let expr = self.parse_or()?;
+                if self.next() != Some(&Token::Close) {
+                    return Err(ParseError::UnbalancedParenthesis);
+                }
+                return Ok(expr);
+            }
+            Some(Token::Id(id)) => spdx_to_debian(id)
+                .unwrap_or_else(|| id.strip_prefix("LicenseRef-").unwrap_or(id).to_string()),
+            Some(token) => return Err(unexpected(token)),
+            None => return Err(ParseError::MissingLicense),
+        };
+        if self.peek() != Some(&Token::With) {
+            return Ok(LicenseExpr::License(license));
+        }
+        self.pos += 1;
+        match self.next() {
+            Some(Token::Id(exception)) => Ok(LicenseExpr::WithException(
+                license,
+                exception_from_spdx(exception),
+            )),
+            _ => Err(ParseError::MissingException(license)),
+        }
+    }
+}
+
+impl LicenseExpr {
+    /// Convert the expression to an SPDX license expression.
+    ///
+    /// Licenses without an SPDX equivalent are represented by a `LicenseRef-` identifier.
+    pub fn to_spdx(&self) -> String {
+        to_spdx(self, Precedence::Or)
+    }
+
+    /// Parse an SPDX license expression, translating the identifiers to Debian short names.
+    ///
+    /// Identifiers without a Debian equivalent are kept as-is, apart from the `LicenseRef-`
+    /// prefix.
+    pub fn from_spdx(s: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(s),
+            pos: 0,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(unexpected(token));
+        }
+        Ok(expr)
+    }
+}
+
+impl crate::License {
+    /// The SPDX license expression for the name of this license, if it has a name that can be
+    /// parsed as a license expression.
+    pub fn spdx_expression(&self) -> Option<String> {
+        self.name()
+            .and_then(|name| name.parse::<LicenseExpr>().ok())
+            .map(|expr| expr.to_spdx())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debian_to_spdx() {
+        assert_eq!(debian_to_spdx("GPL-2").as_deref(), Some("GPL-2.0-only"));
+        assert_eq!(
+            debian_to_spdx("gpl-3+").as_deref(),
+            Some("GPL-3.0-or-later")
+        );
+        assert_eq!(
+            debian_to_spdx("LGPL-2.1+").as_deref(),
+            Some("LGPL-2.1-or-later")
+        );
+        assert_eq!(debian_to_spdx("GFDL-1.3").as_deref(), Some("GFDL-1.3-only"));
+        assert_eq!(
+            debian_to_spdx("BSD-3-Clause").as_deref(),
+            Some("BSD-3-Clause")
+        );
+        assert_eq!(debian_to_spdx("Zope-2.1").as_deref(), Some("ZPL-2.1"));
+        assert_eq!(debian_to_spdx("Perl").as_deref(), Some(PERL));
+        assert_eq!(debian_to_spdx("GPL-NIV"), None);
+        assert_eq!(debian_to_spdx("other"), None);
+    }
+
+    #[test]
+    fn test_spdx_to_debian() {
+        assert_eq!(spdx_to_debian("LGPL-2.1-only").as_deref(), Some("LGPL-2.1"));
+        assert_eq!(
+            spdx_to_debian("AGPL-3.0-or-later").as_deref(),
+            Some("AGPL-3+")
+        );
+        assert_eq!(spdx_to_debian("GPL-2.0+").as_deref(), Some("GPL-2+"));
+        assert_eq!(
+            spdx_to_debian("BSD-2-Clause").as_deref(),
+            Some("BSD-2-clause")
+        );
+        assert_eq!(spdx_to_debian("ZPL-2.1").as_deref(), Some("Zope-2.1"));
+        assert_eq!(spdx_to_debian("0BSD"), None);
+    }
+
+    #[test]
+    fn test_expression_to_spdx() {
+        let spdx = |s: &str| s.parse::<LicenseExpr>().unwrap().to_spdx();
+        assert_eq!(spdx("GPL-2+"), "GPL-2.0-or-later");
+        assert_eq!(
+            spdx("GPL-2+ with OpenSSL exception"),
+            "GPL-2.0-or-later WITH LicenseRef-OpenSSL-exception"
+        );
+        assert_eq!(
+            spdx("GPL-3+ with Foo exception"),
+            "GPL-3.0-or-later WITH LicenseRef-Foo-exception"
+        );
+        assert_eq!(
+            spdx("Apache-2.0 or Expat and Zlib"),
+            "Apache-2.0 OR MIT AND Zlib"
+        );
+        assert_eq!(
+            spdx("Perl and public-domain"),
+            "(Artistic-1.0-Perl OR GPL-1.0-or-later) AND LicenseRef-public-domain"
+        );
+        assert_eq!(
+            spdx("Perl or MIT"),
+            "Artistic-1.0-Perl OR GPL-1.0-or-later OR MIT"
+        );
+    }
+
+    #[test]
+    fn test_expression_from_spdx() {
+        let debian = |s: &str| LicenseExpr::from_spdx(s).unwrap().to_string();
+        assert_eq!(debian("GPL-2.0-or-later"), "GPL-2+");
+        assert_eq!(
+            debian("MIT AND (Apache-2.0 OR BSD-3-Clause)"),
+            "Expat, and Apache-2.0 or BSD-3-clause"
+        );
+        assert_eq!(
+            debian("GPL-2.0-or-later WITH Classpath-exception-2.0"),
+            "GPL-2+ with Classpath exception"
+        );
+        assert_eq!(debian("LicenseRef-public-domain"), "public-domain");
+        assert_eq!(
+            debian("GPL-2.0-or-later WITH LicenseRef-OpenSSL-exception"),
+            "GPL-2+ with OpenSSL exception"
+        );
+        let err = |s: &str| LicenseExpr::from_spdx(s).unwrap_err();
+        assert_eq!(err("MIT AND"), ParseError::MissingLicense);
+        assert_eq!(err("(MIT"), ParseError::UnbalancedParenthesis);
+        assert_eq!(err("MIT)"), ParseError::UnbalancedParenthesis);
+        assert_eq!(
+            err("MIT WITH"),
+            ParseError::MissingException("Expat".to_string())
+        );
+        assert_eq!(err("MIT OR AND Zlib").to_string(), "unexpected \"AND\"");
+    }
+
+    #[test]
+    fn test_license() {
+        let license: crate::License = "GPL-2+ or Expat\nSome text".parse().unwrap();
+        assert_eq!(
+            license.spdx_expression().as_deref(),
+            Some("GPL-2.0-or-later OR MIT")
+        );
+        assert_eq!(
+            crate::License::Text("text".to_string()).spdx_expression(),
+            None
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Implementations of [`arbitrary::Arbitrary`] for fuzzing.
+//!
+//! Generated patch headers always have a description, and the values of their other fields are
+//! chosen so that formatting and parsing the header again returns an equal header.
+//!
+//! # Example
+//!
+//! ```rust
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use dep3::lossy::PatchHeader;
+//!
+//! let mut u = Unstructured::new(b"some bytes provided by the fuzzer");
+//! let header = PatchHeader::arbitrary(&mut u).unwrap();
+//! assert_eq!(header.to_string().parse::<PatchHeader>().unwrap(), header);
+//! ```
+use crate::fields::*;
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+const PEOPLE: &[&str] = &[
+    "John Doe <john.doe@example.com>",
+    "Jane Doe <jane@example.org>",
+    "Jelmer Vernooĳ <jelmer@debian.org>",
+];
+
+const CATEGORIES: &[OriginCategory] = &[
+    OriginCategory::Backport,
+    OriginCategory::Vendor,
+    OriginCategory::Upstream,
+    OriginCategory::Other,
+];
+
+fn commit(u: &mut Unstructured) -> Result<String> {
+    const HEX: &[u8] = b"0123456789abcdef";
+    (0..u.int_in_range(7..=40)?)
+        .map(|_| u.choose(HEX).map(|c| *c as char))
+        .collect()
+}
+
+fn url(u: &mut Unstructured) -> Result<url::Url> {
+    let url = match u.int_in_range(0..=2)? {
+        0 => format!(
+            "https://github.com/example/project/pull/{}",
+            u.int_in_range(1u32..=9999)?
+        ),
+        1 => format!(
+            "https://bugs.example.com/show_bug.cgi?id={}",
+            u.int_in_range(1u32..=99999)?
+        ),
+        _ => format!(
+            "https://lists.example.org/archive/{}.html",
+            u.int_in_range(1u32..=999)?
+        ),
+    };
+    Ok(url.parse().unwrap())
+}
+
+/// Generate a description.
+///
+/// Indented `#` lines are comments in patch headers, so they are left out.
+fn description(u: &mut Unstructured) -> Result<String> {
+    let value = deb822_lossless::arbitrary::field_value(u)?;
+    let lines = value.lines().filter(|line| !line.starts_with('#'));
+    Ok(lines.collect::<Vec<_>>().join("\n").trim_end().to_string())
+}
+
+impl<'a> Arbitrary<'a> for crate::lossy::PatchHeader {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            origin: if u.arbitrary()? {
+                let category = if u.arbitrary()? {
+                    Some(*u.choose(CATEGORIES)?)
+                } else {
+                    None
+                };
+                let origin = if u.arbitrary()? {
+                    Origin::Commit(commit(u)?)
+                } else {
+                    Origin::Other(url(u)?.to_string())
+                };
+                Some((category, origin))
+            } else {
+                None
+            },
+            forwarded: match u.int_in_range(0..=3)? {
+                0 => None,
+                1 => Some(Forwarded::No),
+                2 => Some(Forwarded::NotNeeded),
+                _ => Some(Forwarded::Yes(url(u)?.to_string())),
+            },
+            author: if u.arbitrary()? {
+                Some(u.choose(PEOPLE)?.to_string())
+            } else {
+                None
+            },
+            reviewed_by: if u.arbitrary()? {
+                Some(u.choose(PEOPLE)?.to_string())
+            } else {
+                None
+            },
+            bug_debian: if u.arbitrary()? {
+                Some(
+                    format!(
+                        "https://bugs.debian.org/{}",
+                        u.int_in_range(1u32..=1100000)?
+                    )
+                    .parse()
+                    .unwrap(),
+                )
+            } else {
+                None
+            },
+            last_update: if u.arbitrary()? {
+                chrono::NaiveDate::from_ymd_opt(
+                    u.int_in_range(2000..=2030)?,
+                    u.int_in_range(1..=12)?,
+                    u.int_in_range(1..=28)?,
+                )
+            } else {
+                None
+            },
+            applied_upstream: match u.int_in_range(0..=2)? {
+                0 => None,
+                1 => Some(AppliedUpstream::Commit(commit(u)?)),
+                _ => Some(AppliedUpstream::Other(url(u)?.to_string())),
+            },
+            bug: if u.arbitrary()? { Some(url(u)?) } else { None },
+            description: Some(description(u)?),
+        })
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl<'a> Arbitrary<'a> for crate::lossless::PatchHeader {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(crate::lossy::PatchHeader::arbitrary(u)?
+            .to_string()
+            .parse()
+            .unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random input, so that the tests cover more than empty data.
+    fn data(seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..1024)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for seed in 0..50 {
+            let data = data(seed);
+            let mut u = Unstructured::new(&data);
+            let header = crate::lossy::PatchHeader::arbitrary(&mut u).unwrap();
+            let text = header.to_string();
+            assert_eq!(text.parse::<crate::lossy::PatchHeader>().unwrap(), header);
+
+            #[cfg(feature = "lossless")]
+            {
+                let lossless: crate::lossless::PatchHeader = text.parse().unwrap();
+                assert_eq!(lossless.origin(), header.origin);
+                assert_eq!(lossless.forwarded().transpose().unwrap(), header.forwarded);
+                assert_eq!(lossless.last_update(), header.last_update);
+                assert_eq!(
+                    lossless.applied_upstream().transpose().unwrap(),
+                    header.applied_upstream
+                );
+            }
+        }
+    }
+}
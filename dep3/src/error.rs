@@ -0,0 +1,15 @@
+/// Errors that can occur when parsing DEP-3 patch headers.
+#[derive(Debug, thiserror::Error)]
+pub enum Dep3Error {
+    /// The header could not be parsed as deb822.
+    #[error(transparent)]
+    Syntax(#[from] deb822_lossless::ParseError),
+
+    /// A field in the header has an invalid value, or a required field is missing.
+    #[error(transparent)]
+    InvalidField(#[from] deb822_lossless::FromParagraphError),
+
+    /// The origin category is not one of the known categories.
+    #[error("invalid origin category: {0:?}")]
+    InvalidOriginCategory(String),
+}
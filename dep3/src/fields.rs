@@ -23,7 +23,7 @@ impl std::fmt::Display for Forwarded {
 }
 
 impl std::str::FromStr for Forwarded {
-    type Err = &'static str;
+    type Err = crate::Dep3Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -60,7 +60,7 @@ impl std::fmt::Display for OriginCategory {
 }
 
 impl std::str::FromStr for OriginCategory {
-    type Err = &'static str;
+    type Err = crate::Dep3Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -68,7 +68,7 @@ impl std::str::FromStr for OriginCategory {
             "vendor" => Ok(OriginCategory::Vendor),
             "upstream" => Ok(OriginCategory::Upstream),
             "other" => Ok(OriginCategory::Other),
-            _ => Err("invalid origin category"),
+            s => Err(crate::Dep3Error::InvalidOriginCategory(s.to_string())),
         }
     }
 }
@@ -93,7 +93,7 @@ impl std::fmt::Display for Origin {
 }
 
 impl std::str::FromStr for Origin {
-    type Err = &'static str;
+    type Err = crate::Dep3Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(rest) = s.strip_prefix("commit:") {
@@ -124,7 +124,7 @@ impl std::fmt::Display for AppliedUpstream {
 }
 
 impl std::str::FromStr for AppliedUpstream {
-    type Err = &'static str;
+    type Err = crate::Dep3Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Some(rest) = s.strip_prefix("commit:") {
@@ -18,10 +18,20 @@
 //! assert_eq!(patch_header.description, Some("[PATCH] fix a bug".to_string()));
 //! assert_eq!(patch_header.vendor_bugs("Debian"), Some("https://bugs.debian.org/123456"));
 //! ```
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+mod error;
+pub use error::Dep3Error;
 mod fields;
 pub use fields::*;
 #[cfg(feature = "lossless")]
 pub mod lossless;
 pub mod lossy;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use lossy::PatchHeader;
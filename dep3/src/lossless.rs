@@ -17,11 +17,12 @@
 //! assert_eq!(patch_header.description(), Some("[PATCH] fix a bug".to_string()));
 //! assert_eq!(patch_header.vendor_bugs("Debian").collect::<Vec<_>>(), vec!["https://bugs.debian.org/123456".to_string()]);
 //! ```
-use deb822_lossless::Paragraph;
+use deb822_lossless::{FieldError, Paragraph};
 
 use crate::fields::*;
 
 /// A Debian patch header.
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct PatchHeader(Paragraph);
 
 impl PatchHeader {
@@ -57,11 +58,10 @@ impl PatchHeader {
     }
 
     /// The `Forwarded` field.
-    pub fn forwarded(&self) -> Option<Forwarded> {
+    pub fn forwarded(&self) -> Option<Result<Forwarded, FieldError>> {
         self.0
-            .get("Forwarded")
-            .as_deref()
-            .map(|s| s.parse().unwrap())
+            .get_ref("Forwarded")
+            .map(|s| FieldError::parse("Forwarded", &s))
     }
 
     /// Set the `Forwarded` field.
@@ -103,11 +103,10 @@ impl PatchHeader {
     }
 
     /// The `Applied-Upstream` field.
-    pub fn applied_upstream(&self) -> Option<AppliedUpstream> {
+    pub fn applied_upstream(&self) -> Option<Result<AppliedUpstream, FieldError>> {
         self.0
-            .get("Applied-Upstream")
-            .as_deref()
-            .map(|s| s.parse().unwrap())
+            .get_ref("Applied-Upstream")
+            .map(|s| FieldError::parse("Applied-Upstream", &s))
     }
 
     /// Set the `Applied-Upstream` field.
@@ -166,20 +165,18 @@ impl PatchHeader {
     pub fn set_description(&mut self, description: &str) {
         if let Some(subject) = self.0.get("Subject") {
             // Replace the first line with ours
-            let new = format!(
-                "{}\n{}",
-                description,
-                subject.split_once('\n').map(|x| x.1).unwrap_or("")
-            );
-            self.0.insert("Subject", new.as_str());
-        } else if let Some(description) = self.0.get("Description") {
+            let new = match subject.split_once('\n') {
+                Some((_, rest)) => format!("{}\n{}", description, rest),
+                None => description.to_string(),
+            };
+            self.0.set("Subject", new.as_str());
+        } else if let Some(old) = self.0.get("Description") {
             // Replace the first line with ours
-            let new = format!(
-                "{}\n{}",
-                description.split_once('\n').map(|x| x.1).unwrap_or(""),
-                description
-            );
-            self.0.insert("Description", new.as_str());
+            let new = match old.split_once('\n') {
+                Some((_, rest)) => format!("{}\n{}", description, rest),
+                None => description.to_string(),
+            };
+            self.0.set("Description", new.as_str());
         } else {
             self.0.insert("Description", description);
         }
@@ -201,7 +198,7 @@ impl PatchHeader {
                 .map(|x| x.0)
                 .unwrap_or(subject.as_str());
             let new = format!("{}\n{}", first_line, long_description);
-            self.0.insert("Subject", new.as_str());
+            self.0.set("Subject", new.as_str());
         } else if let Some(description) = self.0.get("Description") {
             // Keep the first line, but replace the rest with our text
             let first_line = description
@@ -209,7 +206,7 @@ impl PatchHeader {
                 .map(|x| x.0)
                 .unwrap_or(description.as_str());
             let new = format!("{}\n{}", first_line, long_description);
-            self.0.insert("Description", new.as_str());
+            self.0.set("Description", new.as_str());
         } else {
             self.0.insert("Description", long_description);
         }
@@ -219,6 +216,95 @@ impl PatchHeader {
     pub fn write<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
         writer.write_all(self.to_string().as_bytes())
     }
+
+    /// Read the patch header from a patch file.
+    ///
+    /// The header ends at the first empty line or the first line starting with `---`; the
+    /// rest of the file is ignored. The error records the path of the file.
+    pub fn from_file<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, deb822_lossless::FileError> {
+        let path = path.as_ref();
+        let read = || -> Result<Self, deb822_lossless::Error> {
+            let text = std::fs::read_to_string(path)?;
+            Ok(split_header(&text).0.parse()?)
+        };
+        read().map_err(|e| deb822_lossless::FileError::new(path, e))
+    }
+
+    /// Write the patch header to a patch file, atomically replacing it.
+    ///
+    /// As with [`PatchHeader::save`], the rest of an existing file is kept. The file is
+    /// written with [`deb822_lossless::write_atomic`], so readers never see a partially
+    /// written patch.
+    ///
+    /// # Example
+    /// ```rust
+    /// use dep3::lossless::PatchHeader;
+    /// let path = std::env::temp_dir().join(format!("dep3-save-atomic-{}.patch", std::process::id()));
+    /// std::fs::write(&path, "Description: Fix a bug\n---\n foo.c | 2 +-\n").unwrap();
+    /// let mut header = PatchHeader::from_file(&path).unwrap();
+    /// header.set_description("Fix two bugs");
+    /// header.save_atomic(&path).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&path).unwrap(),
+    ///     "Description: Fix two bugs\n---\n foo.c | 2 +-\n"
+    /// );
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_atomic<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let rest = match std::fs::read_to_string(path) {
+            Ok(text) => split_header(&text).1.to_string(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        deb822_lossless::write_atomic(path, format!("{}{}", self, rest).as_bytes())
+    }
+
+    /// Write the patch header to a patch file.
+    ///
+    /// If the file already exists, its header is replaced and the rest of the file, such as
+    /// the diff, is kept. The file is replaced atomically, and the error records the path of
+    /// the file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use dep3::lossless::PatchHeader;
+    /// use dep3::Forwarded;
+    /// let path = std::env::temp_dir().join(format!("dep3-save-{}.patch", std::process::id()));
+    /// std::fs::write(&path, "Description: Fix a bug\n---\n foo.c | 2 +-\n").unwrap();
+    /// let mut header = PatchHeader::from_file(&path).unwrap();
+    /// header.set_forwarded(Forwarded::NotNeeded);
+    /// header.save(&path).unwrap();
+    /// assert_eq!(
+    ///     std::fs::read_to_string(&path).unwrap(),
+    ///     "Description: Fix a bug\nForwarded: not-needed\n---\n foo.c | 2 +-\n"
+    /// );
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), deb822_lossless::FileError> {
+        let path = path.as_ref();
+        self.save_atomic(path)
+            .map_err(|e| deb822_lossless::FileError::new(path, e))
+    }
+}
+
+/// Split the text of a patch into the header and the rest of the patch.
+///
+/// The header ends at the first empty line or the first line starting with `---`.
+fn split_header(text: &str) -> (&str, &str) {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']).is_empty() || line.starts_with("---") {
+            break;
+        }
+        offset += line.len();
+    }
+    text.split_at(offset)
 }
 
 impl std::fmt::Display for PatchHeader {
@@ -241,11 +327,55 @@ impl std::str::FromStr for PatchHeader {
     }
 }
 
+impl deb822_lossless::SemanticEq for PatchHeader {
+    fn semantic_eq(&self, other: &Self) -> bool {
+        self.0.semantic_eq(&other.0)
+    }
+
+    fn semantic_hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.semantic_hash(state)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::PatchHeader;
     use std::str::FromStr;
 
+    #[test]
+    fn test_from_file_save() {
+        let dir = std::env::temp_dir().join(format!("dep3-from-file-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("fix.patch");
+        std::fs::write(
+            &path,
+            "Description: Fix a bug\nAuthor: John Doe <john.doe@example.com>\n\nIndex: foo/foo.c\n--- foo.orig/foo.c\n+++ foo/foo.c\n",
+        )
+        .unwrap();
+        let mut header = PatchHeader::from_file(&path).unwrap();
+        assert_eq!(
+            header.author(),
+            Some("John Doe <john.doe@example.com>".to_string())
+        );
+        header.set_description("Fix another bug");
+        header.save(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "Description: Fix another bug\nAuthor: John Doe <john.doe@example.com>\n\nIndex: foo/foo.c\n--- foo.orig/foo.c\n+++ foo/foo.c\n"
+        );
+
+        let new = dir.join("new.patch");
+        header.save(&new).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&new).unwrap(),
+            "Description: Fix another bug\nAuthor: John Doe <john.doe@example.com>\n"
+        );
+
+        let err = PatchHeader::from_file(dir.join("missing.patch")).unwrap_err();
+        assert_eq!(err.path, dir.join("missing.patch"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_upstream() {
         let text = r#"From: Ulrich Drepper <drepper@redhat.com>
@@ -314,9 +444,9 @@ Last-Update: 2006-12-21
         assert_eq!(header.origin(), None);
         assert_eq!(
             header.forwarded(),
-            Some(super::Forwarded::Yes(
+            Some(Ok(super::Forwarded::Yes(
                 "http://lists.example.com/oct-2006/1234.html".to_string()
-            ))
+            )))
         );
         assert_eq!(
             header.author(),
@@ -357,7 +487,7 @@ Author: Thiemo Seufer <ths@debian.org>
                 )
             ))
         );
-        assert_eq!(header.forwarded(), Some(super::Forwarded::NotNeeded));
+        assert_eq!(header.forwarded(), Some(Ok(super::Forwarded::NotNeeded)));
         assert_eq!(
             header.author(),
             Some("Thiemo Seufer <ths@debian.org>".to_string())
@@ -393,9 +523,9 @@ Last-Update: 2010-03-29
         assert_eq!(header.origin(), None);
         assert_eq!(
             header.forwarded(),
-            Some(super::Forwarded::Yes(
+            Some(Ok(super::Forwarded::Yes(
                 "http://lists.example.com/2010/03/1234.html".to_string()
-            ))
+            )))
         );
         assert_eq!(
             header.author(),
@@ -408,9 +538,9 @@ Last-Update: 2010-03-29
         );
         assert_eq!(
             header.applied_upstream(),
-            Some(super::AppliedUpstream::Other(
+            Some(Ok(super::AppliedUpstream::Other(
                 "1.2, http://bzr.example.com/frobnicator/trunk/revision/123".to_string()
-            ))
+            )))
         );
         assert_eq!(header.bugs().collect::<Vec<_>>(), vec![]);
         assert_eq!(
@@ -438,4 +568,25 @@ Bug-Ubuntu: http://bugs.launchpad.net/123
             vec!["http://bugs.launchpad.net/123".to_string()]
         );
     }
+
+    #[test]
+    fn test_set_description() {
+        let mut header =
+            PatchHeader::from_str("Description: Fix a crash\n On startup.\nForwarded: no\n")
+                .unwrap();
+        header.set_description("Fix a crash on startup");
+        assert_eq!(
+            header.to_string(),
+            "Description: Fix a crash on startup\n On startup.\nForwarded: no\n"
+        );
+        header.set_long_description("When the cache is empty.");
+        assert_eq!(
+            header.to_string(),
+            "Description: Fix a crash on startup\n When the cache is empty.\nForwarded: no\n"
+        );
+
+        let mut header = PatchHeader::from_str("Subject: Fix a crash\n").unwrap();
+        header.set_description("Fix a crash on startup");
+        assert_eq!(header.to_string(), "Subject: Fix a crash on startup\n");
+    }
 }
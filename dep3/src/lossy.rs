@@ -95,11 +95,12 @@ impl std::fmt::Display for PatchHeader {
 }
 
 impl std::str::FromStr for PatchHeader {
-    type Err = String;
+    type Err = crate::Dep3Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let paragraph = Paragraph::from_str(s).map_err(|e| e.to_string())?;
-        let mut header = PatchHeader::from_paragraph(&paragraph)?;
+        let paragraph = Paragraph::from_str(s)?;
+        let mut header =
+            PatchHeader::from_paragraph(&paragraph).map_err(crate::Dep3Error::InvalidField)?;
         if header.author.is_none() {
             header.author = paragraph.get("From").map(|v| v.to_string());
         }
@@ -159,7 +160,7 @@ Bug-Debian: http://bugs.debian.org/510219
         );
         assert_eq!(
             header.description,
-            Some("Fix regex problems with some multi-bytes characters\n* posix/bug-regex17.c: Add testcases.\n* posix/regcomp.c (re_compile_fastmap_iter): Rewrite COMPLEX_BRACKET\nhandling.".to_string())
+            Some("Fix regex problems with some multi-bytes characters\n\n* posix/bug-regex17.c: Add testcases.\n* posix/regcomp.c (re_compile_fastmap_iter): Rewrite COMPLEX_BRACKET\nhandling.".to_string())
         );
     }
 
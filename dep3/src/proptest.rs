@@ -0,0 +1,177 @@
+//! [`proptest`] strategies for patch headers.
+//!
+//! The generated patch headers follow the same rules as those generated in
+//! [`crate::arbitrary`], so formatting and parsing them again returns an equal header.
+//!
+//! # Example
+//!
+//! ```rust
+//! use dep3::lossy::PatchHeader;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn roundtrip(header in any::<PatchHeader>()) {
+//!         prop_assert_eq!(header.to_string().parse::<PatchHeader>().unwrap(), header);
+//!     }
+//! }
+//! # roundtrip();
+//! ```
+use crate::fields::*;
+use ::proptest::prelude::*;
+
+fn person() -> impl Strategy<Value = String> {
+    prop::sample::select(
+        &[
+            "John Doe <john.doe@example.com>",
+            "Jane Doe <jane@example.org>",
+            "Jelmer Vernooĳ <jelmer@debian.org>",
+        ][..],
+    )
+    .prop_map(String::from)
+}
+
+fn commit() -> impl Strategy<Value = String> {
+    "[0-9a-f]{7,40}"
+}
+
+fn url() -> impl Strategy<Value = url::Url> {
+    prop_oneof![
+        (1u32..=9999).prop_map(|n| format!("https://github.com/example/project/pull/{}", n)),
+        (1u32..=99999).prop_map(|n| format!("https://bugs.example.com/show_bug.cgi?id={}", n)),
+        (1u32..=999).prop_map(|n| format!("https://lists.example.org/archive/{}.html", n)),
+    ]
+    .prop_map(|url| url.parse().unwrap())
+}
+
+fn origin() -> impl Strategy<Value = (Option<OriginCategory>, Origin)> {
+    (
+        prop::option::of(prop::sample::select(
+            &[
+                OriginCategory::Backport,
+                OriginCategory::Vendor,
+                OriginCategory::Upstream,
+                OriginCategory::Other,
+            ][..],
+        )),
+        prop_oneof![
+            commit().prop_map(Origin::Commit),
+            url().prop_map(|url| Origin::Other(url.to_string())),
+        ],
+    )
+}
+
+fn forwarded() -> impl Strategy<Value = Forwarded> {
+    prop_oneof![
+        Just(Forwarded::No),
+        Just(Forwarded::NotNeeded),
+        url().prop_map(|url| Forwarded::Yes(url.to_string())),
+    ]
+}
+
+fn applied_upstream() -> impl Strategy<Value = AppliedUpstream> {
+    prop_oneof![
+        commit().prop_map(AppliedUpstream::Commit),
+        url().prop_map(|url| AppliedUpstream::Other(url.to_string())),
+    ]
+}
+
+fn date() -> impl Strategy<Value = chrono::NaiveDate> {
+    (2000..=2030, 1u32..=12, 1u32..=28)
+        .prop_map(|(y, m, d)| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap())
+}
+
+/// A strategy for descriptions.
+///
+/// Indented `#` lines are comments in patch headers, so they are left out.
+fn description() -> impl Strategy<Value = String> {
+    deb822_lossless::proptest::field_value().prop_map(|value| {
+        let lines = value.lines().filter(|line| !line.starts_with('#'));
+        lines.collect::<Vec<_>>().join("\n").trim_end().to_string()
+    })
+}
+
+impl Arbitrary for crate::lossy::PatchHeader {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            prop::option::of(origin()),
+            prop::option::of(forwarded()),
+            prop::option::of(person()),
+            prop::option::of(person()),
+            prop::option::of((1u32..=1100000).prop_map(|n| {
+                format!("https://bugs.debian.org/{}", n)
+                    .parse::<url::Url>()
+                    .unwrap()
+            })),
+            prop::option::of(date()),
+            prop::option::of(applied_upstream()),
+            prop::option::of(url()),
+            description(),
+        )
+            .prop_map(
+                |(
+                    origin,
+                    forwarded,
+                    author,
+                    reviewed_by,
+                    bug_debian,
+                    last_update,
+                    applied_upstream,
+                    bug,
+                    description,
+                )| Self {
+                    origin,
+                    forwarded,
+                    author,
+                    reviewed_by,
+                    bug_debian,
+                    last_update,
+                    applied_upstream,
+                    bug,
+                    description: Some(description),
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(feature = "lossless")]
+impl Arbitrary for crate::lossless::PatchHeader {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<crate::lossy::PatchHeader>()
+            .prop_map(|header| header.to_string().parse().unwrap())
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_lossy_roundtrip(header in any::<crate::lossy::PatchHeader>()) {
+            prop_assert_eq!(
+                header.to_string().parse::<crate::lossy::PatchHeader>().unwrap(),
+                header
+            );
+        }
+
+        #[cfg(feature = "lossless")]
+        #[test]
+        fn test_lossless_set_description(
+            mut header in any::<crate::lossless::PatchHeader>(),
+            description in deb822_lossless::proptest::field_line(),
+        ) {
+            let long_description = header.long_description();
+            header.set_description(&description);
+            prop_assert_eq!(header.description(), Some(description));
+            prop_assert_eq!(header.long_description(), long_description);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Python bindings for the lossless patch header parser.
+//!
+//! Patch headers edited from Python keep their comments and field order, since the
+//! [`PatchHeader`](PyPatchHeader) class wraps [`crate::lossless::PatchHeader`].
+use crate::fields::{AppliedUpstream, Forwarded, Origin, OriginCategory};
+use crate::lossless::PatchHeader;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+
+/// A DEP-3 patch header.
+#[pyclass(name = "PatchHeader", unsendable)]
+pub struct PyPatchHeader(PatchHeader);
+
+#[pymethods]
+impl PyPatchHeader {
+    #[new]
+    #[pyo3(signature = (text=None))]
+    fn new(text: Option<&str>) -> PyResult<Self> {
+        match text {
+            Some(text) => text
+                .parse()
+                .map(PyPatchHeader)
+                .map_err(|e: deb822_lossless::ParseError| PyValueError::new_err(e.to_string())),
+            None => Ok(PyPatchHeader(PatchHeader::new())),
+        }
+    }
+
+    /// The first line of the description of the patch.
+    #[getter]
+    fn get_description(&self) -> Option<String> {
+        self.0.description()
+    }
+
+    #[setter]
+    fn set_description(&mut self, description: &str) {
+        self.0.set_description(description);
+    }
+
+    /// The remaining lines of the description of the patch.
+    #[getter]
+    fn get_long_description(&self) -> Option<String> {
+        self.0.long_description()
+    }
+
+    #[setter]
+    fn set_long_description(&mut self, long_description: &str) {
+        self.0.set_long_description(long_description);
+    }
+
+    /// The author of the patch.
+    #[getter]
+    fn get_author(&self) -> Option<String> {
+        self.0.author()
+    }
+
+    #[setter]
+    fn set_author(&mut self, author: &str) {
+        self.0.set_author(author);
+    }
+
+    /// The origin of the patch, as a `(category, origin)` tuple.
+    #[getter]
+    fn get_origin(&self) -> Option<(Option<String>, String)> {
+        self.0
+            .origin()
+            .map(|(category, origin)| (category.map(|c| c.to_string()), origin.to_string()))
+    }
+
+    /// Set the origin of the patch.
+    #[pyo3(signature = (origin, category=None))]
+    fn set_origin(&mut self, origin: &str, category: Option<&str>) -> PyResult<()> {
+        let category = category
+            .map(|c| c.parse::<OriginCategory>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let origin = origin
+            .parse::<Origin>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_origin(category, origin);
+        Ok(())
+    }
+
+    /// Whether and where the patch was forwarded upstream.
+    #[getter]
+    fn get_forwarded(&self) -> PyResult<Option<String>> {
+        self.0
+            .forwarded()
+            .map(|f| {
+                f.map(|f| f.to_string())
+                    .map_err(|e| PyValueError::new_err(e.to_string()))
+            })
+            .transpose()
+    }
+
+    #[setter]
+    fn set_forwarded(&mut self, forwarded: &str) -> PyResult<()> {
+        let forwarded = forwarded
+            .parse::<Forwarded>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_forwarded(forwarded);
+        Ok(())
+    }
+
+    /// Whether and where the patch was applied upstream.
+    #[getter]
+    fn get_applied_upstream(&self) -> PyResult<Option<String>> {
+        self.0
+            .applied_upstream()
+            .map(|a| {
+                a.map(|a| a.to_string())
+                    .map_err(|e| PyValueError::new_err(e.to_string()))
+            })
+            .transpose()
+    }
+
+    #[setter]
+    fn set_applied_upstream(&mut self, applied_upstream: &str) -> PyResult<()> {
+        let applied_upstream = applied_upstream
+            .parse::<AppliedUpstream>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_applied_upstream(applied_upstream);
+        Ok(())
+    }
+
+    /// The date of the last update of the patch, in `YYYY-MM-DD` format.
+    #[getter]
+    fn get_last_update(&self) -> Option<String> {
+        self.0
+            .last_update()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+    }
+
+    #[setter]
+    fn set_last_update(&mut self, date: &str) -> PyResult<()> {
+        let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_last_update(date);
+        Ok(())
+    }
+
+    /// The people who reviewed the patch.
+    #[getter]
+    fn reviewed_by(&self) -> Vec<String> {
+        self.0.reviewed_by()
+    }
+
+    /// The bugs associated with the patch, as `(vendor, url)` tuples.
+    ///
+    /// The vendor is `None` for upstream bugs.
+    fn bugs(&self) -> Vec<(Option<String>, String)> {
+        self.0.bugs().collect()
+    }
+
+    /// The bugs associated with the patch for a specific vendor.
+    fn vendor_bugs(&self, vendor: &str) -> Vec<String> {
+        self.0.vendor_bugs(vendor).collect()
+    }
+
+    /// Set the upstream bug associated with the patch.
+    fn set_upstream_bug(&mut self, bug: &str) {
+        self.0.set_upstream_bug(bug);
+    }
+
+    /// Set the bug associated with the patch for a specific vendor.
+    fn set_vendor_bug(&mut self, vendor: &str, bug: &str) {
+        self.0.set_vendor_bug(vendor, bug);
+    }
+
+    fn __getitem__(&self, key: &str) -> PyResult<String> {
+        self.0
+            .as_deb822()
+            .get(key)
+            .ok_or_else(|| PyKeyError::new_err(key.to_string()))
+    }
+
+    fn __setitem__(&mut self, key: &str, value: &str) {
+        self.0.as_deb822_mut().set(key, value);
+    }
+
+    fn __delitem__(&mut self, key: &str) -> PyResult<()> {
+        if !self.0.as_deb822().contains_key(key) {
+            return Err(PyKeyError::new_err(key.to_string()));
+        }
+        self.0.as_deb822_mut().remove(key);
+        Ok(())
+    }
+
+    fn __contains__(&self, key: &str) -> bool {
+        self.0.as_deb822().contains_key(key)
+    }
+
+    /// The names of the fields in the patch header, in order.
+    fn keys(&self) -> Vec<String> {
+        self.0.as_deb822().keys().collect()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Add the classes from this module to a Python module.
+#[pymodule]
+pub fn dep3(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPatchHeader>()?;
+    Ok(())
+}
+
+// Until we drop support for PyO3 0.22, allow use of deprecated functions.
+#[allow(deprecated)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    fn run(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let m = PyModule::new_bound(py, "dep3").unwrap();
+            dep3(&m).unwrap();
+            let locals = PyDict::new_bound(py);
+            locals.set_item("dep3", m).unwrap();
+            if let Err(e) = py.run_bound(code, None, Some(&locals)) {
+                e.display(py);
+                panic!("Python code failed");
+            }
+        });
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        run(r#"
+text = """From: John Doe <john.doe@example>
+# A comment
+Subject: Fix a bug
+ In a very clever way.
+Bug-Debian: https://bugs.debian.org/123456
+Forwarded: not-needed
+"""
+header = dep3.PatchHeader(text)
+assert str(header) == text
+assert header.author == "John Doe <john.doe@example>"
+assert header.description == "Fix a bug"
+assert header.vendor_bugs("Debian") == ["https://bugs.debian.org/123456"]
+assert header.bugs() == [("Debian", "https://bugs.debian.org/123456")]
+assert header.forwarded == "not-needed"
+assert header.origin is None
+header.set_origin("commit:abcdef", "upstream")
+assert header.origin == ("upstream", "commit:abcdef")
+header.last_update = "2024-01-02"
+header["Forwarded"] = "no"
+assert str(header) == """From: John Doe <john.doe@example>
+# A comment
+Subject: Fix a bug
+ In a very clever way.
+Bug-Debian: https://bugs.debian.org/123456
+Forwarded: no
+Origin: upstream, commit:abcdef
+Last-Update: 2024-01-02
+"""
+"#);
+    }
+
+    #[test]
+    fn test_errors() {
+        run(r#"
+header = dep3.PatchHeader()
+try:
+    header.set_origin("foo", "invalid")
+except ValueError:
+    pass
+else:
+    raise AssertionError("expected ValueError")
+try:
+    header.last_update = "yesterday"
+except ValueError:
+    pass
+else:
+    raise AssertionError("expected ValueError")
+try:
+    header["Origin"]
+except KeyError:
+    pass
+else:
+    raise AssertionError("expected KeyError")
+"#);
+    }
+}
@@ -0,0 +1,170 @@
+//! JavaScript bindings, for use with wasm-bindgen.
+//!
+//! ```js
+//! import { parsePatchHeader } from "dep3";
+//!
+//! const header = parsePatchHeader("Description: Fix a bug\nForwarded: not-needed\n");
+//! console.log(header.description);  // "Fix a bug"
+//! console.log(header.forwarded);  // "not-needed"
+//! ```
+use crate::lossless::PatchHeader;
+use deb822_lossless::wasm::paragraph_to_object;
+use wasm_bindgen::prelude::*;
+
+/// A DEP-3 patch header.
+#[wasm_bindgen(js_name = PatchHeader)]
+pub struct JsPatchHeader(PatchHeader);
+
+#[wasm_bindgen(js_class = PatchHeader)]
+impl JsPatchHeader {
+    /// Create a new, empty patch header.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        JsPatchHeader(PatchHeader::new())
+    }
+
+    /// The first line of the description of the patch.
+    #[wasm_bindgen(getter)]
+    pub fn description(&self) -> Option<String> {
+        self.0.description()
+    }
+
+    /// The remaining lines of the description of the patch.
+    #[wasm_bindgen(getter, js_name = longDescription)]
+    pub fn long_description(&self) -> Option<String> {
+        self.0.long_description()
+    }
+
+    /// The author of the patch.
+    #[wasm_bindgen(getter)]
+    pub fn author(&self) -> Option<String> {
+        self.0.author()
+    }
+
+    /// The origin of the patch, including its category if one was specified.
+    #[wasm_bindgen(getter)]
+    pub fn origin(&self) -> Option<String> {
+        self.0.as_deb822().get("Origin")
+    }
+
+    /// Whether and where the patch was forwarded upstream.
+    #[wasm_bindgen(getter)]
+    pub fn forwarded(&self) -> Result<Option<String>, JsError> {
+        self.0
+            .forwarded()
+            .map(|f| {
+                f.map(|f| f.to_string())
+                    .map_err(|e| JsError::new(&e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Whether and where the patch was applied upstream.
+    #[wasm_bindgen(getter, js_name = appliedUpstream)]
+    pub fn applied_upstream(&self) -> Result<Option<String>, JsError> {
+        self.0
+            .applied_upstream()
+            .map(|a| {
+                a.map(|a| a.to_string())
+                    .map_err(|e| JsError::new(&e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// The date of the last update of the patch, in `YYYY-MM-DD` format.
+    #[wasm_bindgen(getter, js_name = lastUpdate)]
+    pub fn last_update(&self) -> Option<String> {
+        self.0
+            .last_update()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+    }
+
+    /// The bugs associated with the patch for a vendor, or the upstream bugs if no vendor is
+    /// given.
+    #[wasm_bindgen(js_name = vendorBugs)]
+    pub fn vendor_bugs(&self, vendor: Option<String>) -> Vec<String> {
+        self.0
+            .bugs()
+            .filter(|(v, _)| v == &vendor)
+            .map(|(_, bug)| bug)
+            .collect()
+    }
+
+    /// Get the value of a field, or `undefined` if it is not present.
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.0.as_deb822().get(key)
+    }
+
+    /// Set the value of a field, replacing any existing value.
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.0.as_deb822_mut().set(key, value);
+    }
+
+    /// Convert the patch header to a plain object mapping field names to values.
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> js_sys::Object {
+        paragraph_to_object(self.0.as_deb822().items())
+    }
+
+    /// Serialize the patch header, including any comments.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string_js(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl Default for JsPatchHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse a DEP-3 patch header.
+///
+/// Throws an error if the header can not be parsed.
+#[wasm_bindgen(js_name = parsePatchHeader)]
+pub fn parse_patch_header(text: &str) -> Result<JsPatchHeader, JsError> {
+    text.parse()
+        .map(JsPatchHeader)
+        .map_err(|e: deb822_lossless::ParseError| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let mut header = parse_patch_header(
+            r#"From: John Doe <john.doe@example>
+Subject: Fix a bug
+ In a very clever way.
+Origin: upstream, commit:abcdef
+Bug: https://example.com/bugs/1
+Bug-Debian: https://bugs.debian.org/123456
+Last-Update: 2024-01-02
+"#,
+        )
+        .ok()
+        .unwrap();
+        assert_eq!(
+            header.author(),
+            Some("John Doe <john.doe@example>".to_string())
+        );
+        assert_eq!(header.description(), Some("Fix a bug".to_string()));
+        assert_eq!(
+            header.long_description(),
+            Some("In a very clever way.".to_string())
+        );
+        assert_eq!(header.origin(), Some("upstream, commit:abcdef".to_string()));
+        assert_eq!(header.last_update(), Some("2024-01-02".to_string()));
+        assert_eq!(header.forwarded().unwrap(), None);
+        assert_eq!(header.vendor_bugs(None), vec!["https://example.com/bugs/1"]);
+        assert_eq!(
+            header.vendor_bugs(Some("Debian".to_string())),
+            vec!["https://bugs.debian.org/123456"]
+        );
+        header.set("Forwarded", "not-needed");
+        assert_eq!(header.forwarded().unwrap(), Some("not-needed".to_string()));
+    }
+}